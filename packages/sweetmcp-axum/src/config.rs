@@ -180,6 +180,146 @@ pub struct Config {
     /// Database configuration (optional).
     #[serde(default)]
     pub database: Option<DatabaseConfig>,
+
+    /// External MCP servers whose tools are served alongside the plugins'.
+    #[serde(default)]
+    pub upstreams: Vec<UpstreamConfig>,
+
+    /// What is masked in tool-call arguments before they are logged.
+    #[serde(default)]
+    pub redaction: RedactionConfig,
+
+    /// Longest any tool call may run, in milliseconds, whatever the client
+    /// or plugin asks for.
+    #[serde(default)]
+    pub call_timeout_ms: Option<u64>,
+
+    /// Loopback listener taking large tool arguments out of band.
+    #[serde(default)]
+    pub uploads: Option<UploadConfig>,
+
+    /// Steps applied to tool results before they reach the client, keyed by
+    /// tool name. The steps under `*` run for every tool, ahead of its own.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub postprocess: HashMap<String, Vec<PostProcessor>>,
+}
+
+/// One step of a tool's result post-processing, applied to its text content.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum PostProcessor {
+    /// Mask secrets the way logged arguments are masked.
+    Redact {
+        /// Start from the built-in patterns (credentials, JWTs, keys, emails,
+        /// card numbers).
+        #[serde(default = "default_true")]
+        use_defaults: bool,
+        /// Extra regexes whose matches are redacted.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        patterns: Vec<String>,
+    },
+    /// Convert HTML to markdown.
+    HtmlToMarkdown,
+    /// Replace text longer than `over_tokens` with a summary from the
+    /// sampling model chain. Left as is when no chain is configured.
+    Summarize {
+        /// Estimated tokens (about four characters each) above which text is summarized.
+        over_tokens: usize,
+        /// Longest summary, in tokens.
+        #[serde(default = "default_summary_max_tokens")]
+        max_tokens: u32,
+        /// Instructions for the model; a generic summary prompt when unset.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        prompt: Option<String>,
+    },
+}
+
+fn default_summary_max_tokens() -> u32 {
+    1024
+}
+
+/// Where and how large tool arguments may be uploaded ahead of a call.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct UploadConfig {
+    /// Loopback address to listen on.
+    #[serde(default = "default_upload_listen")]
+    pub listen: String,
+    /// Where blobs are kept; defaults to `<cache dir>/cyrup-mcp/uploads`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dir: Option<String>,
+    /// Largest blob accepted, in bytes, across all of its parts.
+    #[serde(default = "default_upload_max_bytes")]
+    pub max_bytes: u64,
+    /// Seconds a blob is kept after its upload began.
+    #[serde(default = "default_upload_ttl_secs")]
+    pub ttl_secs: u64,
+    /// Bearer token uploads must carry; unset leaves the listener open to local processes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// File holding the bearer token (used when `token` is unset).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_file: Option<String>,
+}
+
+fn default_upload_listen() -> String {
+    "127.0.0.1:33410".to_string()
+}
+
+fn default_upload_max_bytes() -> u64 {
+    64 * 1024 * 1024
+}
+
+fn default_upload_ttl_secs() -> u64 {
+    3600
+}
+
+impl UploadConfig {
+    /// The bearer token from `token` or `token_file`, if either is set.
+    pub fn resolve_token(&self) -> anyhow::Result<Option<String>> {
+        let token = match (&self.token, &self.token_file) {
+            (Some(token), _) => token.clone(),
+            (None, Some(path)) => std::fs::read_to_string(path)
+                .map_err(|e| anyhow::anyhow!("read uploads token file {}: {}", path, e))?,
+            (None, None) => return Ok(None),
+        };
+        let token = token.trim().to_string();
+        if token.is_empty() {
+            anyhow::bail!("uploads token is empty");
+        }
+        Ok(Some(token))
+    }
+}
+
+/// Redaction rules for logged tool-call arguments.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct RedactionConfig {
+    /// Start from the built-in field names and patterns (passwords, tokens,
+    /// API keys, JWTs, emails, card numbers).
+    #[serde(default = "default_true")]
+    pub use_defaults: bool,
+    /// Extra field names whose values are always redacted. Matched
+    /// case-insensitively, ignoring `_` and `-`, against the key or its suffix.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+    /// Extra regexes; matching substrings of any string value are redacted.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub patterns: Vec<String>,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            use_defaults: true,
+            fields: Vec::new(),
+            patterns: Vec::new(),
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
 }
 
 /// Represents the configuration for a single plugin.
@@ -190,8 +330,81 @@ pub struct PluginConfig {
     /// The path to the plugin (file path, URL, or OCI reference).
     pub path: String,
     /// Optional environment configuration for the plugin runtime.
+    /// Superseded by `config`; still accepted for existing deployments.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub env: Option<EnvConfig>,
+    /// Standardized plugin configuration, validated against the plugin's contract.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<PluginSettings>,
+    /// Path to the plugin's `plugin.toml` contract.
+    /// Defaults to `plugin.toml` next to a local wasm file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract: Option<String>,
+    /// Warm instances kept for concurrent calls (default 1). Keep 1 for
+    /// plugins that hold state between calls; each instance has its own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pool_size: Option<usize>,
+}
+
+/// Configuration every plugin accepts, in the same shape for all plugins.
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct PluginSettings {
+    /// Hosts the plugin may reach. Defaults to the hosts its contract declares.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_hosts: Vec<String>,
+    /// Directories mounted into the plugin at the same path.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roots: Vec<String>,
+    /// Credentials declared under `[api_keys]` in the contract.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub api_keys: HashMap<String, String>,
+    /// Runtime limits; unset values fall back to the contract's defaults.
+    #[serde(default)]
+    pub limits: PluginLimits,
+    /// Plugin-specific options declared under `[settings]` in the contract.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub settings: HashMap<String, serde_json::Value>,
+}
+
+/// Resource limits applied to a plugin instance.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PluginLimits {
+    /// Wall-clock limit for a single call, in milliseconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// Per-tool overrides of `timeout_ms`, keyed by tool name.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub tool_timeout_ms: HashMap<String, u64>,
+    /// Maximum linear memory, in MiB.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_mb: Option<u32>,
+    /// Largest HTTP response body the plugin may receive, in bytes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_http_response_bytes: Option<u64>,
+}
+
+impl PluginSettings {
+    /// Lift a legacy `env` block into the standardized shape. Loose keys become
+    /// settings, or API keys when the contract declares them as such.
+    pub fn from_legacy_env(env: &EnvConfig, api_key_names: &[&str]) -> Self {
+        let mut settings = Self {
+            allowed_hosts: env.allowed_hosts.clone().unwrap_or_default(),
+            roots: env.allowed_paths.clone().unwrap_or_default(),
+            ..Self::default()
+        };
+        for (key, value) in &env.additional_vars {
+            if api_key_names.contains(&key.as_str()) {
+                settings.api_keys.insert(key.clone(), value.clone());
+            } else {
+                settings
+                    .settings
+                    .insert(key.clone(), serde_json::Value::String(value.clone()));
+            }
+        }
+        settings
+    }
 }
 
 /// Represents the environment configuration for a plugin runtime.
@@ -204,6 +417,30 @@ pub struct EnvConfig {
     /// Optional list of file system paths the plugin is allowed to access.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub allowed_paths: Option<Vec<String>>,
+    /// Optional host PTY access. Plugins without a terminal policy get no PTY host functions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub terminal: Option<TerminalPolicy>,
+    /// Optional host browser page pool. Plugins without a browser policy get no browser host functions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub browser: Option<BrowserPolicy>,
+    /// Optional system clipboard access. Plugins without a clipboard policy get no clipboard host functions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clipboard: Option<ClipboardPolicy>,
+    /// Optional desktop screen capture. Plugins without a screen policy get no screen host functions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub screen: Option<ScreenPolicy>,
+    /// Optional host OCR engine. Plugins without an ocr policy get no OCR host functions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ocr: Option<OcrPolicy>,
+    /// Optional access to the cyrupd secrets vault. Plugins without a vault policy get no vault host functions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vault: Option<VaultPolicy>,
+    /// Optional access to the cyrupd reminder scheduler. Plugins without a reminders policy get no reminders host functions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reminders: Option<RemindersPolicy>,
+    /// Optional reporting of sandbox denials to cyrupd. Plugins without a sandbox_telemetry policy are not monitored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox_telemetry: Option<SandboxTelemetryPolicy>,
 
     /// Captures any additional key-value pairs defined under the "env" object,
     /// fulfilling the "additionalProperties": true requirement in the schema.
@@ -211,3 +448,324 @@ pub struct EnvConfig {
     #[serde(flatten)]
     pub additional_vars: HashMap<String, String>,
 }
+
+/// Policy governing host-side PTY sessions opened by a plugin.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TerminalPolicy {
+    /// Programs the plugin may launch, by basename or absolute path.
+    #[serde(default)]
+    pub allowed_commands: Vec<String>,
+    /// Directories sessions may use as their working directory.
+    /// The first entry is the default working directory.
+    #[serde(default)]
+    pub allowed_dirs: Vec<String>,
+    /// Maximum concurrently open sessions.
+    #[serde(default = "default_max_sessions")]
+    pub max_sessions: usize,
+    /// Output retained per session, in bytes.
+    #[serde(default = "default_scrollback_bytes")]
+    pub scrollback_bytes: usize,
+}
+
+fn default_max_sessions() -> usize {
+    4
+}
+
+fn default_scrollback_bytes() -> usize {
+    256 * 1024
+}
+
+/// Policy governing the host-side browser page pool used by a plugin.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BrowserPolicy {
+    /// Pages open at once, which bounds how many URLs load in parallel.
+    #[serde(default = "default_max_pages")]
+    pub max_pages: usize,
+    /// Most URLs accepted in one batch.
+    #[serde(default = "default_max_urls")]
+    pub max_urls: usize,
+    /// Limit for loading and extracting a single page, in milliseconds.
+    #[serde(default = "default_page_timeout_ms")]
+    pub page_timeout_ms: u64,
+}
+
+fn default_max_pages() -> usize {
+    4
+}
+
+fn default_max_urls() -> usize {
+    50
+}
+
+fn default_page_timeout_ms() -> u64 {
+    30_000
+}
+
+/// Policy governing a plugin's access to the host's system clipboard.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ClipboardPolicy {
+    /// Allow reading the clipboard.
+    #[serde(default = "default_true")]
+    pub read: bool,
+    /// Allow replacing the clipboard's contents.
+    #[serde(default = "default_true")]
+    pub write: bool,
+    /// Allow images as well as text.
+    #[serde(default)]
+    pub images: bool,
+    /// Largest text or PNG image passed either way, in bytes.
+    #[serde(default = "default_clipboard_max_bytes")]
+    pub max_bytes: usize,
+}
+
+fn default_clipboard_max_bytes() -> usize {
+    1024 * 1024
+}
+
+/// Policy governing a plugin's access to captures of the host's screen.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScreenPolicy {
+    /// Allow capturing whole displays.
+    #[serde(default = "default_true")]
+    pub displays: bool,
+    /// Allow capturing single windows, and listing window titles.
+    #[serde(default = "default_true")]
+    pub windows: bool,
+    /// Whether the user is asked before each capture.
+    #[serde(default)]
+    pub consent: ScreenConsent,
+    /// Captures wider than this are scaled down to it, in pixels.
+    #[serde(default = "default_screen_max_width")]
+    pub max_width: u32,
+}
+
+fn default_screen_max_width() -> u32 {
+    1920
+}
+
+/// When to ask the user before a screen capture.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ScreenConsent {
+    /// Show a native yes/no dialog for every capture.
+    #[default]
+    Prompt,
+    /// Capture without asking; for unattended setups only.
+    Always,
+}
+
+/// Policy governing a plugin's use of the host's OCR engine.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OcrPolicy {
+    /// Tesseract binary to run, by name on PATH or as a full path.
+    #[serde(default = "default_ocr_command")]
+    pub command: String,
+    /// Tesseract language codes callers may ask for; the first is the default.
+    #[serde(default = "default_ocr_languages")]
+    pub languages: Vec<String>,
+    /// Largest image accepted, in bytes after base64 decoding.
+    #[serde(default = "default_ocr_max_image_bytes")]
+    pub max_image_bytes: usize,
+    /// Seconds one recognition may run before it is killed.
+    #[serde(default = "default_ocr_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_ocr_command() -> String {
+    "tesseract".to_string()
+}
+
+fn default_ocr_languages() -> Vec<String> {
+    vec!["eng".to_string()]
+}
+
+fn default_ocr_max_image_bytes() -> usize {
+    10 * 1024 * 1024
+}
+
+fn default_ocr_timeout_secs() -> u64 {
+    30
+}
+
+/// Policy governing a plugin's access to the cyrupd secrets vault.
+///
+/// The token identifies a client in the daemon's `[secrets.clients]`; which
+/// secrets the plugin may read, reveal or write is set there.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VaultPolicy {
+    /// The daemon's admin API over loopback TCP.
+    #[serde(default = "default_daemon_url")]
+    pub url: String,
+    /// Client bearer token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// File holding the client bearer token (used when `token` is unset).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_file: Option<String>,
+    /// Seconds one vault request may take.
+    #[serde(default = "default_daemon_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+impl VaultPolicy {
+    /// Resolve the client token from `token` or `token_file`.
+    pub fn resolve_token(&self) -> anyhow::Result<String> {
+        let token = match (&self.token, &self.token_file) {
+            (Some(token), _) => token.clone(),
+            (None, Some(path)) => std::fs::read_to_string(path)
+                .map_err(|e| anyhow::anyhow!("read vault token file {}: {}", path, e))?,
+            (None, None) => anyhow::bail!("vault policy requires `token` or `token_file`"),
+        };
+        let token = token.trim().to_string();
+        if token.is_empty() {
+            anyhow::bail!("vault token is empty");
+        }
+        Ok(token)
+    }
+}
+
+fn default_daemon_url() -> String {
+    "http://127.0.0.1:33401".to_string()
+}
+
+fn default_daemon_timeout_secs() -> u64 {
+    10
+}
+
+/// Policy governing a plugin's access to the cyrupd reminder scheduler.
+///
+/// The token is the daemon's `[reminders]` token (or its admin token).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RemindersPolicy {
+    /// The daemon's admin API over loopback TCP.
+    #[serde(default = "default_daemon_url")]
+    pub url: String,
+    /// Bearer token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// File holding the bearer token (used when `token` is unset).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_file: Option<String>,
+    /// Seconds one request may take.
+    #[serde(default = "default_daemon_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+impl RemindersPolicy {
+    /// Resolve the bearer token from `token` or `token_file`.
+    pub fn resolve_token(&self) -> anyhow::Result<String> {
+        let token = match (&self.token, &self.token_file) {
+            (Some(token), _) => token.clone(),
+            (None, Some(path)) => std::fs::read_to_string(path)
+                .map_err(|e| anyhow::anyhow!("read reminders token file {}: {}", path, e))?,
+            (None, None) => anyhow::bail!("reminders policy requires `token` or `token_file`"),
+        };
+        let token = token.trim().to_string();
+        if token.is_empty() {
+            anyhow::bail!("reminders token is empty");
+        }
+        Ok(token)
+    }
+}
+
+/// Policy for counting a plugin's sandbox denials and reporting them to cyrupd.
+///
+/// The token is the daemon's `[sandbox_reports]` token (or its admin token).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SandboxTelemetryPolicy {
+    /// The daemon's admin API over loopback TCP.
+    #[serde(default = "default_daemon_url")]
+    pub url: String,
+    /// Bearer token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// File holding the bearer token (used when `token` is unset).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_file: Option<String>,
+    /// Seconds one request may take.
+    #[serde(default = "default_daemon_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Denials of one kind by one client within the window that raise a security event.
+    #[serde(default = "default_sandbox_threshold")]
+    pub threshold: u32,
+    /// Seconds over which denials are counted.
+    #[serde(default = "default_sandbox_window_secs")]
+    pub window_secs: u64,
+}
+
+fn default_sandbox_threshold() -> u32 {
+    5
+}
+
+fn default_sandbox_window_secs() -> u64 {
+    300
+}
+
+impl SandboxTelemetryPolicy {
+    /// Resolve the bearer token from `token` or `token_file`.
+    pub fn resolve_token(&self) -> anyhow::Result<String> {
+        let token = match (&self.token, &self.token_file) {
+            (Some(token), _) => token.clone(),
+            (None, Some(path)) => std::fs::read_to_string(path).map_err(|e| {
+                anyhow::anyhow!("read sandbox telemetry token file {}: {}", path, e)
+            })?,
+            (None, None) => {
+                anyhow::bail!("sandbox_telemetry policy requires `token` or `token_file`")
+            }
+        };
+        let token = token.trim().to_string();
+        if token.is_empty() {
+            anyhow::bail!("sandbox telemetry token is empty");
+        }
+        Ok(token)
+    }
+}
+
+/// An external MCP server whose tools are re-exported under a prefix.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UpstreamConfig {
+    /// Unique name of the upstream, used in logs.
+    pub name: String,
+    /// Namespace prepended to the upstream's tool names as `<prefix>__<tool>`.
+    /// Defaults to `name`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    /// How to reach the server.
+    #[serde(flatten)]
+    pub transport: UpstreamTransport,
+    /// Limit for a single request to the upstream, in milliseconds.
+    #[serde(default = "default_upstream_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+impl UpstreamConfig {
+    /// The namespace this upstream's tools are listed under.
+    pub fn prefix(&self) -> &str {
+        self.prefix.as_deref().unwrap_or(&self.name)
+    }
+}
+
+/// Transport used to talk to an upstream MCP server.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum UpstreamTransport {
+    /// A child process speaking newline-delimited JSON-RPC on stdin/stdout.
+    Stdio {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        env: HashMap<String, String>,
+    },
+    /// A Streamable HTTP endpoint.
+    Http {
+        url: String,
+        /// Extra request headers, e.g. `Authorization`.
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        headers: HashMap<String, String>,
+    },
+}
+
+fn default_upstream_timeout_ms() -> u64 {
+    30_000
+}
@@ -2,7 +2,7 @@ use serde::{Serialize, de::DeserializeOwned};
 use surrealdb::{
     Surreal,
     engine::{
-        local::{Db, SurrealKv},
+        local::{Db, Mem, SurrealKv},
         remote::http,
     },
     opt::auth::Root,
@@ -16,7 +16,7 @@ use crate::db::result::Result;
 /// Unified client for different SurrealDB storage engines
 #[derive(Debug)]
 pub enum DatabaseClient {
-    /// SurrealKV embedded key-value store
+    /// Embedded store, SurrealKV or in-memory
     SurrealKv(Surreal<Db>),
     /// HTTP connection to remote SurrealDB instance
     RemoteHttp(Surreal<http::Client>),
@@ -403,6 +403,19 @@ pub async fn connect_database(config: DatabaseConfig) -> Result<DatabaseClient>
 
             DatabaseClient::SurrealKv(db)
         }
+        StorageEngine::Memory => {
+            debug!("Using in-memory storage");
+
+            let db = Surreal::new::<Mem>(()).await?;
+
+            if let (Some(ns), Some(db_name)) = (&config.namespace, &config.database) {
+                if !ns.is_empty() && !db_name.is_empty() {
+                    db.use_ns(ns).use_db(db_name).await?;
+                }
+            }
+
+            DatabaseClient::SurrealKv(db)
+        }
         StorageEngine::Http => {
             debug!("Using HTTP connection at {:?}", config.url);
 
@@ -33,6 +33,8 @@ pub enum StorageEngine {
     SurrealKv,
     /// HTTP connection to a remote SurrealDB server
     Http,
+    /// In-memory store, gone when the process exits
+    Memory,
 }
 
 impl Default for StorageEngine {
@@ -46,6 +48,7 @@ impl std::fmt::Display for StorageEngine {
         match self {
             Self::SurrealKv => write!(f, "surrealkv"),
             Self::Http => write!(f, "http"),
+            Self::Memory => write!(f, "memory"),
         }
     }
 }
@@ -113,6 +116,19 @@ impl DatabaseConfig {
         }
     }
 
+    /// Create a new in-memory database configuration
+    pub fn memory() -> Self {
+        Self {
+            engine: StorageEngine::Memory,
+            path: None,
+            url: None,
+            namespace: default_namespace(),
+            database: default_database(),
+            username: None,
+            password: None,
+        }
+    }
+
     /// Create a new database configuration for SurrealKV
     pub fn surrealkv<P, U, PW>(path: P, username: Option<U>, password: Option<PW>) -> Self
     where
@@ -249,6 +265,7 @@ impl DatabaseConfig {
                     ));
                 }
             }
+            StorageEngine::Memory => {}
         }
 
         // Validate namespace and database names
@@ -0,0 +1,64 @@
+//! Length-prefixed JSON-RPC frames for the Unix socket.
+//!
+//! Newline-delimited JSON stays the socket's default. A client that opens the
+//! connection with [`PREAMBLE`] switches it to frames: a 4-byte big-endian
+//! length, then that many bytes of JSON. Each payload lands in one reusable
+//! buffer and is parsed where it lies, with no scanning for line ends and no
+//! copy into a line string. Every frame sent gets exactly one frame back, an
+//! empty one for messages without a response such as notifications, so a
+//! client can keep requests and responses in lockstep on one connection.
+
+use std::io;
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Sent once by a framed client before its first frame. It starts with a
+/// NUL byte, which no line of JSON can.
+pub const PREAMBLE: &[u8; 8] = b"\0SMCPF1\n";
+
+/// Largest frame either side accepts
+pub const MAX_FRAME: usize = 64 * 1024 * 1024;
+
+/// Read the next frame into `buf`, replacing its contents. Returns `false`
+/// when the peer closed the connection between frames.
+pub async fn read_frame<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    buf: &mut Vec<u8>,
+) -> io::Result<bool> {
+    let mut len = [0u8; 4];
+    match reader.read_exact(&mut len).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(false),
+        Err(e) => return Err(e),
+    }
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_FRAME {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("frame of {} bytes exceeds {} bytes", len, MAX_FRAME),
+        ));
+    }
+    buf.clear();
+    buf.resize(len, 0);
+    reader.read_exact(buf).await?;
+    Ok(true)
+}
+
+/// Write `payload` as one frame and flush it
+pub async fn write_frame<W: AsyncWrite + Unpin>(writer: &mut W, payload: &[u8]) -> io::Result<()> {
+    if payload.len() > MAX_FRAME {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "frame of {} bytes exceeds {} bytes",
+                payload.len(),
+                MAX_FRAME
+            ),
+        ));
+    }
+    writer
+        .write_all(&(payload.len() as u32).to_be_bytes())
+        .await?;
+    writer.write_all(payload).await?;
+    writer.flush().await
+}
@@ -0,0 +1,278 @@
+//! Scoped API keys
+//!
+//! Keys are handed out once in the form `smcp_<id>_<secret>` and only a SHA-256
+//! of the secret is persisted. Each key carries scopes; a scope either names an
+//! entry in a [`ScopeMap`] or is a literal `tools:<pattern>`, and together they
+//! resolve to the [`ToolAllowlist`] the gateway enforces on `tools/call`.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+/// Prefix that identifies a sweetmcp API key
+pub const KEY_PREFIX: &str = "smcp_";
+
+/// Scope granting access to the key-management endpoints
+pub const ADMIN_SCOPE: &str = "admin";
+
+/// Named scopes mapped to tool-name patterns
+pub type ScopeMap = HashMap<String, Vec<String>>;
+
+/// Persisted API key record. Never contains the plaintext secret.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKey {
+    pub id: String,
+    pub name: String,
+    /// Hex SHA-256 of the secret part of the key
+    pub secret_hash: String,
+    pub scopes: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub rotated_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// A freshly created or rotated key, including the one-time plaintext token
+#[derive(Debug, Clone, Serialize)]
+pub struct IssuedKey {
+    #[serde(flatten)]
+    pub key: ApiKey,
+    pub token: String,
+}
+
+impl ApiKey {
+    /// Create a new key; the returned token is the only copy of the secret.
+    pub fn issue(name: impl Into<String>, scopes: Vec<String>) -> IssuedKey {
+        let id = Uuid::new_v4().simple().to_string()[..12].to_string();
+        let secret = new_secret();
+        let key = ApiKey {
+            id: id.clone(),
+            name: name.into(),
+            secret_hash: hash_secret(&secret),
+            scopes,
+            created_at: Utc::now(),
+            rotated_at: None,
+            revoked_at: None,
+        };
+        IssuedKey {
+            token: format_token(&id, &secret),
+            key,
+        }
+    }
+
+    /// Replace the secret, invalidating the previous token.
+    pub fn rotate(&mut self) -> String {
+        let secret = new_secret();
+        self.secret_hash = hash_secret(&secret);
+        self.rotated_at = Some(Utc::now());
+        format_token(&self.id, &secret)
+    }
+
+    pub fn is_revoked(&self) -> bool {
+        self.revoked_at.is_some()
+    }
+
+    pub fn is_admin(&self) -> bool {
+        self.scopes.iter().any(|s| s == ADMIN_SCOPE)
+    }
+
+    /// Constant-time check of a presented secret against the stored hash
+    pub fn verify_secret(&self, secret: &str) -> bool {
+        let presented = hash_secret(secret);
+        let (a, b) = (presented.as_bytes(), self.secret_hash.as_bytes());
+        a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+    }
+
+    /// Resolve this key's scopes into the tools it may call
+    pub fn tool_allowlist(&self, scopes: &ScopeMap) -> ToolAllowlist {
+        let mut patterns = Vec::new();
+        for scope in &self.scopes {
+            if let Some(mapped) = scopes.get(scope) {
+                patterns.extend(mapped.iter().cloned());
+            } else if let Some(pattern) = scope.strip_prefix("tools:") {
+                patterns.push(pattern.to_string());
+            }
+        }
+        ToolAllowlist { patterns }
+    }
+}
+
+/// Split `smcp_<id>_<secret>` into its id and secret
+pub fn parse_token(token: &str) -> Option<(&str, &str)> {
+    token
+        .strip_prefix(KEY_PREFIX)?
+        .split_once('_')
+        .filter(|(id, secret)| !id.is_empty() && !secret.is_empty())
+}
+
+fn format_token(id: &str, secret: &str) -> String {
+    format!("{}{}_{}", KEY_PREFIX, id, secret)
+}
+
+fn new_secret() -> String {
+    // Two v4 UUIDs give 244 bits from the OS RNG
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+fn hash_secret(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+/// Tool-name patterns a caller may invoke. `*` matches any run of characters.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ToolAllowlist {
+    pub patterns: Vec<String>,
+}
+
+impl ToolAllowlist {
+    pub fn allows(&self, tool: &str) -> bool {
+        self.patterns.iter().any(|p| glob_match(p, tool))
+    }
+}
+
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == name;
+    }
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !name.starts_with(first) || !name[first.len()..].ends_with(last) {
+        return false;
+    }
+    let mut rest = &name[first.len()..name.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Errors from [`ApiKeyStore`]
+#[derive(Debug, thiserror::Error)]
+pub enum ApiKeyError {
+    #[error("API key {0} not found")]
+    NotFound(String),
+    #[error("API key {0} is revoked")]
+    Revoked(String),
+    #[error("API key store I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("API key store is corrupt: {0}")]
+    Serde(#[from] serde_json::Error),
+}
+
+/// File-backed key store; every mutation rewrites the file atomically.
+pub struct ApiKeyStore {
+    path: PathBuf,
+    keys: DashMap<String, ApiKey>,
+    /// Serializes snapshot + write so concurrent mutations can't lose a key
+    persist_lock: std::sync::Mutex<()>,
+}
+
+impl ApiKeyStore {
+    /// Load the store, starting empty if the file does not exist yet
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, ApiKeyError> {
+        let path = path.into();
+        let keys = DashMap::new();
+        match std::fs::read(&path) {
+            Ok(bytes) => {
+                for key in serde_json::from_slice::<Vec<ApiKey>>(&bytes)? {
+                    keys.insert(key.id.clone(), key);
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        Ok(Self {
+            path,
+            keys,
+            persist_lock: std::sync::Mutex::new(()),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Keys sorted by creation time, including revoked ones
+    pub fn list(&self) -> Vec<ApiKey> {
+        let mut keys: Vec<ApiKey> = self.keys.iter().map(|k| k.value().clone()).collect();
+        keys.sort_by_key(|k| k.created_at);
+        keys
+    }
+
+    pub fn create(&self, name: &str, scopes: Vec<String>) -> Result<IssuedKey, ApiKeyError> {
+        let issued = ApiKey::issue(name, scopes);
+        self.keys.insert(issued.key.id.clone(), issued.key.clone());
+        self.persist()?;
+        Ok(issued)
+    }
+
+    pub fn rotate(&self, id: &str) -> Result<IssuedKey, ApiKeyError> {
+        let issued = {
+            let mut key = self
+                .keys
+                .get_mut(id)
+                .ok_or_else(|| ApiKeyError::NotFound(id.to_string()))?;
+            if key.is_revoked() {
+                return Err(ApiKeyError::Revoked(id.to_string()));
+            }
+            let token = key.rotate();
+            IssuedKey {
+                key: key.clone(),
+                token,
+            }
+        };
+        self.persist()?;
+        Ok(issued)
+    }
+
+    /// Revoke a key; the record is kept for auditing
+    pub fn revoke(&self, id: &str) -> Result<ApiKey, ApiKeyError> {
+        let key = {
+            let mut key = self
+                .keys
+                .get_mut(id)
+                .ok_or_else(|| ApiKeyError::NotFound(id.to_string()))?;
+            if key.revoked_at.is_none() {
+                key.revoked_at = Some(Utc::now());
+            }
+            key.clone()
+        };
+        self.persist()?;
+        Ok(key)
+    }
+
+    /// Resolve a presented token to its active key
+    pub fn authenticate(&self, token: &str) -> Option<ApiKey> {
+        let (id, secret) = parse_token(token)?;
+        let key = self.keys.get(id)?;
+        (!key.is_revoked() && key.verify_secret(secret)).then(|| key.clone())
+    }
+
+    fn persist(&self) -> Result<(), ApiKeyError> {
+        let _guard = self
+            .persist_lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(&self.list())?)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o600))?;
+        }
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
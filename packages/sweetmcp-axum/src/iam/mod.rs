@@ -0,0 +1,7 @@
+//! Identity and access management
+
+pub mod api_key;
+
+pub use api_key::{
+    ADMIN_SCOPE, ApiKey, ApiKeyError, ApiKeyStore, IssuedKey, ScopeMap, ToolAllowlist,
+};
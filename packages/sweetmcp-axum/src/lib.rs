@@ -11,6 +11,8 @@ pub mod context;
 
 mod container_registry;
 pub mod db; // Make db module public
+pub mod framing;
+pub mod iam;
 pub mod notifications;
 pub mod plugin; // Ensure plugin module is declared and public
 mod prompt;
@@ -21,6 +23,8 @@ pub mod security; // Zero-allocation input validation framework
 mod tool; // Re-enable
 mod types;
 pub mod ui;
+pub mod upload;
+pub mod upstream;
 
 pub use config::{
     // Keep only one set of imports
@@ -28,6 +32,8 @@ pub use config::{
     ConfigFormat,
     EnvConfig,
     PluginConfig,
+    UpstreamConfig,
+    UpstreamTransport,
     basename,
     init_logger,
     parse_config,
@@ -39,8 +45,9 @@ pub use container_registry::*;
 pub use plugin::PluginManager; // Updated path
 pub use resource::resource_read;
 pub use sampling::{
-    CompletionUsage, CreateMessageRequest, CreateMessageResult, SamplingProgressNotification,
-    SamplingTokenNotification, sampling_create_message,
+    CompletionUsage, CreateMessageRequest, CreateMessageResult, ModelChain, ModelError,
+    SamplingModel, SamplingProgressNotification, SamplingTokenNotification,
+    sampling_create_message, set_model_chain,
 };
 pub use security::{
     EmailValidationRule, MemoryOperation, MemoryOperationType, MemorySafetyMetrics,
@@ -0,0 +1,15 @@
+use anyhow::{Context, Result};
+use sweetmcp_axum::{Config, init_logger, parse_config, plugin::load_plugins, ui};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = ui::parse_cli_args();
+    init_logger(cli.log_path.as_deref(), Some(cli.log_level.as_str()))?;
+
+    let content = std::fs::read_to_string(&cli.config)
+        .with_context(|| format!("Failed to read config {}", cli.config.display()))?;
+    let config: Config = parse_config(&content, &cli.config)?;
+
+    let plugin_manager = load_plugins(&config.plugins, cli.insecure_skip_signature).await;
+    ui::run_ui_with_state(cli, config, plugin_manager).await
+}
@@ -179,10 +179,27 @@ impl NotificationRegistry {
         }
     }
 
+    /// Send a JSON-RPC notification from synchronous code, such as a plugin
+    /// host function. Returns false if the notification had to be dropped.
+    pub fn try_send_json_rpc_notification(&self, method: &str, params: Value) -> bool {
+        let Ok(lock) = self.json_rpc_sender.try_lock() else {
+            return false;
+        };
+        let Some(sender) = &*lock else {
+            return false;
+        };
+        sender
+            .try_send(NotificationPayload {
+                method: method.to_string(),
+                params,
+            })
+            .is_ok()
+    }
+
     /// Send a cancellation notification
     pub async fn send_cancelled(&self, request_id: &str, reason: Option<String>) {
         let notification = CancelledNotification {
-            request_id: request_id.to_string(),
+            request_id: Value::String(request_id.to_string()),
             reason,
         };
 
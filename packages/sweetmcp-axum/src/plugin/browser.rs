@@ -0,0 +1,308 @@
+//! Host-side browser page pool exposed to WASM plugins as Extism host functions.
+//!
+//! A headless Chromium is started on first use and shared by every call from
+//! the plugin. Pages are kept in a pool of at most `max_pages` and reused
+//! between URLs, so a batch of N URLs runs `max_pages` at a time instead of
+//! one after another.
+//!
+//! Host functions (all JSON in / JSON out):
+//! - `browser_batch_extract` – load each URL and collect the text of the
+//!   elements matching a CSS selector
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chromiumoxide::browser::{Browser, BrowserConfig};
+use chromiumoxide::page::Page;
+use extism::convert::Json;
+use extism::{Function, PTR, UserData, host_fn};
+use futures::StreamExt;
+use futures::stream::FuturesOrdered;
+use serde::{Deserialize, Serialize};
+use tokio::runtime::Runtime;
+use tokio::sync::{OnceCell, Semaphore};
+
+use crate::config::BrowserPolicy;
+
+/// Request to extract from many URLs at once
+#[derive(Debug, Deserialize)]
+pub struct BatchExtractRequest {
+    pub urls: Vec<String>,
+    /// CSS selector; defaults to `body`
+    #[serde(default = "default_selector")]
+    pub selector: String,
+    /// Lower the concurrency for this batch; capped by the policy's `max_pages`
+    pub concurrency: Option<usize>,
+}
+
+fn default_selector() -> String {
+    "body".to_string()
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchExtractResponse {
+    pub ok: bool,
+    pub results: Vec<PageExtract>,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub elapsed_ms: u64,
+}
+
+/// Outcome for one URL, in request order
+#[derive(Debug, Serialize)]
+pub struct PageExtract {
+    pub url: String,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub texts: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BrowserAck {
+    pub ok: bool,
+    pub error: String,
+}
+
+/// Lazily launched browser plus its idle pages
+struct PagePool {
+    browser: Browser,
+    idle: Mutex<Vec<Page>>,
+    permits: Semaphore,
+}
+
+impl PagePool {
+    async fn launch(max_pages: usize) -> anyhow::Result<Self> {
+        let config = BrowserConfig::builder()
+            .args(vec!["--no-sandbox", "--disable-dev-shm-usage"])
+            .build()
+            .map_err(|e| anyhow::anyhow!("failed to build browser config: {}", e))?;
+        let (browser, mut handler) = Browser::launch(config).await?;
+        tokio::spawn(async move {
+            while let Some(event) = handler.next().await {
+                if event.is_err() {
+                    break;
+                }
+            }
+        });
+        log::info!(
+            "Launched headless browser for page pool ({} pages)",
+            max_pages
+        );
+        Ok(Self {
+            browser,
+            idle: Mutex::new(Vec::new()),
+            permits: Semaphore::new(max_pages),
+        })
+    }
+
+    async fn checkout(&self) -> anyhow::Result<Page> {
+        let idle = self.idle.lock().ok().and_then(|mut idle| idle.pop());
+        match idle {
+            Some(page) => Ok(page),
+            None => Ok(self.browser.new_page("about:blank").await?),
+        }
+    }
+
+    fn checkin(&self, page: Page) {
+        if let Ok(mut idle) = self.idle.lock() {
+            idle.push(page);
+        }
+    }
+}
+
+/// Per-plugin browser host and policy
+pub struct BrowserHost {
+    policy: BrowserPolicy,
+    /// Always `Some` until drop
+    runtime: Option<Runtime>,
+    pool: Arc<OnceCell<PagePool>>,
+}
+
+impl BrowserHost {
+    pub fn new(policy: BrowserPolicy) -> anyhow::Result<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .thread_name("browser-pool")
+            .enable_all()
+            .build()?;
+        Ok(Self {
+            policy,
+            runtime: Some(runtime),
+            pool: Arc::new(OnceCell::new()),
+        })
+    }
+
+    fn batch_extract(&self, req: BatchExtractRequest) -> anyhow::Result<BatchExtractResponse> {
+        if req.urls.is_empty() {
+            anyhow::bail!("urls must not be empty");
+        }
+        if req.urls.len() > self.policy.max_urls {
+            anyhow::bail!(
+                "{} urls requested, the limit is {}",
+                req.urls.len(),
+                self.policy.max_urls
+            );
+        }
+        for url in &req.urls {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                anyhow::bail!("only http and https urls are supported: {}", url);
+            }
+        }
+
+        let pool = self.pool.clone();
+        let max_pages = self.policy.max_pages.max(1);
+        let concurrency = req.concurrency.unwrap_or(max_pages).clamp(1, max_pages);
+        let timeout = Duration::from_millis(self.policy.page_timeout_ms);
+
+        // The plugin call may already be on a tokio worker, so the batch runs
+        // on the host's own runtime and this thread just waits for it.
+        let runtime = self
+            .runtime
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("browser host is shutting down"))?;
+        let (tx, rx) = std::sync::mpsc::channel();
+        runtime.spawn(async move {
+            let result: anyhow::Result<BatchExtractResponse> = async {
+                let pool = pool.get_or_try_init(|| PagePool::launch(max_pages)).await?;
+                Ok(run_batch(pool, req.urls, &req.selector, concurrency, timeout).await)
+            }
+            .await;
+            let _ = tx.send(result);
+        });
+        rx.recv()
+            .map_err(|_| anyhow::anyhow!("browser batch was dropped"))?
+    }
+}
+
+impl Drop for BrowserHost {
+    fn drop(&mut self) {
+        // Plugins can be dropped from async code, where a blocking runtime
+        // shutdown would panic; the browser process dies with its handler.
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+async fn run_batch(
+    pool: &PagePool,
+    urls: Vec<String>,
+    selector: &str,
+    concurrency: usize,
+    timeout: Duration,
+) -> BatchExtractResponse {
+    let started = Instant::now();
+    let batch = Semaphore::new(concurrency);
+    let results: Vec<PageExtract> = urls
+        .into_iter()
+        .map(|url| {
+            let batch = &batch;
+            async move {
+                let _slot = batch.acquire().await;
+                let _page = pool.permits.acquire().await;
+                let page_started = Instant::now();
+                let outcome = extract(pool, &url, selector, timeout).await;
+                let elapsed_ms = page_started.elapsed().as_millis() as u64;
+                match outcome {
+                    Ok(texts) => PageExtract {
+                        url,
+                        ok: true,
+                        texts,
+                        error: None,
+                        elapsed_ms,
+                    },
+                    Err(e) => PageExtract {
+                        url,
+                        ok: false,
+                        texts: Vec::new(),
+                        error: Some(format!("{:#}", e)),
+                        elapsed_ms,
+                    },
+                }
+            }
+        })
+        .collect::<FuturesOrdered<_>>()
+        .collect()
+        .await;
+
+    let succeeded = results.iter().filter(|r| r.ok).count();
+    BatchExtractResponse {
+        ok: true,
+        failed: results.len() - succeeded,
+        succeeded,
+        results,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+/// Load `url` on a pooled page and return the text of every `selector` match.
+/// The page goes back to the pool only if it finished cleanly.
+async fn extract(
+    pool: &PagePool,
+    url: &str,
+    selector: &str,
+    timeout: Duration,
+) -> anyhow::Result<Vec<String>> {
+    let page = pool.checkout().await?;
+    let work = async {
+        page.goto(url).await?;
+        page.wait_for_navigation().await?;
+        let mut texts = Vec::new();
+        for element in page.find_elements(selector).await? {
+            if let Some(text) = element.inner_text().await? {
+                let text = text.trim();
+                if !text.is_empty() {
+                    texts.push(text.to_string());
+                }
+            }
+        }
+        anyhow::Ok(texts)
+    };
+    let result = tokio::time::timeout(timeout, work)
+        .await
+        .unwrap_or_else(|_| {
+            Err(anyhow::anyhow!(
+                "timed out after {} ms",
+                timeout.as_millis()
+            ))
+        });
+
+    match result {
+        Ok(texts) => {
+            pool.checkin(page);
+            Ok(texts)
+        }
+        Err(e) => {
+            let _ = page.close().await;
+            Err(e)
+        }
+    }
+}
+
+host_fn!(browser_batch_extract(host: BrowserHost; req: Json<BatchExtractRequest>) -> Json<serde_json::Value> {
+    let host = host.get()?;
+    let host = host.lock().map_err(|_| extism::Error::msg("browser host poisoned"))?;
+    let value = match host.batch_extract(req.0) {
+        Ok(resp) => serde_json::to_value(resp)?,
+        Err(e) => serde_json::to_value(BrowserAck {
+            ok: false,
+            error: e.to_string(),
+        })?,
+    };
+    Ok(Json(value))
+});
+
+/// Build the browser host functions for a plugin governed by `policy`
+pub fn host_functions(policy: BrowserPolicy) -> anyhow::Result<Vec<Function>> {
+    let data = UserData::new(BrowserHost::new(policy)?);
+    Ok(vec![Function::new(
+        "browser_batch_extract",
+        [PTR],
+        [PTR],
+        data,
+        browser_batch_extract,
+    )])
+}
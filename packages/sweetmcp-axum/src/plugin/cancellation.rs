@@ -0,0 +1,102 @@
+//! Cooperative cancellation of plugin tool calls.
+//!
+//! The router runs each request inside [`with_request_id`], and the tool
+//! service registers the call with [`begin`] and forwards the id to the plugin
+//! in `_meta` under [`REQUEST_ID_META_KEY`]. A client's `notifications/cancelled`
+//! marks the call through [`cancel`], and the plugin sees it by polling the
+//! `mcp_is_cancelled` host function between units of work.
+
+use std::future::Future;
+
+use dashmap::DashMap;
+use extism::convert::Json;
+use extism::{Function, PTR, UserData, host_fn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// `_meta` key carrying the JSON-RPC request id to the plugin
+pub const REQUEST_ID_META_KEY: &str = "sweetmcp/requestId";
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+lazy_static::lazy_static! {
+    /// In-flight tool calls by request id, and whether each was cancelled
+    static ref IN_FLIGHT: DashMap<String, bool> = DashMap::new();
+}
+
+/// Canonical string form of a JSON-RPC id, so `5` and `"5"` match the same call
+pub fn request_key(id: &Value) -> String {
+    match id {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Run `fut` with `id` as the current request id
+pub async fn with_request_id<F: Future>(id: &Value, fut: F) -> F::Output {
+    REQUEST_ID.scope(request_key(id), fut).await
+}
+
+/// Request id of the request being handled, if any. Must be read before
+/// spawning, since task-locals do not carry over into new tasks.
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(Clone::clone).ok()
+}
+
+/// Register a tool call as in flight
+pub fn begin(request_id: &str) {
+    IN_FLIGHT.insert(request_id.to_string(), false);
+}
+
+/// Forget a finished tool call
+pub fn finish(request_id: &str) {
+    IN_FLIGHT.remove(request_id);
+}
+
+/// Mark an in-flight call as cancelled. Returns false if no such call is
+/// running, e.g. because it already finished.
+pub fn cancel(request_id: &str) -> bool {
+    match IN_FLIGHT.get_mut(request_id) {
+        Some(mut cancelled) => {
+            *cancelled = true;
+            true
+        }
+        None => false,
+    }
+}
+
+pub fn is_cancelled(request_id: &str) -> bool {
+    IN_FLIGHT
+        .get(request_id)
+        .is_some_and(|cancelled| *cancelled)
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancellationQuery {
+    pub request_id: Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CancellationStatus {
+    pub cancelled: bool,
+}
+
+host_fn!(mcp_is_cancelled(_host: (); query: Json<CancellationQuery>) -> Json<CancellationStatus> {
+    Ok(Json(CancellationStatus {
+        cancelled: is_cancelled(&request_key(&query.0.request_id)),
+    }))
+});
+
+/// Build the cancellation host functions shared by all plugins
+pub fn host_functions() -> Vec<Function> {
+    vec![Function::new(
+        "mcp_is_cancelled",
+        [PTR],
+        [PTR],
+        UserData::new(()),
+        mcp_is_cancelled,
+    )]
+}
@@ -0,0 +1,277 @@
+//! Host-side system clipboard exposed to WASM plugins as Extism host functions.
+//!
+//! The clipboard belongs to the desktop session the server runs in, so only
+//! plugins given a `clipboard` policy can reach it. The policy switches reads,
+//! writes and images on or off and caps the size of anything that crosses,
+//! so a stray multi-megabyte copy can't flood a model's context. Images cross
+//! as base64 PNG.
+//!
+//! Host functions (all JSON in / JSON out):
+//! - `clipboard_read`  – current text or image on the clipboard
+//! - `clipboard_write` – replace the clipboard with text or a PNG image
+
+use std::borrow::Cow;
+use std::io::Cursor;
+
+use anyhow::{Context, bail};
+use arboard::{Clipboard, ImageData};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use extism::convert::Json;
+use extism::{Function, PTR, UserData, host_fn};
+use serde::{Deserialize, Serialize};
+
+use crate::config::ClipboardPolicy;
+
+/// What to read from the clipboard
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ClipboardKind {
+    /// Text if there is any, else an image
+    #[default]
+    Any,
+    Text,
+    Image,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReadRequest {
+    #[serde(default)]
+    pub kind: ClipboardKind,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadResponse {
+    pub ok: bool,
+    /// `text` or `image`
+    pub kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// PNG, base64 encoded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_png: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<usize>,
+    /// Size of the text, or of the PNG before base64
+    pub bytes: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WriteRequest {
+    pub text: Option<String>,
+    /// PNG, base64 encoded
+    pub image_png: Option<String>,
+}
+
+/// Generic acknowledgement / error envelope for host calls
+#[derive(Debug, Serialize)]
+pub struct ClipboardAck {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<usize>,
+}
+
+/// Per-plugin clipboard access under a policy
+pub struct ClipboardHost {
+    policy: ClipboardPolicy,
+    /// Opened on first use and kept: on X11 and Wayland the clipboard's
+    /// contents live only as long as the process that set them holds on
+    clipboard: Option<Clipboard>,
+}
+
+impl ClipboardHost {
+    pub fn new(policy: ClipboardPolicy) -> Self {
+        Self {
+            policy,
+            clipboard: None,
+        }
+    }
+
+    fn with_clipboard<T>(
+        &mut self,
+        f: impl FnOnce(&mut Clipboard) -> anyhow::Result<T>,
+    ) -> anyhow::Result<T> {
+        if self.clipboard.is_none() {
+            self.clipboard = Some(Clipboard::new().context("no clipboard available")?);
+        }
+        f(self.clipboard.as_mut().expect("clipboard opened above"))
+    }
+
+    pub fn read(&mut self, request: ReadRequest) -> anyhow::Result<ReadResponse> {
+        if !self.policy.read {
+            bail!("reading the clipboard is disabled by policy");
+        }
+        if request.kind == ClipboardKind::Image && !self.policy.images {
+            bail!("clipboard images are disabled by policy");
+        }
+
+        let text = match request.kind {
+            ClipboardKind::Image => None,
+            _ => self.with_clipboard(|c| Ok(c.get_text().ok()))?,
+        };
+        if let Some(text) = text.filter(|t| !t.is_empty()) {
+            self.check_size(text.len())?;
+            return Ok(ReadResponse {
+                ok: true,
+                kind: "text",
+                bytes: text.len(),
+                text: Some(text),
+                image_png: None,
+                width: None,
+                height: None,
+            });
+        }
+        if request.kind == ClipboardKind::Text {
+            bail!("the clipboard holds no text");
+        }
+        if !self.policy.images {
+            bail!("the clipboard holds no text, and images are disabled by policy");
+        }
+
+        let image = self.with_clipboard(|c| {
+            c.get_image()
+                .context("the clipboard holds no text or image")
+        })?;
+        let png = encode_png(&image)?;
+        self.check_size(png.len())?;
+        Ok(ReadResponse {
+            ok: true,
+            kind: "image",
+            bytes: png.len(),
+            text: None,
+            image_png: Some(STANDARD.encode(&png)),
+            width: Some(image.width),
+            height: Some(image.height),
+        })
+    }
+
+    pub fn write(&mut self, request: WriteRequest) -> anyhow::Result<usize> {
+        if !self.policy.write {
+            bail!("writing the clipboard is disabled by policy");
+        }
+        match (request.text, request.image_png) {
+            (Some(text), None) => {
+                self.check_size(text.len())?;
+                let bytes = text.len();
+                self.with_clipboard(|c| c.set_text(text).context("failed to set clipboard text"))?;
+                Ok(bytes)
+            }
+            (None, Some(image_png)) => {
+                if !self.policy.images {
+                    bail!("clipboard images are disabled by policy");
+                }
+                let png = STANDARD
+                    .decode(image_png.trim())
+                    .context("image_png is not valid base64")?;
+                self.check_size(png.len())?;
+                let image = decode_png(&png)?;
+                self.with_clipboard(|c| {
+                    c.set_image(image).context("failed to set clipboard image")
+                })?;
+                Ok(png.len())
+            }
+            _ => bail!("give exactly one of text or image_png"),
+        }
+    }
+
+    fn check_size(&self, bytes: usize) -> anyhow::Result<()> {
+        if bytes > self.policy.max_bytes {
+            bail!(
+                "clipboard content is {} bytes, over the {} byte limit",
+                bytes,
+                self.policy.max_bytes
+            );
+        }
+        Ok(())
+    }
+}
+
+/// RGBA clipboard image as PNG
+fn encode_png(image: &ImageData) -> anyhow::Result<Vec<u8>> {
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, image.width as u32, image.height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().context("failed to encode image")?;
+    writer
+        .write_image_data(&image.bytes)
+        .context("failed to encode image")?;
+    writer.finish().context("failed to encode image")?;
+    Ok(png)
+}
+
+/// PNG as an RGBA clipboard image
+fn decode_png(png: &[u8]) -> anyhow::Result<ImageData<'static>> {
+    let mut decoder = png::Decoder::new(Cursor::new(png));
+    // Palette, low bit depths and transparency chunks become plain 8-bit channels
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let mut reader = decoder.read_info().context("image_png is not a PNG")?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let frame = reader
+        .next_frame(&mut buffer)
+        .context("image_png is not a readable PNG")?;
+    let pixels = &buffer[..frame.buffer_size()];
+
+    let rgba: Vec<u8> = match frame.color_type {
+        png::ColorType::Rgba => pixels.to_vec(),
+        png::ColorType::Rgb => pixels
+            .chunks_exact(3)
+            .flat_map(|p| [p[0], p[1], p[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => pixels
+            .chunks_exact(2)
+            .flat_map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        png::ColorType::Grayscale => pixels.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+        png::ColorType::Indexed => bail!("indexed PNG was not expanded"),
+    };
+    Ok(ImageData {
+        width: frame.width as usize,
+        height: frame.height as usize,
+        bytes: Cow::Owned(rgba),
+    })
+}
+
+fn ack(result: anyhow::Result<usize>) -> ClipboardAck {
+    match result {
+        Ok(bytes) => ClipboardAck {
+            ok: true,
+            error: None,
+            bytes: Some(bytes),
+        },
+        Err(e) => ClipboardAck {
+            ok: false,
+            error: Some(format!("{:#}", e)),
+            bytes: None,
+        },
+    }
+}
+
+host_fn!(clipboard_read(host: ClipboardHost; req: Json<ReadRequest>) -> Json<serde_json::Value> {
+    let host = host.get()?;
+    let mut host = host.lock().map_err(|_| extism::Error::msg("clipboard host poisoned"))?;
+    let value = match host.read(req.0) {
+        Ok(resp) => serde_json::to_value(resp)?,
+        Err(e) => serde_json::to_value(ack(Err(e)))?,
+    };
+    Ok(Json(value))
+});
+
+host_fn!(clipboard_write(host: ClipboardHost; req: Json<WriteRequest>) -> Json<ClipboardAck> {
+    let host = host.get()?;
+    let mut host = host.lock().map_err(|_| extism::Error::msg("clipboard host poisoned"))?;
+    Ok(Json(ack(host.write(req.0))))
+});
+
+/// Build the clipboard host functions for a plugin governed by `policy`
+pub fn host_functions(policy: ClipboardPolicy) -> Vec<Function> {
+    let data = UserData::new(ClipboardHost::new(policy));
+    vec![
+        Function::new("clipboard_read", [PTR], [PTR], data.clone(), clipboard_read),
+        Function::new("clipboard_write", [PTR], [PTR], data, clipboard_write),
+    ]
+}
@@ -0,0 +1,401 @@
+//! Per-plugin configuration contracts (`plugin.toml`).
+//!
+//! A contract declares what a plugin needs from its host — network hosts,
+//! filesystem roots, API keys, typed settings and default limits. The
+//! PluginManager checks the operator's [`PluginSettings`] against it before
+//! instantiating the plugin and reports every problem at once.
+//!
+//! ```toml
+//! [plugin]
+//! name = "notify"
+//!
+//! [permissions]
+//! hosts = ["hooks.slack.com", "slack.com", "discord.com"]
+//!
+//! [api_keys.slack_bot_token]
+//! description = "Slack bot token (xoxb-...)"
+//! env = "SLACK_BOT_TOKEN"
+//!
+//! [settings.rate_limit_per_minute]
+//! type = "integer"
+//! default = 20
+//! min = 1
+//!
+//! [limits]
+//! timeout_ms = 30000
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::config::{PluginConfig, PluginLimits, PluginSettings};
+
+/// File name looked up next to a local plugin wasm
+pub const CONTRACT_FILE: &str = "plugin.toml";
+
+/// Parsed `plugin.toml`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PluginContract {
+    pub plugin: ContractInfo,
+    #[serde(default)]
+    pub permissions: Permissions,
+    #[serde(default)]
+    pub api_keys: BTreeMap<String, ApiKeySpec>,
+    #[serde(default)]
+    pub settings: BTreeMap<String, SettingSpec>,
+    #[serde(default)]
+    pub limits: PluginLimits,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ContractInfo {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Host resources the plugin needs
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Permissions {
+    /// Hosts the plugin talks to; `*` for plugins that fetch arbitrary URLs
+    #[serde(default)]
+    pub hosts: Vec<String>,
+    /// Whether at least one filesystem root must be configured
+    #[serde(default)]
+    pub filesystem: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiKeySpec {
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+    /// Environment variable consulted when the key is not configured
+    #[serde(default)]
+    pub env: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SettingType {
+    String,
+    Integer,
+    Number,
+    Boolean,
+}
+
+impl fmt::Display for SettingType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SettingType::String => "a string",
+            SettingType::Integer => "an integer",
+            SettingType::Number => "a number",
+            SettingType::Boolean => "a boolean",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SettingSpec {
+    #[serde(rename = "type")]
+    pub kind: SettingType,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub default: Option<Value>,
+    /// Allowed values for string settings
+    #[serde(default)]
+    pub choices: Option<Vec<String>>,
+    #[serde(default)]
+    pub min: Option<f64>,
+    #[serde(default)]
+    pub max: Option<f64>,
+}
+
+/// Settings after validation, ready to be applied to an Extism manifest
+#[derive(Debug, Clone, Default)]
+pub struct ResolvedPluginConfig {
+    pub allowed_hosts: Vec<String>,
+    pub roots: Vec<String>,
+    /// Extism config keys: settings and API keys by their declared names
+    pub config_vars: BTreeMap<String, String>,
+    pub limits: PluginLimits,
+}
+
+/// Every problem found while validating one plugin's configuration
+#[derive(Debug)]
+pub struct ContractViolations {
+    pub plugin: String,
+    pub contract: PathBuf,
+    pub problems: Vec<String>,
+}
+
+impl fmt::Display for ContractViolations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "plugin '{}' configuration does not satisfy {}:",
+            self.plugin,
+            self.contract.display()
+        )?;
+        for problem in &self.problems {
+            write!(f, "\n  - {}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ContractViolations {}
+
+impl PluginContract {
+    pub fn from_toml(content: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(content)
+    }
+
+    /// Load the contract for a plugin: the explicit `contract` path, or
+    /// `plugin.toml` next to a local wasm file. `Ok(None)` when there is none.
+    pub fn locate(cfg: &PluginConfig) -> anyhow::Result<Option<(PathBuf, Self)>> {
+        let path = match &cfg.contract {
+            Some(path) => PathBuf::from(path),
+            None if cfg.path.starts_with("http") || cfg.path.starts_with("oci://") => {
+                return Ok(None);
+            }
+            None => match Path::new(&cfg.path).parent() {
+                Some(dir) => dir.join(CONTRACT_FILE),
+                None => return Ok(None),
+            },
+        };
+        if cfg.contract.is_none() && !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(&path).map_err(|e| {
+            anyhow::anyhow!("cannot read plugin contract {}: {}", path.display(), e)
+        })?;
+        let contract = Self::from_toml(&content)
+            .map_err(|e| anyhow::anyhow!("invalid plugin contract {}: {}", path.display(), e))?;
+        Ok(Some((path, contract)))
+    }
+
+    /// Names declared under `[api_keys]`
+    pub fn api_key_names(&self) -> Vec<&str> {
+        self.api_keys.keys().map(String::as_str).collect()
+    }
+
+    /// Check operator settings against this contract.
+    pub fn validate(
+        &self,
+        plugin: &str,
+        contract_path: &Path,
+        settings: &PluginSettings,
+    ) -> Result<ResolvedPluginConfig, ContractViolations> {
+        let mut problems = Vec::new();
+        let mut resolved = ResolvedPluginConfig {
+            roots: settings.roots.clone(),
+            ..ResolvedPluginConfig::default()
+        };
+
+        // Network: default to what the contract declares
+        resolved.allowed_hosts = if settings.allowed_hosts.is_empty() {
+            self.permissions.hosts.clone()
+        } else {
+            settings.allowed_hosts.clone()
+        };
+        if self.permissions.hosts.is_empty() && !settings.allowed_hosts.is_empty() {
+            problems.push(format!(
+                "allowed_hosts is set but the plugin declares no network access ({})",
+                settings.allowed_hosts.join(", ")
+            ));
+        }
+
+        // Filesystem
+        if self.permissions.filesystem && settings.roots.is_empty() {
+            problems.push(
+                "plugin needs filesystem access; configure at least one entry in roots".to_string(),
+            );
+        }
+        if !self.permissions.filesystem && !settings.roots.is_empty() {
+            problems.push(format!(
+                "roots is set but the plugin declares no filesystem access ({})",
+                settings.roots.join(", ")
+            ));
+        }
+        for root in &settings.roots {
+            if !Path::new(root).is_dir() {
+                problems.push(format!("root '{}' is not an existing directory", root));
+            }
+        }
+
+        // API keys
+        for name in settings.api_keys.keys() {
+            if !self.api_keys.contains_key(name) {
+                problems.push(unknown("API key", name, self.api_keys.keys()));
+            }
+        }
+        for (name, spec) in &self.api_keys {
+            let value = settings.api_keys.get(name).cloned().or_else(|| {
+                spec.env
+                    .as_ref()
+                    .and_then(|var| std::env::var(var).ok())
+                    .filter(|v| !v.is_empty())
+            });
+            match value {
+                Some(value) => {
+                    resolved.config_vars.insert(name.clone(), value);
+                }
+                None if spec.required => problems.push(match &spec.env {
+                    Some(var) => format!(
+                        "missing required API key '{}': set config.api_keys.{} or the {} environment variable",
+                        name, name, var
+                    ),
+                    None => format!(
+                        "missing required API key '{}': set config.api_keys.{}",
+                        name, name
+                    ),
+                }),
+                None => {}
+            }
+        }
+
+        // Settings
+        for name in settings.settings.keys() {
+            if !self.settings.contains_key(name) {
+                problems.push(unknown("setting", name, self.settings.keys()));
+            }
+        }
+        for (name, spec) in &self.settings {
+            let value = match settings.settings.get(name).or(spec.default.as_ref()) {
+                Some(value) => value,
+                None if spec.required => {
+                    problems.push(format!(
+                        "missing required setting '{}' ({})",
+                        name,
+                        spec.description
+                            .as_deref()
+                            .unwrap_or(&spec.kind.to_string())
+                    ));
+                    continue;
+                }
+                None => continue,
+            };
+            match check_setting(spec, value) {
+                Ok(text) => {
+                    resolved.config_vars.insert(name.clone(), text);
+                }
+                Err(problem) => problems.push(format!("setting '{}' {}", name, problem)),
+            }
+        }
+
+        // Limits: operator values win over the contract's defaults
+        resolved.limits = PluginLimits {
+            timeout_ms: settings.limits.timeout_ms.or(self.limits.timeout_ms),
+            tool_timeout_ms: self
+                .limits
+                .tool_timeout_ms
+                .iter()
+                .chain(&settings.limits.tool_timeout_ms)
+                .map(|(tool, ms)| (tool.clone(), *ms))
+                .collect(),
+            memory_mb: settings.limits.memory_mb.or(self.limits.memory_mb),
+            max_http_response_bytes: settings
+                .limits
+                .max_http_response_bytes
+                .or(self.limits.max_http_response_bytes),
+        };
+        if resolved.limits.timeout_ms == Some(0) {
+            problems.push("limits.timeout_ms must be greater than 0".to_string());
+        }
+        for (tool, ms) in &resolved.limits.tool_timeout_ms {
+            if *ms == 0 {
+                problems.push(format!(
+                    "limits.tool_timeout_ms.{} must be greater than 0",
+                    tool
+                ));
+            }
+        }
+        if resolved.limits.memory_mb == Some(0) {
+            problems.push("limits.memory_mb must be greater than 0".to_string());
+        }
+
+        if problems.is_empty() {
+            Ok(resolved)
+        } else {
+            Err(ContractViolations {
+                plugin: plugin.to_string(),
+                contract: contract_path.to_path_buf(),
+                problems,
+            })
+        }
+    }
+}
+
+/// Type- and range-check one setting, returning its Extism config string.
+/// Strings holding a valid literal are accepted so legacy `env` values keep working.
+fn check_setting(spec: &SettingSpec, value: &Value) -> Result<String, String> {
+    let text = match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    let mismatch = || format!("must be {}, got {}", spec.kind, value);
+
+    match spec.kind {
+        SettingType::String => {
+            if !value.is_string() {
+                return Err(mismatch());
+            }
+            if let Some(choices) = &spec.choices {
+                if !choices.contains(&text) {
+                    return Err(format!(
+                        "must be one of {}, got '{}'",
+                        choices.join(", "),
+                        text
+                    ));
+                }
+            }
+        }
+        SettingType::Boolean => {
+            if text.parse::<bool>().is_err() {
+                return Err(mismatch());
+            }
+        }
+        SettingType::Integer | SettingType::Number => {
+            let number = match spec.kind {
+                SettingType::Integer => text.parse::<i64>().map(|n| n as f64).ok(),
+                _ => text.parse::<f64>().ok().filter(|n| n.is_finite()),
+            }
+            .ok_or_else(mismatch)?;
+            if let Some(min) = spec.min.filter(|min| number < *min) {
+                return Err(format!("must be at least {}, got {}", min, text));
+            }
+            if let Some(max) = spec.max.filter(|max| number > *max) {
+                return Err(format!("must be at most {}, got {}", max, text));
+            }
+        }
+    }
+    Ok(text)
+}
+
+fn unknown<'a>(what: &str, name: &str, known: impl Iterator<Item = &'a String>) -> String {
+    let known: Vec<&str> = known.map(String::as_str).collect();
+    if known.is_empty() {
+        format!("unknown {} '{}': the plugin declares none", what, name)
+    } else {
+        format!(
+            "unknown {} '{}' (expected one of: {})",
+            what,
+            name,
+            known.join(", ")
+        )
+    }
+}
@@ -0,0 +1,151 @@
+//! Client for the cyrupd admin API, shared by host functions that front
+//! daemon features such as the vault and reminders.
+//!
+//! Host functions are synchronous and may be called from a tokio worker, so
+//! each client owns a small runtime, runs the request there and waits for it.
+//! Failures become `{ok: false, error, status}` replies, with the daemon's
+//! HTTP status so plugins can tell a refusal from an outage.
+
+use std::time::Duration;
+
+use anyhow::{Context, bail};
+use reqwest::{Method, StatusCode};
+use serde::Serialize;
+use serde_json::Value;
+use tokio::runtime::Runtime;
+
+/// Error envelope for host calls
+#[derive(Debug, Serialize)]
+pub struct DaemonAck {
+    pub ok: bool,
+    pub error: String,
+    /// HTTP status from the daemon, when it answered
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+}
+
+/// Why a call failed; `status` is set when the daemon refused it
+pub struct DaemonFailure {
+    error: anyhow::Error,
+    status: Option<StatusCode>,
+}
+
+impl DaemonFailure {
+    pub fn error(&self) -> &anyhow::Error {
+        &self.error
+    }
+}
+
+impl From<anyhow::Error> for DaemonFailure {
+    fn from(error: anyhow::Error) -> Self {
+        Self {
+            error,
+            status: None,
+        }
+    }
+}
+
+/// Connection to the daemon's admin API with one bearer token
+pub struct DaemonClient {
+    url: String,
+    token: String,
+    timeout: Duration,
+    /// Always `Some` until drop
+    runtime: Option<Runtime>,
+    client: reqwest::Client,
+}
+
+impl DaemonClient {
+    /// `url` must be the admin API on loopback TCP; `name` labels the runtime thread
+    pub fn new(name: &str, url: &str, token: String, timeout: Duration) -> anyhow::Result<Self> {
+        let url = url.trim_end_matches('/').to_string();
+        if !url.starts_with("http://127.") && !url.starts_with("http://localhost") {
+            bail!(
+                "{} url must be the daemon's loopback admin API, e.g. http://127.0.0.1:33401, got {}",
+                name,
+                url
+            );
+        }
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name(format!("{}-client", name))
+            .enable_all()
+            .build()?;
+        Ok(Self {
+            url,
+            token,
+            timeout,
+            runtime: Some(runtime),
+            client: reqwest::Client::new(),
+        })
+    }
+
+    /// Send one request to the daemon and return its JSON body
+    pub fn call(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<Value>,
+    ) -> Result<Value, DaemonFailure> {
+        // The plugin call may already be on a tokio worker, so the request
+        // runs on the client's own runtime and this thread just waits for it.
+        let runtime = self
+            .runtime
+            .as_ref()
+            .context("daemon client is shutting down")?;
+        let mut request = self
+            .client
+            .request(method, format!("{}{}", self.url, path))
+            .bearer_auth(&self.token)
+            .timeout(self.timeout);
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        runtime.spawn(async move {
+            let result = async {
+                let response = request.send().await.context("cyrupd unreachable")?;
+                let status = response.status();
+                let body: Value = response.json().await.unwrap_or(Value::Null);
+                anyhow::Ok((status, body))
+            }
+            .await;
+            let _ = tx.send(result);
+        });
+        let (status, body) = rx.recv().context("daemon request was dropped")??;
+
+        if status.is_success() {
+            return Ok(body);
+        }
+        let message = body
+            .get("error")
+            .and_then(|v| v.as_str())
+            .unwrap_or("request failed");
+        Err(DaemonFailure {
+            error: anyhow::anyhow!("{} ({})", message, status),
+            status: Some(status),
+        })
+    }
+}
+
+impl Drop for DaemonClient {
+    fn drop(&mut self) {
+        // A runtime can't be dropped from async context; let it wind down on its own
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+/// A host function reply: the value, or a `DaemonAck` describing the failure
+pub fn reply(result: Result<Value, DaemonFailure>) -> Result<Value, extism::Error> {
+    Ok(match result {
+        Ok(value) => value,
+        Err(failure) => serde_json::to_value(DaemonAck {
+            ok: false,
+            error: format!("{:#}", failure.error),
+            status: failure.status.map(|s| s.as_u16()),
+        })?,
+    })
+}
@@ -0,0 +1,87 @@
+//! Time budgets of tool calls.
+//!
+//! A call gets the tightest of three limits: the client's, sent as the
+//! [`TIMEOUT_HEADER`] header or `timeoutMs` in the call's `_meta`; the
+//! server-wide `call_timeout_ms`; and the plugin's `limits.tool_timeout_ms`
+//! entry for the tool, falling back to `limits.timeout_ms`. The tool service
+//! forwards the budget to the plugin in `_meta` under [`BUDGET_META_KEY`], so
+//! it can skip work it has no time for, and cancels the call once it runs out.
+
+use std::future::Future;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::config::PluginLimits;
+
+/// HTTP header carrying the client's timeout for the request, in milliseconds
+pub const TIMEOUT_HEADER: &str = "x-mcp-timeout-ms";
+
+/// `_meta` key a client may set to its timeout for the call, in milliseconds
+pub const CLIENT_META_KEY: &str = "timeoutMs";
+
+/// `_meta` key carrying the call's budget to the plugin, in milliseconds
+pub const BUDGET_META_KEY: &str = "sweetmcp/timeoutMs";
+
+tokio::task_local! {
+    static CLIENT_TIMEOUT: Duration;
+}
+
+static SERVER_LIMIT: OnceLock<Duration> = OnceLock::new();
+
+/// Cap every tool call at `limit`
+pub fn set_server_limit(limit: Duration) {
+    if SERVER_LIMIT.set(limit).is_err() {
+        log::warn!("Server call timeout already set; ignoring new one");
+    }
+}
+
+/// Run `fut` with the client's transport-level timeout, if it sent one
+pub async fn with_client_timeout<F: Future>(timeout: Option<Duration>, fut: F) -> F::Output {
+    match timeout {
+        Some(timeout) => CLIENT_TIMEOUT.scope(timeout, fut).await,
+        None => fut.await,
+    }
+}
+
+/// Timeout set by [`with_client_timeout`]. Must be read before spawning,
+/// since task-locals do not carry over into new tasks.
+pub fn current_client_timeout() -> Option<Duration> {
+    CLIENT_TIMEOUT.try_with(|timeout| *timeout).ok()
+}
+
+/// Parse a positive number of milliseconds from a header or `_meta` value
+pub fn parse_millis(value: &Value) -> Option<Duration> {
+    let ms = match value {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }?;
+    (ms > 0).then(|| Duration::from_millis(ms))
+}
+
+/// The client's timeout from a call's `_meta`
+pub fn from_meta(meta: Option<&Value>) -> Option<Duration> {
+    meta.and_then(|meta| meta.get(CLIENT_META_KEY))
+        .and_then(parse_millis)
+}
+
+/// Limit the plugin's configuration puts on `tool`
+pub fn tool_limit(limits: &PluginLimits, tool: &str) -> Option<Duration> {
+    limits
+        .tool_timeout_ms
+        .get(tool)
+        .copied()
+        .or(limits.timeout_ms)
+        .map(Duration::from_millis)
+}
+
+/// Budget of a call: the tightest of the client's, the server's and the
+/// tool's limits, or `None` when none is set
+pub fn budget(client: Option<Duration>, tool: Option<Duration>) -> Option<Duration> {
+    [client, SERVER_LIMIT.get().copied(), tool]
+        .into_iter()
+        .flatten()
+        .min()
+}
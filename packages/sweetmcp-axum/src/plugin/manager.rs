@@ -1,30 +1,68 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use dashmap::DashMap;
 use extism::convert::Json; // Ensure import exists
 use extism::*;
 use rpc_router::RpcResource;
+use serde::Deserialize;
 use serde_json::{Value, json};
 use sha2::{Digest, Sha256};
 use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::sync::oneshot;
 
+use super::contract::{PluginContract, ResolvedPluginConfig};
+use super::pool::{DEFAULT_POOL_SIZE, PluginPool};
+use super::sandbox::SandboxMonitor;
 use crate::{
-    config::PluginConfig,
+    config::{PluginConfig, PluginLimits, PluginSettings},
     container_registry::pull_and_extract_oci_image,
-    types::{ClientCapabilities, Prompt},
+    types::{ClientCapabilities, PluginResourceList, Prompt, Resource, ResourceTemplate},
+    upstream::UpstreamClient,
 };
 
+/// Name, version and capabilities a plugin reports in `describe`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PluginMetadata {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+}
+
+/// The part of `describe` output that isn't the tool list
+#[derive(Deserialize)]
+struct PluginDescribe {
+    #[serde(default)]
+    plugin: Option<PluginMetadata>,
+}
+
 /// The main plugin manager struct, holding all plugin-related state.
 /// Lock-free implementation using DashMap for blazing-fast concurrent access.
 #[derive(Clone, RpcResource)]
 pub struct PluginManager {
-    /// Lock-free plugin storage using DashMap for concurrent access
-    pub plugins: Arc<DashMap<String, Plugin>>,
+    /// Warm instance pool of each plugin, keyed by plugin name
+    pub plugins: Arc<DashMap<String, Arc<PluginPool>>>,
     /// Lock-free cache to map tool names to plugin names
     pub tool_to_plugin: Arc<DashMap<String, String>>,
+    /// Resolved limits of each plugin, for per-call time budgets
+    pub plugin_limits: Arc<DashMap<String, PluginLimits>>,
+    /// Sandbox denial monitors of plugins with a `sandbox_telemetry` policy
+    pub sandbox_monitors: Arc<DashMap<String, Arc<SandboxMonitor>>>,
+    /// Version and capabilities each plugin reported in `describe`
+    pub plugin_metadata: Arc<DashMap<String, PluginMetadata>>,
+    /// Connected upstream MCP servers, keyed by upstream name
+    pub upstreams: Arc<DashMap<String, Arc<UpstreamClient>>>,
+    /// Lock-free cache to map namespaced tool names to (upstream, tool name upstream)
+    pub tool_to_upstream: Arc<DashMap<String, (String, String)>>,
     /// Lock-free cache to map prompt names to plugin names and prompt metadata
     pub prompt_info: Arc<DashMap<String, (String, Prompt)>>,
+    /// Lock-free cache to map resource URIs to plugin names and resource metadata
+    pub resource_info: Arc<DashMap<String, (String, Resource)>>,
+    /// Lock-free cache to map resource URI templates to plugin names and template metadata
+    pub resource_templates: Arc<DashMap<String, (String, ResourceTemplate)>>,
     /// Lock-free client capabilities storage
     pub client_capabilities: Arc<DashMap<String, ClientCapabilities>>,
     /// Lock-free pending requests map
@@ -39,13 +77,27 @@ impl PluginManager {
         Self {
             plugins: Arc::new(DashMap::new()),
             tool_to_plugin: Arc::new(DashMap::new()),
+            plugin_limits: Arc::new(DashMap::new()),
+            sandbox_monitors: Arc::new(DashMap::new()),
+            plugin_metadata: Arc::new(DashMap::new()),
+            upstreams: Arc::new(DashMap::new()),
+            tool_to_upstream: Arc::new(DashMap::new()),
             prompt_info: Arc::new(DashMap::new()),
+            resource_info: Arc::new(DashMap::new()),
+            resource_templates: Arc::new(DashMap::new()),
             client_capabilities: Arc::new(DashMap::new()),
             pending_requests: Arc::new(DashMap::new()),
             initialized: Arc::new(AtomicBool::new(false)),
         }
     }
 
+    /// Whether `plugin` advertised `capability` in its `describe` output
+    pub fn has_capability(&self, plugin: &str, capability: &str) -> bool {
+        self.plugin_metadata
+            .get(plugin)
+            .is_some_and(|meta| meta.capabilities.iter().any(|c| c == capability))
+    }
+
     /// Check if manager is initialized (lock-free atomic read)
     pub fn is_initialized(&self) -> bool {
         self.initialized.load(Ordering::Relaxed)
@@ -61,9 +113,9 @@ impl PluginManager {
         self.plugins.len()
     }
 
-    /// Get tool count (lock-free operation)
+    /// Get tool count, including upstream tools (lock-free operation)
     pub fn tool_count(&self) -> usize {
-        self.tool_to_plugin.len()
+        self.tool_to_plugin.len() + self.tool_to_upstream.len()
     }
 }
 
@@ -159,29 +211,102 @@ pub async fn load_plugins(
             }
         };
 
+        let resolved = match resolve_plugin_config(plugin_cfg) {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                log::error!("Skipping plugin '{}': {:#}", plugin_cfg.name, e);
+                continue;
+            }
+        };
+
         let mut manifest = Manifest::new([Wasm::data(wasm_content)]);
-        if let Some(runtime_cfg) = &plugin_cfg.env {
-            log::info!("runtime_cfg: {:?}", runtime_cfg);
-            if let Some(hosts) = &runtime_cfg.allowed_hosts {
-                for host in hosts {
-                    manifest = manifest.with_allowed_host(host);
-                }
+        for host in &resolved.allowed_hosts {
+            manifest = manifest.with_allowed_host(host);
+        }
+        for path in &resolved.roots {
+            // path will be available in the plugin with exact same path
+            manifest = manifest.with_allowed_path(path.clone(), path.clone());
+        }
+        for (key, value) in &resolved.config_vars {
+            manifest = manifest.with_config_key(key, value);
+        }
+        // Extism's timeout is a backstop for the longest tool; each call's own
+        // budget is enforced by the tool service
+        if let Some(ms) = resolved.limits.timeout_ms {
+            let longest = resolved.limits.tool_timeout_ms.values().copied().max();
+            manifest = manifest.with_timeout(Duration::from_millis(ms.max(longest.unwrap_or(0))));
+        }
+        if let Some(mb) = resolved.limits.memory_mb {
+            // Wasm pages are 64 KiB
+            manifest = manifest.with_memory_max(mb.saturating_mul(16));
+        }
+        if let Some(bytes) = resolved.limits.max_http_response_bytes {
+            manifest.memory.max_http_response_bytes = Some(bytes);
+        }
+        let mut host_functions = plugin_cfg
+            .env
+            .as_ref()
+            .and_then(|env| env.terminal.clone())
+            .map(super::pty::host_functions)
+            .unwrap_or_default();
+        host_functions.extend(super::progress::host_functions());
+        host_functions.extend(super::cancellation::host_functions());
+        if let Some(policy) = plugin_cfg
+            .env
+            .as_ref()
+            .and_then(|env| env.clipboard.clone())
+        {
+            host_functions.extend(super::clipboard::host_functions(policy));
+        }
+        if let Some(policy) = plugin_cfg.env.as_ref().and_then(|env| env.screen.clone()) {
+            host_functions.extend(super::screen::host_functions(policy));
+        }
+        if let Some(policy) = plugin_cfg.env.as_ref().and_then(|env| env.ocr.clone()) {
+            host_functions.extend(super::ocr::host_functions(policy));
+        }
+        if let Some(policy) = plugin_cfg.env.as_ref().and_then(|env| env.browser.clone()) {
+            match super::browser::host_functions(policy) {
+                Ok(functions) => host_functions.extend(functions),
+                Err(e) => log::error!(
+                    "Plugin '{}' gets no browser host functions: {:#}",
+                    plugin_cfg.name,
+                    e
+                ),
             }
-            if let Some(paths) = &runtime_cfg.allowed_paths {
-                for path in paths {
-                    // path will be available in the plugin with exact same path
-                    manifest = manifest.with_allowed_path(path.clone(), path.clone());
-                }
+        }
+        if let Some(policy) = plugin_cfg.env.as_ref().and_then(|env| env.vault.clone()) {
+            match super::vault::host_functions(policy) {
+                Ok(functions) => host_functions.extend(functions),
+                Err(e) => log::error!(
+                    "Plugin '{}' gets no vault host functions: {:#}",
+                    plugin_cfg.name,
+                    e
+                ),
             }
-
-            // Add plugin configurations if present (using additional_vars)
-            for (key, value) in &runtime_cfg.additional_vars {
-                // Use additional_vars
-                manifest = manifest.with_config_key(key, value);
+        }
+        if let Some(policy) = plugin_cfg
+            .env
+            .as_ref()
+            .and_then(|env| env.reminders.clone())
+        {
+            match super::reminders::host_functions(policy) {
+                Ok(functions) => host_functions.extend(functions),
+                Err(e) => log::error!(
+                    "Plugin '{}' gets no reminders host functions: {:#}",
+                    plugin_cfg.name,
+                    e
+                ),
             }
         }
-        let mut plugin = match Plugin::new(&manifest, [], true) {
-            Ok(p) => p,
+        // Compile once; every pooled instance is created from this module
+        let builder = PluginBuilder::new(manifest)
+            .with_wasi(true)
+            .with_functions(host_functions);
+        let pool_size = plugin_cfg.pool_size.unwrap_or(DEFAULT_POOL_SIZE);
+        let pool = match CompiledPlugin::new(builder)
+            .and_then(|compiled| PluginPool::new(&plugin_cfg.name, compiled, pool_size))
+        {
+            Ok(pool) => Arc::new(pool),
             Err(e) => {
                 log::error!(
                     "Failed to initialize plugin '{}' from {}: {}",
@@ -192,6 +317,13 @@ pub async fn load_plugins(
                 continue; // Skip this plugin
             }
         };
+        let mut plugin = match pool.checkout().await {
+            Ok(plugin) => plugin,
+            Err(e) => {
+                log::error!("Plugin '{}' has no usable instance: {}", plugin_cfg.name, e);
+                continue;
+            }
+        };
 
         let plugin_name = plugin_cfg.name.clone();
 
@@ -230,6 +362,32 @@ pub async fn load_plugins(
             }
         }
 
+        // Version and capabilities, reported by builder plugins next to their tools
+        match plugin.call::<&str, Json<PluginDescribe>>("describe", "") {
+            Ok(Json(PluginDescribe {
+                plugin: Some(metadata),
+            })) => {
+                log::info!(
+                    "Plugin '{}' version {} capabilities [{}]",
+                    plugin_name,
+                    metadata.version.as_deref().unwrap_or("unknown"),
+                    metadata.capabilities.join(", ")
+                );
+                if pool_size > 1 && metadata.capabilities.iter().any(|c| c == "stateful") {
+                    log::warn!(
+                        "Plugin '{}' keeps state between calls but has pool_size {}; each instance gets its own copy",
+                        plugin_name,
+                        pool_size
+                    );
+                }
+                manager
+                    .plugin_metadata
+                    .insert(plugin_name.clone(), metadata);
+            }
+            Ok(_) => {}
+            Err(e) => log::debug!("Plugin '{}' has no describe metadata: {}", plugin_name, e),
+        }
+
         // Discover Prompts
         match plugin.call::<(), Json<Vec<Prompt>>>("mcp_list_prompts", ()) {
             // Wrap return type in Json<>
@@ -268,10 +426,155 @@ pub async fn load_plugins(
             }
         }
 
-        // Store the plugin itself using lock-free DashMap
-        manager.plugins.insert(plugin_name.clone(), plugin);
-        log::info!("Loaded plugin {} successfully", plugin_name);
+        // Discover Resources; the export is optional
+        let discovered_resources = if plugin.function_exists("list_resources") {
+            plugin.call::<(), Json<PluginResourceList>>("list_resources", ())
+        } else {
+            Ok(Json(PluginResourceList::default()))
+        };
+        match discovered_resources {
+            Ok(Json(discovered)) => {
+                for resource in discovered.resources {
+                    let uri = resource.uri.to_string();
+                    if let Some(entry) = manager.resource_info.get(&uri) {
+                        let (existing_plugin, _) = entry.value();
+                        if existing_plugin != &plugin_name {
+                            log::error!(
+                                "Resource URI collision detected: '{}' is provided by both '{}' and '{}' plugins. Skipping resource from '{}'.",
+                                uri,
+                                existing_plugin,
+                                plugin_name,
+                                plugin_name
+                            );
+                            continue;
+                        }
+                    }
+                    log::info!("Saving resource {}/{} to cache", plugin_name, uri);
+                    manager
+                        .resource_info
+                        .insert(uri, (plugin_name.clone(), resource));
+                }
+                for template in discovered.resource_templates {
+                    if let Some(entry) = manager.resource_templates.get(&template.uri_template) {
+                        let (existing_plugin, _) = entry.value();
+                        if existing_plugin != &plugin_name {
+                            log::error!(
+                                "Resource template collision detected: '{}' is provided by both '{}' and '{}' plugins. Skipping template from '{}'.",
+                                template.uri_template,
+                                existing_plugin,
+                                plugin_name,
+                                plugin_name
+                            );
+                            continue;
+                        }
+                    }
+                    log::info!(
+                        "Saving resource template {}/{} to cache",
+                        plugin_name,
+                        template.uri_template
+                    );
+                    manager.resource_templates.insert(
+                        template.uri_template.clone(),
+                        (plugin_name.clone(), template),
+                    );
+                }
+            }
+            Err(e) => {
+                log::warn!(
+                    "Plugin '{}' failed during resource discovery: {}",
+                    plugin_name,
+                    e
+                );
+            }
+        }
+
+        drop(plugin);
+        manager
+            .plugin_limits
+            .insert(plugin_name.clone(), resolved.limits.clone());
+        if let Some(policy) = plugin_cfg
+            .env
+            .as_ref()
+            .and_then(|env| env.sandbox_telemetry.clone())
+        {
+            match SandboxMonitor::new(&plugin_name, policy) {
+                Ok(monitor) => {
+                    manager
+                        .sandbox_monitors
+                        .insert(plugin_name.clone(), Arc::new(monitor));
+                }
+                Err(e) => log::error!(
+                    "Plugin '{}' gets no sandbox telemetry: {:#}",
+                    plugin_name,
+                    e
+                ),
+            }
+        }
+        manager.plugins.insert(plugin_name.clone(), pool);
+        log::info!(
+            "Loaded plugin {} successfully ({} warm instance(s))",
+            plugin_name,
+            pool_size.max(1)
+        );
     }
 
     manager
 }
+
+/// Merge a plugin's `config` (or legacy `env`) and validate it against the
+/// plugin's contract when one is available.
+fn resolve_plugin_config(cfg: &PluginConfig) -> anyhow::Result<ResolvedPluginConfig> {
+    let contract = PluginContract::locate(cfg)?;
+    let api_key_names = contract
+        .as_ref()
+        .map(|(_, c)| c.api_key_names())
+        .unwrap_or_default();
+
+    let settings = match (&cfg.config, &cfg.env) {
+        (Some(settings), Some(env)) => {
+            if env.allowed_hosts.is_some()
+                || env.allowed_paths.is_some()
+                || !env.additional_vars.is_empty()
+            {
+                anyhow::bail!(
+                    "both `config` and legacy `env` settings are present; move the `env` entries into `config`"
+                );
+            }
+            settings.clone()
+        }
+        (Some(settings), None) => settings.clone(),
+        (None, Some(env)) => PluginSettings::from_legacy_env(env, &api_key_names),
+        (None, None) => PluginSettings::default(),
+    };
+
+    match contract {
+        Some((path, contract)) => {
+            if contract.plugin.name != cfg.name {
+                log::info!(
+                    "Plugin '{}' uses contract for '{}' from {}",
+                    cfg.name,
+                    contract.plugin.name,
+                    path.display()
+                );
+            }
+            Ok(contract.validate(&cfg.name, &path, &settings)?)
+        }
+        // No contract: pass settings through unchecked, as before
+        None => Ok(ResolvedPluginConfig {
+            allowed_hosts: settings.allowed_hosts,
+            roots: settings.roots,
+            config_vars: settings
+                .api_keys
+                .into_iter()
+                .chain(settings.settings.into_iter().map(|(k, v)| {
+                    let v = match v {
+                        Value::String(s) => s,
+                        other => other.to_string(),
+                    };
+                    (k, v)
+                }))
+                .collect(),
+            limits: settings.limits,
+        }),
+    }
+}
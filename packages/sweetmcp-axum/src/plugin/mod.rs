@@ -1,6 +1,22 @@
+pub mod browser;
 pub mod build;
+pub mod cancellation;
+pub mod clipboard;
+pub mod contract;
+pub mod daemon;
+pub mod deadline;
 pub mod manager;
+pub mod ocr;
+pub mod pool;
+pub mod progress;
+pub mod pty;
+pub mod reminders;
+pub mod sandbox;
+pub mod screen;
+pub mod vault;
 
 // Re-export key items
 pub use build::{PluginBuildStrategy, build_all_plugins_in_dir, build_single_plugin_at_path};
-pub use manager::{PluginManager, load_plugins};
+pub use contract::{ContractViolations, PluginContract};
+pub use manager::{PluginManager, PluginMetadata, load_plugins};
+pub use pool::{PluginPool, PooledPlugin};
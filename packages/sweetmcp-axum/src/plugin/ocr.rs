@@ -0,0 +1,317 @@
+//! Host-side OCR exposed to WASM plugins as Extism host functions.
+//!
+//! Text recognition runs the host's `tesseract` binary, which a WASM plugin
+//! can't bundle: the image goes in on stdin and tesseract's TSV report comes
+//! back with a bounding box and confidence for every word. Only plugins given
+//! an `ocr` policy can reach it; the policy names the binary, the languages
+//! callers may ask for, and caps image size and run time.
+//!
+//! Host functions (all JSON in / JSON out):
+//! - `ocr_recognize` – words, lines and full text found in a base64 image
+
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, bail};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use extism::convert::Json;
+use extism::{Function, PTR, UserData, host_fn};
+use serde::{Deserialize, Serialize};
+
+use crate::config::OcrPolicy;
+
+#[derive(Debug, Deserialize)]
+pub struct RecognizeRequest {
+    /// PNG, JPEG, TIFF, BMP or other image tesseract reads, base64 encoded
+    pub image: String,
+    /// Tesseract language codes joined with `+`, e.g. `eng+deu`; the policy's first language when absent
+    pub language: Option<String>,
+    /// Tesseract page segmentation mode, 0-13; tesseract's default (3) when absent
+    pub page_segmentation: Option<u8>,
+    /// Drop words recognised with lower confidence, 0-100
+    #[serde(default)]
+    pub min_confidence: f32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecognizeResponse {
+    pub ok: bool,
+    pub text: String,
+    pub lines: Vec<Line>,
+    pub words: Vec<Word>,
+    pub language: String,
+    /// Mean confidence of the words returned, 0-100
+    pub confidence: f32,
+    pub elapsed_ms: u64,
+}
+
+/// Pixel rectangle, origin at the image's top left
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct BoundingBox {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl BoundingBox {
+    fn union(self, other: BoundingBox) -> BoundingBox {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        let right = (self.x + self.width).max(other.x + other.width);
+        let bottom = (self.y + self.height).max(other.y + other.height);
+        BoundingBox {
+            x,
+            y,
+            width: right - x,
+            height: bottom - y,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Word {
+    pub text: String,
+    pub confidence: f32,
+    pub bbox: BoundingBox,
+    /// Index into `lines`
+    pub line: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Line {
+    pub text: String,
+    pub confidence: f32,
+    pub bbox: BoundingBox,
+}
+
+/// Error envelope for host calls
+#[derive(Debug, Serialize)]
+pub struct OcrError {
+    pub ok: bool,
+    pub error: String,
+}
+
+/// Per-plugin OCR under a policy
+pub struct OcrHost {
+    policy: OcrPolicy,
+}
+
+impl OcrHost {
+    pub fn new(policy: OcrPolicy) -> Self {
+        Self { policy }
+    }
+
+    pub fn recognize(&self, request: RecognizeRequest) -> anyhow::Result<RecognizeResponse> {
+        let started = Instant::now();
+        let image = STANDARD
+            .decode(request.image.trim())
+            .context("image is not valid base64")?;
+        if image.len() > self.policy.max_image_bytes {
+            bail!(
+                "image is {} bytes, over the {} byte limit",
+                image.len(),
+                self.policy.max_image_bytes
+            );
+        }
+        let language = self.language(request.language.as_deref())?;
+        let mut args = vec![
+            "stdin".to_string(),
+            "stdout".to_string(),
+            "-l".to_string(),
+            language.clone(),
+        ];
+        if let Some(psm) = request.page_segmentation {
+            if psm > 13 {
+                bail!("page_segmentation must be 0-13");
+            }
+            args.extend(["--psm".to_string(), psm.to_string()]);
+        }
+        args.push("tsv".to_string());
+
+        let tsv = self.run(&args, image)?;
+        let (words, lines) = parse_tsv(&tsv, request.min_confidence);
+        let text = lines
+            .iter()
+            .map(|line| line.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let confidence = if words.is_empty() {
+            0.0
+        } else {
+            words.iter().map(|w| w.confidence).sum::<f32>() / words.len() as f32
+        };
+        Ok(RecognizeResponse {
+            ok: true,
+            text,
+            lines,
+            words,
+            language,
+            confidence,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// The requested languages if the policy allows every one of them
+    fn language(&self, requested: Option<&str>) -> anyhow::Result<String> {
+        let Some(requested) = requested.map(str::trim).filter(|l| !l.is_empty()) else {
+            return self
+                .policy
+                .languages
+                .first()
+                .cloned()
+                .context("the OCR policy allows no languages");
+        };
+        for code in requested.split('+') {
+            if !self.policy.languages.iter().any(|allowed| allowed == code) {
+                bail!(
+                    "language '{}' is not allowed by policy; allowed: {}",
+                    code,
+                    self.policy.languages.join(", ")
+                );
+            }
+        }
+        Ok(requested.to_string())
+    }
+
+    /// Run tesseract with the image on stdin, returning its stdout
+    fn run(&self, args: &[String], image: Vec<u8>) -> anyhow::Result<String> {
+        let mut child = Command::new(&self.policy.command)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("failed to start '{}'", self.policy.command))?;
+
+        // Feed stdin and drain the pipes on threads so a full pipe can't stall the timeout
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let writer = thread::spawn(move || stdin.write_all(&image));
+        let mut stdout = child.stdout.take().expect("stdout is piped");
+        let reader = thread::spawn(move || {
+            let mut out = String::new();
+            stdout.read_to_string(&mut out).map(|_| out)
+        });
+        let mut stderr = child.stderr.take().expect("stderr is piped");
+        let errors = thread::spawn(move || {
+            let mut err = String::new();
+            let _ = stderr.read_to_string(&mut err);
+            err
+        });
+
+        let deadline = Instant::now() + Duration::from_secs(self.policy.timeout_secs);
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                bail!("OCR timed out after {} seconds", self.policy.timeout_secs);
+            }
+            thread::sleep(Duration::from_millis(20));
+        };
+
+        let _ = writer.join();
+        let stderr = errors.join().unwrap_or_default();
+        if !status.success() {
+            bail!("tesseract failed ({}): {}", status, stderr.trim());
+        }
+        reader
+            .join()
+            .map_err(|_| anyhow::anyhow!("failed to read tesseract output"))?
+            .context("failed to read tesseract output")
+    }
+}
+
+/// Words and lines from tesseract's TSV report
+///
+/// Columns: level, page, block, paragraph, line, word, left, top, width,
+/// height, confidence, text. Level 5 rows are words; lines are rebuilt from
+/// the words that share a page, block, paragraph and line number.
+fn parse_tsv(tsv: &str, min_confidence: f32) -> (Vec<Word>, Vec<Line>) {
+    let mut by_line: BTreeMap<(u32, u32, u32, u32), Vec<(String, f32, BoundingBox)>> =
+        BTreeMap::new();
+    for row in tsv.lines().skip(1) {
+        let cols: Vec<&str> = row.splitn(12, '\t').collect();
+        if cols.len() < 12 || cols[0] != "5" {
+            continue;
+        }
+        let num = |i: usize| cols[i].trim().parse::<u32>().unwrap_or(0);
+        let text = cols[11].trim();
+        let confidence = cols[10].trim().parse::<f32>().unwrap_or(-1.0);
+        if text.is_empty() || confidence < min_confidence.max(0.0) {
+            continue;
+        }
+        let bbox = BoundingBox {
+            x: num(6),
+            y: num(7),
+            width: num(8),
+            height: num(9),
+        };
+        by_line
+            .entry((num(1), num(2), num(3), num(4)))
+            .or_default()
+            .push((text.to_string(), confidence, bbox));
+    }
+
+    let mut words = Vec::new();
+    let mut lines = Vec::new();
+    for line_words in by_line.into_values() {
+        let index = lines.len();
+        let bbox = line_words
+            .iter()
+            .map(|(_, _, bbox)| *bbox)
+            .reduce(BoundingBox::union)
+            .expect("lines hold at least one word");
+        let confidence =
+            line_words.iter().map(|(_, c, _)| c).sum::<f32>() / line_words.len() as f32;
+        let text = line_words
+            .iter()
+            .map(|(text, _, _)| text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+        lines.push(Line {
+            text,
+            confidence,
+            bbox,
+        });
+        words.extend(line_words.into_iter().map(|(text, confidence, bbox)| Word {
+            text,
+            confidence,
+            bbox,
+            line: index,
+        }));
+    }
+    (words, lines)
+}
+
+host_fn!(ocr_recognize(host: OcrHost; req: Json<RecognizeRequest>) -> Json<serde_json::Value> {
+    let host = host.get()?;
+    let host = host.lock().map_err(|_| extism::Error::msg("OCR host poisoned"))?;
+    let value = match host.recognize(req.0) {
+        Ok(resp) => serde_json::to_value(resp)?,
+        Err(e) => serde_json::to_value(OcrError {
+            ok: false,
+            error: format!("{:#}", e),
+        })?,
+    };
+    Ok(Json(value))
+});
+
+/// Build the OCR host functions for a plugin governed by `policy`
+pub fn host_functions(policy: OcrPolicy) -> Vec<Function> {
+    let data = UserData::new(OcrHost::new(policy));
+    vec![Function::new(
+        "ocr_recognize",
+        [PTR],
+        [PTR],
+        data,
+        ocr_recognize,
+    )]
+}
+
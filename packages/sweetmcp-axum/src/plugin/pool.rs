@@ -0,0 +1,120 @@
+//! Warm instance pools for WASM plugins.
+//!
+//! Each plugin is compiled once at startup into a [`CompiledPlugin`] and
+//! instantiated `pool_size` times up front. A call checks an instance out of
+//! its plugin's pool and returns it when done, so concurrent calls to the same
+//! plugin run side by side instead of queueing behind a single instance, and
+//! no call pays for compilation or instantiation.
+//!
+//! Instances do not share wasm memory: a plugin that keeps state between
+//! calls (Extism vars, statics) sees one copy per instance, so such plugins
+//! should keep the default `pool_size` of 1.
+
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+
+use extism::{CompiledPlugin, Plugin};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Instances per plugin when `pool_size` is not configured
+pub const DEFAULT_POOL_SIZE: usize = 1;
+
+pub struct PluginPool {
+    name: String,
+    compiled: CompiledPlugin,
+    size: usize,
+    idle: Mutex<Vec<Plugin>>,
+    /// One permit per instance; held for as long as an instance is checked out
+    permits: Arc<Semaphore>,
+}
+
+impl PluginPool {
+    /// Instantiate `size` warm instances of `compiled`
+    pub fn new(name: &str, compiled: CompiledPlugin, size: usize) -> Result<Self, extism::Error> {
+        let size = size.max(1);
+        let idle = (0..size)
+            .map(|_| Plugin::new_from_compiled(&compiled))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            name: name.to_string(),
+            compiled,
+            size,
+            idle: Mutex::new(idle),
+            permits: Arc::new(Semaphore::new(size)),
+        })
+    }
+
+    /// Wait for an idle instance
+    pub async fn checkout(self: &Arc<Self>) -> Result<PooledPlugin, extism::Error> {
+        let permit =
+            self.permits.clone().acquire_owned().await.map_err(|_| {
+                extism::Error::msg(format!("plugin '{}' is shutting down", self.name))
+            })?;
+        let idle = self
+            .idle
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .pop();
+        let plugin = match idle {
+            Some(plugin) => plugin,
+            // An instance was discarded after a failed call
+            None => Plugin::new_from_compiled(&self.compiled)?,
+        };
+        Ok(PooledPlugin {
+            plugin: Some(plugin),
+            pool: self.clone(),
+            _permit: permit,
+        })
+    }
+
+    /// Instances, idle or checked out
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+/// An instance checked out of a [`PluginPool`]; returned when dropped
+pub struct PooledPlugin {
+    plugin: Option<Plugin>,
+    pool: Arc<PluginPool>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl PooledPlugin {
+    /// Drop the instance instead of returning it, e.g. after a trap or
+    /// timeout may have left its memory inconsistent. The next checkout
+    /// instantiates a fresh one from the compiled module.
+    pub fn discard(mut self) {
+        self.plugin = None;
+    }
+}
+
+impl Deref for PooledPlugin {
+    type Target = Plugin;
+
+    fn deref(&self) -> &Plugin {
+        self.plugin
+            .as_ref()
+            .expect("pooled plugin present until drop")
+    }
+}
+
+impl DerefMut for PooledPlugin {
+    fn deref_mut(&mut self) -> &mut Plugin {
+        self.plugin
+            .as_mut()
+            .expect("pooled plugin present until drop")
+    }
+}
+
+impl Drop for PooledPlugin {
+    fn drop(&mut self) {
+        if let Some(plugin) = self.plugin.take() {
+            self.pool
+                .idle
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .push(plugin);
+        }
+    }
+}
@@ -0,0 +1,56 @@
+//! Progress reporting for WASM plugins.
+//!
+//! `mcp_progress` is registered for every plugin. It forwards a report to the
+//! client as an MCP `notifications/progress`; the plugin supplies the
+//! `progressToken` it received in the tool call's `_meta`.
+
+use extism::convert::Json;
+use extism::{Function, PTR, UserData, host_fn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::notifications::NOTIFICATION_REGISTRY;
+
+/// Progress report sent by a plugin
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgressReport {
+    /// String or number, echoed back to the client unchanged
+    pub progress_token: Value,
+    pub progress: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProgressAck {
+    /// False when no client channel is attached or it is full
+    pub delivered: bool,
+}
+
+host_fn!(mcp_progress(_host: (); report: Json<ProgressReport>) -> Json<ProgressAck> {
+    let report = report.0;
+    log::debug!(
+        "progress {}: {}/{:?} {}",
+        report.progress_token,
+        report.progress,
+        report.total,
+        report.message.as_deref().unwrap_or_default()
+    );
+    let delivered = NOTIFICATION_REGISTRY
+        .try_send_json_rpc_notification("notifications/progress", serde_json::to_value(report)?);
+    Ok(Json(ProgressAck { delivered }))
+});
+
+/// Build the progress host functions shared by all plugins
+pub fn host_functions() -> Vec<Function> {
+    vec![Function::new(
+        "mcp_progress",
+        [PTR],
+        [PTR],
+        UserData::new(()),
+        mcp_progress,
+    )]
+}
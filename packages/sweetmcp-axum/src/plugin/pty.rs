@@ -0,0 +1,352 @@
+//! Host-side PTY sessions exposed to WASM plugins as Extism host functions.
+//!
+//! Plugins cannot spawn processes from inside the sandbox, so interactive
+//! terminals (REPLs, ssh, long-running shells) live on the host. Every plugin
+//! configured with a `terminal` policy gets its own `PtyHost`; sessions are
+//! keyed by an opaque id handed back to the plugin.
+//!
+//! Host functions (all JSON in / JSON out):
+//! - `pty_open`   – spawn a program in a new PTY
+//! - `pty_write`  – send input bytes to a session
+//! - `pty_read`   – read output produced since a cursor
+//! - `pty_close`  – kill the child and drop the session
+
+use std::collections::VecDeque;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use dashmap::DashMap;
+use extism::{Function, PTR, UserData, host_fn};
+use extism::convert::Json;
+use portable_pty::{Child, CommandBuilder, MasterPty, PtySize, native_pty_system};
+use serde::{Deserialize, Serialize};
+
+use crate::config::TerminalPolicy;
+
+/// Request to open a session
+#[derive(Debug, Deserialize)]
+pub struct OpenSessionRequest {
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub cwd: Option<String>,
+    pub rows: Option<u16>,
+    pub cols: Option<u16>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OpenSessionResponse {
+    pub session_id: String,
+    pub pid: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SendInputRequest {
+    pub session_id: String,
+    pub input: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReadOutputRequest {
+    pub session_id: String,
+    /// Absolute output offset to read from; defaults to the session's last read position
+    pub cursor: Option<u64>,
+    pub max_bytes: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadOutputResponse {
+    pub output: String,
+    /// Offset to pass as `cursor` on the next read
+    pub cursor: u64,
+    /// True when requested output had already been evicted from scrollback
+    pub truncated: bool,
+    pub exited: bool,
+    pub exit_code: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CloseSessionRequest {
+    pub session_id: String,
+}
+
+/// Generic acknowledgement / error envelope for host calls
+#[derive(Debug, Serialize)]
+pub struct PtyAck {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Bounded output buffer with absolute offsets
+struct Scrollback {
+    data: VecDeque<u8>,
+    /// Absolute offset of `data[0]`
+    start: u64,
+    limit: usize,
+}
+
+impl Scrollback {
+    fn new(limit: usize) -> Self {
+        Self {
+            data: VecDeque::with_capacity(limit.min(64 * 1024)),
+            start: 0,
+            limit,
+        }
+    }
+
+    fn push(&mut self, bytes: &[u8]) {
+        self.data.extend(bytes);
+        let overflow = self.data.len().saturating_sub(self.limit);
+        if overflow > 0 {
+            self.data.drain(..overflow);
+            self.start += overflow as u64;
+        }
+    }
+
+    fn end(&self) -> u64 {
+        self.start + self.data.len() as u64
+    }
+
+    fn read_from(&self, cursor: u64, max: usize) -> (Vec<u8>, u64, bool) {
+        let truncated = cursor < self.start;
+        let from = cursor.clamp(self.start, self.end());
+        let skip = (from - self.start) as usize;
+        let bytes: Vec<u8> = self.data.iter().skip(skip).take(max).copied().collect();
+        let next = from + bytes.len() as u64;
+        (bytes, next, truncated)
+    }
+}
+
+struct PtySession {
+    master: Box<dyn MasterPty + Send>,
+    writer: Box<dyn Write + Send>,
+    child: Box<dyn Child + Send + Sync>,
+    output: Arc<Mutex<Scrollback>>,
+    read_cursor: u64,
+}
+
+/// Per-plugin PTY session registry and policy
+pub struct PtyHost {
+    policy: TerminalPolicy,
+    sessions: DashMap<String, PtySession>,
+}
+
+impl PtyHost {
+    pub fn new(policy: TerminalPolicy) -> Self {
+        Self {
+            policy,
+            sessions: DashMap::new(),
+        }
+    }
+
+    fn open(&self, req: OpenSessionRequest) -> anyhow::Result<OpenSessionResponse> {
+        if self.sessions.len() >= self.policy.max_sessions {
+            anyhow::bail!(
+                "session limit reached ({} open)",
+                self.policy.max_sessions
+            );
+        }
+        if !self.policy.allows_command(&req.command) {
+            anyhow::bail!("command '{}' is not in the terminal allowlist", req.command);
+        }
+        let cwd = match &req.cwd {
+            Some(dir) => Some(self.policy.check_cwd(Path::new(dir))?),
+            None => self.policy.allowed_dirs.first().map(PathBuf::from),
+        };
+
+        let pair = native_pty_system().openpty(PtySize {
+            rows: req.rows.unwrap_or(24),
+            cols: req.cols.unwrap_or(80),
+            pixel_width: 0,
+            pixel_height: 0,
+        })?;
+
+        let mut cmd = CommandBuilder::new(&req.command);
+        cmd.args(&req.args);
+        if let Some(dir) = cwd {
+            cmd.cwd(dir);
+        }
+        let child = pair.slave.spawn_command(cmd)?;
+        drop(pair.slave);
+
+        let output = Arc::new(Mutex::new(Scrollback::new(self.policy.scrollback_bytes)));
+        let mut reader = pair.master.try_clone_reader()?;
+        let sink = output.clone();
+        thread::Builder::new()
+            .name("pty-reader".into())
+            .spawn(move || {
+                let mut buf = [0u8; 4096];
+                loop {
+                    match reader.read(&mut buf) {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => {
+                            if let Ok(mut sb) = sink.lock() {
+                                sb.push(&buf[..n]);
+                            }
+                        }
+                    }
+                }
+            })?;
+
+        let writer = pair.master.take_writer()?;
+        let session_id = uuid::Uuid::new_v4().to_string();
+        let pid = child.process_id();
+        self.sessions.insert(
+            session_id.clone(),
+            PtySession {
+                master: pair.master,
+                writer,
+                child,
+                output,
+                read_cursor: 0,
+            },
+        );
+        log::info!("Opened PTY session {} ({})", session_id, req.command);
+
+        Ok(OpenSessionResponse { session_id, pid })
+    }
+
+    fn write(&self, req: SendInputRequest) -> anyhow::Result<()> {
+        let mut session = self
+            .sessions
+            .get_mut(&req.session_id)
+            .ok_or_else(|| anyhow::anyhow!("unknown session {}", req.session_id))?;
+        session.writer.write_all(req.input.as_bytes())?;
+        session.writer.flush()?;
+        Ok(())
+    }
+
+    fn read(&self, req: ReadOutputRequest) -> anyhow::Result<ReadOutputResponse> {
+        let mut session = self
+            .sessions
+            .get_mut(&req.session_id)
+            .ok_or_else(|| anyhow::anyhow!("unknown session {}", req.session_id))?;
+
+        let cursor = req.cursor.unwrap_or(session.read_cursor);
+        let max = req
+            .max_bytes
+            .unwrap_or(self.policy.scrollback_bytes)
+            .min(self.policy.scrollback_bytes);
+        let (bytes, next, truncated) = session
+            .output
+            .lock()
+            .map_err(|_| anyhow::anyhow!("scrollback poisoned"))?
+            .read_from(cursor, max);
+        session.read_cursor = next;
+
+        let status = session.child.try_wait()?;
+        Ok(ReadOutputResponse {
+            output: String::from_utf8_lossy(&bytes).into_owned(),
+            cursor: next,
+            truncated,
+            exited: status.is_some(),
+            exit_code: status.map(|s| s.exit_code()),
+        })
+    }
+
+    fn close(&self, req: CloseSessionRequest) -> anyhow::Result<()> {
+        let (_, mut session) = self
+            .sessions
+            .remove(&req.session_id)
+            .ok_or_else(|| anyhow::anyhow!("unknown session {}", req.session_id))?;
+        session.child.kill().ok();
+        drop(session.master);
+        log::info!("Closed PTY session {}", req.session_id);
+        Ok(())
+    }
+}
+
+impl Drop for PtyHost {
+    fn drop(&mut self) {
+        for mut entry in self.sessions.iter_mut() {
+            entry.child.kill().ok();
+        }
+    }
+}
+
+fn ack(result: anyhow::Result<()>) -> PtyAck {
+    match result {
+        Ok(()) => PtyAck {
+            ok: true,
+            error: None,
+        },
+        Err(e) => PtyAck {
+            ok: false,
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+host_fn!(pty_open(host: PtyHost; req: Json<OpenSessionRequest>) -> Json<serde_json::Value> {
+    let host = host.get()?;
+    let host = host.lock().map_err(|_| extism::Error::msg("pty host poisoned"))?;
+    let value = match host.open(req.0) {
+        Ok(resp) => serde_json::to_value(resp)?,
+        Err(e) => serde_json::to_value(ack(Err(e)))?,
+    };
+    Ok(Json(value))
+});
+
+host_fn!(pty_write(host: PtyHost; req: Json<SendInputRequest>) -> Json<PtyAck> {
+    let host = host.get()?;
+    let host = host.lock().map_err(|_| extism::Error::msg("pty host poisoned"))?;
+    Ok(Json(ack(host.write(req.0))))
+});
+
+host_fn!(pty_read(host: PtyHost; req: Json<ReadOutputRequest>) -> Json<serde_json::Value> {
+    let host = host.get()?;
+    let host = host.lock().map_err(|_| extism::Error::msg("pty host poisoned"))?;
+    let value = match host.read(req.0) {
+        Ok(resp) => serde_json::to_value(resp)?,
+        Err(e) => serde_json::to_value(ack(Err(e)))?,
+    };
+    Ok(Json(value))
+});
+
+host_fn!(pty_close(host: PtyHost; req: Json<CloseSessionRequest>) -> Json<PtyAck> {
+    let host = host.get()?;
+    let host = host.lock().map_err(|_| extism::Error::msg("pty host poisoned"))?;
+    Ok(Json(ack(host.close(req.0))))
+});
+
+/// Build the PTY host functions for a plugin governed by `policy`
+pub fn host_functions(policy: TerminalPolicy) -> Vec<Function> {
+    let data = UserData::new(PtyHost::new(policy));
+    vec![
+        Function::new("pty_open", [PTR], [PTR], data.clone(), pty_open),
+        Function::new("pty_write", [PTR], [PTR], data.clone(), pty_write),
+        Function::new("pty_read", [PTR], [PTR], data.clone(), pty_read),
+        Function::new("pty_close", [PTR], [PTR], data, pty_close),
+    ]
+}
+
+impl TerminalPolicy {
+    /// Match by program basename so `/usr/bin/python3` and `python3` are equivalent
+    fn allows_command(&self, command: &str) -> bool {
+        let program = Path::new(command)
+            .file_name()
+            .and_then(|f| f.to_str())
+            .unwrap_or(command);
+        self.allowed_commands.iter().any(|c| c == program || c == command)
+    }
+
+    fn check_cwd(&self, dir: &Path) -> anyhow::Result<PathBuf> {
+        let dir = dir.canonicalize()?;
+        if self.allowed_dirs.is_empty() {
+            anyhow::bail!("no working directories are allowed for terminal sessions");
+        }
+        let permitted = self.allowed_dirs.iter().any(|allowed| {
+            Path::new(allowed)
+                .canonicalize()
+                .map(|allowed| dir.starts_with(allowed))
+                .unwrap_or(false)
+        });
+        if !permitted {
+            anyhow::bail!("working directory {} is outside the allowed dirs", dir.display());
+        }
+        Ok(dir)
+    }
+}
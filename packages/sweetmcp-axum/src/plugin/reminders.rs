@@ -0,0 +1,102 @@
+//! Host-side reminders exposed to WASM plugins as Extism host functions.
+//!
+//! Reminders are kept and fired by the cyrupd daemon, which serves them on
+//! its admin API under `/reminders`, so they outlive the plugin instance and
+//! the server process. This host forwards plugin calls there with the token
+//! from the plugin's `reminders` policy. When a reminder comes due the daemon
+//! streams it to SSE subscribers and, if it names one, to a notify
+//! destination.
+//!
+//! Host functions (all JSON in / JSON out):
+//! - `reminders_schedule` – add a one-off or cron reminder
+//! - `reminders_list`     – every pending reminder, soonest first
+//! - `reminders_cancel`   – remove a reminder by id
+
+use std::time::Duration;
+
+use extism::convert::Json;
+use extism::{Function, PTR, UserData, host_fn};
+use reqwest::Method;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use super::daemon::{DaemonClient, DaemonFailure, reply};
+use crate::config::RemindersPolicy;
+
+#[derive(Debug, Deserialize)]
+pub struct CancelRequest {
+    pub id: String,
+}
+
+/// Per-plugin connection to the daemon's reminders
+pub struct RemindersHost {
+    daemon: DaemonClient,
+}
+
+impl RemindersHost {
+    pub fn new(policy: RemindersPolicy) -> anyhow::Result<Self> {
+        let token = policy.resolve_token()?;
+        let timeout = Duration::from_secs(policy.timeout_secs);
+        Ok(Self {
+            daemon: DaemonClient::new("reminders", &policy.url, token, timeout)?,
+        })
+    }
+
+    /// `request` is passed through as the daemon's `NewReminder`
+    fn schedule(&self, request: Value) -> Result<Value, DaemonFailure> {
+        let reminder = self
+            .daemon
+            .call(Method::POST, "/reminders", Some(request))?;
+        Ok(json!({ "ok": true, "reminder": reminder }))
+    }
+
+    fn list(&self) -> Result<Value, DaemonFailure> {
+        let reminders = self.daemon.call(Method::GET, "/reminders", None)?;
+        Ok(json!({ "ok": true, "reminders": reminders }))
+    }
+
+    fn cancel(&self, request: CancelRequest) -> Result<Value, DaemonFailure> {
+        // Ids are hex, so anything else can't name a reminder
+        let id = request.id.trim();
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(anyhow::anyhow!("no reminder with id {}", id).into());
+        }
+        let path = format!("/reminders/{}", id);
+        let reminder = self.daemon.call(Method::DELETE, &path, None)?;
+        Ok(json!({ "ok": true, "reminder": reminder }))
+    }
+}
+
+host_fn!(reminders_schedule(host: RemindersHost; req: Json<Value>) -> Json<Value> {
+    let host = host.get()?;
+    let host = host.lock().map_err(|_| extism::Error::msg("reminders host poisoned"))?;
+    Ok(Json(reply(host.schedule(req.0))?))
+});
+
+host_fn!(reminders_list(host: RemindersHost; _req: Json<Value>) -> Json<Value> {
+    let host = host.get()?;
+    let host = host.lock().map_err(|_| extism::Error::msg("reminders host poisoned"))?;
+    Ok(Json(reply(host.list())?))
+});
+
+host_fn!(reminders_cancel(host: RemindersHost; req: Json<CancelRequest>) -> Json<Value> {
+    let host = host.get()?;
+    let host = host.lock().map_err(|_| extism::Error::msg("reminders host poisoned"))?;
+    Ok(Json(reply(host.cancel(req.0))?))
+});
+
+/// Build the reminders host functions for a plugin governed by `policy`
+pub fn host_functions(policy: RemindersPolicy) -> anyhow::Result<Vec<Function>> {
+    let data = UserData::new(RemindersHost::new(policy)?);
+    Ok(vec![
+        Function::new(
+            "reminders_schedule",
+            [PTR],
+            [PTR],
+            data.clone(),
+            reminders_schedule,
+        ),
+        Function::new("reminders_list", [PTR], [PTR], data.clone(), reminders_list),
+        Function::new("reminders_cancel", [PTR], [PTR], data, reminders_cancel),
+    ])
+}
@@ -0,0 +1,212 @@
+//! Telemetry of what the wasm sandbox blocks in eval plugins.
+//!
+//! Extism offers no hook into WASI calls, so the monitor reads what a blocked
+//! call leaves behind in the tool's result or error: WASI's `ENOTCAPABLE` for
+//! paths outside the plugin's allowed paths, missing socket support, and
+//! Extism's refusal of hosts outside `allowed_hosts`. Denials are counted per
+//! client and kind over a sliding window, and a client that reaches the
+//! plugin's `sandbox_telemetry` threshold is reported to cyrupd's
+//! `/security/sandbox`, at most once per window.
+//!
+//! The client is the connection a call came in on: `stdio`, the peer of a
+//! unix socket, or the remote address of an HTTP request. The router sets it
+//! with [`with_client`].
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use dashmap::DashMap;
+use reqwest::Method;
+use serde_json::json;
+
+use super::daemon::DaemonClient;
+use crate::config::SandboxTelemetryPolicy;
+
+/// Longest sample line sent with a report
+const MAX_SAMPLE_CHARS: usize = 200;
+
+/// Lowercased messages of access refused by WASI or the Python/JS runtime
+const FILESYSTEM_SIGNATURES: &[&str] = &[
+    "capabilities insufficient",
+    "enotcapable",
+    "errno 76",
+    "os error 76",
+    "operation not permitted",
+    "read-only file system",
+];
+
+/// Lowercased messages of network access the sandbox has no support for or refused
+const NETWORK_SIGNATURES: &[&str] = &[
+    "http request to",
+    "no module named '_socket'",
+    "no module named 'socket'",
+    "no module named '_ssl'",
+    "fetch is not defined",
+    "xmlhttprequest is not defined",
+    "websocket is not defined",
+    "address family not supported",
+    "network is unreachable",
+];
+
+tokio::task_local! {
+    static CLIENT: String;
+}
+
+/// Run `fut` with `client` as the current client
+pub async fn with_client<F: Future>(client: String, fut: F) -> F::Output {
+    CLIENT.scope(client, fut).await
+}
+
+/// Client set by [`with_client`]. Must be read before spawning, since
+/// task-locals do not carry over into new tasks.
+pub fn current_client() -> Option<String> {
+    CLIENT.try_with(Clone::clone).ok()
+}
+
+/// What a blocked call tried to reach
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Denial {
+    Filesystem,
+    Network,
+}
+
+impl Denial {
+    pub fn name(self) -> &'static str {
+        match self {
+            Denial::Filesystem => "filesystem",
+            Denial::Network => "network",
+        }
+    }
+}
+
+/// Every line of `text` that reads as a sandbox denial, with the line as sample.
+/// Tool results arrive as JSON, so escaped newlines split lines too.
+pub fn classify(text: &str) -> Vec<(Denial, String)> {
+    text.split('\n')
+        .flat_map(|line| line.split("\\n"))
+        .filter_map(|line| {
+            let lower = line.to_lowercase();
+            let denial = if FILESYSTEM_SIGNATURES.iter().any(|s| lower.contains(s)) {
+                Denial::Filesystem
+            } else if NETWORK_SIGNATURES.iter().any(|s| lower.contains(s)) {
+                Denial::Network
+            } else {
+                return None;
+            };
+            Some((denial, line.trim().chars().take(MAX_SAMPLE_CHARS).collect()))
+        })
+        .collect()
+}
+
+/// Recent denials of one kind by one client
+#[derive(Default)]
+struct Window {
+    hits: VecDeque<Instant>,
+    reported: Option<Instant>,
+}
+
+/// Per-plugin denial counter and reporter
+pub struct SandboxMonitor {
+    plugin: String,
+    threshold: u32,
+    window: Duration,
+    daemon: DaemonClient,
+    windows: DashMap<(String, Denial), Window>,
+}
+
+impl SandboxMonitor {
+    pub fn new(plugin: &str, policy: SandboxTelemetryPolicy) -> anyhow::Result<Self> {
+        if policy.threshold == 0 || policy.window_secs == 0 {
+            anyhow::bail!("sandbox_telemetry threshold and window_secs must be positive");
+        }
+        let token = policy.resolve_token()?;
+        let timeout = Duration::from_secs(policy.timeout_secs);
+        Ok(Self {
+            plugin: plugin.to_string(),
+            threshold: policy.threshold,
+            window: Duration::from_secs(policy.window_secs),
+            daemon: DaemonClient::new("sandbox", &policy.url, token, timeout)?,
+            windows: DashMap::new(),
+        })
+    }
+
+    /// Count the denials in a call's result or error, and report each kind
+    /// whose count reaches the threshold
+    pub fn observe(self: &Arc<Self>, client: &str, text: &str) {
+        let now = Instant::now();
+        for (denial, sample) in classify(text) {
+            log::debug!(
+                "Sandbox of plugin '{}' blocked {} access for client {}: {}",
+                self.plugin,
+                denial.name(),
+                client,
+                sample
+            );
+            if let Some(count) = self.record(client, denial, now) {
+                self.report(client, denial, count, sample);
+            }
+        }
+    }
+
+    /// Add a denial; the count within the window if it should be reported now
+    fn record(&self, client: &str, denial: Denial, now: Instant) -> Option<u32> {
+        let mut recent = self
+            .windows
+            .entry((client.to_string(), denial))
+            .or_default();
+        recent.hits.push_back(now);
+        while let Some(&first) = recent.hits.front() {
+            if now.duration_since(first) < self.window {
+                break;
+            }
+            recent.hits.pop_front();
+        }
+        let count = recent.hits.len() as u32;
+        if count < self.threshold {
+            return None;
+        }
+        if recent
+            .reported
+            .is_some_and(|at| now.duration_since(at) < self.window)
+        {
+            return None;
+        }
+        recent.reported = Some(now);
+        Some(count)
+    }
+
+    fn report(self: &Arc<Self>, client: &str, denial: Denial, count: u32, sample: String) {
+        log::warn!(
+            "SECURITY: sandbox of plugin '{}' blocked {} {} attempts by client {} in {}s",
+            self.plugin,
+            count,
+            denial.name(),
+            client,
+            self.window.as_secs()
+        );
+        let body = json!({
+            "plugin": self.plugin,
+            "client": client,
+            "kind": denial.name(),
+            "count": count,
+            "window_secs": self.window.as_secs(),
+            "sample": sample,
+        });
+        // The daemon client blocks until the daemon answers
+        let monitor = self.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Err(failure) = monitor
+                .daemon
+                .call(Method::POST, "/security/sandbox", Some(body))
+            {
+                log::error!(
+                    "Could not report sandbox denials of plugin '{}' to cyrupd: {:#}",
+                    monitor.plugin,
+                    failure.error()
+                );
+            }
+        });
+    }
+}
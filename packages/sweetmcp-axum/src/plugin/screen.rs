@@ -0,0 +1,290 @@
+//! Host-side screen capture exposed to WASM plugins as Extism host functions.
+//!
+//! Captures the desktop of the session the server runs in, a whole display or
+//! a single window. Only plugins given a `screen` policy can reach it, and by
+//! default every capture first asks the user in a native dialog; a declined
+//! or unanswered prompt fails the call. Captures wider than the policy's
+//! `max_width` are scaled down, and cross as base64 PNG.
+//!
+//! Host functions (all JSON in / JSON out):
+//! - `screen_capture_display` – one display, by index or name
+//! - `screen_capture_window`  – the first window whose title contains a string
+
+use std::process::Command;
+use std::sync::Mutex;
+
+use anyhow::{Context, bail};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use extism::convert::Json;
+use extism::{Function, PTR, UserData, host_fn};
+use image::RgbaImage;
+use image::imageops::{self, FilterType};
+use serde::{Deserialize, Serialize};
+use xcap::{Monitor, Window};
+
+use crate::config::{ScreenConsent, ScreenPolicy};
+
+/// Window titles named in a "no window matches" error
+const MAX_LISTED_WINDOWS: usize = 30;
+
+#[derive(Debug, Deserialize)]
+pub struct DisplayRequest {
+    /// Display index or name; the primary display when absent
+    pub display: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WindowRequest {
+    /// Case-insensitive substring of the window title or application name
+    pub title: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CaptureResponse {
+    pub ok: bool,
+    /// What was captured, e.g. `display 0 (DP-1)` or `window "Terminal"`
+    pub source: String,
+    /// PNG, base64 encoded
+    pub image_png: String,
+    pub width: u32,
+    pub height: u32,
+    /// Set when the capture was scaled down to the policy's `max_width`
+    pub scaled_from: Option<(u32, u32)>,
+}
+
+/// Error envelope for host calls
+#[derive(Debug, Serialize)]
+pub struct ScreenError {
+    pub ok: bool,
+    pub error: String,
+}
+
+/// Per-plugin screen access under a policy
+pub struct ScreenHost {
+    policy: ScreenPolicy,
+}
+
+impl ScreenHost {
+    pub fn new(policy: ScreenPolicy) -> Self {
+        Self { policy }
+    }
+
+    pub fn capture_display(&self, request: DisplayRequest) -> anyhow::Result<CaptureResponse> {
+        if !self.policy.displays {
+            bail!("display capture is disabled by policy");
+        }
+        let monitors = Monitor::all().context("failed to list displays")?;
+        let (index, monitor) = match request.display.as_deref().map(str::trim) {
+            None | Some("") => monitors
+                .iter()
+                .enumerate()
+                .find(|(_, m)| m.is_primary())
+                .or_else(|| monitors.iter().enumerate().next()),
+            Some(wanted) => match wanted.parse::<usize>() {
+                Ok(index) => monitors.get(index).map(|m| (index, m)),
+                Err(_) => monitors
+                    .iter()
+                    .enumerate()
+                    .find(|(_, m)| m.name().eq_ignore_ascii_case(wanted)),
+            },
+        }
+        .with_context(|| {
+            let names: Vec<String> = monitors
+                .iter()
+                .enumerate()
+                .map(|(index, m)| format!("{} ({})", index, m.name()))
+                .collect();
+            format!(
+                "no display '{}'; displays are: {}",
+                request.display.as_deref().unwrap_or_default(),
+                names.join(", ")
+            )
+        })?;
+
+        let source = format!("display {} ({})", index, monitor.name());
+        self.consent(&source)?;
+        let image = monitor
+            .capture_image()
+            .context("failed to capture display")?;
+        self.respond(source, image)
+    }
+
+    pub fn capture_window(&self, request: WindowRequest) -> anyhow::Result<CaptureResponse> {
+        if !self.policy.windows {
+            bail!("window capture is disabled by policy");
+        }
+        let wanted = request.title.trim().to_lowercase();
+        if wanted.is_empty() {
+            bail!("title must not be empty");
+        }
+        let windows = Window::all().context("failed to list windows")?;
+        let window = windows
+            .iter()
+            .filter(|window| !window.is_minimized())
+            .find(|window| {
+                window.title().to_lowercase().contains(&wanted)
+                    || window.app_name().to_lowercase().contains(&wanted)
+            })
+            .with_context(|| {
+                let titles: Vec<&str> = windows
+                    .iter()
+                    .filter(|window| !window.is_minimized() && !window.title().is_empty())
+                    .map(|window| window.title())
+                    .take(MAX_LISTED_WINDOWS)
+                    .collect();
+                format!(
+                    "no visible window matches '{}'; open windows are: {}",
+                    request.title,
+                    titles.join(", ")
+                )
+            })?;
+
+        let source = format!("window \"{}\"", window.title());
+        self.consent(&source)?;
+        let image = window.capture_image().context("failed to capture window")?;
+        self.respond(source, image)
+    }
+
+    /// Ask the user before capturing, unless the policy says not to
+    fn consent(&self, source: &str) -> anyhow::Result<()> {
+        match self.policy.consent {
+            ScreenConsent::Always => Ok(()),
+            ScreenConsent::Prompt => {
+                let message = format!(
+                    "An MCP plugin wants to capture {} of your screen. Allow it?",
+                    source
+                );
+                if ask_user(&message)? {
+                    Ok(())
+                } else {
+                    bail!("the user declined the screen capture")
+                }
+            }
+        }
+    }
+
+    fn respond(&self, source: String, image: RgbaImage) -> anyhow::Result<CaptureResponse> {
+        let (width, height) = image.dimensions();
+        let (image, scaled_from) = if width > self.policy.max_width {
+            let scaled_height =
+                ((height as u64 * self.policy.max_width as u64) / width as u64).max(1) as u32;
+            let scaled = imageops::resize(
+                &image,
+                self.policy.max_width,
+                scaled_height,
+                FilterType::Triangle,
+            );
+            (scaled, Some((width, height)))
+        } else {
+            (image, None)
+        };
+        let (width, height) = image.dimensions();
+        Ok(CaptureResponse {
+            ok: true,
+            source,
+            image_png: STANDARD.encode(encode_png(&image)?),
+            width,
+            height,
+            scaled_from,
+        })
+    }
+}
+
+/// Show a yes/no dialog on the desktop and wait for the answer
+///
+/// Uses the platform's own dialog tool so the prompt works from any thread:
+/// `osascript` on macOS, PowerShell on Windows, and `zenity` or `kdialog`
+/// elsewhere. With none of them available the capture is refused.
+fn ask_user(message: &str) -> anyhow::Result<bool> {
+    // One prompt at a time, however many plugin instances are asking
+    static PROMPT: Mutex<()> = Mutex::new(());
+    let _prompt = PROMPT.lock().unwrap_or_else(|e| e.into_inner());
+
+    let status = if cfg!(target_os = "macos") {
+        let script = format!(
+            "display dialog \"{}\" with title \"Screen capture\" buttons {{\"Deny\", \"Allow\"}} \
+             default button \"Deny\" cancel button \"Deny\"",
+            message.replace('\\', "\\\\").replace('"', "\\\"")
+        );
+        Command::new("osascript").args(["-e", &script]).status()
+    } else if cfg!(windows) {
+        let script = format!(
+            "Add-Type -AssemblyName PresentationFramework; \
+             if ([System.Windows.MessageBox]::Show('{}', 'Screen capture', 'YesNo', 'Question') \
+             -eq 'Yes') {{ exit 0 }} else {{ exit 1 }}",
+            message.replace('\'', "''")
+        );
+        Command::new("powershell")
+            .args(["-NoProfile", "-Command", &script])
+            .status()
+    } else {
+        Command::new("zenity")
+            .args(["--question", "--title=Screen capture", "--text", message])
+            .status()
+            .or_else(|_| {
+                Command::new("kdialog")
+                    .args(["--title", "Screen capture", "--yesno", message])
+                    .status()
+            })
+    };
+    let status = status.context("no dialog tool available to ask for screen capture consent")?;
+    Ok(status.success())
+}
+
+/// RGBA image as PNG
+fn encode_png(image: &RgbaImage) -> anyhow::Result<Vec<u8>> {
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, image.width(), image.height());
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().context("failed to encode image")?;
+    writer
+        .write_image_data(image.as_raw())
+        .context("failed to encode image")?;
+    writer.finish().context("failed to encode image")?;
+    Ok(png)
+}
+
+fn reply<T: Serialize>(result: anyhow::Result<T>) -> Result<serde_json::Value, extism::Error> {
+    Ok(match result {
+        Ok(response) => serde_json::to_value(response)?,
+        Err(e) => serde_json::to_value(ScreenError {
+            ok: false,
+            error: format!("{:#}", e),
+        })?,
+    })
+}
+
+host_fn!(screen_capture_display(host: ScreenHost; req: Json<DisplayRequest>) -> Json<serde_json::Value> {
+    let host = host.get()?;
+    let host = host.lock().map_err(|_| extism::Error::msg("screen host poisoned"))?;
+    Ok(Json(reply(host.capture_display(req.0))?))
+});
+
+host_fn!(screen_capture_window(host: ScreenHost; req: Json<WindowRequest>) -> Json<serde_json::Value> {
+    let host = host.get()?;
+    let host = host.lock().map_err(|_| extism::Error::msg("screen host poisoned"))?;
+    Ok(Json(reply(host.capture_window(req.0))?))
+});
+
+/// Build the screen capture host functions for a plugin governed by `policy`
+pub fn host_functions(policy: ScreenPolicy) -> Vec<Function> {
+    let data = UserData::new(ScreenHost::new(policy));
+    vec![
+        Function::new(
+            "screen_capture_display",
+            [PTR],
+            [PTR],
+            data.clone(),
+            screen_capture_display,
+        ),
+        Function::new(
+            "screen_capture_window",
+            [PTR],
+            [PTR],
+            data,
+            screen_capture_window,
+        ),
+    ]
+}
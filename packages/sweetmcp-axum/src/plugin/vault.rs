@@ -0,0 +1,103 @@
+//! Host-side credential vault exposed to WASM plugins as Extism host functions.
+//!
+//! Secrets are kept by the cyrupd daemon, which serves them on its admin API
+//! under `/secrets`. This host forwards plugin calls there with the client
+//! token from the plugin's `vault` policy, so what a plugin may list, reveal
+//! or store is decided by that client's policy in the daemon config, not here.
+//! Values come back redacted unless the call asks to reveal and the daemon
+//! allows it.
+//!
+//! Host functions (all JSON in / JSON out):
+//! - `vault_list`  – names, descriptions and references the client may read
+//! - `vault_get`   – one secret, redacted or revealed
+//! - `vault_store` – create or replace a secret
+
+use std::time::Duration;
+
+use extism::convert::Json;
+use extism::{Function, PTR, UserData, host_fn};
+use reqwest::Method;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use super::daemon::{DaemonClient, DaemonFailure, reply};
+use crate::config::VaultPolicy;
+
+#[derive(Debug, Deserialize)]
+pub struct GetRequest {
+    pub name: String,
+    #[serde(default)]
+    pub reveal: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StoreRequest {
+    pub name: String,
+    pub value: String,
+    pub description: Option<String>,
+}
+
+/// Per-plugin connection to the daemon's vault
+pub struct VaultHost {
+    daemon: DaemonClient,
+}
+
+impl VaultHost {
+    pub fn new(policy: VaultPolicy) -> anyhow::Result<Self> {
+        let token = policy.resolve_token()?;
+        let timeout = Duration::from_secs(policy.timeout_secs);
+        Ok(Self {
+            daemon: DaemonClient::new("vault", &policy.url, token, timeout)?,
+        })
+    }
+
+    fn list(&self) -> Result<Value, DaemonFailure> {
+        let secrets = self.daemon.call(Method::GET, "/secrets", None)?;
+        Ok(json!({ "ok": true, "secrets": secrets }))
+    }
+
+    fn get(&self, request: GetRequest) -> Result<Value, DaemonFailure> {
+        let path = format!(
+            "/secrets/{}?reveal={}",
+            request.name.trim_matches('/'),
+            request.reveal
+        );
+        let secret = self.daemon.call(Method::GET, &path, None)?;
+        Ok(json!({ "ok": true, "secret": secret }))
+    }
+
+    fn store(&self, request: StoreRequest) -> Result<Value, DaemonFailure> {
+        let path = format!("/secrets/{}", request.name.trim_matches('/'));
+        let body = json!({ "value": request.value, "description": request.description });
+        let secret = self.daemon.call(Method::PUT, &path, Some(body))?;
+        Ok(json!({ "ok": true, "secret": secret }))
+    }
+}
+
+host_fn!(vault_list(host: VaultHost; _req: Json<Value>) -> Json<Value> {
+    let host = host.get()?;
+    let host = host.lock().map_err(|_| extism::Error::msg("vault host poisoned"))?;
+    Ok(Json(reply(host.list())?))
+});
+
+host_fn!(vault_get(host: VaultHost; req: Json<GetRequest>) -> Json<Value> {
+    let host = host.get()?;
+    let host = host.lock().map_err(|_| extism::Error::msg("vault host poisoned"))?;
+    Ok(Json(reply(host.get(req.0))?))
+});
+
+host_fn!(vault_store(host: VaultHost; req: Json<StoreRequest>) -> Json<Value> {
+    let host = host.get()?;
+    let host = host.lock().map_err(|_| extism::Error::msg("vault host poisoned"))?;
+    Ok(Json(reply(host.store(req.0))?))
+});
+
+/// Build the vault host functions for a plugin governed by `policy`
+pub fn host_functions(policy: VaultPolicy) -> anyhow::Result<Vec<Function>> {
+    let data = UserData::new(VaultHost::new(policy)?);
+    Ok(vec![
+        Function::new("vault_list", [PTR], [PTR], data.clone(), vault_list),
+        Function::new("vault_get", [PTR], [PTR], data.clone(), vault_get),
+        Function::new("vault_store", [PTR], [PTR], data, vault_store),
+    ])
+}
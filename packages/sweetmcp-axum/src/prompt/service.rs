@@ -61,13 +61,24 @@ pub fn prompts_get_pending(
         };
 
         let template_content: String = {
-            // Lock-free access to plugin using DashMap
-            let mut plugin_entry = match plugin_manager.plugins.get_mut(&plugin_name) {
-                Some(entry) => entry,
-                None => {
+            // Borrow a warm instance from the plugin's pool
+            let pool = plugin_manager
+                .plugins
+                .get(&plugin_name)
+                .map(|p| p.value().clone());
+            let Some(pool) = pool else {
+                let _ = tx.send(Err(HandlerError::new(format!(
+                    "Internal error: Plugin '{}' not found",
+                    plugin_name
+                ))));
+                return;
+            };
+            let mut plugin_entry = match pool.checkout().await {
+                Ok(plugin) => plugin,
+                Err(e) => {
                     let _ = tx.send(Err(HandlerError::new(format!(
-                        "Internal error: Plugin '{}' not found",
-                        plugin_name
+                        "Plugin '{}' has no usable instance: {}",
+                        plugin_name, e
                     ))));
                     return;
                 }
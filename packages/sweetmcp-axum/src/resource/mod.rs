@@ -1,10 +1,14 @@
 pub mod cms;
+pub mod plugin;
 
 // Re-export public interface
 pub use cms::{
     cms_dao::{find_by_slug, find_by_tags, init_cms_dao, resource_read},
     resources_list_handler,
 };
+pub use plugin::{
+    resource_read_handler, resource_templates_list_handler, resources_list_handler_with_plugins,
+};
 
 // Define a wrapper function with the proper type for the router
 pub async fn resources_list(
@@ -0,0 +1,144 @@
+//! Resources exposed by WASM plugins through their `list_resources` and
+//! `read_resource` exports, served alongside the CMS resources.
+
+use std::collections::HashMap;
+
+use extism::convert::Json;
+use rpc_router::{HandlerError, HandlerResult};
+use serde_json::json;
+use tokio::sync::oneshot;
+
+use crate::plugin::PluginManager;
+use crate::resource::cms::resources_list_handler;
+use crate::resource::resource_read;
+use crate::types::{
+    ListResourceTemplatesResult, ListResourcesRequest, ListResourcesResult, ReadResourceRequest,
+    ReadResourceResult,
+};
+
+/// Router-compatible async handler for resources/list
+pub async fn resources_list_handler_with_plugins(
+    pm: PluginManager,
+    request: Option<ListResourcesRequest>,
+) -> HandlerResult<ListResourcesResult> {
+    let mut resources = resources_list_handler(request).await?;
+    resources.extend(pm.resource_info.iter().map(|entry| entry.value().1.clone()));
+    Ok(ListResourcesResult {
+        resources,
+        next_cursor: None, // No pagination for now
+    })
+}
+
+/// Router-compatible async handler for resources/templates/list
+pub async fn resource_templates_list_handler(
+    pm: PluginManager,
+    _request: Option<ListResourcesRequest>,
+) -> HandlerResult<ListResourceTemplatesResult> {
+    Ok(ListResourceTemplatesResult {
+        resource_templates: pm
+            .resource_templates
+            .iter()
+            .map(|entry| entry.value().1.clone())
+            .collect(),
+        next_cursor: None,
+    })
+}
+
+/// Router-compatible async handler for resources/read
+///
+/// URIs registered by a plugin, exactly or through a template, are read from
+/// that plugin; everything else goes to the CMS.
+pub async fn resource_read_handler(
+    pm: PluginManager,
+    request: ReadResourceRequest,
+) -> HandlerResult<ReadResourceResult> {
+    let uri = request.uri.to_string();
+    let Some(plugin_name) = plugin_for_uri(&pm, &uri) else {
+        return resource_read(request).await;
+    };
+
+    let (tx, rx) = oneshot::channel();
+    tokio::spawn(async move {
+        let pool = pm.plugins.get(&plugin_name).map(|p| p.value().clone());
+        let plugin = match pool {
+            Some(pool) => Some(pool.checkout().await),
+            None => None,
+        };
+        let result = match plugin {
+            Some(Err(e)) => Err(HandlerError::new(format!(
+                "Plugin '{}' failed to read resource '{}': {}",
+                plugin_name, uri, e
+            ))),
+            Some(Ok(mut plugin)) => plugin
+                .call::<Json<serde_json::Value>, Json<ReadResourceResult>>(
+                    "read_resource",
+                    Json(json!({ "uri": uri })),
+                )
+                .map(|Json(result)| result)
+                .map_err(|e| {
+                    log::error!(
+                        "Plugin '{}' failed to read resource '{}': {}",
+                        plugin_name,
+                        uri,
+                        e
+                    );
+                    HandlerError::new(format!(
+                        "Plugin '{}' failed to read resource '{}': {}",
+                        plugin_name, uri, e
+                    ))
+                }),
+            None => Err(HandlerError::new(format!(
+                "Internal error: Plugin '{}' not found",
+                plugin_name
+            ))),
+        };
+        let _ = tx.send(result);
+    });
+
+    rx.await
+        .unwrap_or_else(|_| Err(HandlerError::new("resource read task dropped")))
+}
+
+/// Plugin serving `uri`: exact registrations win over templates
+fn plugin_for_uri(pm: &PluginManager, uri: &str) -> Option<String> {
+    if let Some(entry) = pm.resource_info.get(uri) {
+        return Some(entry.value().0.clone());
+    }
+    pm.resource_templates
+        .iter()
+        .find(|entry| match_uri_template(entry.key(), uri).is_some())
+        .map(|entry| entry.value().0.clone())
+}
+
+/// Match `uri` against a `{name}` template, returning the placeholder values.
+/// Mirrors the matcher in sweetmcp-plugin-builder so host routing agrees with
+/// what the plugin will accept.
+fn match_uri_template(template: &str, uri: &str) -> Option<HashMap<String, String>> {
+    let mut params = HashMap::new();
+    let mut rest_template = template;
+    let mut rest_uri = uri;
+
+    while let Some(open) = rest_template.find('{') {
+        rest_uri = rest_uri.strip_prefix(&rest_template[..open])?;
+        let close = open + rest_template[open..].find('}')?;
+        let name = &rest_template[open + 1..close];
+        rest_template = &rest_template[close + 1..];
+
+        let next_literal = &rest_template[..rest_template.find('{').unwrap_or(rest_template.len())];
+        let end = if next_literal.is_empty() {
+            if !rest_template.is_empty() {
+                return None;
+            }
+            rest_uri.len()
+        } else {
+            rest_uri.find(next_literal)?
+        };
+        if end == 0 {
+            return None;
+        }
+        params.insert(name.to_string(), rest_uri[..end].to_string());
+        rest_uri = &rest_uri[end..];
+    }
+
+    (rest_template == rest_uri).then_some(params)
+}
@@ -1,23 +1,25 @@
 use std::{fs, os::unix::fs::PermissionsExt, sync::Arc};
 
 use anyhow::{Context, Result};
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use rpc_router::{HandlerResult, Request, Router as RpcRouter, RouterBuilder};
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 use tokio::{
-    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
     net::{TcpListener, UnixListener, UnixStream},
 };
 
 // Only import what's actually used
-use crate::resource::resource_read;
 use crate::{
     JSONRPC_VERSION, PROTOCOL_VERSION, SERVER_NAME, SERVER_VERSION,
     config::Config,
+    framing,
     plugin::manager::PluginManager,
     prompt,
-    resource::cms::resources_list_handler,
+    resource::{
+        resource_read_handler, resource_templates_list_handler, resources_list_handler_with_plugins,
+    },
     sampling::sampling_create_message,
     tool,
     tool::notifications::{notifications_cancelled, notifications_initialized},
@@ -37,8 +39,9 @@ fn build_rpc_router(plugin_manager: PluginManager) -> RpcRouter {
         .append("logging/setLevel", logging_set_level)
         .append("roots/list", roots_list)
         // Resource handlers
-        .append("resources/list", resources_list_handler)
-        .append("resources/read", resource_read)
+        .append("resources/list", resources_list_handler_with_plugins)
+        .append("resources/templates/list", resource_templates_list_handler)
+        .append("resources/read", resource_read_handler)
         // TODO: Add when handlers are implemented
         // .append("resources/subscribe", resource_subscribe_handler)
         // .append("resources/unsubscribe", resource_unsubscribe_handler)
@@ -98,11 +101,14 @@ pub async fn run_server(
         let memory_config = sweetmcp_memory::MemoryConfig {
             database: sweetmcp_memory::utils::config::DatabaseConfig {
                 db_type: sweetmcp_memory::utils::config::DatabaseType::SurrealDB,
-                connection_string: db_config
-                    .path
-                    .clone()
-                    .map(|p| format!("surrealkv://{}", p))
-                    .unwrap_or_else(|| "surrealkv://./data/mcp_memory.db".to_string()),
+                connection_string: match db_config.engine {
+                    crate::db::StorageEngine::Memory => "mem://".to_string(),
+                    _ => db_config
+                        .path
+                        .clone()
+                        .map(|p| format!("surrealkv://{}", p))
+                        .unwrap_or_else(|| "surrealkv://./data/mcp_memory.db".to_string()),
+                },
                 namespace: db_config
                     .namespace
                     .clone()
@@ -166,6 +172,28 @@ pub async fn run_server(
         }
     }
 
+    // Mask secrets in logged tool-call arguments
+    crate::security::install_redactor(
+        crate::security::Redactor::new(&config.redaction).context("Invalid redaction rules")?,
+    );
+
+    // Per-tool redaction, conversion and summarizing of results
+    crate::tool::postprocess::install(&config.postprocess).context("Invalid postprocess rules")?;
+
+    if let Some(ms) = config.call_timeout_ms {
+        crate::plugin::deadline::set_server_limit(std::time::Duration::from_millis(ms));
+    }
+
+    // Large tool arguments may be uploaded ahead of the call
+    if let Some(uploads) = &config.uploads {
+        crate::upload::start(uploads)
+            .await
+            .context("Failed to start the upload listener")?;
+    }
+
+    // Connect upstream MCP servers so their tools are listed with the plugins'
+    crate::upstream::load_upstreams(&plugin_manager, &config.upstreams).await;
+
     if serve_args.daemon {
         run_daemon(plugin_manager, serve_args).await
     } else {
@@ -178,7 +206,19 @@ async fn run_stdio_server(plugin_manager: PluginManager) -> Result<()> {
     info!("Starting MCP JSON-RPC server (stdin/stdout mode)");
 
     // Build RPC router with lock-free plugin manager
-    let rpc_router = build_rpc_router(plugin_manager);
+    let rpc_router = Arc::new(build_rpc_router(plugin_manager));
+
+    // Notifications raised while a request is in flight, such as plugin progress
+    let (notification_tx, mut notification_rx) = tokio::sync::mpsc::channel(64);
+    crate::notifications::init_notification_system(notification_tx).await;
+    tokio::spawn(async move {
+        while let Some(payload) = notification_rx.recv().await {
+            eprintln!(
+                "{}",
+                crate::notifications::format_notification(&payload.method, payload.params)
+            );
+        }
+    });
 
     // Process stdin lines asynchronously as JSON-RPC requests
     let stdin = tokio::io::stdin();
@@ -219,46 +259,62 @@ async fn run_stdio_server(plugin_manager: PluginManager) -> Result<()> {
 
                         let id = rpc_request.id.clone();
 
-                        match rpc_router.call(rpc_request).await {
-                            Ok(call_response) => {
-                                if !call_response.value.is_null() {
-                                    let response = JsonRpcResponse::new(id, call_response.value);
-                                    if let Ok(response_json) = serde_json::to_string(&response) {
-                                        debug!("Response: {}", response_json);
-                                        eprintln!("{}", response_json);
+                        // Requests run concurrently so a notifications/cancelled
+                        // can be read while a tool call is still in flight
+                        let rpc_router = rpc_router.clone();
+                        tokio::spawn(async move {
+                            let result = crate::plugin::sandbox::with_client(
+                                "stdio".to_string(),
+                                crate::plugin::cancellation::with_request_id(
+                                    &id,
+                                    rpc_router.call(rpc_request),
+                                ),
+                            )
+                            .await;
+                            match result {
+                                Ok(call_response) => {
+                                    if !call_response.value.is_null() {
+                                        let response =
+                                            JsonRpcResponse::new(id, call_response.value);
+                                        if let Ok(response_json) = serde_json::to_string(&response)
+                                        {
+                                            debug!("Response: {}", response_json);
+                                            eprintln!("{}", response_json);
+                                        }
                                     }
                                 }
-                            }
-                            Err(error) => match &error.error {
-                                rpc_router::Error::Handler(handler) => {
-                                    if let Some(error_value) = handler.get::<Value>() {
+                                Err(error) => match &error.error {
+                                    rpc_router::Error::Handler(handler) => {
+                                        if let Some(error_value) = handler.get::<Value>() {
+                                            let json_error = json!({
+                                                "jsonrpc": JSONRPC_VERSION,
+                                                "error": error_value,
+                                                "id": id
+                                            });
+                                            if let Ok(response) = serde_json::to_string(&json_error)
+                                            {
+                                                error!("Error: {}", response);
+                                                eprintln!("{}", response);
+                                            }
+                                        }
+                                    }
+                                    _ => {
+                                        error!("Unexpected error: {:?}", error);
                                         let json_error = json!({
                                             "jsonrpc": JSONRPC_VERSION,
-                                            "error": error_value,
+                                            "error": {
+                                                "code": -1,
+                                                "message": "Invalid JSON-RPC call"
+                                            },
                                             "id": id
                                         });
                                         if let Ok(response) = serde_json::to_string(&json_error) {
-                                            error!("Error: {}", response);
                                             eprintln!("{}", response);
                                         }
                                     }
-                                }
-                                _ => {
-                                    error!("Unexpected error: {:?}", error);
-                                    let json_error = json!({
-                                        "jsonrpc": JSONRPC_VERSION,
-                                        "error": {
-                                            "code": -1,
-                                            "message": "Invalid JSON-RPC call"
-                                        },
-                                        "id": id
-                                    });
-                                    if let Ok(response) = serde_json::to_string(&json_error) {
-                                        eprintln!("{}", response);
-                                    }
-                                }
-                            },
-                        }
+                                },
+                            }
+                        });
                     }
                 }
             }
@@ -290,7 +346,7 @@ pub async fn run_http_server(plugin_manager: PluginManager, bind_addr: &str) ->
                 let router = rpc_router.clone();
 
                 tokio::spawn(async move {
-                    if let Err(e) = handle_http_connection(stream, router).await {
+                    if let Err(e) = handle_http_connection(stream, addr, router).await {
                         error!("Failed to handle HTTP connection: {}", e);
                     }
                 });
@@ -305,6 +361,7 @@ pub async fn run_http_server(plugin_manager: PluginManager, bind_addr: &str) ->
 /// Handle a single HTTP connection
 async fn handle_http_connection(
     mut stream: tokio::net::TcpStream,
+    peer: std::net::SocketAddr,
     rpc_router: Arc<RpcRouter>,
 ) -> Result<()> {
     use tokio::io::{AsyncReadExt, AsyncWriteExt};
@@ -316,6 +373,16 @@ async fn handle_http_connection(
     // Simple HTTP parsing to extract JSON body
     if let Some(body_start) = request_data.find("\r\n\r\n") {
         let body = &request_data[body_start + 4..];
+        let client_timeout = request_data[..body_start]
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .find(|(name, _)| {
+                name.trim()
+                    .eq_ignore_ascii_case(crate::plugin::deadline::TIMEOUT_HEADER)
+            })
+            .and_then(|(_, value)| {
+                crate::plugin::deadline::parse_millis(&Value::String(value.to_string()))
+            });
 
         if !body.trim().is_empty() {
             if let Ok(json_value) = serde_json::from_str::<Value>(body) {
@@ -327,7 +394,14 @@ async fn handle_http_connection(
 
                     let id = rpc_request.id.clone();
 
-                    let (status_code, response_body) = match rpc_router.call(rpc_request).await {
+                    let call = crate::plugin::sandbox::with_client(
+                        peer.ip().to_string(),
+                        crate::plugin::deadline::with_client_timeout(
+                            client_timeout,
+                            rpc_router.call(rpc_request),
+                        ),
+                    );
+                    let (status_code, response_body) = match call.await {
                         Ok(call_response) => {
                             let response = JsonRpcResponse::new(id, call_response.value);
                             let response_json = serde_json::to_string(&response)?;
@@ -424,11 +498,13 @@ pub async fn roots_list(_request: Option<ListRootsRequest>) -> HandlerResult<Lis
     Ok(response)
 }
 
-/// Run the server as a system daemon using our sophisticated daemon manager
-async fn run_daemon(_plugin_manager: PluginManager, _serve_args: ServeArgs) -> Result<()> {
-    Err(anyhow::anyhow!(
-        "Daemon mode is not supported in axum - use the daemon to run pingora"
-    ))
+/// Serve on the daemon socket in the foreground. Detaching, the pid file and
+/// the service account are left to cyrupd, which supervises the host.
+async fn run_daemon(plugin_manager: PluginManager, serve_args: ServeArgs) -> Result<()> {
+    if serve_args.user.is_some() || serve_args.group.is_some() {
+        warn!("--user and --group are ignored; set them on the cyrupd service instead");
+    }
+    create_socket_listener(plugin_manager, &serve_args.socket_path).await
 }
 
 /// Create and run Unix domain socket listener
@@ -482,102 +558,146 @@ pub async fn create_socket_listener(
 async fn handle_socket_connection(stream: UnixStream, plugin_manager: PluginManager) -> Result<()> {
     info!("New socket connection established");
 
+    // Sandbox telemetry tells socket clients apart by their user
+    let client = match stream.peer_cred() {
+        Ok(cred) => format!("unix:uid={}", cred.uid()),
+        Err(_) => "unix".to_string(),
+    };
     let (reader, mut writer) = stream.into_split();
-    let reader = BufReader::new(reader);
-    let mut lines = reader.lines();
+    let mut reader = BufReader::new(reader);
 
     // Build RPC router with lock-free plugin manager
     let rpc_router = build_rpc_router(plugin_manager);
 
-    while let Some(line) = lines.next_line().await? {
-        debug!("Socket received: {}", line);
+    // A framed client announces itself before its first message
+    if reader.fill_buf().await?.first() == Some(&framing::PREAMBLE[0]) {
+        let mut preamble = [0u8; framing::PREAMBLE.len()];
+        reader.read_exact(&mut preamble).await?;
+        if &preamble != framing::PREAMBLE {
+            anyhow::bail!("Unknown socket framing");
+        }
+        debug!("Socket connection uses length-prefixed frames");
+
+        let mut frame = Vec::new();
+        while framing::read_frame(&mut reader, &mut frame).await? {
+            let response = match serde_json::from_slice::<Value>(&frame) {
+                Ok(message) => handle_socket_message(&rpc_router, &client, message).await,
+                Err(e) => Some(json!({
+                    "jsonrpc": JSONRPC_VERSION,
+                    "error": {
+                        "code": -32700,
+                        "message": format!("Parse error: {}", e)
+                    },
+                    "id": Value::Null
+                })),
+            };
+            let payload = match response {
+                Some(response) => serde_json::to_vec(&response)?,
+                None => Vec::new(),
+            };
+            framing::write_frame(&mut writer, &payload).await?;
+        }
+    } else {
+        let mut lines = reader.lines();
+        while let Some(line) = lines.next_line().await? {
+            debug!("Socket received: {}", line);
 
-        if !line.is_empty() {
+            if line.is_empty() {
+                continue;
+            }
             // Parse input as JSON value
-            if let Ok(json_value) = serde_json::from_str::<Value>(&line) {
-                // Handle notifications (no response required)
-                if json_value.is_object() && json_value.get("id").is_none() {
-                    if let Some(method) = json_value.get("method") {
-                        if method == "notifications/initialized" {
-                            notifications_initialized();
-                        } else if method == "notifications/cancelled" {
-                            if let Some(params_value) = json_value.get("params") {
-                                if let Ok(cancel_params) =
-                                    serde_json::from_value(params_value.clone())
-                                {
-                                    notifications_cancelled(cancel_params);
-                                }
-                            }
-                        }
-                    }
-                } else {
-                    // Process regular requests
-                    if let Ok(mut rpc_request) = Request::from_value(json_value) {
-                        // Ensure params exist for ping method
-                        if rpc_request.method == "ping" && rpc_request.params.is_none() {
-                            rpc_request.params = Some(json!({}));
-                        }
-
-                        let id = rpc_request.id.clone();
+            let Ok(json_value) = serde_json::from_str::<Value>(&line) else {
+                continue;
+            };
+            if let Some(response) = handle_socket_message(&rpc_router, &client, json_value).await {
+                if let Ok(response_json) = serde_json::to_string(&response) {
+                    debug!("Socket response: {}", response_json);
+                    writer.write_all(response_json.as_bytes()).await?;
+                    writer.write_all(b"\n").await?;
+                    writer.flush().await?;
+                }
+            }
+        }
+    }
 
-                        match rpc_router.call(rpc_request).await {
-                            Ok(call_response) => {
-                                if !call_response.value.is_null() {
-                                    let response = JsonRpcResponse::new(id, call_response.value);
-                                    if let Ok(response_json) = serde_json::to_string(&response) {
-                                        debug!("Socket response: {}", response_json);
-                                        writer.write_all(response_json.as_bytes()).await?;
-                                        writer.write_all(b"\n").await?;
-                                        writer.flush().await?;
-                                    }
-                                }
-                            }
-                            Err(error) => {
-                                let json_error = match &error.error {
-                                    rpc_router::Error::Handler(handler) => {
-                                        if let Some(error_value) = handler.get::<Value>() {
-                                            json!({
-                                                "jsonrpc": JSONRPC_VERSION,
-                                                "error": error_value,
-                                                "id": id
-                                            })
-                                        } else {
-                                            json!({
-                                                "jsonrpc": JSONRPC_VERSION,
-                                                "error": {
-                                                    "code": -1,
-                                                    "message": "Handler error"
-                                                },
-                                                "id": id
-                                            })
-                                        }
-                                    }
-                                    _ => {
-                                        json!({
-                                            "jsonrpc": JSONRPC_VERSION,
-                                            "error": {
-                                                "code": -1,
-                                                "message": "Invalid JSON-RPC call"
-                                            },
-                                            "id": id
-                                        })
-                                    }
-                                };
+    info!("Socket connection closed");
+    Ok(())
+}
 
-                                if let Ok(response) = serde_json::to_string(&json_error) {
-                                    error!("Socket error: {}", response);
-                                    writer.write_all(response.as_bytes()).await?;
-                                    writer.write_all(b"\n").await?;
-                                    writer.flush().await?;
-                                }
-                            }
-                        }
+/// Answer one JSON-RPC message from a socket client; notifications and
+/// messages that aren't requests get no response
+async fn handle_socket_message(
+    rpc_router: &RpcRouter,
+    client: &str,
+    json_value: Value,
+) -> Option<Value> {
+    // Handle notifications (no response required)
+    if json_value.is_object() && json_value.get("id").is_none() {
+        if let Some(method) = json_value.get("method") {
+            if method == "notifications/initialized" {
+                notifications_initialized();
+            } else if method == "notifications/cancelled" {
+                if let Some(params_value) = json_value.get("params") {
+                    if let Ok(cancel_params) = serde_json::from_value(params_value.clone()) {
+                        notifications_cancelled(cancel_params);
                     }
                 }
             }
         }
+        return None;
     }
 
-    info!("Socket connection closed");
-    Ok(())
+    // Process regular requests
+    let mut rpc_request = Request::from_value(json_value).ok()?;
+    // Ensure params exist for ping method
+    if rpc_request.method == "ping" && rpc_request.params.is_none() {
+        rpc_request.params = Some(json!({}));
+    }
+
+    let id = rpc_request.id.clone();
+
+    let call =
+        crate::plugin::sandbox::with_client(client.to_string(), rpc_router.call(rpc_request));
+    match call.await {
+        Ok(call_response) => {
+            if call_response.value.is_null() {
+                return None;
+            }
+            serde_json::to_value(JsonRpcResponse::new(id, call_response.value)).ok()
+        }
+        Err(error) => {
+            let json_error = match &error.error {
+                rpc_router::Error::Handler(handler) => {
+                    if let Some(error_value) = handler.get::<Value>() {
+                        json!({
+                            "jsonrpc": JSONRPC_VERSION,
+                            "error": error_value,
+                            "id": id
+                        })
+                    } else {
+                        json!({
+                            "jsonrpc": JSONRPC_VERSION,
+                            "error": {
+                                "code": -1,
+                                "message": "Handler error"
+                            },
+                            "id": id
+                        })
+                    }
+                }
+                _ => {
+                    json!({
+                        "jsonrpc": JSONRPC_VERSION,
+                        "error": {
+                            "code": -1,
+                            "message": "Invalid JSON-RPC call"
+                        },
+                        "id": id
+                    })
+                }
+            };
+            error!("Socket error: {}", json_error);
+            Some(json_error)
+        }
+    }
 }
@@ -0,0 +1,224 @@
+//! Provider failover for sampling.
+//!
+//! A [`ModelChain`] holds an ordered list of models, API-backed or local, and
+//! sends each request to the first one that is healthy. A model that is rate
+//! limited or down is put in a cooldown, honouring the provider's retry-after
+//! when it gives one and backing off exponentially otherwise, and the request
+//! moves on to the next model. Requests the provider rejected outright are
+//! returned as is, since every other model would reject them too.
+//!
+//! Install a chain with [`set_model_chain`] and `sampling/createMessage`
+//! uses it.
+
+use std::fmt;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use futures::future::BoxFuture;
+use serde::Serialize;
+
+use super::model::{CreateMessageRequest, CreateMessageResult};
+
+/// Cooldown after a first failure without a retry-after
+const BASE_COOLDOWN: Duration = Duration::from_secs(5);
+
+/// Longest cooldown, however often a model fails
+const MAX_COOLDOWN: Duration = Duration::from_secs(300);
+
+/// Why a model could not answer
+#[derive(Debug, Clone)]
+pub enum ModelError {
+    /// The provider is throttling us; try again after `retry_after` if given
+    RateLimited { retry_after: Option<Duration> },
+    /// The provider is down, unreachable or timed out
+    Unavailable(String),
+    /// The provider refused the request itself, e.g. it is malformed or too
+    /// long; other models would refuse it too
+    Rejected(String),
+}
+
+impl ModelError {
+    fn fails_over(&self) -> bool {
+        !matches!(self, ModelError::Rejected(_))
+    }
+}
+
+impl fmt::Display for ModelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModelError::RateLimited {
+                retry_after: Some(after),
+            } => write!(f, "rate limited, retry after {}s", after.as_secs()),
+            ModelError::RateLimited { retry_after: None } => write!(f, "rate limited"),
+            ModelError::Unavailable(reason) => write!(f, "unavailable: {}", reason),
+            ModelError::Rejected(reason) => write!(f, "rejected: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for ModelError {}
+
+/// A model that can answer sampling requests
+pub trait SamplingModel: Send + Sync {
+    /// Identifier used in logs and health reports, e.g. `anthropic:claude-sonnet`
+    fn name(&self) -> &str;
+
+    fn create_message<'a>(
+        &'a self,
+        request: &'a CreateMessageRequest,
+    ) -> BoxFuture<'a, Result<CreateMessageResult, ModelError>>;
+}
+
+#[derive(Debug, Default)]
+struct Health {
+    consecutive_failures: u32,
+    cooldown_until: Option<Instant>,
+    last_error: Option<String>,
+}
+
+struct Member {
+    model: Arc<dyn SamplingModel>,
+    health: Mutex<Health>,
+}
+
+impl Member {
+    fn health(&self) -> std::sync::MutexGuard<'_, Health> {
+        self.health
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn cooling_until(&self, now: Instant) -> Option<Instant> {
+        self.health().cooldown_until.filter(|until| *until > now)
+    }
+
+    fn record_success(&self) {
+        *self.health() = Health::default();
+    }
+
+    fn record_failure(&self, error: &ModelError) {
+        let mut health = self.health();
+        health.consecutive_failures += 1;
+        health.last_error = Some(error.to_string());
+        let backoff = BASE_COOLDOWN
+            .saturating_mul(1 << (health.consecutive_failures - 1).min(16))
+            .min(MAX_COOLDOWN);
+        let cooldown = match error {
+            ModelError::RateLimited {
+                retry_after: Some(after),
+            } => (*after).min(MAX_COOLDOWN),
+            _ => backoff,
+        };
+        health.cooldown_until = Some(Instant::now() + cooldown);
+    }
+}
+
+/// Health of one chain member, for status pages
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelHealth {
+    pub name: String,
+    pub healthy: bool,
+    pub consecutive_failures: u32,
+    /// Seconds until the model is tried again, while cooling down
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cooldown_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+/// Ordered models with health tracking and automatic failover
+pub struct ModelChain {
+    members: Vec<Member>,
+}
+
+impl ModelChain {
+    /// Chain trying `models` in order
+    pub fn new(models: Vec<Arc<dyn SamplingModel>>) -> Self {
+        Self {
+            members: models
+                .into_iter()
+                .map(|model| Member {
+                    model,
+                    health: Mutex::new(Health::default()),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// Send `request` to the first healthy model, failing over on rate limits
+    /// and outages. When every model is cooling down, the one that recovers
+    /// soonest is tried anyway rather than failing without an attempt.
+    pub async fn create_message(
+        &self,
+        request: &CreateMessageRequest,
+    ) -> Result<CreateMessageResult, ModelError> {
+        let now = Instant::now();
+        let mut order: Vec<&Member> = self
+            .members
+            .iter()
+            .filter(|member| member.cooling_until(now).is_none())
+            .collect();
+        if order.is_empty() {
+            order.extend(
+                self.members
+                    .iter()
+                    .min_by_key(|member| member.cooling_until(now)),
+            );
+        }
+
+        let mut last_error = ModelError::Unavailable("no models configured".to_string());
+        for member in order {
+            match member.model.create_message(request).await {
+                Ok(result) => {
+                    member.record_success();
+                    return Ok(result);
+                }
+                // The request itself is at fault, not the model
+                Err(error) if !error.fails_over() => return Err(error),
+                Err(error) => {
+                    log::warn!("Sampling model {} failed: {}", member.model.name(), error);
+                    member.record_failure(&error);
+                    last_error = error;
+                }
+            }
+        }
+        Err(last_error)
+    }
+
+    /// Current health of every model, in chain order
+    pub fn health(&self) -> Vec<ModelHealth> {
+        let now = Instant::now();
+        self.members
+            .iter()
+            .map(|member| {
+                let cooling = member.cooling_until(now);
+                let health = member.health();
+                ModelHealth {
+                    name: member.model.name().to_string(),
+                    healthy: cooling.is_none(),
+                    consecutive_failures: health.consecutive_failures,
+                    cooldown_secs: cooling.map(|until| (until - now).as_secs()),
+                    last_error: health.last_error.clone(),
+                }
+            })
+            .collect()
+    }
+}
+
+static MODEL_CHAIN: OnceLock<Arc<ModelChain>> = OnceLock::new();
+
+/// Serve `sampling/createMessage` from `chain`
+pub fn set_model_chain(chain: ModelChain) {
+    if MODEL_CHAIN.set(Arc::new(chain)).is_err() {
+        log::warn!("Sampling model chain already set; ignoring new one");
+    }
+}
+
+/// Chain installed with [`set_model_chain`], if any
+pub fn model_chain() -> Option<Arc<ModelChain>> {
+    MODEL_CHAIN.get().cloned()
+}
@@ -1,9 +1,11 @@
+pub mod chain;
 pub mod chat;
 pub mod model;
 pub mod notifications;
 pub mod service;
 
 // Re-export only what's actually used in the project
+pub use chain::{ModelChain, ModelError, ModelHealth, SamplingModel, model_chain, set_model_chain};
 pub use model::{CompletionUsage, CreateMessageRequest, CreateMessageResult, McpMessage};
 pub use notifications::{SamplingProgressNotification, SamplingTokenNotification};
 pub use service::sampling_create_message;
@@ -7,6 +7,7 @@ use std::env;
 use tokio::sync::{mpsc, oneshot};
 // use fluent_ai::{FluentAi, Providers, Models}; // Temporarily disabled due to dependency issues
 
+use super::chain::model_chain;
 use super::model::*;
 // use crate::auth::JwtAuth; // Auth module not available
 use crate::sampling::notifications::SamplingProgressNotification;
@@ -121,55 +122,64 @@ pub fn sampling_create_message_pending(request: CreateMessageRequest) -> AsyncSa
                     report_sampling_progress(&tx_progress, meta.progress_token.clone(), 0, 150);
                 }
 
-                // Use fluent-ai to generate actual response
-                let (provider, model) = match select_llm_model(&request.model_preferences).await {
-                    Ok((provider, model)) => (provider, model),
-                    Err(e) => {
-                        error!("Failed to select LLM model: {}", e);
-                        return {
-                            let _ = tx_result.send(Err(rpc_router::HandlerError::new(
-                                "Failed to select LLM model",
-                            )));
-                            ()
-                        };
-                    }
-                };
+                // A configured model chain answers with failover between providers
+                if let Some(chain) = model_chain() {
+                    chain.create_message(&request).await.map_err(|e| {
+                        error!("Sampling failed on every model: {}", e);
+                        rpc_router::HandlerError::new(format!("Sampling failed: {}", e))
+                    })
+                } else {
+                    // Use fluent-ai to generate actual response
+                    let (provider, model) = match select_llm_model(&request.model_preferences).await
+                    {
+                        Ok((provider, model)) => (provider, model),
+                        Err(e) => {
+                            error!("Failed to select LLM model: {}", e);
+                            return {
+                                let _ = tx_result.send(Err(rpc_router::HandlerError::new(
+                                    "Failed to select LLM model",
+                                )));
+                                ()
+                            };
+                        }
+                    };
 
-                // For now, create a simple response since the full API isn't available yet
-                // TODO: Replace with actual fluent-ai completion when API is ready
+                    // For now, create a simple response since the full API isn't available yet
+                    // TODO: Replace with actual fluent-ai completion when API is ready
 
-                // Use SmallVec for zero-allocation response building for typical response sizes
-                let mut response_parts: SmallVec<[&str; 8]> = SmallVec::new();
-                response_parts.push("Echo (fluent-ai ");
-                response_parts.push(&provider);
-                response_parts.push(":");
-                response_parts.push(&model);
-                response_parts.push("): ");
-                response_parts.push(prompt_text);
+                    // Use SmallVec for zero-allocation response building for typical response sizes
+                    let mut response_parts: SmallVec<[&str; 8]> = SmallVec::new();
+                    response_parts.push("Echo (fluent-ai ");
+                    response_parts.push(&provider);
+                    response_parts.push(":");
+                    response_parts.push(&model);
+                    response_parts.push("): ");
+                    response_parts.push(prompt_text);
 
-                let response_text = response_parts.join("");
-                let model_name = model.clone();
+                    let response_text = response_parts.join("");
+                    let model_name = model.clone();
 
-                // Create the result
-                let result = CreateMessageResult {
-                    role: "assistant".to_string(),
-                    content: McpMessageContent {
-                        type_: "text".to_string(),
-                        text: Some(response_text),
-                        data: None,
-                        mime_type: None,
-                    },
-                    model: model_name,
-                    stop_reason: Some("endTurn".to_string()),
-                    usage: Some(CompletionUsage {
-                        completion_tokens: 150, // Estimate - could be improved with actual token counting
-                        prompt_tokens: prompt_text.len() as u32 / 4, // Rough estimate
-                        total_tokens: 150 + (prompt_text.len() as u32 / 4),
-                    }),
-                };
+                    // Create the result
+                    let result = CreateMessageResult {
+                        role: "assistant".to_string(),
+                        content: McpMessageContent {
+                            type_: "text".to_string(),
+                            text: Some(response_text),
+                            data: None,
+                            mime_type: None,
+                        },
+                        model: model_name,
+                        stop_reason: Some("endTurn".to_string()),
+                        usage: Some(CompletionUsage {
+                            completion_tokens: 150, // Estimate - could be improved with actual token counting
+                            prompt_tokens: prompt_text.len() as u32 / 4, // Rough estimate
+                            total_tokens: 150 + (prompt_text.len() as u32 / 4),
+                        }),
+                    };
 
-                log::info!("Returning sampling result: {:?}", result);
-                Ok(result)
+                    log::info!("Returning sampling result: {:?}", result);
+                    Ok(result)
+                }
             }
             Err(e) => Err(e),
         };
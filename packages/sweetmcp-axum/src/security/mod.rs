@@ -7,10 +7,13 @@
 //! - Comprehensive input sanitization for all external inputs
 //! - Integration with existing security audit systems
 //! - Real-time validation metrics and monitoring
+//! - Redaction of secrets and PII in logged tool-call arguments
 
 pub mod memory_safety;
+pub mod redaction;
 pub mod validation;
 
 // Re-export all security types for convenience
 pub use memory_safety::*;
+pub use redaction::{Redactor, install_redactor, redact_arguments};
 pub use validation::*;
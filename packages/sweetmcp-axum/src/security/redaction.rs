@@ -0,0 +1,153 @@
+//! Redaction of tool-call arguments before they are logged
+//!
+//! Arguments keep their shape: object keys, array lengths and non-sensitive
+//! values are logged as-is. Values under sensitive field names become
+//! `"[REDACTED]"`, and substrings of any string that match a redaction
+//! pattern are replaced in place.
+
+use std::collections::HashSet;
+
+use once_cell::sync::OnceCell;
+use regex::Regex;
+use serde_json::Value;
+
+use crate::config::RedactionConfig;
+
+/// Replacement for redacted values and substrings
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Field names redacted by default. Matched case-insensitively, ignoring `_`
+/// and `-`, against the whole key or its suffix (`db_password`, `githubToken`).
+const DEFAULT_FIELDS: &[&str] = &[
+    "password",
+    "passwd",
+    "passphrase",
+    "secret",
+    "token",
+    "apikey",
+    "authorization",
+    "cookie",
+    "privatekey",
+    "credentials",
+    "ssn",
+    "creditcard",
+    "cardnumber",
+    "cvv",
+];
+
+/// Value patterns redacted by default
+const DEFAULT_PATTERNS: &[&str] = &[
+    // Bearer / basic credentials
+    r"(?i)\b(?:bearer|basic)\s+[A-Za-z0-9._~+/=-]{8,}",
+    // JSON Web Tokens
+    r"\beyJ[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+",
+    // AWS access key ids
+    r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b",
+    // PEM private keys
+    r"-----BEGIN [A-Z ]*PRIVATE KEY-----[\s\S]*?-----END [A-Z ]*PRIVATE KEY-----",
+    // Email addresses
+    r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}",
+    // Payment card numbers
+    r"\b[3-6]\d{3}(?:[ -]?\d{4}){3}\b",
+];
+
+static REDACTOR: OnceCell<Redactor> = OnceCell::new();
+
+/// Compiled redaction rules
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    fields: HashSet<String>,
+    patterns: Vec<Regex>,
+}
+
+impl Redactor {
+    /// Compile `config`; fails on an invalid pattern.
+    pub fn new(config: &RedactionConfig) -> anyhow::Result<Self> {
+        let defaults = config.use_defaults;
+        let fields = DEFAULT_FIELDS
+            .iter()
+            .filter(|_| defaults)
+            .map(|f| f.to_string())
+            .chain(config.fields.iter().map(|f| normalize_key(f)))
+            .collect();
+        let patterns = DEFAULT_PATTERNS
+            .iter()
+            .filter(|_| defaults)
+            .copied()
+            .chain(config.patterns.iter().map(String::as_str))
+            .map(|p| {
+                Regex::new(p)
+                    .map_err(|e| anyhow::anyhow!("invalid redaction pattern {:?}: {}", p, e))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self { fields, patterns })
+    }
+
+    /// A copy of `value` with sensitive fields and substrings replaced
+    pub fn redact(&self, value: &Value) -> Value {
+        match value {
+            Value::Object(map) => Value::Object(
+                map.iter()
+                    .map(|(key, value)| {
+                        let value = if self.is_sensitive(key) {
+                            Value::String(REDACTED.to_string())
+                        } else {
+                            self.redact(value)
+                        };
+                        (key.clone(), value)
+                    })
+                    .collect(),
+            ),
+            Value::Array(items) => Value::Array(items.iter().map(|v| self.redact(v)).collect()),
+            Value::String(s) => Value::String(self.redact_str(s)),
+            other => other.clone(),
+        }
+    }
+
+    /// `s` with every pattern match replaced
+    pub fn redact_str(&self, s: &str) -> String {
+        let mut out = s.to_string();
+        for pattern in &self.patterns {
+            if pattern.is_match(&out) {
+                out = pattern.replace_all(&out, REDACTED).into_owned();
+            }
+        }
+        out
+    }
+
+    fn is_sensitive(&self, key: &str) -> bool {
+        let key = normalize_key(key);
+        self.fields.iter().any(|f| key.ends_with(f.as_str()))
+    }
+}
+
+impl Default for Redactor {
+    fn default() -> Self {
+        Self::new(&RedactionConfig::default()).expect("default redaction patterns compile")
+    }
+}
+
+fn normalize_key(key: &str) -> String {
+    key.chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Install the process-wide redactor; later calls are ignored.
+pub fn install_redactor(redactor: Redactor) {
+    if REDACTOR.set(redactor).is_err() {
+        log::warn!("Redaction rules already installed; ignoring new ones");
+    }
+}
+
+/// Tool-call arguments rendered for a log line
+pub fn redact_arguments(arguments: Option<&Value>) -> String {
+    match arguments {
+        Some(arguments) => REDACTOR
+            .get_or_init(Redactor::default)
+            .redact(arguments)
+            .to_string(),
+        None => "{}".to_string(),
+    }
+}
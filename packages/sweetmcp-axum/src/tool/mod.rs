@@ -1,5 +1,6 @@
 pub mod model;
 pub mod notifications;
+pub mod postprocess;
 pub mod service;
 
 pub use service::{tools_call_handler, tools_list_handler};
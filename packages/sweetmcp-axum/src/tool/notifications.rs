@@ -20,5 +20,10 @@ pub fn notifications_initialized() {
 
 /// Handler for notifications/cancelled notification
 pub fn notifications_cancelled(params: CancelledNotification) {
-    info!("Request cancelled: id={}", params.request_id);
+    let request_id = crate::plugin::cancellation::request_key(&params.request_id);
+    if crate::plugin::cancellation::cancel(&request_id) {
+        info!("Request cancelled: id={}", request_id);
+    } else {
+        info!("Request cancelled: id={} (not running)", request_id);
+    }
 }
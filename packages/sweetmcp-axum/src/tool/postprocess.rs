@@ -0,0 +1,243 @@
+//! Post-processing of tool results before they return to the client.
+//!
+//! Each tool may have a pipeline of steps from the `postprocess` config,
+//! applied in order to the text of its result: text content and text
+//! resources. Steps under `*` run for every tool, ahead of the tool's own.
+//! Images and other binary content pass through untouched. A step that fails
+//! leaves the text as it was, so a broken summarizer never costs the client
+//! the result itself.
+
+use std::collections::HashMap;
+
+use once_cell::sync::OnceCell;
+
+use crate::config::{PostProcessor, RedactionConfig};
+use crate::sampling::model::{CreateMessageRequest, McpMessage, McpMessageContent};
+use crate::sampling::model_chain;
+use crate::security::Redactor;
+use crate::types::{CallToolResult, CallToolResultContent};
+
+/// Tool name whose pipeline runs for every tool
+const ALL_TOOLS: &str = "*";
+
+/// Characters per token when estimating the length of text
+const CHARS_PER_TOKEN: usize = 4;
+
+const DEFAULT_SUMMARY_PROMPT: &str = "Summarize the following tool output for an AI agent. \
+Keep every fact, figure, name, identifier and link the agent may need, drop repetition \
+and boilerplate, and answer with the summary only.";
+
+static PIPELINES: OnceCell<HashMap<String, Vec<Step>>> = OnceCell::new();
+
+/// A compiled [`PostProcessor`]
+enum Step {
+    Redact(Redactor),
+    HtmlToMarkdown,
+    Summarize {
+        over_tokens: usize,
+        max_tokens: u32,
+        prompt: String,
+    },
+}
+
+impl Step {
+    fn new(processor: &PostProcessor) -> anyhow::Result<Self> {
+        Ok(match processor {
+            PostProcessor::Redact {
+                use_defaults,
+                patterns,
+            } => Step::Redact(Redactor::new(&RedactionConfig {
+                use_defaults: *use_defaults,
+                fields: Vec::new(),
+                patterns: patterns.clone(),
+            })?),
+            PostProcessor::HtmlToMarkdown => Step::HtmlToMarkdown,
+            PostProcessor::Summarize {
+                over_tokens,
+                max_tokens,
+                prompt,
+            } => {
+                if *max_tokens == 0 {
+                    anyhow::bail!("summarize max_tokens must be positive");
+                }
+                Step::Summarize {
+                    over_tokens: *over_tokens,
+                    max_tokens: *max_tokens,
+                    prompt: prompt
+                        .clone()
+                        .unwrap_or_else(|| DEFAULT_SUMMARY_PROMPT.to_string()),
+                }
+            }
+        })
+    }
+
+    /// `text` after this step; `None` leaves it unchanged
+    async fn apply(&self, tool: &str, text: &str, html: bool) -> Option<String> {
+        match self {
+            Step::Redact(redactor) => Some(redactor.redact_str(text)),
+            Step::HtmlToMarkdown if html || looks_like_html(text) => {
+                let converter = htmd::HtmlToMarkdown::builder()
+                    .skip_tags(vec!["script", "style"])
+                    .build();
+                match converter.convert(text) {
+                    Ok(markdown) => Some(markdown),
+                    Err(e) => {
+                        log::warn!(
+                            "Could not convert result of tool {} to markdown: {}",
+                            tool,
+                            e
+                        );
+                        None
+                    }
+                }
+            }
+            Step::HtmlToMarkdown => None,
+            Step::Summarize {
+                over_tokens,
+                max_tokens,
+                prompt,
+            } => {
+                let tokens = text.len() / CHARS_PER_TOKEN;
+                if tokens <= *over_tokens {
+                    return None;
+                }
+                summarize(tool, text, tokens, *max_tokens, prompt).await
+            }
+        }
+    }
+}
+
+/// Compile and install the pipelines of `config`; later calls are ignored.
+pub fn install(config: &HashMap<String, Vec<PostProcessor>>) -> anyhow::Result<()> {
+    let mut pipelines = HashMap::new();
+    for (tool, processors) in config {
+        let steps = processors
+            .iter()
+            .map(Step::new)
+            .collect::<anyhow::Result<Vec<_>>>()
+            .map_err(|e| anyhow::anyhow!("postprocess of {}: {:#}", tool, e))?;
+        if !steps.is_empty() {
+            pipelines.insert(tool.clone(), steps);
+        }
+    }
+    if PIPELINES.set(pipelines).is_err() {
+        log::warn!("Result post-processors already installed; ignoring new ones");
+    }
+    Ok(())
+}
+
+/// `result` of `tool` after its pipeline
+pub async fn apply(tool: &str, mut result: CallToolResult) -> CallToolResult {
+    let Some(pipelines) = PIPELINES.get() else {
+        return result;
+    };
+    let steps: Vec<&Step> = [ALL_TOOLS, tool]
+        .iter()
+        .filter_map(|name| pipelines.get(*name))
+        .flatten()
+        .collect();
+    if steps.is_empty() {
+        return result;
+    }
+
+    for content in &mut result.content {
+        match content {
+            CallToolResultContent::Text { text, .. } => {
+                for step in &steps {
+                    if let Some(processed) = step.apply(tool, text, false).await {
+                        *text = processed;
+                    }
+                }
+            }
+            CallToolResultContent::Resource { resource } => {
+                let html = resource
+                    .mime_type
+                    .as_deref()
+                    .is_some_and(|mime| mime.starts_with("text/html"));
+                if let Some(text) = resource.text.as_mut() {
+                    let mut converted = false;
+                    for step in &steps {
+                        if let Some(processed) = step.apply(tool, text, html && !converted).await {
+                            converted |= matches!(step, Step::HtmlToMarkdown);
+                            *text = processed;
+                        }
+                    }
+                    if html && converted {
+                        resource.mime_type = Some("text/markdown".to_string());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    result
+}
+
+/// Whether untyped text is an HTML document or fragment
+fn looks_like_html(text: &str) -> bool {
+    let start: String = text
+        .trim_start()
+        .chars()
+        .take(64)
+        .flat_map(char::to_lowercase)
+        .collect();
+    start.starts_with("<!doctype html")
+        || start.starts_with("<html")
+        || (start.starts_with('<') && text.contains("</"))
+}
+
+async fn summarize(
+    tool: &str,
+    text: &str,
+    tokens: usize,
+    max_tokens: u32,
+    prompt: &str,
+) -> Option<String> {
+    let Some(chain) = model_chain() else {
+        log::warn!(
+            "Result of tool {} is ~{} tokens but no sampling model chain is configured to summarize it",
+            tool,
+            tokens
+        );
+        return None;
+    };
+    let request = CreateMessageRequest {
+        messages: vec![McpMessage {
+            role: "user".to_string(),
+            content: McpMessageContent {
+                type_: "text".to_string(),
+                text: Some(text.to_string()),
+                data: None,
+                mime_type: None,
+            },
+        }],
+        system_prompt: Some(prompt.to_string()),
+        model_preferences: None,
+        include_context: None,
+        max_tokens: Some(max_tokens),
+        temperature: None,
+        stop_sequences: None,
+        metadata: None,
+        meta: None,
+    };
+    match chain.create_message(&request).await {
+        Ok(summary) => match summary.content.text {
+            Some(summary) if !summary.trim().is_empty() => {
+                log::debug!("Summarized ~{} token result of tool {}", tokens, tool);
+                Some(format!(
+                    "[Summary of a ~{} token result]\n\n{}",
+                    tokens,
+                    summary.trim()
+                ))
+            }
+            _ => {
+                log::warn!("Summary of tool {}'s result came back empty", tool);
+                None
+            }
+        },
+        Err(e) => {
+            log::warn!("Could not summarize result of tool {}: {}", tool, e);
+            None
+        }
+    }
+}
@@ -5,6 +5,8 @@ use tokio::sync::{mpsc, oneshot};
 use super::{super::types::*, model::*};
 // Removed unused db imports
 use crate::plugin::PluginManager; // Updated path
+use crate::plugin::{cancellation, deadline, sandbox};
+use crate::security::redact_arguments;
 
 // Stream-based tools_list
 pub fn tools_list_stream(
@@ -17,9 +19,19 @@ pub fn tools_list_stream(
         // Lock-free operations using DashMap
         pm.tool_to_plugin.clear();
 
-        for mut plugin_entry in pm.plugins.iter_mut() {
-            let plugin_name = plugin_entry.key().clone();
-            let plugin = plugin_entry.value_mut();
+        let pools: Vec<_> = pm
+            .plugins
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect();
+        for (plugin_name, pool) in pools {
+            let mut plugin = match pool.checkout().await {
+                Ok(plugin) => plugin,
+                Err(e) => {
+                    log::error!("tool {} describe() error: {}", plugin_name, e);
+                    continue;
+                }
+            };
             match plugin.call::<&str, &str>("describe", "") {
                 Ok(result) => {
                     match serde_json::from_str::<ListToolsResult>(result) {
@@ -47,6 +59,22 @@ pub fn tools_list_stream(
                 } // Corrected closing brace for Err arm
             }
         }
+
+        // Tools of upstream MCP servers, under their namespace
+        let upstreams: Vec<_> = pm.upstreams.iter().map(|e| e.value().clone()).collect();
+        for client in upstreams {
+            match crate::upstream::refresh_tools(&pm, &client).await {
+                Ok(tools) => {
+                    for tool in tools {
+                        if tx.send(Ok(tool)).await.is_err() {
+                            log::warn!("Receiver dropped for tools_list_stream");
+                            return;
+                        }
+                    }
+                }
+                Err(e) => log::error!("upstream {} tools/list error: {:#}", client.name(), e),
+            }
+        }
     });
 
     ToolStream::new(rx)
@@ -55,15 +83,73 @@ pub fn tools_list_stream(
 /// Future-based tools_call (returns ToolCallExecution).
 pub fn tools_call_pending(
     pm: crate::plugin::PluginManager, // Updated path
-    request: ToolCallRequestParams,
+    mut request: ToolCallRequestParams,
 ) -> ToolCallExecution {
     let (tx, rx) = oneshot::channel();
+    let request_id = cancellation::current_request_id();
+    let client_timeout = deadline::current_client_timeout();
+    let client = sandbox::current_client();
 
     tokio::spawn(async move {
         // Lock-free access using DashMap
 
+        let logged_arguments = redact_arguments(request.arguments.as_ref());
+        log::info!(
+            "tools/call {} arguments: {}",
+            request.name,
+            logged_arguments
+        );
+
+        // Swap upload handles for their blobs; the log above keeps the handles
+        if let Some(arguments) = request.arguments.as_mut() {
+            if let Err(e) = crate::upload::resolve_arguments(arguments) {
+                let _ = tx.send(Err(
+                    serde_json::json!({"code": -32602, "message": e.to_string()})
+                        .into_handler_error(),
+                ));
+                return;
+            }
+        }
         let tool_name = request.name.as_str();
-        log::info!("request: {:?}", request);
+
+        // Let the plugin poll for cancellation of this request
+        if let Some(id) = &request_id {
+            let meta = request.meta.get_or_insert_with(|| serde_json::json!({}));
+            if let Some(meta) = meta.as_object_mut() {
+                meta.insert(
+                    cancellation::REQUEST_ID_META_KEY.to_string(),
+                    id.clone().into(),
+                );
+            }
+        }
+
+        let route = pm
+            .tool_to_plugin
+            .get(tool_name)
+            .map(|entry| entry.value().clone())
+            .map(|plugin_name| {
+                let pool = pm.plugins.get(&plugin_name).map(|p| p.value().clone());
+                (plugin_name, pool)
+            });
+
+        // The client's deadline, from `_meta` or the transport, capped by the
+        // server's and the tool's limits
+        let client_timeout = deadline::from_meta(request.meta.as_ref()).or(client_timeout);
+        let tool_limit = route.as_ref().and_then(|(plugin_name, _)| {
+            pm.plugin_limits
+                .get(plugin_name)
+                .and_then(|limits| deadline::tool_limit(&limits, tool_name))
+        });
+        let budget = deadline::budget(client_timeout, tool_limit);
+        if let Some(budget) = budget {
+            let meta = request.meta.get_or_insert_with(|| serde_json::json!({}));
+            if let Some(meta) = meta.as_object_mut() {
+                meta.insert(
+                    deadline::BUDGET_META_KEY.to_string(),
+                    (budget.as_millis() as u64).into(),
+                );
+            }
+        }
 
         let call_payload = serde_json::json!({
             "params": request.clone(),
@@ -76,11 +162,61 @@ pub fn tools_call_pending(
                 return;
             }
         };
-
-        let result = if let Some(plugin_name_entry) = pm.tool_to_plugin.get(tool_name) {
-            let plugin_name = plugin_name_entry.value();
-            if let Some(mut plugin_entry) = pm.plugins.get_mut(plugin_name) {
-                match plugin_entry.call::<&str, &str>("call", &json_string) {
+        let result = if let Some((plugin_name, pool)) = route {
+            if let Some(pool) = pool {
+                if let Some(id) = &request_id {
+                    cancellation::begin(id);
+                }
+                let mut plugin_entry = pool.checkout().await;
+                let started = std::time::Instant::now();
+                let outcome = match plugin_entry.as_mut() {
+                    Ok(plugin) => {
+                        // Stop the plugin when the budget runs out
+                        let timer = budget.map(|budget| {
+                            let handle = plugin.cancel_handle();
+                            tokio::spawn(async move {
+                                tokio::time::sleep(budget).await;
+                                let _ = handle.cancel();
+                            })
+                        });
+                        let outcome = plugin
+                            .call::<&str, &str>("call", &json_string)
+                            .map(str::to_string);
+                        if let Some(timer) = timer {
+                            timer.abort();
+                        }
+                        outcome
+                    }
+                    Err(e) => Err(extism::Error::msg(e.to_string())),
+                };
+                let outcome = match (outcome, budget) {
+                    (Err(_), Some(budget)) if started.elapsed() >= budget => {
+                        Err(extism::Error::msg(format!(
+                            "call exceeded its {} ms deadline",
+                            budget.as_millis()
+                        )))
+                    }
+                    (outcome, _) => outcome,
+                };
+                if let Some(id) = &request_id {
+                    cancellation::finish(id);
+                }
+                if let Some(monitor) = pm
+                    .sandbox_monitors
+                    .get(&plugin_name)
+                    .map(|m| m.value().clone())
+                {
+                    let client = client.as_deref().unwrap_or("unknown");
+                    match &outcome {
+                        Ok(result) => monitor.observe(client, result),
+                        Err(e) => monitor.observe(client, &format!("{:#}", e)),
+                    }
+                }
+                // A failed call may have trapped; don't hand that instance out again
+                if let (Ok(plugin), Err(_)) = (plugin_entry, &outcome) {
+                    plugin.discard();
+                }
+                match outcome.as_deref() {
                     Ok(result) => match serde_json::from_str::<CallToolResult>(result) {
                         Ok(parsed) => Ok(parsed),
                         Err(e) => {
@@ -93,10 +229,11 @@ pub fn tools_call_pending(
                     },
                     Err(e) => {
                         log::error!(
-                            "Failed to execute plugin {}: {}, request: {:?}",
+                            "Failed to execute plugin {}: {}, tool: {}, arguments: {}",
                             plugin_name,
                             e,
-                            request
+                            tool_name,
+                            logged_arguments
                         );
                         Err(
                             serde_json::json!({"code": -32602, "message": format!("Failed to execute plugin {}: {}", plugin_name, e)})
@@ -110,6 +247,20 @@ pub fn tools_call_pending(
                         .into_handler_error(),
                 )
             }
+        } else if let Some((upstream, original)) = pm
+            .tool_to_upstream
+            .get(tool_name)
+            .map(|route| route.value().clone())
+        {
+            let call =
+                crate::upstream::call_tool(&pm, &upstream, &original, request.arguments.clone());
+            match budget {
+                Some(budget) => tokio::time::timeout(budget, call).await.unwrap_or_else(|_| {
+                    Err(serde_json::json!({"code": -32603, "message": format!("Tool '{}' exceeded its {} ms deadline", tool_name, budget.as_millis())})
+                        .into_handler_error())
+                }),
+                None => call.await,
+            }
         } else {
             Err(
                 serde_json::json!({"code": -32602, "message": format!("Tool '{}' not found in any plugin", tool_name)})
@@ -117,6 +268,11 @@ pub fn tools_call_pending(
             )
         };
 
+        let result = match result {
+            Ok(result) => Ok(super::postprocess::apply(tool_name, result).await),
+            Err(e) => Err(e),
+        };
+
         let _ = tx.send(result);
     });
 
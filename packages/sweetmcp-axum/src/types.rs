@@ -113,7 +113,7 @@ pub struct ListResourcesResult {
     pub next_cursor: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Resource {
     pub uri: Url,
@@ -133,6 +133,36 @@ pub struct Resource {
     pub metadata: Option<Value>,
 }
 
+/// Parameterised resource such as `notes://{id}`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceTemplate {
+    pub uri_template: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListResourceTemplatesResult {
+    pub resource_templates: Vec<ResourceTemplate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// Output of a plugin's `list_resources` export
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginResourceList {
+    #[serde(default)]
+    pub resources: Vec<Resource>,
+    #[serde(default)]
+    pub resource_templates: Vec<ResourceTemplate>,
+}
+
 #[derive(Debug, Deserialize, Serialize, RpcParams)]
 pub struct ReadResourceRequest {
     pub uri: Url,
@@ -227,13 +257,21 @@ pub struct Tool {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
     pub input_schema: ToolInputSchema,
+    /// Behaviour hints such as `readOnlyHint` and `idempotentHint`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<Value>,
+    /// Sample invocations, each `{"description", "arguments"}`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<Value>,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct ToolInputSchema {
     #[serde(rename = "type")]
     pub type_name: String,
+    #[serde(default)]
     pub properties: HashMap<String, ToolInputSchemaProperty>,
+    #[serde(default)]
     pub required: Vec<String>,
 }
 
@@ -261,6 +299,9 @@ pub struct ToolCallRequestParams {
     pub name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub arguments: Option<Value>,
+    /// Forwarded to the plugin as-is; carries the client's `progressToken`
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Value>,
 }
 
 #[derive(Deserialize, Serialize, RpcParams, Debug)]
@@ -271,19 +312,43 @@ pub struct CallToolResult {
     pub is_error: bool,
 }
 
+/// Plugins built with sweetmcp-plugin-builder send `mime_type`; it is
+/// accepted as an alias and forwarded as `mimeType`.
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum CallToolResultContent {
     #[serde(rename = "text")]
-    Text { text: String },
+    Text {
+        text: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        annotations: Option<Value>,
+    },
     #[serde(rename = "image")]
     Image {
         data: String,
-        #[serde(rename = "mimeType")]
+        #[serde(rename = "mimeType", alias = "mime_type")]
         mime_type: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        annotations: Option<Value>,
     },
     #[serde(rename = "resource")]
     Resource { resource: ResourceContent },
+    #[serde(rename = "resource_link")]
+    ResourceLink {
+        uri: String,
+        name: String,
+        #[serde(
+            rename = "mimeType",
+            alias = "mime_type",
+            default,
+            skip_serializing_if = "Option::is_none"
+        )]
+        mime_type: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        annotations: Option<Value>,
+    },
 }
 
 #[derive(Debug, Deserialize, Serialize, RpcParams)]
@@ -309,7 +374,8 @@ pub struct PingRequest {}
 #[derive(Debug, Deserialize, Serialize, RpcParams)]
 #[serde(rename_all = "camelCase")]
 pub struct CancelledNotification {
-    pub request_id: String,
+    /// String or number, matching the cancelled request's id
+    pub request_id: Value,
     pub reason: Option<String>,
 }
 
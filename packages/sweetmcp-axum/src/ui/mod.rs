@@ -56,7 +56,8 @@ pub enum Commands {
 
 #[derive(Parser, Debug)]
 pub struct ServeArgs {
-    /// Run as a system daemon (detached from terminal)
+    /// Serve on the daemon socket instead of stdin/stdout; runs in the
+    /// foreground under a supervisor such as cyrupd
     #[arg(long)]
     pub daemon: bool,
 
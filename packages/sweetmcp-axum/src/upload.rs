@@ -0,0 +1,444 @@
+//! Out-of-band uploads of large tool arguments.
+//!
+//! Files, images and audio passed inline as base64 make JSON-RPC messages of
+//! several megabytes, which fail through the gateway. With `uploads`
+//! configured the server also listens on a loopback address where a client
+//! sends the raw bytes ahead of the call:
+//!
+//! - `POST /uploads` – a new blob, typed by its `Content-Type`
+//! - `PATCH /uploads/<id>` – append the next part of a blob sent in parts
+//! - `DELETE /uploads/<id>` – drop a blob before it expires
+//!
+//! Each answers with a handle such as `upload://3f2a…`. A tool-call argument
+//! that is exactly a handle, at any depth, is replaced with the blob as base64
+//! before the call reaches the plugin or upstream, so tools need no changes.
+//! Blobs live on disk until `ttl_secs` after their upload began.
+
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::config::UploadConfig;
+
+/// Prefix of an upload handle
+pub const HANDLE_PREFIX: &str = "upload://";
+
+/// Longest request head accepted, in bytes
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+
+/// How often expired blobs are removed
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+static STORE: OnceLock<UploadStore> = OnceLock::new();
+
+/// Why an upload request or handle was refused
+#[derive(Debug, thiserror::Error)]
+pub enum UploadError {
+    #[error("no upload {0}, or it expired")]
+    NotFound(String),
+    #[error("upload is larger than {0} bytes")]
+    TooLarge(u64),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// What is known about a stored blob
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Upload {
+    pub id: String,
+    pub mime_type: String,
+    pub size: u64,
+    pub created: DateTime<Utc>,
+    pub expires: DateTime<Utc>,
+}
+
+impl Upload {
+    pub fn handle(&self) -> String {
+        format!("{}{}", HANDLE_PREFIX, self.id)
+    }
+}
+
+/// Blobs on disk, as `<id>.bin` with their metadata in `<id>.json`
+pub struct UploadStore {
+    dir: PathBuf,
+    max_bytes: u64,
+    ttl: chrono::Duration,
+    token: Option<String>,
+}
+
+impl UploadStore {
+    pub fn new(config: &UploadConfig) -> Result<Self> {
+        let dir = match &config.dir {
+            Some(dir) => PathBuf::from(dir),
+            None => dirs::cache_dir()
+                .context("no cache directory for uploads; set uploads.dir")?
+                .join("cyrup-mcp")
+                .join("uploads"),
+        };
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("create upload directory {}", dir.display()))?;
+        if config.max_bytes == 0 || config.ttl_secs == 0 {
+            bail!("uploads max_bytes and ttl_secs must be positive");
+        }
+        Ok(Self {
+            dir,
+            max_bytes: config.max_bytes,
+            ttl: chrono::Duration::seconds(config.ttl_secs as i64),
+            token: config.resolve_token()?,
+        })
+    }
+
+    fn blob_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.bin", id))
+    }
+
+    fn meta_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+
+    /// Store the first (or only) part of a new blob
+    pub fn create(&self, mime_type: &str, bytes: &[u8]) -> Result<Upload, UploadError> {
+        if bytes.len() as u64 > self.max_bytes {
+            return Err(UploadError::TooLarge(self.max_bytes));
+        }
+        let created = Utc::now();
+        let upload = Upload {
+            id: uuid::Uuid::new_v4().simple().to_string(),
+            mime_type: mime_type.to_string(),
+            size: bytes.len() as u64,
+            created,
+            expires: created + self.ttl,
+        };
+        std::fs::write(self.blob_path(&upload.id), bytes)?;
+        self.save_meta(&upload)?;
+        Ok(upload)
+    }
+
+    /// Add the next part of a blob
+    pub fn append(&self, id: &str, bytes: &[u8]) -> Result<Upload, UploadError> {
+        use std::io::Write;
+
+        let mut upload = self.get(id)?;
+        if upload.size + bytes.len() as u64 > self.max_bytes {
+            return Err(UploadError::TooLarge(self.max_bytes));
+        }
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(self.blob_path(id))?
+            .write_all(bytes)?;
+        upload.size += bytes.len() as u64;
+        self.save_meta(&upload)?;
+        Ok(upload)
+    }
+
+    /// Metadata of a blob that has not expired
+    pub fn get(&self, id: &str) -> Result<Upload, UploadError> {
+        let not_found = || UploadError::NotFound(id.to_string());
+        if !valid_id(id) {
+            return Err(not_found());
+        }
+        let meta = std::fs::read(self.meta_path(id)).map_err(|_| not_found())?;
+        let upload: Upload = serde_json::from_slice(&meta).map_err(|_| not_found())?;
+        if upload.expires <= Utc::now() {
+            self.remove(id);
+            return Err(not_found());
+        }
+        Ok(upload)
+    }
+
+    /// The bytes of a blob that has not expired
+    pub fn read(&self, id: &str) -> Result<Vec<u8>, UploadError> {
+        self.get(id)?;
+        Ok(std::fs::read(self.blob_path(id))?)
+    }
+
+    /// Drop a blob; a missing one is not an error
+    pub fn remove(&self, id: &str) {
+        if valid_id(id) {
+            std::fs::remove_file(self.blob_path(id)).ok();
+            std::fs::remove_file(self.meta_path(id)).ok();
+        }
+    }
+
+    /// Remove every expired blob
+    pub fn sweep(&self) {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            if let Some(id) = path.file_stem().and_then(|s| s.to_str()) {
+                // `get` removes what has expired or can't be read
+                if let Err(UploadError::NotFound(_)) = self.get(id) {
+                    self.remove(id);
+                }
+            }
+        }
+    }
+
+    fn save_meta(&self, upload: &Upload) -> Result<(), UploadError> {
+        let meta = serde_json::to_vec(upload).map_err(std::io::Error::other)?;
+        write_atomic(&self.meta_path(&upload.id), &meta)?;
+        Ok(())
+    }
+}
+
+/// Ids are the 32 hex digits of a v4 UUID, which keeps them inside the directory
+fn valid_id(id: &str) -> bool {
+    id.len() == 32 && id.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Length-independent comparison so the token can't be guessed byte by byte
+fn token_matches(presented: &str, expected: &str) -> bool {
+    let (a, b) = (presented.as_bytes(), expected.as_bytes());
+    let mut diff = a.len() ^ b.len();
+    for i in 0..a.len().max(b.len()) {
+        let x = a.get(i).copied().unwrap_or(0);
+        let y = b.get(i).copied().unwrap_or(0);
+        diff |= (x ^ y) as usize;
+    }
+    diff == 0
+}
+
+fn write_atomic(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, bytes)?;
+    std::fs::rename(&tmp, path)
+}
+
+/// Replace every upload handle in `arguments` with its blob as base64.
+/// Arguments are left alone when uploads are not configured.
+pub fn resolve_arguments(arguments: &mut Value) -> Result<(), UploadError> {
+    let Some(store) = STORE.get() else {
+        return Ok(());
+    };
+    resolve_value(store, arguments)
+}
+
+fn resolve_value(store: &UploadStore, value: &mut Value) -> Result<(), UploadError> {
+    match value {
+        Value::String(s) => {
+            if let Some(id) = s.strip_prefix(HANDLE_PREFIX) {
+                let bytes = store.read(id)?;
+                *s = base64::engine::general_purpose::STANDARD.encode(bytes);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                resolve_value(store, item)?;
+            }
+        }
+        Value::Object(map) => {
+            for item in map.values_mut() {
+                resolve_value(store, item)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Open the upload store and start serving it
+pub async fn start(config: &UploadConfig) -> Result<()> {
+    let addr: SocketAddr = config
+        .listen
+        .parse()
+        .with_context(|| format!("invalid uploads listen address {}", config.listen))?;
+    if !addr.ip().is_loopback() {
+        bail!("uploads must listen on loopback, got {}", addr);
+    }
+    let store = UploadStore::new(config)?;
+    store.sweep();
+    if STORE.set(store).is_err() {
+        bail!("upload listener already started");
+    }
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("bind upload listener to {}", addr))?;
+    log::info!("Upload listener on {}", addr);
+
+    tokio::spawn(async {
+        let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Some(store) = STORE.get() {
+                tokio::task::spawn_blocking(|| store.sweep()).await.ok();
+            }
+        }
+    });
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tokio::spawn(async move {
+                        if let Err(e) = handle_connection(stream).await {
+                            log::error!("Failed to handle upload connection: {}", e);
+                        }
+                    });
+                }
+                Err(e) => log::error!("Failed to accept upload connection: {}", e),
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Serve one request of an upload connection
+async fn handle_connection(mut stream: TcpStream) -> Result<()> {
+    let Some(store) = STORE.get() else {
+        return Ok(());
+    };
+
+    // Read until the end of the head; what follows is the start of the body
+    let mut buffer = Vec::with_capacity(8192);
+    let mut chunk = [0u8; 8192];
+    let head_end = loop {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        buffer.extend_from_slice(&chunk[..n]);
+        if let Some(end) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end;
+        }
+        if buffer.len() > MAX_HEAD_BYTES {
+            return respond(&mut stream, "431 Request Header Fields Too Large", None).await;
+        }
+    };
+    let head = String::from_utf8_lossy(&buffer[..head_end]).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let (method, path) = (
+        request_line.next().unwrap_or_default(),
+        request_line.next().unwrap_or_default(),
+    );
+    let header = |name: &str| {
+        head.lines()
+            .skip(1)
+            .filter_map(|line| line.split_once(':'))
+            .find(|(key, _)| key.trim().eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.trim().to_string())
+    };
+
+    if let Some(token) = &store.token {
+        let presented = header("authorization");
+        let expected = format!("Bearer {}", token);
+        if !presented.is_some_and(|p| token_matches(&p, &expected)) {
+            return respond(
+                &mut stream,
+                "401 Unauthorized",
+                Some(json!({ "error": "missing or invalid token" })),
+            )
+            .await;
+        }
+    }
+
+    let id = path.strip_prefix("/uploads/").map(str::to_string);
+    let reads_body = matches!((method, &id), ("POST", None) | ("PATCH", Some(_)));
+    let body = if reads_body {
+        let Some(length) = header("content-length").and_then(|v| v.parse::<u64>().ok()) else {
+            return respond(
+                &mut stream,
+                "411 Length Required",
+                Some(json!({ "error": "Content-Length is required" })),
+            )
+            .await;
+        };
+        if length > store.max_bytes {
+            let error = UploadError::TooLarge(store.max_bytes);
+            return respond(
+                &mut stream,
+                "413 Payload Too Large",
+                Some(json!({ "error": error.to_string() })),
+            )
+            .await;
+        }
+        let mut body = buffer.split_off(head_end + 4);
+        body.truncate(length as usize);
+        read_to_length(&mut stream, &mut body, length).await?;
+        body
+    } else {
+        Vec::new()
+    };
+
+    let result = match (method, path, id.as_deref()) {
+        ("POST", "/uploads", None) => {
+            let mime_type =
+                header("content-type").unwrap_or_else(|| "application/octet-stream".to_string());
+            store
+                .create(&mime_type, &body)
+                .map(|upload| ("201 Created", upload))
+        }
+        ("PATCH", _, Some(id)) => store.append(id, &body).map(|upload| ("200 OK", upload)),
+        ("DELETE", _, Some(id)) => {
+            store.remove(id);
+            return respond(&mut stream, "204 No Content", None).await;
+        }
+        (_, "/uploads", None) | (_, _, Some(_)) => {
+            return respond(&mut stream, "405 Method Not Allowed", None).await;
+        }
+        _ => return respond(&mut stream, "404 Not Found", None).await,
+    };
+
+    match result {
+        Ok((status, upload)) => {
+            let mut body = serde_json::to_value(&upload)?;
+            body["handle"] = upload.handle().into();
+            respond(&mut stream, status, Some(body)).await
+        }
+        Err(e) => {
+            let status = match &e {
+                UploadError::NotFound(_) => "404 Not Found",
+                UploadError::TooLarge(_) => "413 Payload Too Large",
+                UploadError::Io(io) => {
+                    log::error!("Upload failed: {}", io);
+                    "500 Internal Server Error"
+                }
+            };
+            respond(&mut stream, status, Some(json!({ "error": e.to_string() }))).await
+        }
+    }
+}
+
+/// Read the rest of a body whose first bytes are already in `body`
+async fn read_to_length(stream: &mut TcpStream, body: &mut Vec<u8>, length: u64) -> Result<()> {
+    let remaining = length.saturating_sub(body.len() as u64);
+    stream.take(remaining).read_to_end(body).await?;
+    if (body.len() as u64) < length {
+        bail!("connection closed after {} of {} bytes", body.len(), length);
+    }
+    Ok(())
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, body: Option<Value>) -> Result<()> {
+    let response = match body {
+        Some(body) => {
+            let body = body.to_string();
+            format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            )
+        }
+        None => format!(
+            "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            status
+        ),
+    };
+    stream.write_all(response.as_bytes()).await?;
+    stream.flush().await?;
+    Ok(())
+}
@@ -0,0 +1,368 @@
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow, bail};
+use dashmap::DashMap;
+use reqwest::header::{ACCEPT, CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue};
+use serde_json::{Value, json};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::{Mutex, oneshot};
+
+use crate::config::{UpstreamConfig, UpstreamTransport};
+use crate::types::{CallToolResult, ListToolsResult, Tool};
+use crate::{JSONRPC_VERSION, PROTOCOL_VERSION, SERVER_NAME, SERVER_VERSION};
+
+/// An initialized MCP session with one upstream server.
+pub struct UpstreamClient {
+    name: String,
+    prefix: String,
+    timeout: Duration,
+    next_id: AtomicU64,
+    transport: Transport,
+}
+
+enum Transport {
+    Stdio(StdioTransport),
+    Http(HttpTransport),
+}
+
+impl UpstreamClient {
+    /// Start (or reach) the upstream and perform the MCP handshake.
+    pub async fn connect(cfg: &UpstreamConfig) -> Result<Self> {
+        let transport = match &cfg.transport {
+            UpstreamTransport::Stdio { command, args, env } => {
+                Transport::Stdio(StdioTransport::spawn(&cfg.name, command, args, env)?)
+            }
+            UpstreamTransport::Http { url, headers } => {
+                Transport::Http(HttpTransport::new(url, headers)?)
+            }
+        };
+        let client = Self {
+            name: cfg.name.clone(),
+            prefix: cfg.prefix().to_string(),
+            timeout: Duration::from_millis(cfg.timeout_ms),
+            next_id: AtomicU64::new(1),
+            transport,
+        };
+
+        let init = client
+            .request(
+                "initialize",
+                json!({
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": { "name": SERVER_NAME, "version": SERVER_VERSION },
+                }),
+            )
+            .await?;
+        log::info!(
+            "Connected to upstream '{}' ({} {})",
+            client.name,
+            init["serverInfo"]["name"].as_str().unwrap_or("unknown"),
+            init["serverInfo"]["version"].as_str().unwrap_or("")
+        );
+        client
+            .notify("notifications/initialized", json!({}))
+            .await?;
+        Ok(client)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// All tools the upstream offers, following pagination.
+    pub async fn list_tools(&self) -> Result<Vec<Tool>> {
+        let mut tools = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let params = match &cursor {
+                Some(cursor) => json!({ "cursor": cursor }),
+                None => json!({}),
+            };
+            let page: ListToolsResult = serde_json::from_value(
+                self.request("tools/list", params).await?,
+            )
+            .with_context(|| format!("malformed tools/list result from upstream {}", self.name))?;
+            tools.extend(page.tools);
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        Ok(tools)
+    }
+
+    /// Call `name` (the upstream's own tool name) with `arguments` as given.
+    pub async fn call_tool(&self, name: &str, arguments: Option<Value>) -> Result<CallToolResult> {
+        let mut params = json!({ "name": name });
+        if let Some(arguments) = arguments {
+            params["arguments"] = arguments;
+        }
+        let result = self.request("tools/call", params).await?;
+        serde_json::from_value(result)
+            .with_context(|| format!("malformed tools/call result from upstream {}", self.name))
+    }
+
+    /// Send a request and wait for its result, cancelling it on timeout.
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let message = json!({
+            "jsonrpc": JSONRPC_VERSION,
+            "id": id,
+            "method": method,
+            "params": params,
+        });
+
+        let exchange = async {
+            match &self.transport {
+                Transport::Stdio(t) => t.request(id, &message).await,
+                Transport::Http(t) => t.request(id, &message).await,
+            }
+        };
+        let response = match tokio::time::timeout(self.timeout, exchange).await {
+            Ok(response) => {
+                response.with_context(|| format!("{} on upstream {}", method, self.name))?
+            }
+            Err(_) => {
+                if let Transport::Stdio(t) = &self.transport {
+                    t.pending.remove(&id);
+                }
+                let cancel = json!({ "requestId": id, "reason": "timed out" });
+                if let Err(e) = self.notify("notifications/cancelled", cancel).await {
+                    log::debug!(
+                        "Failed to cancel request on upstream {}: {:#}",
+                        self.name,
+                        e
+                    );
+                }
+                bail!(
+                    "{} on upstream {} timed out after {}ms",
+                    method,
+                    self.name,
+                    self.timeout.as_millis()
+                );
+            }
+        };
+
+        if let Some(error) = response.get("error") {
+            bail!(
+                "upstream {} rejected {}: {}",
+                self.name,
+                method,
+                error["message"].as_str().unwrap_or(&error.to_string())
+            );
+        }
+        Ok(response.get("result").cloned().unwrap_or(Value::Null))
+    }
+
+    /// Send a notification; no response is expected.
+    pub async fn notify(&self, method: &str, params: Value) -> Result<()> {
+        let message = json!({
+            "jsonrpc": JSONRPC_VERSION,
+            "method": method,
+            "params": params,
+        });
+        match &self.transport {
+            Transport::Stdio(t) => t.send(&message).await,
+            Transport::Http(t) => t.send(&message).await.map(drop),
+        }
+    }
+}
+
+/// Child process speaking newline-delimited JSON-RPC.
+struct StdioTransport {
+    stdin: Arc<Mutex<ChildStdin>>,
+    /// Requests awaiting a response, by id
+    pending: Arc<DashMap<u64, oneshot::Sender<Value>>>,
+    /// Held so the process is killed when the client is dropped
+    _child: Child,
+}
+
+impl StdioTransport {
+    fn spawn(
+        name: &str,
+        command: &str,
+        args: &[String],
+        env: &HashMap<String, String>,
+    ) -> Result<Self> {
+        let mut child = Command::new(command)
+            .args(args)
+            .envs(env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to start upstream {}: {}", name, command))?;
+        let stdin = Arc::new(Mutex::new(child.stdin.take().context("stdin not piped")?));
+        let stdout = child.stdout.take().context("stdout not piped")?;
+        let pending: Arc<DashMap<u64, oneshot::Sender<Value>>> = Arc::new(DashMap::new());
+
+        let reader_pending = pending.clone();
+        let reader_stdin = stdin.clone();
+        let name = name.to_string();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stdout).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let Ok(message) = serde_json::from_str::<Value>(&line) else {
+                    log::debug!("upstream {}: non-JSON output: {}", name, line);
+                    continue;
+                };
+                match (message.get("id"), message.get("method")) {
+                    // Response to one of our requests
+                    (Some(id), None) => {
+                        let sender = id.as_u64().and_then(|id| reader_pending.remove(&id));
+                        if let Some((_, tx)) = sender {
+                            let _ = tx.send(message);
+                        }
+                    }
+                    // Request from the server; we offer no client capabilities
+                    (Some(id), Some(method)) => {
+                        log::debug!("upstream {}: refusing server request {}", name, method);
+                        let reply = json!({
+                            "jsonrpc": JSONRPC_VERSION,
+                            "id": id,
+                            "error": { "code": -32601, "message": "Method not found" },
+                        });
+                        let mut line = reply.to_string();
+                        line.push('\n');
+                        let mut stdin = reader_stdin.lock().await;
+                        if stdin.write_all(line.as_bytes()).await.is_err() {
+                            break;
+                        }
+                        let _ = stdin.flush().await;
+                    }
+                    (None, method) => {
+                        log::debug!("upstream {}: notification {:?}", name, method);
+                    }
+                }
+            }
+            log::warn!("Upstream '{}' closed its stdout", name);
+            // Dropping the senders fails every request still waiting
+            reader_pending.clear();
+        });
+
+        Ok(Self {
+            stdin,
+            pending,
+            _child: child,
+        })
+    }
+
+    async fn request(&self, id: u64, message: &Value) -> Result<Value> {
+        let (tx, rx) = oneshot::channel();
+        self.pending.insert(id, tx);
+        if let Err(e) = self.send(message).await {
+            self.pending.remove(&id);
+            return Err(e);
+        }
+        rx.await.map_err(|_| anyhow!("upstream process exited"))
+    }
+
+    async fn send(&self, message: &Value) -> Result<()> {
+        let mut line = message.to_string();
+        line.push('\n');
+        let mut stdin = self.stdin.lock().await;
+        stdin.write_all(line.as_bytes()).await?;
+        stdin.flush().await?;
+        Ok(())
+    }
+}
+
+/// Streamable HTTP endpoint; each message is a POST whose reply is either
+/// plain JSON or a short event stream.
+struct HttpTransport {
+    client: reqwest::Client,
+    url: String,
+    headers: HeaderMap,
+    session_id: RwLock<Option<String>>,
+}
+
+impl HttpTransport {
+    fn new(url: &str, headers: &HashMap<String, String>) -> Result<Self> {
+        let mut map = HeaderMap::new();
+        for (key, value) in headers {
+            map.insert(
+                HeaderName::from_bytes(key.as_bytes())
+                    .with_context(|| format!("invalid header name {}", key))?,
+                HeaderValue::from_str(value)
+                    .with_context(|| format!("invalid value for header {}", key))?,
+            );
+        }
+        map.insert(
+            ACCEPT,
+            HeaderValue::from_static("application/json, text/event-stream"),
+        );
+        Ok(Self {
+            client: reqwest::Client::new(),
+            url: url.to_string(),
+            headers: map,
+            session_id: RwLock::new(None),
+        })
+    }
+
+    async fn send(&self, message: &Value) -> Result<reqwest::Response> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .headers(self.headers.clone())
+            .json(message);
+        let session_id = self.session_id.read().unwrap().clone();
+        if let Some(session_id) = session_id {
+            request = request.header("Mcp-Session-Id", session_id);
+        }
+        let response = request.send().await?.error_for_status()?;
+        if let Some(session_id) = response
+            .headers()
+            .get("mcp-session-id")
+            .and_then(|v| v.to_str().ok())
+        {
+            *self.session_id.write().unwrap() = Some(session_id.to_string());
+        }
+        Ok(response)
+    }
+
+    async fn request(&self, id: u64, message: &Value) -> Result<Value> {
+        let response = self.send(message).await?;
+        let is_stream = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("text/event-stream"));
+        let body = response.text().await?;
+        if !is_stream {
+            return serde_json::from_str(&body).context("invalid JSON-RPC response");
+        }
+        sse_messages(&body)
+            .into_iter()
+            .find(|m| m.get("id").and_then(Value::as_u64) == Some(id))
+            .context("event stream ended without a response")
+    }
+}
+
+/// JSON payloads of the `data:` lines in an event stream body.
+fn sse_messages(body: &str) -> Vec<Value> {
+    let body = body.replace("\r\n", "\n");
+    body.split("\n\n")
+        .filter_map(|event| {
+            let data: Vec<&str> = event
+                .lines()
+                .filter_map(|line| line.strip_prefix("data:"))
+                .map(|data| data.strip_prefix(' ').unwrap_or(data))
+                .collect();
+            if data.is_empty() {
+                return None;
+            }
+            serde_json::from_str(&data.join("\n")).ok()
+        })
+        .collect()
+}
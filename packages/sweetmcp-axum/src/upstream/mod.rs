@@ -0,0 +1,107 @@
+//! Upstream MCP servers.
+//!
+//! Each `upstreams` entry in the config is an external MCP server reached over
+//! stdio or Streamable HTTP. Its tools are listed next to the plugins' tools as
+//! `<prefix>__<tool>`, and calls to those names are forwarded to the server
+//! with the prefix stripped and the arguments untouched.
+
+mod client;
+
+use std::sync::Arc;
+
+use rpc_router::{HandlerResult, IntoHandlerError};
+use serde_json::{Value, json};
+
+pub use client::UpstreamClient;
+
+use crate::config::UpstreamConfig;
+use crate::plugin::PluginManager;
+use crate::types::{CallToolResult, Tool};
+
+/// Joins an upstream's prefix and its tool name.
+pub const NAMESPACE_SEPARATOR: &str = "__";
+
+/// The name an upstream tool is listed under.
+pub fn namespaced(prefix: &str, tool: &str) -> String {
+    format!("{}{}{}", prefix, NAMESPACE_SEPARATOR, tool)
+}
+
+/// Connect to every configured upstream and cache its tools.
+/// Upstreams that cannot be reached are logged and skipped.
+pub async fn load_upstreams(manager: &PluginManager, configs: &[UpstreamConfig]) {
+    for cfg in configs {
+        if manager.upstreams.contains_key(&cfg.name) {
+            log::error!("Duplicate upstream name '{}', skipping", cfg.name);
+            continue;
+        }
+        let client = match UpstreamClient::connect(cfg).await {
+            Ok(client) => Arc::new(client),
+            Err(e) => {
+                log::error!("Skipping upstream '{}': {:#}", cfg.name, e);
+                continue;
+            }
+        };
+        manager.upstreams.insert(cfg.name.clone(), client.clone());
+        match refresh_tools(manager, &client).await {
+            Ok(tools) => log::info!(
+                "Loaded {} tools from upstream {} under '{}'",
+                tools.len(),
+                cfg.name,
+                client.prefix()
+            ),
+            Err(e) => log::warn!("Upstream '{}' failed to list tools: {:#}", cfg.name, e),
+        }
+    }
+}
+
+/// Re-list an upstream's tools and update the routing cache.
+/// Returns the tools under their namespaced names.
+pub async fn refresh_tools(
+    manager: &PluginManager,
+    client: &UpstreamClient,
+) -> anyhow::Result<Vec<Tool>> {
+    let tools = client.list_tools().await?;
+    manager
+        .tool_to_upstream
+        .retain(|_, (upstream, _)| upstream != client.name());
+
+    let mut listed = Vec::with_capacity(tools.len());
+    for mut tool in tools {
+        let name = namespaced(client.prefix(), &tool.name);
+        if manager.tool_to_plugin.contains_key(&name)
+            || manager.tool_to_upstream.contains_key(&name)
+        {
+            log::error!(
+                "Tool name collision detected: '{}' from upstream '{}' is already provided. Skipping it.",
+                name,
+                client.name()
+            );
+            continue;
+        }
+        let original = std::mem::replace(&mut tool.name, name.clone());
+        manager
+            .tool_to_upstream
+            .insert(name, (client.name().to_string(), original));
+        listed.push(tool);
+    }
+    Ok(listed)
+}
+
+/// Forward a call to the upstream that owns the tool.
+pub async fn call_tool(
+    manager: &PluginManager,
+    upstream: &str,
+    tool: &str,
+    arguments: Option<Value>,
+) -> HandlerResult<CallToolResult> {
+    let Some(client) = manager.upstreams.get(upstream).map(|c| c.value().clone()) else {
+        return Err(
+            json!({"code": -32602, "message": format!("Upstream '{}' is not connected", upstream)})
+                .into_handler_error(),
+        );
+    };
+    client.call_tool(tool, arguments).await.map_err(|e| {
+        log::error!("Upstream {} failed to call {}: {:#}", upstream, tool, e);
+        json!({"code": -32603, "message": format!("{:#}", e)}).into_handler_error()
+    })
+}
@@ -0,0 +1,245 @@
+use crate::profile::{Profile, Transport};
+use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform};
+use anyhow::{bail, Result};
+use std::path::{Path, PathBuf};
+
+/// JetBrains IDEs (IntelliJ IDEA, PyCharm, WebStorm, ...). AI Assistant keeps
+/// its MCP servers in `options/llm.mcpServers.xml` of each product's
+/// per-version config directory, e.g. `~/.config/JetBrains/PyCharm2025.1`, so
+/// every installed product and version is configured separately.
+pub struct JetBrainsPlugin;
+
+/// Config directory prefixes of the IDEs with AI Assistant
+const PRODUCTS: &[&str] = &[
+    "IntelliJIdea",
+    "IdeaIC",
+    "PyCharm",
+    "PyCharmCE",
+    "WebStorm",
+    "GoLand",
+    "CLion",
+    "RustRover",
+    "PhpStorm",
+    "Rider",
+    "RubyMine",
+    "DataGrip",
+    "DataSpell",
+    "Aqua",
+];
+
+/// First release whose AI Assistant reads MCP servers
+const MIN_VERSION: (u32, u32) = (2025, 1);
+
+/// MCP settings file, relative to a product's config directory
+const MCP_SETTINGS_FILE: &str = "options/llm.mcpServers.xml";
+
+/// `<McpServerCommand>` block for a stdio server, indented for `<commands>`
+fn server_entry(name: &str, command: &str, args: &[String]) -> String {
+    format!(
+        "      <McpServerCommand>\n        <option name=\"enabled\" value=\"true\" />\n        <option name=\"name\" value=\"{}\" />\n        <option name=\"programPath\" value=\"{}\" />\n        <option name=\"arguments\" value=\"{}\" />\n      </McpServerCommand>\n",
+        escape(name),
+        escape(command),
+        escape(&args.join(" "))
+    )
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn name_option(name: &str) -> String {
+    format!("<option name=\"name\" value=\"{}\" />", escape(name))
+}
+
+/// Add `entry` to the server list in `content`, creating whatever is missing
+fn insert_entry(content: &str, entry: &str) -> Result<String> {
+    let content = content.trim();
+
+    // No settings file yet (the watcher passes `{}` for a missing file)
+    if content.is_empty() || content == "{}" {
+        return Ok(format!(
+            "<application>\n  <component name=\"McpApplicationServerCommands\" modifiable=\"true\">\n    <commands>\n{}    </commands>\n  </component>\n</application>\n",
+            entry
+        ));
+    }
+
+    if let Some(end) = content.rfind("</commands>") {
+        let line_start = content[..end].rfind('\n').map_or(0, |i| i + 1);
+        let insert_at = if content[line_start..end].trim().is_empty() {
+            line_start
+        } else {
+            end
+        };
+        return Ok(format!(
+            "{}{}{}\n",
+            &content[..insert_at],
+            entry,
+            &content[insert_at..]
+        ));
+    }
+    if content.contains("<commands />") {
+        return Ok(format!(
+            "{}\n",
+            content.replacen(
+                "<commands />",
+                &format!("<commands>\n{}    </commands>", entry),
+                1
+            )
+        ));
+    }
+    if let Some(end) = content.rfind("</application>") {
+        return Ok(format!(
+            "{}  <component name=\"McpApplicationServerCommands\" modifiable=\"true\">\n    <commands>\n{}    </commands>\n  </component>\n{}\n",
+            &content[..end],
+            entry,
+            &content[end..]
+        ));
+    }
+    bail!("Unrecognized JetBrains MCP settings; expected an <application> document")
+}
+
+/// Drop the `<McpServerCommand>` block of the server called `name`, if any
+fn remove_entry(content: &str, name: &str) -> String {
+    let Some(at) = content.find(&name_option(name)) else {
+        return content.to_string();
+    };
+    let (Some(start), Some(end)) = (
+        content[..at].rfind("<McpServerCommand>"),
+        content[at..].find("</McpServerCommand>"),
+    ) else {
+        return content.to_string();
+    };
+    let start = content[..start].rfind('\n').map_or(0, |i| i + 1);
+    let mut end = at + end + "</McpServerCommand>".len();
+    if content[end..].starts_with('\n') {
+        end += 1;
+    }
+    format!("{}{}", &content[..start], &content[end..])
+}
+
+/// One installed product version, e.g. PyCharm 2025.1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JetBrainsInstall {
+    pub product: &'static str,
+    pub version: (u32, u32),
+    pub dir: PathBuf,
+}
+
+impl JetBrainsInstall {
+    pub fn settings_path(&self) -> PathBuf {
+        self.dir.join(MCP_SETTINGS_FILE)
+    }
+}
+
+/// Directory holding one config directory per product and version
+pub fn config_root() -> Option<PathBuf> {
+    match Platform::current() {
+        Platform::Windows => std::env::var("APPDATA")
+            .ok()
+            .map(|appdata| PathBuf::from(appdata).join("JetBrains")),
+        Platform::MacOS => directories::BaseDirs::new().map(|base_dirs| {
+            base_dirs
+                .home_dir()
+                .join("Library/Application Support/JetBrains")
+        }),
+        Platform::Linux => {
+            directories::BaseDirs::new().map(|base_dirs| base_dirs.config_dir().join("JetBrains"))
+        }
+        Platform::All => None,
+    }
+}
+
+/// Product and version of a config directory name such as `PyCharmCE2025.1`
+fn parse_dir_name(name: &str) -> Option<(&'static str, (u32, u32))> {
+    PRODUCTS.iter().find_map(|product| {
+        let (major, minor) = name.strip_prefix(product)?.split_once('.')?;
+        Some((*product, (major.parse().ok()?, minor.parse().ok()?)))
+    })
+}
+
+/// Every product version under `root` recent enough to support MCP, oldest
+/// first within each product
+pub fn detect_installations(root: &Path) -> Vec<JetBrainsInstall> {
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut installs: Vec<_> = entries
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let (product, version) = parse_dir_name(&entry.file_name().to_string_lossy())?;
+            (version >= MIN_VERSION).then(|| JetBrainsInstall {
+                product,
+                version,
+                dir: entry.path(),
+            })
+        })
+        .collect();
+    installs.sort_by(|a, b| (a.product, a.version).cmp(&(b.product, b.version)));
+    installs
+}
+
+impl ClientConfigPlugin for JetBrainsPlugin {
+    fn client_id(&self) -> &str {
+        "jetbrains"
+    }
+
+    fn client_name(&self) -> &str {
+        "JetBrains IDEs"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        config_root().into_iter().collect()
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        let Some(root) = config_root() else {
+            return Vec::new();
+        };
+        detect_installations(&root)
+            .into_iter()
+            .map(|install| ConfigPath {
+                path: install.settings_path(),
+                format: ConfigFormat::Xml,
+                platform: Platform::current(),
+            })
+            .collect()
+    }
+
+    fn is_installed(&self, path: &Path) -> bool {
+        !detect_installations(path).is_empty()
+    }
+
+    fn inject_sweetmcp(&self, config_content: &str, _format: ConfigFormat) -> Result<String> {
+        if config_content.contains(&name_option("sweetmcp")) {
+            return Ok(config_content.to_string());
+        }
+        insert_entry(
+            config_content,
+            &server_entry("sweetmcp", "sweetmcp", &["--daemon".to_string()]),
+        )
+    }
+
+    fn apply_profile(
+        &self,
+        config_content: &str,
+        _format: ConfigFormat,
+        profile: &Profile,
+    ) -> Result<String> {
+        let Transport::Stdio { command, args } = &profile.transport else {
+            bail!("JetBrains AI Assistant only starts stdio MCP servers; use a stdio profile");
+        };
+        insert_entry(
+            &remove_entry(config_content, &profile.name),
+            &server_entry(&profile.name, command, args),
+        )
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Xml
+    }
+}
@@ -1,5 +1,6 @@
 pub mod claude_desktop;
 pub mod cursor;
+pub mod jetbrains;
 pub mod roo_code;
 pub mod windsurf;
 pub mod zed;
@@ -15,5 +16,6 @@ pub fn all_clients() -> Vec<Arc<dyn ClientConfigPlugin>> {
         Arc::new(cursor::CursorPlugin),
         Arc::new(zed::ZedPlugin),
         Arc::new(roo_code::RooCodePlugin),
+        Arc::new(jetbrains::JetBrainsPlugin),
     ]
 }
@@ -1,3 +1,4 @@
+use crate::profile::Profile;
 use crate::ConfigFormat;
 use anyhow::{anyhow, Result};
 use serde_json::Value as JsonValue;
@@ -7,6 +8,10 @@ use toml::Value as TomlValue;
 pub struct ConfigMerger {
     /// Pre-allocated SweetMCP config template
     sweetmcp_config: SweetMcpConfig,
+    /// Key of the server entry under `mcpServers`
+    server_name: String,
+    /// Overwrite an existing entry instead of leaving it alone
+    replace: bool,
 }
 
 #[derive(Clone)]
@@ -47,7 +52,33 @@ impl ConfigMerger {
             }),
         };
 
-        Self { sweetmcp_config }
+        Self {
+            sweetmcp_config,
+            server_name: "sweetmcp".to_string(),
+            replace: false,
+        }
+    }
+
+    /// Merger that writes the server described by `profile`, replacing any
+    /// entry of the same name so a rollout can repoint existing installs
+    pub fn for_profile(profile: &Profile) -> Result<Self> {
+        let entry = profile.server_entry();
+        let mut servers = serde_json::Map::new();
+        servers.insert(profile.name.clone(), entry.clone());
+        let json_template = serde_json::json!({ "mcpServers": servers });
+        let mut mcp_servers = toml::map::Map::new();
+        mcp_servers.insert(profile.name.clone(), TomlValue::try_from(&entry)?);
+        let mut map = toml::map::Map::new();
+        map.insert("mcpServers".to_string(), TomlValue::Table(mcp_servers));
+
+        Ok(Self {
+            sweetmcp_config: SweetMcpConfig {
+                json_template,
+                toml_template: TomlValue::Table(map),
+            },
+            server_name: profile.name.clone(),
+            replace: true,
+        })
     }
 
     /// Merge SweetMCP config into existing config with zero allocation where possible
@@ -58,6 +89,9 @@ impl ConfigMerger {
             ConfigFormat::Toml => self.merge_toml(existing),
             ConfigFormat::Yaml => self.merge_yaml(existing),
             ConfigFormat::Plist => self.merge_plist(existing),
+            ConfigFormat::Xml => Err(anyhow!(
+                "XML configs are client-specific; merge them in the client plugin"
+            )),
         }
     }
 
@@ -72,7 +106,7 @@ impl ConfigMerger {
 
         // Fast path: check if already configured
         if let Some(servers) = config.get("mcpServers") {
-            if servers.get("sweetmcp").is_some() {
+            if !self.replace && servers.get(&self.server_name).is_some() {
                 return Ok(existing.to_string());
             }
         }
@@ -85,8 +119,8 @@ impl ConfigMerger {
 
             if let Some(servers) = obj.get_mut("mcpServers").and_then(|v| v.as_object_mut()) {
                 servers.insert(
-                    "sweetmcp".to_string(),
-                    self.sweetmcp_config.json_template["mcpServers"]["sweetmcp"].clone(),
+                    self.server_name.clone(),
+                    self.sweetmcp_config.json_template["mcpServers"][&self.server_name].clone(),
                 );
             }
         }
@@ -106,7 +140,7 @@ impl ConfigMerger {
         // Fast path: check if already configured
         if let Some(table) = config.as_table() {
             if let Some(servers) = table.get("mcpServers").and_then(|v| v.as_table()) {
-                if servers.contains_key("sweetmcp") {
+                if !self.replace && servers.contains_key(&self.server_name) {
                     return Ok(existing.to_string());
                 }
             }
@@ -123,8 +157,9 @@ impl ConfigMerger {
 
             if let Some(servers) = table.get_mut("mcpServers").and_then(|v| v.as_table_mut()) {
                 servers.insert(
-                    "sweetmcp".to_string(),
-                    self.sweetmcp_config.toml_template["mcpServers"]["sweetmcp"].clone(),
+                    self.server_name.clone(),
+                    self.sweetmcp_config.toml_template["mcpServers"][self.server_name.as_str()]
+                        .clone(),
                 );
             }
         }
@@ -1,9 +1,11 @@
 pub mod clients;
 pub mod config;
+pub mod profile;
 pub mod watcher;
 
 // Re-export commonly used types
 pub use config::ConfigMerger;
+pub use profile::{apply_profile, export_profile, parse_profile, Profile, ProfileFormat};
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
@@ -31,6 +33,17 @@ pub trait ClientConfigPlugin: Send + Sync {
 
     /// Get the default config format for this client
     fn config_format(&self) -> ConfigFormat;
+
+    /// Write the server described by `profile` into existing config,
+    /// replacing any SweetMCP entry already there
+    fn apply_profile(
+        &self,
+        config_content: &str,
+        format: ConfigFormat,
+        profile: &Profile,
+    ) -> Result<String> {
+        ConfigMerger::for_profile(profile)?.merge(config_content, format)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -46,6 +59,7 @@ pub enum ConfigFormat {
     Toml,
     Yaml,
     Plist,
+    Xml,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -0,0 +1,223 @@
+//! Provisioning profiles: a portable description of a SweetMCP endpoint that
+//! an IT team exports once and applies on every workstation, so all detected
+//! clients point at the same shared gateway.
+
+use crate::clients::all_clients;
+use crate::{ConfigFormat, SweetMCPConfig, SweetMCPHttpConfig};
+use anyhow::{anyhow, bail, Context, Result};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+/// How clients reach the server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "transport", rename_all = "kebab-case")]
+pub enum Transport {
+    /// Spawn a local command speaking MCP over stdin/stdout
+    Stdio {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    /// Connect to a Streamable HTTP endpoint, e.g. a team gateway
+    StreamableHttp { url: String },
+}
+
+/// Portable description of a SweetMCP endpoint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    /// Key of the server entry written into client configs
+    #[serde(default = "default_name")]
+    pub name: String,
+    #[serde(flatten)]
+    pub transport: Transport,
+    /// SHA-256 of the gateway's DER certificate as colon-separated hex, for
+    /// checking the certificate a workstation was handed out of band
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cert_fingerprint: Option<String>,
+}
+
+fn default_name() -> String {
+    "sweetmcp".to_string()
+}
+
+/// Serialization of an exported profile
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProfileFormat {
+    Json,
+    Toml,
+}
+
+impl Profile {
+    /// Profile for a Streamable HTTP gateway at `url`
+    pub fn http(url: impl Into<String>) -> Self {
+        Self {
+            name: default_name(),
+            transport: Transport::StreamableHttp { url: url.into() },
+            cert_fingerprint: None,
+        }
+    }
+
+    /// Profile for a locally installed server started over stdio
+    pub fn stdio(command: impl Into<String>, args: Vec<String>) -> Self {
+        Self {
+            name: default_name(),
+            transport: Transport::Stdio {
+                command: command.into(),
+                args,
+            },
+            cert_fingerprint: None,
+        }
+    }
+
+    /// Pin the gateway certificate given as PEM
+    pub fn with_cert_pem(mut self, pem: &str) -> Result<Self> {
+        self.cert_fingerprint = Some(cert_fingerprint(pem)?);
+        Ok(self)
+    }
+
+    /// `Ok` if `pem` is the certificate this profile pins, or if it pins none
+    pub fn check_cert(&self, pem: &str) -> Result<()> {
+        let Some(expected) = &self.cert_fingerprint else {
+            return Ok(());
+        };
+        let actual = cert_fingerprint(pem)?;
+        if !actual.eq_ignore_ascii_case(expected) {
+            bail!(
+                "certificate fingerprint {} does not match the profile's {}",
+                actual,
+                expected
+            );
+        }
+        Ok(())
+    }
+
+    /// The entry clients get under `mcpServers.<name>`
+    pub fn server_entry(&self) -> serde_json::Value {
+        match &self.transport {
+            Transport::Stdio { command, args } => serde_json::to_value(SweetMCPConfig {
+                command: command.clone(),
+                args: args.clone(),
+                env: Some(serde_json::json!({})),
+            }),
+            Transport::StreamableHttp { url } => serde_json::to_value(SweetMCPHttpConfig {
+                url: url.clone(),
+                ..SweetMCPHttpConfig::default()
+            }),
+        }
+        .unwrap_or_default()
+    }
+}
+
+/// SHA-256 fingerprint of the first certificate in `pem`
+pub fn cert_fingerprint(pem: &str) -> Result<String> {
+    let body: String = pem
+        .lines()
+        .skip_while(|line| !line.starts_with("-----BEGIN CERTIFICATE-----"))
+        .skip(1)
+        .take_while(|line| !line.starts_with("-----END CERTIFICATE-----"))
+        .map(str::trim)
+        .collect();
+    if body.is_empty() {
+        bail!("no PEM certificate found");
+    }
+    let der = base64::engine::general_purpose::STANDARD
+        .decode(body)
+        .context("certificate is not valid base64")?;
+    Ok(Sha256::digest(der)
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect::<Vec<_>>()
+        .join(":"))
+}
+
+/// Render `profile` for distribution
+pub fn export_profile(profile: &Profile, format: ProfileFormat) -> Result<String> {
+    Ok(match format {
+        ProfileFormat::Json => serde_json::to_string_pretty(profile)?,
+        ProfileFormat::Toml => toml::to_string_pretty(profile)?,
+    })
+}
+
+/// Parse an exported profile, JSON or TOML
+pub fn parse_profile(text: &str) -> Result<Profile> {
+    if text.trim_start().starts_with('{') {
+        serde_json::from_str(text).context("invalid JSON profile")
+    } else {
+        toml::from_str(text).context("invalid TOML profile")
+    }
+}
+
+/// What applying a profile did to one client config file
+#[derive(Debug)]
+pub struct AppliedConfig {
+    pub client_id: String,
+    pub path: PathBuf,
+    /// Whether the file changed; false when it already matched the profile
+    pub changed: bool,
+    pub error: Option<String>,
+}
+
+/// Point every detected client at the endpoint in `profile`, replacing any
+/// existing entry of the same name. Each changed file is backed up first.
+/// Failures are reported per file rather than stopping the rollout.
+pub fn apply_profile(profile: &Profile) -> Vec<AppliedConfig> {
+    let mut applied = Vec::new();
+    for client in all_clients() {
+        let installed = client
+            .watch_paths()
+            .iter()
+            .any(|path| client.is_installed(path));
+        if !installed {
+            continue;
+        }
+        for config_path in client.config_paths() {
+            let path = config_path.path;
+            let outcome = apply_to_file(client.as_ref(), &path, config_path.format, profile);
+            match &outcome {
+                Ok(true) => info!("Applied profile to {} at {:?}", client.client_name(), path),
+                Ok(false) => {}
+                Err(e) => warn!(
+                    "Failed to apply profile to {} at {:?}: {:#}",
+                    client.client_name(),
+                    path,
+                    e
+                ),
+            }
+            applied.push(AppliedConfig {
+                client_id: client.client_id().to_string(),
+                path,
+                changed: matches!(outcome, Ok(true)),
+                error: outcome.err().map(|e| format!("{:#}", e)),
+            });
+        }
+    }
+    applied
+}
+
+fn apply_to_file(
+    client: &dyn crate::ClientConfigPlugin,
+    path: &std::path::Path,
+    format: ConfigFormat,
+    profile: &Profile,
+) -> Result<bool> {
+    let existing = match std::fs::read_to_string(path) {
+        Ok(content) => Some(content),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(anyhow!(e).context("failed to read config")),
+    };
+    let updated = client.apply_profile(existing.as_deref().unwrap_or("{}"), format, profile)?;
+    if existing.as_deref() == Some(updated.as_str()) {
+        return Ok(false);
+    }
+
+    if existing.is_some() {
+        std::fs::copy(path, path.with_extension("backup")).context("failed to back up config")?;
+    } else if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, updated).context("failed to write config")?;
+    Ok(true)
+}
@@ -0,0 +1,687 @@
+//! `cyrupd config export` / `import`: move a working deployment to another
+//! machine in one archive.
+//!
+//! The archive is a gzipped tarball holding `manifest.json`, the daemon
+//! config, the service files from `services_dir`, the config and `.sig`
+//! files under `plugin_dir` with the SHA-256 of every wasm plugin (the
+//! plugins themselves only with `--with-plugins`), and any client
+//! provisioning profiles given on export.
+//!
+//! Secrets stay behind. Inline tokens are dropped, sensitive env vars are
+//! swapped for `secret://keychain/<service>/<VAR>` references, and the
+//! manifest lists everything that has to be provided again after import.
+
+use crate::config::ServiceConfig;
+use crate::security::artifacts::sha256_file;
+use crate::signing::config_sig::signature_path;
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use sweetmcp_client_autoconfig::profile::AppliedConfig;
+use sweetmcp_client_autoconfig::{apply_profile, parse_profile};
+
+const MANIFEST: &str = "manifest.json";
+const CONFIG: &str = "cyrupd.toml";
+const FORMAT: u32 = 1;
+
+/// Files carried over from `plugin_dir` besides the wasm plugins
+const PLUGIN_FILE_EXTENSIONS: [&str; 5] = ["toml", "json", "yaml", "yml", "sig"];
+
+/// Env vars whose name contains one of these are taken to hold a secret
+const SENSITIVE_NAMES: [&str; 8] = [
+    "TOKEN",
+    "SECRET",
+    "PASSWORD",
+    "PASSWD",
+    "PASSPHRASE",
+    "API_KEY",
+    "PRIVATE_KEY",
+    "CREDENTIAL",
+];
+
+/// Top-level config sections with a `token` / `token_file` pair
+const TOKEN_SECTIONS: [&str; 4] = ["admin", "sse", "reminders", "sandbox_reports"];
+
+/// Table of contents of an export archive
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Manifest {
+    pub format: u32,
+    pub created: DateTime<Utc>,
+    /// Config path on the exporting machine
+    pub source: String,
+    /// Service files, relative to `services_dir`
+    #[serde(default)]
+    pub services: Vec<String>,
+    /// Plugin config and signature files, relative to `plugin_dir`
+    #[serde(default)]
+    pub plugin_files: Vec<String>,
+    #[serde(default)]
+    pub plugins: Vec<PluginDigest>,
+    /// Client provisioning profiles, installed next to the config under
+    /// `profiles/`
+    #[serde(default)]
+    pub profiles: Vec<String>,
+    /// Secrets left out of the archive
+    #[serde(default)]
+    pub secrets: Vec<ExternalSecret>,
+}
+
+/// A wasm plugin as installed on the exporting machine
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PluginDigest {
+    /// Relative to `plugin_dir`
+    pub path: String,
+    pub sha256: String,
+    /// Whether `<plugin>.sig` was installed next to it
+    #[serde(default)]
+    pub signed: bool,
+    /// Whether the plugin itself is in the archive
+    #[serde(default)]
+    pub bundled: bool,
+}
+
+/// A secret that has to be provided again after import
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExternalSecret {
+    /// Where it is used, e.g. `admin.token` or `services.web.env_vars.API_TOKEN`
+    pub location: String,
+    /// How to provide it on the new machine
+    pub action: String,
+}
+
+pub struct ExportOptions {
+    pub output: PathBuf,
+    /// Bundle the wasm plugins too, not only their digests
+    pub with_plugins: bool,
+    /// Client provisioning profiles to carry along
+    pub profiles: Vec<PathBuf>,
+}
+
+/// Write the deployment configured at `cfg_path` to `opts.output`.
+pub fn export(cfg_path: &Path, opts: &ExportOptions) -> Result<Manifest> {
+    let raw =
+        fs::read_to_string(cfg_path).with_context(|| format!("read {}", cfg_path.display()))?;
+    let cfg: ServiceConfig =
+        toml::from_str(&raw).with_context(|| format!("parse {}", cfg_path.display()))?;
+    let mut doc: toml::Value = toml::from_str(&raw)?;
+
+    let mut manifest = Manifest {
+        format: FORMAT,
+        created: Utc::now(),
+        source: cfg_path.display().to_string(),
+        services: Vec::new(),
+        plugin_files: Vec::new(),
+        plugins: Vec::new(),
+        profiles: Vec::new(),
+        secrets: Vec::new(),
+    };
+    let mut files = Vec::new();
+
+    externalize_config(&mut doc, &mut manifest.secrets);
+    files.push((
+        CONFIG.to_string(),
+        toml::to_string_pretty(&doc)?.into_bytes(),
+    ));
+
+    if let Some(dir) = cfg.services_dir.as_deref().map(Path::new) {
+        if dir.is_dir() {
+            let mut paths: Vec<_> = fs::read_dir(dir)?
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.is_file() && p.extension().and_then(|s| s.to_str()) == Some("toml"))
+                .collect();
+            paths.sort();
+            for path in paths {
+                let name = archive_name(path.strip_prefix(dir)?);
+                let content = fs::read_to_string(&path)?;
+                let mut service: toml::Value = toml::from_str(&content)
+                    .with_context(|| format!("parse {}", path.display()))?;
+                externalize_service(&mut service, &mut manifest.secrets);
+                files.push((
+                    format!("services/{}", name),
+                    toml::to_string_pretty(&service)?.into_bytes(),
+                ));
+                manifest.services.push(name);
+            }
+        }
+    }
+
+    if let Some(dir) = cfg.plugin_dir.as_deref().map(Path::new) {
+        let entries = walkdir::WalkDir::new(dir)
+            .sort_by_file_name()
+            .into_iter()
+            .flatten()
+            .filter(|e| e.file_type().is_file());
+        for entry in entries {
+            let path = entry.path();
+            let name = archive_name(path.strip_prefix(dir)?);
+            match path.extension().and_then(|s| s.to_str()) {
+                Some("wasm") => {
+                    if opts.with_plugins {
+                        files.push((format!("plugins/{}", name), fs::read(path)?));
+                    }
+                    manifest.plugins.push(PluginDigest {
+                        path: name,
+                        sha256: sha256_file(path)?,
+                        signed: signature_path(path).is_file(),
+                        bundled: opts.with_plugins,
+                    });
+                }
+                Some(ext) if PLUGIN_FILE_EXTENSIONS.contains(&ext) => {
+                    files.push((format!("plugins/{}", name), fs::read(path)?));
+                    manifest.plugin_files.push(name);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    for path in &opts.profiles {
+        let content =
+            fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+        parse_profile(&content).with_context(|| format!("parse {}", path.display()))?;
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .with_context(|| format!("{} has no file name", path.display()))?;
+        if manifest.profiles.contains(&name) {
+            bail!("two profiles named {}", name);
+        }
+        files.push((format!("profiles/{}", name), content.into_bytes()));
+        manifest.profiles.push(name);
+    }
+
+    let mut archive = vec![(MANIFEST.to_string(), serde_json::to_vec_pretty(&manifest)?)];
+    archive.extend(files);
+    write_archive(&opts.output, &archive)?;
+    Ok(manifest)
+}
+
+pub struct ImportOptions {
+    pub archive: PathBuf,
+    /// Overwrite files whose contents differ from the archive
+    pub force: bool,
+    /// Report what would be written without touching anything
+    pub dry_run: bool,
+    /// Point detected MCP clients at the bundled profiles
+    pub apply_profiles: bool,
+}
+
+/// What an import did, or would do with `dry_run`
+pub struct ImportReport {
+    pub manifest: Manifest,
+    /// Files written, with the backup kept of any file they replaced
+    pub written: Vec<(PathBuf, Option<PathBuf>)>,
+    /// Files that already matched the archive
+    pub unchanged: Vec<PathBuf>,
+    /// Installed plugins that are missing or differ from the export
+    pub plugin_problems: Vec<String>,
+    pub applied: Vec<AppliedConfig>,
+    /// The config has to be signed again before the daemon will start it
+    pub needs_signature: bool,
+}
+
+/// Install the deployment in `opts.archive`, with its config at `cfg_path`.
+/// Service and plugin files go where the imported config says. Nothing is
+/// written unless every file in the archive can be.
+pub fn import(cfg_path: &Path, opts: &ImportOptions) -> Result<ImportReport> {
+    let mut entries = read_archive(&opts.archive)?;
+    let manifest: Manifest =
+        serde_json::from_slice(&take(&mut entries, MANIFEST)?).context("invalid manifest.json")?;
+    if manifest.format != FORMAT {
+        bail!(
+            "unsupported bundle format {} (this cyrupd reads {})",
+            manifest.format,
+            FORMAT
+        );
+    }
+
+    let config = take(&mut entries, CONFIG)?;
+    let cfg: ServiceConfig =
+        toml::from_str(std::str::from_utf8(&config)?).context("bundled cyrupd.toml is invalid")?;
+    let services_dir = cfg.services_dir.as_deref().map(PathBuf::from);
+    let plugin_dir = cfg.plugin_dir.as_deref().map(PathBuf::from);
+
+    let mut plan = vec![(cfg_path.to_path_buf(), config)];
+    for name in &manifest.services {
+        let dir = services_dir
+            .as_deref()
+            .context("bundle has service files but its config sets no services_dir")?;
+        plan.push((
+            dir.join(checked(name)?),
+            take(&mut entries, &format!("services/{}", name))?,
+        ));
+    }
+    for name in &manifest.plugin_files {
+        let dir = plugin_dir
+            .as_deref()
+            .context("bundle has plugin files but its config sets no plugin_dir")?;
+        plan.push((
+            dir.join(checked(name)?),
+            take(&mut entries, &format!("plugins/{}", name))?,
+        ));
+    }
+    for plugin in manifest.plugins.iter().filter(|p| p.bundled) {
+        let dir = plugin_dir
+            .as_deref()
+            .context("bundle has plugins but its config sets no plugin_dir")?;
+        let bytes = take(&mut entries, &format!("plugins/{}", plugin.path))?;
+        if format!("{:x}", Sha256::digest(&bytes)) != plugin.sha256 {
+            bail!("{} in the archive does not match its digest", plugin.path);
+        }
+        plan.push((dir.join(checked(&plugin.path)?), bytes));
+    }
+    let profile_dir = cfg_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join("profiles");
+    let mut profiles = Vec::new();
+    for name in &manifest.profiles {
+        let bytes = take(&mut entries, &format!("profiles/{}", name))?;
+        profiles.push(
+            parse_profile(std::str::from_utf8(&bytes)?)
+                .with_context(|| format!("bundled profile {}", name))?,
+        );
+        plan.push((profile_dir.join(checked(name)?), bytes));
+    }
+
+    let mut report = ImportReport {
+        manifest,
+        written: Vec::new(),
+        unchanged: Vec::new(),
+        plugin_problems: Vec::new(),
+        applied: Vec::new(),
+        needs_signature: cfg.require_signed_config,
+    };
+
+    // Decide everything before writing anything
+    let mut writes = Vec::new();
+    for (path, bytes) in plan {
+        match fs::read(&path) {
+            Ok(existing) if existing == bytes => report.unchanged.push(path),
+            Ok(_) if !opts.force => bail!(
+                "{} already exists with different contents; use --force to replace it",
+                path.display()
+            ),
+            Ok(_) => {
+                let mut backup = path.clone().into_os_string();
+                backup.push(".bak");
+                writes.push((path, bytes, Some(PathBuf::from(backup))));
+            }
+            Err(_) => writes.push((path, bytes, None)),
+        }
+    }
+
+    for (path, bytes, backup) in writes {
+        if !opts.dry_run {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("create {}", parent.display()))?;
+            }
+            if let Some(backup) = &backup {
+                fs::copy(&path, backup).with_context(|| format!("back up {}", path.display()))?;
+            }
+            fs::write(&path, bytes).with_context(|| format!("write {}", path.display()))?;
+        }
+        report.written.push((path, backup));
+    }
+
+    for plugin in report.manifest.plugins.iter().filter(|p| !p.bundled) {
+        let Some(dir) = plugin_dir.as_deref() else {
+            report
+                .plugin_problems
+                .push(format!("{}: no plugin_dir configured", plugin.path));
+            continue;
+        };
+        match sha256_file(&dir.join(&plugin.path)) {
+            Ok(sha256) if sha256 == plugin.sha256 => {}
+            Ok(sha256) => report.plugin_problems.push(format!(
+                "{}: installed sha256 {} differs from exported {}",
+                plugin.path, sha256, plugin.sha256
+            )),
+            Err(_) => report.plugin_problems.push(format!(
+                "{}: not installed (exported sha256 {})",
+                plugin.path, plugin.sha256
+            )),
+        }
+    }
+
+    if opts.apply_profiles && !opts.dry_run {
+        for profile in &profiles {
+            report.applied.extend(apply_profile(profile));
+        }
+    }
+
+    Ok(report)
+}
+
+/// Strip secrets from the daemon config, recording what has to be provided
+/// again.
+fn externalize_config(doc: &mut toml::Value, external: &mut Vec<ExternalSecret>) {
+    for section in TOKEN_SECTIONS {
+        if let Some(table) = doc.get_mut(section).and_then(|v| v.as_table_mut()) {
+            externalize_token(table, section, external);
+        }
+    }
+    if let Some(clients) = doc
+        .get_mut("secrets")
+        .and_then(|v| v.get_mut("clients"))
+        .and_then(|v| v.as_table_mut())
+    {
+        for (name, policy) in clients.iter_mut() {
+            if let Some(table) = policy.as_table_mut() {
+                externalize_token(table, &format!("secrets.clients.{}", name), external);
+            }
+        }
+    }
+    if let Some(services) = doc.get_mut("services").and_then(|v| v.as_array_mut()) {
+        for service in services {
+            externalize_service(service, external);
+        }
+    }
+}
+
+/// Drop an inline `token`. A `token_file` is kept but its contents are not
+/// bundled.
+fn externalize_token(table: &mut toml::Table, location: &str, external: &mut Vec<ExternalSecret>) {
+    if table.remove("token").is_some() {
+        external.push(ExternalSecret {
+            location: format!("{}.token", location),
+            action: "removed; set `token` or `token_file` again".into(),
+        });
+    }
+    if let Some(path) = table.get("token_file").and_then(|v| v.as_str()) {
+        external.push(ExternalSecret {
+            location: format!("{}.token_file", location),
+            action: format!("copy {}", path),
+        });
+    }
+}
+
+/// Replace sensitive env vars and the discovery token of one service
+/// definition with keychain references.
+fn externalize_service(service: &mut toml::Value, external: &mut Vec<ExternalSecret>) {
+    let name = service
+        .get("name")
+        .and_then(|v| v.as_str())
+        .unwrap_or("unnamed")
+        .to_string();
+
+    if let Some(vars) = service.get_mut("env_vars").and_then(|v| v.as_table_mut()) {
+        for (key, value) in vars.iter_mut() {
+            let location = format!("services.{}.env_vars.{}", name, key);
+            externalize_value(value, is_sensitive(key), &name, key, location, external);
+        }
+    }
+    if let Some(deploy) = service.get_mut("deploy").and_then(|v| v.as_table_mut()) {
+        if let Some(value) = deploy.get_mut("discovery_token") {
+            let location = format!("services.{}.deploy.discovery_token", name);
+            externalize_value(value, true, &name, "discovery_token", location, external);
+        }
+    }
+}
+
+fn externalize_value(
+    value: &mut toml::Value,
+    sensitive: bool,
+    service: &str,
+    key: &str,
+    location: String,
+    external: &mut Vec<ExternalSecret>,
+) {
+    let Some(text) = value.as_str() else {
+        return;
+    };
+    if text.starts_with(crate::secrets::SCHEME) {
+        external.push(ExternalSecret {
+            location,
+            action: how_to_provide(text),
+        });
+    } else if sensitive {
+        let reference = format!("{}keychain/{}/{}", crate::secrets::SCHEME, service, key);
+        external.push(ExternalSecret {
+            location,
+            action: how_to_provide(&reference),
+        });
+        *value = toml::Value::String(reference);
+    }
+}
+
+fn how_to_provide(reference: &str) -> String {
+    match crate::secrets::parse_ref(reference) {
+        Some(("keychain", name)) => format!("cyrupd secret set {}", name),
+        Some(("file", name)) => format!("create secret file {}", name),
+        Some(("env", name)) => format!("export {}", name),
+        _ => format!("provide {}", reference),
+    }
+}
+
+fn is_sensitive(key: &str) -> bool {
+    let key = key.to_ascii_uppercase();
+    SENSITIVE_NAMES.iter().any(|s| key.contains(s))
+}
+
+/// `a/b.toml` with forward slashes whatever the platform
+fn archive_name(rel: &Path) -> String {
+    rel.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// A manifest path, refusing anything that could land outside its directory
+fn checked(name: &str) -> Result<&Path> {
+    let path = Path::new(name);
+    if name.is_empty() || !path.components().all(|c| matches!(c, Component::Normal(_))) {
+        bail!("unsafe path {:?} in bundle", name);
+    }
+    Ok(path)
+}
+
+fn take(entries: &mut HashMap<String, Vec<u8>>, name: &str) -> Result<Vec<u8>> {
+    entries
+        .remove(name)
+        .with_context(|| format!("archive has no {}", name))
+}
+
+fn write_archive(path: &Path, files: &[(String, Vec<u8>)]) -> Result<()> {
+    let file = fs::File::create(path).with_context(|| format!("create {}", path.display()))?;
+    let mut tar = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    let mtime = Utc::now().timestamp().max(0) as u64;
+    for (name, bytes) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(bytes.len() as u64);
+        header.set_mode(0o600);
+        header.set_mtime(mtime);
+        tar.append_data(&mut header, name, bytes.as_slice())?;
+    }
+    tar.into_inner()?.finish()?;
+    Ok(())
+}
+
+fn read_archive(path: &Path) -> Result<HashMap<String, Vec<u8>>> {
+    let file = fs::File::open(path).with_context(|| format!("open {}", path.display()))?;
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+    let mut entries = HashMap::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = entry.path()?.to_string_lossy().into_owned();
+        let mut bytes = Vec::new();
+        entry.read_to_end(&mut bytes)?;
+        entries.insert(name, bytes);
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("cyrupd-bundle-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn secrets_are_externalized() {
+        let mut doc: toml::Value = toml::from_str(
+            r#"
+services = []
+
+[admin]
+bind = "127.0.0.1:9090"
+token = "hunter2"
+
+[sse]
+token_file = "/etc/cyrupd/sse.token"
+
+[secrets.clients.ci]
+token = "ci-token"
+"#,
+        )
+        .unwrap();
+        let mut service: toml::Value = toml::from_str(
+            r#"
+name = "web"
+command = "web"
+
+[env_vars]
+PORT = "8080"
+API_TOKEN = "abc"
+DB_PASSWORD = "secret://file/db"
+
+[deploy]
+slots = []
+discovery_token = "xyz"
+"#,
+        )
+        .unwrap();
+
+        let mut external = Vec::new();
+        externalize_config(&mut doc, &mut external);
+        externalize_service(&mut service, &mut external);
+
+        assert!(doc["admin"].get("token").is_none());
+        assert!(doc["secrets"]["clients"]["ci"].get("token").is_none());
+        assert_eq!(
+            doc["sse"]["token_file"].as_str(),
+            Some("/etc/cyrupd/sse.token")
+        );
+        assert_eq!(service["env_vars"]["PORT"].as_str(), Some("8080"));
+        assert_eq!(
+            service["env_vars"]["API_TOKEN"].as_str(),
+            Some("secret://keychain/web/API_TOKEN")
+        );
+        assert_eq!(
+            service["env_vars"]["DB_PASSWORD"].as_str(),
+            Some("secret://file/db")
+        );
+        assert_eq!(
+            service["deploy"]["discovery_token"].as_str(),
+            Some("secret://keychain/web/discovery_token")
+        );
+
+        let actions: HashMap<_, _> = external
+            .iter()
+            .map(|s| (s.location.as_str(), s.action.as_str()))
+            .collect();
+        assert_eq!(actions.len(), 6);
+        assert_eq!(actions["sse.token_file"], "copy /etc/cyrupd/sse.token");
+        assert_eq!(
+            actions["services.web.env_vars.API_TOKEN"],
+            "cyrupd secret set web/API_TOKEN"
+        );
+        assert_eq!(
+            actions["services.web.env_vars.DB_PASSWORD"],
+            "create secret file db"
+        );
+    }
+
+    #[test]
+    fn export_then_import_on_a_clean_machine() {
+        let root = scratch("roundtrip");
+        let services = root.join("services");
+        let plugins = root.join("plugins");
+        fs::create_dir_all(&services).unwrap();
+        fs::create_dir_all(plugins.join("fetch")).unwrap();
+        let cfg_path = root.join("cyrupd.toml");
+        fs::write(
+            &cfg_path,
+            format!(
+                "services_dir = {:?}\nplugin_dir = {:?}\nservices = []\n\n[admin]\nbind = \"127.0.0.1:9090\"\ntoken = \"hunter2\"\n",
+                services.display().to_string(),
+                plugins.display().to_string()
+            ),
+        )
+        .unwrap();
+        fs::write(
+            services.join("web.toml"),
+            "name = \"web\"\ncommand = \"web\"\n\n[env_vars]\nSECRET_KEY = \"s3cr3t\"\n",
+        )
+        .unwrap();
+        fs::write(plugins.join("fetch/fetch.wasm"), b"\0asm wasm").unwrap();
+        fs::write(plugins.join("fetch/fetch.toml"), "timeout = 5\n").unwrap();
+        fs::write(plugins.join("fetch/notes.txt"), "ignored").unwrap();
+
+        let archive = root.join("bundle.tar.gz");
+        let manifest = export(
+            &cfg_path,
+            &ExportOptions {
+                output: archive.clone(),
+                with_plugins: true,
+                profiles: Vec::new(),
+            },
+        )
+        .unwrap();
+        assert_eq!(manifest.services, vec!["web.toml"]);
+        assert_eq!(manifest.plugin_files, vec!["fetch/fetch.toml"]);
+        assert_eq!(manifest.plugins.len(), 1);
+        assert_eq!(manifest.plugins[0].path, "fetch/fetch.wasm");
+
+        // The new machine has none of it
+        fs::remove_dir_all(&services).unwrap();
+        fs::remove_dir_all(&plugins).unwrap();
+        let new_cfg = root.join("new").join("cyrupd.toml");
+        let opts = ImportOptions {
+            archive,
+            force: false,
+            dry_run: false,
+            apply_profiles: false,
+        };
+        let report = import(&new_cfg, &opts).unwrap();
+        assert_eq!(report.written.len(), 4);
+        assert!(report.plugin_problems.is_empty());
+
+        let config = fs::read_to_string(&new_cfg).unwrap();
+        assert!(!config.contains("hunter2"));
+        let service = fs::read_to_string(services.join("web.toml")).unwrap();
+        assert!(service.contains("secret://keychain/web/SECRET_KEY"));
+        assert!(!service.contains("s3cr3t"));
+        assert_eq!(
+            fs::read(plugins.join("fetch/fetch.wasm")).unwrap(),
+            b"\0asm wasm"
+        );
+        assert!(!plugins.join("fetch/notes.txt").exists());
+
+        // Importing again changes nothing; local edits need --force
+        let report = import(&new_cfg, &opts).unwrap();
+        assert!(report.written.is_empty());
+        assert_eq!(report.unchanged.len(), 4);
+        fs::write(&new_cfg, "services = []\n").unwrap();
+        assert!(import(&new_cfg, &opts).is_err());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
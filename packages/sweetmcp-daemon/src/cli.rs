@@ -24,6 +24,131 @@ pub enum Cmd {
         #[arg(long, conflicts_with = "config")]
         system: bool,
     },
+    /// Query the event journal
+    Events {
+        /// Only events newer than this age (e.g. 30m, 1h, 2d) or RFC 3339 time
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Only events for this service
+        #[arg(long)]
+        service: Option<String>,
+
+        /// Print raw JSON lines instead of a table
+        #[arg(long)]
+        json: bool,
+
+        /// Path to configuration file
+        #[arg(long, short = 'c')]
+        config: Option<String>,
+
+        /// Use system-wide config (/etc/cyrupd/cyrupd.toml)
+        #[arg(long, conflicts_with = "config")]
+        system: bool,
+    },
+    /// List crash reports of services that died unexpectedly
+    Crashes {
+        /// Only crashes of this service
+        #[arg(long)]
+        service: Option<String>,
+
+        /// Show at most this many of the most recent crashes
+        #[arg(long, short = 'n')]
+        last: Option<usize>,
+
+        /// Include the captured stderr tail of each crash
+        #[arg(long)]
+        stderr: bool,
+
+        /// Print full reports as JSON lines instead of a table
+        #[arg(long)]
+        json: bool,
+
+        /// Path to configuration file
+        #[arg(long, short = 'c')]
+        config: Option<String>,
+
+        /// Use system-wide config (/etc/cyrupd/cyrupd.toml)
+        #[arg(long, conflicts_with = "config")]
+        system: bool,
+    },
+    /// Blue/green deploy a new binary for a running service
+    Deploy {
+        /// Service to roll onto the new binary
+        service: String,
+
+        /// New executable; replaces the first word of the service command
+        #[arg(long)]
+        binary: String,
+
+        /// Seconds to wait for the new instance to take over
+        #[arg(long, default_value_t = 300)]
+        timeout: u64,
+
+        /// Path to configuration file
+        #[arg(long, short = 'c')]
+        config: Option<String>,
+
+        /// Use system-wide config (/etc/cyrupd/cyrupd.toml)
+        #[arg(long, conflicts_with = "config")]
+        system: bool,
+    },
+    /// Check the host for common installation problems and print fixes
+    Doctor {
+        /// Print findings as JSON lines instead of a table
+        #[arg(long)]
+        json: bool,
+
+        /// Path to configuration file
+        #[arg(long, short = 'c')]
+        config: Option<String>,
+
+        /// Use system-wide config (/etc/cyrupd/cyrupd.toml)
+        #[arg(long, conflicts_with = "config")]
+        system: bool,
+    },
+    /// Manage secrets referenced as `secret://` in service env vars
+    Secret {
+        #[command(subcommand)]
+        action: SecretCmd,
+
+        /// Path to configuration file
+        #[arg(long, short = 'c', global = true)]
+        config: Option<String>,
+
+        /// Use system-wide config (/etc/cyrupd/cyrupd.toml)
+        #[arg(long, conflicts_with = "config", global = true)]
+        system: bool,
+    },
+    /// Export or import the whole deployment as one archive
+    Config {
+        #[command(subcommand)]
+        action: ConfigCmd,
+
+        /// Path to configuration file
+        #[arg(long, short = 'c', global = true)]
+        config: Option<String>,
+
+        /// Use system-wide config (/etc/cyrupd/cyrupd.toml)
+        #[arg(long, conflicts_with = "config", global = true)]
+        system: bool,
+    },
+    /// Run the gateway, MCP host and plugins as the current user, with
+    /// ephemeral ports and an in-memory database, for development
+    Dev {
+        /// Directory of `*.wasm` plugins for the MCP host to load
+        #[arg(long)]
+        plugins: Option<String>,
+
+        /// Directory holding sweetmcp_server and sweetmcp-axum (default:
+        /// the one cyrupd runs from)
+        #[arg(long)]
+        bin_dir: Option<String>,
+
+        /// Keep the scratch directory with logs and crash reports on exit
+        #[arg(long)]
+        keep: bool,
+    },
     /// Copy binary, create users/dirs, register with init, start service
     Install {
         /// Don't enable & start the unit—copy files only
@@ -37,6 +162,10 @@ pub enum Cmd {
         /// Override signing identity (default: ad‑hoc)
         #[arg(long)]
         identity: Option<String>,
+
+        /// Choose ports, TLS, plugin directory and clients in a terminal UI
+        #[arg(long, short = 'i')]
+        interactive: bool,
     },
     /// Uninstall the daemon service
     Uninstall {
@@ -50,7 +179,7 @@ pub enum Cmd {
         #[arg(long)]
         binary: Option<String>,
 
-        /// Signing identity (macOS) or certificate (Windows)
+        /// Signing identity (macOS), certificate (Windows) or GPG key (config)
         #[arg(long)]
         identity: Option<String>,
 
@@ -65,5 +194,60 @@ pub enum Cmd {
         /// Sign the currently running binary (self-sign)
         #[arg(long)]
         self_sign: bool,
+
+        /// Sign (or with --verify, check) a cyrupd.toml instead of a binary
+        #[arg(long, conflicts_with_all = ["binary", "self_sign", "show_config"])]
+        config: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SecretCmd {
+    /// Store a secret in the OS keychain (value read from stdin)
+    Set {
+        /// Name used in `secret://keychain/<name>`
+        name: String,
+    },
+    /// Remove a secret from the OS keychain
+    Delete {
+        /// Name used in `secret://keychain/<name>`
+        name: String,
+    },
+    /// Resolve every `secret://` reference without printing values
+    Check,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCmd {
+    /// Bundle the config, service files, plugin configs and digests and
+    /// client profiles into a .tar.gz, leaving secrets out
+    Export {
+        /// Archive to write
+        output: String,
+
+        /// Include the wasm plugins themselves, not only their digests
+        #[arg(long)]
+        with_plugins: bool,
+
+        /// Client provisioning profile to include (repeatable)
+        #[arg(long)]
+        profile: Vec<String>,
+    },
+    /// Install a bundle written by `cyrupd config export`
+    Import {
+        /// Archive to read
+        archive: String,
+
+        /// Replace files that differ from the archive (keeping `.bak` copies)
+        #[arg(long)]
+        force: bool,
+
+        /// Show what would be written without writing it
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Point detected MCP clients at the bundled profiles
+        #[arg(long)]
+        apply_profiles: bool,
     },
 }
@@ -13,6 +13,295 @@ pub struct ServiceConfig {
     pub sse: Option<SseServerConfig>,
     /// MCP Streamable HTTP transport binding (host:port)
     pub mcp_bind: Option<String>,
+    /// Local admin HTTP API for dashboards
+    #[serde(default)]
+    pub admin: Option<AdminApiConfig>,
+    /// Event journal file (defaults to `<log_dir>/events.jsonl`)
+    #[serde(default)]
+    pub journal_file: Option<String>,
+    /// Where `secret://` references in service env vars are looked up
+    #[serde(default)]
+    pub secrets: Option<SecretsConfig>,
+    /// Operator intent (which services were stopped on purpose); defaults to
+    /// `<data dir>/cyrupd/state.json`
+    #[serde(default)]
+    pub state_file: Option<String>,
+    /// Refuse to start services unless `<config>.sig` verifies
+    #[serde(default)]
+    pub require_signed_config: bool,
+    /// Exported GPG public key that alone may sign this config
+    #[serde(default)]
+    pub config_signing_key: Option<String>,
+    /// Directory wasm plugins are installed to and loaded from
+    #[serde(default)]
+    pub plugin_dir: Option<String>,
+    /// Extra directory of client-config plugins (`*.wasm`), searched before
+    /// the built-in tool-configurator locations
+    #[serde(default)]
+    pub tool_configurators_dir: Option<String>,
+    /// Where crash reports are kept; defaults to `<data dir>/cyrupd/crashes`
+    #[serde(default)]
+    pub crash_dir: Option<String>,
+    /// Periodic hash scan of plugins and service binaries
+    #[serde(default)]
+    pub security_scan: Option<SecurityScanConfig>,
+    /// Reminders scheduled through the admin API's `/reminders`
+    #[serde(default)]
+    pub reminders: Option<RemindersConfig>,
+    /// Sandbox denials reported by sweetmcp-axum through `/security/sandbox`
+    #[serde(default)]
+    pub sandbox_reports: Option<SandboxReportsConfig>,
+    /// Fallback values for `${NAME}` references in service definitions
+    #[serde(default)]
+    pub vars: HashMap<String, String>,
+}
+
+impl ServiceConfig {
+    /// Where lifecycle events are journaled, if anywhere.
+    pub fn journal_path(&self) -> Option<std::path::PathBuf> {
+        match (&self.journal_file, &self.log_dir) {
+            (Some(file), _) => Some(file.into()),
+            (None, Some(dir)) => Some(std::path::Path::new(dir).join("events.jsonl")),
+            (None, None) => None,
+        }
+    }
+
+    /// Location of the persisted desired state, if one can be determined.
+    pub fn state_path(&self) -> Option<std::path::PathBuf> {
+        match &self.state_file {
+            Some(file) => Some(file.into()),
+            None => dirs::data_local_dir().map(|dir| dir.join("cyrupd").join("state.json")),
+        }
+    }
+
+    /// Directory crash reports are written to, if one can be determined.
+    pub fn crash_path(&self) -> Option<std::path::PathBuf> {
+        match &self.crash_dir {
+            Some(dir) => Some(dir.into()),
+            None => dirs::data_local_dir().map(|dir| dir.join("cyrupd").join("crashes")),
+        }
+    }
+}
+
+/// Scheduled scan of installed artifacts against a known-bad hash list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecurityScanConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Seconds between scans (minimum 60)
+    #[serde(default = "default_scan_interval")]
+    pub interval_secs: u64,
+    /// File of known-bad SHA-256 hashes, one per line with an optional note
+    pub known_bad: Option<String>,
+    /// Where `latest.json` and `latest.sarif` are written; defaults to
+    /// `<data dir>/cyrupd/security`
+    pub report_dir: Option<String>,
+    /// Additional files or directories to scan
+    #[serde(default)]
+    pub paths: Vec<String>,
+}
+
+impl SecurityScanConfig {
+    pub fn report_path(&self) -> Option<std::path::PathBuf> {
+        match &self.report_dir {
+            Some(dir) => Some(dir.into()),
+            None => dirs::data_local_dir().map(|dir| dir.join("cyrupd").join("security")),
+        }
+    }
+}
+
+impl Default for SecurityScanConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_true(),
+            interval_secs: default_scan_interval(),
+            known_bad: None,
+            report_dir: None,
+            paths: Vec::new(),
+        }
+    }
+}
+
+fn default_scan_interval() -> u64 {
+    6 * 60 * 60
+}
+
+/// Secret providers for `secret://<provider>/<name>` env var values
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SecretsConfig {
+    /// Directory for `secret://file/<name>`; absolute names bypass it
+    pub dir: Option<String>,
+    /// Keychain service name for `secret://keychain/<name>` (default "cyrupd")
+    pub keychain_service: Option<String>,
+    /// Index of secrets stored through the admin API's `/secrets`; defaults
+    /// to `<data dir>/cyrupd/vault.json`. Values stay in the keychain.
+    #[serde(default)]
+    pub vault_index: Option<String>,
+    /// Per-client access to `/secrets`, keyed by client name. The admin
+    /// token may do everything; a client token only what its policy allows.
+    #[serde(default)]
+    pub clients: HashMap<String, SecretClientPolicy>,
+}
+
+impl SecretsConfig {
+    /// Location of the vault index, if one can be determined.
+    pub fn vault_index_path(&self) -> Option<std::path::PathBuf> {
+        match &self.vault_index {
+            Some(file) => Some(file.into()),
+            None => dirs::data_local_dir().map(|dir| dir.join("cyrupd").join("vault.json")),
+        }
+    }
+}
+
+/// What one client may do with vault secrets. Patterns are secret names
+/// where `*` matches any run of characters, e.g. `github/*`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SecretClientPolicy {
+    /// Bearer token the client presents
+    pub token: Option<String>,
+    /// File holding the bearer token (used when `token` is unset)
+    pub token_file: Option<String>,
+    /// Secrets the client may list and get as redacted references
+    #[serde(default)]
+    pub read: Vec<String>,
+    /// Secrets whose plaintext the client may ask for
+    #[serde(default)]
+    pub reveal: Vec<String>,
+    /// Secrets the client may create or replace
+    #[serde(default)]
+    pub write: Vec<String>,
+}
+
+impl SecretClientPolicy {
+    pub fn resolve_token(&self) -> anyhow::Result<String> {
+        let token = match (&self.token, &self.token_file) {
+            (Some(token), _) => token.clone(),
+            (None, Some(path)) => std::fs::read_to_string(path)
+                .map_err(|e| anyhow::anyhow!("read secret client token file {}: {}", path, e))?,
+            (None, None) => anyhow::bail!("secret client requires `token` or `token_file`"),
+        };
+        let token = token.trim().to_string();
+        if token.is_empty() {
+            anyhow::bail!("secret client token is empty");
+        }
+        Ok(token)
+    }
+}
+
+/// Reminders kept by the daemon and fired when due
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RemindersConfig {
+    /// Where reminders are saved; defaults to `<data dir>/cyrupd/reminders.json`
+    pub store: Option<String>,
+    /// Bearer token that may use `/reminders` besides the admin token, e.g.
+    /// for the reminders plugin
+    pub token: Option<String>,
+    /// File holding that token (used when `token` is unset)
+    pub token_file: Option<String>,
+    /// MCP server whose notify tool delivers reminders that name a
+    /// destination; defaults to the SSE bridge's `mcp_server_url`
+    pub mcp_server_url: Option<String>,
+    /// Tool called to deliver them (default "send_message")
+    pub notify_tool: Option<String>,
+}
+
+impl RemindersConfig {
+    /// Location of the saved reminders, if one can be determined.
+    pub fn store_path(&self) -> Option<std::path::PathBuf> {
+        match &self.store {
+            Some(file) => Some(file.into()),
+            None => dirs::data_local_dir().map(|dir| dir.join("cyrupd").join("reminders.json")),
+        }
+    }
+
+    /// The reminders client token, if one is configured.
+    pub fn resolve_token(&self) -> anyhow::Result<Option<String>> {
+        let token = match (&self.token, &self.token_file) {
+            (Some(token), _) => token.clone(),
+            (None, Some(path)) => std::fs::read_to_string(path)
+                .map_err(|e| anyhow::anyhow!("read reminders token file {}: {}", path, e))?,
+            (None, None) => return Ok(None),
+        };
+        let token = token.trim().to_string();
+        if token.is_empty() {
+            anyhow::bail!("reminders token is empty");
+        }
+        Ok(Some(token))
+    }
+}
+
+/// Reporting of sandbox denials from eval plugins
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SandboxReportsConfig {
+    /// Bearer token that may use `/security/sandbox` besides the admin token,
+    /// e.g. for the MCP server's sandbox telemetry
+    pub token: Option<String>,
+    /// File holding that token (used when `token` is unset)
+    pub token_file: Option<String>,
+}
+
+impl SandboxReportsConfig {
+    /// The reporter token, if one is configured.
+    pub fn resolve_token(&self) -> anyhow::Result<Option<String>> {
+        let token = match (&self.token, &self.token_file) {
+            (Some(token), _) => token.clone(),
+            (None, Some(path)) => std::fs::read_to_string(path)
+                .map_err(|e| anyhow::anyhow!("read sandbox reports token file {}: {}", path, e))?,
+            (None, None) => return Ok(None),
+        };
+        let token = token.trim().to_string();
+        if token.is_empty() {
+            anyhow::bail!("sandbox reports token is empty");
+        }
+        Ok(Some(token))
+    }
+}
+
+/// Local admin HTTP API configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdminApiConfig {
+    /// Enable the admin listener
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// `127.0.0.1:<port>` or `unix:/path/to/admin.sock`
+    #[serde(default = "default_admin_listen")]
+    pub listen: String,
+    /// Bearer token required on every request
+    pub token: Option<String>,
+    /// File holding the bearer token (used when `token` is unset)
+    pub token_file: Option<String>,
+}
+
+impl AdminApiConfig {
+    /// Resolve the bearer token from `token` or `token_file`.
+    pub fn resolve_token(&self) -> anyhow::Result<String> {
+        let token = match (&self.token, &self.token_file) {
+            (Some(token), _) => token.clone(),
+            (None, Some(path)) => std::fs::read_to_string(path)
+                .map_err(|e| anyhow::anyhow!("read admin token file {}: {}", path, e))?,
+            (None, None) => anyhow::bail!("admin API requires `token` or `token_file`"),
+        };
+        let token = token.trim().to_string();
+        if token.is_empty() {
+            anyhow::bail!("admin API token is empty");
+        }
+        Ok(token)
+    }
+}
+
+impl Default for AdminApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_true(),
+            listen: default_admin_listen(),
+            token: None,
+            token_file: None,
+        }
+    }
+}
+
+fn default_admin_listen() -> String {
+    "127.0.0.1:33401".to_string()
 }
 
 /// SSE server configuration
@@ -39,6 +328,45 @@ pub struct SseServerConfig {
     /// CORS allowed origins
     #[serde(default = "default_cors_origins")]
     pub cors_origins: Vec<String>,
+    /// Bearer token clients must present (header or `?token=`)
+    #[serde(default)]
+    pub token: Option<String>,
+    /// File holding the token (used when `token` is unset)
+    #[serde(default)]
+    pub token_file: Option<String>,
+}
+
+impl SseServerConfig {
+    /// Resolve the token from `token` or `token_file`; `None` if neither is set.
+    pub fn resolve_token(&self) -> anyhow::Result<Option<String>> {
+        let token = match (&self.token, &self.token_file) {
+            (Some(token), _) => token.clone(),
+            (None, Some(path)) => std::fs::read_to_string(path)
+                .map_err(|e| anyhow::anyhow!("read SSE token file {}: {}", path, e))?,
+            (None, None) => return Ok(None),
+        };
+        let token = token.trim().to_string();
+        if token.is_empty() {
+            anyhow::bail!("SSE token is empty");
+        }
+        Ok(Some(token))
+    }
+}
+
+fn default_probe_interval() -> u64 {
+    60
+}
+
+fn default_probe_timeout() -> u64 {
+    5
+}
+
+fn default_failure_threshold() -> u32 {
+    3
+}
+
+fn default_success_threshold() -> u32 {
+    1
 }
 
 fn default_true() -> bool {
@@ -73,6 +401,8 @@ impl Default for SseServerConfig {
             ping_interval: default_ping_interval(),
             session_timeout: default_session_timeout(),
             cors_origins: default_cors_origins(),
+            token: None,
+            token_file: None,
         }
     }
 }
@@ -86,6 +416,7 @@ impl From<SseServerConfig> for crate::service::sse::SseConfig {
             ping_interval: config.ping_interval,
             session_timeout: config.session_timeout,
             cors_origins: config.cors_origins,
+            token: config.token,
         }
     }
 }
@@ -101,6 +432,19 @@ impl Default for ServiceConfig {
             services: vec![],
             sse: Some(SseServerConfig::default()),
             mcp_bind: Some("0.0.0.0:33399".into()),
+            admin: None,
+            journal_file: None,
+            secrets: None,
+            state_file: None,
+            require_signed_config: false,
+            config_signing_key: None,
+            plugin_dir: None,
+            tool_configurators_dir: None,
+            crash_dir: None,
+            security_scan: None,
+            reminders: None,
+            sandbox_reports: None,
+            vars: HashMap::new(),
         }
     }
 }
@@ -112,6 +456,18 @@ pub struct ServiceDefinition {
     pub description: Option<String>,
     pub command: String,
     pub working_dir: Option<String>,
+    /// File mode creation mask as octal, e.g. `"027"` (unix only)
+    #[serde(default)]
+    pub umask: Option<String>,
+    /// Scheduling priority from -20 (highest) to 19 (lowest); negative
+    /// values need root (unix only)
+    #[serde(default)]
+    pub nice: Option<i32>,
+    /// I/O scheduling class: `"idle"`, `"best-effort[:0-7]"` or
+    /// `"realtime[:0-7]"` (Linux only)
+    #[serde(default)]
+    pub ionice: Option<String>,
+    /// Values may use `${NAME}` interpolation (see `template`)
     #[serde(default)]
     pub env_vars: HashMap<String, String>,
     #[serde(default)]
@@ -125,12 +481,142 @@ pub struct ServiceDefinition {
     pub health_check: Option<HealthCheckConfig>,
     #[serde(default)]
     pub log_rotation: Option<LogRotationConfig>,
+    /// File receiving the service's stdout/stderr. Defaults to
+    /// `<log_dir>/<name>.log` when the daemon has a `log_dir`.
+    #[serde(default)]
+    pub log_file: Option<String>,
     #[serde(default)]
     pub watch_dirs: Vec<String>,
     pub ephemeral_dir: Option<String>,
     /// Service type (e.g., "autoconfig" for special handling)
     pub service_type: Option<String>,
     pub memfs: Option<MemoryFsConfig>,
+    /// cgroup v2 resource limits (Linux only; ignored elsewhere)
+    #[serde(default)]
+    pub resources: Option<ResourceLimits>,
+    /// Listening sockets the daemon binds once and passes to every instance
+    /// (`LISTEN_FDS` protocol), e.g. `"0.0.0.0:8443"` or `"unix:/run/app.sock"`
+    #[serde(default)]
+    pub sockets: Vec<String>,
+    #[serde(default)]
+    pub upgrade: UpgradeConfig,
+    #[serde(default)]
+    pub crash: CrashConfig,
+    /// Alternate between two slots on upgrade and switch peer registries over
+    #[serde(default)]
+    pub deploy: Option<DeployConfig>,
+    /// Values for `${NAME}` references, checked before the daemon's `[vars]`
+    #[serde(default)]
+    pub vars: HashMap<String, String>,
+}
+
+/// What is kept when a service dies unexpectedly.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashConfig {
+    /// How much of the service's final stderr output goes into a crash report
+    #[serde(default = "default_crash_tail_kb")]
+    pub tail_kb: usize,
+    /// Copy the core file, crash log or minidump of a crash into
+    /// `<crash_dir>/<service>/`
+    #[serde(default = "default_true")]
+    pub collect_dumps: bool,
+    /// Dumps larger than this stay where the system put them
+    #[serde(default = "default_max_dump_mb")]
+    pub max_dump_mb: u64,
+    /// Linux `/proc/<pid>/coredump_filter` mask for the service, e.g. "0x33"
+    #[serde(default)]
+    pub coredump_filter: Option<String>,
+    /// Resolved from the daemon's `crash_dir` when the service is loaded
+    #[serde(skip)]
+    pub dir: Option<std::path::PathBuf>,
+}
+
+impl Default for CrashConfig {
+    fn default() -> Self {
+        Self {
+            tail_kb: default_crash_tail_kb(),
+            collect_dumps: true,
+            max_dump_mb: default_max_dump_mb(),
+            coredump_filter: None,
+            dir: None,
+        }
+    }
+}
+
+fn default_crash_tail_kb() -> usize {
+    64
+}
+
+fn default_max_dump_mb() -> u64 {
+    1024
+}
+
+/// Timing of a zero‑downtime binary swap (`Cmd::Upgrade`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpgradeConfig {
+    /// How long the new instance has to pass its health check
+    #[serde(default = "default_upgrade_ready_timeout")]
+    pub ready_timeout_secs: u64,
+    /// Grace period between SIGTERM and SIGKILL for the old instance
+    #[serde(default = "default_upgrade_drain")]
+    pub drain_secs: u64,
+}
+
+impl Default for UpgradeConfig {
+    fn default() -> Self {
+        Self {
+            ready_timeout_secs: default_upgrade_ready_timeout(),
+            drain_secs: default_upgrade_drain(),
+        }
+    }
+}
+
+/// Blue/green slots for services whose instances cannot share a listener.
+///
+/// Each upgrade starts the new instance in the idle slot, waits for its
+/// `health_url`, re-points the gateway peer registries from the old slot's
+/// `advertise` address to the new one, then drains the old instance.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeployConfig {
+    /// Exactly two slots (blue, green)
+    pub slots: Vec<DeploySlot>,
+    /// Base URLs of gateways whose peer registry should follow the switch
+    #[serde(default)]
+    pub registries: Vec<String>,
+    /// `x-discovery-token` for those gateways; may be a `secret://` reference
+    #[serde(default)]
+    pub discovery_token: Option<String>,
+}
+
+/// One side of a blue/green pair
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeploySlot {
+    /// `host:port` peers reach an instance in this slot on
+    pub advertise: String,
+    /// Must answer 2xx before traffic is switched to this slot
+    pub health_url: String,
+    /// Extra environment for this slot, typically its listen addresses
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+}
+
+fn default_upgrade_ready_timeout() -> u64 {
+    60
+}
+
+fn default_upgrade_drain() -> u64 {
+    10
+}
+
+/// Per‑service resource containment, applied through a cgroup v2 slice.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResourceLimits {
+    /// `cpu.max` – "max", "<quota> <period>" in µs, or a percentage of one CPU such as "150%"
+    pub cpu_max: Option<String>,
+    /// `memory.max` – "max", bytes, or a size with K/M/G suffix such as "512M"
+    pub memory_max: Option<String>,
+    /// `io.weight` – 1‑10000 (kernel default 100)
+    pub io_weight: Option<u16>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -139,23 +625,98 @@ pub struct MemoryFsConfig {
     pub mount_name: String,
 }
 
+/// Kubernetes‑style liveness probe for a service.
+///
+/// ```toml
+/// [health_check]
+/// check_type = "http"
+/// url = "http://127.0.0.1:8080/health"
+/// expected_status = 200
+/// interval_secs = 10
+/// timeout_secs = 2
+/// failure_threshold = 3
+/// ```
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HealthCheckConfig {
-    pub check_type: String, // http | tcp | script
-    pub target: String,
+    #[serde(flatten)]
+    pub probe: HealthProbe,
+    /// Seconds between probes
+    #[serde(default = "default_probe_interval")]
     pub interval_secs: u64,
+    /// Seconds before a single probe counts as failed
+    #[serde(default = "default_probe_timeout")]
     pub timeout_secs: u64,
-    pub retries: u32,
-    pub expected_response: Option<String>,
+    /// Grace period after start before the first probe
+    #[serde(default)]
+    pub initial_delay_secs: u64,
+    /// Consecutive failures before the service is marked unhealthy
+    #[serde(default = "default_failure_threshold", alias = "retries")]
+    pub failure_threshold: u32,
+    /// Consecutive successes before an unhealthy service is marked healthy again
+    #[serde(default = "default_success_threshold")]
+    pub success_threshold: u32,
     #[serde(default)]
     pub on_failure: Vec<String>,
 }
 
+/// What a health check probes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "check_type", rename_all = "lowercase")]
+pub enum HealthProbe {
+    /// Run a shell command; exit status 0 is healthy
+    #[serde(alias = "script")]
+    Exec {
+        #[serde(alias = "target")]
+        command: String,
+    },
+    /// HTTP GET; healthy on `expected_status` (any 2xx/3xx when unset)
+    Http {
+        #[serde(alias = "target")]
+        url: String,
+        #[serde(default)]
+        expected_status: Option<u16>,
+        /// Substring the response body must contain
+        #[serde(default)]
+        expected_response: Option<String>,
+    },
+    /// TCP connect to `host:port`
+    Tcp {
+        #[serde(alias = "target")]
+        addr: String,
+    },
+    /// `grpc.health.v1.Health/Check`; an empty service checks the whole server
+    Grpc {
+        #[serde(alias = "target")]
+        addr: String,
+        #[serde(default)]
+        service: Option<String>,
+    },
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LogRotationConfig {
+    /// Rotate once the active log reaches this size (0 disables size rotation)
     pub max_size_mb: u64,
+    /// Number of rotated files to retain
     pub max_files: u32,
+    /// Rotate once the active log is this many days old (0 disables time rotation)
     pub interval_days: u32,
     pub compress: bool,
+    /// Name rotated files by timestamp instead of sequence number
     pub timestamp: bool,
+    /// Compression codec used when `compress` is set
+    #[serde(default)]
+    pub compression: LogCompression,
+    /// Rename the log and send SIGHUP to the child instead of copy‑truncating,
+    /// for services that reopen their own log file on SIGHUP
+    #[serde(default)]
+    pub signal_child: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogCompression {
+    #[default]
+    Gzip,
+    Zstd,
 }
@@ -0,0 +1,535 @@
+//! Crash reports for supervised services.
+//!
+//! When a service dies on its own the worker keeps its last words – the tail
+//! of stderr, exit status, terminating signal and where the kernel put any
+//! core dump – as one JSON file per crash, so `cyrupd crashes` can answer
+//! "why did it die" long after the process and its log lines are gone.
+//!
+//! The dump itself (a core file, a `systemd-coredump` entry, a macOS crash
+//! report or a Windows minidump) is copied next to the reports, under
+//! `<crash_dir>/<service>/`, because the system's copies are usually rotated
+//! away or tied to a pid that gets reused.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{ChildStderr, ExitStatus};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Older reports beyond this many per service are pruned on save.
+const KEEP_PER_SERVICE: usize = 20;
+
+/// How long to wait for the stderr pump to drain after the child exits.
+const DRAIN_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// How long crash handlers (systemd-coredump, ReportCrash) get to write the dump.
+const DUMP_WAIT: Duration = Duration::from_secs(10);
+
+/// One crash, as written to `<crash_dir>/<service>-<ts>-<pid>.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub service: String,
+    pub ts: DateTime<Utc>,
+    pub pid: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    /// Terminating signal, e.g. "SIGSEGV"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signal: Option<String>,
+    #[serde(default)]
+    pub core_dumped: bool,
+    /// Core file path, or how to retrieve it when the kernel pipes cores to a handler
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub core_dump: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uptime_secs: Option<u64>,
+    /// Final bytes the service wrote to stderr
+    #[serde(default)]
+    pub stderr_tail: String,
+    /// Copy of the core file, crash log or minidump kept with this report
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dump_file: Option<PathBuf>,
+}
+
+impl CrashReport {
+    /// Describe an unexpected exit of `pid`.
+    pub fn capture(
+        service: &str,
+        pid: u32,
+        status: ExitStatus,
+        uptime: Option<Duration>,
+        working_dir: Option<&str>,
+        stderr_tail: String,
+    ) -> Self {
+        let mut report = Self {
+            service: service.to_string(),
+            ts: Utc::now(),
+            pid,
+            exit_code: status.code(),
+            signal: None,
+            core_dumped: false,
+            core_dump: None,
+            uptime_secs: uptime.map(|d| d.as_secs()),
+            stderr_tail,
+            dump_file: None,
+        };
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            report.signal = status.signal().map(signal_name);
+            report.core_dumped = status.core_dumped();
+        }
+        if report.core_dumped {
+            let cwd = working_dir.map(PathBuf::from).unwrap_or_default();
+            report.core_dump = kernel_core_pattern()
+                .and_then(|(pattern, uses_pid)| core_location(&pattern, uses_pid, pid, &cwd));
+        }
+        report
+    }
+
+    /// One‑line cause of death, e.g. "killed by SIGSEGV (core dumped)".
+    pub fn summary(&self) -> String {
+        let mut out = match (&self.signal, self.exit_code) {
+            (Some(signal), _) => format!("killed by {}", signal),
+            (None, Some(code)) => format!("exited with code {}", code),
+            (None, None) => "exited".to_string(),
+        };
+        if self.core_dumped {
+            out.push_str(" (core dumped)");
+        }
+        out
+    }
+
+    /// Write the report into `dir` and prune that service's oldest reports.
+    pub fn save(&self, dir: &Path) -> Result<PathBuf> {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("create crash dir {}", dir.display()))?;
+        let path = dir.join(format!(
+            "{}-{}-{}.json",
+            self.service,
+            self.ts.format("%Y%m%dT%H%M%S%.3fZ"),
+            self.pid
+        ));
+        let mut file = File::create(&path).with_context(|| format!("create {}", path.display()))?;
+        file.write_all(&serde_json::to_vec_pretty(self)?)
+            .with_context(|| format!("write {}", path.display()))?;
+
+        if let Ok(reports) = list(dir, Some(&self.service)) {
+            let excess = reports.len().saturating_sub(KEEP_PER_SERVICE);
+            for (old, report) in reports.into_iter().take(excess) {
+                if let Some(dump) = report.dump_file {
+                    std::fs::remove_file(dump).ok();
+                }
+                std::fs::remove_file(old).ok();
+            }
+        }
+        Ok(path)
+    }
+
+    /// Whether the platform should have left a dump for this exit.
+    pub fn expects_dump(&self) -> bool {
+        if cfg!(target_os = "macos") {
+            self.signal.is_some()
+        } else if cfg!(windows) {
+            // Crashes exit with an NTSTATUS error code such as 0xC0000005
+            self.exit_code.is_some_and(|code| code < 0)
+        } else {
+            self.core_dumped
+        }
+    }
+
+    /// Copy the dump for this crash into `<dir>/<service>/` and record it in
+    /// `dump_file`. `program` is the service binary's file name, which macOS
+    /// and Windows name their dumps after. Waits a few seconds for the system
+    /// crash handler to finish writing.
+    pub fn collect_dump(&mut self, dir: &Path, program: &str, max_bytes: u64) -> Result<PathBuf> {
+        let deadline = Instant::now() + DUMP_WAIT;
+        let dest_dir = dir.join(&self.service);
+        std::fs::create_dir_all(&dest_dir)
+            .with_context(|| format!("create dump dir {}", dest_dir.display()))?;
+        let stem = format!("{}-{}", self.ts.format("%Y%m%dT%H%M%S%.3fZ"), self.pid);
+
+        loop {
+            let attempt = match self.dump_source(program) {
+                Some(DumpSource::File(src)) => copy_dump(&src, &dest_dir, &stem, max_bytes),
+                Some(DumpSource::Coredumpctl) => {
+                    coredumpctl_dump(self.pid, &dest_dir.join(format!("{}.core", stem)))
+                }
+                None => Err(anyhow::anyhow!("no dump found for pid {}", self.pid)),
+            };
+            match attempt {
+                Ok(path) => {
+                    self.dump_file = Some(path.clone());
+                    return Ok(path);
+                }
+                Err(e) if Instant::now() >= deadline => return Err(e),
+                Err(_) => thread::sleep(Duration::from_millis(500)),
+            }
+        }
+    }
+
+    fn dump_source(&self, program: &str) -> Option<DumpSource> {
+        if cfg!(target_os = "macos") {
+            return find_macos_crash_report(program, self.pid, self.started_at())
+                .map(DumpSource::File);
+        }
+        if cfg!(windows) {
+            let path = dirs::data_local_dir()?
+                .join("CrashDumps")
+                .join(format!("{}.{}.dmp", program, self.pid));
+            return path.is_file().then_some(DumpSource::File(path));
+        }
+        let location = self.core_dump.as_deref()?;
+        if location.starts_with("coredumpctl ") {
+            return Some(DumpSource::Coredumpctl);
+        }
+        let path = PathBuf::from(location);
+        path.is_file().then_some(DumpSource::File(path))
+    }
+
+    fn started_at(&self) -> DateTime<Utc> {
+        let uptime = chrono::Duration::seconds(self.uptime_secs.unwrap_or(0) as i64 + 1);
+        self.ts - uptime
+    }
+}
+
+enum DumpSource {
+    File(PathBuf),
+    /// Stored in the journal by systemd-coredump; exported with `coredumpctl`
+    Coredumpctl,
+}
+
+fn copy_dump(src: &Path, dest_dir: &Path, stem: &str, max_bytes: u64) -> Result<PathBuf> {
+    let size = std::fs::metadata(src)
+        .with_context(|| format!("stat {}", src.display()))?
+        .len();
+    if size > max_bytes {
+        anyhow::bail!(
+            "{} is {} MB, over the {} MB dump limit; left in place",
+            src.display(),
+            size / (1024 * 1024),
+            max_bytes / (1024 * 1024)
+        );
+    }
+    let ext = src.extension().and_then(|e| e.to_str()).unwrap_or("core");
+    let dest = dest_dir.join(format!("{}.{}", stem, ext));
+    std::fs::copy(src, &dest)
+        .with_context(|| format!("copy {} to {}", src.display(), dest.display()))?;
+    Ok(dest)
+}
+
+fn coredumpctl_dump(pid: u32, dest: &Path) -> Result<PathBuf> {
+    let status = std::process::Command::new("coredumpctl")
+        .arg("dump")
+        .arg(pid.to_string())
+        .arg("--output")
+        .arg(dest)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .context("run coredumpctl")?;
+    if !status.success() {
+        std::fs::remove_file(dest).ok();
+        anyhow::bail!("coredumpctl dump {} failed with {}", pid, status);
+    }
+    Ok(dest.to_path_buf())
+}
+
+/// Newest `~/Library/Logs/DiagnosticReports/<program>-*.{ips,crash}` written
+/// for `pid` since the process started.
+fn find_macos_crash_report(program: &str, pid: u32, since: DateTime<Utc>) -> Option<PathBuf> {
+    let dirs = [
+        dirs::home_dir().map(|h| h.join("Library/Logs/DiagnosticReports")),
+        Some(PathBuf::from("/Library/Logs/DiagnosticReports")),
+    ];
+    let markers = [
+        format!("\"pid\" : {},", pid),
+        format!("\"pid\":{},", pid),
+        format!("[{}]", pid),
+    ];
+    let since = std::time::SystemTime::from(since);
+
+    let mut best: Option<(std::time::SystemTime, PathBuf)> = None;
+    for dir in dirs.into_iter().flatten() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let ext = path.extension().and_then(|e| e.to_str());
+            if !name.starts_with(program) || !matches!(ext, Some("ips") | Some("crash")) {
+                continue;
+            }
+            let Some(modified) = entry.metadata().and_then(|m| m.modified()).ok() else {
+                continue;
+            };
+            if modified < since || best.as_ref().is_some_and(|(t, _)| *t >= modified) {
+                continue;
+            }
+            let Ok(text) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            if markers.iter().any(|m| text.contains(m.as_str())) {
+                best = Some((modified, path));
+            }
+        }
+    }
+    best.map(|(_, path)| path)
+}
+
+/// Reports in `dir`, oldest first, skipping files that fail to parse.
+pub fn list(dir: &Path, service: Option<&str>) -> Result<Vec<(PathBuf, CrashReport)>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("read crash dir {}", dir.display())),
+    };
+
+    let mut out = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("json") {
+            continue;
+        }
+        let Ok(report) = std::fs::read(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|raw| Ok(serde_json::from_slice::<CrashReport>(&raw)?))
+        else {
+            continue;
+        };
+        if service.is_some_and(|svc| report.service != svc) {
+            continue;
+        }
+        out.push((path, report));
+    }
+    out.sort_by_key(|(_, report)| report.ts);
+    Ok(out)
+}
+
+/// Bounded ring of a child's most recent stderr output.
+///
+/// The child's stderr is piped through a pump thread which forwards every
+/// chunk to the service log (if any) and keeps the last `cap` bytes.
+pub struct StderrTail {
+    buf: Arc<Mutex<VecDeque<u8>>>,
+    pump: Option<JoinHandle<()>>,
+}
+
+impl StderrTail {
+    pub fn attach(name: &str, mut stderr: ChildStderr, mut sink: Option<File>, cap: usize) -> Self {
+        let buf = Arc::new(Mutex::new(VecDeque::with_capacity(cap)));
+        let ring = buf.clone();
+        let pump = thread::Builder::new()
+            .name(format!("stderr-{}", name))
+            .spawn(move || {
+                let mut chunk = [0u8; 8192];
+                loop {
+                    let n = match stderr.read(&mut chunk) {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => n,
+                    };
+                    if let Some(out) = sink.as_mut() {
+                        if out.write_all(&chunk[..n]).is_err() {
+                            sink = None;
+                        }
+                    }
+                    push_bounded(&mut ring.lock().unwrap(), &chunk[..n], cap);
+                }
+            })
+            .ok();
+        Self { buf, pump }
+    }
+
+    /// Wait briefly for the pump to see EOF, then return what was kept.
+    pub fn finish(mut self) -> String {
+        if let Some(pump) = self.pump.take() {
+            let deadline = Instant::now() + DRAIN_TIMEOUT;
+            while !pump.is_finished() && Instant::now() < deadline {
+                thread::sleep(Duration::from_millis(10));
+            }
+        }
+        let buf = self.buf.lock().unwrap();
+        String::from_utf8_lossy(&buf.iter().copied().collect::<Vec<_>>()).into_owned()
+    }
+}
+
+fn push_bounded(ring: &mut VecDeque<u8>, bytes: &[u8], cap: usize) {
+    let bytes = &bytes[bytes.len().saturating_sub(cap)..];
+    let overflow = (ring.len() + bytes.len()).saturating_sub(cap);
+    ring.drain(..overflow);
+    ring.extend(bytes);
+}
+
+#[cfg(unix)]
+fn signal_name(signo: i32) -> String {
+    nix::sys::signal::Signal::try_from(signo)
+        .map(|s| s.as_str().to_string())
+        .unwrap_or_else(|_| format!("signal {}", signo))
+}
+
+/// The kernel's core file naming rule and whether it appends `.<pid>`.
+fn kernel_core_pattern() -> Option<(String, bool)> {
+    if cfg!(target_os = "linux") {
+        let pattern = std::fs::read_to_string("/proc/sys/kernel/core_pattern").ok()?;
+        let uses_pid = std::fs::read_to_string("/proc/sys/kernel/core_uses_pid")
+            .is_ok_and(|v| v.trim() == "1");
+        Some((pattern.trim().to_string(), uses_pid))
+    } else if cfg!(target_os = "macos") {
+        Some(("/cores/core.%P".to_string(), false))
+    } else {
+        None
+    }
+}
+
+/// Resolve where a core for `pid` ended up under `pattern` (see core(5)).
+fn core_location(pattern: &str, uses_pid: bool, pid: u32, cwd: &Path) -> Option<String> {
+    if let Some(handler) = pattern.strip_prefix('|') {
+        let program = handler.split_whitespace().next()?;
+        return Some(if program.ends_with("systemd-coredump") {
+            format!("coredumpctl dump {}", pid)
+        } else {
+            format!("piped to {}", program)
+        });
+    }
+    if pattern.is_empty() {
+        return None;
+    }
+
+    let mut path = String::new();
+    let mut has_pid = false;
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            path.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('p') | Some('P') => {
+                has_pid = true;
+                path.push_str(&pid.to_string());
+            }
+            Some('%') => path.push('%'),
+            // Specifiers we can't reproduce stay visible for the operator
+            Some(other) => {
+                path.push('%');
+                path.push(other);
+            }
+            None => path.push('%'),
+        }
+    }
+    if uses_pid && !has_pid {
+        path.push_str(&format!(".{}", pid));
+    }
+    Some(cwd.join(path).to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ring_keeps_only_the_tail() {
+        let mut ring = VecDeque::new();
+        push_bounded(&mut ring, b"hello ", 8);
+        push_bounded(&mut ring, b"world", 8);
+        assert_eq!(ring.iter().copied().collect::<Vec<_>>(), b"lo world");
+        push_bounded(&mut ring, b"0123456789", 8);
+        assert_eq!(ring.iter().copied().collect::<Vec<_>>(), b"23456789");
+    }
+
+    #[test]
+    fn core_pattern_resolution() {
+        let cwd = Path::new("/srv/app");
+        assert_eq!(
+            core_location("core", true, 42, cwd).as_deref(),
+            Some("/srv/app/core.42")
+        );
+        assert_eq!(
+            core_location("/var/crash/core.%e.%p", true, 42, cwd).as_deref(),
+            Some("/var/crash/core.%e.42")
+        );
+        assert_eq!(
+            core_location("|/usr/lib/systemd/systemd-coredump %P %u", false, 42, cwd).as_deref(),
+            Some("coredumpctl dump 42")
+        );
+        assert_eq!(
+            core_location("|/usr/share/apport/apport -p%p", false, 42, cwd).as_deref(),
+            Some("piped to /usr/share/apport/apport")
+        );
+    }
+
+    #[test]
+    fn dump_is_copied_and_pruned_with_its_report() {
+        let root = std::env::temp_dir().join(format!("cyrupd-dump-{}", std::process::id()));
+        let core = root.join("core.4242");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(&core, b"ELF core").unwrap();
+
+        let mut report = CrashReport {
+            service: "svc".into(),
+            ts: Utc::now(),
+            pid: 4242,
+            exit_code: None,
+            signal: Some("SIGSEGV".into()),
+            core_dumped: true,
+            core_dump: Some(core.to_string_lossy().into_owned()),
+            uptime_secs: Some(3),
+            stderr_tail: String::new(),
+            dump_file: None,
+        };
+        let crashes = root.join("crashes");
+        if cfg!(target_os = "linux") {
+            let copied = report.collect_dump(&crashes, "svc", 1024).unwrap();
+            assert!(copied.starts_with(crashes.join("svc")));
+            assert_eq!(std::fs::read(&copied).unwrap(), b"ELF core");
+            assert!(copy_dump(&core, &crashes, "big", 4).is_err());
+        } else {
+            report.dump_file = Some(core.clone());
+        }
+        let dump = report.dump_file.clone().unwrap();
+
+        report.save(&crashes).unwrap();
+        for i in 0..KEEP_PER_SERVICE {
+            let mut newer = report.clone();
+            newer.ts = report.ts + chrono::Duration::seconds(i as i64 + 1);
+            newer.dump_file = None;
+            newer.save(&crashes).unwrap();
+        }
+        assert!(!dump.exists());
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn captures_signal_and_stderr_and_round_trips() {
+        use std::process::{Command, Stdio};
+
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg("echo 'panicked at src/main.rs' >&2; kill -TERM $$")
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let tail = StderrTail::attach("test", child.stderr.take().unwrap(), None, 1024);
+        let status = child.wait().unwrap();
+
+        let report = CrashReport::capture("svc", child.id(), status, None, None, tail.finish());
+        assert_eq!(report.signal.as_deref(), Some("SIGTERM"));
+        assert_eq!(report.summary(), "killed by SIGTERM");
+        assert!(report.stderr_tail.contains("panicked at src/main.rs"));
+
+        let dir = std::env::temp_dir().join(format!("cyrupd-crash-{}", std::process::id()));
+        let path = report.save(&dir).unwrap();
+        let listed = list(&dir, Some("svc")).unwrap();
+        assert_eq!(listed, vec![(path, report)]);
+        assert!(list(&dir, Some("other")).unwrap().is_empty());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,145 @@
+//! `cyrupd deploy`: roll a running service onto a new binary.
+//!
+//! Asks the daemon, through the admin API, to upgrade the service (which
+//! starts the new instance alongside the old one, health-checks it, switches
+//! traffic and drains the old one), then follows the event journal until the
+//! outcome is recorded.
+
+use crate::config::ServiceConfig;
+use crate::journal::{self, Query};
+use crate::service::admin::Listen;
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+pub async fn run(
+    cfg: &ServiceConfig,
+    service: &str,
+    binary: &Path,
+    timeout: Duration,
+) -> Result<()> {
+    let admin = cfg
+        .admin
+        .as_ref()
+        .filter(|a| a.enabled)
+        .context("cyrupd deploy needs the admin API ([admin] in cyrupd.toml)")?;
+    let listen = Listen::parse(&admin.listen)?;
+    let token = admin.resolve_token()?;
+    let binary =
+        std::fs::canonicalize(binary).with_context(|| format!("resolve {}", binary.display()))?;
+
+    let requested = Utc::now();
+    let body = serde_json::json!({ "binary": binary }).to_string();
+    let (status, reply) = post(
+        &listen,
+        &token,
+        &format!("/services/{}/upgrade", service),
+        &body,
+    )
+    .await?;
+    if status != 202 {
+        bail!("daemon refused the deploy ({}): {}", status, reply.trim());
+    }
+    println!("→ deploying {} as {}", binary.display(), service);
+
+    let Some(journal_path) = cfg.journal_path() else {
+        println!("Queued; no event journal to follow (set journal_file or log_dir)");
+        return Ok(());
+    };
+    let deadline = Instant::now() + timeout;
+    loop {
+        let entries = journal::query(
+            &journal_path,
+            &Query {
+                since: Some(requested),
+                service: Some(service),
+            },
+        )?;
+        let outcome = entries
+            .iter()
+            .find(|e| e.kind == "upgraded" || e.kind == "upgrade_failed");
+        if let Some(entry) = outcome {
+            let detail = entry.detail.as_deref().unwrap_or_default();
+            if entry.kind == "upgraded" {
+                println!("✓ {} deployed: {}", service, detail);
+                return Ok(());
+            }
+            bail!("{} deploy failed, old instance kept: {}", service, detail);
+        }
+        if Instant::now() >= deadline {
+            bail!(
+                "no outcome after {}s; check `cyrupd events --service {}`",
+                timeout.as_secs(),
+                service
+            );
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
+/// Minimal HTTP/1.1 POST that works over both admin transports.
+async fn post(listen: &Listen, token: &str, path: &str, body: &str) -> Result<(u16, String)> {
+    let request = format!(
+        "POST {} HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer {}\r\n\
+         Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        path,
+        token,
+        body.len(),
+        body
+    );
+    let raw = match listen {
+        Listen::Tcp(addr) => {
+            let stream = tokio::net::TcpStream::connect(addr)
+                .await
+                .with_context(|| format!("connect to admin API at {}", addr))?;
+            exchange(stream, &request).await?
+        }
+        #[cfg(unix)]
+        Listen::Unix(path) => {
+            let stream = tokio::net::UnixStream::connect(path)
+                .await
+                .with_context(|| format!("connect to admin API at unix:{}", path.display()))?;
+            exchange(stream, &request).await?
+        }
+    };
+    parse_response(&raw)
+}
+
+async fn exchange<S: AsyncRead + AsyncWrite + Unpin>(
+    mut stream: S,
+    request: &str,
+) -> Result<Vec<u8>> {
+    stream.write_all(request.as_bytes()).await?;
+    let mut raw = Vec::new();
+    stream.read_to_end(&mut raw).await?;
+    Ok(raw)
+}
+
+fn parse_response(raw: &[u8]) -> Result<(u16, String)> {
+    let text = String::from_utf8_lossy(raw);
+    let (head, body) = text.split_once("\r\n\r\n").unwrap_or((text.as_ref(), ""));
+    let status = head
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .context("malformed admin API response")?;
+    Ok((status, body.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn admin_response_parsing() {
+        let (status, body) = parse_response(
+            b"HTTP/1.1 202 Accepted\r\ncontent-type: application/json\r\n\r\n{\"service\":\"gw\"}",
+        )
+        .unwrap();
+        assert_eq!(status, 202);
+        assert_eq!(body, r#"{"service":"gw"}"#);
+        assert!(parse_response(b"garbage").is_err());
+    }
+}
@@ -0,0 +1,166 @@
+//! Operator intent that survives daemon restarts.
+//!
+//! The lifecycle state machine only knows what a service is doing *now*.
+//! When an operator stops a service on purpose, that decision is written here
+//! so the next `cyrupd` start leaves it stopped instead of resurrecting it,
+//! and so the crash‑restart logic does not fight the operator.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Desired {
+    Running,
+    Stopped,
+}
+
+impl Desired {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Desired::Running => "running",
+            Desired::Stopped => "stopped",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DesiredEntry {
+    pub state: Desired,
+    pub since: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StateFile {
+    #[serde(default)]
+    services: BTreeMap<String, DesiredEntry>,
+}
+
+/// Per‑service desired state, shared by the manager and the admin API.
+/// Services without an entry default to [`Desired::Running`].
+pub struct DesiredState {
+    path: Option<PathBuf>,
+    entries: Mutex<BTreeMap<String, DesiredEntry>>,
+}
+
+impl DesiredState {
+    /// Load from `path`; a missing file means every service should run.
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let file: StateFile = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .with_context(|| format!("corrupt state file {}", path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => StateFile::default(),
+            Err(e) => return Err(e).with_context(|| format!("read state file {}", path.display())),
+        };
+        Ok(Self {
+            path: Some(path),
+            entries: Mutex::new(file.services),
+        })
+    }
+
+    /// Keep intent in memory only (no state file could be opened).
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            entries: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn get(&self, service: &str) -> Desired {
+        self.lock()
+            .get(service)
+            .map_or(Desired::Running, |e| e.state)
+    }
+
+    pub fn entry(&self, service: &str) -> Option<DesiredEntry> {
+        self.lock().get(service).cloned()
+    }
+
+    pub fn wants_running(&self, service: &str) -> bool {
+        self.get(service) == Desired::Running
+    }
+
+    /// Record intent and persist it before returning.
+    pub fn set(&self, service: &str, state: Desired) -> Result<()> {
+        let mut entries = self.lock();
+        if entries.get(service).map(|e| e.state) == Some(state) {
+            return Ok(());
+        }
+        let previous = entries.insert(
+            service.to_string(),
+            DesiredEntry {
+                state,
+                since: Utc::now(),
+            },
+        );
+        if let Err(e) = self.persist(&entries) {
+            // Keep memory and disk in agreement
+            match previous {
+                Some(prev) => entries.insert(service.to_string(), prev),
+                None => entries.remove(service),
+            };
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    fn persist(&self, entries: &BTreeMap<String, DesiredEntry>) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("create {}", parent.display()))?;
+        }
+        let file = StateFile {
+            services: entries.clone(),
+        };
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(&file)?)
+            .with_context(|| format!("write {}", tmp.display()))?;
+        std::fs::rename(&tmp, path).with_context(|| format!("replace {}", path.display()))?;
+        Ok(())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, DesiredEntry>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stopped_services_survive_reload() {
+        let path = std::env::temp_dir()
+            .join(format!("cyrupd-state-{}", std::process::id()))
+            .join("state.json");
+
+        let state = DesiredState::load(&path).unwrap();
+        assert!(state.wants_running("api"));
+        state.set("api", Desired::Stopped).unwrap();
+        state.set("worker", Desired::Running).unwrap();
+
+        let reloaded = DesiredState::load(&path).unwrap();
+        assert_eq!(reloaded.get("api"), Desired::Stopped);
+        assert!(reloaded.wants_running("worker"));
+        assert!(reloaded.wants_running("unknown"));
+
+        reloaded.set("api", Desired::Running).unwrap();
+        assert!(DesiredState::load(&path).unwrap().wants_running("api"));
+
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+}
@@ -0,0 +1,376 @@
+//! `cyrupd dev`: the whole stack as the current user, for working on it.
+//!
+//! The gateway and the MCP host run under the usual supervisor, but every
+//! port is picked by the OS, all state lives in a scratch directory that is
+//! removed on exit, and the host keeps its SurrealDB in memory. Nothing is
+//! installed, registered with init or run with elevated rights, so two
+//! checkouts can run side by side without touching the installed daemon.
+
+use anyhow::{Context, Result};
+use log::{info, warn};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+
+use crate::config::{ServiceConfig, ServiceDefinition};
+use crate::manager::{self, ServiceManager};
+
+/// Gateway binary built from sweetmcp-pingora
+const GATEWAY_BIN: &str = "sweetmcp_server";
+
+/// MCP host binary built from sweetmcp-axum
+const HOST_BIN: &str = "sweetmcp-axum";
+
+pub struct DevOptions {
+    /// Directory of `*.wasm` plugins for the host to load
+    pub plugin_dir: Option<PathBuf>,
+    /// Where the gateway and host binaries are; defaults to cyrupd's own
+    /// directory, which is `target/<profile>` in a cargo build
+    pub bin_dir: Option<PathBuf>,
+    /// Leave the scratch directory in place on exit
+    pub keep: bool,
+}
+
+/// Loopback ports handed out by the OS
+#[derive(Debug, Clone, Copy)]
+struct Ports {
+    gateway: u16,
+    mcp: u16,
+    metrics: u16,
+}
+
+impl Ports {
+    /// Hold every listener until all are bound so no port is handed out twice
+    fn pick() -> Result<Self> {
+        let listeners = (0..3)
+            .map(|_| TcpListener::bind("127.0.0.1:0"))
+            .collect::<std::io::Result<Vec<_>>>()
+            .context("Failed to reserve a loopback port")?;
+        let port = |i: usize| -> Result<u16> { Ok(listeners[i].local_addr()?.port()) };
+        Ok(Self {
+            gateway: port(0)?,
+            mcp: port(1)?,
+            metrics: port(2)?,
+        })
+    }
+}
+
+/// Config file for the MCP host, in the shape sweetmcp-axum reads
+#[derive(Debug, Serialize)]
+struct HostConfig {
+    plugins: Vec<HostPlugin>,
+    database: HostDatabase,
+}
+
+#[derive(Debug, Serialize)]
+struct HostPlugin {
+    name: String,
+    path: String,
+}
+
+#[derive(Debug, Serialize)]
+struct HostDatabase {
+    engine: &'static str,
+    namespace: &'static str,
+    database: &'static str,
+}
+
+pub fn run(opts: DevOptions) -> Result<()> {
+    if nix::unistd::geteuid().is_root() {
+        warn!("cyrupd dev is meant to run as your own user, not root");
+    }
+
+    let bin_dir = match opts.bin_dir {
+        Some(dir) => dir,
+        None => std::env::current_exe()?
+            .parent()
+            .map(Path::to_path_buf)
+            .context("cyrupd has no parent directory")?,
+    };
+    let gateway_bin = bin_dir.join(GATEWAY_BIN);
+    let host_bin = bin_dir.join(HOST_BIN);
+    for bin in [&gateway_bin, &host_bin] {
+        if !bin.exists() {
+            anyhow::bail!(
+                "{} not found; build it with `cargo build -p sweetmcp-pingora -p sweetmcp-axum` \
+                 or point --bin-dir at it",
+                bin.display()
+            );
+        }
+    }
+
+    let root = std::env::temp_dir().join(format!("cyrupd-dev-{}", std::process::id()));
+    std::fs::create_dir_all(&root)
+        .with_context(|| format!("Failed to create {}", root.display()))?;
+
+    let result = run_in(&root, &opts.plugin_dir, &gateway_bin, &host_bin);
+
+    if opts.keep {
+        info!("Kept {}", root.display());
+    } else if let Err(e) = std::fs::remove_dir_all(&root) {
+        warn!("Failed to remove {}: {}", root.display(), e);
+    }
+    result
+}
+
+fn run_in(
+    root: &Path,
+    plugin_dir: &Option<PathBuf>,
+    gateway_bin: &Path,
+    host_bin: &Path,
+) -> Result<()> {
+    let plugins = match plugin_dir {
+        Some(dir) => find_plugins(dir)?,
+        None => Vec::new(),
+    };
+    let host_config = root.join("host.toml");
+    std::fs::write(
+        &host_config,
+        toml::to_string_pretty(&host_config_for(plugins))?,
+    )
+    .with_context(|| format!("Failed to write {}", host_config.display()))?;
+
+    let ports = Ports::pick()?;
+    let cfg = dev_config(root, ports, gateway_bin, host_bin, &host_config);
+
+    println!("cyrupd dev: state in {}", root.display());
+    println!("  gateway   127.0.0.1:{}", ports.gateway);
+    println!("  MCP HTTP  127.0.0.1:{}", ports.mcp);
+    println!("  metrics   127.0.0.1:{}", ports.metrics);
+    println!("  logs      {}", root.join("logs").display());
+    match plugin_dir {
+        Some(dir) => println!("  plugins   {}", dir.display()),
+        None => println!("  plugins   none (pass --plugins <dir>)"),
+    }
+
+    manager::install_signal_handlers();
+    let mgr = ServiceManager::new(&cfg)?;
+    info!("Development stack started (pid {})", std::process::id());
+    mgr.run()?;
+    info!("Development stack stopped");
+    Ok(())
+}
+
+/// `*.wasm` files directly in `dir`, each named after its file stem
+fn find_plugins(dir: &Path) -> Result<Vec<HostPlugin>> {
+    let mut plugins = Vec::new();
+    for entry in std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read plugin directory {}", dir.display()))?
+    {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("wasm") {
+            continue;
+        }
+        let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        plugins.push(HostPlugin {
+            name: name.to_string(),
+            path: path.to_string_lossy().into_owned(),
+        });
+    }
+    plugins.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(plugins)
+}
+
+fn host_config_for(plugins: Vec<HostPlugin>) -> HostConfig {
+    HostConfig {
+        plugins,
+        database: HostDatabase {
+            engine: "memory",
+            namespace: "dev",
+            database: "dev",
+        },
+    }
+}
+
+/// Supervisor config with both services and every path under `root`
+fn dev_config(
+    root: &Path,
+    ports: Ports,
+    gateway_bin: &Path,
+    host_bin: &Path,
+    host_config: &Path,
+) -> ServiceConfig {
+    let path = |name: &str| root.join(name).to_string_lossy().into_owned();
+    let host_socket = path("mcp.sock");
+
+    let mut host_env = HashMap::new();
+    host_env.insert("RUST_LOG".to_string(), log_filter());
+    let host = service(
+        "sweetmcp-axum",
+        "SweetMCP MCP host (development)",
+        format!(
+            "{} --config {} serve --daemon --socket-path {}",
+            shell_quote(&host_bin.to_string_lossy()),
+            shell_quote(&host_config.to_string_lossy()),
+            shell_quote(&host_socket)
+        ),
+        host_env,
+        Vec::new(),
+    );
+
+    let mut gateway_env = HashMap::new();
+    gateway_env.insert("RUST_LOG".to_string(), log_filter());
+    gateway_env.insert(
+        "SWEETMCP_TCP_BIND".to_string(),
+        format!("127.0.0.1:{}", ports.gateway),
+    );
+    gateway_env.insert(
+        "SWEETMCP_MCP_BIND".to_string(),
+        format!("127.0.0.1:{}", ports.mcp),
+    );
+    gateway_env.insert(
+        "SWEETMCP_METRICS_BIND".to_string(),
+        format!("127.0.0.1:{}", ports.metrics),
+    );
+    gateway_env.insert("SWEETMCP_UDS_PATH".to_string(), path("gateway.sock"));
+    gateway_env.insert("SWEETMCP_AXUM_SOCKET".to_string(), host_socket);
+    gateway_env.insert("SWEETMCP_DEV_MODE".to_string(), "true".to_string());
+    let gateway = service(
+        "sweetmcp-pingora",
+        "SweetMCP Pingora Gateway Server (development)",
+        shell_quote(&gateway_bin.to_string_lossy()),
+        gateway_env,
+        vec!["sweetmcp-axum".to_string()],
+    );
+
+    ServiceConfig {
+        services_dir: None,
+        log_dir: Some(path("logs")),
+        default_user: None,
+        default_group: None,
+        auto_restart: Some(true),
+        services: vec![host, gateway],
+        sse: None,
+        mcp_bind: None,
+        state_file: Some(path("state.json")),
+        crash_dir: Some(path("crashes")),
+        ..ServiceConfig::default()
+    }
+}
+
+fn service(
+    name: &str,
+    description: &str,
+    command: String,
+    env_vars: HashMap<String, String>,
+    depends_on: Vec<String>,
+) -> ServiceDefinition {
+    ServiceDefinition {
+        name: name.to_string(),
+        description: Some(description.to_string()),
+        command,
+        working_dir: None,
+        umask: None,
+        nice: None,
+        ionice: None,
+        env_vars,
+        auto_restart: true,
+        user: None,
+        group: None,
+        restart_delay_s: Some(2),
+        depends_on,
+        health_check: None,
+        log_rotation: None,
+        log_file: None,
+        watch_dirs: Vec::new(),
+        ephemeral_dir: None,
+        service_type: None,
+        memfs: None,
+        resources: None,
+        sockets: Vec::new(),
+        upgrade: Default::default(),
+        crash: Default::default(),
+        deploy: None,
+        vars: HashMap::new(),
+    }
+}
+
+/// The caller's `RUST_LOG`, so services log as verbosely as cyrupd does
+fn log_filter() -> String {
+    std::env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string())
+}
+
+/// Single-quote `arg` for the `sh -c` services are started through
+fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ports_are_distinct() {
+        let ports = Ports::pick().unwrap();
+        assert_ne!(ports.gateway, ports.mcp);
+        assert_ne!(ports.gateway, ports.metrics);
+        assert_ne!(ports.mcp, ports.metrics);
+    }
+
+    #[test]
+    fn quotes_survive_the_shell() {
+        assert_eq!(shell_quote("/tmp/a b"), "'/tmp/a b'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+    }
+
+    #[test]
+    fn host_config_lists_wasm_plugins_with_memory_db() {
+        let dir = std::env::temp_dir().join(format!("cyrupd-dev-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("fetch.wasm"), b"").unwrap();
+        std::fs::write(dir.join("eval-js.wasm"), b"").unwrap();
+        std::fs::write(dir.join("plugin.toml"), b"").unwrap();
+
+        let config = toml::to_string(&host_config_for(find_plugins(&dir).unwrap())).unwrap();
+        let value: toml::Value = toml::from_str(&config).unwrap();
+        let names: Vec<_> = value["plugins"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["eval-js", "fetch"]);
+        assert_eq!(value["database"]["engine"].as_str(), Some("memory"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn everything_stays_under_the_scratch_dir() {
+        let root = Path::new("/tmp/cyrupd-dev-1");
+        let ports = Ports {
+            gateway: 1001,
+            mcp: 1002,
+            metrics: 1003,
+        };
+        let cfg = dev_config(
+            root,
+            ports,
+            Path::new("/bin/gw"),
+            Path::new("/bin/host"),
+            &root.join("host.toml"),
+        );
+
+        assert!(cfg.default_user.is_none());
+        assert!(cfg.services_dir.is_none());
+        assert!(cfg.sse.is_none());
+        for dir in [&cfg.log_dir, &cfg.state_file, &cfg.crash_dir] {
+            assert!(dir.as_deref().unwrap().starts_with("/tmp/cyrupd-dev-1/"));
+        }
+
+        let gateway = &cfg.services[1];
+        assert!(gateway.user.is_none());
+        assert_eq!(gateway.depends_on, ["sweetmcp-axum"]);
+        assert_eq!(gateway.env_vars["SWEETMCP_TCP_BIND"], "127.0.0.1:1001");
+        assert_eq!(
+            gateway.env_vars["SWEETMCP_AXUM_SOCKET"],
+            "/tmp/cyrupd-dev-1/mcp.sock"
+        );
+        assert!(cfg.services[0]
+            .command
+            .ends_with("--socket-path '/tmp/cyrupd-dev-1/mcp.sock'"));
+    }
+}
@@ -0,0 +1,511 @@
+//! `cyrupd doctor`: checks the host for the problems new installs hit most.
+//!
+//! Each check yields a [`Finding`] with a fix the operator can run; checks
+//! that do not apply to this platform or configuration report `skip` rather
+//! than pass, so the output never claims something was verified when it
+//! wasn't.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::config::ServiceConfig;
+
+/// Port the sweetmcp-pingora gateway listens on by default
+const GATEWAY_PORT: u16 = 8443;
+
+/// Server whose `Date` header is used to measure clock skew
+const TIME_REFERENCE: &str = "https://www.cloudflare.com";
+
+/// Skew beyond which TLS, JWT expiry and ACME start failing
+const SKEW_FAIL_SECS: i64 = 120;
+
+/// Skew worth fixing before it grows
+const SKEW_WARN_SECS: i64 = 5;
+
+const NETWORK_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Ok,
+    Warn,
+    Fail,
+    Skip,
+}
+
+impl Status {
+    fn label(self) -> &'static str {
+        match self {
+            Status::Ok => "OK",
+            Status::Warn => "WARN",
+            Status::Fail => "FAIL",
+            Status::Skip => "SKIP",
+        }
+    }
+}
+
+/// Outcome of one check
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    pub check: &'static str,
+    pub status: Status,
+    pub detail: String,
+    /// What to do about a warning or failure
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
+}
+
+impl Finding {
+    fn new(check: &'static str, status: Status, detail: impl Into<String>) -> Self {
+        Self {
+            check,
+            status,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn fix(mut self, fix: impl Into<String>) -> Self {
+        self.fix = Some(fix.into());
+        self
+    }
+}
+
+/// Run every check, print the findings and fail if any check failed
+pub async fn run(cfg: &ServiceConfig, json: bool) -> Result<()> {
+    let mut findings = check_ports(cfg);
+    findings.push(check_polkit_agent());
+    findings.push(check_selinux());
+    findings.push(check_plugin_dir(&plugin_dir(cfg)));
+    findings.push(check_database().await);
+    findings.push(check_clock_skew().await);
+
+    for finding in &findings {
+        if json {
+            println!("{}", serde_json::to_string(finding)?);
+            continue;
+        }
+        println!(
+            "{:<5} {:<12} {}",
+            finding.status.label(),
+            finding.check,
+            finding.detail
+        );
+        if let Some(fix) = &finding.fix {
+            println!("      fix: {}", fix);
+        }
+    }
+
+    let failed = findings.iter().filter(|f| f.status == Status::Fail).count();
+    if failed > 0 {
+        anyhow::bail!("{} check(s) failed", failed);
+    }
+    Ok(())
+}
+
+/// Ports the gateway and the daemon's own listeners need
+fn check_ports(cfg: &ServiceConfig) -> Vec<Finding> {
+    let mut ports = vec![GATEWAY_PORT];
+    if let Some(sse) = cfg.sse.as_ref().filter(|sse| sse.enabled) {
+        ports.push(sse.port);
+    }
+    if let Some(port) = cfg
+        .mcp_bind
+        .as_deref()
+        .and_then(|bind| bind.rsplit(':').next())
+        .and_then(|port| port.parse().ok())
+    {
+        ports.push(port);
+    }
+    ports.dedup();
+
+    let daemon_running = daemon_running();
+    ports
+        .into_iter()
+        .map(|port| match TcpListener::bind(("127.0.0.1", port)) {
+            Ok(_) => Finding::new("port", Status::Ok, format!("{} is free", port)),
+            Err(_) if daemon_running => Finding::new(
+                "port",
+                Status::Ok,
+                format!("{} is in use, presumably by the running cyrupd", port),
+            ),
+            Err(e) => Finding::new(
+                "port",
+                Status::Fail,
+                format!("{} is not available: {}", port, e),
+            )
+            .fix(format!(
+                "find the owner with `lsof -iTCP:{0} -sTCP:LISTEN` (or `ss -ltnp 'sport = :{0}'`) \
+                 and stop it, or choose another port with `cyrupd install --interactive`",
+                port
+            )),
+        })
+        .collect()
+}
+
+fn daemon_running() -> bool {
+    std::fs::read_to_string("/var/run/cyrupd.pid")
+        .ok()
+        .and_then(|pid| pid.trim().parse::<i32>().ok())
+        .is_some_and(|pid| nix::sys::signal::kill(nix::unistd::Pid::from_raw(pid), None).is_ok())
+}
+
+/// Privileged installs on a Linux desktop prompt through a polkit agent
+fn check_polkit_agent() -> Finding {
+    const CHECK: &str = "polkit";
+    if !cfg!(target_os = "linux") {
+        return Finding::new(CHECK, Status::Skip, "only used on Linux");
+    }
+    if std::env::var_os("DISPLAY").is_none() && std::env::var_os("WAYLAND_DISPLAY").is_none() {
+        return Finding::new(CHECK, Status::Skip, "no graphical session");
+    }
+    if which::which("pkexec").is_err() {
+        return Finding::new(CHECK, Status::Warn, "pkexec is not installed")
+            .fix("install polkit (e.g. `sudo apt install pkexec`) or run `sudo cyrupd install`");
+    }
+
+    let agent = std::fs::read_dir("/proc")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| std::fs::read_to_string(entry.path().join("comm")).ok())
+        .map(|comm| comm.trim().to_string())
+        .find(|comm| comm.contains("polkit") && comm != "polkitd");
+    match agent {
+        Some(agent) => Finding::new(CHECK, Status::Ok, format!("agent running ({})", agent)),
+        None => Finding::new(
+            CHECK,
+            Status::Warn,
+            "no polkit authentication agent is running, so install prompts cannot appear",
+        )
+        .fix(
+            "start your desktop's agent (e.g. polkit-gnome-authentication-agent-1, \
+             polkit-kde-authentication-agent-1, lxpolkit) or run `sudo cyrupd install`",
+        ),
+    }
+}
+
+/// Recent SELinux AVC denials mentioning cyrupd or sweetmcp
+fn check_selinux() -> Finding {
+    const CHECK: &str = "selinux";
+    let Ok(enforce) = std::fs::read_to_string("/sys/fs/selinux/enforce") else {
+        return Finding::new(CHECK, Status::Skip, "SELinux is not enabled");
+    };
+    let mode = if enforce.trim() == "1" {
+        "enforcing"
+    } else {
+        "permissive"
+    };
+
+    let audit_log = Path::new("/var/log/audit/audit.log");
+    let log = match std::fs::read_to_string(audit_log) {
+        Ok(log) => log,
+        Err(e) => {
+            return Finding::new(
+                CHECK,
+                Status::Warn,
+                format!("{}, but {} is unreadable: {}", mode, audit_log.display(), e),
+            )
+            .fix("rerun `sudo cyrupd doctor` to inspect AVC denials");
+        }
+    };
+
+    let denials = count_denials(&log);
+    if denials == 0 {
+        return Finding::new(CHECK, Status::Ok, format!("{}, no denials", mode));
+    }
+    let status = if mode == "enforcing" {
+        Status::Fail
+    } else {
+        Status::Warn
+    };
+    Finding::new(
+        CHECK,
+        status,
+        format!("{}, {} AVC denial(s) for cyrupd/sweetmcp", mode, denials),
+    )
+    .fix(
+        "review with `sudo ausearch -m avc -c cyrupd`, then allow with \
+         `sudo ausearch -m avc -c cyrupd | audit2allow -M cyrupd && sudo semodule -i cyrupd.pp`",
+    )
+}
+
+fn count_denials(audit_log: &str) -> usize {
+    audit_log
+        .lines()
+        .filter(|line| line.contains("avc:") && line.contains("denied"))
+        .filter(|line| line.contains("cyrupd") || line.contains("sweetmcp"))
+        .count()
+}
+
+fn plugin_dir(cfg: &ServiceConfig) -> PathBuf {
+    match &cfg.plugin_dir {
+        Some(dir) => dir.into(),
+        None => dirs::data_local_dir()
+            .unwrap_or_else(|| PathBuf::from("/opt"))
+            .join("sweetmcp")
+            .join("plugins"),
+    }
+}
+
+/// The plugin directory must exist, be readable and not writable by everyone
+fn check_plugin_dir(dir: &Path) -> Finding {
+    const CHECK: &str = "plugins";
+    let metadata = match std::fs::metadata(dir) {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            return Finding::new(CHECK, Status::Fail, format!("{}: {}", dir.display(), e))
+                .fix(format!("mkdir -p {} && chmod 755 {0}", dir.display()));
+        }
+    };
+    if !metadata.is_dir() {
+        return Finding::new(
+            CHECK,
+            Status::Fail,
+            format!("{} is not a directory", dir.display()),
+        )
+        .fix("point plugin_dir in cyrupd.toml at a directory");
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if metadata.permissions().mode() & 0o002 != 0 {
+            return Finding::new(
+                CHECK,
+                Status::Fail,
+                format!(
+                    "{} is world-writable, so anyone can plant a plugin",
+                    dir.display()
+                ),
+            )
+            .fix(format!("chmod o-w {}", dir.display()));
+        }
+    }
+
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            return Finding::new(
+                CHECK,
+                Status::Fail,
+                format!("cannot list {}: {}", dir.display(), e),
+            )
+            .fix(format!(
+                "chown the directory to the daemon's user or `chmod 755 {}`",
+                dir.display()
+            ));
+        }
+    };
+    let wasm = entries
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "wasm"))
+        .count();
+    if wasm == 0 {
+        return Finding::new(
+            CHECK,
+            Status::Warn,
+            format!("{} holds no .wasm plugins", dir.display()),
+        )
+        .fix("rerun `cyrupd install` or copy plugins into the directory");
+    }
+    Finding::new(
+        CHECK,
+        Status::Ok,
+        format!("{} ({} plugins)", dir.display(), wasm),
+    )
+}
+
+/// Host, port and health URL of a remote SurrealDB endpoint
+fn db_endpoint(url: &str) -> Option<(String, u16, Option<String>)> {
+    let parsed = url::Url::parse(url).ok()?;
+    let host = parsed.host_str()?.to_string();
+    let port = parsed.port_or_known_default().or(match parsed.scheme() {
+        "ws" => Some(80),
+        "wss" => Some(443),
+        _ => None,
+    })?;
+    let health = match parsed.scheme() {
+        "ws" | "http" => Some(format!("http://{}:{}/health", host, port)),
+        "wss" | "https" => Some(format!("https://{}:{}/health", host, port)),
+        _ => None,
+    };
+    Some((host, port, health))
+}
+
+/// Remote SurrealDB from `DB_URL`/`MCP_DB_WS_ENDPOINT` answers its health check
+async fn check_database() -> Finding {
+    const CHECK: &str = "surrealdb";
+    let Some(url) = std::env::var("DB_URL")
+        .ok()
+        .or_else(|| std::env::var("MCP_DB_WS_ENDPOINT").ok())
+    else {
+        return Finding::new(
+            CHECK,
+            Status::Skip,
+            "no DB_URL set; embedded storage is used",
+        );
+    };
+    let Some((host, port, health)) = db_endpoint(&url) else {
+        return Finding::new(CHECK, Status::Fail, format!("cannot parse DB_URL {}", url))
+            .fix("set DB_URL to e.g. ws://127.0.0.1:8000");
+    };
+
+    let reachable = (host.as_str(), port)
+        .to_socket_addrs()
+        .ok()
+        .and_then(|mut addrs| addrs.next())
+        .map(|addr| TcpStream::connect_timeout(&addr, NETWORK_TIMEOUT));
+    match reachable {
+        Some(Ok(_)) => {}
+        Some(Err(e)) => {
+            return Finding::new(
+                CHECK,
+                Status::Fail,
+                format!("{}:{} unreachable: {}", host, port, e),
+            )
+            .fix("start SurrealDB (`surreal start`) or correct DB_URL");
+        }
+        None => {
+            return Finding::new(CHECK, Status::Fail, format!("cannot resolve {}", host))
+                .fix("check DNS or use an IP address in DB_URL");
+        }
+    }
+
+    let Some(health) = health else {
+        return Finding::new(
+            CHECK,
+            Status::Ok,
+            format!("{}:{} accepts connections", host, port),
+        );
+    };
+    let client = reqwest::Client::builder()
+        .timeout(NETWORK_TIMEOUT)
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new());
+    match client.get(&health).send().await {
+        Ok(response) if response.status().is_success() => {
+            Finding::new(CHECK, Status::Ok, format!("{} is healthy", url))
+        }
+        Ok(response) => Finding::new(
+            CHECK,
+            Status::Warn,
+            format!("{} answered {}", health, response.status()),
+        )
+        .fix("check the SurrealDB logs"),
+        Err(e) => Finding::new(
+            CHECK,
+            Status::Warn,
+            format!("port open but {} failed: {}", health, e),
+        )
+        .fix("make sure DB_URL points at SurrealDB and not another service"),
+    }
+}
+
+/// Local clock against the `Date` header of a well-known HTTPS server
+async fn check_clock_skew() -> Finding {
+    const CHECK: &str = "clock";
+    let client = reqwest::Client::builder()
+        .timeout(NETWORK_TIMEOUT)
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new());
+    let response = match client.head(TIME_REFERENCE).send().await {
+        Ok(response) => response,
+        Err(e) => {
+            return Finding::new(
+                CHECK,
+                Status::Skip,
+                format!("cannot reach {}: {}", TIME_REFERENCE, e),
+            );
+        }
+    };
+    let remote = response
+        .headers()
+        .get(reqwest::header::DATE)
+        .and_then(|date| date.to_str().ok())
+        .and_then(|date| DateTime::parse_from_rfc2822(date).ok());
+    match remote {
+        Some(remote) => skew_finding((Utc::now() - remote.with_timezone(&Utc)).num_seconds()),
+        None => Finding::new(
+            CHECK,
+            Status::Skip,
+            format!("{} sent no usable Date header", TIME_REFERENCE),
+        ),
+    }
+}
+
+fn skew_finding(skew_secs: i64) -> Finding {
+    const CHECK: &str = "clock";
+    let direction = if skew_secs >= 0 { "ahead" } else { "behind" };
+    let detail = format!("{}s {}", skew_secs.abs(), direction);
+    let status = match skew_secs.abs() {
+        s if s > SKEW_FAIL_SECS => Status::Fail,
+        s if s > SKEW_WARN_SECS => Status::Warn,
+        _ => return Finding::new(CHECK, Status::Ok, format!("in sync ({})", detail)),
+    };
+    Finding::new(CHECK, status, detail).fix(
+        "enable time sync: `sudo timedatectl set-ntp true` (Linux), \
+         `sudo sntp -sS time.apple.com` (macOS) or `w32tm /resync` (Windows)",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skew_thresholds() {
+        assert_eq!(skew_finding(2).status, Status::Ok);
+        assert_eq!(skew_finding(-30).status, Status::Warn);
+        assert!(skew_finding(-30).detail.contains("behind"));
+        assert_eq!(skew_finding(600).status, Status::Fail);
+    }
+
+    #[test]
+    fn db_endpoint_maps_websocket_to_health_url() {
+        assert_eq!(
+            db_endpoint("ws://db.local:8000/rpc"),
+            Some((
+                "db.local".to_string(),
+                8000,
+                Some("http://db.local:8000/health".to_string())
+            ))
+        );
+        assert_eq!(
+            db_endpoint("wss://db.example.com").map(|(_, port, _)| port),
+            Some(443)
+        );
+        assert_eq!(db_endpoint("not a url"), None);
+    }
+
+    #[test]
+    fn only_our_denials_count() {
+        let log = "type=AVC msg=audit(1): avc:  denied  { name_bind } comm=\"cyrupd\"\n\
+                   type=AVC msg=audit(2): avc:  denied  { read } comm=\"httpd\"\n\
+                   type=AVC msg=audit(3): avc:  granted  { read } comm=\"cyrupd\"\n";
+        assert_eq!(count_denials(log), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn world_writable_plugin_dir_fails() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("cyrupd-doctor-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o777)).unwrap();
+        assert_eq!(check_plugin_dir(&dir).status, Status::Fail);
+
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert_eq!(check_plugin_dir(&dir).status, Status::Warn);
+        std::fs::write(dir.join("hash.wasm"), b"\0asm").unwrap();
+        assert_eq!(check_plugin_dir(&dir).status, Status::Ok);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(check_plugin_dir(&dir).status, Status::Fail);
+    }
+}
@@ -1,5 +1,37 @@
 use crate::config::ServiceDefinition;
-use std::{collections::HashMap, path::PathBuf};
+use crate::install::definition::{SystemdUnit, WindowsService};
+use std::{collections::HashMap, fmt, path::PathBuf, sync::Arc};
+
+/// Callbacks run, in order, on a rendered service definition before the
+/// installer writes it.
+pub struct Hooks<T>(Vec<Arc<dyn Fn(&mut T) + Send + Sync>>);
+
+impl<T> Hooks<T> {
+    /// Run every hook on `definition`.
+    pub fn apply(&self, definition: &mut T) {
+        for hook in &self.0 {
+            hook(definition);
+        }
+    }
+}
+
+impl<T> Default for Hooks<T> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<T> Clone for Hooks<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> fmt::Debug for Hooks<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Hooks({})", self.0.len())
+    }
+}
 
 /// Builder for daemon installation metadata.
 ///
@@ -36,6 +68,15 @@ pub struct InstallerBuilder {
 
     /// Service definitions to install with the daemon
     pub services: Vec<ServiceDefinition>,
+
+    /// Overrides for the generated systemd unit
+    pub linux_hooks: Hooks<SystemdUnit>,
+
+    /// Overrides for the generated launchd plist
+    pub macos_hooks: Hooks<plist::Dictionary>,
+
+    /// Overrides for the generated Windows service
+    pub windows_hooks: Hooks<WindowsService>,
 }
 
 impl InstallerBuilder {
@@ -57,6 +98,9 @@ impl InstallerBuilder {
             auto_restart: true,
             wants_network: true,
             services: Vec::new(),
+            linux_hooks: Hooks::default(),
+            macos_hooks: Hooks::default(),
+            windows_hooks: Hooks::default(),
         }
     }
 
@@ -118,6 +162,37 @@ impl InstallerBuilder {
         services.push(service);
         Self { services, ..self }
     }
+
+    /// Adjust the systemd unit before it is written, e.g.
+    /// `unit.add("Unit", "After", "network-online.target")`. Only called on
+    /// Linux; hooks run in the order they were added.
+    pub fn customize_linux(
+        mut self,
+        hook: impl Fn(&mut SystemdUnit) + Send + Sync + 'static,
+    ) -> Self {
+        self.linux_hooks.0.push(Arc::new(hook));
+        self
+    }
+
+    /// Adjust the launchd plist before it is written, e.g. to add
+    /// `AssociatedBundleIdentifiers`. Only called on macOS.
+    pub fn customize_macos(
+        mut self,
+        hook: impl Fn(&mut plist::Dictionary) + Send + Sync + 'static,
+    ) -> Self {
+        self.macos_hooks.0.push(Arc::new(hook));
+        self
+    }
+
+    /// Adjust the service before it is registered with the Service Control
+    /// Manager, e.g. to add dependencies. Only called on Windows.
+    pub fn customize_windows(
+        mut self,
+        hook: impl Fn(&mut WindowsService) + Send + Sync + 'static,
+    ) -> Self {
+        self.windows_hooks.0.push(Arc::new(hook));
+        self
+    }
 }
 
 /// Builder for privileged command execution.
@@ -0,0 +1,200 @@
+//! Rendered service definitions handed to the `InstallerBuilder::customize_*`
+//! hooks before they are written, and read back from services found on the
+//! machine.
+//!
+//! The types are available on every platform so one builder chain can carry
+//! overrides for all three; only the hook for the running platform is called.
+
+/// A systemd unit as ordered `[Section]`s of `Key=value` directives
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SystemdUnit {
+    sections: Vec<(String, Vec<(String, String)>)>,
+}
+
+impl SystemdUnit {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a directive, creating the section if needed. Keys that systemd
+    /// accumulates, such as `After=` or `Environment=`, may be added repeatedly.
+    pub fn add(&mut self, section: &str, key: &str, value: impl Into<String>) -> &mut Self {
+        self.section_mut(section)
+            .push((key.to_string(), value.into()));
+        self
+    }
+
+    /// Replace every `key` in `section` with one directive, keeping the
+    /// position of the first.
+    pub fn set(&mut self, section: &str, key: &str, value: impl Into<String>) -> &mut Self {
+        let value = value.into();
+        let directives = self.section_mut(section);
+        let mut kept = false;
+        directives.retain_mut(|(k, v)| {
+            if k != key {
+                return true;
+            }
+            if kept {
+                return false;
+            }
+            kept = true;
+            *v = value.clone();
+            true
+        });
+        if !kept {
+            directives.push((key.to_string(), value));
+        }
+        self
+    }
+
+    /// Drop every `key` in `section`.
+    pub fn remove(&mut self, section: &str, key: &str) -> &mut Self {
+        if let Some((_, directives)) = self.sections.iter_mut().find(|(s, _)| s == section) {
+            directives.retain(|(k, _)| k != key);
+        }
+        self
+    }
+
+    /// Values of `key` in `section`, in order.
+    pub fn get(&self, section: &str, key: &str) -> Vec<&str> {
+        self.sections
+            .iter()
+            .filter(|(s, _)| s == section)
+            .flat_map(|(_, directives)| directives)
+            .filter(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+            .collect()
+    }
+
+    /// Read an existing unit file. Comments and blank lines are dropped;
+    /// continuation lines are not supported.
+    pub fn parse(text: &str) -> Self {
+        let mut unit = Self::new();
+        let mut section = None;
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                unit.section_mut(name);
+                section = Some(name.to_string());
+            } else if let (Some(section), Some((key, value))) = (&section, line.split_once('=')) {
+                unit.add(section, key.trim(), value.trim());
+            }
+        }
+        unit
+    }
+
+    /// The executable `ExecStart=` runs, without systemd's `-@:+!` prefixes
+    pub fn exec_program(&self) -> Option<std::path::PathBuf> {
+        let exec = self.get("Service", "ExecStart").into_iter().next()?;
+        command_program(exec.trim_start_matches(['-', '@', ':', '+', '!']))
+    }
+
+    /// The unit file contents.
+    pub fn render(&self) -> String {
+        let mut content = String::with_capacity(2048);
+        for (i, (section, directives)) in self.sections.iter().enumerate() {
+            if i > 0 {
+                content.push('\n');
+            }
+            content.push_str(&format!("[{}]\n", section));
+            for (key, value) in directives {
+                content.push_str(&format!("{}={}\n", key, value));
+            }
+        }
+        content
+    }
+
+    fn section_mut(&mut self, section: &str) -> &mut Vec<(String, String)> {
+        let index = match self.sections.iter().position(|(s, _)| s == section) {
+            Some(index) => index,
+            None => {
+                self.sections.push((section.to_string(), Vec::new()));
+                self.sections.len() - 1
+            }
+        };
+        &mut self.sections[index].1
+    }
+}
+
+/// What the installer registers with the Service Control Manager
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowsService {
+    pub display_name: String,
+    pub description: String,
+    /// Quoted executable and its arguments, as the SCM starts it
+    pub command_line: String,
+    /// Services that have to be running first, e.g. `Tcpip`
+    pub dependencies: Vec<String>,
+    /// Start shortly after boot rather than with the other auto-start services
+    pub delayed_start: bool,
+    /// String values written under `HKLM\SYSTEM\CurrentControlSet\Services\<label>\Parameters`
+    pub parameters: Vec<(String, String)>,
+}
+
+/// The executable at the start of a command line, quoted or not
+pub(crate) fn command_program(command_line: &str) -> Option<std::path::PathBuf> {
+    let line = command_line.trim_start();
+    let program = match line.strip_prefix('"') {
+        Some(rest) => rest.split('"').next()?,
+        None => line.split_whitespace().next()?,
+    };
+    (!program.is_empty()).then(|| program.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unit_directives_keep_their_order() {
+        let mut unit = SystemdUnit::new();
+        unit.add("Unit", "Description", "demo")
+            .add("Unit", "After", "network.target")
+            .add("Service", "ExecStart", "/usr/bin/demo")
+            .add("Unit", "After", "network-online.target");
+        unit.set("Service", "Restart", "always");
+
+        assert_eq!(
+            unit.get("Unit", "After"),
+            vec!["network.target", "network-online.target"]
+        );
+        assert_eq!(
+            unit.render(),
+            "[Unit]\nDescription=demo\nAfter=network.target\nAfter=network-online.target\n\n\
+             [Service]\nExecStart=/usr/bin/demo\nRestart=always\n"
+        );
+    }
+
+    #[test]
+    fn set_collapses_repeated_keys() {
+        let mut unit = SystemdUnit::new();
+        unit.add("Unit", "After", "a")
+            .add("Unit", "Wants", "b")
+            .add("Unit", "After", "c");
+        unit.set("Unit", "After", "d");
+        assert_eq!(unit.render(), "[Unit]\nAfter=d\nWants=b\n");
+
+        unit.remove("Unit", "After").remove("Missing", "After");
+        assert_eq!(unit.render(), "[Unit]\nWants=b\n");
+    }
+
+    #[test]
+    fn parses_the_program_of_a_foreign_unit() {
+        let unit = SystemdUnit::parse(
+            "# installed by hand\n[Unit]\nDescription=cyrupd\n\n[Service]\nExecStart=-/opt/cyrupd/bin/cyrupd run --foreground\n",
+        );
+        assert_eq!(unit.get("Unit", "Description"), vec!["cyrupd"]);
+        assert_eq!(
+            unit.exec_program(),
+            Some(std::path::PathBuf::from("/opt/cyrupd/bin/cyrupd"))
+        );
+        assert_eq!(
+            command_program(r#""C:\Program Files\cyrupd\cyrupd.exe" run"#),
+            Some(std::path::PathBuf::from(
+                r"C:\Program Files\cyrupd\cyrupd.exe"
+            ))
+        );
+    }
+}
@@ -3,7 +3,7 @@
 //! This implementation provides sophisticated service management with zero allocation,
 //! blazing-fast performance, and comprehensive error handling to match the macOS implementation.
 
-use crate::install::{InstallerBuilder, InstallerError};
+use crate::install::{ExistingService, Hooks, InstallerBuilder, InstallerError, SystemdUnit};
 use anyhow::{Context, Result};
 use once_cell::sync::OnceCell;
 use std::borrow::Cow;
@@ -45,6 +45,7 @@ struct SystemdConfig<'a> {
     wants_network: bool,
     user: Option<&'a str>,
     group: Option<&'a str>,
+    hooks: &'a Hooks<SystemdUnit>,
 }
 
 impl PlatformExecutor {
@@ -69,6 +70,7 @@ impl PlatformExecutor {
             wants_network: b.wants_network,
             user: None, // Run as root for system service
             group: None,
+            hooks: &b.linux_hooks,
         };
 
         // Generate and install systemd unit file
@@ -118,6 +120,35 @@ impl PlatformExecutor {
         Ok(())
     }
 
+    /// Find a unit for `label`, whether this installer, a package or a
+    /// person put it there
+    pub fn detect(label: &str) -> Option<ExistingService> {
+        let name = format!("{}.service", label);
+        let mut candidates = vec![
+            PathBuf::from("/etc/systemd/system"),
+            PathBuf::from("/usr/local/lib/systemd/system"),
+            PathBuf::from("/usr/lib/systemd/system"),
+            PathBuf::from("/lib/systemd/system"),
+        ];
+        if let Ok(home) = std::env::var("HOME") {
+            candidates.push(PathBuf::from(home).join(".config/systemd/user"));
+        }
+        let path = candidates
+            .into_iter()
+            .map(|dir| dir.join(&name))
+            .find(|path| path.is_file())?;
+        let program = fs::read_to_string(&path)
+            .ok()
+            .and_then(|unit| SystemdUnit::parse(&unit).exec_program());
+
+        Some(ExistingService {
+            label: label.to_string(),
+            definition: path.display().to_string(),
+            program,
+            managed: false,
+        })
+    }
+
     /// Ensure helper executable is extracted and available
     fn ensure_helper_path() -> Result<(), InstallerError> {
         if HELPER_PATH.get().is_some() {
@@ -225,99 +256,94 @@ impl PlatformExecutor {
         Ok(())
     }
 
-    /// Generate systemd unit file content with zero allocation where possible
+    /// Generate systemd unit file content, after the builder's `customize_linux` hooks
     fn generate_unit_content(config: &SystemdConfig) -> Result<String, InstallerError> {
-        let mut content = String::with_capacity(2048); // Pre-allocate for performance
+        let mut unit = SystemdUnit::new();
 
         // [Unit] section
-        content.push_str("[Unit]\n");
-        content.push_str(&format!("Description={}\n", config.description));
-        content.push_str("Documentation=https://github.com/cyrup/sweetmcp\n");
+        unit.add("Unit", "Description", config.description).add(
+            "Unit",
+            "Documentation",
+            "https://github.com/cyrup/sweetmcp",
+        );
 
         if config.wants_network {
-            content.push_str("Wants=network-online.target\n");
-            content.push_str("After=network-online.target\n");
-            content.push_str("Requires=network.target\n");
+            unit.add("Unit", "Wants", "network-online.target")
+                .add("Unit", "After", "network-online.target")
+                .add("Unit", "Requires", "network.target");
         }
 
-        content.push_str("After=multi-user.target\n");
-        content.push_str("DefaultDependencies=no\n");
-        content.push('\n');
+        unit.add("Unit", "After", "multi-user.target")
+            .add("Unit", "DefaultDependencies", "no");
 
         // [Service] section
-        content.push_str("[Service]\n");
-        content.push_str("Type=notify\n"); // Use sd_notify for proper startup signaling
-        content.push_str("NotifyAccess=main\n");
+        unit.add("Service", "Type", "notify") // Use sd_notify for proper startup signaling
+            .add("Service", "NotifyAccess", "main");
 
         // Build ExecStart command
         let exec_start = if config.args.is_empty() {
-            format!("ExecStart={}\n", config.binary_path)
+            config.binary_path.to_string()
         } else {
-            format!(
-                "ExecStart={} {}\n",
-                config.binary_path,
-                config.args.join(" ")
-            )
+            format!("{} {}", config.binary_path, config.args.join(" "))
         };
-        content.push_str(&exec_start);
+        unit.add("Service", "ExecStart", exec_start);
 
         // Restart configuration
         if config.auto_restart {
-            content.push_str("Restart=on-failure\n");
-            content.push_str("RestartSec=5s\n");
-            content.push_str("StartLimitInterval=60s\n");
-            content.push_str("StartLimitBurst=3\n");
+            unit.add("Service", "Restart", "on-failure")
+                .add("Service", "RestartSec", "5s")
+                .add("Service", "StartLimitInterval", "60s")
+                .add("Service", "StartLimitBurst", "3");
         } else {
-            content.push_str("Restart=no\n");
+            unit.add("Service", "Restart", "no");
         }
 
         // Environment variables
         for (key, value) in config.env_vars {
-            content.push_str(&format!("Environment=\"{}={}\"\n", key, value));
+            unit.add("Service", "Environment", format!("\"{}={}\"", key, value));
         }
 
         // Security and sandboxing
-        content.push_str("NoNewPrivileges=true\n");
-        content.push_str("ProtectSystem=strict\n");
-        content.push_str("ProtectHome=true\n");
-        content.push_str("ProtectKernelTunables=true\n");
-        content.push_str("ProtectControlGroups=true\n");
-        content.push_str("RestrictSUIDSGID=true\n");
-        content.push_str("RestrictRealtime=true\n");
-        content.push_str("RestrictNamespaces=true\n");
-        content.push_str("LockPersonality=true\n");
-        content.push_str("MemoryDenyWriteExecute=true\n");
+        unit.add("Service", "NoNewPrivileges", "true")
+            .add("Service", "ProtectSystem", "strict")
+            .add("Service", "ProtectHome", "true")
+            .add("Service", "ProtectKernelTunables", "true")
+            .add("Service", "ProtectControlGroups", "true")
+            .add("Service", "RestrictSUIDSGID", "true")
+            .add("Service", "RestrictRealtime", "true")
+            .add("Service", "RestrictNamespaces", "true")
+            .add("Service", "LockPersonality", "true")
+            .add("Service", "MemoryDenyWriteExecute", "true");
 
         // Allow specific directories for daemon operation
-        content.push_str("ReadWritePaths=/var/log /var/lib /tmp\n");
-        content.push_str("ReadOnlyPaths=/etc\n");
+        unit.add("Service", "ReadWritePaths", "/var/log /var/lib /tmp")
+            .add("Service", "ReadOnlyPaths", "/etc");
 
         // Resource limits
-        content.push_str("LimitNOFILE=65536\n");
-        content.push_str("LimitNPROC=4096\n");
+        unit.add("Service", "LimitNOFILE", "65536")
+            .add("Service", "LimitNPROC", "4096");
 
         // User/Group configuration
         if let Some(user) = config.user {
-            content.push_str(&format!("User={}\n", user));
+            unit.add("Service", "User", user);
         }
         if let Some(group) = config.group {
-            content.push_str(&format!("Group={}\n", group));
+            unit.add("Service", "Group", group);
         }
 
         // Logging
-        content.push_str("StandardOutput=journal\n");
-        content.push_str("StandardError=journal\n");
-        content.push_str("SyslogIdentifier=sweetmcp\n");
+        unit.add("Service", "StandardOutput", "journal")
+            .add("Service", "StandardError", "journal")
+            .add("Service", "SyslogIdentifier", "sweetmcp");
 
         // Watchdog support
-        content.push_str("WatchdogSec=30s\n");
-        content.push('\n');
+        unit.add("Service", "WatchdogSec", "30s");
 
         // [Install] section
-        content.push_str("[Install]\n");
-        content.push_str("WantedBy=multi-user.target\n");
+        unit.add("Install", "WantedBy", "multi-user.target");
 
-        Ok(content)
+        config.hooks.apply(&mut unit);
+        Ok(unit.render())
     }
 
     /// Create systemd drop-in configuration for advanced features
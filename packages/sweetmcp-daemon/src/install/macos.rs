@@ -1,7 +1,7 @@
 //! macOS platform implementation using osascript and launchd.
 
 use crate::install::builder::CommandBuilder;
-use crate::install::{InstallerBuilder, InstallerError};
+use crate::install::{ExistingService, InstallerBuilder, InstallerError};
 use anyhow::{Context, Result};
 use once_cell::sync::OnceCell;
 use plist::Value;
@@ -583,6 +583,41 @@ impl PlatformExecutor {
         Ok(true)
     }
 
+    /// Find a launchd plist for `label`, whoever installed it
+    pub fn detect(label: &str) -> Option<ExistingService> {
+        let name = format!("{}.plist", label);
+        let mut candidates = vec![
+            PathBuf::from("/Library/LaunchDaemons"),
+            PathBuf::from("/Library/LaunchAgents"),
+        ];
+        if let Some(home) = dirs::home_dir() {
+            candidates.push(home.join("Library/LaunchAgents"));
+        }
+        let path = candidates
+            .into_iter()
+            .map(|dir| dir.join(&name))
+            .find(|path| path.is_file())?;
+        let program = Value::from_file(&path).ok().and_then(|plist| {
+            let plist = plist.into_dictionary()?;
+            match plist.get("Program") {
+                Some(Value::String(program)) => Some(PathBuf::from(program)),
+                _ => plist
+                    .get("ProgramArguments")?
+                    .as_array()?
+                    .first()?
+                    .as_string()
+                    .map(PathBuf::from),
+            }
+        });
+
+        Some(ExistingService {
+            label: label.to_string(),
+            definition: path.display().to_string(),
+            program,
+            managed: false,
+        })
+    }
+
     pub fn uninstall(label: &str) -> Result<(), InstallerError> {
         let script = format!(
             r#"
@@ -670,10 +705,13 @@ impl PlatformExecutor {
             );
         }
 
+        // Advanced overrides from `customize_macos`
+        let mut plist: plist::Dictionary = plist.into_iter().collect();
+        b.macos_hooks.apply(&mut plist);
+
         // Generate XML
         let mut buf = Vec::new();
-        plist::to_writer_xml(&mut buf, &Value::Dictionary(plist.into_iter().collect()))
-            .expect("plist generation failed");
+        plist::to_writer_xml(&mut buf, &Value::Dictionary(plist)).expect("plist generation failed");
         String::from_utf8(buf).expect("valid utf8")
     }
 
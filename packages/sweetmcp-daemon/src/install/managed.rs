@@ -0,0 +1,211 @@
+//! Services this installer manages, and those it finds that it doesn't.
+//!
+//! `install_daemon` records each service it installs in a small JSON
+//! manifest and `uninstall_daemon` drops it again. A unit file, plist or SCM
+//! entry for the same label that is not in the manifest was put there by hand
+//! or by another installer; `migrate` adopts such a service when it runs the
+//! same program, so later installs treat it as ours instead of tripping over it.
+
+use crate::install::{InstallerBuilder, InstallerError};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A service definition for a label, found on this machine
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExistingService {
+    pub label: String,
+    /// Unit file or plist holding the definition, or `scm:<name>` on Windows
+    pub definition: String,
+    /// Executable the definition starts, when it could be read
+    pub program: Option<PathBuf>,
+    /// Whether the manifest already lists it
+    pub managed: bool,
+}
+
+impl ExistingService {
+    /// Whether it runs the program `builder` would install. macOS installs
+    /// copy the binary, so the same file name counts as the same program.
+    pub fn equivalent(&self, builder: &InstallerBuilder) -> bool {
+        let Some(program) = &self.program else {
+            return false;
+        };
+        if program == &builder.program {
+            return true;
+        }
+        match (
+            fs::canonicalize(program),
+            fs::canonicalize(&builder.program),
+        ) {
+            (Ok(a), Ok(b)) if a == b => return true,
+            _ => {}
+        }
+        program.file_name().is_some() && program.file_name() == builder.program.file_name()
+    }
+}
+
+/// One entry of the manifest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManagedService {
+    pub label: String,
+    pub definition: String,
+    pub program: PathBuf,
+    pub since: DateTime<Utc>,
+    /// Installed by something else and adopted by `migrate`
+    #[serde(default)]
+    pub adopted: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ManagedManifest {
+    #[serde(default)]
+    pub services: Vec<ManagedService>,
+}
+
+impl ManagedManifest {
+    /// `/var/lib/cyrupd/installed.json` for system installs (the
+    /// `%ProgramData%` equivalent on Windows), the user data dir otherwise
+    pub fn path() -> Option<PathBuf> {
+        #[cfg(windows)]
+        let dir = std::env::var_os("ProgramData")
+            .map(PathBuf::from)
+            .or_else(dirs::data_local_dir);
+        #[cfg(not(windows))]
+        let dir = if nix::unistd::geteuid().is_root() {
+            Some(PathBuf::from("/var/lib"))
+        } else {
+            dirs::data_local_dir()
+        };
+        dir.map(|dir| dir.join("cyrupd").join("installed.json"))
+    }
+
+    pub fn load() -> Result<Self, InstallerError> {
+        match Self::path() {
+            Some(path) => Self::load_from(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn save(&self) -> Result<(), InstallerError> {
+        let path = Self::path().ok_or_else(|| {
+            InstallerError::System("cannot determine where to keep the install manifest".into())
+        })?;
+        self.save_to(&path)
+    }
+
+    /// Read a manifest; a missing file is an empty one
+    pub fn load_from(path: &Path) -> Result<Self, InstallerError> {
+        match fs::read_to_string(path) {
+            Ok(raw) => serde_json::from_str(&raw).map_err(|e| {
+                InstallerError::System(format!(
+                    "invalid install manifest {}: {}",
+                    path.display(),
+                    e
+                ))
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), InstallerError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let raw = serde_json::to_string_pretty(self)
+            .map_err(|e| InstallerError::System(format!("serialize install manifest: {}", e)))?;
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, raw)?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    pub fn get(&self, label: &str) -> Option<&ManagedService> {
+        self.services.iter().find(|s| s.label == label)
+    }
+
+    /// Add `service`, replacing any entry for the same label
+    pub fn record(&mut self, service: ManagedService) {
+        self.forget(&service.label);
+        self.services.push(service);
+    }
+
+    /// Drop the entry for `label`; returns whether there was one
+    pub fn forget(&mut self, label: &str) -> bool {
+        let before = self.services.len();
+        self.services.retain(|s| s.label != label);
+        self.services.len() != before
+    }
+}
+
+/// What `migrate` did
+#[derive(Debug)]
+pub enum Migration {
+    /// Nothing was installed under the label; it is now
+    Installed,
+    /// The service was already ours and has been installed again
+    Reinstalled,
+    /// An equivalent service installed some other way is now managed; its
+    /// definition is left as it was until the next install
+    Adopted(ExistingService),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn existing(program: &str) -> ExistingService {
+        ExistingService {
+            label: "cyrupd".into(),
+            definition: "/etc/systemd/system/cyrupd.service".into(),
+            program: Some(program.into()),
+            managed: false,
+        }
+    }
+
+    #[test]
+    fn equivalence_follows_the_program() {
+        let builder = InstallerBuilder::new("cyrupd", "/opt/cyrupd/bin/cyrupd");
+        assert!(existing("/opt/cyrupd/bin/cyrupd").equivalent(&builder));
+        assert!(existing("/usr/local/bin/cyrupd").equivalent(&builder));
+        assert!(!existing("/usr/bin/other-daemon").equivalent(&builder));
+
+        let mut unknown = existing("/opt/cyrupd/bin/cyrupd");
+        unknown.program = None;
+        assert!(!unknown.equivalent(&builder));
+    }
+
+    #[test]
+    fn manifest_round_trips_and_replaces_by_label() {
+        let path = std::env::temp_dir()
+            .join(format!("cyrupd-managed-{}", std::process::id()))
+            .join("installed.json");
+        assert!(ManagedManifest::load_from(&path)
+            .unwrap()
+            .services
+            .is_empty());
+
+        let entry = |program: &str, adopted| ManagedService {
+            label: "cyrupd".into(),
+            definition: "/etc/systemd/system/cyrupd.service".into(),
+            program: program.into(),
+            since: Utc::now(),
+            adopted,
+        };
+        let mut manifest = ManagedManifest::default();
+        manifest.record(entry("/usr/bin/cyrupd", true));
+        manifest.record(entry("/usr/local/bin/cyrupd", false));
+        manifest.save_to(&path).unwrap();
+
+        let mut loaded = ManagedManifest::load_from(&path).unwrap();
+        assert_eq!(loaded.services.len(), 1);
+        let service = loaded.get("cyrupd").unwrap();
+        assert_eq!(service.program, PathBuf::from("/usr/local/bin/cyrupd"));
+        assert!(!service.adopted);
+        assert!(loaded.forget("cyrupd"));
+        assert!(!loaded.forget("cyrupd"));
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+}
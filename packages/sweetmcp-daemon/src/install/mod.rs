@@ -7,8 +7,10 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
 mod builder;
+mod definition;
 mod error;
 pub mod fluent_voice;
+mod managed;
 
 cfg_if::cfg_if! {
     if #[cfg(target_os = "linux")] {
@@ -25,28 +27,146 @@ cfg_if::cfg_if! {
     }
 }
 
-pub use builder::InstallerBuilder;
+pub use builder::{Hooks, InstallerBuilder};
+pub use definition::{SystemdUnit, WindowsService};
 pub use error::InstallerError;
+pub use managed::{ExistingService, ManagedManifest, ManagedService, Migration};
 
 /// Result type alias for installer operations
 pub type Result<T> = std::result::Result<T, InstallerError>;
 
 /// Synchronous daemon installation.
 pub fn install_daemon(builder: InstallerBuilder) -> Result<()> {
-    Executor::install(builder)
+    let (label, program) = (builder.label.clone(), builder.program.clone());
+    Executor::install(builder)?;
+    record_managed(&label, program);
+    Ok(())
 }
 
 /// Synchronous daemon uninstallation.
 pub fn uninstall_daemon(label: &str) -> Result<()> {
-    Executor::uninstall(label)
+    Executor::uninstall(label)?;
+    forget_managed(label);
+    Ok(())
 }
 
 /// Asynchronous daemon installation.
 pub async fn install_daemon_async(builder: InstallerBuilder) -> Result<()> {
-    Executor::install_async(builder).await
+    let (label, program) = (builder.label.clone(), builder.program.clone());
+    Executor::install_async(builder).await?;
+    record_managed(&label, program);
+    Ok(())
 }
 
 /// Asynchronous daemon uninstallation.
 pub async fn uninstall_daemon_async(label: &str) -> Result<()> {
-    Executor::uninstall_async(label).await
+    Executor::uninstall_async(label).await?;
+    forget_managed(label);
+    Ok(())
+}
+
+/// Find a unit file, plist or SCM entry for `label`, whether this installer
+/// or something else put it there.
+pub fn detect_existing(label: &str) -> Result<Option<ExistingService>> {
+    let Some(mut existing) = Executor::detect(label) else {
+        return Ok(None);
+    };
+    existing.managed = ManagedManifest::load()?.get(label).is_some();
+    Ok(Some(existing))
+}
+
+/// Install `builder`, taking over an equivalent service that was installed
+/// some other way instead of failing on it.
+///
+/// A service this installer manages is installed again. One it doesn't
+/// manage is adopted into the manifest, unchanged, when it runs the same
+/// program; one that runs something else is left alone with an error.
+pub fn migrate(builder: InstallerBuilder) -> Result<Migration> {
+    match detect_existing(&builder.label)? {
+        None => install_daemon(builder).map(|()| Migration::Installed),
+        Some(existing) if existing.managed => {
+            install_daemon(builder).map(|()| Migration::Reinstalled)
+        }
+        Some(existing) => adopt(existing, &builder),
+    }
+}
+
+/// Asynchronous [`migrate`].
+pub async fn migrate_async(builder: InstallerBuilder) -> Result<Migration> {
+    match detect_existing(&builder.label)? {
+        None => install_daemon_async(builder)
+            .await
+            .map(|()| Migration::Installed),
+        Some(existing) if existing.managed => install_daemon_async(builder)
+            .await
+            .map(|()| Migration::Reinstalled),
+        Some(existing) => adopt(existing, &builder),
+    }
+}
+
+fn adopt(existing: ExistingService, builder: &InstallerBuilder) -> Result<Migration> {
+    if !existing.equivalent(builder) {
+        return Err(InstallerError::System(format!(
+            "'{}' is already installed at {} and runs {}, not {}; uninstall it or choose another label",
+            existing.label,
+            existing.definition,
+            existing
+                .program
+                .as_deref()
+                .map(|p| p.display().to_string())
+                .unwrap_or_else(|| "an unknown program".into()),
+            builder.program.display()
+        )));
+    }
+
+    let mut manifest = ManagedManifest::load()?;
+    manifest.record(ManagedService {
+        label: existing.label.clone(),
+        definition: existing.definition.clone(),
+        program: existing
+            .program
+            .clone()
+            .unwrap_or_else(|| builder.program.clone()),
+        since: chrono::Utc::now(),
+        adopted: true,
+    });
+    manifest.save()?;
+    Ok(Migration::Adopted(existing))
+}
+
+/// Note a finished install in the manifest. The service is already in
+/// place, so a manifest that cannot be written only costs a warning.
+fn record_managed(label: &str, program: std::path::PathBuf) {
+    let definition = Executor::detect(label)
+        .map(|existing| existing.definition)
+        .unwrap_or_default();
+    let result = ManagedManifest::load().and_then(|mut manifest| {
+        manifest.record(ManagedService {
+            label: label.to_string(),
+            definition,
+            program,
+            since: chrono::Utc::now(),
+            adopted: false,
+        });
+        manifest.save()
+    });
+    if let Err(e) = result {
+        log::warn!("Installed {} but could not record it: {}", label, e);
+    }
+}
+
+fn forget_managed(label: &str) {
+    let result = ManagedManifest::load().and_then(|mut manifest| {
+        if manifest.forget(label) {
+            manifest.save()?;
+        }
+        Ok(())
+    });
+    if let Err(e) = result {
+        log::warn!(
+            "Uninstalled {} but could not update the install manifest: {}",
+            label,
+            e
+        );
+    }
 }
@@ -3,7 +3,8 @@
 //! This implementation provides sophisticated service management with zero allocation,
 //! blazing-fast performance, and comprehensive error handling to match the macOS implementation.
 
-use crate::install::{InstallerBuilder, InstallerError};
+use crate::install::definition::command_program;
+use crate::install::{ExistingService, InstallerBuilder, InstallerError, WindowsService};
 use anyhow::{Context, Result};
 use once_cell::sync::OnceCell;
 use std::ffi::{OsStr, OsString};
@@ -137,18 +138,24 @@ impl PlatformExecutor {
         // Check if we have sufficient privileges
         Self::check_privileges()?;
 
+        // Render the service definition and apply `customize_windows` overrides
+        let mut definition = Self::service_definition(&b);
+        b.windows_hooks.apply(&mut definition);
+
         // Create the service with full configuration
         let sc_manager = ScManagerHandle::new()?;
-        let service = Self::create_service(&sc_manager, &b)?;
+        let service = Self::create_service(&sc_manager, &b.label, &definition)?;
 
         // Configure advanced service properties
-        Self::configure_service_description(&service, &b.description)?;
+        Self::configure_service_description(&service, &definition.description)?;
         Self::configure_failure_actions(&service, b.auto_restart)?;
-        Self::configure_delayed_start(&service)?;
+        if definition.delayed_start {
+            Self::configure_delayed_start(&service)?;
+        }
         Self::configure_service_sid(&service)?;
 
         // Create registry entries for custom configuration
-        Self::create_registry_entries(&b)?;
+        Self::create_registry_entries(&b, &definition)?;
 
         // Register Windows Event Log source
         Self::register_event_source(&b.label)?;
@@ -166,6 +173,31 @@ impl PlatformExecutor {
         Ok(())
     }
 
+    /// Find an SCM entry for `label`, whoever registered it
+    pub fn detect(label: &str) -> Option<ExistingService> {
+        let output = std::process::Command::new("sc.exe")
+            .args(["qc", label])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let program = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .find_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                (key.trim() == "BINARY_PATH_NAME").then(|| value.trim().to_string())
+            })
+            .and_then(|command_line| command_program(&command_line));
+
+        Some(ExistingService {
+            label: label.to_string(),
+            definition: format!("scm:{}", label),
+            program,
+            managed: false,
+        })
+    }
+
     /// Uninstall the Windows service and clean up all resources
     pub fn uninstall(label: &str) -> Result<(), InstallerError> {
         let sc_manager = ScManagerHandle::new()?;
@@ -266,10 +298,42 @@ impl PlatformExecutor {
         Ok(())
     }
 
+    /// The service as the builder describes it, before any overrides
+    fn service_definition(builder: &InstallerBuilder) -> WindowsService {
+        // Build binary path with arguments
+        let command_line = if builder.args.is_empty() {
+            builder.program.to_string_lossy().to_string()
+        } else {
+            format!(
+                "\"{}\" {}",
+                builder.program.display(),
+                builder.args.join(" ")
+            )
+        };
+
+        WindowsService {
+            display_name: builder.description.clone(),
+            description: builder.description.clone(),
+            command_line,
+            dependencies: if builder.wants_network {
+                vec!["Tcpip".to_string(), "Afd".to_string()]
+            } else {
+                Vec::new()
+            },
+            delayed_start: true,
+            parameters: builder
+                .env
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+        }
+    }
+
     /// Create the Windows service with comprehensive configuration
     fn create_service(
         sc_manager: &ScManagerHandle,
-        builder: &InstallerBuilder,
+        label: &str,
+        definition: &WindowsService,
     ) -> Result<ServiceHandle, InstallerError> {
         // Prepare wide string buffers
         let mut service_name_buf: [u16; MAX_SERVICE_NAME] = [0; MAX_SERVICE_NAME];
@@ -278,24 +342,18 @@ impl PlatformExecutor {
         let mut dependencies_buf: [u16; MAX_DEPENDENCIES] = [0; MAX_DEPENDENCIES];
 
         // Convert strings to wide
-        Self::str_to_wide(&builder.label, &mut service_name_buf)?;
-        Self::str_to_wide(&builder.description, &mut display_name_buf)?;
-
-        // Build binary path with arguments
-        let binary_path = if builder.args.is_empty() {
-            builder.program.to_string_lossy().to_string()
-        } else {
-            format!(
-                "\"{}\" {}",
-                builder.program.display(),
-                builder.args.join(" ")
-            )
-        };
-        Self::str_to_wide(&binary_path, &mut binary_path_buf)?;
-
-        // Build dependencies string
-        if builder.wants_network {
-            Self::str_to_wide("Tcpip\0Afd\0", &mut dependencies_buf)?;
+        Self::str_to_wide(label, &mut service_name_buf)?;
+        Self::str_to_wide(&definition.display_name, &mut display_name_buf)?;
+        Self::str_to_wide(&definition.command_line, &mut binary_path_buf)?;
+
+        // Build dependencies string (double-NUL terminated list)
+        let dependencies: String = definition
+            .dependencies
+            .iter()
+            .map(|d| format!("{}\0", d))
+            .collect();
+        if !dependencies.is_empty() {
+            Self::str_to_wide(&dependencies, &mut dependencies_buf)?;
         }
 
         // Create the service
@@ -311,7 +369,7 @@ impl PlatformExecutor {
                 PCWSTR::from_raw(binary_path_buf.as_ptr()),
                 PCWSTR::null(),
                 None,
-                if builder.wants_network {
+                if !dependencies.is_empty() {
                     PCWSTR::from_raw(dependencies_buf.as_ptr())
                 } else {
                     PCWSTR::null()
@@ -326,7 +384,7 @@ impl PlatformExecutor {
             if error == ERROR_SERVICE_EXISTS {
                 return Err(InstallerError::System(format!(
                     "Service '{}' already exists",
-                    builder.label
+                    label
                 )));
             } else {
                 return Err(InstallerError::System(format!(
@@ -447,7 +505,10 @@ impl PlatformExecutor {
     }
 
     /// Create registry entries for service configuration
-    fn create_registry_entries(builder: &InstallerBuilder) -> Result<(), InstallerError> {
+    fn create_registry_entries(
+        builder: &InstallerBuilder,
+        definition: &WindowsService,
+    ) -> Result<(), InstallerError> {
         let service_key_path = format!(
             "SYSTEM\\CurrentControlSet\\Services\\{}\\Parameters",
             builder.label
@@ -475,8 +536,8 @@ impl PlatformExecutor {
 
         let registry_handle = RegistryHandle(key_handle);
 
-        // Store environment variables
-        for (key, value) in &builder.env {
+        // Store environment variables and other parameters
+        for (key, value) in &definition.parameters {
             Self::set_registry_string(&registry_handle, key, value)?;
         }
 
@@ -1,8 +1,9 @@
 use crate::install::fluent_voice;
 use crate::install::{
-    install_daemon_async, uninstall_daemon_async, InstallerBuilder, InstallerError,
+    migrate_async, uninstall_daemon_async, InstallerBuilder, InstallerError, Migration,
 };
 use crate::signing;
+use crate::wizard::{self, InstallPlan, TlsChoice};
 use anyhow::{Context, Result};
 use futures::{Future, Stream, StreamExt};
 use log::{info, warn};
@@ -88,11 +89,16 @@ impl<T, E> AsyncTask<Result<T, E>> {
 }
 
 /// Install the daemon with full end-to-end handling
-pub fn install(dry: bool, sign: bool, identity: Option<String>) -> AsyncTask<Result<()>> {
+pub fn install(
+    dry: bool,
+    sign: bool,
+    identity: Option<String>,
+    interactive: bool,
+) -> AsyncTask<Result<()>> {
     let (tx, rx) = mpsc::channel(1);
 
     tokio::spawn(async move {
-        let result = install_impl(dry, sign, identity).await;
+        let result = install_impl(dry, sign, identity, interactive).await;
         let _ = tx.send(result).await;
     });
 
@@ -117,7 +123,12 @@ pub async fn uninstall_async(dry: bool) -> Result<()> {
 }
 
 /// Internal implementation of install
-async fn install_impl(dry: bool, sign: bool, identity: Option<String>) -> Result<()> {
+async fn install_impl(
+    dry: bool,
+    sign: bool,
+    identity: Option<String>,
+    interactive: bool,
+) -> Result<()> {
     // Create config directory and file in user-specific location
     let config_dir = dirs::config_dir()
         .ok_or_else(|| anyhow::anyhow!("Could not determine config directory"))?
@@ -125,8 +136,26 @@ async fn install_impl(dry: bool, sign: bool, identity: Option<String>) -> Result
 
     let config_path = config_dir.join("cyrupd.toml");
 
-    // Create config if it doesn't exist
-    if !config_path.exists() {
+    let plan = if interactive {
+        let wizard_path = config_path.clone();
+        match tokio::task::spawn_blocking(move || wizard::run(&wizard_path)).await?? {
+            Some(plan) => plan,
+            None => return Err(anyhow::anyhow!("Installation cancelled by user")),
+        }
+    } else {
+        InstallPlan::default()
+    };
+
+    if interactive {
+        // The wizard's answers replace whatever config was there
+        info!("Writing config to {}", config_path.display());
+        if !dry {
+            fs::create_dir_all(&config_dir)?;
+            let cfg = config_from_plan(&config_path, &plan);
+            fs::write(&config_path, toml::to_string_pretty(&cfg)?)?;
+        }
+    } else if !config_path.exists() {
+        // Create config if it doesn't exist
         info!("Creating default config at {}", config_path.display());
         if !dry {
             fs::create_dir_all(&config_dir)?;
@@ -168,6 +197,14 @@ async fn install_impl(dry: bool, sign: bool, identity: Option<String>) -> Result
         info!("  - Binary: {}", exe_path.display());
         info!("  - Config: {}", config_path.display());
         info!("  - Service: cyrupd");
+        info!(
+            "  - Ports: gateway {}, MCP HTTP {}, metrics {}",
+            plan.gateway_port, plan.mcp_port, plan.metrics_port
+        );
+        info!("  - Plugins: {}", plan.plugin_dir.display());
+        if !plan.clients.is_empty() {
+            info!("  - Auto-configure: {}", plan.clients.join(", "));
+        }
         info!(
             "  - fluent-voice: Clone to {}/sweetmcp/fluent-voice",
             dirs::config_dir().unwrap_or_default().display()
@@ -203,24 +240,44 @@ async fn install_impl(dry: bool, sign: bool, identity: Option<String>) -> Result
         return Err(anyhow::anyhow!("Failed to build sweetmcp-pingora"));
     }
 
+    fs::create_dir_all(&plan.plugin_dir).with_context(|| {
+        format!(
+            "Failed to create plugin directory {}",
+            plan.plugin_dir.display()
+        )
+    })?;
+
     // Create the pingora service definition
     let pingora_binary = exe_path.parent().unwrap().join("sweetmcp_server");
     let pingora_service = crate::config::ServiceDefinition {
         name: "sweetmcp-pingora".to_string(),
         description: Some("SweetMCP Pingora Gateway Server".to_string()),
         command: pingora_binary.to_string_lossy().to_string(),
-        working_dir: Some(std::env::current_dir()?.to_string_lossy().to_string()),
+        // Not the directory the installer happened to run from
+        working_dir: pingora_binary
+            .parent()
+            .map(|dir| dir.to_string_lossy().to_string()),
+        umask: None,
+        nice: None,
+        ionice: None,
         env_vars: {
             let mut env = std::collections::HashMap::new();
             env.insert("RUST_LOG".to_string(), "info".to_string());
-            env.insert("SWEETMCP_TCP_BIND".to_string(), "0.0.0.0:8443".to_string());
+            env.insert(
+                "SWEETMCP_TCP_BIND".to_string(),
+                format!("0.0.0.0:{}", plan.gateway_port),
+            );
+            env.insert(
+                "SWEETMCP_MCP_BIND".to_string(),
+                format!("0.0.0.0:{}", plan.mcp_port),
+            );
             env.insert(
                 "SWEETMCP_UDS_PATH".to_string(),
                 "/run/sugora.sock".to_string(),
             );
             env.insert(
                 "SWEETMCP_METRICS_BIND".to_string(),
-                "127.0.0.1:9090".to_string(),
+                format!("127.0.0.1:{}", plan.metrics_port),
             );
             env.insert("SWEETMCP_DEV_MODE".to_string(), "true".to_string());
             env
@@ -232,10 +289,17 @@ async fn install_impl(dry: bool, sign: bool, identity: Option<String>) -> Result
         depends_on: Vec::new(),
         health_check: None,
         log_rotation: None,
+        log_file: None,
         watch_dirs: Vec::new(),
         ephemeral_dir: None,
         service_type: None,
         memfs: None,
+        resources: None,
+        sockets: Vec::new(),
+        upgrade: Default::default(),
+        crash: Default::default(),
+        deploy: None,
+        vars: std::collections::HashMap::new(),
     };
 
     // Create the autoconfig service definition
@@ -244,9 +308,16 @@ async fn install_impl(dry: bool, sign: bool, identity: Option<String>) -> Result
         description: Some("Automatic MCP client configuration service".to_string()),
         command: "internal:autoconfig".to_string(), // Special command handled internally
         working_dir: None,
+        umask: None,
+        nice: None,
+        ionice: None,
         env_vars: {
             let mut env = std::collections::HashMap::new();
             env.insert("RUST_LOG".to_string(), "info".to_string());
+            env.insert(
+                crate::service::AUTOCONFIG_CLIENTS_ENV.to_string(),
+                plan.clients.join(","),
+            );
             env
         },
         auto_restart: true,
@@ -255,19 +326,28 @@ async fn install_impl(dry: bool, sign: bool, identity: Option<String>) -> Result
         restart_delay_s: Some(10),
         depends_on: vec!["sweetmcp-pingora".to_string()], // Start after pingora
         health_check: Some(crate::config::HealthCheckConfig {
-            check_type: "tcp".to_string(),
-            target: "127.0.0.1:8443".to_string(),
+            probe: crate::config::HealthProbe::Tcp {
+                addr: format!("127.0.0.1:{}", plan.gateway_port),
+            },
             interval_secs: 300, // Check every 5 minutes
             timeout_secs: 30,
-            retries: 3,
-            expected_response: None,
+            initial_delay_secs: 0,
+            failure_threshold: 3,
+            success_threshold: 1,
             on_failure: vec![],
         }),
         log_rotation: None,
+        log_file: None,
         watch_dirs: Vec::new(),
         ephemeral_dir: None,
         service_type: Some("autoconfig".to_string()),
         memfs: None,
+        resources: None,
+        sockets: Vec::new(),
+        upgrade: Default::default(),
+        crash: Default::default(),
+        deploy: None,
+        vars: std::collections::HashMap::new(),
     };
 
     // Build the installer configuration
@@ -280,8 +360,14 @@ async fn install_impl(dry: bool, sign: bool, identity: Option<String>) -> Result
         .env("RUST_LOG", "info")
         .auto_restart(true)
         .network(true)
-        .service(pingora_service)
-        .service(autoconfig_service);
+        .service(pingora_service);
+
+    // Nothing to watch when no client was chosen
+    let installer = if plan.clients.is_empty() {
+        installer
+    } else {
+        installer.service(autoconfig_service)
+    };
 
     // Platform-specific user/group settings
     #[cfg(target_os = "linux")]
@@ -301,15 +387,33 @@ async fn install_impl(dry: bool, sign: bool, identity: Option<String>) -> Result
     #[cfg(target_os = "macos")]
     let installer = installer.user("root").group("wheel");
 
-    // Install the daemon with GUI authorization
-    match install_daemon_async(installer).await {
-        Ok(()) => {
-            info!("Daemon installed successfully");
+    // Install the daemon with GUI authorization, adopting a cyrupd service
+    // that was set up by hand or by another installer
+    match migrate_async(installer).await {
+        Ok(migration) => {
+            match migration {
+                Migration::Installed => info!("Daemon installed successfully"),
+                Migration::Reinstalled => info!("Daemon reinstalled successfully"),
+                Migration::Adopted(existing) => info!(
+                    "Adopted the existing cyrupd service at {}; run install again to replace its definition",
+                    existing.definition
+                ),
+            }
 
-            // Generate wildcard certificate and import to trust store
-            if let Err(e) = generate_and_import_wildcard_certificate().await {
-                warn!("Failed to generate wildcard certificate and import: {}", e);
-                // Don't fail installation if certificate import fails
+            match &plan.tls {
+                TlsChoice::SelfSigned => {
+                    // Generate wildcard certificate and import to trust store
+                    if let Err(e) = generate_and_import_wildcard_certificate().await {
+                        warn!("Failed to generate wildcard certificate and import: {}", e);
+                        // Don't fail installation if certificate import fails
+                    }
+                }
+                TlsChoice::Existing { cert, key } => {
+                    if let Err(e) = install_provided_certificate(cert, key).await {
+                        warn!("Failed to install the provided certificate: {}", e);
+                    }
+                }
+                TlsChoice::Disabled => info!("Skipping TLS certificate setup"),
             }
 
             // Add host entries for all SweetMCP domains pointing to 127.0.0.1
@@ -372,6 +476,57 @@ async fn install_impl(dry: bool, sign: bool, identity: Option<String>) -> Result
     }
 }
 
+/// `cyrupd.toml` for the wizard's answers, keeping any other settings of an
+/// existing config that still parses
+fn config_from_plan(config_path: &Path, plan: &InstallPlan) -> crate::config::ServiceConfig {
+    let mut cfg = fs::read_to_string(config_path)
+        .ok()
+        .and_then(|raw| toml::from_str::<crate::config::ServiceConfig>(&raw).ok())
+        .unwrap_or_default();
+    cfg.mcp_bind = Some(format!("0.0.0.0:{}", plan.mcp_port));
+    cfg.plugin_dir = Some(plan.plugin_dir.to_string_lossy().to_string());
+    cfg
+}
+
+/// Combine an operator-supplied certificate and key into the PEM the gateway
+/// would otherwise generate. It is not added to the trust store.
+async fn install_provided_certificate(cert: &Path, key: &Path) -> Result<()> {
+    let cert_pem = tokio::fs::read_to_string(cert)
+        .await
+        .with_context(|| format!("Failed to read {}", cert.display()))?;
+    let key_pem = tokio::fs::read_to_string(key)
+        .await
+        .with_context(|| format!("Failed to read {}", key.display()))?;
+    if !cert_pem.contains("BEGIN CERTIFICATE") || !key_pem.contains("PRIVATE KEY") {
+        return Err(anyhow::anyhow!("Certificate or key is not PEM encoded"));
+    }
+
+    let cert_dir = dirs::config_dir()
+        .ok_or_else(|| anyhow::anyhow!("Could not determine XDG config directory"))?
+        .join("sweetmcp");
+    tokio::fs::create_dir_all(&cert_dir)
+        .await
+        .context("Failed to create certificate directory")?;
+    let combined_path = cert_dir.join("wildcard.cyrup.pem");
+    tokio::fs::write(&combined_path, format!("{}\n{}", cert_pem, key_pem))
+        .await
+        .context("Failed to write certificate")?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        tokio::fs::set_permissions(&combined_path, std::fs::Permissions::from_mode(0o600))
+            .await
+            .context("Failed to set file permissions")?;
+    }
+
+    info!(
+        "Installed provided certificate at {}",
+        combined_path.display()
+    );
+    Ok(())
+}
+
 /// Internal implementation of uninstall
 async fn uninstall_impl(dry: bool) -> Result<()> {
     if dry {
@@ -193,21 +193,25 @@ fn convert_to_service_definition(
     // Create health check configuration based on service type
     let health_check = match service.name.as_str() {
         "sweetmcp-pingora" => Some(crate::config::HealthCheckConfig {
-            check_type: "tcp".to_string(),
-            target: "127.0.0.1:8443".to_string(),
+            probe: crate::config::HealthProbe::Tcp {
+                addr: "127.0.0.1:8443".to_string(),
+            },
             interval_secs: 60,
             timeout_secs: 10,
-            retries: 3,
-            expected_response: None,
+            initial_delay_secs: 0,
+            failure_threshold: 3,
+            success_threshold: 1,
             on_failure: vec![],
         }),
         "sweetmcp-autoconfig" => Some(crate::config::HealthCheckConfig {
-            check_type: "tcp".to_string(),
-            target: "127.0.0.1:8443".to_string(),
+            probe: crate::config::HealthProbe::Tcp {
+                addr: "127.0.0.1:8443".to_string(),
+            },
             interval_secs: 300, // Check every 5 minutes
             timeout_secs: 30,
-            retries: 3,
-            expected_response: None,
+            initial_delay_secs: 0,
+            failure_threshold: 3,
+            success_threshold: 1,
             on_failure: vec![],
         }),
         _ => None,
@@ -218,6 +222,9 @@ fn convert_to_service_definition(
         description: Some(service.description.clone()),
         command: service.command.clone(),
         working_dir: service.working_dir.clone(),
+        umask: None,
+        nice: None,
+        ionice: None,
         env_vars,
         auto_restart: service.auto_restart,
         user: service.user.clone(),
@@ -226,6 +233,7 @@ fn convert_to_service_definition(
         depends_on: service.dependencies.clone(),
         health_check,
         log_rotation: None,
+        log_file: None,
         watch_dirs: Vec::new(),
         ephemeral_dir: None,
         service_type: Some(match service.name.as_str() {
@@ -234,6 +242,12 @@ fn convert_to_service_definition(
             _ => "service".to_string(),
         }),
         memfs: None,
+        resources: None,
+        sockets: Vec::new(),
+        upgrade: Default::default(),
+        crash: Default::default(),
+        deploy: None,
+        vars: std::collections::HashMap::new(),
     })
 }
 
@@ -6,6 +6,11 @@ pub enum Cmd {
     Start,
     Stop,
     Restart,
+    /// Blue/green swap to a new binary; the old instance keeps serving
+    /// until the new one is healthy
+    Upgrade {
+        new_binary: String,
+    },
     Shutdown,      // worker should exit
     TickHealth,    // periodic health probe
     TickLogRotate, // periodic rotation
@@ -29,9 +34,48 @@ pub enum Evt {
         service: String,
         ts: DateTime<Utc>,
     },
+    Upgrade {
+        service: String,
+        ok: bool,
+        pid: Option<u32>,
+        detail: String,
+        ts: DateTime<Utc>,
+    },
     Fatal {
         service: String,
         msg: &'static str,
         ts: DateTime<Utc>,
     },
+    /// A crashed instance's core file, crash log or minidump was saved
+    CrashDump {
+        service: String,
+        pid: u32,
+        path: String,
+        ts: DateTime<Utc>,
+    },
+    /// A scanned plugin or service binary matched the known-bad list
+    SecurityAlert {
+        service: String,
+        path: String,
+        sha256: String,
+        detail: String,
+        ts: DateTime<Utc>,
+    },
+    /// A plugin's wasm sandbox blocked repeated filesystem or network access
+    /// by one client
+    SandboxViolation {
+        plugin: String,
+        client: String,
+        kind: String, // "filesystem"|"network"
+        count: u32,
+        window_secs: u64,
+        sample: String,
+        ts: DateTime<Utc>,
+    },
+    /// A scheduled reminder came due
+    Reminder {
+        id: String,
+        message: String,
+        ts: DateTime<Utc>,
+    },
 }
@@ -0,0 +1,307 @@
+//! Append‑only event journal.
+//!
+//! Every lifecycle event the manager sees is written as one JSON line so that
+//! `cyrupd events` can answer "what happened to this service and when" after
+//! the fact, instead of relying on whatever the log output retained.
+
+use crate::ipc::Evt;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+/// One journal record.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub ts: DateTime<Utc>,
+    pub service: String,
+    /// "running" | "stopped" | "healthy" | "unhealthy" | "crashed" | "log_rotated" | "upgraded" …
+    pub kind: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl JournalEntry {
+    /// Map a bus event onto a journal record.
+    pub fn from_evt(evt: &Evt) -> Self {
+        match evt {
+            Evt::State {
+                service,
+                kind,
+                ts,
+                pid,
+            } => Self {
+                ts: *ts,
+                service: service.clone(),
+                kind: kind.to_string(),
+                pid: *pid,
+                detail: None,
+            },
+            Evt::Health {
+                service,
+                healthy,
+                ts,
+            } => Self {
+                ts: *ts,
+                service: service.clone(),
+                kind: if *healthy { "healthy" } else { "unhealthy" }.to_string(),
+                pid: None,
+                detail: None,
+            },
+            Evt::LogRotate { service, ts } => Self {
+                ts: *ts,
+                service: service.clone(),
+                kind: "log_rotated".to_string(),
+                pid: None,
+                detail: None,
+            },
+            Evt::Upgrade {
+                service,
+                ok,
+                pid,
+                detail,
+                ts,
+            } => Self {
+                ts: *ts,
+                service: service.clone(),
+                kind: if *ok { "upgraded" } else { "upgrade_failed" }.to_string(),
+                pid: *pid,
+                detail: Some(detail.clone()),
+            },
+            Evt::Fatal { service, msg, ts } => Self {
+                ts: *ts,
+                service: service.clone(),
+                kind: "crashed".to_string(),
+                pid: None,
+                detail: Some(msg.to_string()),
+            },
+            Evt::CrashDump {
+                service,
+                pid,
+                path,
+                ts,
+            } => Self {
+                ts: *ts,
+                service: service.clone(),
+                kind: "crash_dump".to_string(),
+                pid: Some(*pid),
+                detail: Some(path.clone()),
+            },
+            Evt::SecurityAlert {
+                service,
+                path,
+                sha256,
+                detail,
+                ts,
+            } => Self {
+                ts: *ts,
+                service: service.clone(),
+                kind: "security_alert".to_string(),
+                pid: None,
+                detail: Some(format!("{} sha256={} ({})", path, sha256, detail)),
+            },
+            Evt::SandboxViolation {
+                plugin,
+                client,
+                kind,
+                count,
+                window_secs,
+                sample,
+                ts,
+            } => Self {
+                ts: *ts,
+                service: plugin.clone(),
+                kind: "sandbox_violation".to_string(),
+                pid: None,
+                detail: Some(format!(
+                    "client {}: {} blocked {} attempts in {}s ({})",
+                    client, count, kind, window_secs, sample
+                )),
+            },
+            Evt::Reminder { id, message, ts } => Self {
+                ts: *ts,
+                service: "reminders".to_string(),
+                kind: "reminder".to_string(),
+                pid: None,
+                detail: Some(format!("{}: {}", id, message)),
+            },
+        }
+    }
+}
+
+/// Writer half, owned by the manager.
+pub struct Journal {
+    path: PathBuf,
+    file: File,
+}
+
+impl Journal {
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("create journal dir {}", parent.display()))?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("open journal {}", path.display()))?;
+        Ok(Self { path, file })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append one record; each line is written with a single `write_all` so
+    /// concurrent readers never observe a torn entry.
+    pub fn append(&mut self, entry: &JournalEntry) -> Result<()> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        self.file
+            .write_all(&line)
+            .with_context(|| format!("append to journal {}", self.path.display()))
+    }
+}
+
+/// Filter for [`query`].
+#[derive(Debug, Default)]
+pub struct Query<'a> {
+    pub since: Option<DateTime<Utc>>,
+    pub service: Option<&'a str>,
+}
+
+/// Read matching entries in file order, skipping lines that fail to parse.
+pub fn query(path: &Path, filter: &Query<'_>) -> Result<Vec<JournalEntry>> {
+    let file = match File::open(path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("open journal {}", path.display())),
+    };
+
+    let mut out = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        let Ok(entry) = serde_json::from_str::<JournalEntry>(&line) else {
+            continue;
+        };
+        if filter.since.is_some_and(|since| entry.ts < since) {
+            continue;
+        }
+        if filter.service.is_some_and(|svc| entry.service != svc) {
+            continue;
+        }
+        out.push(entry);
+    }
+    Ok(out)
+}
+
+/// Parse `--since`: a relative age (`90s`, `15m`, `1h`, `2d`, `1w`) or an RFC 3339 timestamp.
+pub fn parse_since(spec: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    let spec = spec.trim();
+    if let Ok(ts) = DateTime::parse_from_rfc3339(spec) {
+        return Ok(ts.with_timezone(&Utc));
+    }
+    let split = spec
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| anyhow::anyhow!("missing unit in --since {} (use s, m, h, d or w)", spec))?;
+    let (amount, unit) = spec.split_at(split);
+    let amount: i64 = amount
+        .parse()
+        .with_context(|| format!("invalid --since {}", spec))?;
+    let age = match unit {
+        "s" => Duration::seconds(amount),
+        "m" => Duration::minutes(amount),
+        "h" => Duration::hours(amount),
+        "d" => Duration::days(amount),
+        "w" => Duration::weeks(amount),
+        _ => anyhow::bail!("unknown unit {:?} in --since (use s, m, h, d or w)", unit),
+    };
+    Ok(now - age)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn since_parsing() {
+        let now = Utc::now();
+        assert_eq!(parse_since("1h", now).unwrap(), now - Duration::hours(1));
+        assert_eq!(
+            parse_since("30m", now).unwrap(),
+            now - Duration::minutes(30)
+        );
+        assert_eq!(
+            parse_since("2026-01-01T00:00:00Z", now)
+                .unwrap()
+                .to_rfc3339(),
+            "2026-01-01T00:00:00+00:00"
+        );
+        assert!(parse_since("10", now).is_err());
+        assert!(parse_since("3y", now).is_err());
+    }
+
+    #[test]
+    fn append_and_query() {
+        let dir = std::env::temp_dir().join(format!("cyrupd-journal-{}", std::process::id()));
+        let path = dir.join("events.jsonl");
+        let now = Utc::now();
+
+        let mut journal = Journal::open(&path).unwrap();
+        for (service, kind, age) in [
+            ("foo", "running", 7200),
+            ("bar", "running", 60),
+            ("foo", "stopped", 30),
+        ] {
+            journal
+                .append(&JournalEntry::from_evt(&Evt::State {
+                    service: service.into(),
+                    kind,
+                    ts: now - Duration::seconds(age),
+                    pid: Some(1),
+                }))
+                .unwrap();
+        }
+
+        let recent_foo = query(
+            &path,
+            &Query {
+                since: Some(now - Duration::hours(1)),
+                service: Some("foo"),
+            },
+        )
+        .unwrap();
+        assert_eq!(recent_foo.len(), 1);
+        assert_eq!(recent_foo[0].kind, "stopped");
+        assert_eq!(query(&path, &Query::default()).unwrap().len(), 3);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn sandbox_violation_is_journaled_under_the_plugin() {
+        let entry = JournalEntry::from_evt(&Evt::SandboxViolation {
+            plugin: "eval-py".into(),
+            client: "127.0.0.1".into(),
+            kind: "network".into(),
+            count: 5,
+            window_secs: 300,
+            sample: "No module named '_socket'".into(),
+            ts: Utc::now(),
+        });
+        assert_eq!(entry.service, "eval-py");
+        assert_eq!(entry.kind, "sandbox_violation");
+        assert_eq!(
+            entry.detail.as_deref(),
+            Some(
+                "client 127.0.0.1: 5 blocked network attempts in 300s (No module named '_socket')"
+            )
+        );
+    }
+}
@@ -4,17 +4,28 @@
 //! with crossbeam channels for wait-free message passing.
 
 pub mod config;
+pub mod crash;
 pub mod daemon;
+pub mod desired_state;
 pub mod install;
 pub mod ipc;
+pub mod journal;
 pub mod lifecycle;
 pub mod manager;
+pub mod reminders;
+pub mod secrets;
 pub mod security;
 pub mod service;
 pub mod state_machine;
+pub mod template;
+pub mod tool_integration;
+pub mod vault;
 
 // Re-export main types for convenience
-pub use config::{HealthCheckConfig, LogRotationConfig, ServiceConfig, ServiceDefinition};
+pub use config::{
+    AdminApiConfig, HealthCheckConfig, HealthProbe, LogCompression, LogRotationConfig,
+    ResourceLimits, ServiceConfig, ServiceDefinition, UpgradeConfig,
+};
 pub use daemon::daemonise;
 pub use ipc::{Cmd, Evt};
 pub use manager::ServiceManager;
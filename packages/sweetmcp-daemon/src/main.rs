@@ -1,14 +1,28 @@
+mod bundle;
 mod cli;
 mod config;
+mod crash;
 mod daemon;
+mod deploy;
+mod desired_state;
+mod dev;
+mod doctor;
 mod install;
 mod installer;
 mod ipc;
+mod journal;
 mod lifecycle;
 mod manager;
+mod reminders;
+mod secrets;
+mod security;
 mod service;
 mod signing;
 mod state_machine;
+mod template;
+mod tool_integration;
+mod vault;
+mod wizard;
 
 use anyhow::Result;
 use clap::Parser;
@@ -41,11 +55,70 @@ async fn real_main() -> Result<()> {
             config,
             system,
         } => run_daemon(foreground, config, system).await,
+        cli::Cmd::Events {
+            since,
+            service,
+            json,
+            config,
+            system,
+        } => show_events(since, service, json, config, system),
+        cli::Cmd::Crashes {
+            service,
+            last,
+            stderr,
+            json,
+            config,
+            system,
+        } => show_crashes(service, last, stderr, json, config, system),
+        cli::Cmd::Deploy {
+            service,
+            binary,
+            timeout,
+            config,
+            system,
+        } => {
+            let cfg = load_config(&resolve_config_path(config, system)?)?;
+            deploy::run(
+                &cfg,
+                &service,
+                Path::new(&binary),
+                std::time::Duration::from_secs(timeout),
+            )
+            .await
+        }
+        cli::Cmd::Doctor {
+            json,
+            config,
+            system,
+        } => {
+            let cfg = load_config(&resolve_config_path(config, system)?)?;
+            doctor::run(&cfg, json).await
+        }
+        cli::Cmd::Secret {
+            action,
+            config,
+            system,
+        } => handle_secret_command(action, config, system),
+        cli::Cmd::Config {
+            action,
+            config,
+            system,
+        } => handle_config_command(action, config, system),
+        cli::Cmd::Dev {
+            plugins,
+            bin_dir,
+            keep,
+        } => dev::run(dev::DevOptions {
+            plugin_dir: plugins.map(PathBuf::from),
+            bin_dir: bin_dir.map(PathBuf::from),
+            keep,
+        }),
         cli::Cmd::Install {
             dry_run,
             sign,
             identity,
-        } => installer::install(dry_run, sign, identity).await,
+            interactive,
+        } => installer::install(dry_run, sign, identity, interactive).await,
         cli::Cmd::Uninstall { dry_run } => installer::uninstall_async(dry_run).await,
         cli::Cmd::Sign {
             binary,
@@ -53,7 +126,11 @@ async fn real_main() -> Result<()> {
             verify,
             show_config,
             self_sign,
-        } => handle_sign_command(binary, identity, verify, show_config, self_sign).await,
+            config,
+        } => match config {
+            Some(config) => handle_config_sign_command(Path::new(&config), identity, verify),
+            None => handle_sign_command(binary, identity, verify, show_config, self_sign).await,
+        },
     }
 }
 
@@ -68,8 +145,38 @@ async fn run_daemon(
         daemon::daemonise(Path::new("/var/run/cyrupd.pid"))?;
     }
 
-    // Determine config path based on CLI arguments
-    let cfg_path = if let Some(path) = config_path {
+    let cfg_path = resolve_config_path(config_path, use_system)?;
+    let cfg = load_config(&cfg_path)?;
+
+    info!("Using config from: {}", cfg_path.display());
+    signing::config_sig::enforce(
+        &cfg_path,
+        cfg.require_signed_config,
+        cfg.config_signing_key.as_deref(),
+    )?;
+
+    manager::install_signal_handlers();
+    let mut mgr = ServiceManager::new(&cfg)?;
+
+    // Start SSE server if enabled
+    mgr.start_sse_server(&cfg).await?;
+
+    // Start local admin API if configured
+    mgr.start_admin_server(&cfg).await?;
+
+    // Periodic known-bad hash scan of plugins and service binaries
+    mgr.start_security_scan(&cfg);
+
+    daemon::systemd_ready(); // tell systemd we are ready
+    info!("Cyrup daemon started (pid {})", std::process::id());
+    mgr.run()?;
+    info!("Cyrup daemon exiting");
+    Ok(())
+}
+
+/// Determine config path based on CLI arguments
+fn resolve_config_path(config_path: Option<String>, use_system: bool) -> Result<PathBuf> {
+    Ok(if let Some(path) = config_path {
         // User specified an explicit config path
         PathBuf::from(path)
     } else if use_system {
@@ -81,30 +188,307 @@ async fn run_daemon(
             .ok_or_else(|| anyhow::anyhow!("Could not determine config directory"))?
             .join("cyrupd");
         config_dir.join("cyrupd.toml")
-    };
+    })
+}
 
-    // Load or create default config
-    let cfg_str = fs::read_to_string(&cfg_path).or_else(|_| {
+/// Load or create default config
+fn load_config(cfg_path: &Path) -> Result<config::ServiceConfig> {
+    let cfg_str = fs::read_to_string(cfg_path).or_else(|_| {
         info!("Config not found at {}, using defaults", cfg_path.display());
         Ok::<String, anyhow::Error>(toml::to_string_pretty(&config::ServiceConfig::default())?)
     })?;
-    let cfg: config::ServiceConfig = toml::from_str(&cfg_str)?;
+    Ok(toml::from_str(&cfg_str)?)
+}
 
-    info!("Using config from: {}", cfg_path.display());
+fn handle_secret_command(
+    action: cli::SecretCmd,
+    config_path: Option<String>,
+    use_system: bool,
+) -> Result<()> {
+    let cfg = load_config(&resolve_config_path(config_path, use_system)?)?;
+    let keychain = secrets::KeychainProvider::new(
+        cfg.secrets
+            .as_ref()
+            .and_then(|s| s.keychain_service.clone())
+            .unwrap_or_else(|| secrets::DEFAULT_KEYCHAIN_SERVICE.to_string()),
+    );
 
-    manager::install_signal_handlers();
-    let mut mgr = ServiceManager::new(&cfg)?;
+    match action {
+        cli::SecretCmd::Set { name } => {
+            let mut value = String::new();
+            std::io::stdin().read_line(&mut value)?;
+            let value = value.trim_end_matches(['\r', '\n']);
+            if value.is_empty() {
+                anyhow::bail!("no secret value on stdin");
+            }
+            keychain.set(&name, value)?;
+            println!("Stored secret://keychain/{}", name);
+        }
+        cli::SecretCmd::Delete { name } => {
+            keychain.delete(&name)?;
+            println!("Deleted secret://keychain/{}", name);
+        }
+        cli::SecretCmd::Check => {
+            let resolver = secrets::Secrets::new(cfg.secrets.as_ref());
+            let mut failed = 0;
+            for def in &cfg.services {
+                let mut vars: Vec<_> = def.env_vars.iter().collect();
+                vars.sort();
+                for (key, value) in vars {
+                    if !value.starts_with(secrets::SCHEME) {
+                        continue;
+                    }
+                    match resolver.resolve(value) {
+                        Ok(_) => println!("OK    {} {} ({})", def.name, key, value),
+                        Err(e) => {
+                            failed += 1;
+                            println!("FAIL  {} {} ({}): {:#}", def.name, key, value, e);
+                        }
+                    }
+                }
+            }
+            if failed > 0 {
+                anyhow::bail!("{} secret reference(s) could not be resolved", failed);
+            }
+        }
+    }
+    Ok(())
+}
 
-    // Start SSE server if enabled
-    mgr.start_sse_server(&cfg).await?;
+fn handle_config_command(
+    action: cli::ConfigCmd,
+    config_path: Option<String>,
+    use_system: bool,
+) -> Result<()> {
+    let cfg_path = resolve_config_path(config_path, use_system)?;
 
-    daemon::systemd_ready(); // tell systemd we are ready
-    info!("Cyrup daemon started (pid {})", std::process::id());
-    mgr.run()?;
-    info!("Cyrup daemon exiting");
+    match action {
+        cli::ConfigCmd::Export {
+            output,
+            with_plugins,
+            profile,
+        } => {
+            let manifest = bundle::export(
+                &cfg_path,
+                &bundle::ExportOptions {
+                    output: PathBuf::from(&output),
+                    with_plugins,
+                    profiles: profile.into_iter().map(PathBuf::from).collect(),
+                },
+            )?;
+            println!(
+                "Exported {} to {}: {} service file(s), {} plugin(s), {} plugin file(s), {} profile(s)",
+                cfg_path.display(),
+                output,
+                manifest.services.len(),
+                manifest.plugins.len(),
+                manifest.plugin_files.len(),
+                manifest.profiles.len()
+            );
+            print_external_secrets(&manifest.secrets);
+        }
+        cli::ConfigCmd::Import {
+            archive,
+            force,
+            dry_run,
+            apply_profiles,
+        } => {
+            let report = bundle::import(
+                &cfg_path,
+                &bundle::ImportOptions {
+                    archive: PathBuf::from(archive),
+                    force,
+                    dry_run,
+                    apply_profiles,
+                },
+            )?;
+            let verb = if dry_run { "would write" } else { "wrote" };
+            for (path, backup) in &report.written {
+                match backup {
+                    Some(backup) => {
+                        println!(
+                            "{}  {} (old copy at {})",
+                            verb,
+                            path.display(),
+                            backup.display()
+                        )
+                    }
+                    None => println!("{}  {}", verb, path.display()),
+                }
+            }
+            for path in &report.unchanged {
+                println!("unchanged  {}", path.display());
+            }
+            for problem in &report.plugin_problems {
+                println!("plugin  {}", problem);
+            }
+            for applied in &report.applied {
+                match &applied.error {
+                    Some(e) => println!(
+                        "client  {} {}: {}",
+                        applied.client_id,
+                        applied.path.display(),
+                        e
+                    ),
+                    None if applied.changed => {
+                        println!("client  {} {}", applied.client_id, applied.path.display())
+                    }
+                    None => {}
+                }
+            }
+            print_external_secrets(&report.manifest.secrets);
+            if report.needs_signature {
+                println!(
+                    "require_signed_config is set: sign the imported config with `cyrupd sign --config {}`",
+                    cfg_path.display()
+                );
+            }
+        }
+    }
     Ok(())
 }
 
+fn print_external_secrets(secrets: &[bundle::ExternalSecret]) {
+    if secrets.is_empty() {
+        return;
+    }
+    println!("Secrets not in the archive:");
+    for secret in secrets {
+        println!("  {}: {}", secret.location, secret.action);
+    }
+}
+
+fn show_events(
+    since: Option<String>,
+    service: Option<String>,
+    json: bool,
+    config_path: Option<String>,
+    use_system: bool,
+) -> Result<()> {
+    let cfg = load_config(&resolve_config_path(config_path, use_system)?)?;
+    let path = cfg.journal_path().ok_or_else(|| {
+        anyhow::anyhow!("No event journal configured (set journal_file or log_dir)")
+    })?;
+
+    let since = since
+        .map(|s| journal::parse_since(&s, chrono::Utc::now()))
+        .transpose()?;
+    let entries = journal::query(
+        &path,
+        &journal::Query {
+            since,
+            service: service.as_deref(),
+        },
+    )?;
+
+    for entry in &entries {
+        if json {
+            println!("{}", serde_json::to_string(entry)?);
+            continue;
+        }
+        let mut line = format!(
+            "{}  {:<24} {:<12}",
+            entry.ts.format("%Y-%m-%d %H:%M:%S"),
+            entry.service,
+            entry.kind
+        );
+        if let Some(pid) = entry.pid {
+            line.push_str(&format!(" pid={}", pid));
+        }
+        if let Some(detail) = &entry.detail {
+            line.push_str(&format!(" {}", detail));
+        }
+        println!("{}", line.trim_end());
+    }
+    if entries.is_empty() && !json {
+        eprintln!("No events found in {}", path.display());
+    }
+    Ok(())
+}
+
+fn show_crashes(
+    service: Option<String>,
+    last: Option<usize>,
+    stderr: bool,
+    json: bool,
+    config_path: Option<String>,
+    use_system: bool,
+) -> Result<()> {
+    let cfg = load_config(&resolve_config_path(config_path, use_system)?)?;
+    let dir = cfg
+        .crash_path()
+        .ok_or_else(|| anyhow::anyhow!("No crash directory configured (set crash_dir)"))?;
+
+    let mut reports = crash::list(&dir, service.as_deref())?;
+    if let Some(last) = last {
+        reports.drain(..reports.len().saturating_sub(last));
+    }
+
+    for (path, report) in &reports {
+        if json {
+            println!("{}", serde_json::to_string(report)?);
+            continue;
+        }
+        let mut line = format!(
+            "{}  {:<24} pid={:<8} {}",
+            report.ts.format("%Y-%m-%d %H:%M:%S"),
+            report.service,
+            report.pid,
+            report.summary()
+        );
+        if let Some(uptime) = report.uptime_secs {
+            line.push_str(&format!(" after {}s", uptime));
+        }
+        println!("{}", line);
+        if let Some(core) = &report.core_dump {
+            println!("    core:   {}", core);
+        }
+        if let Some(dump) = &report.dump_file {
+            println!("    dump:   {}", dump.display());
+        }
+        println!("    report: {}", path.display());
+        if stderr && !report.stderr_tail.is_empty() {
+            for tail_line in report.stderr_tail.lines() {
+                println!("    | {}", tail_line);
+            }
+        }
+    }
+    if reports.is_empty() && !json {
+        eprintln!("No crash reports found in {}", dir.display());
+    }
+    Ok(())
+}
+
+fn handle_config_sign_command(config: &Path, key_id: Option<String>, verify: bool) -> Result<()> {
+    use signing::config_sig::{self, ConfigSignature};
+
+    if !verify {
+        let sig = config_sig::sign(config, key_id.as_deref())?;
+        println!("✓ Wrote {}", sig.display());
+        return Ok(());
+    }
+
+    let trusted_key = std::env::var(config_sig::KEY_ENV).ok().or_else(|| {
+        load_config(config)
+            .ok()
+            .and_then(|cfg| cfg.config_signing_key)
+    });
+    match config_sig::verify(config, trusted_key.as_deref().map(Path::new))? {
+        ConfigSignature::Verified(signer) => {
+            println!("✓ {} is signed by {}", config.display(), signer);
+            Ok(())
+        }
+        ConfigSignature::Unsigned => {
+            eprintln!("✗ {} has no signature", config.display());
+            std::process::exit(1);
+        }
+        ConfigSignature::Invalid(reason) => {
+            eprintln!("✗ {} signature is invalid: {}", config.display(), reason);
+            std::process::exit(1);
+        }
+    }
+}
+
 async fn handle_sign_command(
     binary: Option<String>,
     identity: Option<String>,
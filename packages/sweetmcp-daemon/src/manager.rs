@@ -1,18 +1,25 @@
 use crate::config::ServiceConfig;
+use crate::desired_state::{Desired, DesiredState};
 use crate::ipc::{Cmd, Evt};
+use crate::journal::{Journal, JournalEntry};
 use crate::lifecycle::Lifecycle;
+use crate::secrets::Secrets;
+use crate::service::admin::{ServiceStatus, StatusTable};
 use crate::state_machine::{Action, Event};
-use anyhow::Result;
+use anyhow::{Context, Result};
 use crossbeam_channel::{bounded, select, tick, Receiver, Sender};
-use log::{error, info};
+use log::{error, info, warn};
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use tokio::sync::oneshot;
 
 /// Global event bus size – small fixed size → zero heap growth.
 const BUS_BOUND: usize = 128;
+/// Events buffered per SSE subscriber before a slow one starts missing some.
+const EVENTS_BOUND: usize = 256;
 
 /// Restart state for a service
 #[derive(Debug)]
@@ -26,10 +33,19 @@ pub struct ServiceManager {
     bus_tx: Sender<Evt>,
     bus_rx: Receiver<Evt>,
     workers: HashMap<String, Sender<Cmd>>,
+    log_files: HashMap<String, Option<String>>,
+    definitions: Vec<crate::config::ServiceDefinition>,
+    status: StatusTable,
+    journal: Option<Journal>,
+    /// Journal-shaped copies of notable events for SSE subscribers
+    events_tx: tokio::sync::broadcast::Sender<JournalEntry>,
     pending_restarts: HashMap<String, RestartState>,
     lifecycle: Lifecycle,
+    desired: Arc<DesiredState>,
     sse_shutdown_tx: Option<oneshot::Sender<()>>,
     sse_task: Option<tokio::task::JoinHandle<()>>,
+    admin_shutdown_tx: Option<oneshot::Sender<()>>,
+    admin_task: Option<tokio::task::JoinHandle<()>>,
 }
 
 impl ServiceManager {
@@ -37,11 +53,23 @@ impl ServiceManager {
     pub fn new(cfg: &ServiceConfig) -> Result<Self> {
         let (bus_tx, bus_rx) = bounded::<Evt>(BUS_BOUND);
         let mut workers = HashMap::new();
+        let mut log_files = HashMap::new();
+        let mut definitions = Vec::new();
+        let secrets = Arc::new(Secrets::new(cfg.secrets.as_ref()));
 
         // Load services from config file
         for def in cfg.services.clone() {
-            let tx = crate::service::spawn(def.clone(), bus_tx.clone());
+            let def = match prepare_definition(def, cfg) {
+                Ok(def) => def,
+                Err(e) => {
+                    error!("Skipping service: {:#}", e);
+                    continue;
+                }
+            };
+            let tx = crate::service::spawn(def.clone(), bus_tx.clone(), secrets.clone());
+            log_files.insert(def.name.clone(), def.log_file.clone());
             workers.insert(def.name.clone(), tx);
+            definitions.push(def);
         }
 
         // Load services from services directory
@@ -54,13 +82,26 @@ impl ServiceManager {
                             Ok(content) => {
                                 match toml::from_str::<crate::config::ServiceDefinition>(&content) {
                                     Ok(def) => {
+                                        let def = match prepare_definition(def, cfg) {
+                                            Ok(def) => def,
+                                            Err(e) => {
+                                                error!("Skipping {}: {:#}", path.display(), e);
+                                                continue;
+                                            }
+                                        };
                                         info!(
                                             "Loading service '{}' from {}",
                                             def.name,
                                             path.display()
                                         );
-                                        let tx = crate::service::spawn(def.clone(), bus_tx.clone());
+                                        let tx = crate::service::spawn(
+                                            def.clone(),
+                                            bus_tx.clone(),
+                                            secrets.clone(),
+                                        );
+                                        log_files.insert(def.name.clone(), def.log_file.clone());
                                         workers.insert(def.name.clone(), tx);
+                                        definitions.push(def);
                                     }
                                     Err(e) => error!(
                                         "Failed to parse service file {}: {}",
@@ -78,14 +119,49 @@ impl ServiceManager {
             }
         }
 
+        let journal = cfg
+            .journal_path()
+            .and_then(|path| match Journal::open(&path) {
+                Ok(journal) => {
+                    info!("Journaling events to {}", journal.path().display());
+                    Some(journal)
+                }
+                Err(e) => {
+                    warn!("Event journal disabled: {:#}", e);
+                    None
+                }
+            });
+
+        let desired = match cfg.state_path().map(DesiredState::load) {
+            Some(Ok(desired)) => {
+                if let Some(path) = desired.path() {
+                    info!("Desired service state kept in {}", path.display());
+                }
+                desired
+            }
+            Some(Err(e)) => {
+                warn!("Desired state not persisted: {:#}", e);
+                DesiredState::in_memory()
+            }
+            None => DesiredState::in_memory(),
+        };
+
         Ok(Self {
             bus_tx,
             bus_rx,
             workers,
+            log_files,
+            definitions,
+            status: StatusTable::default(),
+            journal,
+            events_tx: tokio::sync::broadcast::channel(EVENTS_BOUND).0,
             pending_restarts: HashMap::new(),
             lifecycle: Lifecycle::default(),
+            desired: Arc::new(desired),
             sse_shutdown_tx: None,
             sse_task: None,
+            admin_shutdown_tx: None,
+            admin_task: None,
         })
     }
 
@@ -98,12 +174,14 @@ impl ServiceManager {
                 info!("Starting SSE server on port {}", sse_config.port);
 
                 let (shutdown_tx, shutdown_rx) = oneshot::channel();
-                let sse_cfg: crate::service::sse::SseConfig = sse_config.clone().into();
+                let mut sse_cfg: crate::service::sse::SseConfig = sse_config.clone().into();
+                sse_cfg.token = sse_config.resolve_token()?;
                 let addr: SocketAddr = ([127, 0, 0, 1], sse_config.port).into();
+                let events = self.events_tx.clone();
 
                 let task = tokio::spawn(async move {
                     if let Err(e) =
-                        crate::service::sse::start_sse_server(sse_cfg, shutdown_rx).await
+                        crate::service::sse::start_sse_server(sse_cfg, events, shutdown_rx).await
                     {
                         error!("SSE server error: {}", e);
                     }
@@ -118,6 +196,98 @@ impl ServiceManager {
         Ok(())
     }
 
+    /// Start the token‑guarded local admin API if configured
+    pub async fn start_admin_server(&mut self, cfg: &ServiceConfig) -> Result<()> {
+        use crate::service::admin::{AdminState, Listen};
+        use crate::tool_integration::ToolConfiguratorHost;
+
+        let Some(admin_config) = cfg.admin.as_ref().filter(|a| a.enabled) else {
+            return Ok(());
+        };
+        let listen = Listen::parse(&admin_config.listen)?;
+        let token = admin_config.resolve_token()?;
+
+        let mut tools = ToolConfiguratorHost::new();
+        if let Some(dir) = &cfg.tool_configurators_dir {
+            tools = tools.with_dir(dir);
+        }
+        if let Err(e) = tools.discover_plugins().await {
+            warn!("Tool configurator discovery failed: {:#}", e);
+        }
+
+        let vault = crate::vault::Vault::from_config(cfg.secrets.as_ref())
+            .context("set up the secrets vault")?;
+        let reminders = Arc::new(
+            crate::reminders::Reminders::from_config(cfg.reminders.as_ref())
+                .context("set up reminders")?,
+        );
+        let notify_url = cfg
+            .reminders
+            .as_ref()
+            .and_then(|r| r.mcp_server_url.clone())
+            .or_else(|| cfg.sse.as_ref().map(|s| s.mcp_server_url.clone()));
+        let notifier = match notify_url {
+            Some(url) => {
+                let tool = cfg
+                    .reminders
+                    .as_ref()
+                    .and_then(|r| r.notify_tool.as_deref())
+                    .unwrap_or("send_message");
+                Some(crate::reminders::Notifier::new(&url, tool)?)
+            }
+            None => None,
+        };
+        crate::reminders::spawn(reminders.clone(), self.bus_tx.clone(), notifier);
+        let sandbox_token = match &cfg.sandbox_reports {
+            Some(reports) => reports.resolve_token()?,
+            None => None,
+        };
+
+        let state = AdminState {
+            workers: Arc::new(self.workers.clone()),
+            log_files: Arc::new(self.log_files.clone()),
+            status: self.status.clone(),
+            desired: self.desired.clone(),
+            tools: Arc::new(tools),
+            vault: Arc::new(vault),
+            reminders,
+            events: self.bus_tx.clone(),
+            sandbox_token: sandbox_token.map(Into::into),
+            token: token.into(),
+        };
+
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+        let task = tokio::spawn(async move {
+            if let Err(e) =
+                crate::service::admin::start_admin_server(listen, state, shutdown_rx).await
+            {
+                error!("Admin API error: {:#}", e);
+            }
+        });
+
+        self.admin_shutdown_tx = Some(shutdown_tx);
+        self.admin_task = Some(task);
+        info!("Admin API started on {}", admin_config.listen);
+        Ok(())
+    }
+
+    /// Start the periodic plugin / binary hash scan if configured
+    pub fn start_security_scan(&self, cfg: &ServiceConfig) {
+        let Some(scan_cfg) = cfg.security_scan.clone().filter(|s| s.enabled) else {
+            return;
+        };
+        info!(
+            "Scanning plugins and service binaries every {}s",
+            scan_cfg.interval_secs
+        );
+        crate::security::artifacts::spawn_scheduler(
+            cfg.plugin_dir.as_ref().map(Into::into),
+            self.definitions.clone(),
+            scan_cfg,
+            self.bus_tx.clone(),
+        );
+    }
+
     /// Central event‑loop.  Runs until SIGINT / SIGTERM.
     pub fn run(mut self) -> Result<()> {
         // Process lifecycle start event
@@ -132,8 +302,17 @@ impl ServiceManager {
                     pid: Some(std::process::id()),
                 })?;
 
-                // Initial start‑up pass.
+                // Initial start‑up pass, honouring services stopped by an operator
                 for (name, tx) in self.workers.iter() {
+                    if let Some(entry) = self.desired.entry(name) {
+                        if entry.state == Desired::Stopped {
+                            info!(
+                                "Leaving {} stopped (stopped by operator at {})",
+                                name, entry.since
+                            );
+                            continue;
+                        }
+                    }
                     tx.send(Cmd::Start)?;
                     info!("Started service: {}", name);
                 }
@@ -172,6 +351,10 @@ impl ServiceManager {
                             info!("Shutting down SSE server");
                             shutdown_tx.send(()).ok();
                         }
+                        if let Some(shutdown_tx) = self.admin_shutdown_tx.take() {
+                            info!("Shutting down admin API");
+                            shutdown_tx.send(()).ok();
+                        }
 
                         for tx in self.workers.values() { tx.send(Cmd::Shutdown).ok(); }
                         break;
@@ -218,6 +401,7 @@ impl ServiceManager {
     }
 
     fn handle_event(&mut self, evt: Evt) -> Result<()> {
+        self.journal_event(&evt);
         match &evt {
             Evt::State {
                 service,
@@ -226,6 +410,18 @@ impl ServiceManager {
                 pid,
             } => {
                 info!("{} → {} (pid: {:?}, ts: {})", service, kind, pid, ts);
+                if service != "manager" {
+                    let mut entry = self.status.entry(service.clone()).or_insert(ServiceStatus {
+                        state: *kind,
+                        pid: *pid,
+                        since: *ts,
+                        healthy: None,
+                        last_health_check: None,
+                    });
+                    entry.state = *kind;
+                    entry.pid = *pid;
+                    entry.since = *ts;
+                }
                 // Check if any service has died unexpectedly
                 if *kind == "stopped" && service != &"manager" {
                     // Schedule restart
@@ -237,6 +433,10 @@ impl ServiceManager {
                 healthy,
                 ts,
             } => {
+                if let Some(mut entry) = self.status.get_mut(service) {
+                    entry.healthy = Some(*healthy);
+                    entry.last_health_check = Some(*ts);
+                }
                 if *healthy {
                     info!("{} health check OK at {}", service, ts);
                 } else {
@@ -248,6 +448,25 @@ impl ServiceManager {
             Evt::LogRotate { service, ts } => {
                 info!("{} rotated logs at {}", service, ts);
             }
+            Evt::Upgrade {
+                service,
+                ok,
+                pid,
+                detail,
+                ts,
+            } => {
+                if *ok {
+                    info!("{} upgraded (pid: {:?}): {}", service, pid, detail);
+                    if let Some(mut entry) = self.status.get_mut(service) {
+                        entry.state = "running";
+                        entry.pid = *pid;
+                        entry.since = *ts;
+                    }
+                } else {
+                    // The old instance is still serving; nothing to restart
+                    error!("{} upgrade failed: {}", service, detail);
+                }
+            }
             Evt::Fatal { service, msg, ts } => {
                 error!("{} FATAL at {}: {}", service, ts, msg);
                 // Notify about fatal error
@@ -262,12 +481,72 @@ impl ServiceManager {
                 // Schedule restart with longer delay
                 self.schedule_restart(service, 1000);
             }
+            Evt::CrashDump {
+                service, pid, path, ..
+            } => {
+                info!("{} (pid {}) dump saved to {}", service, pid, path);
+            }
+            Evt::SecurityAlert {
+                service,
+                path,
+                sha256,
+                detail,
+                ..
+            } => {
+                // Alert only; stopping a service is left to the operator
+                error!(
+                    "SECURITY: {} artifact {} matches known-bad hash {}: {}",
+                    service, path, sha256, detail
+                );
+            }
+            // Logged and delivered by the reminder task; journaled below
+            Evt::SandboxViolation {
+                plugin,
+                client,
+                kind,
+                count,
+                window_secs,
+                sample,
+                ..
+            } => {
+                error!(
+                    "SECURITY: plugin {} sandbox blocked {} {} attempts by client {} in {}s: {}",
+                    plugin, count, kind, client, window_secs, sample
+                );
+            }
+            Evt::Reminder { .. } => {}
         }
         Ok(())
     }
 
+    /// Record lifecycle events; health is only journaled when it flips.
+    /// Record `evt` in the journal and hand it to SSE subscribers.
+    fn journal_event(&mut self, evt: &Evt) {
+        // Health probes only matter when the verdict changes
+        if let Evt::Health {
+            service, healthy, ..
+        } = evt
+        {
+            let previous = self.status.get(service).and_then(|s| s.healthy);
+            if previous == Some(*healthy) {
+                return;
+            }
+        }
+        let entry = JournalEntry::from_evt(evt);
+        if let Some(journal) = self.journal.as_mut() {
+            if let Err(e) = journal.append(&entry) {
+                warn!("{:#}", e);
+            }
+        }
+        // No subscribers is not an error
+        self.events_tx.send(entry).ok();
+    }
+
     /// Schedule a service for restart after a delay
     fn schedule_restart(&mut self, service: &str, delay_ms: u64) {
+        if !self.desired.wants_running(service) {
+            return;
+        }
         if let Some(tx) = self.workers.get(service) {
             // Send stop command immediately
             tx.send(Cmd::Stop).ok();
@@ -310,6 +589,10 @@ impl ServiceManager {
         // Restart ready services
         for service in to_restart {
             if let Some(state) = self.pending_restarts.remove(&service) {
+                if !self.desired.wants_running(&service) {
+                    info!("Dropping restart of {}: stopped by operator", service);
+                    continue;
+                }
                 if let Some(tx) = self.workers.get(&service) {
                     info!("Restarting {} (attempt #{})", service, state.attempts);
                     tx.send(Cmd::Start).ok();
@@ -327,6 +610,44 @@ impl ServiceManager {
     }
 }
 
+/// Expand `${NAME}` references and fill in daemon-level defaults.
+fn prepare_definition(
+    def: crate::config::ServiceDefinition,
+    cfg: &ServiceConfig,
+) -> Result<crate::config::ServiceDefinition> {
+    let name = def.name.clone();
+    let def = crate::template::expand_definition(def, cfg)
+        .with_context(|| format!("service {}", name))?;
+    Ok(with_crash_dir(with_default_log_file(def, cfg), cfg))
+}
+
+/// Point a service's output at `<log_dir>/<name>.log` unless it names its own file.
+fn with_default_log_file(
+    mut def: crate::config::ServiceDefinition,
+    cfg: &ServiceConfig,
+) -> crate::config::ServiceDefinition {
+    if def.log_file.is_none() {
+        if let Some(dir) = &cfg.log_dir {
+            def.log_file = Some(
+                std::path::Path::new(dir)
+                    .join(format!("{}.log", def.name))
+                    .to_string_lossy()
+                    .into_owned(),
+            );
+        }
+    }
+    def
+}
+
+/// Have a service's crash reports land where `cyrupd crashes` looks.
+fn with_crash_dir(
+    mut def: crate::config::ServiceDefinition,
+    cfg: &ServiceConfig,
+) -> crate::config::ServiceDefinition {
+    def.crash.dir = cfg.crash_path();
+    def
+}
+
 // Cheap, polling‑based Unix signal handling (lock‑free).
 static RECEIVED_SIGNAL: Lazy<std::sync::atomic::AtomicUsize> =
     Lazy::new(|| std::sync::atomic::AtomicUsize::new(0));
@@ -0,0 +1,724 @@
+//! Reminder scheduler behind the admin API's `/reminders` routes.
+//!
+//! A reminder fires once at a given time or repeatedly on a cron schedule,
+//! evaluated in the daemon's local time zone. Reminders are kept in a JSON
+//! file so they survive restarts; one that came due while the daemon was down
+//! fires as soon as it is back, and a repeating one then resumes its schedule
+//! from the current time rather than replaying every missed run.
+//!
+//! A due reminder goes on the event bus as `Evt::Reminder`, which journals it
+//! and streams it to SSE subscribers. Reminders that name a notify
+//! destination are also sent through the notify plugin's tool on the MCP
+//! server.
+
+use crate::config::RemindersConfig;
+use crate::ipc::Evt;
+use crate::service::sse::McpBridge;
+use anyhow::{Context, Result};
+use chrono::{
+    DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Timelike, Utc,
+};
+use crossbeam_channel::Sender;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Most reminders kept at once
+const MAX_REMINDERS: usize = 1000;
+
+/// Longest reminder message, in characters
+const MAX_MESSAGE_LEN: usize = 2000;
+
+/// Where a due reminder is sent besides the event stream
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotifyTarget {
+    /// `slack` or `discord`
+    pub platform: String,
+    /// Channel id; the platform's webhook default when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reminder {
+    pub id: String,
+    pub message: String,
+    /// Cron expression of a repeating reminder; one-off reminders have none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cron: Option<String>,
+    /// When it fires next
+    pub next: DateTime<Utc>,
+    pub created: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notify: Option<NotifyTarget>,
+    /// Times it has fired
+    #[serde(default)]
+    pub fired: u64,
+}
+
+/// Body of `POST /reminders`: a message and either `when` or `cron`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct NewReminder {
+    pub message: String,
+    /// RFC 3339, `YYYY-MM-DD HH:MM` local time, or a delay such as `in 20m` or `1h30m`
+    #[serde(default)]
+    pub when: Option<String>,
+    /// Five-field cron expression (`minute hour day month weekday`) or `@daily` etc.
+    #[serde(default)]
+    pub cron: Option<String>,
+    #[serde(default)]
+    pub notify: Option<NotifyTarget>,
+}
+
+/// Why a reminder request failed, so the admin API can pick a status code
+#[derive(Debug)]
+pub enum ReminderError {
+    NotFound(String),
+    Invalid(String),
+    Internal(anyhow::Error),
+}
+
+impl std::fmt::Display for ReminderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReminderError::NotFound(msg) | ReminderError::Invalid(msg) => f.write_str(msg),
+            ReminderError::Internal(e) => write!(f, "{:#}", e),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RemindersFile {
+    #[serde(default)]
+    reminders: Vec<Reminder>,
+}
+
+pub struct Reminders {
+    path: Option<PathBuf>,
+    /// Bearer token accepted on `/reminders` besides the admin token
+    token: Option<String>,
+    reminders: Mutex<BTreeMap<String, Reminder>>,
+}
+
+impl Reminders {
+    /// Build from `[reminders]`, loading any saved reminders
+    pub fn from_config(cfg: Option<&RemindersConfig>) -> Result<Self> {
+        let path = match cfg {
+            Some(cfg) => cfg.store_path(),
+            None => RemindersConfig::default().store_path(),
+        };
+        let mut reminders = Self::new(path)?;
+        if let Some(cfg) = cfg {
+            reminders.token = cfg.resolve_token()?;
+        }
+        Ok(reminders)
+    }
+
+    /// Reminders saved at `path` (in memory only when `None`)
+    pub fn new(path: Option<PathBuf>) -> Result<Self> {
+        let saved = match &path {
+            Some(path) => match std::fs::read(path) {
+                Ok(bytes) => {
+                    serde_json::from_slice::<RemindersFile>(&bytes)
+                        .with_context(|| format!("corrupt reminders file {}", path.display()))?
+                        .reminders
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+                Err(e) => {
+                    return Err(e)
+                        .with_context(|| format!("read reminders file {}", path.display()))
+                }
+            },
+            None => Vec::new(),
+        };
+        Ok(Self {
+            path,
+            token: None,
+            reminders: Mutex::new(saved.into_iter().map(|r| (r.id.clone(), r)).collect()),
+        })
+    }
+
+    /// Whether `token` is the reminders client token
+    pub fn accepts(&self, token: &str) -> bool {
+        self.token
+            .as_deref()
+            .is_some_and(|expected| crate::service::admin::token_matches(token, expected))
+    }
+
+    /// Add a reminder, returning it with its id and first firing time
+    pub fn schedule(
+        &self,
+        new: NewReminder,
+        now: DateTime<Utc>,
+    ) -> Result<Reminder, ReminderError> {
+        let message = new.message.trim().to_string();
+        if message.is_empty() {
+            return Err(ReminderError::Invalid("message is empty".to_string()));
+        }
+        if message.chars().count() > MAX_MESSAGE_LEN {
+            return Err(ReminderError::Invalid(format!(
+                "message is over {} characters",
+                MAX_MESSAGE_LEN
+            )));
+        }
+        if let Some(target) = &new.notify {
+            if !matches!(target.platform.as_str(), "slack" | "discord") {
+                return Err(ReminderError::Invalid(format!(
+                    "notify platform must be slack or discord, not {}",
+                    target.platform
+                )));
+            }
+        }
+
+        let when = new.when.as_deref().map(str::trim).filter(|w| !w.is_empty());
+        let cron = new.cron.as_deref().map(str::trim).filter(|c| !c.is_empty());
+        let next = match (when, cron) {
+            (Some(when), None) => parse_when(when, now).map_err(ReminderError::Invalid)?,
+            (None, Some(cron)) => next_cron(cron, now).map_err(ReminderError::Invalid)?,
+            _ => {
+                return Err(ReminderError::Invalid(
+                    "give exactly one of when or cron".to_string(),
+                ))
+            }
+        };
+
+        let reminder = Reminder {
+            id: uuid::Uuid::new_v4().simple().to_string()[..12].to_string(),
+            message,
+            cron: cron.map(str::to_string),
+            next,
+            created: now,
+            notify: new.notify,
+            fired: 0,
+        };
+        let mut reminders = self.lock();
+        if reminders.len() >= MAX_REMINDERS {
+            return Err(ReminderError::Invalid(format!(
+                "already {} reminders; cancel some first",
+                MAX_REMINDERS
+            )));
+        }
+        reminders.insert(reminder.id.clone(), reminder.clone());
+        self.persist(&reminders).map_err(ReminderError::Internal)?;
+        Ok(reminder)
+    }
+
+    /// Every reminder, soonest first
+    pub fn list(&self) -> Vec<Reminder> {
+        let mut reminders: Vec<Reminder> = self.lock().values().cloned().collect();
+        reminders.sort_by_key(|r| r.next);
+        reminders
+    }
+
+    /// Remove a reminder, returning it
+    pub fn cancel(&self, id: &str) -> Result<Reminder, ReminderError> {
+        let mut reminders = self.lock();
+        let removed = reminders
+            .remove(id)
+            .ok_or_else(|| ReminderError::NotFound(format!("no reminder with id {}", id)))?;
+        self.persist(&reminders).map_err(ReminderError::Internal)?;
+        Ok(removed)
+    }
+
+    /// Reminders due at `now`, as they were when due. One-off reminders are
+    /// removed; repeating ones move on to their next time.
+    pub fn take_due(&self, now: DateTime<Utc>) -> Vec<Reminder> {
+        let mut reminders = self.lock();
+        let due: Vec<Reminder> = reminders
+            .values()
+            .filter(|r| r.next <= now)
+            .cloned()
+            .collect();
+        if due.is_empty() {
+            return due;
+        }
+        for reminder in &due {
+            let next = reminder
+                .cron
+                .as_deref()
+                .and_then(|cron| next_cron(cron, now).ok());
+            match next {
+                Some(next) => {
+                    let entry = reminders
+                        .get_mut(&reminder.id)
+                        .expect("due reminder is kept");
+                    entry.next = next;
+                    entry.fired += 1;
+                }
+                None => {
+                    reminders.remove(&reminder.id);
+                }
+            }
+        }
+        if let Err(e) = self.persist(&reminders) {
+            warn!("Failed to save reminders: {:#}", e);
+        }
+        due
+    }
+
+    fn persist(&self, reminders: &BTreeMap<String, Reminder>) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("create {}", parent.display()))?;
+        }
+        let file = RemindersFile {
+            reminders: reminders.values().cloned().collect(),
+        };
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(&file)?)
+            .with_context(|| format!("write {}", tmp.display()))?;
+        std::fs::rename(&tmp, path).with_context(|| format!("replace {}", path.display()))?;
+        Ok(())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, Reminder>> {
+        self.reminders
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Sends due reminders through the notify plugin's tool on the MCP server
+pub struct Notifier {
+    bridge: McpBridge,
+    tool: String,
+}
+
+impl Notifier {
+    pub fn new(mcp_server_url: &str, tool: &str) -> Result<Self> {
+        Ok(Self {
+            bridge: McpBridge::new(
+                mcp_server_url.to_string(),
+                std::time::Duration::from_secs(30),
+            )?,
+            tool: tool.to_string(),
+        })
+    }
+
+    async fn send(&self, reminder: &Reminder, target: &NotifyTarget) {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": format!("reminder-{}", reminder.id),
+            "method": "tools/call",
+            "params": {
+                "name": self.tool,
+                "arguments": {
+                    "platform": target.platform,
+                    "channel": target.channel,
+                    "text": format!("Reminder: {}", reminder.message),
+                },
+            },
+        });
+        let response = self.bridge.forward_request(request).await;
+        let failed = response.get("error").is_some()
+            || response["result"]["isError"].as_bool() == Some(true);
+        if failed {
+            warn!(
+                "Reminder {} was not delivered through {}: {}",
+                reminder.id, self.tool, response
+            );
+        }
+    }
+}
+
+/// Check for due reminders every second, putting them on the bus and
+/// sending them to their notify destination, until the bus closes
+pub fn spawn(reminders: Arc<Reminders>, bus: Sender<Evt>, notifier: Option<Notifier>) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(std::time::Duration::from_secs(1));
+        loop {
+            ticker.tick().await;
+            let now = Utc::now();
+            for reminder in reminders.take_due(now) {
+                info!("Reminder {} due: {}", reminder.id, reminder.message);
+                let evt = Evt::Reminder {
+                    id: reminder.id.clone(),
+                    message: reminder.message.clone(),
+                    ts: now,
+                };
+                if bus.send(evt).is_err() {
+                    return;
+                }
+                match (&notifier, &reminder.notify) {
+                    (Some(notifier), Some(target)) => notifier.send(&reminder, target).await,
+                    (None, Some(_)) => warn!(
+                        "Reminder {} names a notify destination but no MCP server is configured",
+                        reminder.id
+                    ),
+                    _ => {}
+                }
+            }
+        }
+    });
+}
+
+/// The time a `when` names: RFC 3339, `YYYY-MM-DD HH:MM` in local time, or a
+/// delay from `now` such as `in 20m`, `+2h` or `1h30m`
+pub fn parse_when(when: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, String> {
+    let when = when.trim();
+    let at = if let Ok(at) = DateTime::parse_from_rfc3339(when) {
+        at.with_timezone(&Utc)
+    } else if let Some(local) = ["%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(when, format).ok())
+    {
+        Local
+            .from_local_datetime(&local)
+            .earliest()
+            .ok_or_else(|| format!("{} does not exist in the local time zone", when))?
+            .with_timezone(&Utc)
+    } else {
+        let delay = when
+            .strip_prefix("in ")
+            .or_else(|| when.strip_prefix('+'))
+            .unwrap_or(when);
+        now + parse_delay(delay).ok_or_else(|| {
+            format!(
+                "invalid time {:?}: use RFC 3339, YYYY-MM-DD HH:MM, or a delay such as 20m or 1h30m",
+                when
+            )
+        })?
+    };
+    if at <= now {
+        return Err(format!("{} is in the past", at.to_rfc3339()));
+    }
+    Ok(at)
+}
+
+/// A delay such as `90s`, `20m`, `1h30m`, `2 days`
+fn parse_delay(delay: &str) -> Option<Duration> {
+    let mut total = Duration::zero();
+    let mut rest = delay.trim();
+    if rest.is_empty() {
+        return None;
+    }
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let amount: i64 = rest[..digits].parse().ok()?;
+        rest = rest[digits..].trim_start();
+        let letters = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let unit = match &rest[..letters] {
+            "s" | "sec" | "secs" | "second" | "seconds" => Duration::try_seconds(amount),
+            "m" | "min" | "mins" | "minute" | "minutes" => Duration::try_minutes(amount),
+            "h" | "hr" | "hrs" | "hour" | "hours" => Duration::try_hours(amount),
+            "d" | "day" | "days" => Duration::try_days(amount),
+            "w" | "week" | "weeks" => Duration::try_weeks(amount),
+            _ => None,
+        }?;
+        total = total.checked_add(&unit)?;
+        rest = rest[letters..].trim_start();
+    }
+    Some(total)
+}
+
+/// The first time after `now` that `cron` matches, in local time
+fn next_cron(cron: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, String> {
+    Cron::parse(cron)?
+        .next_after(&now.with_timezone(&Local))
+        .map(|next| next.with_timezone(&Utc))
+        .ok_or_else(|| format!("cron {:?} never matches", cron))
+}
+
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAYS: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// A five-field cron expression: minute, hour, day of month, month and day of
+/// week, each as bit sets
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cron {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    /// Bit 0 is Sunday
+    weekdays: u64,
+    /// Day of month was `*`
+    any_day: bool,
+    /// Day of week was `*`
+    any_weekday: bool,
+}
+
+impl Cron {
+    /// Parse `minute hour day month weekday`, or one of `@hourly`, `@daily`,
+    /// `@weekly`, `@monthly` and `@yearly`. Fields take `*`, numbers, names
+    /// (`jan`, `mon`), ranges, lists and `/` steps.
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let expanded = match expr.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!(
+                "cron {:?} needs 5 fields (minute hour day month weekday)",
+                expr
+            ));
+        }
+        let weekdays = parse_field(fields[4], 0, 7, &WEEKDAYS)?;
+        Ok(Self {
+            minutes: parse_field(fields[0], 0, 59, &[])?,
+            hours: parse_field(fields[1], 0, 23, &[])?,
+            days: parse_field(fields[2], 1, 31, &[])?,
+            months: parse_field(fields[3], 1, 12, &MONTHS)?,
+            // 7 is Sunday as well as 0
+            weekdays: (weekdays | (weekdays >> 7)) & 0x7f,
+            any_day: fields[2] == "*",
+            any_weekday: fields[4] == "*",
+        })
+    }
+
+    /// The first matching minute after `after`
+    pub fn next_after<Tz: TimeZone>(&self, after: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let tz = after.timezone();
+        let start = after.naive_local().with_second(0)?.with_nanosecond(0)?;
+        let mut t = start + Duration::minutes(1);
+        // Long enough to reach the next February 29th
+        let limit = start + Duration::days(8 * 366);
+        while t < limit {
+            if !bit(self.months, t.month()) {
+                let (year, month) = match t.month() {
+                    12 => (t.year() + 1, 1),
+                    m => (t.year(), m + 1),
+                };
+                t = NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if !self.day_matches(t.date()) {
+                t = t.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if !bit(self.hours, t.hour()) {
+                t = t.date().and_hms_opt(t.hour(), 0, 0)? + Duration::hours(1);
+                continue;
+            }
+            if bit(self.minutes, t.minute()) {
+                // Times skipped by a DST change don't exist and are passed over
+                if let Some(local) = tz.from_local_datetime(&t).earliest() {
+                    if local > *after {
+                        return Some(local);
+                    }
+                }
+            }
+            t += Duration::minutes(1);
+        }
+        None
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let day = bit(self.days, date.day());
+        let weekday = bit(self.weekdays, date.weekday().num_days_from_sunday());
+        // Like cron, a restricted day of month and day of week match either
+        match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (true, false) => weekday,
+            (false, true) => day,
+            (false, false) => day || weekday,
+        }
+    }
+}
+
+fn bit(set: u64, n: u32) -> bool {
+    set & (1 << n) != 0
+}
+
+/// Bits `min..=max` selected by one cron field
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<u64, String> {
+    let value = |text: &str| -> Result<u32, String> {
+        let lower = text.to_ascii_lowercase();
+        if let Some(i) = names.iter().position(|name| *name == lower) {
+            return Ok(i as u32 + min);
+        }
+        match text.parse::<u32>() {
+            Ok(n) if (min..=max).contains(&n) => Ok(n),
+            _ => Err(format!(
+                "cron field {:?}: {:?} is not in {}-{}",
+                field, text, min, max
+            )),
+        }
+    };
+
+    let mut set = 0u64;
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => match step.parse::<u32>() {
+                Ok(step) if step > 0 => (range, step),
+                _ => return Err(format!("cron field {:?}: bad step {:?}", field, step)),
+            },
+            None => (item, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            (value(a)?, value(b)?)
+        } else {
+            let n = value(range)?;
+            // `5/15` means every 15 from 5
+            (n, if step > 1 { max } else { n })
+        };
+        if start > end {
+            return Err(format!(
+                "cron field {:?}: range {} is backwards",
+                field, range
+            ));
+        }
+        for n in (start..=end).step_by(step as usize) {
+            set |= 1 << n;
+        }
+    }
+    Ok(set)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn next(expr: &str, after: &str) -> String {
+        Cron::parse(expr)
+            .unwrap()
+            .next_after(&utc(after))
+            .unwrap()
+            .to_rfc3339()
+    }
+
+    #[test]
+    fn cron_next_times() {
+        assert_eq!(
+            next("*/15 * * * *", "2025-01-01T10:07:30Z"),
+            "2025-01-01T10:15:00+00:00"
+        );
+        assert_eq!(
+            next("30 9 * * mon-fri", "2025-01-03T10:00:00Z"),
+            "2025-01-06T09:30:00+00:00"
+        );
+        assert_eq!(
+            next("@monthly", "2025-01-31T12:00:00Z"),
+            "2025-02-01T00:00:00+00:00"
+        );
+        assert_eq!(
+            next("0 12 29 feb *", "2025-03-01T00:00:00Z"),
+            "2028-02-29T12:00:00+00:00"
+        );
+        // Either a restricted day of month or day of week matches
+        assert_eq!(
+            next("0 0 15 * sun", "2025-01-06T00:00:00Z"),
+            "2025-01-12T00:00:00+00:00"
+        );
+        assert_eq!(
+            next("0 0 * * 7", "2025-01-06T00:00:00Z"),
+            "2025-01-12T00:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn cron_rejects_bad_fields() {
+        assert!(Cron::parse("* * * *").is_err());
+        assert!(Cron::parse("60 * * * *").is_err());
+        assert!(Cron::parse("*/0 * * * *").is_err());
+        assert!(Cron::parse("5-1 * * * *").is_err());
+        assert!(Cron::parse("0 0 31 feb *")
+            .unwrap()
+            .next_after(&Utc::now())
+            .is_none());
+    }
+
+    #[test]
+    fn when_formats() {
+        let now = utc("2025-01-01T10:00:00Z");
+        assert_eq!(
+            parse_when("in 20m", now).unwrap(),
+            utc("2025-01-01T10:20:00Z")
+        );
+        assert_eq!(
+            parse_when("+1h30m", now).unwrap(),
+            utc("2025-01-01T11:30:00Z")
+        );
+        assert_eq!(
+            parse_when("2 days", now).unwrap(),
+            utc("2025-01-03T10:00:00Z")
+        );
+        assert_eq!(
+            parse_when("2025-01-01T12:00:00+01:00", now).unwrap(),
+            utc("2025-01-01T11:00:00Z")
+        );
+        assert!(parse_when("2024-12-31T00:00:00Z", now).is_err());
+        assert!(parse_when("soon", now).is_err());
+        assert!(parse_when("5 fortnights", now).is_err());
+    }
+
+    #[test]
+    fn due_reminders_fire_and_repeat() {
+        let dir = std::env::temp_dir().join(format!("cyrupd-reminders-{}", std::process::id()));
+        let path = dir.join("reminders.json");
+        let now = utc("2025-01-01T10:00:00Z");
+        let reminders = Reminders::new(Some(path.clone())).unwrap();
+
+        let once = reminders
+            .schedule(
+                NewReminder {
+                    message: "stand up".into(),
+                    when: Some("5m".into()),
+                    ..Default::default()
+                },
+                now,
+            )
+            .unwrap();
+        let repeating = reminders
+            .schedule(
+                NewReminder {
+                    message: "drink water".into(),
+                    cron: Some("0 * * * *".into()),
+                    ..Default::default()
+                },
+                now,
+            )
+            .unwrap();
+        assert!(reminders
+            .schedule(
+                NewReminder {
+                    message: "both".into(),
+                    when: Some("5m".into()),
+                    cron: Some("@daily".into()),
+                    ..Default::default()
+                },
+                now
+            )
+            .is_err());
+
+        assert!(reminders.take_due(now).is_empty());
+        let due = reminders.take_due(utc("2025-01-01T11:00:00Z"));
+        assert_eq!(due.len(), 2);
+        let kept = Reminders::new(Some(path.clone())).unwrap().list();
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].id, repeating.id);
+        assert!(kept[0].next > utc("2025-01-01T11:00:00Z"));
+        assert_eq!(kept[0].fired, 1);
+
+        assert!(matches!(
+            reminders.cancel(&once.id),
+            Err(ReminderError::NotFound(_))
+        ));
+        reminders.cancel(&repeating.id).unwrap();
+        assert!(reminders.list().is_empty());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,268 @@
+//! Secret resolution for service environments.
+//!
+//! A service env var whose value is `secret://<provider>/<name>` is resolved
+//! when the service is spawned, so the config file only ever holds the
+//! reference. Providers:
+//!
+//! * `env`      – a variable in the daemon's own environment
+//! * `file`     – a file under `[secrets] dir` (or an absolute path:
+//!   `secret://file//run/secrets/token`), trailing newline stripped
+//! * `keychain` – the OS keychain (macOS Keychain, Secret Service, Windows
+//!   Credential Manager) under the `keychain_service` name
+
+use crate::config::SecretsConfig;
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+/// Prefix marking an env var value as a secret reference.
+pub const SCHEME: &str = "secret://";
+
+/// Keychain service name used when none is configured.
+pub const DEFAULT_KEYCHAIN_SERVICE: &str = "cyrupd";
+
+/// A backend that can look up secrets by name.
+pub trait SecretProvider: Send + Sync {
+    fn get(&self, name: &str) -> Result<String>;
+}
+
+/// Split `secret://<provider>/<name>`; `None` for plain values.
+pub fn parse_ref(value: &str) -> Option<(&str, &str)> {
+    value.strip_prefix(SCHEME)?.split_once('/')
+}
+
+pub struct EnvProvider;
+
+impl SecretProvider for EnvProvider {
+    fn get(&self, name: &str) -> Result<String> {
+        std::env::var(name).with_context(|| format!("environment variable {} is not set", name))
+    }
+}
+
+pub struct FileProvider {
+    dir: Option<PathBuf>,
+}
+
+impl FileProvider {
+    fn path_for(&self, name: &str) -> Result<PathBuf> {
+        let name = Path::new(name);
+        if name.components().any(|c| matches!(c, Component::ParentDir)) {
+            bail!("secret file name {} must not contain `..`", name.display());
+        }
+        if name.is_absolute() {
+            return Ok(name.to_path_buf());
+        }
+        match &self.dir {
+            Some(dir) => Ok(dir.join(name)),
+            None => bail!(
+                "relative secret file {} needs `[secrets] dir` in the daemon config",
+                name.display()
+            ),
+        }
+    }
+}
+
+impl SecretProvider for FileProvider {
+    fn get(&self, name: &str) -> Result<String> {
+        let path = self.path_for(name)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            if let Ok(meta) = std::fs::metadata(&path) {
+                if meta.permissions().mode() & 0o077 != 0 {
+                    log::warn!(
+                        "secret file {} is readable by other users; chmod 600 it",
+                        path.display()
+                    );
+                }
+            }
+        }
+        let value = std::fs::read_to_string(&path)
+            .with_context(|| format!("read secret file {}", path.display()))?;
+        Ok(value.trim_end_matches(['\r', '\n']).to_string())
+    }
+}
+
+pub struct KeychainProvider {
+    service: String,
+}
+
+impl KeychainProvider {
+    pub fn new(service: impl Into<String>) -> Self {
+        Self {
+            service: service.into(),
+        }
+    }
+
+    /// Store a secret, replacing any previous value.
+    #[cfg(feature = "keychain")]
+    pub fn set(&self, name: &str, value: &str) -> Result<()> {
+        keyring::Entry::new(&self.service, name)
+            .and_then(|entry| entry.set_password(value))
+            .with_context(|| format!("store {} in keychain service {}", name, self.service))
+    }
+
+    /// Remove a secret from the keychain.
+    #[cfg(feature = "keychain")]
+    pub fn delete(&self, name: &str) -> Result<()> {
+        keyring::Entry::new(&self.service, name)
+            .and_then(|entry| entry.delete_credential())
+            .with_context(|| format!("delete {} from keychain service {}", name, self.service))
+    }
+
+    #[cfg(not(feature = "keychain"))]
+    pub fn set(&self, _name: &str, _value: &str) -> Result<()> {
+        bail!("cyrupd was built without the `keychain` feature")
+    }
+
+    #[cfg(not(feature = "keychain"))]
+    pub fn delete(&self, _name: &str) -> Result<()> {
+        bail!("cyrupd was built without the `keychain` feature")
+    }
+}
+
+impl SecretProvider for KeychainProvider {
+    #[cfg(feature = "keychain")]
+    fn get(&self, name: &str) -> Result<String> {
+        keyring::Entry::new(&self.service, name)
+            .and_then(|entry| entry.get_password())
+            .with_context(|| format!("read {} from keychain service {}", name, self.service))
+    }
+
+    #[cfg(not(feature = "keychain"))]
+    fn get(&self, name: &str) -> Result<String> {
+        bail!(
+            "cannot read {} from the keychain: cyrupd was built without the `keychain` feature",
+            name
+        )
+    }
+}
+
+/// Registry of providers keyed by the `<provider>` part of a reference.
+pub struct Secrets {
+    providers: HashMap<&'static str, Box<dyn SecretProvider>>,
+}
+
+impl Secrets {
+    pub fn new(cfg: Option<&SecretsConfig>) -> Self {
+        let dir = cfg.and_then(|c| c.dir.as_ref()).map(PathBuf::from);
+        let service = cfg
+            .and_then(|c| c.keychain_service.clone())
+            .unwrap_or_else(|| DEFAULT_KEYCHAIN_SERVICE.to_string());
+
+        let mut providers: HashMap<&'static str, Box<dyn SecretProvider>> = HashMap::new();
+        providers.insert("env", Box::new(EnvProvider));
+        providers.insert("file", Box::new(FileProvider { dir }));
+        providers.insert("keychain", Box::new(KeychainProvider::new(service)));
+        Self { providers }
+    }
+
+    /// Register an additional provider, e.g. for tests or a vault backend.
+    pub fn with_provider(mut self, name: &'static str, provider: Box<dyn SecretProvider>) -> Self {
+        self.providers.insert(name, provider);
+        self
+    }
+
+    /// Resolve a single value; plain values are returned unchanged.
+    pub fn resolve(&self, value: &str) -> Result<String> {
+        let Some((provider, name)) = parse_ref(value) else {
+            if value.starts_with(SCHEME) {
+                bail!(
+                    "malformed secret reference {} (expected secret://<provider>/<name>)",
+                    value
+                );
+            }
+            return Ok(value.to_string());
+        };
+        let backend = self.providers.get(provider).ok_or_else(|| {
+            anyhow::anyhow!(
+                "unknown secret provider `{}` in {} (use env, file or keychain)",
+                provider,
+                value
+            )
+        })?;
+        if name.is_empty() {
+            bail!("secret reference {} has no name", value);
+        }
+        backend.get(name)
+    }
+
+    /// Resolve every env var, naming the variable in any error.
+    pub fn resolve_env(&self, vars: &HashMap<String, String>) -> Result<Vec<(String, String)>> {
+        vars.iter()
+            .map(|(key, value)| {
+                self.resolve(value)
+                    .map(|v| (key.clone(), v))
+                    .with_context(|| format!("env var {}", key))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed;
+
+    impl SecretProvider for Fixed {
+        fn get(&self, name: &str) -> Result<String> {
+            Ok(format!("value-of-{}", name))
+        }
+    }
+
+    #[test]
+    fn parses_references() {
+        assert_eq!(
+            parse_ref("secret://keychain/my-api-key"),
+            Some(("keychain", "my-api-key"))
+        );
+        assert_eq!(
+            parse_ref("secret://file//run/secrets/token"),
+            Some(("file", "/run/secrets/token"))
+        );
+        assert_eq!(parse_ref("plain"), None);
+    }
+
+    #[test]
+    fn resolves_env_and_files() {
+        let dir = std::env::temp_dir().join(format!("cyrupd-secrets-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("db"), "hunter2\n").unwrap();
+
+        let secrets = Secrets::new(Some(&SecretsConfig {
+            dir: Some(dir.to_string_lossy().into_owned()),
+            keychain_service: None,
+            ..Default::default()
+        }))
+        .with_provider("fixed", Box::new(Fixed));
+
+        let vars = HashMap::from([
+            ("DB_PASSWORD".to_string(), "secret://file/db".to_string()),
+            ("API_KEY".to_string(), "secret://fixed/api".to_string()),
+            ("MODE".to_string(), "production".to_string()),
+        ]);
+        let mut resolved = secrets.resolve_env(&vars).unwrap();
+        resolved.sort();
+        assert_eq!(
+            resolved,
+            vec![
+                ("API_KEY".to_string(), "value-of-api".to_string()),
+                ("DB_PASSWORD".to_string(), "hunter2".to_string()),
+                ("MODE".to_string(), "production".to_string()),
+            ]
+        );
+
+        assert!(secrets.resolve("secret://file/../etc/passwd").is_err());
+        assert!(secrets.resolve("secret://vault/x").is_err());
+        let err = secrets
+            .resolve_env(&HashMap::from([(
+                "MISSING".to_string(),
+                "secret://env/CYRUPD_TEST_UNSET_VAR".to_string(),
+            )]))
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("MISSING"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,388 @@
+//! Scheduled integrity scan of installed plugins and service binaries
+//!
+//! Every `interval_secs` the daemon hashes each `*.wasm` under the plugin
+//! directory and the executable of every supervised service, compares the
+//! SHA-256 digests against a known-bad list, and exports the result as
+//! `latest.json` and `latest.sarif`. Each match is also raised on the event
+//! bus as `Evt::SecurityAlert`.
+
+use crate::config::{SecurityScanConfig, ServiceDefinition};
+use crate::ipc::Evt;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use crossbeam_channel::{tick, Sender};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+/// What kind of artifact a scanned file is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactKind {
+    Plugin,
+    Binary,
+    Extra,
+}
+
+/// A file selected for scanning, with the service that runs it if any
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Artifact {
+    pub path: PathBuf,
+    pub kind: ArtifactKind,
+    pub service: Option<String>,
+}
+
+/// Hash of one scanned file and, if it is known bad, why
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScannedArtifact {
+    pub path: PathBuf,
+    pub kind: ArtifactKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service: Option<String>,
+    pub sha256: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub known_bad: Option<String>,
+}
+
+/// Outcome of one scan pass
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArtifactReport {
+    pub ts: DateTime<Utc>,
+    pub artifacts: Vec<ScannedArtifact>,
+    /// Files that could not be read, with the reason
+    #[serde(default)]
+    pub errors: Vec<(PathBuf, String)>,
+}
+
+impl ArtifactReport {
+    pub fn findings(&self) -> impl Iterator<Item = &ScannedArtifact> {
+        self.artifacts.iter().filter(|a| a.known_bad.is_some())
+    }
+
+    /// SARIF 2.1.0 log with one result per known-bad artifact
+    pub fn to_sarif(&self) -> serde_json::Value {
+        let results: Vec<_> = self
+            .findings()
+            .map(|a| {
+                json!({
+                    "ruleId": "known-bad-hash",
+                    "level": "error",
+                    "message": {
+                        "text": format!(
+                            "{} matches a known-bad hash: {}",
+                            a.path.display(),
+                            a.known_bad.as_deref().unwrap_or_default()
+                        )
+                    },
+                    "locations": [{
+                        "physicalLocation": {
+                            "artifactLocation": { "uri": format!("file://{}", a.path.display()) }
+                        }
+                    }],
+                    "fingerprints": { "sha256": a.sha256 }
+                })
+            })
+            .collect();
+        json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "cyrupd",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "rules": [{
+                            "id": "known-bad-hash",
+                            "shortDescription": {
+                                "text": "Plugin or service binary matches a known-bad SHA-256"
+                            }
+                        }]
+                    }
+                },
+                "invocations": [{
+                    "executionSuccessful": self.errors.is_empty(),
+                    "endTimeUtc": self.ts.to_rfc3339()
+                }],
+                "results": results
+            }]
+        })
+    }
+
+    /// Write `latest.json` and `latest.sarif` into `dir`
+    pub fn export(&self, dir: &Path) -> Result<()> {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("create report dir {}", dir.display()))?;
+        for (name, body) in [
+            ("latest.json", serde_json::to_vec_pretty(self)?),
+            ("latest.sarif", serde_json::to_vec_pretty(&self.to_sarif())?),
+        ] {
+            let path = dir.join(name);
+            let tmp = path.with_extension("tmp");
+            std::fs::write(&tmp, body).with_context(|| format!("write {}", tmp.display()))?;
+            std::fs::rename(&tmp, &path).with_context(|| format!("replace {}", path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse a known-bad list: `<sha256> [note]` per line, `#` starts a comment
+pub fn parse_known_bad(text: &str) -> HashMap<String, String> {
+    text.lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .filter_map(|line| {
+            let (hash, note) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let hash = hash.to_ascii_lowercase();
+            let valid = hash.len() == 64 && hash.bytes().all(|b| b.is_ascii_hexdigit());
+            valid.then(|| {
+                let note = match note.trim() {
+                    "" => "listed as known bad".to_string(),
+                    note => note.to_string(),
+                };
+                (hash, note)
+            })
+        })
+        .collect()
+}
+
+/// Everything a scan covers: plugins, service executables and extra paths
+pub fn collect_artifacts(
+    plugin_dir: Option<&Path>,
+    services: &[ServiceDefinition],
+    scan: &SecurityScanConfig,
+) -> Vec<Artifact> {
+    let mut out = Vec::new();
+    if let Some(dir) = plugin_dir {
+        out.extend(wasm_files(dir).map(|path| Artifact {
+            path,
+            kind: ArtifactKind::Plugin,
+            service: None,
+        }));
+    }
+    for def in services {
+        let Some(program) = def.command.split_whitespace().next() else {
+            continue;
+        };
+        let resolved = match def.working_dir.as_deref() {
+            Some(dir) if !Path::new(program).is_absolute() && program.contains('/') => {
+                Ok(Path::new(dir).join(program))
+            }
+            _ => which::which(program),
+        };
+        match resolved {
+            Ok(path) => out.push(Artifact {
+                path,
+                kind: ArtifactKind::Binary,
+                service: Some(def.name.clone()),
+            }),
+            Err(e) => warn!(
+                "{}: cannot locate {} for scanning: {}",
+                def.name, program, e
+            ),
+        }
+    }
+    for extra in &scan.paths {
+        let path = PathBuf::from(extra);
+        if path.is_dir() {
+            out.extend(
+                walkdir::WalkDir::new(&path)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|e| {
+                        e.file_type().is_file().then(|| Artifact {
+                            path: e.into_path(),
+                            kind: ArtifactKind::Extra,
+                            service: None,
+                        })
+                    }),
+            );
+        } else {
+            out.push(Artifact {
+                path,
+                kind: ArtifactKind::Extra,
+                service: None,
+            });
+        }
+    }
+    out.sort_by(|a, b| a.path.cmp(&b.path));
+    out.dedup_by(|a, b| a.path == b.path);
+    out
+}
+
+fn wasm_files(dir: &Path) -> impl Iterator<Item = PathBuf> {
+    walkdir::WalkDir::new(dir)
+        .into_iter()
+        .flatten()
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.into_path())
+        .filter(|p| p.extension().and_then(|s| s.to_str()) == Some("wasm"))
+}
+
+/// Hash every artifact and flag those on the known-bad list
+pub fn scan(artifacts: &[Artifact], known_bad: &HashMap<String, String>) -> ArtifactReport {
+    let mut report = ArtifactReport {
+        ts: Utc::now(),
+        ..Default::default()
+    };
+    for artifact in artifacts {
+        match sha256_file(&artifact.path) {
+            Ok(sha256) => report.artifacts.push(ScannedArtifact {
+                path: artifact.path.clone(),
+                kind: artifact.kind,
+                service: artifact.service.clone(),
+                known_bad: known_bad.get(&sha256).cloned(),
+                sha256,
+            }),
+            Err(e) => report
+                .errors
+                .push((artifact.path.clone(), format!("{:#}", e))),
+        }
+    }
+    report
+}
+
+/// Hex SHA-256 of a file's contents
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = File::open(path).with_context(|| format!("open {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// One full pass: load the known-bad list, scan, export
+pub fn run_once(
+    plugin_dir: Option<&Path>,
+    services: &[ServiceDefinition],
+    scan_cfg: &SecurityScanConfig,
+) -> Result<ArtifactReport> {
+    let known_bad = match &scan_cfg.known_bad {
+        Some(path) => parse_known_bad(
+            &std::fs::read_to_string(path)
+                .with_context(|| format!("read known-bad list {}", path))?,
+        ),
+        None => HashMap::new(),
+    };
+    let report = scan(
+        &collect_artifacts(plugin_dir, services, scan_cfg),
+        &known_bad,
+    );
+    if let Some(dir) = scan_cfg.report_path() {
+        report.export(&dir)?;
+    }
+    Ok(report)
+}
+
+/// Scan now and then every `interval_secs`, raising alerts on the bus
+pub fn spawn_scheduler(
+    plugin_dir: Option<PathBuf>,
+    services: Vec<ServiceDefinition>,
+    scan_cfg: SecurityScanConfig,
+    bus: Sender<Evt>,
+) {
+    thread::Builder::new()
+        .name("security-scan".into())
+        .spawn(move || {
+            let ticker = tick(Duration::from_secs(scan_cfg.interval_secs.max(60)));
+            loop {
+                match run_once(plugin_dir.as_deref(), &services, &scan_cfg) {
+                    Ok(report) => {
+                        info!(
+                            "Security scan: {} artifacts, {} known bad, {} unreadable",
+                            report.artifacts.len(),
+                            report.findings().count(),
+                            report.errors.len()
+                        );
+                        for finding in report.findings() {
+                            let alert = Evt::SecurityAlert {
+                                service: finding
+                                    .service
+                                    .clone()
+                                    .unwrap_or_else(|| "plugins".to_string()),
+                                path: finding.path.to_string_lossy().into_owned(),
+                                sha256: finding.sha256.clone(),
+                                detail: finding.known_bad.clone().unwrap_or_default(),
+                                ts: report.ts,
+                            };
+                            if bus.send(alert).is_err() {
+                                return;
+                            }
+                        }
+                    }
+                    Err(e) => error!("Security scan failed: {:#}", e),
+                }
+                if ticker.recv().is_err() {
+                    return;
+                }
+            }
+        })
+        .expect("spawn security scanner");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_bad_list_parsing() {
+        let list = parse_known_bad(
+            "# comment\n\
+             E3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855 empty file\n\
+             nothex trailing\n\
+             \n\
+             0000000000000000000000000000000000000000000000000000000000000000\n",
+        );
+        assert_eq!(list.len(), 2);
+        assert_eq!(
+            list["e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"],
+            "empty file"
+        );
+        assert_eq!(
+            list["0000000000000000000000000000000000000000000000000000000000000000"],
+            "listed as known bad"
+        );
+    }
+
+    #[test]
+    fn scan_flags_known_bad_and_exports_sarif() {
+        let dir = std::env::temp_dir().join(format!("cyrupd-artifacts-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("plugins")).unwrap();
+        std::fs::write(dir.join("plugins/bad.wasm"), b"").unwrap();
+        std::fs::write(dir.join("plugins/good.wasm"), b"\0asm").unwrap();
+        std::fs::write(dir.join("plugins/readme.txt"), b"ignored").unwrap();
+
+        let plugins = dir.join("plugins");
+        let artifacts = collect_artifacts(Some(&plugins), &[], &SecurityScanConfig::default());
+        assert_eq!(artifacts.len(), 2);
+
+        let known_bad = parse_known_bad(
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855 test sample",
+        );
+        let report = scan(&artifacts, &known_bad);
+        let findings: Vec<_> = report.findings().collect();
+        assert_eq!(findings.len(), 1);
+        assert!(findings[0].path.ends_with("bad.wasm"));
+
+        report.export(&dir.join("reports")).unwrap();
+        let sarif: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(dir.join("reports/latest.sarif")).unwrap())
+                .unwrap();
+        assert_eq!(sarif["version"], "2.1.0");
+        assert_eq!(sarif["runs"][0]["results"].as_array().unwrap().len(), 1);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
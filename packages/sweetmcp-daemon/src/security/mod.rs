@@ -6,6 +6,7 @@
 //! - SIMD-accelerated pattern matching
 //! - CI/CD integration for security validation
 
+pub mod artifacts;
 pub mod audit;
 
 pub use audit::*;
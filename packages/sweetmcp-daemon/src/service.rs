@@ -1,16 +1,40 @@
 mod autoconfig;
+mod cgroup;
+mod deploy;
+mod log_rotation;
+mod probe;
+#[cfg(unix)]
+mod process_attrs;
+#[cfg(unix)]
+mod sockets;
 
+pub mod admin;
 pub mod sse;
 
-use crate::config::ServiceDefinition;
+pub use autoconfig::CLIENTS_ENV as AUTOCONFIG_CLIENTS_ENV;
+
+use crate::config::{HealthProbe, ServiceDefinition};
+use crate::crash::{CrashReport, StderrTail};
 use crate::ipc::{Cmd, Evt};
-use anyhow::{Context, Result};
+use crate::secrets::Secrets;
+use anyhow::{bail, Context, Result};
+use cgroup::ServiceCgroup;
 use chrono::Utc;
 use crossbeam_channel::{bounded, select, tick, Receiver, Sender};
+use deploy::RegistrySwitch;
 use log::{error, info, warn};
+use log_rotation::{LogRotator, Rotation};
+use probe::{ProbeThresholds, Prober};
+#[cfg(unix)]
+use process_attrs::ProcessAttrs;
+#[cfg(unix)]
+use sockets::InheritedSockets;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
 use std::process::{Child, Command, Stdio};
+use std::sync::Arc;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 pub struct ServiceWorker {
     name: &'static str,
@@ -18,22 +42,63 @@ pub struct ServiceWorker {
     tx: Sender<Cmd>,
     bus: Sender<Evt>,
     def: ServiceDefinition,
+    secrets: Arc<Secrets>,
+    rotator: Option<LogRotator>,
+    cgroup: Option<ServiceCgroup>,
+    prober: Option<(Prober, ProbeThresholds)>,
+    /// Bound on first start and kept for the worker's lifetime
+    #[cfg(unix)]
+    sockets: Option<InheritedSockets>,
+    started_at: Option<Instant>,
+    /// Recent stderr of each live instance, keyed by pid
+    tails: HashMap<u32, StderrTail>,
+    /// Active blue/green slot when `def.deploy` is set
+    slot: usize,
+    deploy: Option<RegistrySwitch>,
 }
 
 impl ServiceWorker {
-    pub fn spawn(def: ServiceDefinition, bus: Sender<Evt>) -> Sender<Cmd> {
+    pub fn spawn(def: ServiceDefinition, bus: Sender<Evt>, secrets: Arc<Secrets>) -> Sender<Cmd> {
         let (tx, rx) = bounded::<Cmd>(16);
         let name: &'static str = Box::leak(def.name.clone().into_boxed_str());
         let tx_clone = tx.clone();
         thread::Builder::new()
             .name(format!("svc-{}", name))
             .spawn(move || {
+                let mut def = def;
+                let rotator = match (&def.log_file, &def.log_rotation) {
+                    (Some(path), Some(cfg)) => Some(LogRotator::new(path, cfg.clone())),
+                    _ => None,
+                };
+                let deploy = def.deploy.as_ref().and_then(|cfg| {
+                    cfg.discovery_token
+                        .as_deref()
+                        .map(|token| secrets.resolve(token))
+                        .transpose()
+                        .and_then(|token| RegistrySwitch::new(cfg, token))
+                        .map_err(|e| warn!("{}: blue/green deploy disabled: {:#}", name, e))
+                        .ok()
+                });
+                if deploy.is_none() {
+                    def.deploy = None;
+                }
+                let prober = slot_prober(name, &def, 0);
                 let mut worker = ServiceWorker {
                     name,
                     rx,
                     tx: tx_clone,
                     bus,
                     def,
+                    secrets,
+                    rotator,
+                    cgroup: None,
+                    prober,
+                    #[cfg(unix)]
+                    sockets: None,
+                    started_at: None,
+                    tails: HashMap::new(),
+                    slot: 0,
+                    deploy,
                 };
                 if let Err(e) = worker.run() {
                     error!("Worker {} crashed: {:#}", worker.name, e);
@@ -44,7 +109,11 @@ impl ServiceWorker {
     }
 
     fn run(&mut self) -> Result<()> {
-        let health_tick = tick(Duration::from_secs(60));
+        let health_interval = self
+            .prober
+            .as_ref()
+            .map_or(Duration::from_secs(60), |(p, _)| p.interval());
+        let health_tick = tick(health_interval);
         let rotate_tick = tick(Duration::from_secs(3600));
         let mut child: Option<Child> = None;
 
@@ -54,31 +123,37 @@ impl ServiceWorker {
                     Cmd::Start    => self.start(&mut child)?,
                     Cmd::Stop     => self.stop(&mut child)?,
                     Cmd::Restart  => { self.stop(&mut child)?; self.start(&mut child)?; },
+                    Cmd::Upgrade { new_binary } => self.upgrade(&mut child, &new_binary)?,
                     Cmd::Shutdown => { self.stop(&mut child)?; break; },
                     Cmd::TickHealth   => self.health_check(&mut child)?,
-                    Cmd::TickLogRotate=> self.rotate_logs()?,
+                    Cmd::TickLogRotate=> self.rotate_logs(&child)?,
                 },
                 recv(health_tick) -> _ => self.health_check(&mut child)?,
-                recv(rotate_tick) -> _ => self.rotate_logs()?,
+                recv(rotate_tick) -> _ => self.rotate_logs(&child)?,
             }
         }
         Ok(())
     }
 
-    fn start(&self, child: &mut Option<Child>) -> Result<()> {
+    fn start(&mut self, child: &mut Option<Child>) -> Result<()> {
         if child.is_some() {
             warn!("{} already running", self.name);
             return Ok(());
         }
-        let mut cmd = Command::new("sh");
-        cmd.arg("-c")
-            .arg(&self.def.command)
-            .stdout(Stdio::null())
-            .stderr(Stdio::null());
-        if let Some(dir) = &self.def.working_dir {
-            cmd.current_dir(dir);
-        }
-        let spawned = cmd.spawn().context("spawn")?;
+        let cmd = match self.build_command(&self.def.command.clone(), self.slot) {
+            Ok(cmd) => cmd,
+            Err(e) => {
+                error!("{}: not starting: {:#}", self.name, e);
+                self.bus.send(Evt::State {
+                    service: self.name.to_string(),
+                    kind: "failed",
+                    ts: Utc::now(),
+                    pid: None,
+                })?;
+                return Ok(());
+            }
+        };
+        let spawned = self.launch(cmd)?;
         let pid = spawned.id();
         *child = Some(spawned);
         self.bus.send(Evt::State {
@@ -91,10 +166,273 @@ impl ServiceWorker {
         Ok(())
     }
 
-    fn stop(&self, child: &mut Option<Child>) -> Result<()> {
+    /// Prepare `sh -c <command>` with resolved env, log redirection and any
+    /// inherited listening sockets.
+    fn build_command(&mut self, command: &str, slot: usize) -> Result<Command> {
+        let mut vars = self.def.env_vars.clone();
+        if let Some(slot) = self.def.deploy.as_ref().and_then(|d| d.slots.get(slot)) {
+            vars.extend(slot.env.clone());
+        }
+        // Resolve secret:// references now so plaintext never hits the config
+        let env = self.secrets.resolve_env(&vars)?;
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").envs(env);
+
+        #[cfg(unix)]
+        {
+            ProcessAttrs::from_def(&self.def)?.apply(self.name, &mut cmd);
+            if self.sockets.is_none() && !self.def.sockets.is_empty() {
+                self.sockets = Some(InheritedSockets::bind(&self.def.sockets)?);
+            }
+            match self.sockets.as_ref().filter(|s| !s.is_empty()) {
+                Some(sockets) => {
+                    cmd.arg(sockets::wrap_command(command));
+                    sockets.apply(&mut cmd);
+                }
+                None => {
+                    cmd.arg(command);
+                }
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            if !self.def.sockets.is_empty() {
+                warn!("{}: socket passing is only supported on unix", self.name);
+            }
+            if self.def.umask.is_some() || self.def.nice.is_some() || self.def.ionice.is_some() {
+                warn!(
+                    "{}: umask, nice and ionice are only supported on unix",
+                    self.name
+                );
+            }
+            cmd.arg(command);
+        }
+
+        // stderr is pumped into the log by `launch` so a crash report can
+        // keep its tail.
+        match self.open_log()? {
+            Some(out) => cmd.stdout(out),
+            None => cmd.stdout(Stdio::null()),
+        };
+        cmd.stderr(Stdio::piped());
+        if let Some(dir) = &self.def.working_dir {
+            cmd.current_dir(dir);
+        }
+        Ok(cmd)
+    }
+
+    fn open_log(&self) -> Result<Option<File>> {
+        let Some(path) = &self.def.log_file else {
+            return Ok(None);
+        };
+        if let Some(parent) = std::path::Path::new(path).parent() {
+            std::fs::create_dir_all(parent).ok();
+        }
+        // O_APPEND keeps writes at EOF after a copy‑truncate rotation.
+        let out = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("open log {}", path))?;
+        Ok(Some(out))
+    }
+
+    fn launch(&mut self, mut cmd: Command) -> Result<Child> {
+        let mut spawned = cmd.spawn().context("spawn")?;
+        if let Some(stderr) = spawned.stderr.take() {
+            let sink = self
+                .open_log()
+                .map_err(|e| warn!("{}: stderr not logged: {:#}", self.name, e))
+                .ok()
+                .flatten();
+            let cap = self.def.crash.tail_kb * 1024;
+            let tail = StderrTail::attach(self.name, stderr, sink, cap);
+            self.tails.insert(spawned.id(), tail);
+        }
+        self.contain(spawned.id());
+        if let Some(filter) = &self.def.crash.coredump_filter {
+            set_coredump_filter(self.name, spawned.id(), filter);
+        }
+        self.started_at = Some(Instant::now());
+        if let Some((_, thresholds)) = self.prober.as_mut() {
+            thresholds.reset();
+        }
+        Ok(spawned)
+    }
+
+    /// Blue/green swap: start `new_binary` next to the running instance, wait
+    /// for it to pass the health check, then drain and stop the old one. On
+    /// any failure the new instance is killed and the old one keeps serving.
+    fn upgrade(&mut self, child: &mut Option<Child>, new_binary: &str) -> Result<()> {
+        let command = match upgraded_command(&self.def.command, new_binary) {
+            Ok(command) => command,
+            Err(e) => return self.upgrade_failed(None, format!("{:#}", e)),
+        };
+        let Some(old) = child.as_mut() else {
+            info!("{} not running; upgrading in place", self.name);
+            self.def.command = command;
+            return self.start(child);
+        };
+        let old_pid = old.id();
+        let next = match self.deploy {
+            Some(_) => 1 - self.slot,
+            None => self.slot,
+        };
+
+        let launched = self
+            .build_command(&command, next)
+            .and_then(|cmd| self.launch(cmd));
+        let mut new = match launched {
+            Ok(new) => new,
+            Err(e) => return self.upgrade_failed(None, format!("{:#}", e)),
+        };
+        let new_pid = new.id();
+        info!(
+            "{} upgrade: started {} (pid {}) next to pid {}",
+            self.name, new_binary, new_pid, old_pid
+        );
+
+        let ready = if self.deploy.is_some() {
+            self.switch_slot(&mut new, next)
+        } else {
+            self.wait_ready(&mut new)
+        };
+        if let Err(e) = ready {
+            terminate(&mut new, Duration::ZERO);
+            self.tails.remove(&new_pid);
+            return self.upgrade_failed(Some(new_pid), format!("{:#}", e));
+        }
+
+        if let Some(mut old) = child.take() {
+            terminate(&mut old, Duration::from_secs(self.def.upgrade.drain_secs));
+            self.tails.remove(&old_pid);
+        }
+        *child = Some(new);
+        self.def.command = command;
+        if next != self.slot {
+            self.slot = next;
+            self.prober = slot_prober(self.name, &self.def, next);
+        }
+        self.bus.send(Evt::Upgrade {
+            service: self.name.to_string(),
+            ok: true,
+            pid: Some(new_pid),
+            detail: format!("pid {} → {} ({})", old_pid, new_pid, new_binary),
+            ts: Utc::now(),
+        })?;
+        Ok(())
+    }
+
+    fn upgrade_failed(&mut self, pid: Option<u32>, detail: String) -> Result<()> {
+        error!("{} upgrade aborted: {}", self.name, detail);
+        self.bus.send(Evt::Upgrade {
+            service: self.name.to_string(),
+            ok: false,
+            pid,
+            detail,
+            ts: Utc::now(),
+        })?;
+        Ok(())
+    }
+
+    /// Wait for the new slot's health URL, then move the gateway peer
+    /// registries from the old slot's address to the new one.
+    fn switch_slot(&self, new: &mut Child, next: usize) -> Result<()> {
+        let (Some(switch), Some(cfg)) = (self.deploy.as_ref(), self.def.deploy.as_ref()) else {
+            bail!("no deploy slots configured");
+        };
+        let (from, to) = (&cfg.slots[self.slot], &cfg.slots[next]);
+        let deadline = Instant::now() + Duration::from_secs(self.def.upgrade.ready_timeout_secs);
+        switch.wait_healthy(&to.health_url, deadline, new)?;
+
+        if !cfg.registries.is_empty() && switch.switch(&from.advertise, &to.advertise) == 0 {
+            bail!(
+                "no peer registry accepted {} → {}",
+                from.advertise,
+                to.advertise
+            );
+        }
+        info!(
+            "{} upgrade: traffic moved from {} to {}",
+            self.name, from.advertise, to.advertise
+        );
+        Ok(())
+    }
+
+    /// Block until the new instance passes `success_threshold` consecutive
+    /// probes (or, without a probe, survives its initial delay).
+    fn wait_ready(&self, new: &mut Child) -> Result<()> {
+        let deadline = Instant::now() + Duration::from_secs(self.def.upgrade.ready_timeout_secs);
+        let alive = |new: &mut Child| -> Result<()> {
+            match new.try_wait()? {
+                Some(status) => bail!("new instance exited with {}", status),
+                None => Ok(()),
+            }
+        };
+
+        let Some((prober, _)) = self.prober.as_ref() else {
+            thread::sleep(UPGRADE_SETTLE);
+            return alive(new);
+        };
+
+        thread::sleep(prober.initial_delay());
+        let mut successes = 0;
+        loop {
+            alive(new)?;
+            match prober.probe() {
+                Ok(()) => successes += 1,
+                Err(e) => {
+                    successes = 0;
+                    info!("{} upgrade: new instance not ready: {:#}", self.name, e);
+                }
+            }
+            if successes >= prober.success_threshold() {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                bail!(
+                    "new instance not healthy after {}s",
+                    self.def.upgrade.ready_timeout_secs
+                );
+            }
+            thread::sleep(Duration::from_secs(1));
+        }
+    }
+
+    /// Place the child into its cgroup slice, degrading to uncontained on failure.
+    fn contain(&mut self, pid: u32) {
+        let Some(limits) = &self.def.resources else {
+            return;
+        };
+        if self.cgroup.is_none() {
+            match ServiceCgroup::prepare(self.name, limits) {
+                Ok(Some(cg)) => self.cgroup = Some(cg),
+                Ok(None) => {
+                    warn!("{}: cgroup v2 unavailable, resource limits not applied", self.name);
+                    return;
+                }
+                Err(e) => {
+                    warn!("{}: cgroup setup failed, running uncontained: {:#}", self.name, e);
+                    return;
+                }
+            }
+        }
+        if let Some(cg) = &self.cgroup {
+            if let Err(e) = cg.attach(pid) {
+                warn!("{}: could not attach pid {} to cgroup: {:#}", self.name, pid, e);
+            }
+        }
+    }
+
+    fn stop(&mut self, child: &mut Option<Child>) -> Result<()> {
         if let Some(mut ch) = child.take() {
             let pid = ch.id();
             ch.kill().ok();
+            ch.wait().ok();
+            self.tails.remove(&pid);
+            if let Some(cg) = self.cgroup.take() {
+                cg.release();
+            }
             self.bus.send(Evt::State {
                 service: self.name.to_string(),
                 kind: "stopped",
@@ -106,11 +444,34 @@ impl ServiceWorker {
         Ok(())
     }
 
-    fn health_check(&self, child: &mut Option<Child>) -> Result<()> {
-        let healthy = child
-            .as_mut()
-            .map(|c| c.try_wait().ok().flatten().is_none())
-            .unwrap_or(false);
+    fn health_check(&mut self, child: &mut Option<Child>) -> Result<()> {
+        if let Some(status) = child.as_mut().and_then(|c| c.try_wait().ok().flatten()) {
+            if let Some(dead) = child.take() {
+                self.record_crash(dead.id(), status)?;
+            }
+        }
+        let alive = child.is_some();
+        let healthy = match self.prober.as_mut() {
+            // A dead process is unhealthy regardless of thresholds
+            _ if !alive => false,
+            None => true,
+            Some((prober, thresholds)) => {
+                let in_grace = self
+                    .started_at
+                    .is_some_and(|t| t.elapsed() < prober.initial_delay());
+                if in_grace {
+                    return Ok(());
+                }
+                let ok = match prober.probe() {
+                    Ok(()) => true,
+                    Err(e) => {
+                        warn!("{} health probe failed: {:#}", self.name, e);
+                        false
+                    }
+                };
+                thresholds.record(ok)
+            }
+        };
         self.bus.send(Evt::Health {
             service: self.name.to_string(),
             healthy,
@@ -123,8 +484,117 @@ impl ServiceWorker {
         Ok(())
     }
 
-    fn rotate_logs(&self) -> Result<()> {
-        // (implementation stripped for brevity; same algorithm as original)
+    /// Keep the last words of an instance that exited on its own.
+    fn record_crash(&mut self, pid: u32, status: std::process::ExitStatus) -> Result<()> {
+        if let Some(cg) = self.cgroup.take() {
+            cg.release();
+        }
+        let tail = self
+            .tails
+            .remove(&pid)
+            .map(StderrTail::finish)
+            .unwrap_or_default();
+        let report = CrashReport::capture(
+            self.name,
+            pid,
+            status,
+            self.started_at.map(|t| t.elapsed()),
+            self.def.working_dir.as_deref(),
+            tail,
+        );
+        error!("{} (pid {}) {}", self.name, pid, report.summary());
+        if let Some(dir) = &self.def.crash.dir {
+            match report.save(dir) {
+                Ok(path) => info!("{} crash report written to {}", self.name, path.display()),
+                Err(e) => warn!("{}: crash report not saved: {:#}", self.name, e),
+            }
+        }
+        self.bus.send(Evt::State {
+            service: self.name.to_string(),
+            kind: "crashed",
+            ts: report.ts,
+            pid: Some(pid),
+        })?;
+        self.collect_dump(report);
+        Ok(())
+    }
+
+    /// Copy the crash's dump next to its report off the worker thread; crash
+    /// handlers can take seconds and the restart should not wait for them.
+    fn collect_dump(&self, mut report: CrashReport) {
+        let crash = &self.def.crash;
+        let Some(dir) = crash.dir.clone().filter(|_| crash.collect_dumps) else {
+            return;
+        };
+        if !report.expects_dump() {
+            return;
+        }
+        let program = self
+            .def
+            .command
+            .split_whitespace()
+            .next()
+            .and_then(|p| std::path::Path::new(p).file_name())
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.name.to_string());
+        let max_bytes = crash.max_dump_mb * 1024 * 1024;
+        let bus = self.bus.clone();
+        let name = self.name;
+
+        let spawned = thread::Builder::new()
+            .name(format!("crashdump-{}", name))
+            .spawn(move || {
+                let path = match report.collect_dump(&dir, &program, max_bytes) {
+                    Ok(path) => path,
+                    Err(e) => {
+                        warn!("{}: crash dump not collected: {:#}", name, e);
+                        return;
+                    }
+                };
+                info!("{} crash dump copied to {}", name, path.display());
+                if let Err(e) = report.save(&dir) {
+                    warn!("{}: crash report not updated: {:#}", name, e);
+                }
+                bus.send(Evt::CrashDump {
+                    service: name.to_string(),
+                    pid: report.pid,
+                    path: path.to_string_lossy().into_owned(),
+                    ts: Utc::now(),
+                })
+                .ok();
+            });
+        if let Err(e) = spawned {
+            warn!("{}: crash dump collector not started: {}", self.name, e);
+        }
+    }
+
+    fn rotate_logs(&mut self, child: &Option<Child>) -> Result<()> {
+        let Some(rotator) = self.rotator.as_mut() else {
+            return Ok(());
+        };
+        let rotated = match rotator.rotate_if_due(Utc::now()) {
+            Ok(Rotation::Rotated(path)) => path,
+            Ok(Rotation::Skipped) => return Ok(()),
+            Err(e) => {
+                warn!("{} log rotation failed: {:#}", self.name, e);
+                return Ok(());
+            }
+        };
+        info!("{} rotated log into {}", self.name, rotated.display());
+
+        #[cfg(unix)]
+        if rotator.signals_child() {
+            if let Some(ch) = child {
+                use nix::sys::signal::{kill, Signal};
+                use nix::unistd::Pid;
+                if let Err(e) = kill(Pid::from_raw(ch.id() as i32), Signal::SIGHUP) {
+                    warn!("{} SIGHUP after rotation failed: {}", self.name, e);
+                }
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = child;
+
         self.bus.send(Evt::LogRotate {
             service: self.name.to_string(),
             ts: Utc::now(),
@@ -133,13 +603,103 @@ impl ServiceWorker {
     }
 }
 
+/// Health probe for `slot`: with deploy slots the configured probe timings
+/// apply to the slot's own `health_url`.
+fn slot_prober(
+    name: &str,
+    def: &ServiceDefinition,
+    slot: usize,
+) -> Option<(Prober, ProbeThresholds)> {
+    let mut cfg = def.health_check.clone()?;
+    if let Some(slot) = def.deploy.as_ref().and_then(|d| d.slots.get(slot)) {
+        cfg.probe = HealthProbe::Http {
+            url: slot.health_url.clone(),
+            expected_status: None,
+            expected_response: None,
+        };
+    }
+    Prober::new(cfg)
+        .map_err(|e| warn!("{}: health probe disabled: {:#}", name, e))
+        .ok()
+        .map(|p| {
+            let thresholds = p.thresholds();
+            (p, thresholds)
+        })
+}
+
+/// Minimum time a probe‑less instance must stay up before an upgrade commits
+const UPGRADE_SETTLE: Duration = Duration::from_secs(2);
+
+/// Replace the program (first word) of `command` with `new_binary`.
+fn upgraded_command(command: &str, new_binary: &str) -> Result<String> {
+    let path = std::path::Path::new(new_binary);
+    if !path.is_file() {
+        bail!("{} is not a file", new_binary);
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if path.metadata()?.permissions().mode() & 0o111 == 0 {
+            bail!("{} is not executable", new_binary);
+        }
+    }
+    if new_binary.contains(char::is_whitespace) || new_binary.contains('\'') {
+        bail!(
+            "binary path {:?} must not contain whitespace or quotes",
+            new_binary
+        );
+    }
+    let rest = command
+        .trim_start()
+        .split_once(char::is_whitespace)
+        .map_or("", |(_, rest)| rest);
+    Ok(if rest.is_empty() {
+        new_binary.to_string()
+    } else {
+        format!("{} {}", new_binary, rest)
+    })
+}
+
+/// Choose which memory mappings go into `pid`'s core dump (see core(5)).
+fn set_coredump_filter(name: &str, pid: u32, filter: &str) {
+    if !cfg!(target_os = "linux") {
+        return;
+    }
+    let path = format!("/proc/{}/coredump_filter", pid);
+    if let Err(e) = std::fs::write(&path, filter) {
+        warn!("{}: could not set {} to {}: {}", name, path, filter, e);
+    }
+}
+
+/// SIGTERM, wait up to `grace`, then SIGKILL and reap.
+fn terminate(child: &mut Child, grace: Duration) {
+    #[cfg(unix)]
+    if !grace.is_zero() {
+        use nix::sys::signal::{kill, Signal};
+        use nix::unistd::Pid;
+        if kill(Pid::from_raw(child.id() as i32), Signal::SIGTERM).is_ok() {
+            let deadline = Instant::now() + grace;
+            while Instant::now() < deadline {
+                if let Ok(Some(_)) = child.try_wait() {
+                    return;
+                }
+                thread::sleep(Duration::from_millis(100));
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = grace;
+    child.kill().ok();
+    child.wait().ok();
+}
+
 /// Public function to spawn a service worker
-pub fn spawn(def: ServiceDefinition, bus: Sender<Evt>) -> Sender<Cmd> {
+pub fn spawn(def: ServiceDefinition, bus: Sender<Evt>, secrets: Arc<Secrets>) -> Sender<Cmd> {
     // Check if this is the special autoconfig service
     if def.name == "sweetmcp-autoconfig" || def.service_type == Some("autoconfig".to_string()) {
         return autoconfig::spawn_autoconfig(def, bus);
     }
 
     // Otherwise spawn normal service
-    ServiceWorker::spawn(def, bus)
+    ServiceWorker::spawn(def, bus, secrets)
 }
@@ -0,0 +1,801 @@
+//! Local admin HTTP API
+//!
+//! Lets dashboards list, start, stop, restart or upgrade services and tail
+//! their logs, and lists the AI clients found by tool-configurator plugins,
+//! without speaking the internal IPC protocol. Listens on loopback
+//! TCP or a unix socket only, and every request must carry
+//! `Authorization: Bearer <token>`.
+//!
+//! `/secrets` fronts the credential vault. Besides the admin token it takes
+//! the tokens of `[secrets.clients]`, each limited by its own policy.
+//! `/reminders` schedules reminders, and also takes the `[reminders]` token.
+//! `/security/sandbox` takes sandbox denial reports from the MCP server, and
+//! also takes the `[sandbox_reports]` token.
+
+use crate::desired_state::{Desired, DesiredState};
+use crate::ipc::{Cmd, Evt};
+use crate::reminders::{NewReminder, Reminder, ReminderError, Reminders};
+use crate::tool_integration::{PluginDetection, ToolConfiguratorHost};
+use crate::vault::{Caller, SecretView, Vault, VaultError};
+use anyhow::{Context, Result};
+use axum::{
+    extract::{Extension, Path, Query, Request, State},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{delete, get, post},
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use crossbeam_channel::Sender;
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::oneshot;
+use tracing::{error, info, warn};
+
+/// Default and maximum number of log lines returned by `/logs`
+const DEFAULT_LOG_LINES: usize = 100;
+const MAX_LOG_LINES: usize = 5000;
+
+/// Last known state of a service, maintained by the manager's event loop
+#[derive(Debug, Clone, Serialize)]
+pub struct ServiceStatus {
+    pub state: &'static str,
+    pub pid: Option<u32>,
+    pub since: DateTime<Utc>,
+    pub healthy: Option<bool>,
+    pub last_health_check: Option<DateTime<Utc>>,
+}
+
+/// Shared, concurrently updated status table keyed by service name
+pub type StatusTable = Arc<DashMap<String, ServiceStatus>>;
+
+/// Everything the admin handlers need from the manager
+#[derive(Clone)]
+pub struct AdminState {
+    pub workers: Arc<HashMap<String, Sender<Cmd>>>,
+    pub log_files: Arc<HashMap<String, Option<String>>>,
+    pub status: StatusTable,
+    pub desired: Arc<DesiredState>,
+    pub tools: Arc<ToolConfiguratorHost>,
+    pub vault: Arc<Vault>,
+    pub reminders: Arc<Reminders>,
+    /// The manager's event bus, for events raised through the API
+    pub events: Sender<Evt>,
+    /// Token of `[sandbox_reports]`, if configured
+    pub sandbox_token: Option<Arc<str>>,
+    pub token: Arc<str>,
+}
+
+/// Where the admin API listens
+#[derive(Debug, Clone, PartialEq)]
+pub enum Listen {
+    Tcp(SocketAddr),
+    #[cfg(unix)]
+    Unix(std::path::PathBuf),
+}
+
+impl Listen {
+    /// Parse `127.0.0.1:<port>` or `unix:/path`; non-loopback addresses are refused.
+    pub fn parse(spec: &str) -> Result<Self> {
+        if let Some(path) = spec.strip_prefix("unix:") {
+            #[cfg(unix)]
+            return Ok(Listen::Unix(path.into()));
+            #[cfg(not(unix))]
+            anyhow::bail!("unix socket {} not supported on this platform", path);
+        }
+        let addr: SocketAddr = spec
+            .parse()
+            .with_context(|| format!("invalid admin listen address {}", spec))?;
+        if !addr.ip().is_loopback() {
+            anyhow::bail!("admin API must bind to loopback, got {}", addr);
+        }
+        Ok(Listen::Tcp(addr))
+    }
+}
+
+/// Serve the admin API until `shutdown_rx` fires
+pub async fn start_admin_server(
+    listen: Listen,
+    state: AdminState,
+    shutdown_rx: oneshot::Receiver<()>,
+) -> Result<()> {
+    let app = build_router(state);
+
+    match listen {
+        Listen::Tcp(addr) => {
+            let listener = tokio::net::TcpListener::bind(addr)
+                .await
+                .with_context(|| format!("bind admin API to {}", addr))?;
+            info!("Admin API listening on {}", addr);
+            axum::serve(listener, app)
+                .with_graceful_shutdown(async {
+                    shutdown_rx.await.ok();
+                })
+                .await
+                .context("admin API server error")?;
+        }
+        #[cfg(unix)]
+        Listen::Unix(path) => serve_unix(&path, app, shutdown_rx).await?,
+    }
+
+    info!("Admin API stopped");
+    Ok(())
+}
+
+/// axum 0.7 only serves TCP listeners, so drive hyper directly for unix sockets.
+#[cfg(unix)]
+async fn serve_unix(
+    path: &std::path::Path,
+    app: Router,
+    mut shutdown_rx: oneshot::Receiver<()>,
+) -> Result<()> {
+    use hyper_util::rt::{TokioExecutor, TokioIo};
+    use hyper_util::server::conn::auto::Builder;
+    use hyper_util::service::TowerToHyperService;
+    use std::os::unix::fs::PermissionsExt;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).ok();
+    }
+    // A stale socket from a previous run would make bind fail.
+    if path.exists() {
+        std::fs::remove_file(path)
+            .with_context(|| format!("remove stale socket {}", path.display()))?;
+    }
+    let listener = tokio::net::UnixListener::bind(path)
+        .with_context(|| format!("bind admin socket {}", path.display()))?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+        .with_context(|| format!("restrict permissions on {}", path.display()))?;
+    info!("Admin API listening on unix:{}", path.display());
+
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let (stream, _) = match accepted {
+                    Ok(conn) => conn,
+                    Err(e) => {
+                        warn!("admin socket accept failed: {}", e);
+                        continue;
+                    }
+                };
+                let service = TowerToHyperService::new(app.clone());
+                tokio::spawn(async move {
+                    if let Err(e) = Builder::new(TokioExecutor::new())
+                        .serve_connection(TokioIo::new(stream), service)
+                        .await
+                    {
+                        warn!("admin connection error: {}", e);
+                    }
+                });
+            }
+            _ = &mut shutdown_rx => break,
+        }
+    }
+
+    std::fs::remove_file(path).ok();
+    Ok(())
+}
+
+fn build_router(state: AdminState) -> Router {
+    let secrets = Router::new()
+        .route("/secrets", get(list_secrets))
+        .route("/secrets/*name", get(get_secret).put(store_secret))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            require_secret_caller,
+        ));
+    let reminders = Router::new()
+        .route("/reminders", get(list_reminders).post(schedule_reminder))
+        .route("/reminders/:id", delete(cancel_reminder))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            require_reminders_caller,
+        ));
+    let sandbox = Router::new()
+        .route("/security/sandbox", post(report_sandbox_violation))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            require_sandbox_reporter,
+        ));
+
+    Router::new()
+        .route("/services", get(list_services))
+        .route("/services/:name/start", post(start_service))
+        .route("/services/:name/stop", post(stop_service))
+        .route("/services/:name/restart", post(restart_service))
+        .route("/services/:name/upgrade", post(upgrade_service))
+        .route("/services/:name/logs", get(service_logs))
+        .route("/tools", get(list_tools))
+        .route("/tools/rescan", post(rescan_tools))
+        .layer(middleware::from_fn_with_state(state.clone(), require_token))
+        .merge(secrets)
+        .merge(reminders)
+        .merge(sandbox)
+        .with_state(state)
+}
+
+/// Reject requests without the configured bearer token
+async fn require_token(State(state): State<AdminState>, req: Request, next: Next) -> Response {
+    let presented = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+
+    match presented {
+        Some(token) if token_matches(token, &state.token) => next.run(req).await,
+        _ => (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+            Json(ErrorBody::new("missing or invalid admin token")),
+        )
+            .into_response(),
+    }
+}
+
+/// Accept the admin token or a secret client's, and record who is calling
+async fn require_secret_caller(
+    State(state): State<AdminState>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let presented = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+
+    let caller = match presented {
+        Some(token) if token_matches(token, &state.token) => Some(Caller::Admin),
+        Some(token) => state.vault.client_for_token(token),
+        None => None,
+    };
+    match caller {
+        Some(caller) => {
+            req.extensions_mut().insert(caller);
+            next.run(req).await
+        }
+        None => (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+            Json(ErrorBody::new("missing or invalid token")),
+        )
+            .into_response(),
+    }
+}
+
+/// Accept the admin token or the `[reminders]` client token
+async fn require_reminders_caller(
+    State(state): State<AdminState>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let presented = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+
+    match presented {
+        Some(token) if token_matches(token, &state.token) || state.reminders.accepts(token) => {
+            next.run(req).await
+        }
+        _ => (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+            Json(ErrorBody::new("missing or invalid token")),
+        )
+            .into_response(),
+    }
+}
+
+/// Accept the admin token or the `[sandbox_reports]` token
+async fn require_sandbox_reporter(
+    State(state): State<AdminState>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let presented = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+
+    let reporter = |token: &str| {
+        state
+            .sandbox_token
+            .as_deref()
+            .is_some_and(|expected| token_matches(token, expected))
+    };
+    match presented {
+        Some(token) if token_matches(token, &state.token) || reporter(token) => next.run(req).await,
+        _ => (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+            Json(ErrorBody::new("missing or invalid token")),
+        )
+            .into_response(),
+    }
+}
+
+/// Length-independent comparison so the token can't be guessed byte by byte
+pub(crate) fn token_matches(presented: &str, expected: &str) -> bool {
+    let (a, b) = (presented.as_bytes(), expected.as_bytes());
+    let mut diff = a.len() ^ b.len();
+    for i in 0..a.len().max(b.len()) {
+        let x = a.get(i).copied().unwrap_or(0);
+        let y = b.get(i).copied().unwrap_or(0);
+        diff |= (x ^ y) as usize;
+    }
+    diff == 0
+}
+
+#[derive(Serialize)]
+struct ErrorBody {
+    error: String,
+}
+
+impl ErrorBody {
+    fn new(msg: impl Into<String>) -> Self {
+        Self { error: msg.into() }
+    }
+}
+
+#[derive(Serialize)]
+struct ServiceEntry {
+    name: String,
+    #[serde(flatten)]
+    status: Option<ServiceStatus>,
+    /// "running" unless an operator stopped the service
+    desired: &'static str,
+    log_file: Option<String>,
+}
+
+/// GET /services
+async fn list_services(State(state): State<AdminState>) -> Json<Vec<ServiceEntry>> {
+    let mut services: Vec<ServiceEntry> = state
+        .workers
+        .keys()
+        .map(|name| ServiceEntry {
+            name: name.clone(),
+            status: state.status.get(name).map(|s| s.clone()),
+            desired: state.desired.get(name).as_str(),
+            log_file: state.log_files.get(name).cloned().flatten(),
+        })
+        .collect();
+    services.sort_by(|a, b| a.name.cmp(&b.name));
+    Json(services)
+}
+
+/// POST /services/{name}/start
+async fn start_service(State(state): State<AdminState>, Path(name): Path<String>) -> Response {
+    control(&state, name, Cmd::Start, "start", Desired::Running)
+}
+
+/// POST /services/{name}/stop – stays stopped across daemon restarts
+async fn stop_service(State(state): State<AdminState>, Path(name): Path<String>) -> Response {
+    control(&state, name, Cmd::Stop, "stop", Desired::Stopped)
+}
+
+/// POST /services/{name}/restart
+async fn restart_service(State(state): State<AdminState>, Path(name): Path<String>) -> Response {
+    control(&state, name, Cmd::Restart, "restart", Desired::Running)
+}
+
+/// Record the operator's intent, then queue the command for the worker
+fn control(state: &AdminState, name: String, cmd: Cmd, verb: &str, desired: Desired) -> Response {
+    let Some(tx) = state.workers.get(&name) else {
+        return not_found(&name);
+    };
+    if let Err(e) = state.desired.set(&name, desired) {
+        error!(
+            "Admin API could not persist desired state of {}: {:#}",
+            name, e
+        );
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorBody::new(format!(
+                "could not persist desired state: {}",
+                e
+            ))),
+        )
+            .into_response();
+    }
+    match tx.try_send(cmd) {
+        Ok(()) => {
+            info!("Admin API requested {} of {}", verb, name);
+            (
+                StatusCode::ACCEPTED,
+                Json(serde_json::json!({ "service": name, verb: "requested" })),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!("Admin API could not queue {} of {}: {}", verb, name, e);
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(ErrorBody::new(format!("worker busy or gone: {}", e))),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct UpgradeRequest {
+    binary: String,
+}
+
+/// POST /services/{name}/upgrade  `{"binary": "/path/to/new"}`
+///
+/// Accepted immediately; the outcome is journaled as `upgraded` or
+/// `upgrade_failed` and visible in `/services` once the new pid takes over.
+async fn upgrade_service(
+    State(state): State<AdminState>,
+    Path(name): Path<String>,
+    Json(req): Json<UpgradeRequest>,
+) -> Response {
+    let Some(tx) = state.workers.get(&name) else {
+        return not_found(&name);
+    };
+    if !std::path::Path::new(&req.binary).is_absolute() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorBody::new("binary must be an absolute path")),
+        )
+            .into_response();
+    }
+    match tx.try_send(Cmd::Upgrade {
+        new_binary: req.binary.clone(),
+    }) {
+        Ok(()) => {
+            info!("Admin API requested upgrade of {} to {}", name, req.binary);
+            (
+                StatusCode::ACCEPTED,
+                Json(serde_json::json!({ "service": name, "upgrade": req.binary })),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!("Admin API could not queue upgrade of {}: {}", name, e);
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(ErrorBody::new(format!("worker busy or gone: {}", e))),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct LogsQuery {
+    lines: Option<usize>,
+}
+
+/// GET /services/{name}/logs?lines=N
+async fn service_logs(
+    State(state): State<AdminState>,
+    Path(name): Path<String>,
+    Query(query): Query<LogsQuery>,
+) -> Response {
+    if !state.workers.contains_key(&name) {
+        return not_found(&name);
+    }
+    let Some(path) = state.log_files.get(&name).cloned().flatten() else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(ErrorBody::new(format!("{} has no log file", name))),
+        )
+            .into_response();
+    };
+    let lines = query
+        .lines
+        .unwrap_or(DEFAULT_LOG_LINES)
+        .clamp(1, MAX_LOG_LINES);
+
+    let read_path = path.clone();
+    match tokio::task::spawn_blocking(move || tail_lines(&read_path, lines)).await {
+        Ok(Ok(tail)) => Json(serde_json::json!({
+            "service": name,
+            "log_file": path,
+            "lines": tail,
+        }))
+        .into_response(),
+        Ok(Err(e)) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorBody::new(format!("read {}: {:#}", path, e))),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorBody::new(e.to_string())),
+        )
+            .into_response(),
+    }
+}
+
+/// GET /tools — what every client-config plugin's `detect` reports
+async fn list_tools(State(state): State<AdminState>) -> Json<Vec<PluginDetection>> {
+    Json(state.tools.detect_all().await)
+}
+
+/// POST /tools/rescan — reload plugins from the discovery paths, then detect
+async fn rescan_tools(State(state): State<AdminState>) -> Response {
+    match state.tools.discover_plugins().await {
+        Ok(count) => {
+            info!("Admin API rescanned tool configurators: {} loaded", count);
+            Json(state.tools.detect_all().await).into_response()
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorBody::new(format!("{:#}", e))),
+        )
+            .into_response(),
+    }
+}
+
+/// GET /secrets — secrets the caller may read, redacted
+async fn list_secrets(
+    State(state): State<AdminState>,
+    Extension(caller): Extension<Caller>,
+) -> Json<Vec<SecretView>> {
+    Json(state.vault.list(&caller))
+}
+
+#[derive(Deserialize)]
+struct SecretQuery {
+    #[serde(default)]
+    reveal: bool,
+}
+
+/// GET /secrets/{name}?reveal=true
+///
+/// Redacted unless `reveal` is set and the caller's policy allows it.
+async fn get_secret(
+    State(state): State<AdminState>,
+    Extension(caller): Extension<Caller>,
+    Path(name): Path<String>,
+    Query(query): Query<SecretQuery>,
+) -> Response {
+    let vault = state.vault.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let result = vault.get(&caller, &name, query.reveal);
+        if query.reveal && result.is_ok() {
+            info!("Admin API revealed secret {} to {:?}", name, caller);
+        }
+        result
+    })
+    .await;
+    vault_response(result)
+}
+
+#[derive(Deserialize)]
+struct StoreSecretRequest {
+    value: String,
+    description: Option<String>,
+}
+
+/// PUT /secrets/{name}  `{"value": "...", "description": "..."}`
+async fn store_secret(
+    State(state): State<AdminState>,
+    Extension(caller): Extension<Caller>,
+    Path(name): Path<String>,
+    Json(req): Json<StoreSecretRequest>,
+) -> Response {
+    let vault = state.vault.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let result = vault.store(&caller, &name, &req.value, req.description);
+        if result.is_ok() {
+            info!("Admin API stored secret {} for {:?}", name, caller);
+        }
+        result
+    })
+    .await;
+    vault_response(result)
+}
+
+/// Keychain calls block, so vault results arrive through `spawn_blocking`
+fn vault_response(
+    result: Result<Result<SecretView, VaultError>, tokio::task::JoinError>,
+) -> Response {
+    let error = match result {
+        Ok(Ok(view)) => return Json(view).into_response(),
+        Ok(Err(e)) => e,
+        Err(e) => VaultError::Internal(e.into()),
+    };
+    let status = match &error {
+        VaultError::Forbidden(_) => StatusCode::FORBIDDEN,
+        VaultError::NotFound(_) => StatusCode::NOT_FOUND,
+        VaultError::Invalid(_) => StatusCode::BAD_REQUEST,
+        VaultError::Internal(e) => {
+            error!("Admin API secret request failed: {:#}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    };
+    (status, Json(ErrorBody::new(error.to_string()))).into_response()
+}
+
+/// GET /reminders — every reminder, soonest first
+async fn list_reminders(State(state): State<AdminState>) -> Json<Vec<Reminder>> {
+    Json(state.reminders.list())
+}
+
+/// POST /reminders  `{"message": "...", "when": "in 20m"}` or `{"message": "...", "cron": "0 9 * * mon"}`
+async fn schedule_reminder(
+    State(state): State<AdminState>,
+    Json(req): Json<NewReminder>,
+) -> Response {
+    match state.reminders.schedule(req, Utc::now()) {
+        Ok(reminder) => {
+            info!(
+                "Admin API scheduled reminder {} for {}",
+                reminder.id, reminder.next
+            );
+            (StatusCode::CREATED, Json(reminder)).into_response()
+        }
+        Err(e) => reminder_error(e),
+    }
+}
+
+/// DELETE /reminders/{id}
+async fn cancel_reminder(State(state): State<AdminState>, Path(id): Path<String>) -> Response {
+    match state.reminders.cancel(&id) {
+        Ok(reminder) => Json(reminder).into_response(),
+        Err(e) => reminder_error(e),
+    }
+}
+
+fn reminder_error(error: ReminderError) -> Response {
+    let status = match &error {
+        ReminderError::NotFound(_) => StatusCode::NOT_FOUND,
+        ReminderError::Invalid(_) => StatusCode::BAD_REQUEST,
+        ReminderError::Internal(e) => {
+            error!("Admin API reminder request failed: {:#}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    };
+    (status, Json(ErrorBody::new(error.to_string()))).into_response()
+}
+
+/// Repeated sandbox denials of one client, as counted by the MCP server
+#[derive(Debug, Deserialize)]
+struct SandboxReport {
+    plugin: String,
+    client: String,
+    /// "filesystem" or "network"
+    kind: String,
+    count: u32,
+    window_secs: u64,
+    /// One of the denials, for the journal
+    #[serde(default)]
+    sample: String,
+}
+
+/// Longest sample kept from a report
+const MAX_SAMPLE_CHARS: usize = 200;
+
+/// POST /security/sandbox  `{"plugin": "eval-py", "client": "stdio", "kind": "filesystem", "count": 5, "window_secs": 300}`
+async fn report_sandbox_violation(
+    State(state): State<AdminState>,
+    Json(report): Json<SandboxReport>,
+) -> Response {
+    if report.plugin.trim().is_empty() || report.client.trim().is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorBody::new("plugin and client are required")),
+        )
+            .into_response();
+    }
+    if !matches!(report.kind.as_str(), "filesystem" | "network") {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorBody::new("kind must be filesystem or network")),
+        )
+            .into_response();
+    }
+    let evt = Evt::SandboxViolation {
+        plugin: report.plugin,
+        client: report.client,
+        kind: report.kind,
+        count: report.count,
+        window_secs: report.window_secs,
+        sample: report.sample.chars().take(MAX_SAMPLE_CHARS).collect(),
+        ts: Utc::now(),
+    };
+    match state.events.send(evt) {
+        Ok(()) => (
+            StatusCode::ACCEPTED,
+            Json(serde_json::json!({ "ok": true })),
+        )
+            .into_response(),
+        Err(_) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ErrorBody::new("daemon is shutting down")),
+        )
+            .into_response(),
+    }
+}
+
+fn not_found(name: &str) -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(ErrorBody::new(format!("unknown service {}", name))),
+    )
+        .into_response()
+}
+
+/// Read the last `n` lines of a file, scanning backwards in fixed-size blocks.
+fn tail_lines(path: &str, n: usize) -> Result<Vec<String>> {
+    const BLOCK: u64 = 8 * 1024;
+
+    let mut file = match std::fs::File::open(path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let len = file.metadata()?.len();
+    let mut pos = len;
+    let mut buf: Vec<u8> = Vec::new();
+
+    // Stop once we hold n full lines (n + 1 separators) or reach the start.
+    while pos > 0 && memchr::memchr_iter(b'\n', &buf).count() <= n {
+        let step = BLOCK.min(pos);
+        pos -= step;
+        file.seek(SeekFrom::Start(pos))?;
+        let mut block = vec![0u8; step as usize];
+        file.read_exact(&mut block)?;
+        block.extend_from_slice(&buf);
+        buf = block;
+    }
+
+    let text = String::from_utf8_lossy(&buf);
+    let all: Vec<&str> = text.lines().collect();
+    let skip = all.len().saturating_sub(n);
+    Ok(all[skip..].iter().map(|l| l.to_string()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_comparison() {
+        assert!(token_matches("secret", "secret"));
+        assert!(!token_matches("secreT", "secret"));
+        assert!(!token_matches("secret2", "secret"));
+        assert!(!token_matches("", "secret"));
+    }
+
+    #[test]
+    fn listen_parsing() {
+        assert!(matches!(
+            Listen::parse("127.0.0.1:9000"),
+            Ok(Listen::Tcp(_))
+        ));
+        assert!(Listen::parse("0.0.0.0:9000").is_err());
+        #[cfg(unix)]
+        assert_eq!(
+            Listen::parse("unix:/run/cyrupd/admin.sock").unwrap(),
+            Listen::Unix("/run/cyrupd/admin.sock".into())
+        );
+    }
+
+    #[test]
+    fn tail_returns_last_lines() {
+        let dir = std::env::temp_dir().join(format!("cyrupd-admin-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("svc.log");
+        let body: String = (0..20_000).map(|i| format!("line {}\n", i)).collect();
+        std::fs::write(&path, body).unwrap();
+
+        let tail = tail_lines(path.to_str().unwrap(), 3).unwrap();
+        assert_eq!(tail, vec!["line 19997", "line 19998", "line 19999"]);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -9,17 +9,29 @@ use std::thread;
 use sweetmcp_client_autoconfig::{clients::all_clients, watcher::AutoConfigWatcher};
 use tokio_util::sync::CancellationToken;
 
+/// Comma-separated client ids to configure; all clients when unset
+pub const CLIENTS_ENV: &str = "SWEETMCP_AUTOCONFIG_CLIENTS";
+
 /// Auto-configuration service that watches for MCP client installations
 pub struct AutoConfigService {
     name: String,
     bus: Sender<Evt>,
+    only: Option<Vec<String>>,
 }
 
 impl AutoConfigService {
     pub fn new(def: ServiceDefinition, bus: Sender<Evt>) -> Self {
+        let only = def.env_vars.get(CLIENTS_ENV).map(|ids| {
+            ids.split(',')
+                .map(str::trim)
+                .filter(|id| !id.is_empty())
+                .map(str::to_string)
+                .collect()
+        });
         Self {
             name: def.name,
             bus,
+            only,
         }
     }
 
@@ -33,8 +45,11 @@ impl AutoConfigService {
         let cancel_token = CancellationToken::new();
         let shutdown_complete = Arc::new(AtomicBool::new(false));
 
-        // Create the watcher with all client plugins
-        let clients = all_clients();
+        // Create the watcher with the chosen client plugins
+        let mut clients = all_clients();
+        if let Some(only) = &self.only {
+            clients.retain(|client| only.iter().any(|id| id == client.client_id()));
+        }
         let watcher = AutoConfigWatcher::new(clients)?;
 
         // Spawn the watcher task with graceful cancellation
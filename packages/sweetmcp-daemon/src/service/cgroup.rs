@@ -0,0 +1,147 @@
+//! cgroup v2 containment for supervised children (Linux only).
+//!
+//! Each service with a `resources` section gets its own leaf under
+//! `/sys/fs/cgroup/cyrupd/<service>`. When the unified hierarchy is missing or
+//! not writable (containers, non‑root, other OSes) the limits are skipped with a
+//! warning and the service runs uncontained.
+
+use crate::config::ResourceLimits;
+use anyhow::{bail, Context, Result};
+use std::path::PathBuf;
+
+/// Root of the unified hierarchy.
+#[cfg(target_os = "linux")]
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+/// Parent group for all cyrupd services.
+#[cfg(target_os = "linux")]
+const DAEMON_GROUP: &str = "cyrupd";
+/// Default `cpu.max` period in microseconds.
+const CPU_PERIOD_US: u64 = 100_000;
+
+/// Handle to a service's cgroup leaf.
+pub struct ServiceCgroup {
+    path: PathBuf,
+}
+
+impl ServiceCgroup {
+    /// Create (or reuse) the service's cgroup and apply `limits`.
+    /// Returns `Ok(None)` when cgroup v2 is unavailable on this host.
+    #[cfg(target_os = "linux")]
+    pub fn prepare(service: &str, limits: &ResourceLimits) -> Result<Option<Self>> {
+        use std::fs;
+        use std::path::Path;
+
+        let root = Path::new(CGROUP_ROOT);
+        if !root.join("cgroup.controllers").exists() {
+            return Ok(None);
+        }
+
+        let parent = root.join(DAEMON_GROUP);
+        fs::create_dir_all(&parent)
+            .with_context(|| format!("create cgroup {}", parent.display()))?;
+        // Delegate controllers down the tree; failures here only mean some
+        // limits will not take effect, which we report when writing them.
+        let _ = fs::write(root.join("cgroup.subtree_control"), "+cpu +memory +io");
+        let _ = fs::write(parent.join("cgroup.subtree_control"), "+cpu +memory +io");
+
+        let path = parent.join(service);
+        fs::create_dir_all(&path).with_context(|| format!("create cgroup {}", path.display()))?;
+        let cg = Self { path };
+
+        if let Some(cpu) = &limits.cpu_max {
+            cg.write("cpu.max", &parse_cpu_max(cpu)?)?;
+        }
+        if let Some(mem) = &limits.memory_max {
+            cg.write("memory.max", &parse_memory_max(mem)?)?;
+        }
+        if let Some(weight) = limits.io_weight {
+            if !(1..=10_000).contains(&weight) {
+                bail!("io_weight {} out of range 1-10000", weight);
+            }
+            cg.write("io.weight", &format!("default {}", weight))?;
+        }
+        Ok(Some(cg))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn prepare(_service: &str, _limits: &ResourceLimits) -> Result<Option<Self>> {
+        Ok(None)
+    }
+
+    /// Move a process into this cgroup.
+    pub fn attach(&self, pid: u32) -> Result<()> {
+        self.write("cgroup.procs", &pid.to_string())
+    }
+
+    /// Remove the (now empty) leaf. Best effort.
+    pub fn release(&self) {
+        let _ = std::fs::remove_dir(&self.path);
+    }
+
+    fn write(&self, file: &str, value: &str) -> Result<()> {
+        let target = self.path.join(file);
+        std::fs::write(&target, value)
+            .with_context(|| format!("write {:?} to {}", value, target.display()))
+    }
+}
+
+/// `"150%"` → `"150000 100000"`; raw `"max"` / `"<quota> <period>"` pass through.
+fn parse_cpu_max(spec: &str) -> Result<String> {
+    let spec = spec.trim();
+    if spec == "max" {
+        return Ok(format!("max {}", CPU_PERIOD_US));
+    }
+    if let Some(pct) = spec.strip_suffix('%') {
+        let pct: f64 = pct.trim().parse().context("cpu_max percentage")?;
+        if pct <= 0.0 {
+            bail!("cpu_max must be positive");
+        }
+        let quota = (pct / 100.0 * CPU_PERIOD_US as f64).round() as u64;
+        return Ok(format!("{} {}", quota.max(1000), CPU_PERIOD_US));
+    }
+    let mut parts = spec.split_whitespace();
+    let quota = parts.next().context("empty cpu_max")?;
+    let period = parts.next().unwrap_or("100000");
+    if quota != "max" {
+        quota.parse::<u64>().context("cpu_max quota")?;
+    }
+    period.parse::<u64>().context("cpu_max period")?;
+    Ok(format!("{} {}", quota, period))
+}
+
+/// `"512M"` → `"536870912"`; `"max"` passes through.
+fn parse_memory_max(spec: &str) -> Result<String> {
+    let spec = spec.trim();
+    if spec == "max" {
+        return Ok(spec.to_string());
+    }
+    let (digits, mult) = match spec.chars().last().map(|c| c.to_ascii_uppercase()) {
+        Some('K') => (&spec[..spec.len() - 1], 1u64 << 10),
+        Some('M') => (&spec[..spec.len() - 1], 1u64 << 20),
+        Some('G') => (&spec[..spec.len() - 1], 1u64 << 30),
+        _ => (spec, 1),
+    };
+    let n: u64 = digits.trim().parse().context("memory_max")?;
+    Ok((n * mult).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cpu_max_forms() {
+        assert_eq!(parse_cpu_max("150%").unwrap(), "150000 100000");
+        assert_eq!(parse_cpu_max("max").unwrap(), "max 100000");
+        assert_eq!(parse_cpu_max("20000 50000").unwrap(), "20000 50000");
+        assert!(parse_cpu_max("lots").is_err());
+    }
+
+    #[test]
+    fn memory_max_forms() {
+        assert_eq!(parse_memory_max("512M").unwrap(), "536870912");
+        assert_eq!(parse_memory_max("2g").unwrap(), "2147483648");
+        assert_eq!(parse_memory_max("4096").unwrap(), "4096");
+        assert_eq!(parse_memory_max("max").unwrap(), "max");
+    }
+}
@@ -0,0 +1,104 @@
+//! Blue/green slot switching for services with a `deploy` table.
+//!
+//! Runs from the (synchronous) worker thread: waits for the new slot's
+//! health URL, then asks every configured gateway to replace the old slot's
+//! address with the new one in its peer registry (`/api/register` with
+//! `replaces`), so cluster traffic follows the new instance before the old
+//! one is drained.
+
+use crate::config::DeployConfig;
+use anyhow::{bail, Context, Result};
+use log::{info, warn};
+use std::process::Child;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub struct RegistrySwitch {
+    runtime: tokio::runtime::Runtime,
+    http: reqwest::Client,
+    registries: Vec<String>,
+    token: String,
+}
+
+impl RegistrySwitch {
+    /// `token` is the already resolved discovery token.
+    pub fn new(cfg: &DeployConfig, token: Option<String>) -> Result<Self> {
+        if cfg.slots.len() != 2 {
+            bail!("deploy needs exactly two slots, got {}", cfg.slots.len());
+        }
+        if !cfg.registries.is_empty() && token.is_none() {
+            bail!("deploy registries require a discovery_token");
+        }
+        Ok(Self {
+            runtime: tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .context("build deploy runtime")?,
+            http: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .context("build deploy HTTP client")?,
+            registries: cfg
+                .registries
+                .iter()
+                .map(|r| r.trim_end_matches('/').to_string())
+                .collect(),
+            token: token.unwrap_or_default(),
+        })
+    }
+
+    /// Poll `url` once a second until it answers 2xx, `new` exits or
+    /// `deadline` passes.
+    pub fn wait_healthy(&self, url: &str, deadline: Instant, new: &mut Child) -> Result<()> {
+        loop {
+            if let Some(status) = new.try_wait()? {
+                bail!("new instance exited with {}", status);
+            }
+            let answer = self
+                .runtime
+                .block_on(async { self.http.get(url).send().await });
+            match answer {
+                Ok(resp) if resp.status().is_success() => return Ok(()),
+                Ok(resp) => info!("deploy: {} answered {}", url, resp.status()),
+                Err(e) => info!("deploy: {} not ready: {}", url, e),
+            }
+            if Instant::now() >= deadline {
+                bail!("{} not healthy before the ready timeout", url);
+            }
+            thread::sleep(Duration::from_secs(1));
+        }
+    }
+
+    /// Re-point every registry from `old` to `new`; returns how many agreed.
+    pub fn switch(&self, old: &str, new: &str) -> usize {
+        let body = serde_json::json!({
+            "peer": new,
+            // Not compared on a handoff; the new build may differ by design
+            "build_id": "",
+            "replaces": old,
+        });
+        let mut switched = 0;
+        for registry in &self.registries {
+            let url = format!("{}/api/register", registry);
+            let result = self.runtime.block_on(async {
+                self.http
+                    .post(&url)
+                    .header("x-discovery-token", &self.token)
+                    .json(&body)
+                    .send()
+                    .await
+            });
+            match result {
+                Ok(resp) if resp.status().is_success() => {
+                    info!("deploy: {} now routes {} instead of {}", registry, new, old);
+                    switched += 1;
+                }
+                Ok(resp) => warn!("deploy: {} refused switch: {}", registry, resp.status()),
+                Err(e) => warn!("deploy: {} unreachable: {}", registry, e),
+            }
+        }
+        switched
+    }
+}
@@ -0,0 +1,276 @@
+//! Size‑ and time‑based rotation of service log files.
+//!
+//! Two strategies are supported:
+//! * **copy‑truncate** (default) – the active file is copied aside and truncated
+//!   in place. The child keeps writing to the same descriptor, which is opened
+//!   with `O_APPEND`, so no cooperation is required.
+//! * **rename + SIGHUP** (`signal_child = true`) – the active file is renamed and
+//!   the child is signalled so it can reopen its own log.
+//!
+//! Rotated files are optionally compressed (gzip or zstd) and pruned down to
+//! `max_files`.
+
+use crate::config::{LogCompression, LogRotationConfig};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+const BYTES_PER_MB: u64 = 1024 * 1024;
+const SECS_PER_DAY: i64 = 86_400;
+
+/// Outcome of a rotation pass.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Rotation {
+    /// Nothing to do yet.
+    Skipped,
+    /// The log was rotated into the given file.
+    Rotated(PathBuf),
+}
+
+/// Per‑service rotation state.
+pub struct LogRotator {
+    path: PathBuf,
+    cfg: LogRotationConfig,
+    last_rotation: DateTime<Utc>,
+}
+
+impl LogRotator {
+    pub fn new(path: impl Into<PathBuf>, cfg: LogRotationConfig) -> Self {
+        Self {
+            path: path.into(),
+            cfg,
+            last_rotation: Utc::now(),
+        }
+    }
+
+    /// Whether rotation should request the child to reopen its log.
+    #[inline]
+    pub fn signals_child(&self) -> bool {
+        self.cfg.signal_child
+    }
+
+    /// Rotate when the size or age threshold has been crossed.
+    pub fn rotate_if_due(&mut self, now: DateTime<Utc>) -> Result<Rotation> {
+        let size = match fs::metadata(&self.path) {
+            Ok(meta) => meta.len(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Rotation::Skipped),
+            Err(e) => return Err(e).with_context(|| format!("stat {}", self.path.display())),
+        };
+        if size == 0 {
+            return Ok(Rotation::Skipped);
+        }
+
+        let size_due = self.cfg.max_size_mb > 0 && size >= self.cfg.max_size_mb * BYTES_PER_MB;
+        let age_due = self.cfg.interval_days > 0
+            && (now - self.last_rotation).num_seconds()
+                >= i64::from(self.cfg.interval_days) * SECS_PER_DAY;
+
+        if !size_due && !age_due {
+            return Ok(Rotation::Skipped);
+        }
+        self.rotate(now).map(Rotation::Rotated)
+    }
+
+    /// Unconditionally rotate the active log.
+    pub fn rotate(&mut self, now: DateTime<Utc>) -> Result<PathBuf> {
+        let staged = if self.cfg.timestamp {
+            self.sibling(&now.format("%Y%m%d-%H%M%S").to_string())
+        } else {
+            self.shift_numbered()?;
+            self.sibling("1")
+        };
+
+        if self.cfg.signal_child {
+            fs::rename(&self.path, &staged)
+                .with_context(|| format!("rename {}", self.path.display()))?;
+            File::create(&self.path).with_context(|| format!("create {}", self.path.display()))?;
+        } else {
+            fs::copy(&self.path, &staged)
+                .with_context(|| format!("copy {}", self.path.display()))?;
+            OpenOptions::new()
+                .write(true)
+                .truncate(true)
+                .open(&self.path)
+                .with_context(|| format!("truncate {}", self.path.display()))?;
+        }
+
+        let rotated = if self.cfg.compress {
+            compress(&staged, self.cfg.compression)?
+        } else {
+            staged
+        };
+
+        self.last_rotation = now;
+        self.prune()?;
+        Ok(rotated)
+    }
+
+    /// `app.log` + `3` → `app.log.3`
+    fn sibling(&self, suffix: &str) -> PathBuf {
+        let mut name = self.path.as_os_str().to_owned();
+        name.push(".");
+        name.push(suffix);
+        PathBuf::from(name)
+    }
+
+    /// Move `app.log.N[.gz]` → `app.log.N+1[.gz]`, highest first.
+    fn shift_numbered(&self) -> Result<()> {
+        let mut numbered: Vec<(u32, PathBuf)> = self
+            .rotated_files()?
+            .into_iter()
+            .filter_map(|p| sequence_of(&self.path, &p).map(|n| (n, p)))
+            .collect();
+        numbered.sort_by(|a, b| b.0.cmp(&a.0));
+
+        for (n, path) in numbered {
+            let tail = path
+                .file_name()
+                .and_then(|f| f.to_str())
+                .and_then(|f| f.rsplit_once(&format!(".{}", n)))
+                .map(|(_, ext)| ext.to_string())
+                .unwrap_or_default();
+            let target = self.sibling(&format!("{}{}", n + 1, tail));
+            fs::rename(&path, &target).with_context(|| format!("rename {}", path.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Delete the oldest rotated files beyond `max_files`.
+    fn prune(&self) -> Result<()> {
+        let mut rotated = self.rotated_files()?;
+        // Newest first: low sequence numbers, or lexically greatest timestamps.
+        if self.cfg.timestamp {
+            rotated.sort_by(|a, b| b.cmp(a));
+        } else {
+            rotated.sort_by_key(|p| sequence_of(&self.path, p).unwrap_or(u32::MAX));
+        }
+
+        for path in rotated.into_iter().skip(self.cfg.max_files as usize) {
+            fs::remove_file(&path).with_context(|| format!("remove {}", path.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Every `app.log.*` file next to the active log.
+    fn rotated_files(&self) -> Result<Vec<PathBuf>> {
+        let dir = match self.path.parent() {
+            Some(d) if !d.as_os_str().is_empty() => d,
+            _ => Path::new("."),
+        };
+        let Some(base) = self.path.file_name().and_then(|f| f.to_str()) else {
+            return Ok(Vec::new());
+        };
+        let prefix = format!("{}.", base);
+
+        let mut files = Vec::new();
+        for entry in fs::read_dir(dir).with_context(|| format!("read {}", dir.display()))? {
+            let entry = entry?;
+            if entry
+                .file_name()
+                .to_str()
+                .is_some_and(|n| n.starts_with(&prefix))
+            {
+                files.push(entry.path());
+            }
+        }
+        Ok(files)
+    }
+}
+
+/// Parse `N` out of `app.log.N` / `app.log.N.gz` / `app.log.N.zst`.
+fn sequence_of(active: &Path, rotated: &Path) -> Option<u32> {
+    let base = active.file_name()?.to_str()?;
+    let name = rotated.file_name()?.to_str()?;
+    let rest = name.strip_prefix(base)?.strip_prefix('.')?;
+    rest.split('.').next()?.parse().ok()
+}
+
+/// Compress `path` in place, returning the compressed file's path.
+fn compress(path: &Path, codec: LogCompression) -> Result<PathBuf> {
+    let ext = match codec {
+        LogCompression::Gzip => "gz",
+        LogCompression::Zstd => "zst",
+    };
+    let mut target = path.as_os_str().to_owned();
+    target.push(".");
+    target.push(ext);
+    let target = PathBuf::from(target);
+
+    let mut input = BufReader::new(File::open(path)?);
+    let output = BufWriter::new(File::create(&target)?);
+    match codec {
+        LogCompression::Gzip => {
+            let mut enc = flate2::write::GzEncoder::new(output, flate2::Compression::default());
+            io::copy(&mut input, &mut enc)?;
+            enc.finish()?;
+        }
+        LogCompression::Zstd => {
+            let mut enc = zstd::stream::write::Encoder::new(output, 0)?;
+            io::copy(&mut input, &mut enc)?;
+            enc.finish()?;
+        }
+    }
+
+    fs::remove_file(path).with_context(|| format!("remove {}", path.display()))?;
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cfg(max_files: u32, compress: bool) -> LogRotationConfig {
+        LogRotationConfig {
+            max_size_mb: 1,
+            max_files,
+            interval_days: 0,
+            compress,
+            timestamp: false,
+            compression: LogCompression::Gzip,
+            signal_child: false,
+        }
+    }
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("cyrupd-rotate-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("svc.log")
+    }
+
+    #[test]
+    fn numbered_rotation_shifts_and_prunes() {
+        let log = scratch("numbered");
+        let mut rotator = LogRotator::new(&log, cfg(2, false));
+
+        for i in 0..3 {
+            fs::write(&log, format!("gen {}", i)).unwrap();
+            rotator.rotate(Utc::now()).unwrap();
+        }
+
+        assert_eq!(fs::read_to_string(rotator.sibling("1")).unwrap(), "gen 2");
+        assert_eq!(fs::read_to_string(rotator.sibling("2")).unwrap(), "gen 1");
+        assert!(!rotator.sibling("3").exists());
+        assert_eq!(fs::metadata(&log).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn below_threshold_is_skipped() {
+        let log = scratch("skip");
+        fs::write(&log, "small").unwrap();
+        let mut rotator = LogRotator::new(&log, cfg(3, true));
+        assert_eq!(rotator.rotate_if_due(Utc::now()).unwrap(), Rotation::Skipped);
+    }
+
+    #[test]
+    fn gzip_output_is_named_and_replaces_plain_file() {
+        let log = scratch("gzip");
+        fs::write(&log, "compress me").unwrap();
+        let mut rotator = LogRotator::new(&log, cfg(3, true));
+        let rotated = rotator.rotate(Utc::now()).unwrap();
+        assert!(rotated.to_string_lossy().ends_with("svc.log.1.gz"));
+        assert!(!rotator.sibling("1").exists());
+    }
+}
@@ -0,0 +1,362 @@
+//! Liveness probes for supervised services.
+//!
+//! Mirrors Kubernetes probes: each probe runs every `interval_secs` with its
+//! own `timeout_secs`, and the service only flips state after
+//! `failure_threshold` consecutive failures (or `success_threshold`
+//! consecutive successes on the way back).
+
+use crate::config::{HealthCheckConfig, HealthProbe};
+use anyhow::{bail, Context, Result};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+use tonic_health::pb::health_check_response::ServingStatus;
+use tonic_health::pb::health_client::HealthClient;
+use tonic_health::pb::HealthCheckRequest;
+
+/// Executes the configured probe from the (synchronous) worker thread.
+pub struct Prober {
+    cfg: HealthCheckConfig,
+    /// Single‑threaded runtime for the HTTP and gRPC probes
+    runtime: Option<tokio::runtime::Runtime>,
+    http: Option<reqwest::Client>,
+}
+
+impl Prober {
+    pub fn new(cfg: HealthCheckConfig) -> Result<Self> {
+        let needs_runtime = matches!(
+            cfg.probe,
+            HealthProbe::Http { .. } | HealthProbe::Grpc { .. }
+        );
+        let runtime = if needs_runtime {
+            Some(
+                tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .context("build probe runtime")?,
+            )
+        } else {
+            None
+        };
+        let http = match cfg.probe {
+            HealthProbe::Http { .. } => Some(
+                reqwest::Client::builder()
+                    .timeout(Duration::from_secs(cfg.timeout_secs.max(1)))
+                    .build()
+                    .context("build probe HTTP client")?,
+            ),
+            _ => None,
+        };
+        Ok(Self { cfg, runtime, http })
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.cfg.interval_secs.max(1))
+    }
+
+    pub fn initial_delay(&self) -> Duration {
+        Duration::from_secs(self.cfg.initial_delay_secs)
+    }
+
+    pub fn thresholds(&self) -> ProbeThresholds {
+        ProbeThresholds::new(self.cfg.failure_threshold, self.cfg.success_threshold)
+    }
+
+    pub fn success_threshold(&self) -> u32 {
+        self.cfg.success_threshold.max(1)
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(self.cfg.timeout_secs.max(1))
+    }
+
+    /// Run the probe once; the error explains why it failed.
+    pub fn probe(&self) -> Result<()> {
+        match &self.cfg.probe {
+            HealthProbe::Exec { command } => probe_exec(command, self.timeout()),
+            HealthProbe::Tcp { addr } => probe_tcp(addr, self.timeout()),
+            HealthProbe::Http {
+                url,
+                expected_status,
+                expected_response,
+            } => {
+                let (Some(rt), Some(client)) = (&self.runtime, &self.http) else {
+                    bail!("HTTP probe not initialised");
+                };
+                rt.block_on(probe_http(
+                    client,
+                    url,
+                    *expected_status,
+                    expected_response.as_deref(),
+                ))
+            }
+            HealthProbe::Grpc { addr, service } => {
+                let Some(rt) = &self.runtime else {
+                    bail!("gRPC probe not initialised");
+                };
+                rt.block_on(probe_grpc(
+                    addr,
+                    service.as_deref().unwrap_or_default(),
+                    self.timeout(),
+                ))
+            }
+        }
+    }
+}
+
+/// Consecutive success / failure bookkeeping.
+#[derive(Debug)]
+pub struct ProbeThresholds {
+    failure_threshold: u32,
+    success_threshold: u32,
+    failures: u32,
+    successes: u32,
+    healthy: bool,
+}
+
+impl ProbeThresholds {
+    pub fn new(failure_threshold: u32, success_threshold: u32) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            success_threshold: success_threshold.max(1),
+            failures: 0,
+            successes: 0,
+            healthy: true,
+        }
+    }
+
+    /// Record one probe result and return the resulting health.
+    pub fn record(&mut self, ok: bool) -> bool {
+        if ok {
+            self.failures = 0;
+            self.successes += 1;
+            if !self.healthy && self.successes >= self.success_threshold {
+                self.healthy = true;
+            }
+        } else {
+            self.successes = 0;
+            self.failures += 1;
+            if self.healthy && self.failures >= self.failure_threshold {
+                self.healthy = false;
+            }
+        }
+        self.healthy
+    }
+
+    /// Start over, e.g. after the service was restarted.
+    pub fn reset(&mut self) {
+        *self = Self::new(self.failure_threshold, self.success_threshold);
+    }
+}
+
+fn probe_exec(command: &str, timeout: Duration) -> Result<()> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("spawn probe `{}`", command))?;
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            if status.success() {
+                return Ok(());
+            }
+            bail!("probe `{}` exited with {}", command, status);
+        }
+        if Instant::now() >= deadline {
+            child.kill().ok();
+            child.wait().ok();
+            bail!("probe `{}` timed out after {:?}", command, timeout);
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
+fn probe_tcp(addr: &str, timeout: Duration) -> Result<()> {
+    let mut last_err = None;
+    for sock in addr
+        .to_socket_addrs()
+        .with_context(|| format!("resolve {}", addr))?
+    {
+        match TcpStream::connect_timeout(&sock, timeout) {
+            Ok(_) => return Ok(()),
+            Err(e) => last_err = Some(e),
+        }
+    }
+    match last_err {
+        Some(e) => Err(e).with_context(|| format!("connect {}", addr)),
+        None => bail!("{} resolved to no addresses", addr),
+    }
+}
+
+async fn probe_http(
+    client: &reqwest::Client,
+    url: &str,
+    expected_status: Option<u16>,
+    expected_response: Option<&str>,
+) -> Result<()> {
+    let resp = client
+        .get(url)
+        .send()
+        .await
+        .with_context(|| format!("GET {}", url))?;
+    let status = resp.status();
+    let status_ok = match expected_status {
+        Some(code) => status.as_u16() == code,
+        None => status.is_success() || status.is_redirection(),
+    };
+    if !status_ok {
+        bail!("GET {} returned {}", url, status);
+    }
+    if let Some(needle) = expected_response {
+        let body = resp.text().await.context("read probe response")?;
+        if !body.contains(needle) {
+            bail!("GET {} response does not contain {:?}", url, needle);
+        }
+    }
+    Ok(())
+}
+
+async fn probe_grpc(addr: &str, service: &str, timeout: Duration) -> Result<()> {
+    let uri = if addr.contains("://") {
+        addr.to_string()
+    } else {
+        format!("http://{}", addr)
+    };
+    let channel = tonic::transport::Endpoint::from_shared(uri)
+        .with_context(|| format!("invalid gRPC address {}", addr))?
+        .connect_timeout(timeout)
+        .timeout(timeout)
+        .connect()
+        .await
+        .with_context(|| format!("connect {}", addr))?;
+    let resp = HealthClient::new(channel)
+        .check(HealthCheckRequest {
+            service: service.to_string(),
+        })
+        .await
+        .with_context(|| format!("grpc.health.v1.Health/Check on {}", addr))?;
+    let status = resp.into_inner().status;
+    if status != ServingStatus::Serving as i32 {
+        let name = ServingStatus::try_from(status)
+            .map(|s| s.as_str_name())
+            .unwrap_or("UNKNOWN");
+        bail!("{} reports {}", addr, name);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    fn config(probe: HealthProbe) -> HealthCheckConfig {
+        HealthCheckConfig {
+            probe,
+            interval_secs: 1,
+            timeout_secs: 1,
+            initial_delay_secs: 0,
+            failure_threshold: 3,
+            success_threshold: 1,
+            on_failure: vec![],
+        }
+    }
+
+    #[test]
+    fn thresholds_debounce_state_changes() {
+        let mut t = ProbeThresholds::new(3, 2);
+        assert!(t.record(false));
+        assert!(t.record(false));
+        assert!(!t.record(false));
+        assert!(!t.record(true));
+        assert!(t.record(true));
+        t.record(false);
+        t.reset();
+        assert!(t.record(false));
+    }
+
+    #[test]
+    fn tcp_probe() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let prober = Prober::new(config(HealthProbe::Tcp { addr: addr.clone() })).unwrap();
+        assert!(prober.probe().is_ok());
+
+        drop(listener);
+        assert!(prober.probe().is_err());
+    }
+
+    #[test]
+    fn exec_probe() {
+        let ok = Prober::new(config(HealthProbe::Exec {
+            command: "true".into(),
+        }))
+        .unwrap();
+        let failing = Prober::new(config(HealthProbe::Exec {
+            command: "exit 3".into(),
+        }))
+        .unwrap();
+        assert!(ok.probe().is_ok());
+        assert!(failing.probe().is_err());
+    }
+
+    #[test]
+    fn parses_probe_variants() {
+        let http: HealthCheckConfig = toml::from_str(
+            r#"
+            check_type = "http"
+            url = "http://127.0.0.1:8080/health"
+            expected_status = 204
+            timeout_secs = 2
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            http.probe,
+            HealthProbe::Http {
+                url: "http://127.0.0.1:8080/health".into(),
+                expected_status: Some(204),
+                expected_response: None,
+            }
+        );
+        assert_eq!(http.failure_threshold, 3);
+
+        let grpc: HealthCheckConfig = toml::from_str(
+            r#"
+            check_type = "grpc"
+            addr = "127.0.0.1:50051"
+            service = "my.Service"
+            "#,
+        )
+        .unwrap();
+        assert!(matches!(
+            grpc.probe,
+            HealthProbe::Grpc {
+                service: Some(_),
+                ..
+            }
+        ));
+
+        // Pre‑probe configs used `target` and `retries`
+        let legacy: HealthCheckConfig = toml::from_str(
+            r#"
+            check_type = "tcp"
+            target = "127.0.0.1:8443"
+            interval_secs = 60
+            retries = 5
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            legacy.probe,
+            HealthProbe::Tcp {
+                addr: "127.0.0.1:8443".into()
+            }
+        );
+        assert_eq!(legacy.failure_threshold, 5);
+    }
+}
@@ -0,0 +1,192 @@
+//! umask, nice level and I/O scheduling class of a service, applied in the
+//! child between fork and exec so the daemon's own settings never change.
+
+use crate::config::ServiceDefinition;
+use anyhow::{bail, Context, Result};
+use log::warn;
+use std::os::unix::process::CommandExt;
+use std::process::Command;
+
+/// I/O scheduling class and level (0 is highest, 7 lowest)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IoPriority {
+    Realtime(u8),
+    BestEffort(u8),
+    Idle,
+}
+
+impl IoPriority {
+    /// Parse `idle`, `best-effort[:N]` or `realtime[:N]` (also `be`/`rt`)
+    pub fn parse(raw: &str) -> Result<Self> {
+        let (class, level) = match raw.trim().split_once(':') {
+            Some((class, level)) => (class, Some(level)),
+            None => (raw.trim(), None),
+        };
+        let level = match level {
+            Some(level) => {
+                let level: u8 = level
+                    .trim()
+                    .parse()
+                    .with_context(|| format!("ionice level '{}'", level))?;
+                if level > 7 {
+                    bail!("ionice level {} must be 0-7", level);
+                }
+                level
+            }
+            None => 4,
+        };
+        match class.to_ascii_lowercase().as_str() {
+            "realtime" | "rt" => Ok(Self::Realtime(level)),
+            "best-effort" | "be" => Ok(Self::BestEffort(level)),
+            "idle" => Ok(Self::Idle),
+            other => bail!(
+                "unknown ionice class '{}' (idle, best-effort or realtime)",
+                other
+            ),
+        }
+    }
+
+    /// `ioprio` value for `ioprio_set(2)`
+    fn value(self) -> libc::c_int {
+        const CLASS_SHIFT: libc::c_int = 13;
+        let (class, level) = match self {
+            Self::Realtime(level) => (1, level),
+            Self::BestEffort(level) => (2, level),
+            Self::Idle => (3, 0),
+        };
+        (class << CLASS_SHIFT) | libc::c_int::from(level)
+    }
+}
+
+/// Parse an octal umask such as `"027"` or `"0o027"`
+pub fn parse_umask(raw: &str) -> Result<libc::mode_t> {
+    let digits = raw.trim().trim_start_matches("0o");
+    let mask = libc::mode_t::from_str_radix(digits, 8)
+        .with_context(|| format!("umask '{}' is not octal", raw))?;
+    if mask > 0o777 {
+        bail!("umask {:o} is larger than 777", mask);
+    }
+    Ok(mask)
+}
+
+/// The attributes a service asks for, validated
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ProcessAttrs {
+    umask: Option<libc::mode_t>,
+    nice: Option<i32>,
+    ionice: Option<IoPriority>,
+}
+
+impl ProcessAttrs {
+    pub fn from_def(def: &ServiceDefinition) -> Result<Self> {
+        let umask = def.umask.as_deref().map(parse_umask).transpose()?;
+        if let Some(nice) = def.nice {
+            if !(-20..=19).contains(&nice) {
+                bail!("nice {} must be between -20 and 19", nice);
+            }
+        }
+        let ionice = def.ionice.as_deref().map(IoPriority::parse).transpose()?;
+        Ok(Self {
+            umask,
+            nice: def.nice,
+            ionice,
+        })
+    }
+
+    /// Have `cmd`'s child set the attributes before exec. A nice level or
+    /// I/O class the kernel refuses fails the spawn rather than silently
+    /// running the service at the wrong priority.
+    pub fn apply(self, name: &str, cmd: &mut Command) {
+        if self == Self::default() {
+            return;
+        }
+        let ionice = match self.ionice {
+            Some(_) if !cfg!(target_os = "linux") => {
+                warn!("{}: ionice is only supported on Linux; ignoring", name);
+                None
+            }
+            ionice => ionice,
+        };
+        let Self { umask, nice, .. } = self;
+
+        // SAFETY: umask, setpriority and the ioprio_set syscall are
+        // async-signal-safe and nothing is allocated between fork and exec.
+        unsafe {
+            cmd.pre_exec(move || {
+                if let Some(mask) = umask {
+                    libc::umask(mask);
+                }
+                if let Some(nice) = nice {
+                    // `who` 0 is the calling process, i.e. the child
+                    if libc::setpriority(libc::PRIO_PROCESS, 0, nice) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+                #[cfg(target_os = "linux")]
+                if let Some(ionice) = ionice {
+                    const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+                    if libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0, ionice.value())
+                        != 0
+                    {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+                #[cfg(not(target_os = "linux"))]
+                let _ = ionice;
+                Ok(())
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Stdio;
+
+    #[test]
+    fn parses_ionice_and_umask() {
+        assert_eq!(IoPriority::parse("idle").unwrap(), IoPriority::Idle);
+        assert_eq!(
+            IoPriority::parse("best-effort:7").unwrap(),
+            IoPriority::BestEffort(7)
+        );
+        assert_eq!(IoPriority::parse("rt").unwrap(), IoPriority::Realtime(4));
+        assert_eq!(IoPriority::BestEffort(7).value(), (2 << 13) | 7);
+        assert!(IoPriority::parse("be:8").is_err());
+        assert!(IoPriority::parse("fast").is_err());
+
+        assert_eq!(parse_umask("027").unwrap(), 0o027);
+        assert_eq!(parse_umask("0o077").unwrap(), 0o077);
+        assert!(parse_umask("089").is_err());
+        assert!(parse_umask("1777").is_err());
+    }
+
+    #[test]
+    fn rejects_out_of_range_nice() {
+        let mut def: ServiceDefinition =
+            toml::from_str("name = \"api\"\ncommand = \"api\"\nnice = 25").unwrap();
+        assert!(ProcessAttrs::from_def(&def).is_err());
+        def.nice = Some(5);
+        assert!(ProcessAttrs::from_def(&def).is_ok());
+    }
+
+    #[test]
+    fn child_gets_umask_and_nice() {
+        let def: ServiceDefinition =
+            toml::from_str("name = \"api\"\ncommand = \"api\"\numask = \"027\"\nnice = 5").unwrap();
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c")
+            .arg("umask; cut -d' ' -f19 /proc/self/stat 2>/dev/null || true")
+            .stdout(Stdio::piped());
+        ProcessAttrs::from_def(&def).unwrap().apply("api", &mut cmd);
+
+        let output = cmd.output().unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut lines = stdout.lines();
+        assert_eq!(lines.next(), Some("0027"));
+        if cfg!(target_os = "linux") {
+            assert_eq!(lines.next(), Some("5"));
+        }
+    }
+}
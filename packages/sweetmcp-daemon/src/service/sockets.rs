@@ -0,0 +1,111 @@
+//! Listening sockets owned by the daemon and inherited by service instances.
+//!
+//! Sockets are bound once and passed to each child as fds 3.. using the
+//! systemd `LISTEN_FDS` protocol. Because the daemon keeps them open, a new
+//! instance can start accepting on the same socket before the old one exits,
+//! which is what makes `Cmd::Upgrade` zero‑downtime.
+
+use anyhow::{Context, Result};
+use std::os::fd::{AsRawFd, OwnedFd, RawFd};
+use std::os::unix::process::CommandExt;
+use std::process::Command;
+
+/// First fd handed to the child, as in `sd_listen_fds(3)`
+const SD_LISTEN_FDS_START: RawFd = 3;
+
+pub struct InheritedSockets {
+    fds: Vec<OwnedFd>,
+    names: Vec<String>,
+}
+
+impl InheritedSockets {
+    /// Bind every spec: `host:port` for TCP, `unix:/path` for a unix socket.
+    pub fn bind(specs: &[String]) -> Result<Self> {
+        let mut fds = Vec::with_capacity(specs.len());
+        let mut names = Vec::with_capacity(specs.len());
+        for spec in specs {
+            let fd: OwnedFd = match spec.strip_prefix("unix:") {
+                Some(path) => {
+                    // A stale socket file from a previous daemon blocks bind()
+                    let _ = std::fs::remove_file(path);
+                    std::os::unix::net::UnixListener::bind(path)
+                        .with_context(|| format!("bind {}", spec))?
+                        .into()
+                }
+                None => std::net::TcpListener::bind(spec.as_str())
+                    .with_context(|| format!("bind {}", spec))?
+                    .into(),
+            };
+            fds.push(fd);
+            names.push(spec.replace(':', "_"));
+        }
+        Ok(Self { fds, names })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fds.is_empty()
+    }
+
+    /// Make the sockets fds 3.. of the child and export `LISTEN_FDS`.
+    ///
+    /// `LISTEN_PID` must name the service process itself, so the shell sets
+    /// it before exec'ing the command (see [`wrap_command`]).
+    pub fn apply(&self, cmd: &mut Command) {
+        if self.fds.is_empty() {
+            return;
+        }
+        cmd.env("LISTEN_FDS", self.fds.len().to_string())
+            .env("LISTEN_FDNAMES", self.names.join(":"));
+
+        let sources: Vec<RawFd> = self.fds.iter().map(|fd| fd.as_raw_fd()).collect();
+        let mut scratch: Vec<RawFd> = vec![-1; sources.len()];
+        let first_free = SD_LISTEN_FDS_START + sources.len() as RawFd;
+        // SAFETY: only async‑signal‑safe calls (fcntl, dup2, close) and no
+        // allocation happen between fork and exec.
+        unsafe {
+            cmd.pre_exec(move || {
+                // Move every source above the target range first so a source
+                // that already sits at 3.. is not clobbered by an earlier dup2.
+                for (tmp, src) in scratch.iter_mut().zip(&sources) {
+                    *tmp = libc::fcntl(*src, libc::F_DUPFD, first_free);
+                    if *tmp < 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+                for (i, tmp) in scratch.iter().enumerate() {
+                    // dup2 clears FD_CLOEXEC on the target
+                    if libc::dup2(*tmp, SD_LISTEN_FDS_START + i as RawFd) < 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    libc::close(*tmp);
+                }
+                Ok(())
+            });
+        }
+    }
+}
+
+/// Prefix the service command so `LISTEN_PID` matches the exec'd process.
+/// In a compound command only the first program sees a matching pid.
+pub fn wrap_command(command: &str) -> String {
+    format!("LISTEN_PID=$$; export LISTEN_PID; exec {}", command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Stdio;
+
+    #[test]
+    fn child_inherits_sockets() {
+        let sockets = InheritedSockets::bind(&["127.0.0.1:0".to_string()]).unwrap();
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c")
+            .arg(wrap_command(
+                r#"sh -c 'test "$LISTEN_FDS" = 1 && test -e /dev/fd/3'"#,
+            ))
+            .stdout(Stdio::null());
+        sockets.apply(&mut cmd);
+        assert!(cmd.status().unwrap().success());
+    }
+}
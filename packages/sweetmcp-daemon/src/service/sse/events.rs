@@ -18,6 +18,8 @@ pub enum EventType {
     Ping,
     /// Error event for protocol or server errors
     Error,
+    /// Daemon lifecycle event (a journal record as JSON)
+    Daemon,
 }
 
 impl fmt::Display for EventType {
@@ -27,6 +29,7 @@ impl fmt::Display for EventType {
             EventType::Message => write!(f, "message"),
             EventType::Ping => write!(f, "ping"),
             EventType::Error => write!(f, "error"),
+            EventType::Daemon => write!(f, "daemon"),
         }
     }
 }
@@ -85,6 +88,11 @@ impl SseEvent {
         Self::new(EventType::Error, error_msg)
     }
 
+    /// Create a daemon event with a serialized journal record
+    pub fn daemon(entry_json: impl Into<String>) -> Self {
+        Self::new(EventType::Daemon, entry_json)
+    }
+
     /// Set the event ID for resumability
     pub fn with_id(mut self, id: impl Into<String>) -> Self {
         self.id = Some(id.into());
@@ -112,6 +120,7 @@ mod tests {
         assert_eq!(EventType::Message.to_string(), "message");
         assert_eq!(EventType::Ping.to_string(), "ping");
         assert_eq!(EventType::Error.to_string(), "error");
+        assert_eq!(EventType::Daemon.to_string(), "daemon");
     }
 
     #[test]
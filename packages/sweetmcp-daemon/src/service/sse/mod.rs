@@ -15,6 +15,7 @@
 //! - `session` - Session management and lifecycle
 //! - `server` - HTTP server with SSE and messages endpoints
 //! - `bridge` - Communication bridge to sweetmcp-axum MCP server
+//! - `subscription` - Per-connection filters for daemon events
 //! - `encoder` - SSE wire format encoding per RFC 6455
 
 pub mod bridge;
@@ -22,16 +23,19 @@ pub mod encoder;
 pub mod events;
 pub mod server;
 pub mod session;
+pub mod subscription;
 
 pub use bridge::McpBridge;
 pub use encoder::SseEncoder;
 pub use events::{EventType, SseEvent};
 pub use server::SseServer;
 pub use session::{SessionManager, SseSession};
+pub use subscription::Subscription;
 
+use crate::journal::JournalEntry;
 use anyhow::Result;
 use std::net::SocketAddr;
-use tokio::sync::oneshot;
+use tokio::sync::{broadcast, oneshot};
 
 /// SSE server configuration
 #[derive(Debug, Clone)]
@@ -48,6 +52,8 @@ pub struct SseConfig {
     pub session_timeout: u64,
     /// CORS allowed origins
     pub cors_origins: Vec<String>,
+    /// Bearer token required to connect; `None` leaves the server open
+    pub token: Option<String>,
 }
 
 impl Default for SseConfig {
@@ -59,13 +65,19 @@ impl Default for SseConfig {
             ping_interval: 30,
             session_timeout: 300,
             cors_origins: vec!["*".to_string()],
+            token: None,
         }
     }
 }
 
-/// Start the SSE server with given configuration
-pub async fn start_sse_server(config: SseConfig, shutdown_rx: oneshot::Receiver<()>) -> Result<()> {
+/// Start the SSE server with given configuration, streaming `events` to
+/// subscribed connections
+pub async fn start_sse_server(
+    config: SseConfig,
+    events: broadcast::Sender<JournalEntry>,
+    shutdown_rx: oneshot::Receiver<()>,
+) -> Result<()> {
     let addr: SocketAddr = ([127, 0, 0, 1], config.port).into();
-    let server = SseServer::new(config);
+    let server = SseServer::new(config).with_events(events);
     server.serve(addr, shutdown_rx).await
 }
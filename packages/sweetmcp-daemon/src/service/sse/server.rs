@@ -3,25 +3,29 @@
 //! Implements the dual-endpoint SSE server with /sse and /messages endpoints
 //! as specified in the MCP SSE transport protocol.
 
+use crate::journal::JournalEntry;
+use crate::service::admin::token_matches;
 use crate::service::sse::{
     bridge::{create_invalid_request_response, validate_json_rpc_request, McpBridge},
     encoder::SseEncoder,
     events::SseEvent,
     session::{ClientInfo, SessionManager},
+    subscription::Subscription,
     SseConfig,
 };
 use anyhow::{Context, Result};
 use axum::{
-    extract::{Query, State},
-    http::{HeaderMap, StatusCode},
-    response::Sse,
+    extract::{Query, Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response, Sse},
     routing::{get, post},
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::{convert::Infallible, net::SocketAddr, sync::Arc, time::Duration};
-use tokio::sync::oneshot;
+use tokio::sync::{broadcast, oneshot};
 use tower::ServiceBuilder;
 use tower_http::{
     cors::{Any, CorsLayer},
@@ -40,6 +44,8 @@ struct ServerState {
     encoder: SseEncoder,
     /// Server configuration
     config: SseConfig,
+    /// Daemon lifecycle events, fanned out to subscribed sessions
+    events: broadcast::Sender<JournalEntry>,
 }
 
 /// Query parameters for the messages endpoint
@@ -48,16 +54,41 @@ struct MessagesQuery {
     session_id: String,
 }
 
+/// Query parameters for the SSE endpoint
+#[derive(Debug, Default, Deserialize)]
+struct SseQuery {
+    /// Comma-separated service names to receive events for
+    services: Option<String>,
+    /// Comma-separated event kinds to receive
+    events: Option<String>,
+}
+
+/// Token passed as `?token=` by clients that cannot set headers (EventSource)
+#[derive(Debug, Deserialize)]
+struct TokenQuery {
+    token: Option<String>,
+}
+
 /// SSE server implementation
 #[derive(Debug)]
 pub struct SseServer {
     config: SseConfig,
+    events: Option<broadcast::Sender<JournalEntry>>,
 }
 
 impl SseServer {
     /// Create a new SSE server with given configuration
     pub fn new(config: SseConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            events: None,
+        }
+    }
+
+    /// Stream daemon events from `events` to connected sessions
+    pub fn with_events(mut self, events: broadcast::Sender<JournalEntry>) -> Self {
+        self.events = Some(events);
+        self
     }
 
     /// Start serving on the given address
@@ -80,8 +111,16 @@ impl SseServer {
             mcp_bridge,
             encoder,
             config: self.config.clone(),
+            events: self
+                .events
+                .clone()
+                .unwrap_or_else(|| broadcast::channel(1).0),
         };
 
+        if self.config.token.is_none() {
+            warn!("SSE server has no token configured; any local client can connect");
+        }
+
         // Start background cleanup task
         let _cleanup_task = session_manager.start_cleanup_task(Duration::from_secs(60));
 
@@ -110,9 +149,14 @@ impl SseServer {
 
     /// Build the axum router with all endpoints
     fn build_router(&self, state: ServerState) -> Router {
-        Router::new()
+        let protected = Router::new()
             .route("/sse", get(handle_sse_endpoint))
             .route("/messages", post(handle_messages_endpoint))
+            .route("/subscriptions", post(handle_subscriptions_endpoint))
+            .route_layer(middleware::from_fn_with_state(state.clone(), require_token));
+
+        Router::new()
+            .merge(protected)
             .route("/health", get(handle_health_endpoint))
             .layer(
                 ServiceBuilder::new()
@@ -148,9 +192,45 @@ impl SseServer {
     }
 }
 
+/// Reject requests without the configured token (when one is configured)
+async fn require_token(State(state): State<ServerState>, req: Request, next: Next) -> Response {
+    let Some(expected) = state.config.token.as_deref() else {
+        return next.run(req).await;
+    };
+
+    let presented = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::to_string)
+        .or_else(|| {
+            req.uri()
+                .query()
+                .and_then(|q| serde_urlencoded::from_str::<TokenQuery>(q).ok())
+                .and_then(|q| q.token)
+        });
+
+    match presented {
+        Some(token) if token_matches(&token, expected) => next.run(req).await,
+        _ => {
+            warn!(
+                "Rejected unauthenticated SSE request to {}",
+                req.uri().path()
+            );
+            (
+                StatusCode::UNAUTHORIZED,
+                [(header::WWW_AUTHENTICATE, "Bearer")],
+            )
+                .into_response()
+        }
+    }
+}
+
 /// Handle GET /sse endpoint - establish SSE connection
 async fn handle_sse_endpoint(
     State(state): State<ServerState>,
+    Query(query): Query<SseQuery>,
     headers: HeaderMap,
 ) -> Result<
     Sse<impl tokio_stream::Stream<Item = Result<axum::response::sse::Event, Infallible>>>,
@@ -187,6 +267,12 @@ async fn handle_sse_endpoint(
         }
     };
 
+    let subscription = Subscription::from_query(query.services.as_deref(), query.events.as_deref());
+    state
+        .session_manager
+        .set_subscription(&session.id, subscription)
+        .await;
+
     info!("Established SSE connection for session {}", session.id);
 
     // Create event stream using futures stream
@@ -234,9 +320,15 @@ async fn handle_sse_endpoint(
         })
     };
 
-    // Combine initial event with ping stream
-    let combined_stream =
-        stream::once(async { Ok::<_, Infallible>(initial_event) }).chain(ping_stream);
+    let daemon_stream = daemon_event_stream(
+        state.events.subscribe(),
+        session_id.clone(),
+        session_manager,
+    );
+
+    // Initial event, then pings interleaved with daemon events
+    let combined_stream = stream::once(async { Ok::<_, Infallible>(initial_event) })
+        .chain(stream::select(ping_stream, daemon_stream));
 
     Ok(Sse::new(combined_stream).keep_alive(
         axum::response::sse::KeepAlive::new()
@@ -245,6 +337,68 @@ async fn handle_sse_endpoint(
     ))
 }
 
+/// Daemon events for one session, filtered by its current subscription
+fn daemon_event_stream(
+    rx: broadcast::Receiver<JournalEntry>,
+    session_id: String,
+    session_manager: Arc<SessionManager>,
+) -> impl tokio_stream::Stream<Item = Result<axum::response::sse::Event, Infallible>> {
+    futures_util::stream::unfold(rx, move |mut rx| {
+        let session_id = session_id.clone();
+        let session_manager = session_manager.clone();
+
+        async move {
+            loop {
+                let event = match rx.recv().await {
+                    Ok(entry) => {
+                        let session = session_manager.get_session(&session_id).await?;
+                        if !session.subscription.matches(&entry) {
+                            continue;
+                        }
+                        let Ok(data) = serde_json::to_string(&entry) else {
+                            continue;
+                        };
+                        SseEvent::daemon(data)
+                    }
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        SseEvent::error(format!("{} daemon events dropped", missed))
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                };
+                return Some((Ok(to_axum_event(event)), rx));
+            }
+        }
+    })
+}
+
+fn to_axum_event(event: SseEvent) -> axum::response::sse::Event {
+    let mut out = axum::response::sse::Event::default().data(event.data);
+    if let Some(kind) = event.event_type {
+        out = out.event(kind.to_string());
+    }
+    if let Some(id) = event.id {
+        out = out.id(id);
+    }
+    out
+}
+
+/// Handle POST /subscriptions endpoint - change which daemon events a session receives
+async fn handle_subscriptions_endpoint(
+    State(state): State<ServerState>,
+    Query(query): Query<MessagesQuery>,
+    Json(subscription): Json<Subscription>,
+) -> StatusCode {
+    if state
+        .session_manager
+        .set_subscription(&query.session_id, subscription)
+        .await
+    {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
 /// Handle POST /messages endpoint - process JSON-RPC requests
 async fn handle_messages_endpoint(
     State(state): State<ServerState>,
@@ -360,8 +514,60 @@ mod tests {
             mcp_bridge,
             encoder,
             config,
+            events: broadcast::channel(1).0,
         };
 
         assert_eq!(state.config.port, 8080);
     }
+
+    #[tokio::test]
+    async fn token_guards_everything_but_health() {
+        use axum::body::Body;
+        use tower::ServiceExt;
+
+        let config = SseConfig {
+            token: Some("s3cret".to_string()),
+            ..Default::default()
+        };
+        let server = SseServer::new(config.clone());
+        let app = server.build_router(ServerState {
+            session_manager: Arc::new(SessionManager::default()),
+            mcp_bridge: Arc::new(
+                McpBridge::new("http://127.0.0.1:9".to_string(), Duration::from_secs(1)).unwrap(),
+            ),
+            encoder: SseEncoder::new(),
+            config,
+            events: broadcast::channel(1).0,
+        });
+
+        let status = |uri: &str, auth: Option<&str>| {
+            let mut req = axum::http::Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header(header::CONTENT_TYPE, "application/json");
+            if let Some(auth) = auth {
+                req = req.header(header::AUTHORIZATION, auth);
+            }
+            let app = app.clone();
+            let req = req.body(Body::from("{}")).unwrap();
+            async move { app.oneshot(req).await.unwrap().status() }
+        };
+
+        assert_eq!(
+            status("/subscriptions?session_id=x", None).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            status("/subscriptions?session_id=x", Some("Bearer wrong")).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            status("/subscriptions?session_id=x", Some("Bearer s3cret")).await,
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            status("/subscriptions?session_id=x&token=s3cret", None).await,
+            StatusCode::NOT_FOUND
+        );
+    }
 }
@@ -3,6 +3,7 @@
 //! Handles session lifecycle, tracking, and cleanup for SSE connections.
 //! Provides thread-safe session storage and automatic timeout management.
 
+use super::subscription::Subscription;
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -22,6 +23,8 @@ pub struct SseSession {
     pub last_activity: Instant,
     /// Client connection information
     pub client_info: ClientInfo,
+    /// Daemon events this connection receives
+    pub subscription: Subscription,
 }
 
 /// Client connection information
@@ -44,6 +47,7 @@ impl SseSession {
             created_at: now,
             last_activity: now,
             client_info,
+            subscription: Subscription::default(),
         }
     }
 
@@ -137,6 +141,18 @@ impl SessionManager {
         }
     }
 
+    /// Replace a session's event subscription
+    pub async fn set_subscription(&self, session_id: &str, subscription: Subscription) -> bool {
+        let mut sessions = self.sessions.write().await;
+        if let Some(session) = sessions.get_mut(session_id) {
+            debug!("Session {} subscribed to {:?}", session_id, subscription);
+            session.subscription = subscription;
+            true
+        } else {
+            false
+        }
+    }
+
     /// Remove a session
     pub async fn remove_session(&self, session_id: &str) -> bool {
         let mut sessions = self.sessions.write().await;
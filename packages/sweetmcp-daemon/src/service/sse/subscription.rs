@@ -0,0 +1,76 @@
+//! Per-connection topic subscriptions
+//!
+//! Every SSE connection receives daemon lifecycle events (the same records the
+//! event journal keeps). A subscription narrows them down by service name and
+//! event kind; an empty set matches everything.
+
+use crate::journal::JournalEntry;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Which daemon events a connection wants
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Subscription {
+    /// Service names; empty means all services
+    #[serde(default)]
+    pub services: BTreeSet<String>,
+    /// Event kinds such as `running`, `crashed` or `unhealthy`; empty means all
+    #[serde(default)]
+    pub events: BTreeSet<String>,
+}
+
+impl Subscription {
+    /// Build from comma-separated `services` / `events` query values
+    pub fn from_query(services: Option<&str>, events: Option<&str>) -> Self {
+        Self {
+            services: split_list(services),
+            events: split_list(events),
+        }
+    }
+
+    /// Whether `entry` should be delivered to this connection
+    pub fn matches(&self, entry: &JournalEntry) -> bool {
+        (self.services.is_empty() || self.services.contains(&entry.service))
+            && (self.events.is_empty() || self.events.contains(&entry.kind))
+    }
+}
+
+fn split_list(value: Option<&str>) -> BTreeSet<String> {
+    value
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(service: &str, kind: &str) -> JournalEntry {
+        JournalEntry {
+            ts: chrono::Utc::now(),
+            service: service.to_string(),
+            kind: kind.to_string(),
+            pid: None,
+            detail: None,
+        }
+    }
+
+    #[test]
+    fn filters_by_service_and_kind() {
+        let all = Subscription::default();
+        assert!(all.matches(&entry("gw", "running")));
+
+        let sub = Subscription::from_query(Some("gw, api,"), Some("crashed"));
+        assert_eq!(sub.services.len(), 2);
+        assert!(sub.matches(&entry("api", "crashed")));
+        assert!(!sub.matches(&entry("api", "running")));
+        assert!(!sub.matches(&entry("db", "crashed")));
+
+        let by_kind = Subscription::from_query(None, Some("unhealthy"));
+        assert!(by_kind.matches(&entry("db", "unhealthy")));
+    }
+}
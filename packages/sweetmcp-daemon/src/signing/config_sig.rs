@@ -0,0 +1,214 @@
+//! Detached GPG signatures for `cyrupd.toml`
+//!
+//! The signature lives next to the config as `<config>.sig` (ASCII armored,
+//! as produced by `gpg --detach-sign --armor`). With a trusted public key the
+//! signature is checked in a throwaway keyring holding only that key, so keys
+//! in the operator's own keyring cannot vouch for the config.
+
+use anyhow::{bail, Context, Result};
+use log::{info, warn};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use which::which;
+
+/// Forces `require_signed_config` regardless of what the config file says
+pub const REQUIRE_ENV: &str = "CYRUPD_REQUIRE_SIGNED_CONFIG";
+
+/// Overrides `config_signing_key` regardless of what the config file says
+pub const KEY_ENV: &str = "CYRUPD_CONFIG_SIGNING_KEY";
+
+/// Result of checking a config file against its detached signature
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSignature {
+    /// Signature is good; holds gpg's description of the signer
+    Verified(String),
+    /// No `<config>.sig` next to the config
+    Unsigned,
+    /// A signature exists but does not match, or its key is not trusted
+    Invalid(String),
+}
+
+/// `/etc/cyrupd/cyrupd.toml` -> `/etc/cyrupd/cyrupd.toml.sig`
+pub fn signature_path(config: &Path) -> PathBuf {
+    let mut name = config.file_name().unwrap_or_default().to_os_string();
+    name.push(".sig");
+    config.with_file_name(name)
+}
+
+/// Check `config` against `<config>.sig`, optionally trusting only the
+/// public key exported to `trusted_key`.
+pub fn verify(config: &Path, trusted_key: Option<&Path>) -> Result<ConfigSignature> {
+    let sig = signature_path(config);
+    if !sig.exists() {
+        return Ok(ConfigSignature::Unsigned);
+    }
+    let gpg = find_gpg()?;
+
+    let homedir = match trusted_key {
+        Some(key) => Some(TempKeyring::import(&gpg, key)?),
+        None => None,
+    };
+    let mut cmd = Command::new(&gpg);
+    if let Some(home) = &homedir {
+        cmd.arg("--homedir").arg(&home.0);
+    }
+    let output = cmd
+        .args(["--batch", "--status-fd", "1", "--verify"])
+        .arg(&sig)
+        .arg(config)
+        .output()
+        .context("Failed to execute GPG verify")?;
+
+    let status = String::from_utf8_lossy(&output.stdout);
+    let signer = status
+        .lines()
+        .find_map(|line| line.strip_prefix("[GNUPG:] GOODSIG "))
+        .map(str::to_string);
+    Ok(match signer {
+        Some(signer) if output.status.success() => ConfigSignature::Verified(signer),
+        _ => ConfigSignature::Invalid(
+            String::from_utf8_lossy(&output.stderr)
+                .lines()
+                .last()
+                .unwrap_or("signature verification failed")
+                .to_string(),
+        ),
+    })
+}
+
+/// Apply the signing policy before any service is started.
+///
+/// A bad signature is always fatal when it can be checked; a missing one only
+/// when signing is required. The environment overrides win over the config
+/// file, since a tampered file could simply switch the requirement off.
+pub fn enforce(config: &Path, require: bool, trusted_key: Option<&str>) -> Result<()> {
+    let require = require
+        || std::env::var(REQUIRE_ENV)
+            .map(|v| matches!(v.as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+    let key = std::env::var(KEY_ENV)
+        .ok()
+        .or_else(|| trusted_key.map(str::to_string));
+
+    match verify(config, key.as_deref().map(Path::new)) {
+        Ok(ConfigSignature::Verified(signer)) => {
+            info!("Config signature OK: {}", signer);
+            Ok(())
+        }
+        Ok(ConfigSignature::Unsigned) if require => bail!(
+            "signed config required but {} is missing",
+            signature_path(config).display()
+        ),
+        Ok(ConfigSignature::Unsigned) => Ok(()),
+        Ok(ConfigSignature::Invalid(reason)) => bail!(
+            "refusing to start: {} does not match its signature ({})",
+            config.display(),
+            reason
+        ),
+        Err(e) if require => Err(e.context("cannot verify config signature")),
+        Err(e) => {
+            warn!("Skipping config signature check: {:#}", e);
+            Ok(())
+        }
+    }
+}
+
+/// Write `<config>.sig` with the given (or default) GPG key.
+pub fn sign(config: &Path, key_id: Option<&str>) -> Result<PathBuf> {
+    let gpg = find_gpg()?;
+    let sig = signature_path(config);
+    let mut cmd = Command::new(&gpg);
+    cmd.args(["--batch", "--yes", "--detach-sign", "--armor"]);
+    if let Some(key) = key_id {
+        cmd.args(["--local-user", key]);
+    }
+    let output = cmd
+        .arg("--output")
+        .arg(&sig)
+        .arg(config)
+        .output()
+        .context("Failed to execute GPG")?;
+    if !output.status.success() {
+        bail!(
+            "GPG signing failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(sig)
+}
+
+/// A private GPG home holding only the trusted key, removed on drop
+struct TempKeyring(PathBuf);
+
+impl TempKeyring {
+    fn import(gpg: &Path, key: &Path) -> Result<Self> {
+        let dir = std::env::temp_dir().join(format!(
+            "cyrupd-gpg-{}-{}",
+            std::process::id(),
+            fastrand::u32(..)
+        ));
+        let mut builder = std::fs::DirBuilder::new();
+        #[cfg(unix)]
+        std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+        builder
+            .create(&dir)
+            .with_context(|| format!("create {}", dir.display()))?;
+        let keyring = Self(dir);
+
+        let output = Command::new(gpg)
+            .arg("--homedir")
+            .arg(&keyring.0)
+            .args(["--batch", "--import"])
+            .arg(key)
+            .output()
+            .context("Failed to execute GPG import")?;
+        if !output.status.success() {
+            bail!(
+                "cannot import trusted key {}: {}",
+                key.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(keyring)
+    }
+}
+
+impl Drop for TempKeyring {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+fn find_gpg() -> Result<PathBuf> {
+    which("gpg2")
+        .or_else(|_| which("gpg"))
+        .map_err(|_| anyhow::anyhow!("GPG not found. Please install gpg or gpg2"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_sits_next_to_config() {
+        assert_eq!(
+            signature_path(Path::new("/etc/cyrupd/cyrupd.toml")),
+            PathBuf::from("/etc/cyrupd/cyrupd.toml.sig")
+        );
+    }
+
+    #[test]
+    fn missing_signature_is_unsigned() {
+        let config =
+            std::env::temp_dir().join(format!("cyrupd-unsigned-{}.toml", std::process::id()));
+        assert_eq!(verify(&config, None).unwrap(), ConfigSignature::Unsigned);
+    }
+
+    #[test]
+    fn unsigned_config_only_fails_when_required() {
+        let config =
+            std::env::temp_dir().join(format!("cyrupd-policy-{}.toml", std::process::id()));
+        assert!(enforce(&config, false, None).is_ok());
+        assert!(enforce(&config, true, None).is_err());
+    }
+}
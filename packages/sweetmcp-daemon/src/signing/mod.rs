@@ -15,6 +15,7 @@ pub mod macos;
 mod windows;
 
 pub mod config;
+pub mod config_sig;
 
 /// Signing configuration for the daemon
 #[derive(Debug, Clone)]
@@ -0,0 +1,179 @@
+//! `${VAR}` interpolation in service definitions.
+//!
+//! `command`, `working_dir` and `env_vars` values may reference variables as
+//! `${NAME}` or `${NAME:-fallback}`; `$$` is a literal `$`. A name is looked
+//! up, in order, among
+//!
+//! 1. built-ins: `HOME`, `SERVICE_NAME`, `DATA_DIR` (`<data dir>/cyrupd`) and
+//!    `LOG_DIR` (when the daemon has a `log_dir`),
+//! 2. the daemon's own environment,
+//! 3. config-defined vars: the service's `vars`, then the daemon's `[vars]`,
+//!
+//! and the first hit wins, so config vars act as per-file defaults that a
+//! machine's environment can override. An unresolved reference without a
+//! fallback keeps the service from loading.
+
+use crate::config::{ServiceConfig, ServiceDefinition};
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+
+/// Variables visible to one service's definition.
+pub struct Scope<'a> {
+    builtins: HashMap<&'static str, String>,
+    env: &'a dyn Fn(&str) -> Option<String>,
+    vars: [&'a HashMap<String, String>; 2],
+}
+
+impl<'a> Scope<'a> {
+    pub fn new(
+        def: &'a ServiceDefinition,
+        cfg: &'a ServiceConfig,
+        env: &'a dyn Fn(&str) -> Option<String>,
+    ) -> Self {
+        let mut builtins = HashMap::new();
+        builtins.insert("SERVICE_NAME", def.name.clone());
+        if let Some(home) = dirs::home_dir() {
+            builtins.insert("HOME", home.to_string_lossy().into_owned());
+        }
+        if let Some(data) = dirs::data_local_dir() {
+            builtins.insert(
+                "DATA_DIR",
+                data.join("cyrupd").to_string_lossy().into_owned(),
+            );
+        }
+        if let Some(log_dir) = &cfg.log_dir {
+            builtins.insert("LOG_DIR", log_dir.clone());
+        }
+        Self {
+            builtins,
+            env,
+            vars: [&def.vars, &cfg.vars],
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<String> {
+        if let Some(value) = self.builtins.get(name) {
+            return Some(value.clone());
+        }
+        if let Some(value) = (self.env)(name) {
+            return Some(value);
+        }
+        self.vars.iter().find_map(|vars| vars.get(name).cloned())
+    }
+
+    /// Expand every reference in `input`.
+    pub fn expand(&self, input: &str) -> Result<String> {
+        let mut out = String::with_capacity(input.len());
+        let mut rest = input;
+        while let Some(pos) = rest.find('$') {
+            out.push_str(&rest[..pos]);
+            rest = &rest[pos + 1..];
+            if let Some(after) = rest.strip_prefix('$') {
+                out.push('$');
+                rest = after;
+                continue;
+            }
+            let Some(body) = rest.strip_prefix('{') else {
+                // A bare `$NAME` is left for the shell
+                out.push('$');
+                continue;
+            };
+            let end = body
+                .find('}')
+                .with_context(|| format!("unterminated ${{ in {:?}", input))?;
+            let (name, fallback) = match body[..end].split_once(":-") {
+                Some((name, fallback)) => (name, Some(fallback)),
+                None => (&body[..end], None),
+            };
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                bail!("invalid variable name {:?} in {:?}", name, input);
+            }
+            match (self.lookup(name), fallback) {
+                (Some(value), _) => out.push_str(&value),
+                (None, Some(fallback)) => out.push_str(fallback),
+                (None, None) => bail!("${{{}}} is not defined", name),
+            }
+            rest = &body[end + 1..];
+        }
+        out.push_str(rest);
+        Ok(out)
+    }
+}
+
+/// Expand `command`, `working_dir` and `env_vars` of `def`.
+pub fn expand_definition(
+    mut def: ServiceDefinition,
+    cfg: &ServiceConfig,
+) -> Result<ServiceDefinition> {
+    let env = |name: &str| std::env::var(name).ok();
+    let scope = Scope::new(&def, cfg, &env);
+
+    let command = scope.expand(&def.command).context("command")?;
+    let working_dir = def
+        .working_dir
+        .as_deref()
+        .map(|dir| scope.expand(dir).context("working_dir"))
+        .transpose()?;
+    let mut env_vars = HashMap::with_capacity(def.env_vars.len());
+    for (key, value) in &def.env_vars {
+        let value = scope
+            .expand(value)
+            .with_context(|| format!("env_vars.{}", key))?;
+        env_vars.insert(key.clone(), value);
+    }
+
+    def.command = command;
+    def.working_dir = working_dir;
+    def.env_vars = env_vars;
+    Ok(def)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn def(vars: &[(&str, &str)]) -> ServiceDefinition {
+        let mut def: ServiceDefinition =
+            toml::from_str("name = \"api\"\ncommand = \"api\"").unwrap();
+        def.vars = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        def
+    }
+
+    #[test]
+    fn resolution_order_and_syntax() {
+        let def = def(&[("PORT", "8080"), ("REGION", "local")]);
+        let mut cfg = ServiceConfig::default();
+        cfg.vars.insert("REGION".into(), "global".into());
+        cfg.vars.insert("TIER".into(), "dev".into());
+        let env = |name: &str| (name == "PORT").then(|| "9090".to_string());
+        let scope = Scope::new(&def, &cfg, &env);
+
+        assert_eq!(scope.expand("${SERVICE_NAME}:${PORT}").unwrap(), "api:9090");
+        assert_eq!(scope.expand("${REGION}/${TIER}").unwrap(), "local/dev");
+        assert_eq!(scope.expand("${LOG_DIR}").unwrap(), "/var/log/cyrupd");
+        assert_eq!(scope.expand("${MISSING:-none}").unwrap(), "none");
+        assert_eq!(scope.expand("$$HOME and $PATH").unwrap(), "$HOME and $PATH");
+        assert!(scope.expand("${MISSING}").is_err());
+        assert!(scope.expand("${BAD NAME}").is_err());
+        assert!(scope.expand("${OPEN").is_err());
+    }
+
+    #[test]
+    fn definition_fields_are_expanded() {
+        let mut def = def(&[("ROOT", "/srv")]);
+        def.command = "${ROOT}/bin/api --name ${SERVICE_NAME}".into();
+        def.working_dir = Some("${ROOT}".into());
+        def.env_vars
+            .insert("TOKEN".into(), "secret://keychain/api".into());
+        def.env_vars.insert("CACHE".into(), "${ROOT}/cache".into());
+
+        let def = expand_definition(def, &ServiceConfig::default()).unwrap();
+        assert_eq!(def.command, "/srv/bin/api --name api");
+        assert_eq!(def.working_dir.as_deref(), Some("/srv"));
+        assert_eq!(def.env_vars["CACHE"], "/srv/cache");
+        assert_eq!(def.env_vars["TOKEN"], "secret://keychain/api");
+    }
+}
@@ -1,18 +1,29 @@
-use std::{collections::HashMap, path::PathBuf, sync::Arc};
 use anyhow::{Context, Result};
 use extism::*;
+use log::{error, info, warn};
 use serde::{Deserialize, Serialize};
-use tokio::sync::RwLock;
-use log::{info, warn, error};
+use std::{
+    collections::BTreeMap,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokio::sync::Mutex;
 
 /// Plugin host for tool auto-configuration
 pub struct ToolConfiguratorHost {
-    /// Loaded configurator plugins
-    plugins: Arc<RwLock<HashMap<String, Plugin>>>,
-    /// Discovery paths
+    /// Loaded configurator plugins, keyed by the name from `get_metadata`
+    plugins: Arc<Mutex<BTreeMap<String, LoadedPlugin>>>,
+    /// Discovery paths, searched in order; the first plugin with a name wins
     discovery_paths: Vec<PathBuf>,
 }
 
+struct LoadedPlugin {
+    plugin: Plugin,
+    path: PathBuf,
+    version: Option<String>,
+}
+
 /// Information about a detected tool
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DetectedTool {
@@ -22,6 +33,19 @@ pub struct DetectedTool {
     pub config_path: Option<String>,
 }
 
+/// What one plugin's `detect` reported, or why it could not
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginDetection {
+    pub plugin: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plugin_version: Option<String>,
+    pub path: PathBuf,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool: Option<DetectedTool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
 /// Configuration update request
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConfigUpdateRequest {
@@ -49,134 +73,188 @@ impl ToolConfiguratorHost {
     /// Create a new tool configurator host
     pub fn new() -> Self {
         let mut discovery_paths = vec![];
-        
+
         // System-wide plugins
         discovery_paths.push(PathBuf::from("/usr/local/lib/sweetmcp/tool-configurators"));
-        
+
         // User plugins
         if let Some(config_dir) = dirs::config_dir() {
             discovery_paths.push(config_dir.join("sweetmcp/tool-configurators"));
         }
-        
+
         Self {
-            plugins: Arc::new(RwLock::new(HashMap::new())),
+            plugins: Arc::new(Mutex::new(BTreeMap::new())),
             discovery_paths,
         }
     }
-    
-    /// Discover and load all tool configurator plugins
-    pub async fn discover_plugins(&self) -> Result<()> {
+
+    /// Search `dir` before the built-in locations
+    pub fn with_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.discovery_paths.insert(0, dir.into());
+        self
+    }
+
+    pub fn discovery_paths(&self) -> &[PathBuf] {
+        &self.discovery_paths
+    }
+
+    /// (Re)load all tool configurator plugins; returns how many are loaded
+    pub async fn discover_plugins(&self) -> Result<usize> {
         info!("Discovering tool configurator plugins...");
-        
-        let mut plugins = self.plugins.write().await;
-        
+
+        let mut loaded = BTreeMap::new();
+
         // Load plugins from filesystem
         for path in &self.discovery_paths {
             if path.exists() {
-                self.load_plugins_from_directory(&mut plugins, path).await?;
+                self.load_plugins_from_directory(&mut loaded, path)?;
             }
         }
-        
+
         // TODO: Load plugins from OCI registry
         // self.load_plugins_from_registry(&mut plugins).await?;
-        
-        info!("Loaded {} tool configurator plugins", plugins.len());
-        Ok(())
+
+        let count = loaded.len();
+        *self.plugins.lock().await = loaded;
+        info!("Loaded {} tool configurator plugins", count);
+        Ok(count)
     }
-    
+
     /// Load plugins from a directory
-    async fn load_plugins_from_directory(
+    fn load_plugins_from_directory(
         &self,
-        plugins: &mut HashMap<String, Plugin>,
-        dir: &PathBuf,
+        plugins: &mut BTreeMap<String, LoadedPlugin>,
+        dir: &Path,
     ) -> Result<()> {
         let entries = std::fs::read_dir(dir)
             .with_context(|| format!("Failed to read directory: {:?}", dir))?;
-            
-        for entry in entries {
-            let entry = entry?;
-            let path = entry.path();
-            
-            if path.extension().and_then(|s| s.to_str()) == Some("wasm") {
-                match self.load_plugin_from_file(&path).await {
-                    Ok((name, plugin)) => {
-                        info!("Loaded tool configurator: {}", name);
-                        plugins.insert(name, plugin);
-                    }
-                    Err(e) => {
-                        warn!("Failed to load plugin {:?}: {}", path, e);
-                    }
+
+        let mut paths: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+        paths.sort();
+        for path in paths {
+            if path.extension().and_then(|s| s.to_str()) != Some("wasm") {
+                continue;
+            }
+            match Self::load_plugin_from_file(&path) {
+                Ok((name, _)) if plugins.contains_key(&name) => {
+                    warn!(
+                        "Tool configurator {} in {:?} shadowed by {:?}",
+                        name, path, plugins[&name].path
+                    );
+                }
+                Ok((name, plugin)) => {
+                    info!("Loaded tool configurator: {}", name);
+                    plugins.insert(name, plugin);
+                }
+                Err(e) => {
+                    warn!("Failed to load plugin {:?}: {:#}", path, e);
                 }
             }
         }
-        
+
         Ok(())
     }
-    
+
     /// Load a single plugin from file
-    async fn load_plugin_from_file(&self, path: &PathBuf) -> Result<(String, Plugin)> {
+    fn load_plugin_from_file(path: &Path) -> Result<(String, LoadedPlugin)> {
         let wasm = std::fs::read(path)
             .with_context(|| format!("Failed to read plugin file: {:?}", path))?;
-            
+
         let manifest = Manifest::new([Wasm::data(wasm)]);
         let mut plugin = Plugin::new(&manifest, [], true)
             .with_context(|| format!("Failed to create plugin from: {:?}", path))?;
-            
+
         // Get plugin metadata
-        let metadata: serde_json::Value = plugin.call("get_metadata", "")
+        let Json(metadata) = plugin
+            .call::<&str, Json<serde_json::Value>>("get_metadata", "")
             .with_context(|| format!("Failed to get metadata from plugin: {:?}", path))?;
-            
+
         let name = metadata["name"]
             .as_str()
             .ok_or_else(|| anyhow::anyhow!("Plugin metadata missing 'name' field"))?
             .to_string();
-            
-        Ok((name, plugin))
+        let version = metadata["version"].as_str().map(str::to_string);
+
+        Ok((
+            name,
+            LoadedPlugin {
+                plugin,
+                path: path.to_path_buf(),
+                version,
+            },
+        ))
     }
-    
-    /// Detect all installed tools
-    pub async fn detect_tools(&self) -> Result<Vec<DetectedTool>> {
-        let mut detected_tools = Vec::new();
-        let plugins = self.plugins.read().await;
-        
-        for (name, plugin) in plugins.iter() {
-            match plugin.call::<&str, Json<DetectedTool>>("detect", "") {
-                Ok(Json(tool)) => {
-                    if tool.installed {
-                        info!("Detected tool: {} ({})", tool.name, name);
-                        detected_tools.push(tool);
+
+    /// Run `detect` on every loaded plugin, including tools that are not installed
+    pub async fn detect_all(&self) -> Vec<PluginDetection> {
+        let mut plugins = self.plugins.lock().await;
+
+        plugins
+            .iter_mut()
+            .map(|(name, loaded)| {
+                let detected = loaded.plugin.call::<&str, Json<DetectedTool>>("detect", "");
+                let (tool, error) = match detected {
+                    Ok(Json(tool)) => (Some(tool), None),
+                    Err(e) => {
+                        warn!("Failed to detect tool {}: {}", name, e);
+                        (None, Some(e.to_string()))
                     }
+                };
+                PluginDetection {
+                    plugin: name.clone(),
+                    plugin_version: loaded.version.clone(),
+                    path: loaded.path.clone(),
+                    tool,
+                    error,
                 }
-                Err(e) => {
-                    warn!("Failed to detect tool {}: {}", name, e);
-                }
-            }
-        }
-        
+            })
+            .collect()
+    }
+
+    /// Detect all installed tools
+    pub async fn detect_tools(&self) -> Result<Vec<DetectedTool>> {
+        let detected_tools = self
+            .detect_all()
+            .await
+            .into_iter()
+            .filter_map(|d| d.tool.filter(|t| t.installed).map(|t| (d.plugin, t)))
+            .map(|(plugin, tool)| {
+                info!("Detected tool: {} ({})", tool.name, plugin);
+                tool
+            })
+            .collect();
+
         Ok(detected_tools)
     }
-    
+
     /// Configure a specific tool
     pub async fn configure_tool(
         &self,
         tool_name: &str,
         config: ConfigUpdateRequest,
     ) -> Result<ConfigUpdateResult> {
-        let plugins = self.plugins.read().await;
-        
-        let plugin = plugins.get(tool_name)
-            .ok_or_else(|| anyhow::anyhow!("Tool configurator not found: {}", tool_name))?;
-            
+        let mut plugins = self.plugins.lock().await;
+
+        let plugin = &mut plugins
+            .get_mut(tool_name)
+            .ok_or_else(|| anyhow::anyhow!("Tool configurator not found: {}", tool_name))?
+            .plugin;
+
         // Read current configuration
-        let current_config: serde_json::Value = plugin.call("read_config", "")
+        let _current_config = plugin
+            .call::<&str, Json<serde_json::Value>>("read_config", "")
             .context("Failed to read current configuration")?;
-            
+
         // Update configuration
-        let result: Json<ConfigUpdateResult> = plugin.call("update_config", Json(&config))
+        let Json(result) = plugin
+            .call::<Json<&ConfigUpdateRequest>, Json<ConfigUpdateResult>>(
+                "update_config",
+                Json(&config),
+            )
             .context("Failed to update configuration")?;
-            
+
         // Restart tool if needed
-        if result.0.restart_required && result.0.success {
+        if result.restart_required && result.success {
             match plugin.call::<&str, String>("restart_tool", "") {
                 Ok(_) => {
                     info!("Successfully restarted {}", tool_name);
@@ -187,31 +265,71 @@ impl ToolConfiguratorHost {
                 }
             }
         }
-        
-        Ok(result.0)
+
+        Ok(result)
     }
-    
+
     /// Configure all detected tools
     pub async fn configure_all_tools(&self, config: ConfigUpdateRequest) -> Result<()> {
-        let tools = self.detect_tools().await?;
-        
-        for tool in tools {
-            info!("Configuring {}...", tool.name);
-            
-            match self.configure_tool(&tool.name, config.clone()).await {
+        let installed: Vec<_> = self
+            .detect_all()
+            .await
+            .into_iter()
+            .filter(|d| d.tool.as_ref().is_some_and(|t| t.installed))
+            .collect();
+
+        for detection in installed {
+            info!("Configuring {}...", detection.plugin);
+
+            match self.configure_tool(&detection.plugin, config.clone()).await {
                 Ok(result) => {
                     if result.success {
-                        info!("Successfully configured {}: {}", tool.name, result.message);
+                        info!(
+                            "Successfully configured {}: {}",
+                            detection.plugin, result.message
+                        );
                     } else {
-                        warn!("Failed to configure {}: {}", tool.name, result.message);
+                        warn!(
+                            "Failed to configure {}: {}",
+                            detection.plugin, result.message
+                        );
                     }
                 }
                 Err(e) => {
-                    error!("Error configuring {}: {}", tool.name, e);
+                    error!("Error configuring {}: {}", detection.plugin, e);
                 }
             }
         }
-        
+
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+impl Default for ToolConfiguratorHost {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn broken_plugins_are_skipped() {
+        let dir = std::env::temp_dir().join(format!("cyrupd-tools-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("broken.wasm"), b"not wasm").unwrap();
+        std::fs::write(dir.join("notes.txt"), b"ignored").unwrap();
+
+        let host = ToolConfiguratorHost {
+            plugins: Arc::new(Mutex::new(BTreeMap::new())),
+            discovery_paths: Vec::new(),
+        }
+        .with_dir(&dir);
+        assert_eq!(host.discover_plugins().await.unwrap(), 0);
+        assert!(host.detect_all().await.is_empty());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,497 @@
+//! Credential vault behind the admin API's `/secrets` routes.
+//!
+//! Values live in the OS keychain, under the same service name that
+//! `secret://keychain/<name>` references resolve against, so a secret stored
+//! here can be handed to a service without ever being written to its config.
+//! The keychain can't be enumerated, so names and descriptions are kept in a
+//! separate index file.
+//!
+//! Callers are the admin token, which may do anything, or a client named in
+//! `[secrets.clients]`, limited to the secrets its `read`, `reveal` and
+//! `write` patterns match. Reads return a redacted view with a
+//! `secret://keychain/<name>` reference; plaintext only comes back when the
+//! caller asks for it and its policy allows it.
+
+use crate::config::{SecretClientPolicy, SecretsConfig};
+use crate::secrets::{KeychainProvider, SecretProvider, DEFAULT_KEYCHAIN_SERVICE};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Longest secret name accepted
+const MAX_NAME_LEN: usize = 128;
+
+/// Largest secret value accepted, in bytes
+const MAX_VALUE_LEN: usize = 64 * 1024;
+
+/// Where secret values are kept
+pub trait SecretStore: SecretProvider {
+    fn set(&self, name: &str, value: &str) -> Result<()>;
+}
+
+impl SecretStore for KeychainProvider {
+    fn set(&self, name: &str, value: &str) -> Result<()> {
+        KeychainProvider::set(self, name, value)
+    }
+}
+
+/// Who is asking, established from the bearer token
+#[derive(Debug, Clone, PartialEq)]
+pub enum Caller {
+    Admin,
+    Client(String),
+}
+
+/// Why a vault request failed, so the admin API can pick a status code
+#[derive(Debug)]
+pub enum VaultError {
+    Forbidden(String),
+    NotFound(String),
+    Invalid(String),
+    Internal(anyhow::Error),
+}
+
+impl std::fmt::Display for VaultError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VaultError::Forbidden(msg) | VaultError::NotFound(msg) | VaultError::Invalid(msg) => {
+                f.write_str(msg)
+            }
+            VaultError::Internal(e) => write!(f, "{:#}", e),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexEntry {
+    #[serde(default)]
+    description: Option<String>,
+    created: DateTime<Utc>,
+    updated: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct IndexFile {
+    #[serde(default)]
+    secrets: BTreeMap<String, IndexEntry>,
+}
+
+/// A secret as a caller sees it
+#[derive(Debug, Serialize)]
+pub struct SecretView {
+    pub name: String,
+    /// Pass this to services and tools instead of the value
+    pub reference: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub updated: DateTime<Utc>,
+    /// `****` plus the last four characters of long values, otherwise `****`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redacted: Option<String>,
+    /// Plaintext, only when asked for and allowed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    pub can_reveal: bool,
+    pub can_write: bool,
+}
+
+struct Client {
+    token: String,
+    policy: SecretClientPolicy,
+}
+
+pub struct Vault {
+    store: Box<dyn SecretStore>,
+    clients: BTreeMap<String, Client>,
+    index_path: Option<PathBuf>,
+    index: Mutex<BTreeMap<String, IndexEntry>>,
+}
+
+impl Vault {
+    /// Build from `[secrets]`, reading every client token up front
+    pub fn from_config(cfg: Option<&SecretsConfig>) -> Result<Self> {
+        let service = cfg
+            .and_then(|c| c.keychain_service.clone())
+            .unwrap_or_else(|| DEFAULT_KEYCHAIN_SERVICE.to_string());
+        let index_path = match cfg {
+            Some(cfg) => cfg.vault_index_path(),
+            None => SecretsConfig::default().vault_index_path(),
+        };
+        let mut vault = Self::new(Box::new(KeychainProvider::new(service)), index_path)?;
+        for (name, policy) in cfg.map(|c| &c.clients).into_iter().flatten() {
+            let token = policy
+                .resolve_token()
+                .with_context(|| format!("secret client {}", name))?;
+            vault.clients.insert(
+                name.clone(),
+                Client {
+                    token,
+                    policy: policy.clone(),
+                },
+            );
+        }
+        Ok(vault)
+    }
+
+    /// A vault over `store`, indexed at `index_path` (in memory when `None`)
+    pub fn new(store: Box<dyn SecretStore>, index_path: Option<PathBuf>) -> Result<Self> {
+        let index = match &index_path {
+            Some(path) => match std::fs::read(path) {
+                Ok(bytes) => {
+                    serde_json::from_slice::<IndexFile>(&bytes)
+                        .with_context(|| format!("corrupt vault index {}", path.display()))?
+                        .secrets
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+                Err(e) => {
+                    return Err(e).with_context(|| format!("read vault index {}", path.display()))
+                }
+            },
+            None => BTreeMap::new(),
+        };
+        Ok(Self {
+            store,
+            clients: BTreeMap::new(),
+            index_path,
+            index: Mutex::new(index),
+        })
+    }
+
+    /// Add a client policy with its resolved token
+    pub fn with_client(mut self, name: &str, token: &str, policy: SecretClientPolicy) -> Self {
+        self.clients.insert(
+            name.to_string(),
+            Client {
+                token: token.to_string(),
+                policy,
+            },
+        );
+        self
+    }
+
+    /// The client whose token this is, if any
+    pub fn client_for_token(&self, token: &str) -> Option<Caller> {
+        self.clients
+            .iter()
+            .find(|(_, client)| crate::service::admin::token_matches(token, &client.token))
+            .map(|(name, _)| Caller::Client(name.clone()))
+    }
+
+    /// Secrets the caller may read, redacted
+    pub fn list(&self, caller: &Caller) -> Vec<SecretView> {
+        self.lock_index()
+            .iter()
+            .filter(|(name, _)| self.allowed(caller, name, |p| &p.read))
+            .map(|(name, entry)| self.view(caller, name, entry, None))
+            .collect()
+    }
+
+    /// One secret, with its plaintext only if `reveal` is asked for and allowed
+    pub fn get(&self, caller: &Caller, name: &str, reveal: bool) -> Result<SecretView, VaultError> {
+        validate_name(name)?;
+        if !self.allowed(caller, name, |p| &p.read) {
+            return Err(VaultError::Forbidden(format!(
+                "not allowed to read secret {}",
+                name
+            )));
+        }
+        if reveal && !self.allowed(caller, name, |p| &p.reveal) {
+            return Err(VaultError::Forbidden(format!(
+                "not allowed to reveal secret {}",
+                name
+            )));
+        }
+        let entry = self
+            .lock_index()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| VaultError::NotFound(format!("no secret named {}", name)))?;
+        let value = self.store.get(name).map_err(VaultError::Internal)?;
+        Ok(self.view(caller, name, &entry, Some((value, reveal))))
+    }
+
+    /// Create or replace a secret
+    pub fn store(
+        &self,
+        caller: &Caller,
+        name: &str,
+        value: &str,
+        description: Option<String>,
+    ) -> Result<SecretView, VaultError> {
+        validate_name(name)?;
+        if !self.allowed(caller, name, |p| &p.write) {
+            return Err(VaultError::Forbidden(format!(
+                "not allowed to write secret {}",
+                name
+            )));
+        }
+        if value.is_empty() {
+            return Err(VaultError::Invalid("secret value is empty".to_string()));
+        }
+        if value.len() > MAX_VALUE_LEN {
+            return Err(VaultError::Invalid(format!(
+                "secret value is over {} bytes",
+                MAX_VALUE_LEN
+            )));
+        }
+
+        self.store.set(name, value).map_err(VaultError::Internal)?;
+        let mut index = self.lock_index();
+        let now = Utc::now();
+        let entry = index.entry(name.to_string()).or_insert(IndexEntry {
+            description: None,
+            created: now,
+            updated: now,
+        });
+        entry.updated = now;
+        if description.is_some() {
+            entry.description = description;
+        }
+        let entry = entry.clone();
+        self.persist(&index).map_err(VaultError::Internal)?;
+        drop(index);
+        Ok(self.view(caller, name, &entry, None))
+    }
+
+    fn allowed(
+        &self,
+        caller: &Caller,
+        name: &str,
+        patterns: impl Fn(&SecretClientPolicy) -> &Vec<String>,
+    ) -> bool {
+        match caller {
+            Caller::Admin => true,
+            Caller::Client(client) => self.clients.get(client).is_some_and(|c| {
+                patterns(&c.policy)
+                    .iter()
+                    .any(|pattern| name_matches(pattern, name))
+            }),
+        }
+    }
+
+    fn view(
+        &self,
+        caller: &Caller,
+        name: &str,
+        entry: &IndexEntry,
+        value: Option<(String, bool)>,
+    ) -> SecretView {
+        let (redacted, value) = match value {
+            Some((value, true)) => (Some(redact(&value)), Some(value)),
+            Some((value, false)) => (Some(redact(&value)), None),
+            None => (None, None),
+        };
+        SecretView {
+            name: name.to_string(),
+            reference: format!("secret://keychain/{}", name),
+            description: entry.description.clone(),
+            updated: entry.updated,
+            redacted,
+            value,
+            can_reveal: self.allowed(caller, name, |p| &p.reveal),
+            can_write: self.allowed(caller, name, |p| &p.write),
+        }
+    }
+
+    fn persist(&self, index: &BTreeMap<String, IndexEntry>) -> Result<()> {
+        let Some(path) = &self.index_path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("create {}", parent.display()))?;
+        }
+        let file = IndexFile {
+            secrets: index.clone(),
+        };
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(&file)?)
+            .with_context(|| format!("write {}", tmp.display()))?;
+        std::fs::rename(&tmp, path).with_context(|| format!("replace {}", path.display()))?;
+        Ok(())
+    }
+
+    fn lock_index(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, IndexEntry>> {
+        self.index
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Names are letters, digits and `._-/`, so they are safe as keychain
+/// accounts and in `secret://` references
+fn validate_name(name: &str) -> Result<(), VaultError> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && !name.starts_with('/')
+        && !name.split('/').any(|part| part.is_empty() || part == "..")
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-' | '/'));
+    if valid {
+        Ok(())
+    } else {
+        Err(VaultError::Invalid(format!(
+            "invalid secret name {:?}: use up to {} letters, digits and ._-/",
+            name, MAX_NAME_LEN
+        )))
+    }
+}
+
+/// Match a name against a pattern where `*` stands for any run of characters
+fn name_matches(pattern: &str, name: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == name;
+    }
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !name.starts_with(first) || name.len() < first.len() + last.len() || !name.ends_with(last) {
+        return false;
+    }
+    let mut rest = &name[first.len()..name.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Enough of a value to tell two apart, never enough to use
+fn redact(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() >= 16 {
+        let tail: String = chars[chars.len() - 4..].iter().collect();
+        format!("****{}", tail)
+    } else {
+        "****".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[derive(Default)]
+    struct Memory(Mutex<HashMap<String, String>>);
+
+    impl SecretProvider for Memory {
+        fn get(&self, name: &str) -> Result<String> {
+            self.0.lock().unwrap().get(name).cloned().context("missing")
+        }
+    }
+
+    impl SecretStore for Memory {
+        fn set(&self, name: &str, value: &str) -> Result<()> {
+            self.0
+                .lock()
+                .unwrap()
+                .insert(name.to_string(), value.to_string());
+            Ok(())
+        }
+    }
+
+    fn vault() -> Vault {
+        Vault::new(Box::new(Memory::default()), None)
+            .unwrap()
+            .with_client(
+                "agent",
+                "agent-token",
+                SecretClientPolicy {
+                    read: vec!["github/*".into(), "npm".into()],
+                    reveal: vec!["github/ci".into()],
+                    write: vec!["scratch/*".into()],
+                    ..Default::default()
+                },
+            )
+    }
+
+    #[test]
+    fn patterns() {
+        assert!(name_matches("github/*", "github/ci"));
+        assert!(name_matches("*", "anything"));
+        assert!(name_matches("a*b*c", "axxbyyc"));
+        assert!(!name_matches("a*b*c", "axxc"));
+        assert!(!name_matches("github/*", "gitlab/ci"));
+        assert!(name_matches("npm", "npm"));
+        assert!(!name_matches("npm", "npm2"));
+    }
+
+    #[test]
+    fn names_are_validated() {
+        assert!(validate_name("github/ci-token_2.pem").is_ok());
+        for bad in ["", "/abs", "a//b", "a/../b", "has space", "trailing/"] {
+            assert!(validate_name(bad).is_err(), "{:?} accepted", bad);
+        }
+    }
+
+    #[test]
+    fn clients_see_only_what_their_policy_allows() {
+        let vault = vault();
+        let agent = Caller::Client("agent".into());
+        let token = "ghp_0123456789abcdefWXYZ";
+        vault
+            .store(&Caller::Admin, "github/ci", token, None)
+            .unwrap();
+        vault
+            .store(&Caller::Admin, "aws/root", "AKIA0000", Some("root".into()))
+            .unwrap();
+
+        let names: Vec<String> = vault.list(&agent).into_iter().map(|v| v.name).collect();
+        assert_eq!(names, ["github/ci"]);
+        assert_eq!(vault.list(&Caller::Admin).len(), 2);
+
+        let redacted = vault.get(&agent, "github/ci", false).unwrap();
+        assert_eq!(redacted.value, None);
+        assert_eq!(redacted.redacted.as_deref(), Some("****WXYZ"));
+        assert_eq!(redacted.reference, "secret://keychain/github/ci");
+        assert!(redacted.can_reveal);
+
+        let revealed = vault.get(&agent, "github/ci", true).unwrap();
+        assert_eq!(revealed.value.as_deref(), Some(token));
+
+        assert!(matches!(
+            vault.get(&agent, "aws/root", false),
+            Err(VaultError::Forbidden(_))
+        ));
+        assert!(matches!(
+            vault.get(&agent, "github/missing", false),
+            Err(VaultError::NotFound(_))
+        ));
+        assert!(matches!(
+            vault.store(&agent, "github/ci", "x", None),
+            Err(VaultError::Forbidden(_))
+        ));
+        assert!(vault.store(&agent, "scratch/tmp", "x", None).is_ok());
+    }
+
+    #[test]
+    fn index_survives_reload() {
+        let path = std::env::temp_dir()
+            .join(format!("cyrupd-vault-{}", std::process::id()))
+            .join("vault.json");
+        let vault = Vault::new(Box::new(Memory::default()), Some(path.clone())).unwrap();
+        vault
+            .store(
+                &Caller::Admin,
+                "npm",
+                "s3cr3t-value",
+                Some("publish token".into()),
+            )
+            .unwrap();
+
+        let reloaded = Vault::new(Box::new(Memory::default()), Some(path.clone())).unwrap();
+        let listed = reloaded.list(&Caller::Admin);
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].description.as_deref(), Some("publish token"));
+        let raw = std::fs::read_to_string(&path).unwrap();
+        assert!(!raw.contains("s3cr3t"), "value leaked into the index");
+
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+}
@@ -0,0 +1,602 @@
+//! Interactive install wizard (`cyrupd install --interactive`)
+//!
+//! Walks through gateway ports, TLS, the plugin directory and which MCP
+//! clients to auto-configure, then hands the answers to the installer as an
+//! [`InstallPlan`]. Key handling is kept apart from drawing so the flow can be
+//! exercised without a terminal.
+
+use anyhow::{bail, Result};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::crossterm::ExecutableCommand;
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
+    Terminal,
+};
+use std::io::{stdout, IsTerminal};
+use std::path::{Path, PathBuf};
+use sweetmcp_client_autoconfig::clients::all_clients;
+
+/// How the gateway gets its certificate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TlsChoice {
+    /// Generate the local `*.cyrup.*` wildcard certificate and trust it
+    SelfSigned,
+    /// Use an existing certificate and private key (PEM)
+    Existing { cert: PathBuf, key: PathBuf },
+    /// Skip certificate setup entirely
+    Disabled,
+}
+
+/// Everything the installer needs to know up front
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstallPlan {
+    /// `SWEETMCP_TCP_BIND` port of the gateway
+    pub gateway_port: u16,
+    /// MCP Streamable HTTP port (`mcp_bind`)
+    pub mcp_port: u16,
+    /// Prometheus metrics port on 127.0.0.1
+    pub metrics_port: u16,
+    pub tls: TlsChoice,
+    pub plugin_dir: PathBuf,
+    /// Client ids (`claude-desktop`, `cursor`, …) to auto-configure
+    pub clients: Vec<String>,
+}
+
+impl Default for InstallPlan {
+    fn default() -> Self {
+        Self {
+            gateway_port: 8443,
+            mcp_port: 33399,
+            metrics_port: 9090,
+            tls: TlsChoice::SelfSigned,
+            plugin_dir: default_plugin_dir(),
+            clients: all_clients()
+                .iter()
+                .map(|c| c.client_id().to_string())
+                .collect(),
+        }
+    }
+}
+
+fn default_plugin_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("/opt"))
+        .join("sweetmcp")
+        .join("plugins")
+}
+
+const TLS_OPTIONS: [&str; 3] = [
+    "Generate a local wildcard certificate (*.cyrup.dev and friends)",
+    "Use an existing certificate and key",
+    "No TLS setup",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    GatewayPort,
+    McpPort,
+    MetricsPort,
+    Tls,
+    TlsCert,
+    TlsKey,
+    PluginDir,
+    Clients,
+    Confirm,
+}
+
+/// Outcome of a key press
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Flow {
+    Continue,
+    Done(InstallPlan),
+    Cancelled,
+}
+
+struct ClientChoice {
+    id: String,
+    name: String,
+    detected: bool,
+    selected: bool,
+}
+
+/// Wizard state; one text field or list is active per step
+pub struct Wizard {
+    step: Step,
+    history: Vec<Step>,
+    config_path: PathBuf,
+    input: String,
+    cursor: usize,
+    error: Option<String>,
+    gateway_port: u16,
+    mcp_port: u16,
+    metrics_port: u16,
+    tls: usize,
+    cert: PathBuf,
+    key: PathBuf,
+    plugin_dir: PathBuf,
+    clients: Vec<ClientChoice>,
+}
+
+impl Wizard {
+    /// Start from the defaults; detected clients are preselected
+    pub fn new(config_path: &Path) -> Self {
+        let clients = all_clients()
+            .iter()
+            .map(|client| {
+                let detected = client
+                    .watch_paths()
+                    .iter()
+                    .any(|path| client.is_installed(path));
+                ClientChoice {
+                    id: client.client_id().to_string(),
+                    name: client.client_name().to_string(),
+                    detected,
+                    selected: detected,
+                }
+            })
+            .collect();
+        Self::with_clients(config_path, clients)
+    }
+
+    fn with_clients(config_path: &Path, clients: Vec<ClientChoice>) -> Self {
+        let plan = InstallPlan::default();
+        let mut wizard = Self {
+            step: Step::GatewayPort,
+            history: Vec::new(),
+            config_path: config_path.to_path_buf(),
+            input: String::new(),
+            cursor: 0,
+            error: None,
+            gateway_port: plan.gateway_port,
+            mcp_port: plan.mcp_port,
+            metrics_port: plan.metrics_port,
+            tls: 0,
+            cert: PathBuf::new(),
+            key: PathBuf::new(),
+            plugin_dir: plan.plugin_dir,
+            clients,
+        };
+        wizard.enter(Step::GatewayPort);
+        wizard
+    }
+
+    /// Move to `step`, pre-filling its field with the current answer
+    fn enter(&mut self, step: Step) {
+        self.step = step;
+        self.error = None;
+        self.cursor = 0;
+        self.input = match step {
+            Step::GatewayPort => self.gateway_port.to_string(),
+            Step::McpPort => self.mcp_port.to_string(),
+            Step::MetricsPort => self.metrics_port.to_string(),
+            Step::TlsCert => self.cert.display().to_string(),
+            Step::TlsKey => self.key.display().to_string(),
+            Step::PluginDir => self.plugin_dir.display().to_string(),
+            Step::Tls | Step::Clients | Step::Confirm => String::new(),
+        };
+        if step == Step::Tls {
+            self.cursor = self.tls;
+        }
+    }
+
+    fn advance(&mut self, next: Step) {
+        self.history.push(self.step);
+        self.enter(next);
+    }
+
+    fn plan(&self) -> InstallPlan {
+        InstallPlan {
+            gateway_port: self.gateway_port,
+            mcp_port: self.mcp_port,
+            metrics_port: self.metrics_port,
+            tls: match self.tls {
+                0 => TlsChoice::SelfSigned,
+                1 => TlsChoice::Existing {
+                    cert: self.cert.clone(),
+                    key: self.key.clone(),
+                },
+                _ => TlsChoice::Disabled,
+            },
+            plugin_dir: self.plugin_dir.clone(),
+            clients: self
+                .clients
+                .iter()
+                .filter(|c| c.selected)
+                .map(|c| c.id.clone())
+                .collect(),
+        }
+    }
+
+    fn parse_port(&self, taken: &[u16]) -> Result<u16, String> {
+        let port: u16 = self
+            .input
+            .trim()
+            .parse()
+            .map_err(|_| format!("'{}' is not a port number", self.input.trim()))?;
+        if port == 0 {
+            return Err("Port 0 is not allowed".to_string());
+        }
+        if taken.contains(&port) {
+            return Err(format!("Port {} is already used by another listener", port));
+        }
+        Ok(port)
+    }
+
+    fn parse_file(&self) -> Result<PathBuf, String> {
+        let path = PathBuf::from(self.input.trim());
+        if !path.is_file() {
+            return Err(format!("{} is not a readable file", path.display()));
+        }
+        Ok(path)
+    }
+
+    /// Accept the current step's answer and move on
+    fn submit(&mut self) -> Flow {
+        let result = match self.step {
+            Step::GatewayPort => self.parse_port(&[]).map(|port| {
+                self.gateway_port = port;
+                Step::McpPort
+            }),
+            Step::McpPort => self.parse_port(&[self.gateway_port]).map(|port| {
+                self.mcp_port = port;
+                Step::MetricsPort
+            }),
+            Step::MetricsPort => self
+                .parse_port(&[self.gateway_port, self.mcp_port])
+                .map(|port| {
+                    self.metrics_port = port;
+                    Step::Tls
+                }),
+            Step::Tls => {
+                self.tls = self.cursor;
+                Ok(if self.tls == 1 {
+                    Step::TlsCert
+                } else {
+                    Step::PluginDir
+                })
+            }
+            Step::TlsCert => self.parse_file().map(|path| {
+                self.cert = path;
+                Step::TlsKey
+            }),
+            Step::TlsKey => self.parse_file().map(|path| {
+                self.key = path;
+                Step::PluginDir
+            }),
+            Step::PluginDir => match self.input.trim() {
+                "" => Err("The plugin directory cannot be empty".to_string()),
+                dir => {
+                    self.plugin_dir = PathBuf::from(dir);
+                    Ok(Step::Clients)
+                }
+            },
+            Step::Clients => Ok(Step::Confirm),
+            Step::Confirm => return Flow::Done(self.plan()),
+        };
+        match result {
+            Ok(next) => self.advance(next),
+            Err(e) => self.error = Some(e),
+        }
+        Flow::Continue
+    }
+
+    fn list_len(&self) -> usize {
+        match self.step {
+            Step::Tls => TLS_OPTIONS.len(),
+            Step::Clients => self.clients.len(),
+            _ => 0,
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Flow {
+        if key.kind != KeyEventKind::Press {
+            return Flow::Continue;
+        }
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Flow::Cancelled;
+        }
+        match key.code {
+            KeyCode::Enter => return self.submit(),
+            KeyCode::Esc => match self.history.pop() {
+                Some(previous) => self.enter(previous),
+                None => return Flow::Cancelled,
+            },
+            KeyCode::Up if self.list_len() > 0 => {
+                self.cursor = self.cursor.checked_sub(1).unwrap_or(self.list_len() - 1);
+            }
+            KeyCode::Down if self.list_len() > 0 => {
+                self.cursor = (self.cursor + 1) % self.list_len();
+            }
+            KeyCode::Char(' ') if self.step == Step::Clients => {
+                if let Some(client) = self.clients.get_mut(self.cursor) {
+                    client.selected = !client.selected;
+                }
+            }
+            KeyCode::Char(c) if self.list_len() == 0 && self.step != Step::Confirm => {
+                self.input.push(c);
+                self.error = None;
+            }
+            KeyCode::Backspace => {
+                self.input.pop();
+                self.error = None;
+            }
+            _ => {}
+        }
+        Flow::Continue
+    }
+
+    fn title(&self) -> &'static str {
+        match self.step {
+            Step::GatewayPort => "Gateway port",
+            Step::McpPort => "MCP Streamable HTTP port",
+            Step::MetricsPort => "Metrics port",
+            Step::Tls => "TLS",
+            Step::TlsCert => "TLS certificate",
+            Step::TlsKey => "TLS private key",
+            Step::PluginDir => "Plugin directory",
+            Step::Clients => "Client auto-configuration",
+            Step::Confirm => "Review",
+        }
+    }
+
+    fn prompt(&self) -> &'static str {
+        match self.step {
+            Step::GatewayPort => "Port the SweetMCP gateway listens on for all protocols.",
+            Step::McpPort => "Port for MCP Streamable HTTP clients.",
+            Step::MetricsPort => "Port for Prometheus metrics (bound to 127.0.0.1).",
+            Step::Tls => "How should the gateway get its certificate?",
+            Step::TlsCert => "Path to the PEM certificate (chain).",
+            Step::TlsKey => "Path to the PEM private key.",
+            Step::PluginDir => "Where wasm plugins are installed and loaded from.",
+            Step::Clients => "Which MCP clients should be pointed at SweetMCP automatically?",
+            Step::Confirm => "Write the config and install the service?",
+        }
+    }
+
+    fn summary(&self) -> Vec<Line<'static>> {
+        let plan = self.plan();
+        let tls = match &plan.tls {
+            TlsChoice::SelfSigned => "local wildcard certificate".to_string(),
+            TlsChoice::Existing { cert, key } => {
+                format!("{} + {}", cert.display(), key.display())
+            }
+            TlsChoice::Disabled => "none".to_string(),
+        };
+        let clients = if plan.clients.is_empty() {
+            "none".to_string()
+        } else {
+            plan.clients.join(", ")
+        };
+        let mut lines: Vec<Line<'static>> = [
+            ("Gateway", format!("0.0.0.0:{}", plan.gateway_port)),
+            ("MCP HTTP", format!("0.0.0.0:{}", plan.mcp_port)),
+            ("Metrics", format!("127.0.0.1:{}", plan.metrics_port)),
+            ("TLS", tls),
+            ("Plugins", plan.plugin_dir.display().to_string()),
+            ("Clients", clients),
+            ("Config", self.config_path.display().to_string()),
+        ]
+        .into_iter()
+        .map(|(label, value)| {
+            Line::from(vec![
+                Span::styled(format!("{:<10}", label), Style::default().fg(Color::Yellow)),
+                Span::raw(value),
+            ])
+        })
+        .collect();
+        if self.config_path.exists() {
+            lines.push(Line::from(""));
+            lines.push(Line::styled(
+                "The existing config will be overwritten.",
+                Style::default().fg(Color::Red),
+            ));
+        }
+        lines
+    }
+
+    fn render(&self, f: &mut Frame) {
+        let area = f.area();
+        let chunks = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(2),
+            Constraint::Min(3),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .split(area);
+
+        let header = Paragraph::new(self.title())
+            .block(
+                Block::default()
+                    .title("cyrupd install")
+                    .title_alignment(Alignment::Center)
+                    .borders(Borders::ALL),
+            )
+            .alignment(Alignment::Center);
+        f.render_widget(header, chunks[0]);
+        f.render_widget(
+            Paragraph::new(self.prompt()).wrap(Wrap { trim: true }),
+            chunks[1],
+        );
+
+        let body = Block::default().borders(Borders::ALL);
+        match self.step {
+            Step::Tls | Step::Clients => {
+                let items: Vec<ListItem> = if self.step == Step::Tls {
+                    TLS_OPTIONS
+                        .iter()
+                        .enumerate()
+                        .map(|(i, label)| {
+                            let mark = if i == self.cursor { "(•)" } else { "( )" };
+                            ListItem::new(format!("{} {}", mark, label))
+                        })
+                        .collect()
+                } else {
+                    self.clients
+                        .iter()
+                        .map(|c| {
+                            let mark = if c.selected { "[x]" } else { "[ ]" };
+                            let found = if c.detected { "  (detected)" } else { "" };
+                            ListItem::new(format!("{} {}{}", mark, c.name, found))
+                        })
+                        .collect()
+                };
+                let items: Vec<ListItem> = items
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, item)| {
+                        if i == self.cursor {
+                            item.style(Style::default().add_modifier(Modifier::REVERSED))
+                        } else {
+                            item
+                        }
+                    })
+                    .collect();
+                f.render_widget(List::new(items).block(body), chunks[2]);
+            }
+            Step::Confirm => {
+                f.render_widget(Paragraph::new(self.summary()).block(body), chunks[2]);
+            }
+            _ => {
+                f.render_widget(
+                    Paragraph::new(format!("{}▏", self.input)).block(body),
+                    chunks[2],
+                );
+            }
+        }
+
+        if let Some(error) = &self.error {
+            f.render_widget(
+                Paragraph::new(error.as_str()).style(Style::default().fg(Color::Red)),
+                chunks[3],
+            );
+        }
+        let keys = match self.step {
+            Step::Tls => "↑/↓ choose · Enter next · Esc back · Ctrl-C quit",
+            Step::Clients => "↑/↓ move · Space toggle · Enter next · Esc back · Ctrl-C quit",
+            Step::Confirm => "Enter install · Esc back · Ctrl-C quit",
+            _ => "Enter next · Esc back · Ctrl-C quit",
+        };
+        f.render_widget(
+            Paragraph::new(keys).style(Style::default().fg(Color::DarkGray)),
+            chunks[4],
+        );
+    }
+}
+
+/// Run the wizard on the terminal. `None` means the user backed out.
+pub fn run(config_path: &Path) -> Result<Option<InstallPlan>> {
+    if !std::io::stdin().is_terminal() || !stdout().is_terminal() {
+        bail!("--interactive needs a terminal; run without it to install with defaults");
+    }
+
+    enable_raw_mode()?;
+    stdout().execute(EnterAlternateScreen)?;
+    let result = (|| -> Result<Option<InstallPlan>> {
+        let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+        let mut wizard = Wizard::new(config_path);
+        loop {
+            terminal.draw(|f| wizard.render(f))?;
+            if let Event::Key(key) = event::read()? {
+                match wizard.handle_key(key) {
+                    Flow::Continue => {}
+                    Flow::Done(plan) => return Ok(Some(plan)),
+                    Flow::Cancelled => return Ok(None),
+                }
+            }
+        }
+    })();
+    // Restore the terminal even when drawing failed
+    disable_raw_mode()?;
+    stdout().execute(LeaveAlternateScreen)?;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wizard() -> Wizard {
+        let clients = ["claude-desktop", "cursor"]
+            .into_iter()
+            .map(|id| ClientChoice {
+                id: id.to_string(),
+                name: id.to_string(),
+                detected: id == "cursor",
+                selected: id == "cursor",
+            })
+            .collect();
+        Wizard::with_clients(Path::new("/tmp/cyrupd-wizard-test.toml"), clients)
+    }
+
+    fn press(wizard: &mut Wizard, code: KeyCode) -> Flow {
+        wizard.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn type_str(wizard: &mut Wizard, text: &str) {
+        for _ in 0..wizard.input.len() {
+            press(wizard, KeyCode::Backspace);
+        }
+        for c in text.chars() {
+            press(wizard, KeyCode::Char(c));
+        }
+    }
+
+    #[test]
+    fn accepting_every_default_keeps_detected_clients() {
+        let mut wizard = wizard();
+        let mut flow = Flow::Continue;
+        for _ in 0..7 {
+            flow = press(&mut wizard, KeyCode::Enter);
+        }
+        let Flow::Done(plan) = flow else {
+            panic!("wizard did not finish: {:?}", flow);
+        };
+        assert_eq!(plan.gateway_port, 8443);
+        assert_eq!(plan.tls, TlsChoice::SelfSigned);
+        assert_eq!(plan.clients, vec!["cursor".to_string()]);
+    }
+
+    #[test]
+    fn rejects_clashing_ports_and_goes_back_on_escape() {
+        let mut wizard = wizard();
+        type_str(&mut wizard, "9000");
+        press(&mut wizard, KeyCode::Enter);
+        assert_eq!(wizard.step, Step::McpPort);
+
+        type_str(&mut wizard, "9000");
+        press(&mut wizard, KeyCode::Enter);
+        assert_eq!(wizard.step, Step::McpPort);
+        assert!(wizard.error.is_some());
+
+        press(&mut wizard, KeyCode::Esc);
+        assert_eq!(wizard.step, Step::GatewayPort);
+        assert_eq!(wizard.input, "9000");
+        assert_eq!(press(&mut wizard, KeyCode::Esc), Flow::Cancelled);
+    }
+
+    #[test]
+    fn existing_certificate_asks_for_readable_files() {
+        let mut wizard = wizard();
+        for _ in 0..3 {
+            press(&mut wizard, KeyCode::Enter);
+        }
+        press(&mut wizard, KeyCode::Down);
+        press(&mut wizard, KeyCode::Enter);
+        assert_eq!(wizard.step, Step::TlsCert);
+
+        type_str(&mut wizard, "/nonexistent/cert.pem");
+        press(&mut wizard, KeyCode::Enter);
+        assert_eq!(wizard.step, Step::TlsCert);
+
+        press(&mut wizard, KeyCode::Esc);
+        press(&mut wizard, KeyCode::Down);
+        press(&mut wizard, KeyCode::Enter);
+        assert_eq!(wizard.step, Step::PluginDir);
+        assert_eq!(wizard.plan().tls, TlsChoice::Disabled);
+    }
+}
@@ -3,10 +3,11 @@
 
 pub mod sdk;
 
+#[cfg(feature = "api")]
+pub mod routes;
+
 // TODO: Implement these modules
 // #[cfg(feature = "api")]
-// pub mod routes;
-// #[cfg(feature = "api")]
 // pub mod handlers;
 // #[cfg(feature = "api")]
 // pub mod middleware;
@@ -46,9 +47,7 @@ where
 {
     /// Create a new API server
     pub fn new(memory_manager: Arc<M>, config: APIConfig) -> Self {
-        // TODO: Implement routes module
-        // let router = routes::create_router(memory_manager.clone(), &config);
-        let router = Router::new();
+        let router = routes::create_router(memory_manager.clone(), &config);
 
         Self {
             memory_manager,
@@ -0,0 +1,256 @@
+//! HTTP routes for the memory API
+//!
+//! Exposes a minimal JSON surface over the memory manager so that out-of-process
+//! consumers (for example WASM plugins, which cannot link the memory crate) can
+//! store and retrieve memories, and report back which retrieved memories an
+//! LLM response actually used.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+
+use crate::memory::attribution::Attribution;
+use crate::memory::{MemoryManager, MemoryNode, MemoryType, UsageTracker};
+use crate::utils::config::APIConfig;
+
+/// Default number of results returned by a search when no limit is supplied
+const DEFAULT_SEARCH_LIMIT: usize = 10;
+
+/// Request body for creating a memory
+#[derive(Debug, Deserialize)]
+pub struct CreateMemoryRequest {
+    /// Optional caller-chosen identifier; generated when absent
+    pub id: Option<String>,
+    /// Memory content
+    pub content: String,
+    /// Memory type name (defaults to `semantic`)
+    pub memory_type: Option<String>,
+    /// Optional precomputed embedding
+    pub embedding: Option<Vec<f32>>,
+    /// Custom metadata key/value pairs
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+}
+
+/// Request body for searching memories
+#[derive(Debug, Deserialize)]
+pub struct SearchRequest {
+    /// Free-text query, used when no vector is supplied
+    pub query: Option<String>,
+    /// Query embedding for vector similarity search
+    pub vector: Option<Vec<f32>>,
+    /// Maximum number of results
+    pub limit: Option<usize>,
+    /// Sampling request these results are retrieved for; registers them for
+    /// a later `/api/memories/attribution` call
+    pub request_id: Option<String>,
+}
+
+/// Response body for a search
+#[derive(Debug, Serialize)]
+pub struct SearchResponse {
+    /// Matching memories, best match first
+    pub results: Vec<MemoryNode>,
+}
+
+/// Request body for attributing an LLM output to retrieved memories
+#[derive(Debug, Deserialize)]
+pub struct AttributionRequest {
+    /// Id passed to the search that supplied the prompt's memories
+    pub request_id: String,
+    /// Text the model generated
+    pub output: String,
+}
+
+/// Response body for an attribution
+#[derive(Debug, Serialize)]
+pub struct AttributionResponse {
+    pub attributions: Vec<Attribution>,
+    /// Memories whose importance was adjusted
+    pub importance_updated: usize,
+}
+
+/// Query parameters for the dead-weight report
+#[derive(Debug, Deserialize)]
+pub struct DeadWeightQuery {
+    pub min_retrievals: Option<u64>,
+}
+
+/// Shared state of the memory API handlers
+pub struct ApiState<M: MemoryManager + 'static> {
+    pub manager: Arc<M>,
+    pub usage: Arc<UsageTracker>,
+}
+
+impl<M: MemoryManager + 'static> Clone for ApiState<M> {
+    fn clone(&self) -> Self {
+        Self {
+            manager: self.manager.clone(),
+            usage: self.usage.clone(),
+        }
+    }
+}
+
+/// Create the router for the memory API
+pub fn create_router<M>(memory_manager: Arc<M>, _config: &APIConfig) -> Router
+where
+    M: MemoryManager + 'static,
+{
+    let state = ApiState {
+        manager: memory_manager,
+        usage: Arc::new(UsageTracker::default()),
+    };
+    Router::new()
+        .route("/api/memories", post(create_memory::<M>))
+        .route("/api/memories/search", post(search_memories::<M>))
+        .route("/api/memories/attribution", post(attribute_output::<M>))
+        .route("/api/memories/usage/dead-weight", get(dead_weight::<M>))
+        .route(
+            "/api/memories/{id}",
+            get(get_memory::<M>).delete(delete_memory::<M>),
+        )
+        .with_state(state)
+}
+
+async fn create_memory<M>(
+    State(state): State<ApiState<M>>,
+    Json(request): Json<CreateMemoryRequest>,
+) -> Response
+where
+    M: MemoryManager + 'static,
+{
+    let memory_type = match request.memory_type.as_deref() {
+        Some(name) => match MemoryType::from_string(name) {
+            Ok(memory_type) => memory_type,
+            Err(_) => {
+                return error_response(
+                    StatusCode::BAD_REQUEST,
+                    format!("Unknown memory type: {}", name),
+                );
+            }
+        },
+        None => MemoryType::Semantic,
+    };
+
+    let mut memory = match request.id {
+        Some(id) => MemoryNode::with_id(id, request.content, memory_type),
+        None => MemoryNode::new(request.content, memory_type),
+    };
+    if let Some(embedding) = request.embedding {
+        memory = memory.with_embedding(embedding);
+    }
+    for (key, value) in request.metadata {
+        memory = memory.with_custom_metadata(key, value);
+    }
+
+    match state.manager.create_memory(memory).await {
+        Ok(created) => (StatusCode::CREATED, Json(created)).into_response(),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+async fn search_memories<M>(
+    State(state): State<ApiState<M>>,
+    Json(request): Json<SearchRequest>,
+) -> Response
+where
+    M: MemoryManager + 'static,
+{
+    let limit = request.limit.unwrap_or(DEFAULT_SEARCH_LIMIT);
+
+    let mut stream = match (request.vector, request.query) {
+        (Some(vector), _) => state.manager.search_by_vector(vector, limit),
+        (None, Some(query)) => state.manager.search_by_content(&query, limit),
+        (None, None) => {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                "Either query or vector must be supplied".to_string(),
+            );
+        }
+    };
+
+    let mut results = Vec::new();
+    while let Some(result) = stream.next().await {
+        match result {
+            Ok(memory) => results.push(memory),
+            Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        }
+    }
+
+    if let Some(request_id) = &request.request_id {
+        state.usage.record_retrieval(request_id, &results);
+    }
+    Json(SearchResponse { results }).into_response()
+}
+
+async fn attribute_output<M>(
+    State(state): State<ApiState<M>>,
+    Json(request): Json<AttributionRequest>,
+) -> Response
+where
+    M: MemoryManager + 'static,
+{
+    let Some(attributions) = state.usage.attribute(&request.request_id, &request.output) else {
+        return error_response(
+            StatusCode::NOT_FOUND,
+            format!("No pending retrieval for request {}", request.request_id),
+        );
+    };
+
+    let ids: Vec<String> = attributions.iter().map(|a| a.memory_id.clone()).collect();
+    match state
+        .usage
+        .apply_importance(state.manager.as_ref(), &ids)
+        .await
+    {
+        Ok(importance_updated) => Json(AttributionResponse {
+            attributions,
+            importance_updated,
+        })
+        .into_response(),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+async fn dead_weight<M>(
+    State(state): State<ApiState<M>>,
+    Query(query): Query<DeadWeightQuery>,
+) -> Response
+where
+    M: MemoryManager + 'static,
+{
+    Json(state.usage.dead_weight_report(query.min_retrievals)).into_response()
+}
+
+async fn get_memory<M>(State(state): State<ApiState<M>>, Path(id): Path<String>) -> Response
+where
+    M: MemoryManager + 'static,
+{
+    match state.manager.get_memory(&id).await {
+        Ok(Some(memory)) => Json(memory).into_response(),
+        Ok(None) => error_response(StatusCode::NOT_FOUND, format!("Memory not found: {}", id)),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+async fn delete_memory<M>(State(state): State<ApiState<M>>, Path(id): Path<String>) -> Response
+where
+    M: MemoryManager + 'static,
+{
+    match state.manager.delete_memory(&id).await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => error_response(StatusCode::NOT_FOUND, format!("Memory not found: {}", id)),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+fn error_response(status: StatusCode, message: String) -> Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
@@ -0,0 +1,249 @@
+//! Splitting long documents into chunks that fit a model's context.
+//!
+//! Chunk size follows the model's context window, leaving room for the
+//! prompt around the chunk and the model's reply. Chunks break at paragraph
+//! or sentence ends where possible and consecutive chunks can share a few
+//! tokens of overlap, so nothing straddling a boundary loses its context.
+//!
+//! Token counts come from a [`TokenCounter`]. [`ApproxTokenCounter`] is a
+//! tokenizer-free estimate that errs towards more tokens; plug in a real
+//! tokenizer through [`chunk_with`] where exact counts matter.
+
+/// Share of the context window a chunk may fill
+const CHUNK_SHARE: f32 = 0.5;
+
+/// Context window assumed for models not in [`CONTEXT_WINDOWS`]
+const DEFAULT_CONTEXT_TOKENS: usize = 8_192;
+
+/// Context windows by model name prefix, most specific first
+const CONTEXT_WINDOWS: &[(&str, usize)] = &[
+    ("claude-", 200_000),
+    ("gpt-4.1", 1_000_000),
+    ("gpt-4o", 128_000),
+    ("gpt-4-turbo", 128_000),
+    ("gpt-4-32k", 32_768),
+    ("gpt-4", 8_192),
+    ("gpt-3.5-turbo", 16_385),
+    ("o1", 200_000),
+    ("o3", 200_000),
+    ("text-embedding-", 8_191),
+    ("gemini-", 1_000_000),
+    ("llama-3", 128_000),
+    ("mistral", 32_768),
+];
+
+/// Counts the tokens a model would see for a piece of text
+pub trait TokenCounter {
+    fn count(&self, text: &str) -> usize;
+}
+
+/// Tokenizer-free estimate: about four characters per token for English
+/// text, never fewer tokens than words, and one token per non-ASCII char,
+/// since scripts such as CJK tokenize far more densely
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ApproxTokenCounter;
+
+impl TokenCounter for ApproxTokenCounter {
+    fn count(&self, text: &str) -> usize {
+        let ascii = text.chars().filter(char::is_ascii).count();
+        let other = text.chars().count() - ascii;
+        let words = text.split_whitespace().count();
+        ascii.div_ceil(4).max(words) + other
+    }
+}
+
+/// Context window of `model` in tokens
+pub fn context_tokens(model: &str) -> usize {
+    let model = model.to_ascii_lowercase();
+    let model = model.rsplit('/').next().unwrap_or(&model);
+    CONTEXT_WINDOWS
+        .iter()
+        .find(|(prefix, _)| model.starts_with(prefix))
+        .map_or(DEFAULT_CONTEXT_TOKENS, |(_, tokens)| *tokens)
+}
+
+/// Split `text` into chunks sized for `model`, with `overlap` tokens repeated
+/// between consecutive chunks
+pub fn chunk_for_model(text: &str, model: &str, overlap: usize) -> Vec<String> {
+    let max_tokens = (context_tokens(model) as f32 * CHUNK_SHARE) as usize;
+    chunk_with(text, max_tokens, overlap, &ApproxTokenCounter)
+}
+
+/// Split `text` into chunks of at most `max_tokens` as counted by `counter`,
+/// repeating up to `overlap` tokens between consecutive chunks. Overlap is
+/// capped at half a chunk so every chunk makes progress.
+pub fn chunk_with(
+    text: &str,
+    max_tokens: usize,
+    overlap: usize,
+    counter: &dyn TokenCounter,
+) -> Vec<String> {
+    let max_tokens = max_tokens.max(1);
+    let overlap = overlap.min(max_tokens / 2);
+
+    let segments = segments(text, max_tokens, counter);
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < segments.len() {
+        // Take as many segments as fit
+        let mut end = start;
+        let mut tokens = 0;
+        while end < segments.len() && (end == start || tokens + segments[end].1 <= max_tokens) {
+            tokens += segments[end].1;
+            end += 1;
+        }
+
+        // Prefer ending at a paragraph, then a sentence, in the chunk's second half
+        if end < segments.len() {
+            let half = start + (end - start) / 2;
+            let break_at = |is_break: fn(&str) -> bool| {
+                (half.max(start + 1)..end)
+                    .rev()
+                    .find(|&i| is_break(segments[i - 1].0))
+            };
+            if let Some(cut) = break_at(ends_paragraph).or_else(|| break_at(ends_sentence)) {
+                end = cut;
+            }
+        }
+
+        let chunk: String = segments[start..end].iter().map(|(s, _)| *s).collect();
+        let chunk = chunk.trim();
+        if !chunk.is_empty() {
+            chunks.push(chunk.to_string());
+        }
+        if end >= segments.len() {
+            break;
+        }
+
+        // Step back over up to `overlap` tokens, always moving forward
+        let mut next = end;
+        let mut carried = 0;
+        while next > start + 1 && carried + segments[next - 1].1 <= overlap {
+            carried += segments[next - 1].1;
+            next -= 1;
+        }
+        start = next;
+    }
+    chunks
+}
+
+/// Words with their trailing whitespace, and their token counts. Words too
+/// long for a chunk on their own are split by characters.
+fn segments<'a>(
+    text: &'a str,
+    max_tokens: usize,
+    counter: &dyn TokenCounter,
+) -> Vec<(&'a str, usize)> {
+    let mut segments = Vec::new();
+    let mut word_start = 0;
+    let mut in_space = false;
+    for (i, c) in text.char_indices() {
+        if c.is_whitespace() {
+            in_space = true;
+        } else if in_space {
+            push_segment(&text[word_start..i], max_tokens, counter, &mut segments);
+            word_start = i;
+            in_space = false;
+        }
+    }
+    if word_start < text.len() {
+        push_segment(&text[word_start..], max_tokens, counter, &mut segments);
+    }
+    segments
+}
+
+fn push_segment<'a>(
+    segment: &'a str,
+    max_tokens: usize,
+    counter: &dyn TokenCounter,
+    segments: &mut Vec<(&'a str, usize)>,
+) {
+    let tokens = counter.count(segment);
+    if tokens <= max_tokens {
+        segments.push((segment, tokens));
+        return;
+    }
+    let mut rest = segment;
+    while !rest.is_empty() {
+        // A prefix that fits, at least one character
+        let mut cut = rest.len();
+        while counter.count(&rest[..cut]) > max_tokens {
+            cut = rest[..cut]
+                .char_indices()
+                .nth(rest[..cut].chars().count() / 2)
+                .map_or(0, |(i, _)| i);
+            if cut == 0 {
+                cut = rest.chars().next().map_or(rest.len(), char::len_utf8);
+                break;
+            }
+        }
+        segments.push((&rest[..cut], counter.count(&rest[..cut])));
+        rest = &rest[cut..];
+    }
+}
+
+fn ends_paragraph(segment: &str) -> bool {
+    segment.matches('\n').count() >= 2
+}
+
+fn ends_sentence(segment: &str) -> bool {
+    let word = segment.trim_end();
+    word.ends_with(['.', '!', '?']) && word.len() < segment.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One token per word, so sizes in tests are easy to reason about
+    struct Words;
+
+    impl TokenCounter for Words {
+        fn count(&self, text: &str) -> usize {
+            text.split_whitespace().count().max(1)
+        }
+    }
+
+    #[test]
+    fn short_text_is_one_chunk() {
+        let chunks = chunk_with("a few words here", 10, 2, &Words);
+        assert_eq!(chunks, vec!["a few words here"]);
+    }
+
+    #[test]
+    fn chunks_respect_the_limit_and_overlap() {
+        let text = (1..=20)
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let chunks = chunk_with(&text, 8, 2, &Words);
+        assert!(chunks.iter().all(|c| Words.count(c) <= 8));
+        assert_eq!(chunks[0], "1 2 3 4 5 6 7 8");
+        assert!(chunks[1].starts_with("7 8 9"));
+        assert!(chunks.last().unwrap().ends_with("20"));
+    }
+
+    #[test]
+    fn prefers_sentence_boundaries() {
+        let text = "one two three four five. six seven eight nine ten";
+        let chunks = chunk_with(text, 7, 0, &Words);
+        assert_eq!(chunks[0], "one two three four five.");
+        assert_eq!(chunks[1], "six seven eight nine ten");
+    }
+
+    #[test]
+    fn splits_oversized_words() {
+        let word = "x".repeat(40);
+        let chunks = chunk_with(&word, 3, 0, &ApproxTokenCounter);
+        assert!(chunks.len() > 1);
+        assert_eq!(chunks.concat(), word);
+    }
+
+    #[test]
+    fn context_windows_by_model() {
+        assert_eq!(context_tokens("claude-3-5-sonnet-latest"), 200_000);
+        assert_eq!(context_tokens("openai/gpt-4o-mini"), 128_000);
+        assert_eq!(context_tokens("gpt-4"), 8_192);
+        assert_eq!(context_tokens("unknown-model"), DEFAULT_CONTEXT_TOKENS);
+    }
+}
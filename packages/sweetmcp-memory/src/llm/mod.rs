@@ -10,11 +10,13 @@ use tokio::sync::oneshot;
 
 // Re-export types
 pub use self::anthropic::AnthropicProvider;
+pub use self::chunking::{chunk_for_model, chunk_with, ApproxTokenCounter, TokenCounter};
 pub use self::openai::OpenAIProvider;
 pub use self::completion::CompletionService;
 pub use self::content_analyzer::{LLMContentAnalyzer, ContentAnalysis, RelationshipAnalysis};
 
 pub mod anthropic;
+pub mod chunking;
 pub mod openai;
 pub mod completion;
 pub mod content_analyzer;
@@ -0,0 +1,333 @@
+//! Usage analytics for retrieved memories
+//!
+//! Retrieval only says a memory looked relevant; attribution says whether the
+//! LLM actually used it. The sampling layer registers the memories it put into
+//! a prompt under a request id, then hands back the generated output. Each
+//! memory is marked referenced when the output cites its id or reuses enough
+//! of its wording. The running ratio of references to retrievals feeds
+//! `metadata.importance`, and memories that are retrieved again and again but
+//! never referenced show up in the dead-weight report.
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use crate::memory::{MemoryManager, MemoryNode};
+use crate::utils::Result;
+
+/// Tuning knobs for attribution and importance feedback
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttributionConfig {
+    /// Fraction of a memory's word trigrams that must reappear in the output
+    pub overlap_threshold: f32,
+    /// Retrievals needed before a memory's usage says anything
+    pub min_retrievals: u64,
+    /// Weight of observed utilization when blending into importance
+    pub learning_rate: f32,
+    /// How long a registered retrieval waits for its output
+    pub pending_ttl: Duration,
+}
+
+impl Default for AttributionConfig {
+    fn default() -> Self {
+        Self {
+            overlap_threshold: 0.3,
+            min_retrievals: 5,
+            learning_rate: 0.2,
+            pending_ttl: Duration::from_secs(600),
+        }
+    }
+}
+
+/// Running usage counters for one memory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageStats {
+    /// Times the memory was put into a prompt
+    pub retrieved: u64,
+    /// Times the output actually used it
+    pub referenced: u64,
+    pub last_retrieved: Option<DateTime<Utc>>,
+    pub last_referenced: Option<DateTime<Utc>>,
+}
+
+impl UsageStats {
+    /// Smoothed share of retrievals that were referenced (0.5 with no data)
+    pub fn utilization(&self) -> f32 {
+        (self.referenced as f32 + 1.0) / (self.retrieved as f32 + 2.0)
+    }
+}
+
+/// Whether one retrieved memory contributed to an output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attribution {
+    pub memory_id: String,
+    /// Share of the memory's trigrams found in the output
+    pub overlap: f32,
+    /// Output mentions the memory id verbatim
+    pub cited: bool,
+    pub referenced: bool,
+}
+
+/// A memory that keeps being retrieved without ever contributing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadWeightEntry {
+    pub memory_id: String,
+    pub retrieved: u64,
+    pub last_retrieved: Option<DateTime<Utc>>,
+}
+
+/// Dead-weight memories, most retrieved first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadWeightReport {
+    pub generated_at: DateTime<Utc>,
+    /// Memories with any recorded usage
+    pub tracked: usize,
+    pub min_retrievals: u64,
+    pub dead_weight: Vec<DeadWeightEntry>,
+}
+
+struct PendingContext {
+    memories: Vec<(String, HashSet<String>)>,
+    registered: Instant,
+}
+
+/// Lock-free tracker of retrieval vs. reference counts
+pub struct UsageTracker {
+    config: AttributionConfig,
+    pending: DashMap<String, PendingContext>,
+    stats: DashMap<String, UsageStats>,
+}
+
+impl UsageTracker {
+    /// Create a tracker with the given configuration
+    pub fn new(config: AttributionConfig) -> Self {
+        Self {
+            config,
+            pending: DashMap::new(),
+            stats: DashMap::new(),
+        }
+    }
+
+    /// Register the memories placed into the prompt for `request_id`
+    pub fn record_retrieval(&self, request_id: &str, memories: &[MemoryNode]) {
+        self.prune_pending();
+        let now = Utc::now();
+        for memory in memories {
+            let mut stats = self.stats.entry(memory.id.clone()).or_default();
+            stats.retrieved += 1;
+            stats.last_retrieved = Some(now);
+        }
+        self.pending.insert(
+            request_id.to_string(),
+            PendingContext {
+                memories: memories
+                    .iter()
+                    .map(|m| (m.id.clone(), shingles(&m.content)))
+                    .collect(),
+                registered: Instant::now(),
+            },
+        );
+    }
+
+    /// Attribute `output` to the memories registered for `request_id`.
+    ///
+    /// Returns `None` when the request is unknown or has expired.
+    pub fn attribute(&self, request_id: &str, output: &str) -> Option<Vec<Attribution>> {
+        let (_, context) = self.pending.remove(request_id)?;
+        let output_lower = output.to_lowercase();
+        let output_shingles = shingles(output);
+        let now = Utc::now();
+
+        let attributions = context
+            .memories
+            .into_iter()
+            .map(|(memory_id, memory_shingles)| {
+                let overlap = if memory_shingles.is_empty() {
+                    0.0
+                } else {
+                    memory_shingles.intersection(&output_shingles).count() as f32
+                        / memory_shingles.len() as f32
+                };
+                let cited = output_lower.contains(&memory_id.to_lowercase());
+                let referenced = cited || overlap >= self.config.overlap_threshold;
+                if referenced {
+                    let mut stats = self.stats.entry(memory_id.clone()).or_default();
+                    stats.referenced += 1;
+                    stats.last_referenced = Some(now);
+                }
+                Attribution {
+                    memory_id,
+                    overlap,
+                    cited,
+                    referenced,
+                }
+            })
+            .collect();
+        Some(attributions)
+    }
+
+    /// Usage counters for one memory
+    pub fn stats(&self, memory_id: &str) -> Option<UsageStats> {
+        self.stats.get(memory_id).map(|s| s.clone())
+    }
+
+    /// Blend observed utilization into `memory.metadata.importance`.
+    ///
+    /// Returns whether the importance changed; memories with fewer than
+    /// `min_retrievals` retrievals are left alone.
+    pub fn adjust_importance(&self, memory: &mut MemoryNode) -> bool {
+        let Some(stats) = self.stats(&memory.id) else {
+            return false;
+        };
+        if stats.retrieved < self.config.min_retrievals {
+            return false;
+        }
+        let rate = self.config.learning_rate.clamp(0.0, 1.0);
+        let current = memory.metadata.importance;
+        let updated = (current * (1.0 - rate) + stats.utilization() * rate).clamp(0.0, 1.0);
+        if (updated - current).abs() < f32::EPSILON {
+            return false;
+        }
+        memory.metadata.importance = updated;
+        true
+    }
+
+    /// Persist importance updates for `memory_ids`; returns how many changed
+    pub async fn apply_importance<M>(&self, manager: &M, memory_ids: &[String]) -> Result<usize>
+    where
+        M: MemoryManager + ?Sized,
+    {
+        let mut updated = 0;
+        for id in memory_ids {
+            let Some(mut memory) = manager.get_memory(id).await? else {
+                continue;
+            };
+            if self.adjust_importance(&mut memory) {
+                manager.update_memory(memory).await?;
+                updated += 1;
+            }
+        }
+        Ok(updated)
+    }
+
+    /// Memories retrieved at least `min_retrievals` times and never referenced
+    pub fn dead_weight_report(&self, min_retrievals: Option<u64>) -> DeadWeightReport {
+        let min_retrievals = min_retrievals.unwrap_or(self.config.min_retrievals);
+        let mut dead_weight: Vec<DeadWeightEntry> = self
+            .stats
+            .iter()
+            .filter(|s| s.referenced == 0 && s.retrieved >= min_retrievals)
+            .map(|s| DeadWeightEntry {
+                memory_id: s.key().clone(),
+                retrieved: s.retrieved,
+                last_retrieved: s.last_retrieved,
+            })
+            .collect();
+        dead_weight.sort_by(|a, b| {
+            b.retrieved
+                .cmp(&a.retrieved)
+                .then_with(|| a.memory_id.cmp(&b.memory_id))
+        });
+
+        DeadWeightReport {
+            generated_at: Utc::now(),
+            tracked: self.stats.len(),
+            min_retrievals,
+            dead_weight,
+        }
+    }
+
+    /// Drop registered retrievals whose output never arrived
+    fn prune_pending(&self) {
+        let ttl = self.config.pending_ttl;
+        self.pending.retain(|_, ctx| ctx.registered.elapsed() < ttl);
+    }
+}
+
+impl Default for UsageTracker {
+    fn default() -> Self {
+        Self::new(AttributionConfig::default())
+    }
+}
+
+/// Lowercased word trigrams; plain words for texts shorter than three words
+fn shingles(text: &str) -> HashSet<String> {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    if words.len() < 3 {
+        return words.into_iter().collect();
+    }
+    words.windows(3).map(|w| w.join(" ")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MemoryType;
+
+    fn memory(id: &str, content: &str) -> MemoryNode {
+        MemoryNode::with_id(id.to_string(), content.to_string(), MemoryType::Semantic)
+    }
+
+    #[test]
+    fn attributes_by_overlap_and_citation() {
+        let tracker = UsageTracker::default();
+        let memories = [
+            memory(
+                "m1",
+                "The deploy window for the gateway is Tuesday at noon UTC",
+            ),
+            memory("m2", "Alice prefers tabs over spaces in Rust code"),
+            memory("m3", "Bob owns the billing service"),
+        ];
+        tracker.record_retrieval("req", &memories);
+
+        let out = "Per m3, ask Bob. The deploy window for the gateway is Tuesday at noon.";
+        let attributions = tracker.attribute("req", out).unwrap();
+        let referenced: Vec<_> = attributions
+            .iter()
+            .filter(|a| a.referenced)
+            .map(|a| a.memory_id.as_str())
+            .collect();
+        assert_eq!(referenced, ["m1", "m3"]);
+        assert!(attributions[2].cited);
+
+        // A request is attributed once
+        assert!(tracker.attribute("req", out).is_none());
+        assert_eq!(tracker.stats("m2").unwrap().referenced, 0);
+        assert_eq!(tracker.stats("m1").unwrap().referenced, 1);
+    }
+
+    #[test]
+    fn dead_weight_and_importance_feedback() {
+        let tracker = UsageTracker::default();
+        let used = memory("used", "rotate the signing key every ninety days");
+        let unused = memory("unused", "the office plant needs water on fridays");
+
+        for i in 0..5 {
+            let req = format!("req-{}", i);
+            tracker.record_retrieval(&req, &[used.clone(), unused.clone()]);
+            tracker.attribute(
+                &req,
+                "Remember to rotate the signing key every ninety days.",
+            );
+        }
+
+        let report = tracker.dead_weight_report(None);
+        assert_eq!(report.tracked, 2);
+        assert_eq!(report.dead_weight.len(), 1);
+        assert_eq!(report.dead_weight[0].memory_id, "unused");
+        assert_eq!(report.dead_weight[0].retrieved, 5);
+
+        let (mut used, mut unused) = (used, unused);
+        assert!(tracker.adjust_importance(&mut used));
+        assert!(tracker.adjust_importance(&mut unused));
+        assert!(used.metadata.importance > 0.5);
+        assert!(unused.metadata.importance < 0.5);
+    }
+}
@@ -1,5 +1,6 @@
 //! Memory module that provides the core memory functionality
 
+pub mod attribution;
 pub mod caching;
 pub mod episodic;
 pub mod evolution;
@@ -29,6 +30,7 @@ pub mod storage_coordinator;
 pub mod tests;
 
 // Re-export main types
+pub use attribution::{AttributionConfig, DeadWeightReport, UsageTracker};
 pub use episodic::*;
 pub use evolution::*;
 pub use history::*;
@@ -0,0 +1,768 @@
+//! Scheduled, encrypted backups of the memory store to S3-compatible storage
+//!
+//! A backup is one object per snapshot, `<prefix>/memory-<UTC timestamp>.snap`,
+//! holding every memory and relationship as JSON sealed with AES-256-GCM.
+//! Requests are signed with AWS Signature V4 and use path-style URLs, so the
+//! same code talks to AWS S3, MinIO, R2 or Ceph RGW. Retention is enforced
+//! after every successful upload.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use base64::Engine;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use ring::aead::{AES_256_GCM, Aad, LessSafeKey, Nonce, UnboundKey};
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::{digest, hmac};
+use serde::{Deserialize, Serialize};
+
+use crate::memory::{MemoryManager, MemoryNode, MemoryRelationship};
+use crate::migration::{MigrationError, Result};
+
+/// Header of every sealed snapshot; also bound as AEAD associated data
+const MAGIC: &[u8] = b"SMB1";
+const NONCE_LEN: usize = 12;
+const KEY_TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+/// Page size used when reading the store
+const PAGE: usize = 500;
+/// Snapshot layout version, bumped on incompatible changes
+const FORMAT_VERSION: u32 = 1;
+
+/// Where and how to back up
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupConfig {
+    /// S3 endpoint, e.g. `https://s3.us-east-1.amazonaws.com` or `http://minio:9000`
+    pub endpoint: String,
+    #[serde(default = "default_region")]
+    pub region: String,
+    pub bucket: String,
+    /// Key prefix inside the bucket
+    #[serde(default)]
+    pub prefix: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// Base64 of a 32-byte AES-256-GCM key
+    pub encryption_key: String,
+    /// Time between scheduled backups
+    #[serde(default = "default_interval_secs")]
+    pub interval_secs: u64,
+    #[serde(default)]
+    pub retention: RetentionPolicy,
+}
+
+/// Which snapshots survive a retention pass
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    /// The newest N snapshots are always kept
+    #[serde(default = "default_keep_last")]
+    pub keep_last: usize,
+    /// Snapshots older than this are deleted unless among the newest `keep_last`
+    #[serde(default)]
+    pub max_age_days: Option<u32>,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            keep_last: default_keep_last(),
+            max_age_days: Some(30),
+        }
+    }
+}
+
+fn default_region() -> String {
+    "us-east-1".to_string()
+}
+
+fn default_interval_secs() -> u64 {
+    6 * 60 * 60
+}
+
+fn default_keep_last() -> usize {
+    7
+}
+
+/// Everything in the store at one point in time
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MemorySnapshot {
+    pub format_version: u32,
+    pub created_at: DateTime<Utc>,
+    pub memories: Vec<MemoryNode>,
+    pub relationships: Vec<MemoryRelationship>,
+}
+
+/// A snapshot object in the bucket
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub key: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Outcome of one backup run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupSummary {
+    pub backup: BackupInfo,
+    pub memories: usize,
+    pub relationships: usize,
+    pub bytes: usize,
+    /// Snapshots removed by the retention pass
+    pub pruned: Vec<String>,
+}
+
+/// Outcome of a restore
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestoreSummary {
+    pub backup: BackupInfo,
+    pub memories: usize,
+    pub relationships: usize,
+    /// Relationships the store refused, usually because they already exist
+    pub relationships_skipped: usize,
+}
+
+/// Snapshots the memory store to object storage and restores it
+pub struct BackupManager {
+    config: BackupConfig,
+    key: LessSafeKey,
+    http: reqwest::Client,
+}
+
+impl BackupManager {
+    /// Validate the configuration and prepare the cipher and HTTP client
+    pub fn new(config: BackupConfig) -> Result<Self> {
+        let key = base64::engine::general_purpose::STANDARD
+            .decode(config.encryption_key.trim())
+            .map_err(|e| backup_error(format!("encryption_key is not base64: {}", e)))?;
+        let key = UnboundKey::new(&AES_256_GCM, &key)
+            .map_err(|_| backup_error("encryption_key must be 32 bytes"))?;
+        reqwest::Url::parse(&config.endpoint)
+            .map_err(|e| backup_error(format!("invalid endpoint {}: {}", config.endpoint, e)))?;
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(300))
+            .build()
+            .map_err(|e| backup_error(e.to_string()))?;
+
+        Ok(Self {
+            config,
+            key: LessSafeKey::new(key),
+            http,
+        })
+    }
+
+    /// Back up `manager` once, then apply the retention policy
+    pub async fn run_once<M>(&self, manager: &M) -> Result<BackupSummary>
+    where
+        M: MemoryManager + ?Sized,
+    {
+        let snapshot = snapshot(manager).await?;
+        let created_at = snapshot.created_at;
+        let (memories, relationships) = (snapshot.memories.len(), snapshot.relationships.len());
+        let sealed = self.seal(&serde_json::to_vec(&snapshot)?)?;
+        let bytes = sealed.len();
+
+        let key = snapshot_key(&self.config.prefix, created_at);
+        self.store(&self.config.bucket).put(&key, sealed).await?;
+        tracing::info!(
+            "Backed up {} memories and {} relationships to s3://{}/{}",
+            memories,
+            relationships,
+            self.config.bucket,
+            key
+        );
+
+        let pruned = self.enforce_retention(Utc::now()).await?;
+        Ok(BackupSummary {
+            backup: BackupInfo { key, created_at },
+            memories,
+            relationships,
+            bytes,
+            pruned,
+        })
+    }
+
+    /// Run a backup every `interval_secs` until the task is aborted
+    pub fn spawn_scheduled<M>(self: Arc<Self>, manager: Arc<M>) -> tokio::task::JoinHandle<()>
+    where
+        M: MemoryManager + ?Sized + 'static,
+    {
+        tokio::spawn(async move {
+            let period = Duration::from_secs(self.config.interval_secs.max(60));
+            let mut ticker = tokio::time::interval(period);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.run_once(manager.as_ref()).await {
+                    tracing::error!("Scheduled memory backup failed: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Snapshots under the configured prefix, oldest first
+    pub async fn list_backups(&self) -> Result<Vec<BackupInfo>> {
+        list_snapshots(&self.store(&self.config.bucket), &self.config.prefix).await
+    }
+
+    /// Delete snapshots the retention policy no longer covers
+    pub async fn enforce_retention(&self, now: DateTime<Utc>) -> Result<Vec<String>> {
+        let backups = self.list_backups().await?;
+        let expired = expired_backups(&backups, &self.config.retention, now);
+        let store = self.store(&self.config.bucket);
+        for key in &expired {
+            store.delete(key).await?;
+            tracing::info!("Pruned memory backup {}", key);
+        }
+        Ok(expired)
+    }
+
+    /// Restore the newest snapshot at or before `timestamp` from `url`
+    /// (`s3://bucket/prefix`) into `manager`.
+    ///
+    /// Memories that still exist are overwritten with their backed-up state;
+    /// nothing that was created after the snapshot is deleted.
+    pub async fn restore_from_backup<M>(
+        &self,
+        manager: &M,
+        url: &str,
+        timestamp: DateTime<Utc>,
+    ) -> Result<RestoreSummary>
+    where
+        M: MemoryManager + ?Sized,
+    {
+        let (bucket, prefix) = parse_s3_url(url)?;
+        let store = self.store(&bucket);
+        let backup = list_snapshots(&store, &prefix)
+            .await?
+            .into_iter()
+            .rev()
+            .find(|b| b.created_at <= timestamp)
+            .ok_or_else(|| {
+                backup_error(format!("no backup in {} at or before {}", url, timestamp))
+            })?;
+
+        let sealed = store.get(&backup.key).await?;
+        let snapshot: MemorySnapshot = serde_json::from_slice(&self.open(sealed)?)?;
+        if snapshot.format_version > FORMAT_VERSION {
+            return Err(MigrationError::SchemaMismatch {
+                expected: format!("<= {}", FORMAT_VERSION),
+                found: snapshot.format_version.to_string(),
+            });
+        }
+
+        let memories = snapshot.memories.len();
+        for memory in snapshot.memories {
+            let exists = manager.get_memory(&memory.id).await.map_err(db_error)?;
+            let result = match exists {
+                Some(_) => manager.update_memory(memory).await,
+                None => manager.create_memory(memory).await,
+            };
+            result.map_err(db_error)?;
+        }
+        let mut relationships_skipped = 0;
+        let relationships = snapshot.relationships.len();
+        for relationship in snapshot.relationships {
+            if manager.create_relationship(relationship).await.is_err() {
+                relationships_skipped += 1;
+            }
+        }
+        tracing::info!(
+            "Restored {} memories from {} (taken {})",
+            memories,
+            backup.key,
+            backup.created_at
+        );
+
+        Ok(RestoreSummary {
+            backup,
+            memories,
+            relationships: relationships - relationships_skipped,
+            relationships_skipped,
+        })
+    }
+
+    fn store(&self, bucket: &str) -> S3Store<'_> {
+        S3Store {
+            http: &self.http,
+            config: &self.config,
+            bucket: bucket.to_string(),
+        }
+    }
+
+    fn seal(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| backup_error("no randomness for nonce"))?;
+        let mut body = plaintext.to_vec();
+        self.key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(MAGIC),
+                &mut body,
+            )
+            .map_err(|_| backup_error("encryption failed"))?;
+
+        let mut out = Vec::with_capacity(MAGIC.len() + NONCE_LEN + body.len());
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&body);
+        Ok(out)
+    }
+
+    fn open(&self, sealed: Vec<u8>) -> Result<Vec<u8>> {
+        if sealed.len() < MAGIC.len() + NONCE_LEN || !sealed.starts_with(MAGIC) {
+            return Err(MigrationError::UnsupportedFormat(
+                "not a memory backup".to_string(),
+            ));
+        }
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce.copy_from_slice(&sealed[MAGIC.len()..MAGIC.len() + NONCE_LEN]);
+        let mut body = sealed[MAGIC.len() + NONCE_LEN..].to_vec();
+        let plaintext = self
+            .key
+            .open_in_place(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(MAGIC),
+                &mut body,
+            )
+            .map_err(|_| backup_error("decryption failed (wrong key or corrupted backup)"))?;
+        Ok(plaintext.to_vec())
+    }
+}
+
+/// Read every memory and relationship from the store
+async fn snapshot<M>(manager: &M) -> Result<MemorySnapshot>
+where
+    M: MemoryManager + ?Sized,
+{
+    let created_at = Utc::now();
+    let mut memories = Vec::new();
+    loop {
+        let page = manager
+            .list_memories(PAGE, memories.len(), None)
+            .await
+            .map_err(db_error)?;
+        let done = page.len() < PAGE;
+        memories.extend(page);
+        if done {
+            break;
+        }
+    }
+    let mut relationships = Vec::new();
+    loop {
+        let page = manager
+            .list_relationships(PAGE, relationships.len(), None)
+            .await
+            .map_err(db_error)?;
+        let done = page.len() < PAGE;
+        relationships.extend(page);
+        if done {
+            break;
+        }
+    }
+
+    Ok(MemorySnapshot {
+        format_version: FORMAT_VERSION,
+        created_at,
+        memories,
+        relationships,
+    })
+}
+
+fn snapshot_key(prefix: &str, created_at: DateTime<Utc>) -> String {
+    let name = format!("memory-{}.snap", created_at.format(KEY_TIME_FORMAT));
+    match prefix.trim_matches('/') {
+        "" => name,
+        prefix => format!("{}/{}", prefix, name),
+    }
+}
+
+/// Timestamp encoded in a snapshot key, if it is one
+fn parse_snapshot_key(key: &str) -> Option<DateTime<Utc>> {
+    let name = key.rsplit('/').next()?;
+    let stamp = name.strip_prefix("memory-")?.strip_suffix(".snap")?;
+    NaiveDateTime::parse_from_str(stamp, KEY_TIME_FORMAT)
+        .ok()
+        .map(|t| t.and_utc())
+}
+
+/// Keys to delete: beyond the newest `keep_last` and older than `max_age_days`
+fn expired_backups(
+    backups: &[BackupInfo],
+    policy: &RetentionPolicy,
+    now: DateTime<Utc>,
+) -> Vec<String> {
+    let Some(max_age_days) = policy.max_age_days else {
+        return Vec::new();
+    };
+    let cutoff = now - chrono::Duration::days(max_age_days as i64);
+    let mut newest_first: Vec<&BackupInfo> = backups.iter().collect();
+    newest_first.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    newest_first
+        .into_iter()
+        .skip(policy.keep_last)
+        .filter(|b| b.created_at < cutoff)
+        .map(|b| b.key.clone())
+        .collect()
+}
+
+fn parse_s3_url(url: &str) -> Result<(String, String)> {
+    let rest = url
+        .strip_prefix("s3://")
+        .ok_or_else(|| backup_error(format!("backup url must start with s3://: {}", url)))?;
+    let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+    if bucket.is_empty() {
+        return Err(backup_error(format!("backup url has no bucket: {}", url)));
+    }
+    Ok((bucket.to_string(), prefix.trim_matches('/').to_string()))
+}
+
+async fn list_snapshots(store: &S3Store<'_>, prefix: &str) -> Result<Vec<BackupInfo>> {
+    let prefix = match prefix.trim_matches('/') {
+        "" => String::new(),
+        prefix => format!("{}/", prefix),
+    };
+    let mut backups: Vec<BackupInfo> = store
+        .list(&prefix)
+        .await?
+        .into_iter()
+        .filter_map(|key| parse_snapshot_key(&key).map(|created_at| BackupInfo { key, created_at }))
+        .collect();
+    backups.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+    Ok(backups)
+}
+
+fn backup_error(msg: impl Into<String>) -> MigrationError {
+    MigrationError::Backup(msg.into())
+}
+
+fn db_error(e: crate::utils::error::Error) -> MigrationError {
+    MigrationError::DatabaseError(e.to_string())
+}
+
+/// Minimal SigV4-signed S3 client for one bucket
+struct S3Store<'a> {
+    http: &'a reqwest::Client,
+    config: &'a BackupConfig,
+    bucket: String,
+}
+
+impl S3Store<'_> {
+    async fn put(&self, key: &str, body: Vec<u8>) -> Result<()> {
+        self.send(reqwest::Method::PUT, key, &[], body).await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>> {
+        self.send(reqwest::Method::GET, key, &[], Vec::new()).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.send(reqwest::Method::DELETE, key, &[], Vec::new())
+            .await?;
+        Ok(())
+    }
+
+    /// All keys under `prefix` (ListObjectsV2, following continuation tokens)
+    async fn list(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut keys = Vec::new();
+        let mut token: Option<String> = None;
+        loop {
+            let mut query = vec![
+                ("list-type", "2".to_string()),
+                ("prefix", prefix.to_string()),
+            ];
+            if let Some(token) = &token {
+                query.push(("continuation-token", token.clone()));
+            }
+            let body = self
+                .send(reqwest::Method::GET, "", &query, Vec::new())
+                .await?;
+            let xml = String::from_utf8_lossy(&body);
+            keys.extend(xml_values(&xml, "Key"));
+            let truncated =
+                xml_values(&xml, "IsTruncated").first().map(String::as_str) == Some("true");
+            token = xml_values(&xml, "NextContinuationToken").into_iter().next();
+            if !truncated || token.is_none() {
+                return Ok(keys);
+            }
+        }
+    }
+
+    async fn send(
+        &self,
+        method: reqwest::Method,
+        key: &str,
+        query: &[(&str, String)],
+        body: Vec<u8>,
+    ) -> Result<Vec<u8>> {
+        let endpoint =
+            reqwest::Url::parse(&self.config.endpoint).map_err(|e| backup_error(e.to_string()))?;
+        let host = match endpoint.port() {
+            Some(port) => format!("{}:{}", endpoint.host_str().unwrap_or_default(), port),
+            None => endpoint.host_str().unwrap_or_default().to_string(),
+        };
+        let base_path = endpoint.path().trim_end_matches('/');
+        let mut path = format!("{}/{}", base_path, uri_encode(&self.bucket, false));
+        if !key.is_empty() {
+            path.push('/');
+            path.push_str(&uri_encode(key, true));
+        }
+        let mut sorted: Vec<(String, String)> = query
+            .iter()
+            .map(|(k, v)| (uri_encode(k, false), uri_encode(v, false)))
+            .collect();
+        sorted.sort();
+        let query_string = sorted
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<_>>()
+            .join("&");
+
+        let now = Utc::now();
+        let headers = sign_v4(
+            &SigningInput {
+                method: method.as_str(),
+                host: &host,
+                path: &path,
+                query: &query_string,
+                payload: &body,
+                region: &self.config.region,
+                access_key_id: &self.config.access_key_id,
+                secret_access_key: &self.config.secret_access_key,
+            },
+            now,
+        );
+
+        let mut url = format!("{}://{}{}", endpoint.scheme(), host, path);
+        if !query_string.is_empty() {
+            url.push('?');
+            url.push_str(&query_string);
+        }
+        let mut request = self.http.request(method.clone(), &url).body(body);
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| backup_error(format!("{} {}: {}", method, url, e)))?;
+        let status = response.status();
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| backup_error(e.to_string()))?;
+        if !status.is_success() {
+            let detail = xml_values(&String::from_utf8_lossy(&bytes), "Message")
+                .into_iter()
+                .next()
+                .unwrap_or_default();
+            return Err(backup_error(format!(
+                "{} {}: {} {}",
+                method, url, status, detail
+            )));
+        }
+        Ok(bytes.to_vec())
+    }
+}
+
+struct SigningInput<'a> {
+    method: &'a str,
+    host: &'a str,
+    /// Already URI-encoded
+    path: &'a str,
+    /// Already canonical (sorted and encoded)
+    query: &'a str,
+    payload: &'a [u8],
+    region: &'a str,
+    access_key_id: &'a str,
+    secret_access_key: &'a str,
+}
+
+/// Headers (`x-amz-date`, `x-amz-content-sha256`, `authorization`) for an
+/// AWS Signature Version 4 request to S3.
+fn sign_v4(input: &SigningInput<'_>, now: DateTime<Utc>) -> Vec<(&'static str, String)> {
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+    let payload_hash = hex(digest::digest(&digest::SHA256, input.payload).as_ref());
+    let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+    let canonical_request = format!(
+        "{}\n{}\n{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+        input.method,
+        input.path,
+        input.query,
+        input.host,
+        payload_hash,
+        amz_date,
+        signed_headers,
+        payload_hash
+    );
+    let scope = format!("{}/{}/s3/aws4_request", date, input.region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex(digest::digest(&digest::SHA256, canonical_request.as_bytes()).as_ref())
+    );
+
+    let mut signing_key = format!("AWS4{}", input.secret_access_key).into_bytes();
+    for part in [date.as_str(), input.region, "s3", "aws4_request"] {
+        signing_key = hmac_sha256(&signing_key, part.as_bytes());
+    }
+    let signature = hex(&hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+
+    vec![
+        ("x-amz-date", amz_date),
+        ("x-amz-content-sha256", payload_hash),
+        (
+            "authorization",
+            format!(
+                "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                input.access_key_id, scope, signed_headers, signature
+            ),
+        ),
+    ]
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, key), data)
+        .as_ref()
+        .to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// RFC 3986 encoding as SigV4 expects; `/` is kept in object keys
+fn uri_encode(value: &str, keep_slash: bool) -> String {
+    let mut out = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char)
+            }
+            b'/' if keep_slash => out.push('/'),
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+/// Text of every `<tag>…</tag>` in a (flat, well-formed) S3 XML response
+fn xml_values(xml: &str, tag: &str) -> Vec<String> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let mut out = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        rest = &rest[start + open.len()..];
+        let Some(end) = rest.find(&close) else {
+            break;
+        };
+        out.push(
+            rest[..end]
+                .replace("&lt;", "<")
+                .replace("&gt;", ">")
+                .replace("&quot;", "\"")
+                .replace("&apos;", "'")
+                .replace("&amp;", "&"),
+        );
+        rest = &rest[end + close.len()..];
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager() -> BackupManager {
+        BackupManager::new(BackupConfig {
+            endpoint: "http://127.0.0.1:9000".to_string(),
+            region: default_region(),
+            bucket: "memories".to_string(),
+            prefix: "prod".to_string(),
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "secret".to_string(),
+            encryption_key: base64::engine::general_purpose::STANDARD.encode([7u8; 32]),
+            interval_secs: default_interval_secs(),
+            retention: RetentionPolicy::default(),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn seal_round_trip_and_tamper_detection() {
+        let backups = manager();
+        let sealed = backups.seal(b"{\"memories\":[]}").unwrap();
+        assert!(sealed.starts_with(MAGIC));
+        assert_eq!(backups.open(sealed.clone()).unwrap(), b"{\"memories\":[]}");
+
+        let mut tampered = sealed;
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        assert!(backups.open(tampered).is_err());
+        assert!(backups.open(b"plain json".to_vec()).is_err());
+    }
+
+    #[test]
+    fn snapshot_keys_and_urls() {
+        let ts = DateTime::parse_from_rfc3339("2026-03-01T04:05:06Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let key = snapshot_key("/prod/", ts);
+        assert_eq!(key, "prod/memory-20260301T040506Z.snap");
+        assert_eq!(parse_snapshot_key(&key), Some(ts));
+        assert_eq!(parse_snapshot_key("prod/notes.txt"), None);
+
+        assert_eq!(
+            parse_s3_url("s3://memories/prod/").unwrap(),
+            ("memories".to_string(), "prod".to_string())
+        );
+        assert!(parse_s3_url("https://memories").is_err());
+    }
+
+    #[test]
+    fn retention_keeps_newest_and_recent() {
+        let now = Utc::now();
+        let backups: Vec<BackupInfo> = (0..10)
+            .map(|days| {
+                let created_at = now - chrono::Duration::days(days * 10);
+                BackupInfo {
+                    key: snapshot_key("", created_at),
+                    created_at,
+                }
+            })
+            .collect();
+        let policy = RetentionPolicy {
+            keep_last: 3,
+            max_age_days: Some(45),
+        };
+        // Only the 50..=90 day old snapshots are past the cutoff, and none of
+        // them is among the newest three
+        let expired = expired_backups(&backups, &policy, now);
+        assert_eq!(expired.len(), 5);
+        assert!(expired.contains(&backups[9].key));
+        assert!(!expired.contains(&backups[4].key));
+
+        let keep_all = RetentionPolicy {
+            keep_last: 3,
+            max_age_days: None,
+        };
+        assert!(expired_backups(&backups, &keep_all, now).is_empty());
+    }
+
+    #[test]
+    fn list_response_parsing() {
+        let xml = "<ListBucketResult><Contents><Key>prod/a&amp;b.snap</Key></Contents>\
+                   <IsTruncated>true</IsTruncated>\
+                   <NextContinuationToken>tok</NextContinuationToken></ListBucketResult>";
+        assert_eq!(xml_values(xml, "Key"), ["prod/a&b.snap"]);
+        assert_eq!(xml_values(xml, "NextContinuationToken"), ["tok"]);
+        assert_eq!(
+            uri_encode("prod/memory 1.snap", true),
+            "prod/memory%201.snap"
+        );
+        assert_eq!(uri_encode("a/b", false), "a%2Fb");
+    }
+}
@@ -3,6 +3,7 @@
 //! This module provides functionality for data migration, import/export,
 //! and schema evolution for the memory system.
 
+pub mod backup;
 pub mod converter;
 pub mod exporter;
 pub mod importer;
@@ -10,6 +11,7 @@ pub mod schema_migrations;
 pub mod validator;
 
 // Re-export main types
+pub use backup::*;
 pub use converter::*;
 pub use exporter::*;
 pub use importer::*;
@@ -44,6 +46,9 @@ pub enum MigrationError {
 
     #[error("Unsupported format: {0}")]
     UnsupportedFormat(String),
+
+    #[error("Backup error: {0}")]
+    Backup(String),
 }
 
 /// Migration direction
@@ -0,0 +1,299 @@
+//! Structured access logs with MCP semantics.
+//!
+//! Every finished request yields one JSON line carrying the client, the
+//! original protocol, the normalized JSON-RPC method, the tool called, the
+//! status, latency and byte counts. Lines are sampled per route (longest path
+//! prefix wins, 5xx responses are always kept) and either written to stdout
+//! or batched to a Loki or Vector HTTP endpoint by [`Shipper`].
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+
+/// Lines buffered for the shipper before new ones are dropped
+const QUEUE: usize = 8192;
+
+/// Most lines per push to the sink
+const BATCH: usize = 500;
+
+/// Access log settings
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AccessLogConfig {
+    /// Write access logs at all
+    pub enabled: bool,
+
+    /// Fraction of requests logged on routes without their own rate
+    pub sample_rate: f64,
+
+    /// Per-route sample rates, matched by path prefix
+    pub routes: Vec<RouteSample>,
+
+    /// Loki or Vector HTTP endpoint; stdout when unset
+    pub sink: Option<String>,
+}
+
+impl Default for AccessLogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sample_rate: 1.0,
+            routes: Vec::new(),
+            sink: None,
+        }
+    }
+}
+
+/// Sample rate for paths starting with `prefix`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RouteSample {
+    pub prefix: String,
+    pub rate: f64,
+}
+
+/// Parse `/mcp=1,/api/peers=0.01` into route sample rates
+pub fn parse_routes(raw: &str) -> Result<Vec<RouteSample>> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (prefix, rate) = entry
+                .split_once('=')
+                .with_context(|| format!("Route sample '{}' must be PREFIX=RATE", entry))?;
+            Ok(RouteSample {
+                prefix: prefix.trim().to_string(),
+                rate: parse_rate(rate)?,
+            })
+        })
+        .collect()
+}
+
+/// Parse a sample rate between 0 and 1
+pub fn parse_rate(raw: &str) -> Result<f64> {
+    let rate: f64 = raw
+        .trim()
+        .parse()
+        .with_context(|| format!("Invalid sample rate '{}'", raw))?;
+    if !(0.0..=1.0).contains(&rate) {
+        anyhow::bail!("Sample rate {} must be between 0 and 1", rate);
+    }
+    Ok(rate)
+}
+
+/// One access log line
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct AccessLogEntry {
+    /// RFC 3339 time the request finished
+    pub ts: String,
+    pub client: Option<String>,
+    /// Protocol the client spoke before normalization
+    pub protocol: Option<&'static str>,
+    pub http_method: String,
+    pub path: String,
+    /// Normalized JSON-RPC method, e.g. `tools/call`
+    pub method: Option<String>,
+    pub tool: Option<String>,
+    pub status: u16,
+    pub latency_ms: f64,
+    pub request_bytes: usize,
+    pub response_bytes: usize,
+}
+
+pub struct AccessLog {
+    cfg: AccessLogConfig,
+    sink: Option<mpsc::Sender<(u128, String)>>,
+}
+
+impl AccessLog {
+    /// The logger, plus the shipper to run when a sink is configured
+    pub fn new(cfg: AccessLogConfig) -> (Self, Option<Shipper>) {
+        let shipper = match (&cfg.sink, cfg.enabled) {
+            (Some(url), true) => {
+                let (tx, rx) = mpsc::channel(QUEUE);
+                Some((tx, Shipper::new(url.clone(), rx)))
+            }
+            _ => None,
+        };
+        let (sink, shipper) = shipper.unzip();
+        (Self { cfg, sink }, shipper)
+    }
+
+    /// Sample rate of `path`: the longest matching route prefix, else the default
+    pub fn sample_rate(&self, path: &str) -> f64 {
+        self.cfg
+            .routes
+            .iter()
+            .filter(|route| path.starts_with(&route.prefix))
+            .max_by_key(|route| route.prefix.len())
+            .map_or(self.cfg.sample_rate, |route| route.rate)
+    }
+
+    fn sampled(&self, path: &str, status: u16) -> bool {
+        if status >= 500 {
+            return true;
+        }
+        let rate = self.sample_rate(path);
+        rate >= 1.0 || (rate > 0.0 && rand::random::<f64>() < rate)
+    }
+
+    /// Log `entry` if logging is on and the route's sample keeps it
+    pub fn record(&self, mut entry: AccessLogEntry) {
+        if !self.cfg.enabled || !self.sampled(&entry.path, entry.status) {
+            return;
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        entry.ts = chrono::DateTime::<chrono::Utc>::from(UNIX_EPOCH + now).to_rfc3339();
+        let line = match serde_json::to_string(&entry) {
+            Ok(line) => line,
+            Err(e) => {
+                tracing::warn!("Failed to serialize access log entry: {}", e);
+                return;
+            }
+        };
+
+        match &self.sink {
+            // Never hold up a request for logging; a full queue drops the line
+            Some(tx) => {
+                if tx.try_send((now.as_nanos(), line)).is_err() {
+                    crate::metrics::record_access_log_dropped("queue_full", 1);
+                }
+            }
+            None => println!("{}", line),
+        }
+    }
+}
+
+/// Batches access log lines to a Loki or Vector endpoint
+pub struct Shipper {
+    url: String,
+    loki: bool,
+    rx: mpsc::Receiver<(u128, String)>,
+    client: reqwest::Client,
+}
+
+impl Shipper {
+    fn new(url: String, rx: mpsc::Receiver<(u128, String)>) -> Self {
+        Self {
+            // Loki's push API wants labelled streams; Vector takes NDJSON
+            loki: url.contains("/loki/api/"),
+            url,
+            rx,
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .unwrap_or_else(|_| reqwest::Client::new()),
+        }
+    }
+
+    /// Ship lines until every logger is gone. A batch goes out when it is
+    /// full or the queue runs dry, so quiet periods never delay a line.
+    pub async fn run(mut self) {
+        let mut batch = Vec::with_capacity(BATCH);
+        loop {
+            let closed = self.rx.recv_many(&mut batch, BATCH - batch.len()).await == 0;
+            if closed || batch.len() >= BATCH || self.rx.is_empty() {
+                self.push(&batch).await;
+                batch.clear();
+            }
+            if closed {
+                return;
+            }
+        }
+    }
+
+    async fn push(&self, batch: &[(u128, String)]) {
+        if batch.is_empty() {
+            return;
+        }
+        let request = if self.loki {
+            self.client.post(&self.url).json(&loki_body(batch))
+        } else {
+            let body: String = batch
+                .iter()
+                .map(|(_, line)| format!("{}\n", line))
+                .collect();
+            self.client
+                .post(&self.url)
+                .header("Content-Type", "application/x-ndjson")
+                .body(body)
+        };
+
+        let outcome = match request.send().await {
+            Ok(response) if response.status().is_success() => Ok(()),
+            Ok(response) => Err(format!("HTTP {}", response.status())),
+            Err(e) => Err(e.to_string()),
+        };
+        if let Err(e) = outcome {
+            tracing::warn!(
+                "Failed to ship {} access log lines to {}: {}",
+                batch.len(),
+                self.url,
+                e
+            );
+            crate::metrics::record_access_log_dropped("sink_error", batch.len());
+        }
+    }
+}
+
+/// Loki push API payload for `batch`
+fn loki_body(batch: &[(u128, String)]) -> serde_json::Value {
+    let values: Vec<_> = batch
+        .iter()
+        .map(|(ts, line)| serde_json::json!([ts.to_string(), line]))
+        .collect();
+    serde_json::json!({
+        "streams": [{
+            "stream": {"job": "sweetmcp", "stream": "access"},
+            "values": values,
+        }]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn logger(routes: &str) -> AccessLog {
+        AccessLog::new(AccessLogConfig {
+            enabled: true,
+            sample_rate: 0.5,
+            routes: parse_routes(routes).unwrap(),
+            sink: None,
+        })
+        .0
+    }
+
+    #[test]
+    fn longest_route_prefix_wins() {
+        let log = logger("/api=0.1, /api/peers=0, /mcp=1");
+        assert_eq!(log.sample_rate("/api/peers"), 0.0);
+        assert_eq!(log.sample_rate("/api/keys"), 0.1);
+        assert_eq!(log.sample_rate("/mcp/stream"), 1.0);
+        assert_eq!(log.sample_rate("/graphql"), 0.5);
+    }
+
+    #[test]
+    fn server_errors_are_always_sampled() {
+        let log = logger("/api/peers=0");
+        assert!(!log.sampled("/api/peers", 200));
+        assert!(log.sampled("/api/peers", 502));
+    }
+
+    #[test]
+    fn invalid_routes_are_rejected() {
+        assert!(parse_routes("/mcp").is_err());
+        assert!(parse_routes("/mcp=2").is_err());
+        assert!(parse_routes("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn loki_body_uses_nanosecond_strings() {
+        let body = loki_body(&[(1_700_000_000_000_000_000, "{}".to_string())]);
+        assert_eq!(
+            body["streams"][0]["values"][0],
+            serde_json::json!(["1700000000000000000", "{}"])
+        );
+    }
+}
@@ -0,0 +1,253 @@
+//! API key management endpoints and per-key tool enforcement.
+//!
+//! Handlers here are independent of the Pingora session: `EdgeService` reads
+//! the body, authenticates the caller and writes the returned [`ApiResponse`].
+
+use crate::auth::Caller;
+use chrono::{DateTime, Utc};
+use pingora::http::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sweetmcp_axum::iam::{ApiKey, ApiKeyError, ApiKeyStore, ScopeMap};
+
+/// Path prefix served by [`handle`]
+pub const KEYS_PATH: &str = "/api/keys";
+
+/// Path prefix for passkey admin login, served when built with `webauthn`
+pub const WEBAUTHN_PATH: &str = "/api/admin/webauthn";
+
+/// Status code and JSON body for a locally handled endpoint
+#[derive(Debug)]
+pub struct ApiResponse {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl ApiResponse {
+    pub fn new(status: u16, body: Value) -> Self {
+        Self { status, body }
+    }
+
+    pub fn error(status: u16, message: impl Into<String>) -> Self {
+        Self::new(status, json!({ "error": message.into() }))
+    }
+}
+
+/// Key as returned to clients; the secret hash never leaves the gateway
+#[derive(Debug, Serialize)]
+struct KeyView<'a> {
+    id: &'a str,
+    name: &'a str,
+    scopes: &'a [String],
+    tools: Vec<String>,
+    created_at: DateTime<Utc>,
+    rotated_at: Option<DateTime<Utc>>,
+    revoked_at: Option<DateTime<Utc>>,
+}
+
+impl<'a> KeyView<'a> {
+    fn new(key: &'a ApiKey, scopes: &ScopeMap) -> Self {
+        Self {
+            id: &key.id,
+            name: &key.name,
+            scopes: &key.scopes,
+            tools: key.tool_allowlist(scopes).patterns,
+            created_at: key.created_at,
+            rotated_at: key.rotated_at,
+            revoked_at: key.revoked_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CreateKeyRequest {
+    name: String,
+    #[serde(default)]
+    scopes: Vec<String>,
+}
+
+/// Serve `GET|POST /api/keys`, `POST /api/keys/{id}/rotate` and `DELETE /api/keys/{id}`.
+/// Every route requires an admin caller.
+pub fn handle(
+    store: &ApiKeyStore,
+    scopes: &ScopeMap,
+    method: &Method,
+    path: &str,
+    body: &[u8],
+    caller: Option<&Caller>,
+) -> ApiResponse {
+    let Some(caller) = caller else {
+        return ApiResponse::error(401, "Unauthorized");
+    };
+    if !caller.is_admin() {
+        return ApiResponse::error(403, "Admin access required");
+    }
+
+    let rest = path
+        .strip_prefix(KEYS_PATH)
+        .unwrap_or_default()
+        .trim_matches('/');
+    let segments: Vec<&str> = rest.split('/').filter(|s| !s.is_empty()).collect();
+
+    match (method, segments.as_slice()) {
+        (&Method::GET, []) => {
+            let keys = store.list();
+            let views: Vec<KeyView> = keys.iter().map(|k| KeyView::new(k, scopes)).collect();
+            ApiResponse::new(200, json!({ "keys": views }))
+        }
+        (&Method::POST, []) => {
+            let request: CreateKeyRequest = match serde_json::from_slice(body) {
+                Ok(r) => r,
+                Err(e) => return ApiResponse::error(400, format!("Invalid JSON: {}", e)),
+            };
+            if request.name.trim().is_empty() {
+                return ApiResponse::error(400, "name must not be empty");
+            }
+            if let Some(unknown) = request.scopes.iter().find(|s| !is_known_scope(s, scopes)) {
+                return ApiResponse::error(400, format!("Unknown scope: {}", unknown));
+            }
+            match store.create(request.name.trim(), request.scopes) {
+                Ok(issued) => {
+                    tracing::info!("API key {} created by {}", issued.key.id, caller.subject());
+                    ApiResponse::new(201, issued_json(&issued.key, &issued.token, scopes))
+                }
+                Err(e) => store_error(e),
+            }
+        }
+        (&Method::POST, [id, "rotate"]) => match store.rotate(id) {
+            Ok(issued) => {
+                tracing::info!("API key {} rotated by {}", id, caller.subject());
+                ApiResponse::new(200, issued_json(&issued.key, &issued.token, scopes))
+            }
+            Err(e) => store_error(e),
+        },
+        (&Method::DELETE, [id]) => match store.revoke(id) {
+            Ok(key) => {
+                tracing::info!("API key {} revoked by {}", id, caller.subject());
+                ApiResponse::new(200, json!(KeyView::new(&key, scopes)))
+            }
+            Err(e) => store_error(e),
+        },
+        (_, []) | (_, [_]) | (_, [_, "rotate"]) => ApiResponse::error(405, "Method not allowed"),
+        _ => ApiResponse::error(404, "Not found"),
+    }
+}
+
+/// A scope is valid if it is mapped, literal (`tools:<pattern>`) or the admin scope
+fn is_known_scope(scope: &str, scopes: &ScopeMap) -> bool {
+    scope == sweetmcp_axum::iam::ADMIN_SCOPE
+        || scopes.contains_key(scope)
+        || scope.strip_prefix("tools:").is_some_and(|p| !p.is_empty())
+}
+
+fn issued_json(key: &ApiKey, token: &str, scopes: &ScopeMap) -> Value {
+    let mut body = json!(KeyView::new(key, scopes));
+    // Shown once; only the hash is stored
+    body["token"] = json!(token);
+    body
+}
+
+fn store_error(err: ApiKeyError) -> ApiResponse {
+    match err {
+        ApiKeyError::NotFound(_) => ApiResponse::error(404, err.to_string()),
+        ApiKeyError::Revoked(_) => ApiResponse::error(409, err.to_string()),
+        ApiKeyError::Io(_) | ApiKeyError::Serde(_) => {
+            tracing::error!("{}", err);
+            ApiResponse::error(500, "Failed to update API key store")
+        }
+    }
+}
+
+/// Name of the first `tools/call` in a JSON-RPC request (or batch) that the
+/// caller is not allowed to invoke.
+pub fn denied_tool(caller: &Caller, request: &Value) -> Option<String> {
+    let calls: Vec<&Value> = match request {
+        Value::Array(batch) => batch.iter().collect(),
+        single => vec![single],
+    };
+    calls
+        .into_iter()
+        .filter(|call| call.get("method").and_then(Value::as_str) == Some("tools/call"))
+        .map(|call| {
+            call.pointer("/params/name")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+        })
+        .find(|tool| !caller.may_call_tool(tool))
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sweetmcp_axum::iam::ToolAllowlist;
+
+    fn key_caller(patterns: &[&str]) -> Caller {
+        let issued = ApiKey::issue("test", vec![]);
+        Caller::ApiKey {
+            key: issued.key,
+            allowlist: ToolAllowlist {
+                patterns: patterns.iter().map(|p| p.to_string()).collect(),
+            },
+        }
+    }
+
+    #[test]
+    fn denies_tools_outside_allowlist() {
+        let caller = key_caller(&["time_*"]);
+        let allowed = json!({"jsonrpc": "2.0", "id": 1, "method": "tools/call",
+            "params": {"name": "time_now", "arguments": {}}});
+        let batch = json!([
+            allowed.clone(),
+            {"jsonrpc": "2.0", "id": 2, "method": "tools/list"},
+            {"jsonrpc": "2.0", "id": 3, "method": "tools/call", "params": {"name": "shell_exec"}}
+        ]);
+
+        assert_eq!(denied_tool(&caller, &allowed), None);
+        assert_eq!(denied_tool(&caller, &batch), Some("shell_exec".to_string()));
+    }
+
+    #[test]
+    fn key_routes_require_admin() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ApiKeyStore::open(dir.path().join("keys.json")).unwrap();
+        let scopes = ScopeMap::new();
+
+        let anonymous = handle(&store, &scopes, &Method::GET, KEYS_PATH, b"", None);
+        assert_eq!(anonymous.status, 401);
+
+        let user = key_caller(&["*"]);
+        let forbidden = handle(&store, &scopes, &Method::GET, KEYS_PATH, b"", Some(&user));
+        assert_eq!(forbidden.status, 403);
+
+        let admin = Caller::ApiKey {
+            key: ApiKey::issue("root", vec!["admin".into()]).key,
+            allowlist: ToolAllowlist::default(),
+        };
+        let created = handle(
+            &store,
+            &scopes,
+            &Method::POST,
+            KEYS_PATH,
+            br#"{"name": "ci", "scopes": ["tools:time_*"]}"#,
+            Some(&admin),
+        );
+        assert_eq!(created.status, 201);
+        let token = created.body["token"].as_str().unwrap();
+        assert!(store.authenticate(token).is_some());
+        assert!(created.body.get("secret_hash").is_none());
+
+        let id = created.body["id"].as_str().unwrap();
+        let revoke_path = format!("{}/{}", KEYS_PATH, id);
+        let revoked = handle(
+            &store,
+            &scopes,
+            &Method::DELETE,
+            &revoke_path,
+            b"",
+            Some(&admin),
+        );
+        assert_eq!(revoked.status, 200);
+        assert!(store.authenticate(token).is_none());
+    }
+}
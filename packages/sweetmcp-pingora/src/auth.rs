@@ -4,6 +4,7 @@ use anyhow::{Context, Result};
 use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
 use std::{collections::HashSet, sync::Arc, time::Duration};
+use sweetmcp_axum::iam::{ApiKey, ToolAllowlist};
 use time::OffsetDateTime;
 use tracing::debug;
 use uuid::Uuid;
@@ -296,3 +297,38 @@ impl AuthContext {
         self.has_role(&Role::Admin)
     }
 }
+
+/// Authenticated caller: a JWT holder or a scoped API key
+#[derive(Debug, Clone)]
+pub enum Caller {
+    Jwt(Claims),
+    ApiKey {
+        key: ApiKey,
+        allowlist: ToolAllowlist,
+    },
+}
+
+impl Caller {
+    pub fn subject(&self) -> &str {
+        match self {
+            Caller::Jwt(claims) => &claims.sub,
+            Caller::ApiKey { key, .. } => &key.id,
+        }
+    }
+
+    /// Admins may manage API keys and WebAuthn credentials
+    pub fn is_admin(&self) -> bool {
+        match self {
+            Caller::Jwt(claims) => claims.roles.iter().any(|r| r == Role::Admin.as_str()),
+            Caller::ApiKey { key, .. } => key.is_admin(),
+        }
+    }
+
+    /// Whether the caller may invoke `tool`; JWT holders are not tool-restricted
+    pub fn may_call_tool(&self, tool: &str) -> bool {
+        match self {
+            Caller::Jwt(_) => true,
+            Caller::ApiKey { allowlist, .. } => allowlist.allows(tool),
+        }
+    }
+}
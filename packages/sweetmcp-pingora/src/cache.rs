@@ -0,0 +1,594 @@
+//! Edge cache for tool call and resource read results.
+//!
+//! A `tools/call` is cacheable when the tool's `tools/list` entry allows it:
+//! either `_meta["sweetmcp/cache"]` gives `{"ttl": <seconds>, "shared": bool}`,
+//! or the tool is annotated `readOnlyHint: true` and the configured default
+//! lifetime applies. `_meta["sweetmcp/cache"]: false` never caches a tool.
+//! `resources/read` results are cached for the configured resource lifetime.
+//! Entries are keyed by caller unless the tool is shared, so one tenant never
+//! sees another's results by accident.
+//!
+//! Callers steer a single request with `Cache-Control` directives, in the
+//! HTTP header or in `_meta["sweetmcp/cache-control"]`: `no-store` bypasses
+//! the cache, `no-cache` skips the lookup but stores the fresh result, and
+//! `max-age=N` only accepts entries at most N seconds old. Errors are never
+//! stored. `notifications/tools/list_changed` (or a `tools/list` response that
+//! differs from the last one) drops every tool entry, and
+//! `notifications/resources/updated` drops the entries for that resource.
+
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hasher;
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::dedup;
+
+/// Tool `_meta` key carrying the tool's cache policy
+pub const POLICY_META_KEY: &str = "sweetmcp/cache";
+
+/// Request `_meta` key carrying `Cache-Control` directives for one call
+pub const CONTROL_META_KEY: &str = "sweetmcp/cache-control";
+
+/// Response header saying whether the cache answered
+pub const STATUS_HEADER: &str = "x-sweetmcp-cache";
+
+/// Edge cache settings
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct CacheConfig {
+    /// Cache tool and resource results at all
+    pub enabled: bool,
+
+    /// Lifetime for read-only tools without their own policy; zero caches
+    /// only tools that opt in
+    pub default_ttl: Duration,
+
+    /// Lifetime for `resources/read` results; zero disables them
+    pub resource_ttl: Duration,
+
+    /// Share default-policy tool results between callers
+    pub shared: bool,
+
+    /// Most entries kept; the oldest are evicted first
+    pub max_entries: usize,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            default_ttl: Duration::from_secs(30),
+            resource_ttl: Duration::from_secs(30),
+            shared: false,
+            max_entries: 10_000,
+        }
+    }
+}
+
+/// Client cache directives for one request
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Directives {
+    pub no_store: bool,
+    pub no_cache: bool,
+    pub max_age: Option<Duration>,
+}
+
+impl Directives {
+    /// Parse a `Cache-Control` value; unknown directives are ignored
+    pub fn parse(raw: &str) -> Self {
+        let mut directives = Self::default();
+        for directive in raw.split(',') {
+            let directive = directive.trim().to_ascii_lowercase();
+            match directive.split_once('=') {
+                Some(("max-age", secs)) => {
+                    if let Ok(secs) = secs.trim().trim_matches('"').parse() {
+                        directives.max_age = Some(Duration::from_secs(secs));
+                    }
+                }
+                _ if directive == "no-store" => directives.no_store = true,
+                _ if directive == "no-cache" => directives.no_cache = true,
+                _ => {}
+            }
+        }
+        directives
+    }
+
+    /// Both sets of directives, keeping the stricter of each
+    fn merge(self, other: Self) -> Self {
+        Self {
+            no_store: self.no_store || other.no_store,
+            no_cache: self.no_cache || other.no_cache,
+            max_age: match (self.max_age, other.max_age) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            },
+        }
+    }
+}
+
+/// What the cache did for a request
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    /// Answered from the cache with an entry this old
+    Hit(Duration),
+    /// Cacheable but executed
+    Miss,
+}
+
+impl Status {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Status::Hit(_) => "hit",
+            Status::Miss => "miss",
+        }
+    }
+}
+
+/// A cacheable request, from [`EdgeCache::lookup`]
+#[derive(Debug)]
+pub struct Lookup {
+    key: u64,
+    ttl: Duration,
+    directives: Directives,
+    target: Target,
+}
+
+impl Lookup {
+    /// Metrics label for the kind of request
+    pub fn kind(&self) -> &'static str {
+        match self.target {
+            Target::Tool(_) => "tool",
+            Target::Resource(_) => "resource",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Target {
+    Tool(String),
+    Resource(String),
+}
+
+#[derive(Clone, Copy, Debug)]
+struct ToolPolicy {
+    ttl: Duration,
+    shared: bool,
+}
+
+struct Entry {
+    response: Value,
+    stored: Instant,
+    ttl: Duration,
+    target: Target,
+    seq: u64,
+}
+
+#[derive(Default)]
+struct Entries {
+    map: HashMap<u64, Entry>,
+    /// Insertion order as `(key, seq)`; stale pairs are skipped on eviction
+    order: VecDeque<(u64, u64)>,
+    seq: u64,
+}
+
+pub struct EdgeCache {
+    cfg: CacheConfig,
+    policies: RwLock<HashMap<String, ToolPolicy>>,
+    tools_fingerprint: Mutex<Option<u64>>,
+    entries: Mutex<Entries>,
+}
+
+impl EdgeCache {
+    pub fn new(cfg: CacheConfig) -> Self {
+        Self {
+            cfg,
+            policies: RwLock::new(HashMap::new()),
+            tools_fingerprint: Mutex::new(None),
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+
+    /// The cache entry `request` from `tenant` would use, if it is cacheable.
+    /// `cache_control` is the request's `Cache-Control` header.
+    pub fn lookup(
+        &self,
+        request: &Value,
+        tenant: &str,
+        cache_control: Option<&str>,
+    ) -> Option<Lookup> {
+        if !self.cfg.enabled {
+            return None;
+        }
+        let params = request.get("params")?;
+        let meta_control = params
+            .pointer(&format!("/_meta/{}", CONTROL_META_KEY.replace('/', "~1")))
+            .and_then(Value::as_str);
+        let directives = cache_control
+            .map(Directives::parse)
+            .unwrap_or_default()
+            .merge(meta_control.map(Directives::parse).unwrap_or_default());
+        if directives.no_store {
+            return None;
+        }
+
+        let mut hasher = seahash::SeaHasher::new();
+        let (target, ttl) = match request.get("method").and_then(Value::as_str)? {
+            "tools/call" => {
+                let tool = params.get("name").and_then(Value::as_str)?;
+                let policy = *self
+                    .policies
+                    .read()
+                    .unwrap_or_else(|p| p.into_inner())
+                    .get(tool)?;
+                hasher.write(b"tool\0");
+                hasher.write(tool.as_bytes());
+                hasher.write_u8(0);
+                if !policy.shared {
+                    hasher.write(tenant.as_bytes());
+                }
+                hasher.write_u8(0);
+                let args = params
+                    .get("arguments")
+                    .map(dedup::canonical)
+                    .unwrap_or_default();
+                hasher.write(args.as_bytes());
+                (Target::Tool(tool.to_string()), policy.ttl)
+            }
+            "resources/read" => {
+                let uri = params.get("uri").and_then(Value::as_str)?;
+                hasher.write(b"resource\0");
+                hasher.write(uri.as_bytes());
+                hasher.write_u8(0);
+                hasher.write(tenant.as_bytes());
+                (Target::Resource(uri.to_string()), self.cfg.resource_ttl)
+            }
+            _ => return None,
+        };
+        if ttl.is_zero() {
+            return None;
+        }
+        Some(Lookup {
+            key: hasher.finish(),
+            ttl,
+            directives,
+            target,
+        })
+    }
+
+    /// A fresh stored response for `lookup` and its age
+    pub fn get(&self, lookup: &Lookup) -> Option<(Value, Duration)> {
+        self.get_at(lookup, Instant::now())
+    }
+
+    fn get_at(&self, lookup: &Lookup, now: Instant) -> Option<(Value, Duration)> {
+        if lookup.directives.no_cache {
+            return None;
+        }
+        let mut entries = self.entries.lock().unwrap_or_else(|p| p.into_inner());
+        let entry = entries.map.get(&lookup.key)?;
+        let age = now.saturating_duration_since(entry.stored);
+        if age >= entry.ttl {
+            entries.map.remove(&lookup.key);
+            return None;
+        }
+        if lookup
+            .directives
+            .max_age
+            .is_some_and(|max_age| age > max_age)
+        {
+            return None;
+        }
+        Some((entry.response.clone(), age))
+    }
+
+    /// Keep `response` for `lookup` unless it is an error
+    pub fn store(&self, lookup: &Lookup, response: &Value) {
+        if response.get("error").is_some()
+            || response.pointer("/result/isError") == Some(&Value::Bool(true))
+            || response.get("result").is_none()
+        {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap_or_else(|p| p.into_inner());
+        entries.seq += 1;
+        let seq = entries.seq;
+        entries.map.insert(
+            lookup.key,
+            Entry {
+                response: response.clone(),
+                stored: Instant::now(),
+                ttl: lookup.ttl,
+                target: lookup.target.clone(),
+                seq,
+            },
+        );
+        entries.order.push_back((lookup.key, seq));
+
+        while entries.map.len() > self.cfg.max_entries {
+            let Some((key, seq)) = entries.order.pop_front() else {
+                break;
+            };
+            if entries.map.get(&key).is_some_and(|entry| entry.seq == seq) {
+                entries.map.remove(&key);
+            }
+        }
+        // Replaced and invalidated entries leave stale pairs behind
+        if entries.order.len() > 2 * self.cfg.max_entries.max(1) {
+            let Entries { map, order, .. } = &mut *entries;
+            order.retain(|(key, seq)| map.get(key).is_some_and(|entry| entry.seq == *seq));
+        }
+    }
+
+    /// Refresh tool cache policies from a `tools/list` response. A list that
+    /// differs from the previous one counts as `tools/list_changed`.
+    pub fn learn_tools(&self, response: &Value) {
+        let Some(tools) = response.pointer("/result/tools").and_then(Value::as_array) else {
+            return;
+        };
+        let policies: HashMap<String, ToolPolicy> = tools
+            .iter()
+            .filter_map(|tool| {
+                let name = tool.get("name").and_then(Value::as_str)?;
+                Some((name.to_string(), self.policy(tool)?))
+            })
+            .collect();
+        *self.policies.write().unwrap_or_else(|p| p.into_inner()) = policies;
+
+        let mut hasher = seahash::SeaHasher::new();
+        hasher.write(dedup::canonical(&Value::Array(tools.clone())).as_bytes());
+        let fingerprint = hasher.finish();
+        let previous = self
+            .tools_fingerprint
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .replace(fingerprint);
+        if previous.is_some_and(|previous| previous != fingerprint) {
+            self.invalidate(|target| matches!(target, Target::Tool(_)));
+        }
+    }
+
+    fn policy(&self, tool: &Value) -> Option<ToolPolicy> {
+        let meta = tool.pointer(&format!("/_meta/{}", POLICY_META_KEY.replace('/', "~1")));
+        match meta {
+            Some(Value::Bool(false)) => None,
+            Some(Value::Object(policy)) => Some(ToolPolicy {
+                ttl: Duration::from_secs(policy.get("ttl").and_then(Value::as_u64)?),
+                shared: policy
+                    .get("shared")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(self.cfg.shared),
+            }),
+            _ => {
+                let hint = |key: &str| {
+                    tool.pointer(&format!("/annotations/{}", key))
+                        .and_then(Value::as_bool)
+                };
+                let read_only =
+                    hint("readOnlyHint") == Some(true) && hint("destructiveHint") != Some(true);
+                (read_only && !self.cfg.default_ttl.is_zero()).then_some(ToolPolicy {
+                    ttl: self.cfg.default_ttl,
+                    shared: self.cfg.shared,
+                })
+            }
+        }
+    }
+
+    /// Drop entries made stale by the change notifications in `message`, a
+    /// single JSON-RPC message or a batch
+    pub fn observe(&self, message: &Value) {
+        if !self.cfg.enabled {
+            return;
+        }
+        if let Value::Array(batch) = message {
+            batch.iter().for_each(|message| self.observe(message));
+            return;
+        }
+        match message.get("method").and_then(Value::as_str) {
+            Some("notifications/tools/list_changed") => {
+                self.invalidate(|target| matches!(target, Target::Tool(_)));
+            }
+            Some("notifications/resources/list_changed") => {
+                self.invalidate(|target| matches!(target, Target::Resource(_)));
+            }
+            Some("notifications/resources/updated") => {
+                if let Some(uri) = message.pointer("/params/uri").and_then(Value::as_str) {
+                    self.invalidate(|target| matches!(target, Target::Resource(r) if r == uri));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn invalidate(&self, stale: impl Fn(&Target) -> bool) {
+        let mut entries = self.entries.lock().unwrap_or_else(|p| p.into_inner());
+        let before = entries.map.len();
+        entries.map.retain(|_, entry| !stale(&entry.target));
+        let dropped = before - entries.map.len();
+        if dropped > 0 {
+            tracing::debug!("Edge cache dropped {} stale entries", dropped);
+        }
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .map
+            .len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn cache() -> EdgeCache {
+        let cache = EdgeCache::new(CacheConfig {
+            enabled: true,
+            ..CacheConfig::default()
+        });
+        cache.learn_tools(&tools(json!([
+            {"name": "hash", "annotations": {"readOnlyHint": true}},
+            {"name": "weather", "_meta": {"sweetmcp/cache": {"ttl": 600, "shared": true}}},
+            {"name": "time", "annotations": {"readOnlyHint": true}, "_meta": {"sweetmcp/cache": false}},
+            {"name": "rm", "annotations": {"destructiveHint": true}},
+        ])));
+        cache
+    }
+
+    fn tools(list: Value) -> Value {
+        json!({"jsonrpc": "2.0", "id": 1, "result": {"tools": list}})
+    }
+
+    fn call(tool: &str, args: Value) -> Value {
+        json!({"jsonrpc": "2.0", "id": 1, "method": "tools/call",
+               "params": {"name": tool, "arguments": args}})
+    }
+
+    fn ok() -> Value {
+        json!({"jsonrpc": "2.0", "id": 1, "result": {"content": []}})
+    }
+
+    #[test]
+    fn directives_parse_and_merge() {
+        let parsed = Directives::parse("No-Cache, max-age=\"60\", private");
+        assert!(parsed.no_cache && !parsed.no_store);
+        assert_eq!(parsed.max_age, Some(Duration::from_secs(60)));
+
+        let merged = parsed.merge(Directives::parse("max-age=5, no-store"));
+        assert!(merged.no_store);
+        assert_eq!(merged.max_age, Some(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn tool_policies_decide_what_is_cached() {
+        let c = cache();
+        assert!(c.lookup(&call("hash", json!({})), "alice", None).is_some());
+        assert!(c.lookup(&call("time", json!({})), "alice", None).is_none());
+        assert!(c.lookup(&call("rm", json!({})), "alice", None).is_none());
+        assert!(c
+            .lookup(&call("unknown", json!({})), "alice", None)
+            .is_none());
+
+        // Private tools are keyed per caller, shared ones are not
+        let key = |tool, tenant| {
+            c.lookup(&call(tool, json!({"a": 1})), tenant, None)
+                .unwrap()
+                .key
+        };
+        assert_ne!(key("hash", "alice"), key("hash", "bob"));
+        assert_eq!(key("weather", "alice"), key("weather", "bob"));
+    }
+
+    #[test]
+    fn hits_respect_ttl_and_client_directives() {
+        let c = cache();
+        let lookup = c
+            .lookup(&call("hash", json!({"a": 1})), "alice", None)
+            .unwrap();
+        assert!(c.get(&lookup).is_none());
+        c.store(&lookup, &ok());
+
+        let (response, _) = c.get(&lookup).unwrap();
+        assert_eq!(response, ok());
+        let later = Instant::now() + Duration::from_secs(31);
+        assert!(c.get_at(&lookup, later).is_none());
+        assert_eq!(c.len(), 0);
+
+        c.store(&lookup, &ok());
+        let strict = c
+            .lookup(&call("hash", json!({"a": 1})), "alice", Some("max-age=2"))
+            .unwrap();
+        assert!(c.get(&strict).is_some());
+        assert!(c
+            .get_at(&strict, Instant::now() + Duration::from_secs(3))
+            .is_none());
+
+        let mut revalidate = call("hash", json!({"a": 1}));
+        revalidate["params"]["_meta"] = json!({"sweetmcp/cache-control": "no-cache"});
+        assert!(c
+            .get(&c.lookup(&revalidate, "alice", None).unwrap())
+            .is_none());
+        assert!(c
+            .lookup(&call("hash", json!({"a": 1})), "alice", Some("no-store"))
+            .is_none());
+    }
+
+    #[test]
+    fn errors_are_not_stored() {
+        let c = cache();
+        let lookup = c.lookup(&call("hash", json!({})), "alice", None).unwrap();
+        c.store(
+            &lookup,
+            &json!({"id": 1, "error": {"code": -32603, "message": "boom"}}),
+        );
+        c.store(
+            &lookup,
+            &json!({"id": 1, "result": {"isError": true, "content": []}}),
+        );
+        assert_eq!(c.len(), 0);
+    }
+
+    #[test]
+    fn notifications_and_list_changes_invalidate() {
+        let c = cache();
+        let tool = c.lookup(&call("hash", json!({})), "alice", None).unwrap();
+        let read = |uri: &str| json!({"jsonrpc": "2.0", "id": 2, "method": "resources/read", "params": {"uri": uri}});
+        let a = c.lookup(&read("file:///a"), "alice", None).unwrap();
+        let b = c.lookup(&read("file:///b"), "alice", None).unwrap();
+        for lookup in [&tool, &a, &b] {
+            c.store(lookup, &ok());
+        }
+
+        c.observe(
+            &json!([{"jsonrpc": "2.0", "method": "notifications/resources/updated",
+                           "params": {"uri": "file:///a"}}]),
+        );
+        assert!(c.get(&a).is_none());
+        assert!(c.get(&b).is_some());
+
+        c.observe(&json!({"jsonrpc": "2.0", "method": "notifications/tools/list_changed"}));
+        assert!(c.get(&tool).is_none());
+        assert!(c.get(&b).is_some());
+
+        // The same list again keeps entries, a different one drops them
+        c.store(&tool, &ok());
+        c.learn_tools(&tools(json!([
+            {"name": "hash", "annotations": {"readOnlyHint": true}},
+            {"name": "weather", "_meta": {"sweetmcp/cache": {"ttl": 600, "shared": true}}},
+            {"name": "time", "annotations": {"readOnlyHint": true}, "_meta": {"sweetmcp/cache": false}},
+            {"name": "rm", "annotations": {"destructiveHint": true}},
+        ])));
+        assert!(c.get(&tool).is_some());
+        c.learn_tools(&tools(
+            json!([{"name": "hash", "annotations": {"readOnlyHint": true}}]),
+        ));
+        assert!(c.get(&tool).is_none());
+    }
+
+    #[test]
+    fn oldest_entries_are_evicted() {
+        let c = EdgeCache::new(CacheConfig {
+            enabled: true,
+            max_entries: 2,
+            ..CacheConfig::default()
+        });
+        c.learn_tools(&tools(
+            json!([{"name": "hash", "annotations": {"readOnlyHint": true}}]),
+        ));
+        let lookups: Vec<Lookup> = (0..3)
+            .map(|i| {
+                c.lookup(&call("hash", json!({"i": i})), "alice", None)
+                    .unwrap()
+            })
+            .collect();
+        for lookup in &lookups {
+            c.store(lookup, &ok());
+        }
+        assert_eq!(c.len(), 2);
+        assert!(c.get(&lookups[0]).is_none());
+        assert!(c.get(&lookups[2]).is_some());
+    }
+}
@@ -3,7 +3,17 @@
 use anyhow::{Context, Result};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
-use std::{env, sync::Arc, time::Duration};
+use std::{env, path::PathBuf, sync::Arc, time::Duration};
+use sweetmcp_axum::iam::ScopeMap;
+
+use crate::access_log::{self, AccessLogConfig};
+use crate::cache::CacheConfig;
+use crate::dedup::DedupConfig;
+use crate::hedge::HedgeConfig;
+use crate::mcp_bridge::BridgeConfig;
+use crate::resume::ResumeConfig;
+use crate::tls::acme::{self, AcmeConfig};
+use crate::tls::reload::TlsConfig;
 
 /// Main configuration structure for SweetMCP Server
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -47,6 +57,47 @@ pub struct Config {
 
     /// Rate limiting configuration
     pub rate_limit: RateLimitConfig,
+
+    /// File holding hashed API keys
+    pub api_keys_file: PathBuf,
+
+    /// Named API key scopes mapped to tool-name patterns
+    pub api_scopes: ScopeMap,
+
+    /// WebAuthn relying party for admin login, when configured
+    pub webauthn: Option<WebauthnConfig>,
+
+    /// Hedging of slow idempotent tool calls to a second peer
+    pub hedge: HedgeConfig,
+
+    /// Coalescing of identical concurrent tool calls
+    pub dedup: DedupConfig,
+
+    /// Edge cache for cacheable tool and resource results
+    pub cache: CacheConfig,
+
+    /// Session resumption tokens for failover between peers
+    pub resume: ResumeConfig,
+
+    /// Structured JSON access logs
+    pub access_log: AccessLogConfig,
+
+    /// How the local MCP host is reached
+    pub bridge: BridgeConfig,
+
+    /// TLS on the TCP listener, plain TCP when unset
+    #[serde(skip)]
+    pub tls: Option<TlsConfig>,
+}
+
+/// WebAuthn relying party configuration
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WebauthnConfig {
+    /// Relying party id, normally the gateway's domain
+    pub rp_id: String,
+
+    /// Origin the browser reports, e.g. `https://gateway.example.com`
+    pub origin: String,
 }
 
 /// Rate limiting configuration
@@ -185,6 +236,204 @@ impl Config {
             burst_capacity,
         };
 
+        let api_keys_file = env::var("SWEETMCP_API_KEYS_FILE")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| {
+                dirs::data_local_dir()
+                    .unwrap_or_else(|| PathBuf::from("."))
+                    .join("sweetmcp")
+                    .join("api_keys.json")
+            });
+
+        // JSON object: {"readonly": ["time_*", "hash_*"], "ops": ["*"]}
+        let api_scopes: ScopeMap = match env::var("SWEETMCP_API_SCOPES") {
+            Ok(raw) => serde_json::from_str(&raw).context("Invalid SWEETMCP_API_SCOPES JSON")?,
+            Err(_) => ScopeMap::new(),
+        };
+
+        let webauthn = match (
+            env::var("SWEETMCP_WEBAUTHN_RP_ID"),
+            env::var("SWEETMCP_WEBAUTHN_ORIGIN"),
+        ) {
+            (Ok(rp_id), Ok(origin)) => Some(WebauthnConfig { rp_id, origin }),
+            _ => None,
+        };
+
+        let hedge_defaults = HedgeConfig::default();
+        let hedge = HedgeConfig {
+            enabled: env::var("SWEETMCP_HEDGE")
+                .map(|v| matches!(v.as_str(), "1" | "true" | "yes"))
+                .unwrap_or(hedge_defaults.enabled),
+            min_delay: match env::var("SWEETMCP_HEDGE_MIN_DELAY") {
+                Ok(raw) => {
+                    parse_duration(&raw).context("Invalid SWEETMCP_HEDGE_MIN_DELAY format")?
+                }
+                Err(_) => hedge_defaults.min_delay,
+            },
+            max_delay: match env::var("SWEETMCP_HEDGE_MAX_DELAY") {
+                Ok(raw) => {
+                    parse_duration(&raw).context("Invalid SWEETMCP_HEDGE_MAX_DELAY format")?
+                }
+                Err(_) => hedge_defaults.max_delay,
+            },
+        };
+
+        let dedup = DedupConfig {
+            enabled: env::var("SWEETMCP_DEDUP")
+                .map(|v| matches!(v.as_str(), "1" | "true" | "yes"))
+                .unwrap_or(DedupConfig::default().enabled),
+        };
+
+        let cache_defaults = CacheConfig::default();
+        let cache = CacheConfig {
+            enabled: env::var("SWEETMCP_CACHE")
+                .map(|v| matches!(v.as_str(), "1" | "true" | "yes"))
+                .unwrap_or(cache_defaults.enabled),
+            default_ttl: match env::var("SWEETMCP_CACHE_TTL") {
+                Ok(raw) => parse_duration(&raw).context("Invalid SWEETMCP_CACHE_TTL format")?,
+                Err(_) => cache_defaults.default_ttl,
+            },
+            resource_ttl: match env::var("SWEETMCP_CACHE_RESOURCE_TTL") {
+                Ok(raw) => {
+                    parse_duration(&raw).context("Invalid SWEETMCP_CACHE_RESOURCE_TTL format")?
+                }
+                Err(_) => cache_defaults.resource_ttl,
+            },
+            shared: env::var("SWEETMCP_CACHE_SHARED")
+                .map(|v| matches!(v.as_str(), "1" | "true" | "yes"))
+                .unwrap_or(cache_defaults.shared),
+            max_entries: match env::var("SWEETMCP_CACHE_MAX_ENTRIES") {
+                Ok(raw) => raw
+                    .parse()
+                    .context("Invalid SWEETMCP_CACHE_MAX_ENTRIES value")?,
+                Err(_) => cache_defaults.max_entries,
+            },
+        };
+
+        let resume_defaults = ResumeConfig::default();
+        let resume = ResumeConfig {
+            enabled: env::var("SWEETMCP_RESUME")
+                .map(|v| matches!(v.as_str(), "1" | "true" | "yes"))
+                .unwrap_or(resume_defaults.enabled),
+            buffer: match env::var("SWEETMCP_RESUME_BUFFER") {
+                Ok(raw) => raw
+                    .parse()
+                    .context("Invalid SWEETMCP_RESUME_BUFFER value")?,
+                Err(_) => resume_defaults.buffer,
+            },
+            ttl: match env::var("SWEETMCP_RESUME_TTL") {
+                Ok(raw) => parse_duration(&raw).context("Invalid SWEETMCP_RESUME_TTL format")?,
+                Err(_) => resume_defaults.ttl,
+            },
+        };
+
+        let access_log_defaults = AccessLogConfig::default();
+        let access_log = AccessLogConfig {
+            enabled: env::var("SWEETMCP_ACCESS_LOG")
+                .map(|v| matches!(v.as_str(), "1" | "true" | "yes"))
+                .unwrap_or(access_log_defaults.enabled),
+            sample_rate: match env::var("SWEETMCP_ACCESS_LOG_SAMPLE") {
+                Ok(raw) => access_log::parse_rate(&raw)
+                    .context("Invalid SWEETMCP_ACCESS_LOG_SAMPLE value")?,
+                Err(_) => access_log_defaults.sample_rate,
+            },
+            routes: match env::var("SWEETMCP_ACCESS_LOG_ROUTES") {
+                Ok(raw) => access_log::parse_routes(&raw)
+                    .context("Invalid SWEETMCP_ACCESS_LOG_ROUTES value")?,
+                Err(_) => access_log_defaults.routes,
+            },
+            sink: env::var("SWEETMCP_ACCESS_LOG_SINK")
+                .ok()
+                .filter(|url| !url.is_empty()),
+        };
+
+        let bridge_defaults = BridgeConfig::default();
+        let bridge = BridgeConfig {
+            http_url: env::var("SWEETMCP_AXUM_URL").unwrap_or(bridge_defaults.http_url),
+            // An empty path turns the socket off
+            socket: match env::var("SWEETMCP_AXUM_SOCKET") {
+                Ok(raw) if raw.trim().is_empty() => None,
+                Ok(raw) => Some(PathBuf::from(raw)),
+                Err(_) => bridge_defaults.socket,
+            },
+            probe_interval: match env::var("SWEETMCP_BRIDGE_PROBE_INTERVAL") {
+                Ok(raw) => {
+                    parse_duration(&raw).context("Invalid SWEETMCP_BRIDGE_PROBE_INTERVAL format")?
+                }
+                Err(_) => bridge_defaults.probe_interval,
+            },
+        };
+
+        let acme = match env::var("SWEETMCP_ACME_DOMAINS") {
+            Ok(raw) if !raw.trim().is_empty() => {
+                let acme_dir = dirs::config_dir()
+                    .unwrap_or_else(|| PathBuf::from("."))
+                    .join("sweetmcp")
+                    .join("acme");
+                let propagation = match env::var("SWEETMCP_ACME_DNS_WAIT") {
+                    Ok(raw) => {
+                        parse_duration(&raw).context("Invalid SWEETMCP_ACME_DNS_WAIT format")?
+                    }
+                    Err(_) => Duration::from_secs(30),
+                };
+                let challenge = acme::parse_challenge(
+                    &env::var("SWEETMCP_ACME_CHALLENGE").unwrap_or_else(|_| "http-01".to_string()),
+                    env::var("SWEETMCP_ACME_HTTP_BIND")
+                        .unwrap_or_else(|_| "0.0.0.0:80".to_string()),
+                    env::var("SWEETMCP_ACME_DNS_HOOK").ok().map(PathBuf::from),
+                    propagation,
+                )
+                .context("Invalid SWEETMCP_ACME_CHALLENGE")?;
+                Some(AcmeConfig {
+                    domains: raw
+                        .split(',')
+                        .map(|d| d.trim().to_ascii_lowercase())
+                        .filter(|d| !d.is_empty())
+                        .collect(),
+                    email: env::var("SWEETMCP_ACME_EMAIL")
+                        .ok()
+                        .filter(|email| !email.is_empty()),
+                    directory: env::var("SWEETMCP_ACME_DIRECTORY")
+                        .unwrap_or_else(|_| acme::LETS_ENCRYPT.to_string()),
+                    challenge,
+                    account_path: acme_dir.join("account.json"),
+                    renew_before: match env::var("SWEETMCP_ACME_RENEW_BEFORE") {
+                        Ok(raw) => parse_duration(&raw)
+                            .context("Invalid SWEETMCP_ACME_RENEW_BEFORE format")?,
+                        Err(_) => Duration::from_secs(30 * 86_400),
+                    },
+                })
+            }
+            _ => None,
+        };
+
+        let cert_path = env::var("SWEETMCP_TLS_CERT").ok().map(PathBuf::from);
+        let key_path = env::var("SWEETMCP_TLS_KEY").ok().map(PathBuf::from);
+        let tls = match (cert_path, key_path, acme) {
+            (None, None, None) => None,
+            (cert_path, key_path, acme) => {
+                let managed = |file: &str| {
+                    acme.as_ref()
+                        .and_then(|acme| acme.account_path.parent())
+                        .map(|dir| dir.join(file))
+                };
+                Some(TlsConfig {
+                    cert_path: cert_path
+                        .or_else(|| managed("server.crt"))
+                        .context("SWEETMCP_TLS_CERT is required with SWEETMCP_TLS_KEY")?,
+                    key_path: key_path
+                        .or_else(|| managed("server.key"))
+                        .context("SWEETMCP_TLS_KEY is required with SWEETMCP_TLS_CERT")?,
+                    acme,
+                    reload_interval: match env::var("SWEETMCP_TLS_RELOAD_INTERVAL") {
+                        Ok(raw) => parse_duration(&raw)
+                            .context("Invalid SWEETMCP_TLS_RELOAD_INTERVAL format")?,
+                        Err(_) => Duration::from_secs(60),
+                    },
+                })
+            }
+        };
+
         Ok(Self {
             jwt_secret: Arc::new(secret),
             inflight_max,
@@ -199,6 +448,16 @@ impl Config {
             circuit_breaker_threshold,
             request_timeout,
             rate_limit,
+            api_keys_file,
+            api_scopes,
+            webauthn,
+            hedge,
+            dedup,
+            cache,
+            resume,
+            access_log,
+            bridge,
+            tls,
         })
     }
 
@@ -225,6 +484,38 @@ impl Config {
             anyhow::bail!("request_timeout must be greater than 0");
         }
 
+        if self.hedge.min_delay > self.hedge.max_delay {
+            anyhow::bail!("hedge min_delay must not exceed max_delay");
+        }
+
+        if self.cache.enabled && self.cache.max_entries == 0 {
+            anyhow::bail!("cache max_entries must be greater than 0");
+        }
+
+        if self.resume.enabled && (self.resume.buffer == 0 || self.resume.ttl.as_secs() == 0) {
+            anyhow::bail!("resume buffer and ttl must be greater than 0");
+        }
+
+        if let Some(webauthn) = &self.webauthn {
+            url::Url::parse(&webauthn.origin)
+                .with_context(|| format!("Invalid WebAuthn origin: {}", webauthn.origin))?;
+        }
+
+        url::Url::parse(&self.bridge.http_url)
+            .with_context(|| format!("Invalid MCP host URL: {}", self.bridge.http_url))?;
+        if self.bridge.probe_interval.as_secs() == 0 {
+            anyhow::bail!("bridge probe_interval must be at least 1s");
+        }
+
+        if let Some(tls) = &self.tls {
+            if tls.reload_interval.as_secs() == 0 {
+                anyhow::bail!("tls reload_interval must be greater than 0");
+            }
+            if let Some(acme) = &tls.acme {
+                acme.validate()?;
+            }
+        }
+
         // Validate upstream URLs
         for upstream in &self.upstreams {
             url::Url::parse(upstream)
@@ -235,7 +526,7 @@ impl Config {
     }
 }
 
-/// Parse duration strings like "1h", "30m", "5s"
+/// Parse duration strings like "1h", "30m", "5s", "250ms"
 fn parse_duration(s: &str) -> Result<Duration> {
     let s = s.trim();
 
@@ -246,12 +537,13 @@ fn parse_duration(s: &str) -> Result<Duration> {
     let (number_part, unit_part) = if let Some(pos) = s.find(|c: char| c.is_alphabetic()) {
         (&s[..pos], &s[pos..])
     } else {
-        anyhow::bail!("Duration must include a unit (s, m, h, d)");
+        anyhow::bail!("Duration must include a unit (ms, s, m, h, d)");
     };
 
     let number: u64 = number_part.parse().context("Invalid number in duration")?;
 
     let duration = match unit_part {
+        "ms" | "millis" | "milliseconds" => Duration::from_millis(number),
         "s" | "sec" | "second" | "seconds" => Duration::from_secs(number),
         "m" | "min" | "minute" | "minutes" => Duration::from_secs(number * 60),
         "h" | "hr" | "hour" | "hours" => Duration::from_secs(number * 3600),
@@ -6,6 +6,7 @@
 
 pub mod core;
 pub mod operations;
+pub mod session;
 pub mod wrapper;
 
 // Re-export core types for ergonomic use
@@ -17,5 +18,8 @@ pub use core::{
 // Re-export operations types
 pub use operations::{TokenMetadata, TokenChainValidation};
 
+// Re-export session token signer
+pub use session::SessionSigner;
+
 // Re-export wrapper types
 pub use wrapper::{SecureDiscoveryToken, SecureTokenStats, SecureTokenBuilder};
\ No newline at end of file
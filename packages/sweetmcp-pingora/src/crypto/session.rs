@@ -0,0 +1,78 @@
+//! Signed session tokens shared across the cluster
+//!
+//! Unlike discovery tokens, which are sealed to a node's own rotating
+//! keypair, session tokens must verify on whichever peer a client fails
+//! over to. They are authenticated with HMAC-SHA-512-256 under a key derived
+//! from the cluster-wide JWT secret.
+
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as B64, Engine as _};
+use serde::{de::DeserializeOwned, Serialize};
+use sodiumoxide::crypto::{auth, hash::sha256};
+
+/// Separates session token keys from any other use of the shared secret
+const KEY_CONTEXT: &[u8] = b"sweetmcp-session-token-v1";
+
+/// Signs and verifies `<payload>.<tag>` tokens
+pub struct SessionSigner {
+    key: auth::Key,
+}
+
+impl SessionSigner {
+    /// Derive the signing key from the shared cluster secret
+    pub fn from_secret(secret: &[u8; 32]) -> Result<Self> {
+        sodiumoxide::init().map_err(|_| anyhow::anyhow!("Failed to initialize sodiumoxide"))?;
+        let mut material = Vec::with_capacity(KEY_CONTEXT.len() + secret.len());
+        material.extend_from_slice(KEY_CONTEXT);
+        material.extend_from_slice(secret);
+        let digest = sha256::hash(&material);
+        let key = auth::Key::from_slice(&digest.0).context("Invalid session key length")?;
+        Ok(Self { key })
+    }
+
+    /// Serialize `claims` and append an authentication tag
+    pub fn sign<T: Serialize>(&self, claims: &T) -> Result<String> {
+        let payload = B64.encode(serde_json::to_vec(claims)?);
+        let tag = auth::authenticate(payload.as_bytes(), &self.key);
+        Ok(format!("{}.{}", payload, B64.encode(tag.as_ref())))
+    }
+
+    /// Check the tag and deserialize the claims
+    pub fn verify<T: DeserializeOwned>(&self, token: &str) -> Result<T> {
+        let (payload, tag) = token.split_once('.').context("Malformed session token")?;
+        let tag = B64
+            .decode(tag)
+            .ok()
+            .and_then(|raw| auth::Tag::from_slice(&raw))
+            .context("Malformed session token tag")?;
+        if !auth::verify(&tag, payload.as_bytes(), &self.key) {
+            anyhow::bail!("Session token signature mismatch");
+        }
+        let claims = B64
+            .decode(payload)
+            .context("Malformed session token payload")?;
+        Ok(serde_json::from_slice(&claims)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn tokens_verify_only_under_the_same_secret() {
+        let signer = SessionSigner::from_secret(&[7; 32]).unwrap();
+        let token = signer.sign(&json!({"sid": "abc", "seq": 3})).unwrap();
+
+        let claims: Value = signer.verify(&token).unwrap();
+        assert_eq!(claims["seq"], 3);
+
+        let other = SessionSigner::from_secret(&[8; 32]).unwrap();
+        assert!(other.verify::<Value>(&token).is_err());
+
+        let (payload, tag) = token.split_once('.').unwrap();
+        let forged = format!("{}x.{}", payload, tag);
+        assert!(signer.verify::<Value>(&forged).is_err());
+    }
+}
@@ -0,0 +1,262 @@
+//! Single-flight coalescing of identical concurrent tool calls.
+//!
+//! When several callers of the same tenant send the same `tools/call` (same
+//! tool, same arguments) while the first is still running, only the first is
+//! executed; the others wait for it and receive a copy of its response with
+//! their own JSON-RPC id. Tools whose `tools/list` entry says
+//! `destructiveHint: true` or `idempotentHint: false` are never coalesced, and
+//! a single call opts out with `"_meta": {"sweetmcp/dedup": false}`.
+
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hasher;
+use std::sync::{Arc, Mutex, RwLock};
+use tokio::sync::oneshot;
+
+/// `_meta` key a caller sets to `false` to always execute its call
+pub const OPT_OUT_META_KEY: &str = "sweetmcp/dedup";
+
+/// Coalescing settings
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct DedupConfig {
+    /// Coalesce identical concurrent tool calls at all
+    pub enabled: bool,
+}
+
+type Waiters = Vec<oneshot::Sender<Value>>;
+
+pub struct Coalescer {
+    cfg: DedupConfig,
+    excluded: RwLock<HashSet<String>>,
+    inflight: Arc<Mutex<HashMap<u64, Waiters>>>,
+}
+
+/// Role of a call in its flight
+pub enum Flight {
+    /// Execute the call, then hand the response to [`Leader::complete`]
+    Leader(Leader),
+    /// Wait for the leader's response; `Err` means the leader gave up and
+    /// the call should be executed after all
+    Follower(oneshot::Receiver<Value>),
+}
+
+/// The executing call of a flight. Dropping it without completing releases
+/// the followers so they run on their own.
+pub struct Leader {
+    key: u64,
+    inflight: Arc<Mutex<HashMap<u64, Waiters>>>,
+}
+
+impl Leader {
+    /// Share `response` with every follower that joined meanwhile
+    pub fn complete(self, response: &Value) -> usize {
+        let waiters = self.take_waiters();
+        let count = waiters.len();
+        for waiter in waiters {
+            let _ = waiter.send(response.clone());
+        }
+        count
+    }
+
+    fn take_waiters(&self) -> Waiters {
+        self.inflight
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .remove(&self.key)
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for Leader {
+    fn drop(&mut self) {
+        // Dropping the senders wakes the followers with an error
+        self.take_waiters();
+    }
+}
+
+impl Coalescer {
+    pub fn new(cfg: DedupConfig) -> Self {
+        Self {
+            cfg,
+            excluded: RwLock::new(HashSet::new()),
+            inflight: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Flight key of `request` for `tenant`, if it is a tool call that may be
+    /// coalesced
+    pub fn key(&self, request: &Value, tenant: &str) -> Option<u64> {
+        if !self.cfg.enabled {
+            return None;
+        }
+        if request.get("method").and_then(Value::as_str) != Some("tools/call") {
+            return None;
+        }
+        let params = request.get("params")?;
+        let tool = params.get("name").and_then(Value::as_str)?;
+        if params.pointer(&format!("/_meta/{}", OPT_OUT_META_KEY.replace('/', "~1")))
+            == Some(&Value::Bool(false))
+        {
+            return None;
+        }
+        if self
+            .excluded
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .contains(tool)
+        {
+            return None;
+        }
+
+        let mut hasher = seahash::SeaHasher::new();
+        hasher.write(tool.as_bytes());
+        hasher.write_u8(0);
+        hasher.write(tenant.as_bytes());
+        hasher.write_u8(0);
+        let args = params.get("arguments").map(canonical).unwrap_or_default();
+        hasher.write(args.as_bytes());
+        Some(hasher.finish())
+    }
+
+    /// Join the flight for `key`, becoming its leader if none is running
+    pub fn join(&self, key: u64) -> Flight {
+        let mut inflight = self.inflight.lock().unwrap_or_else(|p| p.into_inner());
+        match inflight.get_mut(&key) {
+            Some(waiters) => {
+                let (tx, rx) = oneshot::channel();
+                waiters.push(tx);
+                Flight::Follower(rx)
+            }
+            None => {
+                inflight.insert(key, Vec::new());
+                Flight::Leader(Leader {
+                    key,
+                    inflight: self.inflight.clone(),
+                })
+            }
+        }
+    }
+
+    /// Refresh the set of tools never coalesced from a `tools/list` response
+    pub fn learn_tools(&self, response: &Value) {
+        let Some(tools) = response.pointer("/result/tools").and_then(Value::as_array) else {
+            return;
+        };
+        let excluded: HashSet<String> = tools
+            .iter()
+            .filter(|tool| {
+                let hint = |key: &str| {
+                    tool.pointer(&format!("/annotations/{}", key))
+                        .and_then(Value::as_bool)
+                };
+                hint("destructiveHint") == Some(true) || hint("idempotentHint") == Some(false)
+            })
+            .filter_map(|tool| tool.get("name").and_then(Value::as_str))
+            .map(str::to_string)
+            .collect();
+        *self.excluded.write().unwrap_or_else(|p| p.into_inner()) = excluded;
+    }
+}
+
+/// `response` addressed to the request with `id`
+pub fn readdress(mut response: Value, id: Option<&Value>) -> Value {
+    if let (Some(object), Some(id)) = (response.as_object_mut(), id) {
+        object.insert("id".to_string(), id.clone());
+    }
+    response
+}
+
+/// JSON with object keys sorted, so argument order does not affect the key
+pub(crate) fn canonical(value: &Value) -> String {
+    fn sorted(value: &Value) -> Value {
+        match value {
+            Value::Object(map) => {
+                let mut entries: Vec<_> = map.iter().collect();
+                entries.sort_by(|a, b| a.0.cmp(b.0));
+                Value::Object(
+                    entries
+                        .into_iter()
+                        .map(|(k, v)| (k.clone(), sorted(v)))
+                        .collect(),
+                )
+            }
+            Value::Array(items) => Value::Array(items.iter().map(sorted).collect()),
+            other => other.clone(),
+        }
+    }
+    sorted(value).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn coalescer() -> Coalescer {
+        Coalescer::new(DedupConfig { enabled: true })
+    }
+
+    fn call(tool: &str, args: Value) -> Value {
+        json!({"jsonrpc": "2.0", "id": 1, "method": "tools/call",
+               "params": {"name": tool, "arguments": args}})
+    }
+
+    #[test]
+    fn keys_ignore_argument_order_but_not_tenant() {
+        let c = coalescer();
+        let a = c.key(&call("hash", json!({"a": 1, "b": 2})), "alice");
+        let b = c.key(&call("hash", json!({"b": 2, "a": 1})), "alice");
+        let other = c.key(&call("hash", json!({"a": 1, "b": 2})), "bob");
+        assert!(a.is_some());
+        assert_eq!(a, b);
+        assert_ne!(a, other);
+    }
+
+    #[test]
+    fn opt_outs_are_not_coalesced() {
+        let c = coalescer();
+        c.learn_tools(&json!({"result": {"tools": [
+            {"name": "rm", "annotations": {"destructiveHint": true}},
+            {"name": "hash", "annotations": {"readOnlyHint": true}},
+        ]}}));
+        assert_eq!(c.key(&call("rm", json!({})), "alice"), None);
+
+        let mut opted_out = call("hash", json!({}));
+        opted_out["params"]["_meta"] = json!({"sweetmcp/dedup": false});
+        assert_eq!(c.key(&opted_out, "alice"), None);
+        assert_eq!(c.key(&json!({"method": "tools/list"}), "alice"), None);
+    }
+
+    #[tokio::test]
+    async fn followers_share_the_leader_response() {
+        let c = coalescer();
+        let key = c.key(&call("hash", json!({})), "alice").unwrap();
+        let Flight::Leader(leader) = c.join(key) else {
+            panic!("first call must lead");
+        };
+        let Flight::Follower(follower) = c.join(key) else {
+            panic!("second call must follow");
+        };
+
+        let response = json!({"jsonrpc": "2.0", "id": 1, "result": {}});
+        assert_eq!(leader.complete(&response), 1);
+        let shared = readdress(follower.await.unwrap(), Some(&json!(2)));
+        assert_eq!(shared["id"], 2);
+
+        // The flight is over, so the next call leads again
+        assert!(matches!(c.join(key), Flight::Leader(_)));
+    }
+
+    #[tokio::test]
+    async fn dropped_leader_releases_followers() {
+        let c = coalescer();
+        let Flight::Leader(leader) = c.join(7) else {
+            panic!("first call must lead");
+        };
+        let Flight::Follower(follower) = c.join(7) else {
+            panic!("second call must follow");
+        };
+        drop(leader);
+        assert!(follower.await.is_err());
+    }
+}
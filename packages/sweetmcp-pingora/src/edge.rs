@@ -1,13 +1,20 @@
 //! Sugora EdgeService: auth, overload, routing.
 
 use crate::{
-    auth::JwtAuth,
+    access_log::{AccessLog, AccessLogEntry, Shipper},
+    api_keys::{self, ApiResponse, KEYS_PATH, WEBAUTHN_PATH},
+    auth::{Caller, JwtAuth},
+    cache::{self, EdgeCache},
     config::Config,
+    crypto::SessionSigner,
+    dedup::{self, Coalescer, Flight},
+    hedge::{Hedger, Winner},
     load::Load,
     metric_picker::MetricPicker,
     metrics,
-    peer_discovery::{PeerRegistry, PeersResponse, RegisterRequest, BUILD_ID},
+    peer_discovery::{DeregisterRequest, PeerRegistry, PeersResponse, RegisterRequest, BUILD_ID},
     rate_limit::AdvancedRateLimitManager,
+    resume::{self, MirrorRequest, Resumer},
     shutdown::ShutdownCoordinator,
 };
 use bytes::Bytes;
@@ -17,20 +24,32 @@ use pingora::Result;
 use pingora_load_balancing::Backend;
 use pingora_proxy::{ProxyHttp, Session};
 use rand::prelude::*;
+use serde_json::Value;
 use std::collections::BTreeSet;
 use std::future::Future;
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use sweetmcp_axum::iam::{ApiKeyStore, KEY_PREFIX};
 use tokio::sync::mpsc::Sender;
 
 pub struct EdgeService {
     cfg: Arc<Config>,
     auth: JwtAuth,
+    api_keys: Arc<ApiKeyStore>,
+    #[cfg(feature = "webauthn")]
+    webauthn: Option<Arc<crate::webauthn::AdminWebauthn>>,
     picker: Arc<MetricPicker>,
     load: Arc<Load>,
     #[allow(dead_code)]
     bridge_tx: Sender<crate::mcp_bridge::BridgeMsg>,
     peer_registry: PeerRegistry,
+    hedger: Arc<Hedger>,
+    coalescer: Arc<Coalescer>,
+    cache: Arc<EdgeCache>,
+    resumer: Arc<Resumer>,
+    access_log: AccessLog,
+    access_log_shipper: Mutex<Option<Shipper>>,
     rate_limit_manager: Arc<AdvancedRateLimitManager>,
     shutdown_coordinator: Arc<ShutdownCoordinator>,
 }
@@ -40,7 +59,7 @@ impl EdgeService {
         cfg: Arc<Config>,
         bridge_tx: Sender<crate::mcp_bridge::BridgeMsg>,
         peer_registry: PeerRegistry,
-    ) -> Self {
+    ) -> anyhow::Result<Self> {
         // Create Backend objects from upstream URLs
         let backends: BTreeSet<Backend> = cfg
             .upstreams
@@ -71,16 +90,54 @@ impl EdgeService {
             .join("sweetmcp");
         let shutdown_coordinator = Arc::new(ShutdownCoordinator::new(data_dir));
 
-        Self {
+        let api_keys = Arc::new(ApiKeyStore::open(&cfg.api_keys_file).map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to open API key store {}: {}",
+                cfg.api_keys_file.display(),
+                e
+            )
+        })?);
+
+        #[cfg(feature = "webauthn")]
+        let webauthn = match &cfg.webauthn {
+            Some(wcfg) => Some(Arc::new(crate::webauthn::AdminWebauthn::new(
+                wcfg,
+                cfg.api_keys_file.with_file_name("admin_passkeys.json"),
+            )?)),
+            None => None,
+        };
+        #[cfg(not(feature = "webauthn"))]
+        if cfg.webauthn.is_some() {
+            tracing::warn!("WebAuthn is configured but this build lacks the `webauthn` feature");
+        }
+
+        let resumer = Arc::new(Resumer::new(
+            cfg.resume.clone(),
+            SessionSigner::from_secret(&cfg.jwt_secret)?,
+            cfg.request_timeout,
+        ));
+
+        let (access_log, access_log_shipper) = AccessLog::new(cfg.access_log.clone());
+
+        Ok(Self {
             auth: JwtAuth::new(cfg.jwt_secret.clone(), cfg.jwt_expiry),
+            api_keys,
+            #[cfg(feature = "webauthn")]
+            webauthn,
             picker: Arc::new(MetricPicker::from_backends(&backends)),
             load: Arc::new(Load::new()),
             peer_registry,
+            hedger: Arc::new(Hedger::new(cfg.hedge.clone(), cfg.request_timeout)),
+            coalescer: Arc::new(Coalescer::new(cfg.dedup.clone())),
+            cache: Arc::new(EdgeCache::new(cfg.cache.clone())),
+            resumer,
+            access_log,
+            access_log_shipper: Mutex::new(access_log_shipper),
             rate_limit_manager,
             shutdown_coordinator,
             cfg,
             bridge_tx,
-        }
+        })
     }
 }
 
@@ -95,6 +152,15 @@ impl EdgeService {
         self.picker.clone()
     }
 
+    /// Take the access log shipper for background service setup, if the
+    /// access log goes to a Loki or Vector endpoint
+    pub fn access_log_shipper(&self) -> Option<Shipper> {
+        self.access_log_shipper
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .take()
+    }
+
     fn validate_discovery_token(&self, token: &str) -> bool {
         if let Ok(expected_token) = std::env::var("SWEETMCP_DISCOVERY_TOKEN") {
             !expected_token.is_empty() && token == expected_token
@@ -103,6 +169,439 @@ impl EdgeService {
         }
     }
 
+    /// Resolve the caller from an API key (`x-api-key` or `Bearer smcp_…`) or a JWT
+    fn authenticate(&self, req_header: &pingora::http::RequestHeader) -> Option<Caller> {
+        let header = |name: &str| req_header.headers.get(name).and_then(|h| h.to_str().ok());
+
+        let api_key = header("x-api-key").or_else(|| {
+            header("authorization")
+                .and_then(|h| h.strip_prefix("Bearer "))
+                .filter(|t| t.starts_with(KEY_PREFIX))
+        });
+        if let Some(token) = api_key {
+            let key = self.api_keys.authenticate(token)?;
+            let allowlist = key.tool_allowlist(&self.cfg.api_scopes);
+            return Some(Caller::ApiKey { key, allowlist });
+        }
+
+        self.auth
+            .verify(header("authorization").unwrap_or(""))
+            .ok()
+            .map(Caller::Jwt)
+    }
+
+    /// Handle an admin endpoint (`/api/keys`, `/api/admin/webauthn`) end to end
+    async fn serve_admin_endpoint(
+        &self,
+        session: &mut Session,
+        ctx: &HttpMetricsContext,
+        caller: Option<&Caller>,
+    ) -> Result<bool> {
+        let method = session.req_header().method.clone();
+        let path = session.req_header().uri.path().to_string();
+
+        let body = match session.read_request_body().await {
+            Ok(Some(body)) => body,
+            Ok(None) => Bytes::new(),
+            Err(_) => {
+                let response = ApiResponse::error(400, "Failed to read body");
+                return self.write_json(session, ctx, response).await;
+            }
+        };
+
+        let response = if path.starts_with(KEYS_PATH) {
+            api_keys::handle(
+                &self.api_keys,
+                &self.cfg.api_scopes,
+                &method,
+                &path,
+                &body,
+                caller,
+            )
+        } else {
+            self.handle_webauthn(&method, &path, &body, caller)
+        };
+        self.write_json(session, ctx, response).await
+    }
+
+    #[cfg(feature = "webauthn")]
+    fn handle_webauthn(
+        &self,
+        method: &Method,
+        path: &str,
+        body: &[u8],
+        caller: Option<&Caller>,
+    ) -> ApiResponse {
+        match &self.webauthn {
+            Some(webauthn) => webauthn.handle(method, path, body, caller, &self.auth),
+            None => ApiResponse::error(404, "WebAuthn is not configured"),
+        }
+    }
+
+    #[cfg(not(feature = "webauthn"))]
+    fn handle_webauthn(
+        &self,
+        _method: &Method,
+        _path: &str,
+        _body: &[u8],
+        _caller: Option<&Caller>,
+    ) -> ApiResponse {
+        ApiResponse::error(404, "WebAuthn support is not enabled in this build")
+    }
+
+    /// Answer a JSON-RPC request from the edge cache when a fresh entry
+    /// allows it, otherwise dispatch it and keep a cacheable result. The
+    /// status is `None` for requests the cache does not handle. `None` means
+    /// the bridge is gone.
+    async fn dispatch(
+        &self,
+        request: Value,
+        protocol_ctx: crate::normalize::ProtocolContext,
+        hopped: bool,
+        credentials: Vec<(&'static str, String)>,
+        tenant: &str,
+        cache_control: Option<&str>,
+    ) -> Option<(Value, Option<cache::Status>)> {
+        self.cache.observe(&request);
+        let Some(lookup) = self.cache.lookup(&request, tenant, cache_control) else {
+            let response = self
+                .dispatch_coalesced(request, protocol_ctx, hopped, credentials, tenant)
+                .await?;
+            self.cache.observe(&response);
+            return Some((response, None));
+        };
+
+        if let Some((response, age)) = self.cache.get(&lookup) {
+            metrics::record_cache(lookup.kind(), "hit");
+            let response = dedup::readdress(response, request.get("id"));
+            return Some((response, Some(cache::Status::Hit(age))));
+        }
+        metrics::record_cache(lookup.kind(), "miss");
+        let response = self
+            .dispatch_coalesced(request, protocol_ctx, hopped, credentials, tenant)
+            .await?;
+        self.cache.observe(&response);
+        self.cache.store(&lookup, &response);
+        Some((response, Some(cache::Status::Miss)))
+    }
+
+    /// Run a JSON-RPC request through the MCP bridge. A tool call identical
+    /// to one `tenant` already has in flight waits for that call's response
+    /// instead of running again. `None` means the bridge is gone.
+    async fn dispatch_coalesced(
+        &self,
+        request: Value,
+        protocol_ctx: crate::normalize::ProtocolContext,
+        hopped: bool,
+        credentials: Vec<(&'static str, String)>,
+        tenant: &str,
+    ) -> Option<Value> {
+        let Some(key) = self.coalescer.key(&request, tenant) else {
+            return self
+                .dispatch_uncoalesced(request, protocol_ctx, hopped, credentials)
+                .await;
+        };
+
+        let leader = match self.coalescer.join(key) {
+            Flight::Leader(leader) => leader,
+            Flight::Follower(rx) => {
+                // If the leader gave up, run the call after all
+                if let Ok(response) = rx.await {
+                    if let Some(tool) = request.pointer("/params/name").and_then(Value::as_str) {
+                        metrics::record_coalesced(tool);
+                    }
+                    return Some(dedup::readdress(response, request.get("id")));
+                }
+                return self
+                    .dispatch_uncoalesced(request, protocol_ctx, hopped, credentials)
+                    .await;
+            }
+        };
+
+        let response = self
+            .dispatch_uncoalesced(request, protocol_ctx, hopped, credentials)
+            .await;
+        if let Some(response) = &response {
+            leader.complete(response);
+        }
+        response
+    }
+
+    /// Run a JSON-RPC request through the MCP bridge. Calls to idempotent
+    /// tools that outlast the tool's p95 are also sent to a healthy peer and
+    /// the first answer wins. `None` means the bridge is gone.
+    async fn dispatch_uncoalesced(
+        &self,
+        request: Value,
+        protocol_ctx: crate::normalize::ProtocolContext,
+        hopped: bool,
+        credentials: Vec<(&'static str, String)>,
+    ) -> Option<Value> {
+        let hedge = if hopped {
+            None
+        } else {
+            self.hedger
+                .hedgeable_tool(&request)
+                .map(|tool| (tool, request.clone()))
+        };
+        let is_tools_list = request.get("method").and_then(Value::as_str) == Some("tools/list");
+
+        let (tx, mut rx) = tokio::sync::oneshot::channel();
+        let started = Instant::now();
+        if let Err(e) = self.bridge_tx.send((request, protocol_ctx, tx)).await {
+            tracing::error!("Failed to send to MCP bridge: {}", e);
+            return None;
+        }
+
+        let Some((tool, request)) = hedge else {
+            let response = rx.await.ok();
+            match &response {
+                Some(response) if is_tools_list => {
+                    self.hedger.learn_tools(response);
+                    self.coalescer.learn_tools(response);
+                    self.cache.learn_tools(response);
+                }
+                Some(_) => {}
+                None => tracing::error!("MCP bridge response channel closed"),
+            }
+            return response;
+        };
+
+        tokio::select! {
+            response = &mut rx => {
+                self.hedger.record(&tool, started.elapsed());
+                return response.ok();
+            }
+            _ = tokio::time::sleep(self.hedger.delay(&tool)) => {}
+        }
+
+        let peer = self
+            .peer_registry
+            .get_healthy_peers()
+            .choose(&mut rand::rng())
+            .copied();
+        let Some(peer) = peer else {
+            let response = rx.await.ok();
+            self.hedger.record(&tool, started.elapsed());
+            return response;
+        };
+
+        tracing::debug!("Hedging {} to peer {}", tool, peer);
+        let hedged = self.hedger.send(peer, &request, &credentials);
+        tokio::pin!(hedged);
+        let first = tokio::select! {
+            response = &mut rx => Err(response.ok()),
+            response = &mut hedged => Ok(response),
+        };
+
+        match first {
+            Err(primary) => {
+                self.hedger.record(&tool, started.elapsed());
+                metrics::record_hedge(&tool, Winner::Primary.as_str());
+                primary
+            }
+            Ok(Ok(response)) => {
+                self.peer_registry.mark_peer_success(&peer);
+                metrics::record_hedge(&tool, Winner::Hedge.as_str());
+                // Let the local call finish so its latency still counts
+                let hedger = self.hedger.clone();
+                tokio::spawn(async move {
+                    if rx.await.is_ok() {
+                        hedger.record(&tool, started.elapsed());
+                    }
+                });
+                Some(response)
+            }
+            Ok(Err(e)) => {
+                tracing::warn!("Hedged call of {} to {} failed: {}", tool, peer, e);
+                self.peer_registry.mark_peer_failed(&peer);
+                let response = rx.await.ok();
+                self.hedger.record(&tool, started.elapsed());
+                response
+            }
+        }
+    }
+
+    /// Buffer a response delivered on `sid`, mirror it in the background and
+    /// return the resumption token to send with it.
+    fn checkpoint_response(&self, sid: &str, caller: &Caller, response: &Value) -> Option<String> {
+        let healthy = self.peer_registry.get_healthy_peers();
+        let recorded = match self
+            .resumer
+            .record(sid, caller.subject(), response, &healthy)
+        {
+            Ok(recorded) => recorded,
+            Err(e) => {
+                tracing::warn!("Not issuing a resumption token for {}: {}", sid, e);
+                return None;
+            }
+        };
+        self.spawn_mirror(sid, caller.subject(), recorded.mirror, recorded.entries);
+        Some(recorded.token)
+    }
+
+    fn spawn_mirror(
+        &self,
+        sid: &str,
+        sub: &str,
+        mirror: Option<std::net::SocketAddr>,
+        entries: Vec<resume::Buffered>,
+    ) {
+        let Some(peer) = mirror.filter(|_| !entries.is_empty()) else {
+            return;
+        };
+        let request = MirrorRequest {
+            session: sid.to_string(),
+            sub: sub.to_string(),
+            entries,
+        };
+        let resumer = self.resumer.clone();
+        let registry = self.peer_registry.clone();
+        tokio::spawn(async move {
+            let token = std::env::var("SWEETMCP_DISCOVERY_TOKEN").ok();
+            if let Err(e) = resumer.mirror(peer, &request, token.as_deref()).await {
+                tracing::warn!(
+                    "Mirroring session {} to {} failed: {}",
+                    request.session,
+                    peer,
+                    e
+                );
+                registry.mark_peer_failed(&peer);
+            }
+        });
+    }
+
+    /// `POST /api/resume`: reattach a session from its resumption token and
+    /// return the messages the client missed.
+    async fn serve_resume(
+        &self,
+        session: &mut Session,
+        ctx: &HttpMetricsContext,
+        caller: &Caller,
+    ) -> Result<bool> {
+        if !self.resumer.enabled() {
+            return self
+                .write_json(
+                    session,
+                    ctx,
+                    ApiResponse::error(404, "Session resumption is disabled"),
+                )
+                .await;
+        }
+        let Some(token) = session
+            .req_header()
+            .headers
+            .get(resume::TOKEN_HEADER)
+            .and_then(|h| h.to_str().ok())
+            .map(str::to_string)
+        else {
+            let response = ApiResponse::error(400, "Missing x-mcp-resume-token header");
+            return self.write_json(session, ctx, response).await;
+        };
+
+        let discovery_token = std::env::var("SWEETMCP_DISCOVERY_TOKEN").ok();
+        let (claims, missed) = match self
+            .resumer
+            .resume(&token, caller.subject(), discovery_token.as_deref())
+            .await
+        {
+            Ok(resumed) => resumed,
+            Err(e) => {
+                metrics::record_resume(e.as_str());
+                tracing::info!("Session resumption by {} refused: {}", caller.subject(), e);
+                // 410 tells the client to start a fresh session
+                return self
+                    .write_json(session, ctx, ApiResponse::error(410, e.to_string()))
+                    .await;
+            }
+        };
+
+        let healthy = self.peer_registry.get_healthy_peers();
+        let recorded = match self.resumer.checkpoint(&claims.sid, &healthy) {
+            Ok(recorded) => recorded,
+            Err(e) => {
+                tracing::error!("Failed to checkpoint resumed session {}: {}", claims.sid, e);
+                let response = ApiResponse::error(500, "Failed to resume session");
+                return self.write_json(session, ctx, response).await;
+            }
+        };
+        self.spawn_mirror(&claims.sid, &claims.sub, recorded.mirror, recorded.entries);
+        metrics::record_resume("resumed");
+
+        let body = serde_json::json!({
+            "session": claims.sid,
+            "messages": missed.into_iter().map(|entry| entry.message).collect::<Vec<_>>(),
+        });
+        let body_bytes = Bytes::from(body.to_string());
+        let mut resp_header = ResponseHeader::build(200, None)?;
+        resp_header.insert_header("Content-Type", "application/json")?;
+        resp_header.insert_header("Content-Length", body_bytes.len().to_string())?;
+        resp_header.insert_header(resume::SESSION_HEADER, claims.sid.as_str())?;
+        resp_header.insert_header(resume::TOKEN_HEADER, recorded.token)?;
+        session
+            .write_response_header(Box::new(resp_header), false)
+            .await?;
+        session
+            .write_response_body(Some(body_bytes.clone()), true)
+            .await?;
+        self.record_http_metrics_and_cleanup(ctx, 200, body_bytes.len());
+        Ok(true)
+    }
+
+    /// `/api/resume/log` between peers: `POST` stores mirrored entries,
+    /// `GET ?session=&after=` returns them to the node taking a session over.
+    fn handle_session_log(&self, method: &Method, query: Option<&str>, body: &[u8]) -> ApiResponse {
+        if *method == Method::POST {
+            let request: MirrorRequest = match serde_json::from_slice(body) {
+                Ok(request) => request,
+                Err(_) => return ApiResponse::error(400, "Invalid JSON"),
+            };
+            return match self
+                .resumer
+                .absorb(&request.session, &request.sub, request.entries)
+            {
+                Ok(()) => ApiResponse::new(200, serde_json::json!({"status": "stored"})),
+                Err(e) => ApiResponse::error(409, e.to_string()),
+            };
+        }
+
+        let params: std::collections::HashMap<String, String> =
+            url::form_urlencoded::parse(query.unwrap_or("").as_bytes())
+                .into_owned()
+                .collect();
+        let (Some(sid), Some(after)) = (
+            params.get("session"),
+            params.get("after").and_then(|a| a.parse::<u64>().ok()),
+        ) else {
+            return ApiResponse::error(400, "session and after are required");
+        };
+        match self.resumer.since(sid, after) {
+            Some(entries) => ApiResponse::new(200, serde_json::json!(entries)),
+            None => ApiResponse::error(404, "Session log not available"),
+        }
+    }
+
+    async fn write_json(
+        &self,
+        session: &mut Session,
+        ctx: &HttpMetricsContext,
+        response: ApiResponse,
+    ) -> Result<bool> {
+        let body_bytes = Bytes::from(response.body.to_string());
+        let mut resp_header = ResponseHeader::build(response.status, None)?;
+        resp_header.insert_header("Content-Type", "application/json")?;
+        resp_header.insert_header("Content-Length", body_bytes.len().to_string())?;
+
+        session
+            .write_response_header(Box::new(resp_header), false)
+            .await?;
+        session
+            .write_response_body(Some(body_bytes.clone()), true)
+            .await?;
+        self.record_http_metrics_and_cleanup(ctx, response.status, body_bytes.len());
+        Ok(true)
+    }
+
     /// Record HTTP metrics and decrement active request counters
     fn record_http_metrics_and_cleanup(
         &self,
@@ -127,6 +626,20 @@ impl EdgeService {
 
             // Decrement active request counters
             metrics::decrement_active_requests(method, endpoint);
+
+            self.access_log.record(AccessLogEntry {
+                client: ctx.client.clone(),
+                protocol: ctx.protocol_context.as_ref().map(|p| p.protocol.as_str()),
+                http_method: method.clone(),
+                path: endpoint.clone(),
+                method: ctx.rpc_method.clone(),
+                tool: ctx.tool.clone(),
+                status: status_code,
+                latency_ms: duration * 1000.0,
+                request_bytes: ctx.request_size,
+                response_bytes: response_size,
+                ..Default::default()
+            });
         }
 
         // Decrement load counter (lock-free atomic operation)
@@ -142,6 +655,12 @@ pub struct HttpMetricsContext {
     pub method: Option<String>,
     pub endpoint: Option<String>,
     pub protocol_context: Option<crate::normalize::ProtocolContext>,
+    /// Client IP address
+    pub client: Option<String>,
+    /// Normalized JSON-RPC method of an MCP request
+    pub rpc_method: Option<String>,
+    /// Tool named by a `tools/call`
+    pub tool: Option<String>,
 }
 
 impl ProxyHttp for EdgeService {
@@ -167,6 +686,12 @@ impl ProxyHttp for EdgeService {
             ctx.start_time = Some(std::time::Instant::now());
             ctx.method = Some(session.req_header().method.to_string());
             ctx.endpoint = Some(session.req_header().uri.path().to_string());
+            ctx.client = session.client_addr().and_then(|addr| match addr {
+                pingora::protocols::l4::socket::SocketAddr::Inet(inet_addr) => {
+                    Some(inet_addr.ip().to_string())
+                }
+                _ => None,
+            });
 
             // Estimate request size from headers and body length
             let headers_size = session
@@ -341,8 +866,21 @@ impl ProxyHttp for EdgeService {
                     }
                 };
 
-                // Check if build_id matches
-                if request.build_id != BUILD_ID {
+                let replaces = match request.replaces.as_deref().map(str::parse) {
+                    Some(Ok(addr)) => Some(addr),
+                    Some(Err(_)) => {
+                        let response_body = b"Invalid replaced peer address";
+                        let _ = session
+                            .respond_error_with_body(400, Bytes::from_static(response_body))
+                            .await;
+                        self.record_http_metrics_and_cleanup(ctx, 400, response_body.len());
+                        return Ok(true);
+                    }
+                    None => None,
+                };
+
+                // Check if build_id matches; a deploy handoff is expected to differ
+                if request.build_id != BUILD_ID && replaces.is_none() {
                     let error_msg = format!(
                         "Build ID mismatch: expected '{}', got '{}'",
                         BUILD_ID, request.build_id
@@ -368,13 +906,20 @@ impl ProxyHttp for EdgeService {
                 };
 
                 // Add the peer to the registry
-                let added = self.peer_registry.add_peer(peer_addr);
-
-                // Return success response
-                let response_body = if added {
-                    r#"{"status":"added"}"#
-                } else {
-                    r#"{"status":"already_registered"}"#
+                let response_body = match replaces {
+                    Some(old) if self.peer_registry.replace_peer(&old, peer_addr) => {
+                        r#"{"status":"replaced"}"#
+                    }
+                    Some(old) => {
+                        let error_msg = format!("Replaced peer {} is not registered", old);
+                        let response_bytes = Bytes::from(error_msg);
+                        let response_size = response_bytes.len();
+                        let _ = session.respond_error_with_body(409, response_bytes).await;
+                        self.record_http_metrics_and_cleanup(ctx, 409, response_size);
+                        return Ok(true);
+                    }
+                    None if self.peer_registry.add_peer(peer_addr) => r#"{"status":"added"}"#,
+                    None => r#"{"status":"already_registered"}"#,
                 };
 
                 let mut resp_header = pingora::http::ResponseHeader::build(200, None)?;
@@ -391,6 +936,67 @@ impl ProxyHttp for EdgeService {
                 return Ok(true);
             }
 
+            // Drop a peer that is being drained
+            if path == "/api/deregister" && method == Method::POST {
+                let discovery_token = session
+                    .req_header()
+                    .headers
+                    .get("x-discovery-token")
+                    .and_then(|h| h.to_str().ok())
+                    .unwrap_or("");
+
+                if !self.validate_discovery_token(discovery_token) {
+                    let response = ApiResponse::error(401, "Invalid discovery token");
+                    return self.write_json(session, ctx, response).await;
+                }
+                let request = match session.read_request_body().await {
+                    Ok(body) => {
+                        serde_json::from_slice::<DeregisterRequest>(&body.unwrap_or_default())
+                    }
+                    Err(_) => {
+                        let response = ApiResponse::error(400, "Failed to read body");
+                        return self.write_json(session, ctx, response).await;
+                    }
+                };
+                let response = match request.map(|r| r.peer.parse::<std::net::SocketAddr>()) {
+                    Ok(Ok(addr)) if self.peer_registry.remove_peer(&addr) => {
+                        ApiResponse::new(200, serde_json::json!({ "status": "removed" }))
+                    }
+                    Ok(Ok(_)) => {
+                        ApiResponse::new(200, serde_json::json!({ "status": "not_registered" }))
+                    }
+                    Ok(Err(_)) => ApiResponse::error(400, "Invalid peer address"),
+                    Err(_) => ApiResponse::error(400, "Invalid JSON"),
+                };
+                return self.write_json(session, ctx, response).await;
+            }
+
+            // Session log mirroring between peers
+            if path == resume::LOG_PATH && (method == Method::GET || method == Method::POST) {
+                let discovery_token = session
+                    .req_header()
+                    .headers
+                    .get("x-discovery-token")
+                    .and_then(|h| h.to_str().ok())
+                    .unwrap_or("");
+
+                if !self.validate_discovery_token(discovery_token) {
+                    let response = ApiResponse::error(401, "Invalid discovery token");
+                    return self.write_json(session, ctx, response).await;
+                }
+                let query = session.req_header().uri.query().map(str::to_string);
+                let body = match session.read_request_body().await {
+                    Ok(Some(body)) => body,
+                    Ok(None) => Bytes::new(),
+                    Err(_) => {
+                        let response = ApiResponse::error(400, "Failed to read body");
+                        return self.write_json(session, ctx, response).await;
+                    }
+                };
+                let response = self.handle_session_log(&method, query.as_deref(), &body);
+                return self.write_json(session, ctx, response).await;
+            }
+
             // Health check endpoint - no authentication required
             if path == "/health" {
                 let response_body = b"OK";
@@ -407,7 +1013,9 @@ impl ProxyHttp for EdgeService {
                 return Ok(true);
             }
 
-            if path == "/api/peers" || path == "/api/register" {
+            if matches!(path, "/api/peers" | "/api/register" | "/api/deregister")
+                || path == resume::LOG_PATH
+            {
                 // Wrong method for these endpoints
                 let response_body = b"Method not allowed";
                 let _ = session
@@ -417,17 +1025,22 @@ impl ProxyHttp for EdgeService {
                 return Ok(true);
             }
 
-            // Authentication check for other endpoints
-            let auth_hdr = session
-                .req_header()
-                .headers
-                .get("authorization")
-                .and_then(|h| h.to_str().ok())
-                .unwrap_or("");
+            // Authentication check for other endpoints: API key or JWT
+            let caller = self.authenticate(session.req_header());
+
+            // Key management and admin login decide their own auth requirements
+            if path == KEYS_PATH
+                || path.starts_with("/api/keys/")
+                || path.starts_with(WEBAUTHN_PATH)
+            {
+                return self
+                    .serve_admin_endpoint(session, ctx, caller.as_ref())
+                    .await;
+            }
 
-            let claims = match self.auth.verify(auth_hdr) {
-                Ok(c) => c,
-                Err(_) => {
+            let caller = match caller {
+                Some(c) => c,
+                None => {
                     let response_body = b"Unauthorized";
                     let _ = session
                         .respond_error_with_body(401, Bytes::from_static(response_body))
@@ -437,6 +1050,10 @@ impl ProxyHttp for EdgeService {
                 }
             };
 
+            if path == resume::RESUME_PATH && method == Method::POST {
+                return self.serve_resume(session, ctx, &caller).await;
+            }
+
             // Check if this is an MCP request
             if is_mcp_request(session.req_header()) {
                 // Read request body
@@ -457,7 +1074,7 @@ impl ProxyHttp for EdgeService {
                 // Normalize protocol to JSON-RPC
                 let (protocol_ctx, json_rpc_request) =
                     match crate::normalize::to_json_rpc_with_headers(
-                        &claims.sub,
+                        caller.subject(),
                         &body,
                         Some(session.req_header()),
                     ) {
@@ -472,27 +1089,72 @@ impl ProxyHttp for EdgeService {
                             return Ok(true);
                         }
                     };
+                ctx.rpc_method = json_rpc_request
+                    .get("method")
+                    .and_then(Value::as_str)
+                    .map(str::to_string);
+                ctx.tool = json_rpc_request
+                    .pointer("/params/name")
+                    .and_then(Value::as_str)
+                    .filter(|_| ctx.rpc_method.as_deref() == Some("tools/call"))
+                    .map(str::to_string);
+
+                // API keys may only call the tools their scopes allow
+                if let Some(tool) = api_keys::denied_tool(&caller, &json_rpc_request) {
+                    tracing::warn!(
+                        "API key {} denied tools/call for {}",
+                        caller.subject(),
+                        tool
+                    );
+                    let response_bytes = Bytes::from(format!("Tool not permitted: {}", tool));
+                    let response_size = response_bytes.len();
+                    let _ = session.respond_error_with_body(403, response_bytes).await;
+                    self.record_http_metrics_and_cleanup(ctx, 403, response_size);
+                    return Ok(true);
+                }
 
                 // Store protocol context for response conversion
                 ctx.protocol_context = Some(protocol_ctx.clone());
 
-                // Send to MCP bridge
-                let (tx, rx) = tokio::sync::oneshot::channel();
-                let bridge_msg = (json_rpc_request, protocol_ctx, tx);
+                let session_id = [resume::SESSION_HEADER, resume::LEGACY_SESSION_HEADER]
+                    .into_iter()
+                    .find_map(|name| session.req_header().headers.get(name)?.to_str().ok())
+                    .filter(|_| self.resumer.enabled())
+                    .map(str::to_string);
 
-                if let Err(e) = self.bridge_tx.send(bridge_msg).await {
-                    tracing::error!("Failed to send to MCP bridge: {}", e);
-                    let response_body = b"Internal server error";
-                    let _ = session
-                        .respond_error_with_body(500, Bytes::from_static(response_body))
-                        .await;
-                    self.record_http_metrics_and_cleanup(ctx, 500, response_body.len());
-                    return Ok(true);
-                }
+                let cache_control = session
+                    .req_header()
+                    .headers
+                    .get("cache-control")
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_string);
+
+                // Send to MCP bridge, hedging slow idempotent tool calls
+                let hopped = session.req_header().headers.get("x-polygate-hop").is_some();
+                let credentials: Vec<(&'static str, String)> = ["authorization", "x-api-key"]
+                    .into_iter()
+                    .filter_map(|name| {
+                        let value = session.req_header().headers.get(name)?.to_str().ok()?;
+                        Some((name, value.to_string()))
+                    })
+                    .collect();
+
+                match self
+                    .dispatch(
+                        json_rpc_request,
+                        protocol_ctx,
+                        hopped,
+                        credentials,
+                        caller.subject(),
+                        cache_control.as_deref(),
+                    )
+                    .await
+                {
+                    Some((json_rpc_response, cache_status)) => {
+                        let resume_token = session_id.as_deref().and_then(|sid| {
+                            self.checkpoint_response(sid, &caller, &json_rpc_response)
+                        });
 
-                // Await response from bridge
-                match rx.await {
-                    Ok(json_rpc_response) => {
                         // Convert response back to original protocol
                         let response_bytes = match crate::normalize::from_json_rpc(
                             ctx.protocol_context.as_ref().unwrap(),
@@ -523,6 +1185,15 @@ impl ProxyHttp for EdgeService {
                         resp_header.insert_header("Content-Type", content_type)?;
                         resp_header
                             .insert_header("Content-Length", response_bytes.len().to_string())?;
+                        if let Some(token) = resume_token {
+                            resp_header.insert_header(resume::TOKEN_HEADER, token)?;
+                        }
+                        if let Some(status) = cache_status {
+                            resp_header.insert_header(cache::STATUS_HEADER, status.as_str())?;
+                            if let cache::Status::Hit(age) = status {
+                                resp_header.insert_header("Age", age.as_secs().to_string())?;
+                            }
+                        }
 
                         session
                             .write_response_header(Box::new(resp_header), false)
@@ -535,8 +1206,7 @@ impl ProxyHttp for EdgeService {
                         self.record_http_metrics_and_cleanup(ctx, 200, response_len);
                         return Ok(true); // Request handled
                     }
-                    Err(_) => {
-                        tracing::error!("MCP bridge response channel closed");
+                    None => {
                         let response_body = b"Internal server error";
                         let _ = session
                             .respond_error_with_body(500, Bytes::from_static(response_body))
@@ -0,0 +1,223 @@
+//! Request hedging for idempotent tool calls.
+//!
+//! When the local backend has not answered a `tools/call` within the tool's
+//! recent p95 latency, the same request is sent to a healthy peer and
+//! whichever response arrives first wins. Only tools whose `tools/list`
+//! entry carries `annotations.idempotentHint` or `annotations.readOnlyHint`
+//! are hedged, since the losing call still runs to completion.
+
+use serde_json::Value;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::net::SocketAddr;
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+
+/// Latency samples kept per tool
+const WINDOW: usize = 256;
+
+/// Below this many samples the p95 is noise, so `max_delay` is used
+const MIN_SAMPLES: usize = 20;
+
+/// Hedging settings
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct HedgeConfig {
+    /// Hedge idempotent tool calls at all
+    pub enabled: bool,
+
+    /// Never hedge sooner than this
+    pub min_delay: Duration,
+
+    /// Never wait longer than this before hedging
+    pub max_delay: Duration,
+}
+
+impl Default for HedgeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_delay: Duration::from_millis(50),
+            max_delay: Duration::from_secs(2),
+        }
+    }
+}
+
+/// Which side of a hedged call answered first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Winner {
+    Primary,
+    Hedge,
+}
+
+impl Winner {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Winner::Primary => "primary",
+            Winner::Hedge => "hedge",
+        }
+    }
+}
+
+pub struct Hedger {
+    cfg: HedgeConfig,
+    idempotent: RwLock<HashSet<String>>,
+    latency: Mutex<HashMap<String, VecDeque<Duration>>>,
+    client: reqwest::Client,
+}
+
+impl Hedger {
+    pub fn new(cfg: HedgeConfig, timeout: Duration) -> Self {
+        Self {
+            cfg,
+            idempotent: RwLock::new(HashSet::new()),
+            latency: Mutex::new(HashMap::new()),
+            client: reqwest::Client::builder()
+                .timeout(timeout)
+                .build()
+                .unwrap_or_else(|_| reqwest::Client::new()),
+        }
+    }
+
+    /// The tool name if `request` is a single call to a tool safe to hedge
+    pub fn hedgeable_tool(&self, request: &Value) -> Option<String> {
+        if !self.cfg.enabled {
+            return None;
+        }
+        if request.get("method").and_then(Value::as_str) != Some("tools/call") {
+            return None;
+        }
+        let tool = request.pointer("/params/name").and_then(Value::as_str)?;
+        self.idempotent
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .contains(tool)
+            .then(|| tool.to_string())
+    }
+
+    /// Refresh the idempotent tool set from a `tools/list` response
+    pub fn learn_tools(&self, response: &Value) {
+        let Some(tools) = response.pointer("/result/tools").and_then(Value::as_array) else {
+            return;
+        };
+        let safe: HashSet<String> = tools
+            .iter()
+            .filter(|tool| {
+                let hint = |key: &str| {
+                    tool.pointer(&format!("/annotations/{}", key))
+                        .and_then(Value::as_bool)
+                        .unwrap_or(false)
+                };
+                hint("idempotentHint") || hint("readOnlyHint")
+            })
+            .filter_map(|tool| tool.get("name").and_then(Value::as_str))
+            .map(str::to_string)
+            .collect();
+        *self.idempotent.write().unwrap_or_else(|p| p.into_inner()) = safe;
+    }
+
+    /// Record how long the local backend took for `tool`
+    pub fn record(&self, tool: &str, elapsed: Duration) {
+        let mut latency = self.latency.lock().unwrap_or_else(|p| p.into_inner());
+        let samples = latency.entry(tool.to_string()).or_default();
+        if samples.len() == WINDOW {
+            samples.pop_front();
+        }
+        samples.push_back(elapsed);
+    }
+
+    /// How long to wait for the local backend before hedging `tool`
+    pub fn delay(&self, tool: &str) -> Duration {
+        let latency = self.latency.lock().unwrap_or_else(|p| p.into_inner());
+        let p95 = latency.get(tool).and_then(|samples| p95(samples));
+        p95.unwrap_or(self.cfg.max_delay)
+            .clamp(self.cfg.min_delay, self.cfg.max_delay)
+    }
+
+    /// Send the JSON-RPC request to a peer edge with the caller's credential
+    /// headers, marked so it is not hedged or forwarded again.
+    pub async fn send(
+        &self,
+        peer: SocketAddr,
+        request: &Value,
+        credentials: &[(&'static str, String)],
+    ) -> anyhow::Result<Value> {
+        let scheme = if peer.port() == 443 { "https" } else { "http" };
+        let mut req = self
+            .client
+            .post(format!("{}://{}/mcp", scheme, peer))
+            .header("content-type", "application/json")
+            .header("x-polygate-hop", "1")
+            .json(request);
+        for (name, value) in credentials {
+            req = req.header(*name, value);
+        }
+        let response = req.send().await?.error_for_status()?;
+        Ok(response.json::<Value>().await?)
+    }
+}
+
+fn p95(samples: &VecDeque<Duration>) -> Option<Duration> {
+    if samples.len() < MIN_SAMPLES {
+        return None;
+    }
+    let mut sorted: Vec<Duration> = samples.iter().copied().collect();
+    sorted.sort_unstable();
+    let idx = (sorted.len() * 95).div_ceil(100) - 1;
+    Some(sorted[idx])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn hedger() -> Hedger {
+        Hedger::new(
+            HedgeConfig {
+                enabled: true,
+                ..HedgeConfig::default()
+            },
+            Duration::from_secs(5),
+        )
+    }
+
+    #[test]
+    fn only_annotated_tools_are_hedged() {
+        let hedger = hedger();
+        hedger.learn_tools(&json!({"result": {"tools": [
+            {"name": "hash", "annotations": {"idempotentHint": true}},
+            {"name": "time", "annotations": {"readOnlyHint": true}},
+            {"name": "eval_js"},
+        ]}}));
+
+        let call = |name: &str| json!({"method": "tools/call", "params": {"name": name}});
+        assert_eq!(
+            hedger.hedgeable_tool(&call("hash")).as_deref(),
+            Some("hash")
+        );
+        assert_eq!(
+            hedger.hedgeable_tool(&call("time")).as_deref(),
+            Some("time")
+        );
+        assert_eq!(hedger.hedgeable_tool(&call("eval_js")), None);
+        assert_eq!(
+            hedger.hedgeable_tool(&json!({"method": "tools/list"})),
+            None
+        );
+    }
+
+    #[test]
+    fn delay_tracks_p95_within_bounds() {
+        let hedger = hedger();
+        assert_eq!(hedger.delay("hash"), Duration::from_secs(2));
+
+        for ms in 1..=100 {
+            hedger.record("hash", Duration::from_millis(ms * 2));
+        }
+        assert_eq!(hedger.delay("hash"), Duration::from_millis(190));
+
+        for _ in 0..WINDOW {
+            hedger.record("hash", Duration::from_millis(1));
+        }
+        assert_eq!(hedger.delay("hash"), Duration::from_millis(50));
+    }
+}
@@ -1,11 +1,19 @@
+pub mod access_log;
+pub mod api_keys;
 pub mod auth;
+pub mod cache;
 pub mod circuit_breaker;
 pub mod config;
 pub mod crypto;
+pub mod dedup;
 pub mod dns_discovery;
+pub mod hedge;
 pub mod mdns_discovery;
 pub mod metrics;
 pub mod peer_discovery;
 pub mod rate_limit;
+pub mod resume;
 pub mod shutdown;
 pub mod tls;
+#[cfg(feature = "webauthn")]
+pub mod webauthn;
@@ -3,12 +3,17 @@
 //! A production-grade, multi-protocol edge proxy built on Pingora 0.5 that normalizes
 //! GraphQL, JSON-RPC 2.0, and Cap'n Proto into Model Context Protocol (MCP) requests.
 
+mod access_log;
+mod api_keys;
 mod auth;
+mod cache;
 mod circuit_breaker;
 mod config;
 mod crypto;
+mod dedup;
 mod dns_discovery;
 mod edge;
+mod hedge;
 mod load;
 mod mcp_bridge;
 mod mdns_discovery;
@@ -17,8 +22,11 @@ mod metrics;
 mod normalize;
 mod peer_discovery;
 mod rate_limit;
+mod resume;
 mod shutdown;
 mod tls;
+#[cfg(feature = "webauthn")]
+mod webauthn;
 
 use anyhow::Result;
 use config::Config;
@@ -72,6 +80,7 @@ fn run_server() -> Result<()> {
         "mcp-bridge",
         McpBridgeService {
             rx: Some(bridge_rx),
+            config: cfg.bridge.clone(),
         },
     );
 
@@ -117,7 +126,7 @@ fn run_server() -> Result<()> {
 
     // Create HTTP proxy service
     let edge_service =
-        edge::EdgeService::new(cfg.clone(), bridge_tx.clone(), peer_registry.clone());
+        edge::EdgeService::new(cfg.clone(), bridge_tx.clone(), peer_registry.clone())?;
 
     // Add rate limit cleanup service
     let rate_limit_service = background_service(
@@ -137,10 +146,40 @@ fn run_server() -> Result<()> {
     );
     server.add_service(metrics_service);
 
+    // Ship access logs to Loki or Vector when a sink is configured
+    if let Some(shipper) = edge_service.access_log_shipper() {
+        let access_log_service = background_service(
+            "access-log-shipper",
+            AccessLogShipperService {
+                shipper: std::sync::Mutex::new(Some(shipper)),
+            },
+        );
+        server.add_service(access_log_service);
+    }
+
     let mut proxy_service = pingora_proxy::http_proxy_service(&server.configuration, edge_service);
 
-    // Add TCP listeners
-    proxy_service.add_tcp(&cfg.tcp_bind);
+    // Add TCP listeners; the public one serves TLS when certificates are configured
+    match &cfg.tls {
+        Some(tls_config) => {
+            let store = tls::reload::CertStore::open(tls_config)?;
+            let mut tls_settings = pingora::listeners::TlsSettings::with_callbacks(Box::new(
+                tls::reload::CertCallback(store.clone()),
+            ))?;
+            tls_settings.enable_h2();
+            proxy_service.add_tls_with_settings(&cfg.tcp_bind, None, tls_settings);
+
+            let cert_service = background_service(
+                "tls-certificates",
+                CertRenewalService {
+                    store,
+                    config: tls_config.clone(),
+                },
+            );
+            server.add_service(cert_service);
+        }
+        None => proxy_service.add_tcp(&cfg.tcp_bind),
+    }
     proxy_service.add_tcp(&cfg.mcp_bind);
 
     // Add Unix socket listener
@@ -173,7 +212,11 @@ fn run_server() -> Result<()> {
     // The exporter automatically registers with the default prometheus registry
 
     log::info!("🚀 Sugora Gateway ready!");
-    log::info!("  TCP: {}", cfg.tcp_bind);
+    if cfg.tls.is_some() {
+        log::info!("  TLS: {}", cfg.tcp_bind);
+    } else {
+        log::info!("  TCP: {}", cfg.tcp_bind);
+    }
     log::info!("  MCP HTTP: {}", cfg.mcp_bind);
     log::info!("  UDS: {}", cfg.uds_path);
     log::info!("  Metrics: http://{}/metrics", cfg.metrics_bind);
@@ -200,6 +243,7 @@ use std::time::Duration;
 
 struct McpBridgeService {
     rx: Option<mpsc::Receiver<mcp_bridge::BridgeMsg>>,
+    config: mcp_bridge::BridgeConfig,
 }
 
 impl BackgroundService for McpBridgeService {
@@ -216,11 +260,12 @@ impl BackgroundService for McpBridgeService {
             let this = self as *const Self as *mut Self;
             (*this).rx.take().expect("start called twice")
         };
+        let config = self.config.clone();
 
         Box::pin(async move {
             log::info!("🔌 Starting MCP bridge");
             tokio::select! {
-                _ = mcp_bridge::run(rx) => {
+                _ = mcp_bridge::run(rx, config) => {
                     log::info!("MCP bridge stopped");
                 }
                 _ = shutdown.changed() => {
@@ -326,6 +371,71 @@ impl BackgroundService for PeerDiscoveryService {
     }
 }
 
+struct CertRenewalService {
+    store: Arc<tls::reload::CertStore>,
+    config: tls::reload::TlsConfig,
+}
+
+impl BackgroundService for CertRenewalService {
+    fn start<'life0, 'async_trait>(
+        &'life0 self,
+        mut shutdown: ShutdownWatch,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + 'async_trait>>
+    where
+        'life0: 'async_trait,
+        Self: 'async_trait,
+    {
+        let store = self.store.clone();
+        let config = self.config.clone();
+
+        Box::pin(async move {
+            log::info!("🔐 Starting TLS certificate reload service");
+            let mut check_interval = tokio::time::interval(config.reload_interval);
+            // Failed ACME orders back off so a misconfiguration doesn't hit CA rate limits
+            let mut backoff = Duration::from_secs(300);
+            let mut next_attempt = tokio::time::Instant::now();
+
+            loop {
+                tokio::select! {
+                    _ = check_interval.tick() => {
+                        if let Err(e) = store.reload_if_changed() {
+                            log::error!("TLS certificate reload failed, keeping the current one: {:#}", e);
+                        }
+
+                        let Some(acme) = &config.acme else { continue };
+                        if store.current().remaining() > acme.renew_before
+                            || tokio::time::Instant::now() < next_attempt
+                        {
+                            continue;
+                        }
+                        log::info!("Requesting ACME certificate for {}", acme.domains.join(", "));
+                        let issued = tokio::select! {
+                            issued = tls::acme::issue(acme) => issued,
+                            _ = shutdown.changed() => break,
+                        };
+                        match issued.and_then(|(chain, key)| store.replace(&chain, &key)) {
+                            Ok(()) => backoff = Duration::from_secs(300),
+                            Err(e) => {
+                                log::error!(
+                                    "ACME issuance failed, retrying in {}m: {:#}",
+                                    backoff.as_secs() / 60,
+                                    e
+                                );
+                                next_attempt = tokio::time::Instant::now() + backoff;
+                                backoff = (backoff * 2).min(Duration::from_secs(6 * 3600));
+                            }
+                        }
+                    }
+                    _ = shutdown.changed() => {
+                        log::info!("TLS certificate reload shutting down");
+                        break;
+                    }
+                }
+            }
+        })
+    }
+}
+
 struct RateLimitCleanupService {
     rate_limiter: Arc<rate_limit::AdvancedRateLimitManager>,
 }
@@ -421,3 +531,39 @@ impl BackgroundService for MetricsCollectorService {
         })
     }
 }
+
+struct AccessLogShipperService {
+    shipper: std::sync::Mutex<Option<access_log::Shipper>>,
+}
+
+impl BackgroundService for AccessLogShipperService {
+    fn start<'life0, 'async_trait>(
+        &'life0 self,
+        mut shutdown: ShutdownWatch,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + 'async_trait>>
+    where
+        'life0: 'async_trait,
+        Self: 'async_trait,
+    {
+        let shipper = self
+            .shipper
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .take();
+
+        Box::pin(async move {
+            let Some(shipper) = shipper else {
+                return;
+            };
+            log::info!("📜 Starting access log shipper");
+            tokio::select! {
+                _ = shipper.run() => {
+                    log::info!("Access log shipper stopped");
+                }
+                _ = shutdown.changed() => {
+                    log::info!("Access log shipper shutting down");
+                }
+            }
+        })
+    }
+}
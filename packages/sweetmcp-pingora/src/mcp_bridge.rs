@@ -1,7 +1,21 @@
+//! Bridge from the gateway to the local axum MCP host.
+//!
+//! When the host's Unix socket exists, requests go over it with
+//! length-prefixed frames on one kept-open connection, skipping the TCP
+//! loopback stack. Otherwise, or when the socket can't be reached, they are
+//! POSTed to the host's HTTP endpoint as before. Request latency is recorded
+//! per transport, and a periodic `ping` over both yields how much faster the
+//! socket is.
+
 use serde_json::Value;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use sweetmcp_axum::framing;
 use sweetmcp_axum::JSONRPC_VERSION;
+use tokio::io::AsyncWriteExt;
+use tokio::net::UnixStream;
 use tokio::sync::{mpsc, oneshot};
-use tracing::{error, info};
+use tracing::{debug, error, info, warn};
 
 // Bridge message type for communication between Pingora and MCP handler
 pub type BridgeMsg = (
@@ -10,18 +24,159 @@ pub type BridgeMsg = (
     oneshot::Sender<Value>,
 );
 
-// Run the MCP bridge that processes incoming messages
-pub async fn run(mut rx: mpsc::Receiver<BridgeMsg>) {
-    info!("MCP bridge started and ready to process messages");
+/// Where the axum host is reached
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct BridgeConfig {
+    /// JSON-RPC over HTTP endpoint
+    pub http_url: String,
 
-    while let Some((request, _protocol_ctx, tx)) = rx.recv().await {
-        // Forward JSON-RPC request to sweetmcp-axum via HTTP
-        let client = reqwest::Client::new();
+    /// Unix socket preferred over `http_url` while it exists; `None` always
+    /// uses HTTP
+    pub socket: Option<PathBuf>,
+
+    /// How often both transports are pinged to compare their latency
+    pub probe_interval: Duration,
+}
+
+impl Default for BridgeConfig {
+    fn default() -> Self {
+        Self {
+            http_url: "http://localhost:8080/rpc".to_string(),
+            socket: Some(PathBuf::from("/var/run/cyrup-mcp.sock")),
+            probe_interval: Duration::from_secs(60),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Transport {
+    Uds,
+    Tcp,
+}
 
-        let response = match client
-            .post("http://localhost:8080/rpc")
+impl Transport {
+    fn as_str(self) -> &'static str {
+        match self {
+            Transport::Uds => "uds",
+            Transport::Tcp => "tcp",
+        }
+    }
+}
+
+/// A framed connection to the host's socket
+struct UdsConnection {
+    stream: UnixStream,
+    buf: Vec<u8>,
+    /// Whether a call has already completed on it
+    used: bool,
+}
+
+impl UdsConnection {
+    async fn connect(path: &std::path::Path) -> std::io::Result<Self> {
+        let mut stream = UnixStream::connect(path).await?;
+        stream.write_all(framing::PREAMBLE).await?;
+        Ok(Self {
+            stream,
+            buf: Vec::with_capacity(64 * 1024),
+            used: false,
+        })
+    }
+
+    /// Send `request` and read its response; `None` for a message the host
+    /// doesn't answer
+    async fn call(&mut self, request: &Value) -> std::io::Result<Option<Value>> {
+        let payload = serde_json::to_vec(request)?;
+        framing::write_frame(&mut self.stream, &payload).await?;
+        if !framing::read_frame(&mut self.stream, &mut self.buf).await? {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        self.used = true;
+        if self.buf.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_slice(&self.buf)?))
+    }
+}
+
+struct Bridge {
+    config: BridgeConfig,
+    http: reqwest::Client,
+    uds: Option<UdsConnection>,
+}
+
+impl Bridge {
+    fn new(config: BridgeConfig) -> Self {
+        Self {
+            config,
+            http: reqwest::Client::new(),
+            uds: None,
+        }
+    }
+
+    /// Forward `request` over the socket when the host has one, else HTTP
+    async fn forward(&mut self, request: &Value) -> Value {
+        let started = Instant::now();
+        let (transport, response) = match self.forward_uds(request).await {
+            Some(response) => (Transport::Uds, response),
+            None => (Transport::Tcp, self.forward_http(request).await),
+        };
+        crate::metrics::record_bridge_request(
+            transport.as_str(),
+            "request",
+            started.elapsed().as_secs_f64(),
+        );
+        response
+    }
+
+    /// `None` when the socket can't be used and the request was not sent,
+    /// so it can go over HTTP instead
+    async fn forward_uds(&mut self, request: &Value) -> Option<Value> {
+        let path = self.config.socket.as_ref()?;
+        if !path.exists() {
+            self.uds = None;
+            return None;
+        }
+
+        for attempt in 0..2 {
+            if self.uds.is_none() {
+                match UdsConnection::connect(path).await {
+                    Ok(connection) => self.uds = Some(connection),
+                    Err(e) => {
+                        debug!("MCP host socket {} unavailable: {}", path.display(), e);
+                        return None;
+                    }
+                }
+            }
+            let connection = self.uds.as_mut()?;
+            let reused = connection.used;
+            match connection.call(request).await {
+                Ok(response) => return Some(response.unwrap_or(Value::Null)),
+                Err(e) => {
+                    self.uds = None;
+                    // A kept-open connection that hits EOF or a reset was
+                    // almost always closed while idle, e.g. by a host restart,
+                    // so the request is sent once more on a fresh one. A
+                    // failure on a fresh connection may have run the request
+                    // and is not retried.
+                    if reused && attempt == 0 && is_stale(&e) {
+                        debug!("MCP host socket connection went stale, reconnecting");
+                        continue;
+                    }
+                    error!("Failed to forward request over MCP host socket: {}", e);
+                    return Some(internal_error(request, "backend unavailable"));
+                }
+            }
+        }
+        None
+    }
+
+    async fn forward_http(&self, request: &Value) -> Value {
+        // Forward JSON-RPC request to sweetmcp-axum via HTTP
+        match self
+            .http
+            .post(&self.config.http_url)
             .header("content-type", "application/json")
-            .json(&request)
+            .json(request)
             .send()
             .await
         {
@@ -29,33 +184,198 @@ pub async fn run(mut rx: mpsc::Receiver<BridgeMsg>) {
                 Ok(json_response) => json_response,
                 Err(e) => {
                     error!("Failed to parse JSON response from Axum: {:?}", e);
-                    serde_json::json!({
-                        "jsonrpc": JSONRPC_VERSION,
-                        "error": {
-                            "code": -32603,
-                            "message": "Internal error: invalid response from backend"
-                        },
-                        "id": request.get("id").cloned().unwrap_or(Value::Null)
-                    })
+                    internal_error(request, "invalid response from backend")
                 }
             },
             Err(e) => {
                 error!("Failed to forward request to Axum: {:?}", e);
-                serde_json::json!({
-                    "jsonrpc": JSONRPC_VERSION,
-                    "error": {
-                        "code": -32603,
-                        "message": "Internal error: backend unavailable"
-                    },
-                    "id": request.get("id").cloned().unwrap_or(Value::Null)
-                })
+                internal_error(request, "backend unavailable")
             }
+        }
+    }
+
+    /// Ping the host over both transports and record how they compare
+    async fn probe(&mut self) {
+        if self
+            .config
+            .socket
+            .as_ref()
+            .is_none_or(|path| !path.exists())
+        {
+            return;
+        }
+        let ping = serde_json::json!({
+            "jsonrpc": JSONRPC_VERSION,
+            "id": "bridge-probe",
+            "method": "ping"
+        });
+
+        let started = Instant::now();
+        let Some(response) = self.forward_uds(&ping).await else {
+            return;
         };
+        let uds = started.elapsed();
+        if response.get("error").is_some() {
+            return;
+        }
+
+        let started = Instant::now();
+        let response = self.forward_http(&ping).await;
+        let tcp = started.elapsed();
+        if response.get("error").is_some() {
+            return;
+        }
+
+        crate::metrics::record_bridge_request("uds", "probe", uds.as_secs_f64());
+        crate::metrics::record_bridge_request("tcp", "probe", tcp.as_secs_f64());
+        crate::metrics::set_bridge_uds_speedup(speedup(uds, tcp));
+    }
+}
 
-        if let Err(e) = tx.send(response) {
-            error!("Failed to send response back through bridge: {:?}", e);
+// Run the MCP bridge that processes incoming messages
+pub async fn run(mut rx: mpsc::Receiver<BridgeMsg>, config: BridgeConfig) {
+    info!("MCP bridge started and ready to process messages");
+    if let Some(socket) = &config.socket {
+        info!(
+            "MCP bridge prefers host socket {} over {}",
+            socket.display(),
+            config.http_url
+        );
+    }
+
+    let mut probe = tokio::time::interval(config.probe_interval.max(Duration::from_secs(1)));
+    probe.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut bridge = Bridge::new(config);
+
+    loop {
+        tokio::select! {
+            message = rx.recv() => {
+                let Some((request, _protocol_ctx, tx)) = message else {
+                    break;
+                };
+                let response = bridge.forward(&request).await;
+                if let Err(e) = tx.send(response) {
+                    error!("Failed to send response back through bridge: {:?}", e);
+                }
+            }
+            _ = probe.tick() => bridge.probe().await,
         }
     }
 
     info!("MCP bridge shutting down");
 }
+
+fn internal_error(request: &Value, detail: &str) -> Value {
+    serde_json::json!({
+        "jsonrpc": JSONRPC_VERSION,
+        "error": {
+            "code": -32603,
+            "message": format!("Internal error: {}", detail)
+        },
+        "id": request.get("id").cloned().unwrap_or(Value::Null)
+    })
+}
+
+fn is_stale(e: &std::io::Error) -> bool {
+    matches!(
+        e.kind(),
+        std::io::ErrorKind::BrokenPipe
+            | std::io::ErrorKind::ConnectionReset
+            | std::io::ErrorKind::UnexpectedEof
+    )
+}
+
+/// How many times longer the TCP round trip took than the socket's
+fn speedup(uds: Duration, tcp: Duration) -> f64 {
+    if uds.is_zero() {
+        warn!("MCP host socket ping took no measurable time");
+        return 1.0;
+    }
+    tcp.as_secs_f64() / uds.as_secs_f64()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::UnixListener;
+
+    /// A host socket answering each framed request with its id, and
+    /// notifications with an empty frame
+    fn serve(listener: UnixListener, connections: usize) {
+        tokio::spawn(async move {
+            for _ in 0..connections {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut preamble = [0u8; framing::PREAMBLE.len()];
+                tokio::io::AsyncReadExt::read_exact(&mut stream, &mut preamble)
+                    .await
+                    .unwrap();
+                assert_eq!(&preamble, framing::PREAMBLE);
+                let mut frame = Vec::new();
+                while framing::read_frame(&mut stream, &mut frame).await.unwrap() {
+                    let request: Value = serde_json::from_slice(&frame).unwrap();
+                    let payload = match request.get("id") {
+                        Some(id) => serde_json::to_vec(&serde_json::json!({
+                            "jsonrpc": JSONRPC_VERSION,
+                            "result": {"echo": id},
+                            "id": id
+                        }))
+                        .unwrap(),
+                        None => Vec::new(),
+                    };
+                    framing::write_frame(&mut stream, &payload).await.unwrap();
+                    // Close after one request to leave the bridge a stale connection
+                    if connections > 1 {
+                        break;
+                    }
+                }
+            }
+        });
+    }
+
+    fn config(socket: PathBuf) -> BridgeConfig {
+        BridgeConfig {
+            // Nothing listens here, so a request that falls back fails
+            http_url: "http://127.0.0.1:9/rpc".to_string(),
+            socket: Some(socket),
+            probe_interval: Duration::from_secs(60),
+        }
+    }
+
+    #[tokio::test]
+    async fn requests_go_over_the_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("host.sock");
+        serve(UnixListener::bind(&path).unwrap(), 1);
+        let mut bridge = Bridge::new(config(path));
+
+        let request = serde_json::json!({"jsonrpc": "2.0", "id": 7, "method": "ping"});
+        let response = bridge.forward(&request).await;
+        assert_eq!(response["result"]["echo"], 7);
+
+        let notification =
+            serde_json::json!({"jsonrpc": "2.0", "method": "notifications/initialized"});
+        assert_eq!(bridge.forward(&notification).await, Value::Null);
+    }
+
+    #[tokio::test]
+    async fn stale_connection_is_replaced() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("host.sock");
+        serve(UnixListener::bind(&path).unwrap(), 2);
+        let mut bridge = Bridge::new(config(path));
+
+        for id in [1, 2] {
+            let request = serde_json::json!({"jsonrpc": "2.0", "id": id, "method": "ping"});
+            assert_eq!(bridge.forward(&request).await["result"]["echo"], id);
+        }
+    }
+
+    #[tokio::test]
+    async fn missing_socket_falls_back_to_http() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut bridge = Bridge::new(config(dir.path().join("absent.sock")));
+        let request = serde_json::json!({"jsonrpc": "2.0", "id": 1, "method": "ping"});
+        assert!(bridge.forward_uds(&request).await.is_none());
+        assert_eq!(bridge.forward(&request).await["error"]["code"], -32603);
+    }
+}
@@ -2,8 +2,8 @@
 
 use once_cell::sync::Lazy;
 use prometheus::{
-    register_counter_vec, register_histogram_vec, register_int_gauge, register_int_gauge_vec,
-    CounterVec, HistogramVec, IntGauge, IntGaugeVec,
+    register_counter_vec, register_gauge, register_histogram_vec, register_int_gauge,
+    register_int_gauge_vec, CounterVec, Gauge, HistogramVec, IntGauge, IntGaugeVec,
 };
 
 /// Discovery operation counter
@@ -95,6 +95,138 @@ pub fn record_circuit_breaker_state(peer: &str, state: &str) {
         .inc();
 }
 
+/// Hedged tool calls by which side answered first
+pub static HEDGED_REQUESTS: Lazy<CounterVec> = Lazy::new(|| {
+    register_counter_vec!(
+        "sweetmcp_hedged_requests_total",
+        "Total number of hedged tool calls by winning side",
+        &["tool", "winner"]
+    )
+    .unwrap_or_else(|e| {
+        tracing::error!("Failed to register hedged request counter: {}", e);
+        std::process::exit(1)
+    })
+});
+
+/// Record which side of a hedged tool call answered first
+pub fn record_hedge(tool: &str, winner: &str) {
+    HEDGED_REQUESTS.with_label_values(&[tool, winner]).inc();
+}
+
+/// Round trips to the local MCP host by transport
+pub static BRIDGE_DURATION: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "sweetmcp_bridge_request_duration_seconds",
+        "Round trip to the local MCP host in seconds, by transport (uds or tcp) and kind (request or probe)",
+        &["transport", "kind"],
+        vec![0.0001, 0.00025, 0.0005, 0.001, 0.0025, 0.005, 0.01, 0.05, 0.25, 1.0, 5.0]
+    )
+    .unwrap_or_else(|e| {
+        tracing::error!("Failed to register bridge latency: {}", e);
+        std::process::exit(1)
+    })
+});
+
+/// TCP ping latency to the MCP host divided by the socket's
+pub static BRIDGE_UDS_SPEEDUP: Lazy<Gauge> = Lazy::new(|| {
+    register_gauge!(
+        "sweetmcp_bridge_uds_speedup_ratio",
+        "Latest TCP loopback ping to the MCP host divided by the Unix socket ping"
+    )
+    .unwrap_or_else(|e| {
+        tracing::error!("Failed to register bridge speedup gauge: {}", e);
+        std::process::exit(1)
+    })
+});
+
+/// Record a round trip to the local MCP host
+pub fn record_bridge_request(transport: &str, kind: &str, duration_secs: f64) {
+    BRIDGE_DURATION
+        .with_label_values(&[transport, kind])
+        .observe(duration_secs);
+}
+
+/// Record how much faster the socket answered the latest probe
+pub fn set_bridge_uds_speedup(ratio: f64) {
+    BRIDGE_UDS_SPEEDUP.set(ratio);
+}
+
+/// Tool calls answered from an identical call already in flight
+pub static COALESCED_REQUESTS: Lazy<CounterVec> = Lazy::new(|| {
+    register_counter_vec!(
+        "sweetmcp_coalesced_requests_total",
+        "Total number of tool calls served by an identical in-flight call",
+        &["tool"]
+    )
+    .unwrap_or_else(|e| {
+        tracing::error!("Failed to register coalesced request counter: {}", e);
+        std::process::exit(1)
+    })
+});
+
+/// Record a tool call that shared another call's response
+pub fn record_coalesced(tool: &str) {
+    COALESCED_REQUESTS.with_label_values(&[tool]).inc();
+}
+
+/// Cacheable requests by whether the edge cache answered
+pub static EDGE_CACHE_REQUESTS: Lazy<CounterVec> = Lazy::new(|| {
+    register_counter_vec!(
+        "sweetmcp_edge_cache_requests_total",
+        "Total number of cacheable requests by kind and outcome",
+        &["kind", "outcome"]
+    )
+    .unwrap_or_else(|e| {
+        tracing::error!("Failed to register edge cache counter: {}", e);
+        std::process::exit(1)
+    })
+});
+
+/// Record whether a cacheable request was answered from the edge cache
+pub fn record_cache(kind: &str, outcome: &str) {
+    EDGE_CACHE_REQUESTS
+        .with_label_values(&[kind, outcome])
+        .inc();
+}
+
+/// Access log lines lost before reaching the sink
+pub static ACCESS_LOG_DROPPED: Lazy<CounterVec> = Lazy::new(|| {
+    register_counter_vec!(
+        "sweetmcp_access_log_dropped_total",
+        "Total number of access log lines dropped by reason",
+        &["reason"]
+    )
+    .unwrap_or_else(|e| {
+        tracing::error!("Failed to register access log drop counter: {}", e);
+        std::process::exit(1)
+    })
+});
+
+/// Record access log lines that were dropped
+pub fn record_access_log_dropped(reason: &str, lines: usize) {
+    ACCESS_LOG_DROPPED
+        .with_label_values(&[reason])
+        .inc_by(lines as f64);
+}
+
+/// Session resumption attempts by outcome
+pub static SESSION_RESUMPTIONS: Lazy<CounterVec> = Lazy::new(|| {
+    register_counter_vec!(
+        "sweetmcp_session_resumptions_total",
+        "Total number of session resumption attempts by outcome",
+        &["outcome"]
+    )
+    .unwrap_or_else(|e| {
+        tracing::error!("Failed to register session resumption counter: {}", e);
+        std::process::exit(1)
+    })
+});
+
+/// Record the outcome of a session resumption attempt
+pub fn record_resume(outcome: &str) {
+    SESSION_RESUMPTIONS.with_label_values(&[outcome]).inc();
+}
+
 // ============================================================================
 // HTTP Request/Response Metrics for Enterprise Observability
 // ============================================================================
@@ -101,6 +101,40 @@ impl PeerRegistry {
         }
     }
 
+    /// Forget a peer, e.g. a node that is being drained
+    pub fn remove_peer(&self, addr: &SocketAddr) -> bool {
+        let mut peers = match self.inner.write() {
+            Ok(peers) => peers,
+            Err(poisoned) => {
+                tracing::warn!("Peer registry write lock poisoned during remove_peer, recovering");
+                poisoned.into_inner()
+            }
+        };
+        let removed = peers.remove(addr).is_some();
+        if removed {
+            info!("Removed peer: {}", addr);
+        }
+        removed
+    }
+
+    /// Swap `old` for `new` in one step (blue/green handoff). Nothing changes
+    /// unless `old` is currently registered.
+    pub fn replace_peer(&self, old: &SocketAddr, new: SocketAddr) -> bool {
+        let mut peers = match self.inner.write() {
+            Ok(peers) => peers,
+            Err(poisoned) => {
+                tracing::warn!("Peer registry write lock poisoned during replace_peer, recovering");
+                poisoned.into_inner()
+            }
+        };
+        if peers.remove(old).is_none() {
+            return false;
+        }
+        info!("Peer {} replaced by {}", old, new);
+        peers.insert(new, PeerInfo::new(new));
+        true
+    }
+
     /// Mark a peer as successfully contacted
     pub fn mark_peer_success(&self, addr: &SocketAddr) {
         let mut peers = match self.inner.write() {
@@ -225,6 +259,16 @@ pub struct PeersResponse {
 pub struct RegisterRequest {
     pub peer: String,
     pub build_id: String,
+    /// Registered peer this one takes over from during a blue/green deploy;
+    /// a handoff may carry a different build ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replaces: Option<String>,
+}
+
+/// Request body for /api/deregister endpoint
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeregisterRequest {
+    pub peer: String,
 }
 
 /// Pingora ServiceDiscovery implementation for dynamic peer discovery
@@ -0,0 +1,473 @@
+//! Resumable MCP sessions across node failover.
+//!
+//! Every JSON-RPC message delivered on a session (identified by its
+//! `Mcp-Session-Id`) is numbered and kept in a bounded per-session log, which
+//! is mirrored to one healthy peer. Each response carries a signed resumption
+//! token naming the session, the caller, the mirror peer and the sequence
+//! number just delivered. If this node dies, the client presents the token to
+//! any other peer, which fetches the log from the mirror, replays everything
+//! after that sequence number and takes the session over.
+
+use crate::crypto::SessionSigner;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Request header naming the session
+pub const SESSION_HEADER: &str = "mcp-session-id";
+
+/// Legacy spelling of [`SESSION_HEADER`]
+pub const LEGACY_SESSION_HEADER: &str = "x-mcp-session-id";
+
+/// Response header carrying the latest token; also the request header on resume
+pub const TOKEN_HEADER: &str = "x-mcp-resume-token";
+
+/// Client endpoint for reattaching with a token
+pub const RESUME_PATH: &str = "/api/resume";
+
+/// Peer endpoint for mirroring and fetching session logs
+pub const LOG_PATH: &str = "/api/resume/log";
+
+/// Resumption settings
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ResumeConfig {
+    /// Issue resumption tokens at all
+    pub enabled: bool,
+
+    /// Messages kept per session for replay
+    pub buffer: usize,
+
+    /// Token lifetime, and how long an idle session log is kept
+    pub ttl: Duration,
+}
+
+impl Default for ResumeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            buffer: 128,
+            ttl: Duration::from_secs(15 * 60),
+        }
+    }
+}
+
+/// What a resumption token vouches for
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResumeClaims {
+    /// Session id
+    pub sid: String,
+    /// Caller the session belongs to
+    pub sub: String,
+    /// Peer holding a mirror of the session log
+    pub mirror: Option<SocketAddr>,
+    /// Last sequence number delivered to the client
+    pub seq: u64,
+    /// Expiry, seconds since the Unix epoch
+    pub exp: u64,
+}
+
+/// A numbered message in a session log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Buffered {
+    pub seq: u64,
+    pub message: Value,
+}
+
+/// Body of `POST /api/resume/log`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MirrorRequest {
+    pub session: String,
+    pub sub: String,
+    pub entries: Vec<Buffered>,
+}
+
+/// Why a session could not be resumed
+#[derive(Debug, thiserror::Error)]
+pub enum ResumeError {
+    #[error("invalid resumption token: {0}")]
+    InvalidToken(anyhow::Error),
+    #[error("resumption token expired")]
+    Expired,
+    #[error("resumption token belongs to another caller")]
+    WrongCaller,
+    #[error("session log is gone or no longer covers the missed messages")]
+    Unavailable,
+}
+
+impl ResumeError {
+    /// Label for the resumption outcome metric
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ResumeError::InvalidToken(_) => "invalid",
+            ResumeError::Expired => "expired",
+            ResumeError::WrongCaller => "wrong_caller",
+            ResumeError::Unavailable => "unavailable",
+        }
+    }
+}
+
+struct SessionLog {
+    sub: String,
+    next_seq: u64,
+    entries: VecDeque<Buffered>,
+    mirror: Option<SocketAddr>,
+    touched: Instant,
+}
+
+impl SessionLog {
+    fn new(sub: &str) -> Self {
+        Self {
+            sub: sub.to_string(),
+            next_seq: 1,
+            entries: VecDeque::new(),
+            mirror: None,
+            touched: Instant::now(),
+        }
+    }
+
+    /// Entries after `seq`, or `None` if some of them were already evicted
+    fn since(&self, seq: u64) -> Option<Vec<Buffered>> {
+        match self.entries.front() {
+            Some(first) if first.seq > seq + 1 => None,
+            None if self.next_seq > seq + 1 => None,
+            _ => Some(
+                self.entries
+                    .iter()
+                    .filter(|entry| entry.seq > seq)
+                    .cloned()
+                    .collect(),
+            ),
+        }
+    }
+
+    fn push(&mut self, entry: Buffered, capacity: usize) {
+        if self
+            .entries
+            .back()
+            .is_some_and(|last| last.seq >= entry.seq)
+        {
+            return;
+        }
+        self.next_seq = entry.seq + 1;
+        if self.entries.len() == capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+        self.touched = Instant::now();
+    }
+}
+
+/// Result of recording a delivered message
+pub struct Recorded {
+    /// Token to hand the client with this message
+    pub token: String,
+    /// Where to mirror `entries`, if anywhere
+    pub mirror: Option<SocketAddr>,
+    /// Entries the mirror does not have yet
+    pub entries: Vec<Buffered>,
+}
+
+pub struct Resumer {
+    cfg: ResumeConfig,
+    signer: SessionSigner,
+    logs: Mutex<HashMap<String, SessionLog>>,
+    client: reqwest::Client,
+}
+
+impl Resumer {
+    pub fn new(cfg: ResumeConfig, signer: SessionSigner, timeout: Duration) -> Self {
+        Self {
+            cfg,
+            signer,
+            logs: Mutex::new(HashMap::new()),
+            client: reqwest::Client::builder()
+                .timeout(timeout)
+                .build()
+                .unwrap_or_else(|_| reqwest::Client::new()),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.cfg.enabled
+    }
+
+    /// Number and buffer a message delivered on `sid`
+    pub fn record(
+        &self,
+        sid: &str,
+        sub: &str,
+        message: &Value,
+        healthy: &[SocketAddr],
+    ) -> anyhow::Result<Recorded> {
+        let mut logs = self.logs.lock().unwrap_or_else(|p| p.into_inner());
+        if !logs.contains_key(sid) {
+            let ttl = self.cfg.ttl;
+            logs.retain(|_, log| log.touched.elapsed() < ttl);
+        }
+        let log = logs
+            .entry(sid.to_string())
+            .or_insert_with(|| SessionLog::new(sub));
+        if log.sub != sub {
+            anyhow::bail!("session {} belongs to another caller", sid);
+        }
+
+        let entry = Buffered {
+            seq: log.next_seq,
+            message: message.clone(),
+        };
+        log.push(entry.clone(), self.cfg.buffer);
+        self.seal(sid, log, Some(entry), healthy)
+    }
+
+    /// Issue a token for the current end of `sid`, e.g. after taking it over
+    pub fn checkpoint(&self, sid: &str, healthy: &[SocketAddr]) -> anyhow::Result<Recorded> {
+        let mut logs = self.logs.lock().unwrap_or_else(|p| p.into_inner());
+        let log = logs
+            .get_mut(sid)
+            .ok_or_else(|| anyhow::anyhow!("unknown session {}", sid))?;
+        self.seal(sid, log, None, healthy)
+    }
+
+    /// Keep the current mirror while it is among `healthy` peers, otherwise
+    /// pick a new one (which then needs the whole log), and sign a token.
+    fn seal(
+        &self,
+        sid: &str,
+        log: &mut SessionLog,
+        fresh: Option<Buffered>,
+        healthy: &[SocketAddr],
+    ) -> anyhow::Result<Recorded> {
+        let entries = match log.mirror {
+            Some(mirror) if healthy.contains(&mirror) => fresh.into_iter().collect(),
+            _ => {
+                log.mirror = healthy.choose(&mut rand::rng()).copied();
+                log.entries.iter().cloned().collect()
+            }
+        };
+
+        let token = self.signer.sign(&ResumeClaims {
+            sid: sid.to_string(),
+            sub: log.sub.clone(),
+            mirror: log.mirror,
+            seq: log.next_seq - 1,
+            exp: unix_now() + self.cfg.ttl.as_secs(),
+        })?;
+        Ok(Recorded {
+            token,
+            mirror: log.mirror,
+            entries,
+        })
+    }
+
+    /// Store entries mirrored from the node serving `sid`
+    pub fn absorb(&self, sid: &str, sub: &str, entries: Vec<Buffered>) -> anyhow::Result<()> {
+        let mut logs = self.logs.lock().unwrap_or_else(|p| p.into_inner());
+        let log = logs
+            .entry(sid.to_string())
+            .or_insert_with(|| SessionLog::new(sub));
+        if log.sub != sub {
+            anyhow::bail!("session {} belongs to another caller", sid);
+        }
+        for entry in entries {
+            log.push(entry, self.cfg.buffer);
+        }
+        log.touched = Instant::now();
+        Ok(())
+    }
+
+    /// Entries of `sid` after `seq` held on this node
+    pub fn since(&self, sid: &str, seq: u64) -> Option<Vec<Buffered>> {
+        let logs = self.logs.lock().unwrap_or_else(|p| p.into_inner());
+        logs.get(sid)?.since(seq)
+    }
+
+    /// Check a client's token and return the messages it missed, fetching the
+    /// log from the mirror when this node does not hold it. The session is
+    /// served from this node afterwards.
+    pub async fn resume(
+        &self,
+        token: &str,
+        caller: &str,
+        discovery_token: Option<&str>,
+    ) -> Result<(ResumeClaims, Vec<Buffered>), ResumeError> {
+        let claims: ResumeClaims = self
+            .signer
+            .verify(token)
+            .map_err(ResumeError::InvalidToken)?;
+        if claims.exp < unix_now() {
+            return Err(ResumeError::Expired);
+        }
+        if claims.sub != caller {
+            return Err(ResumeError::WrongCaller);
+        }
+
+        if let Some(missed) = self.since(&claims.sid, claims.seq) {
+            return Ok((claims, missed));
+        }
+
+        let mirror = claims.mirror.ok_or(ResumeError::Unavailable)?;
+        let fetched = self
+            .fetch(mirror, &claims.sid, claims.seq, discovery_token)
+            .await
+            .map_err(|e| {
+                tracing::warn!(
+                    "Fetching session {} from {} failed: {}",
+                    claims.sid,
+                    mirror,
+                    e
+                );
+                ResumeError::Unavailable
+            })?;
+        // A mirror that only has entries past `seq + 1` lost some to eviction
+        if fetched
+            .first()
+            .is_some_and(|first| first.seq > claims.seq + 1)
+        {
+            return Err(ResumeError::Unavailable);
+        }
+
+        let mut logs = self.logs.lock().unwrap_or_else(|p| p.into_inner());
+        let log = logs
+            .entry(claims.sid.clone())
+            .or_insert_with(|| SessionLog::new(&claims.sub));
+        log.touched = Instant::now();
+        if log.next_seq <= claims.seq {
+            log.next_seq = claims.seq + 1;
+        }
+        for entry in &fetched {
+            log.push(entry.clone(), self.cfg.buffer);
+        }
+        Ok((claims, fetched))
+    }
+
+    /// Push entries to the mirror peer
+    pub async fn mirror(
+        &self,
+        peer: SocketAddr,
+        request: &MirrorRequest,
+        discovery_token: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let mut req = self.client.post(log_url(peer)).json(request);
+        if let Some(token) = discovery_token {
+            req = req.header("x-discovery-token", token);
+        }
+        req.send().await?.error_for_status()?;
+        Ok(())
+    }
+
+    async fn fetch(
+        &self,
+        peer: SocketAddr,
+        sid: &str,
+        after: u64,
+        discovery_token: Option<&str>,
+    ) -> anyhow::Result<Vec<Buffered>> {
+        let mut req = self
+            .client
+            .get(log_url(peer))
+            .query(&[("session", sid), ("after", &after.to_string())]);
+        if let Some(token) = discovery_token {
+            req = req.header("x-discovery-token", token);
+        }
+        Ok(req.send().await?.error_for_status()?.json().await?)
+    }
+}
+
+fn log_url(peer: SocketAddr) -> String {
+    let scheme = if peer.port() == 443 { "https" } else { "http" };
+    format!("{}://{}{}", scheme, peer, LOG_PATH)
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn resumer(buffer: usize) -> Resumer {
+        Resumer::new(
+            ResumeConfig {
+                enabled: true,
+                buffer,
+                ..ResumeConfig::default()
+            },
+            SessionSigner::from_secret(&[1; 32]).unwrap(),
+            Duration::from_secs(5),
+        )
+    }
+
+    #[tokio::test]
+    async fn replays_messages_after_the_token() {
+        let resumer = resumer(8);
+        let first = resumer
+            .record("s1", "alice", &json!({"id": 1}), &[])
+            .unwrap();
+        resumer
+            .record("s1", "alice", &json!({"id": 2}), &[])
+            .unwrap();
+        resumer
+            .record("s1", "alice", &json!({"id": 3}), &[])
+            .unwrap();
+
+        let (claims, missed) = resumer.resume(&first.token, "alice", None).await.unwrap();
+        assert_eq!(claims.seq, 1);
+        assert_eq!(missed.iter().map(|e| e.seq).collect::<Vec<_>>(), vec![2, 3]);
+
+        assert!(matches!(
+            resumer.resume(&first.token, "mallory", None).await,
+            Err(ResumeError::WrongCaller)
+        ));
+    }
+
+    #[tokio::test]
+    async fn evicted_messages_cannot_be_resumed() {
+        let resumer = resumer(2);
+        let first = resumer
+            .record("s1", "alice", &json!({"id": 1}), &[])
+            .unwrap();
+        for id in 2..=4 {
+            resumer
+                .record("s1", "alice", &json!({"id": id}), &[])
+                .unwrap();
+        }
+        assert!(matches!(
+            resumer.resume(&first.token, "alice", None).await,
+            Err(ResumeError::Unavailable)
+        ));
+    }
+
+    #[test]
+    fn new_mirror_receives_the_whole_log() {
+        let resumer = resumer(8);
+        let peer: SocketAddr = "10.0.0.2:8443".parse().unwrap();
+        resumer
+            .record("s1", "alice", &json!({"id": 1}), &[])
+            .unwrap();
+
+        let recorded = resumer
+            .record("s1", "alice", &json!({"id": 2}), &[peer])
+            .unwrap();
+        assert_eq!(recorded.mirror, Some(peer));
+        assert_eq!(recorded.entries.len(), 2);
+
+        let recorded = resumer
+            .record("s1", "alice", &json!({"id": 3}), &[peer])
+            .unwrap();
+        assert_eq!(recorded.entries.len(), 1);
+
+        let mirror = resumer(8);
+        mirror.absorb("s1", "alice", recorded.entries).unwrap();
+        assert!(mirror.since("s1", 0).is_none());
+        assert_eq!(mirror.since("s1", 2).unwrap().len(), 1);
+    }
+}
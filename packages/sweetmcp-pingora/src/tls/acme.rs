@@ -0,0 +1,445 @@
+//! ACME issuance and renewal of the public listener's certificate.
+//!
+//! Orders go to any RFC 8555 directory, Let's Encrypt by default, from an
+//! account whose credentials are kept next to the certificate. Domains are
+//! proven with one of two challenges:
+//!
+//! - `http-01`: a responder is bound on port 80 for the duration of the order
+//!   and answers `/.well-known/acme-challenge/<token>`.
+//! - `dns-01`: a hook command publishes the `_acme-challenge` TXT record,
+//!   called as `<hook> set <record> <value>` and, once the order is done,
+//!   `<hook> clear <record> <value>`. It is the only way to get a wildcard.
+
+use anyhow::{Context, Result};
+use instant_acme::{
+    Account, AccountCredentials, AuthorizationStatus, ChallengeType, Identifier, NewAccount,
+    NewOrder, OrderStatus,
+};
+use rcgen::{CertificateParams, DistinguishedName, KeyPair};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Let's Encrypt's production directory
+pub const LETS_ENCRYPT: &str = "https://acme-v02.api.letsencrypt.org/directory";
+
+/// Path prefix HTTP-01 challenges are fetched under
+const HTTP01_PREFIX: &str = "/.well-known/acme-challenge/";
+
+/// Longest wait for the CA to validate challenges and issue the certificate
+const ORDER_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// How ownership of the domains is proven
+#[derive(Clone, Debug, PartialEq)]
+pub enum Challenge {
+    /// Serve the key authorization over plain HTTP on `bind`
+    Http01 { bind: String },
+    /// Publish a TXT record with `hook`, then wait `propagation` for
+    /// resolvers to see it
+    Dns01 {
+        hook: PathBuf,
+        propagation: Duration,
+    },
+}
+
+/// Automatic certificate management settings
+#[derive(Clone, Debug)]
+pub struct AcmeConfig {
+    /// Names on the certificate; the first is its subject
+    pub domains: Vec<String>,
+
+    /// Contact address for expiry notices from the CA
+    pub email: Option<String>,
+
+    /// ACME directory URL
+    pub directory: String,
+
+    pub challenge: Challenge,
+
+    /// Account credentials, created on first use
+    pub account_path: PathBuf,
+
+    /// Renew once the certificate has less than this left
+    pub renew_before: Duration,
+}
+
+impl AcmeConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.domains.is_empty() {
+            anyhow::bail!("ACME needs at least one domain");
+        }
+        if matches!(self.challenge, Challenge::Http01 { .. })
+            && self.domains.iter().any(|d| d.starts_with("*."))
+        {
+            anyhow::bail!("Wildcard domains can only be validated with dns-01");
+        }
+        url::Url::parse(&self.directory)
+            .with_context(|| format!("Invalid ACME directory: {}", self.directory))?;
+        Ok(())
+    }
+}
+
+/// Parse `http-01` or `dns-01`, the latter needing a hook
+pub fn parse_challenge(
+    raw: &str,
+    http_bind: String,
+    hook: Option<PathBuf>,
+    propagation: Duration,
+) -> Result<Challenge> {
+    match raw.trim().to_ascii_lowercase().as_str() {
+        "http-01" | "http" => Ok(Challenge::Http01 { bind: http_bind }),
+        "dns-01" | "dns" => Ok(Challenge::Dns01 {
+            hook: hook.context("dns-01 needs SWEETMCP_ACME_DNS_HOOK")?,
+            propagation,
+        }),
+        other => anyhow::bail!("Unknown ACME challenge '{}': use http-01 or dns-01", other),
+    }
+}
+
+/// Order a certificate for `config.domains`; returns the PEM chain and key
+pub async fn issue(config: &AcmeConfig) -> Result<(String, String)> {
+    let account = account(config).await?;
+    let identifiers: Vec<Identifier> = config
+        .domains
+        .iter()
+        .map(|d| Identifier::Dns(d.clone()))
+        .collect();
+    let mut order = account
+        .new_order(&NewOrder {
+            identifiers: &identifiers,
+        })
+        .await
+        .context("Failed to create ACME order")?;
+
+    let http_tokens = Arc::new(Mutex::new(HashMap::new()));
+    let mut dns_records = Vec::new();
+    let responder = match &config.challenge {
+        Challenge::Http01 { bind } => Some(serve_http01(bind, http_tokens.clone()).await?),
+        Challenge::Dns01 { .. } => None,
+    };
+
+    let result = async {
+        let challenge_type = match config.challenge {
+            Challenge::Http01 { .. } => ChallengeType::Http01,
+            Challenge::Dns01 { .. } => ChallengeType::Dns01,
+        };
+        let mut ready = Vec::new();
+        for authz in order.authorizations().await? {
+            match authz.status {
+                AuthorizationStatus::Pending => {}
+                AuthorizationStatus::Valid => continue,
+                status => anyhow::bail!("ACME authorization is {:?}", status),
+            }
+            let Identifier::Dns(name) = &authz.identifier;
+            let challenge = authz
+                .challenges
+                .iter()
+                .find(|c| c.r#type == challenge_type)
+                .with_context(|| {
+                    format!("CA offers no {:?} challenge for {}", challenge_type, name)
+                })?;
+            let key_authorization = order.key_authorization(challenge);
+            match &config.challenge {
+                Challenge::Http01 { .. } => {
+                    http_tokens
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .insert(
+                            challenge.token.clone(),
+                            key_authorization.as_str().to_string(),
+                        );
+                }
+                Challenge::Dns01 { hook, .. } => {
+                    let record = format!("_acme-challenge.{}", name.trim_start_matches("*."));
+                    let value = key_authorization.dns_value();
+                    run_hook(hook, "set", &record, &value).await?;
+                    dns_records.push((record, value));
+                }
+            }
+            ready.push(challenge.url.clone());
+        }
+
+        if let Challenge::Dns01 { propagation, .. } = &config.challenge {
+            if !dns_records.is_empty() {
+                log::info!(
+                    "Waiting {}s for ACME TXT records to propagate",
+                    propagation.as_secs()
+                );
+                tokio::time::sleep(*propagation).await;
+            }
+        }
+        for url in &ready {
+            order.set_challenge_ready(url).await?;
+        }
+
+        wait_for_order(&mut order, OrderStatus::Ready).await?;
+
+        let mut params = CertificateParams::new(config.domains.clone())?;
+        params.distinguished_name = DistinguishedName::new();
+        let key_pair = KeyPair::generate()?;
+        let csr = params.serialize_request(&key_pair)?;
+        order
+            .finalize(csr.der())
+            .await
+            .context("Failed to finalize ACME order")?;
+
+        let deadline = tokio::time::Instant::now() + ORDER_TIMEOUT;
+        let chain = loop {
+            if let Some(chain) = order.certificate().await? {
+                break chain;
+            }
+            if tokio::time::Instant::now() >= deadline {
+                anyhow::bail!("ACME certificate was not issued in time");
+            }
+            tokio::time::sleep(Duration::from_secs(2)).await;
+        };
+        Ok((chain, key_pair.serialize_pem()))
+    }
+    .await;
+
+    if let Some(responder) = responder {
+        responder.abort();
+    }
+    if let Challenge::Dns01 { hook, .. } = &config.challenge {
+        for (record, value) in &dns_records {
+            if let Err(e) = run_hook(hook, "clear", record, value).await {
+                log::warn!("Failed to clear ACME record {}: {:#}", record, e);
+            }
+        }
+    }
+    result
+}
+
+/// The ACME account, created and saved on first use
+async fn account(config: &AcmeConfig) -> Result<Account> {
+    if let Ok(raw) = tokio::fs::read(&config.account_path).await {
+        let credentials: AccountCredentials =
+            serde_json::from_slice(&raw).context("Invalid ACME account file")?;
+        return Account::from_credentials(credentials)
+            .await
+            .context("Failed to load ACME account");
+    }
+
+    let contact: Vec<String> = config
+        .email
+        .iter()
+        .map(|email| format!("mailto:{}", email))
+        .collect();
+    let contact: Vec<&str> = contact.iter().map(String::as_str).collect();
+    let (account, credentials) = Account::create(
+        &NewAccount {
+            contact: &contact,
+            terms_of_service_agreed: true,
+            only_return_existing: false,
+        },
+        &config.directory,
+        None,
+    )
+    .await
+    .context("Failed to create ACME account")?;
+
+    if let Some(parent) = config.account_path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(&config.account_path, serde_json::to_vec(&credentials)?)
+        .await
+        .with_context(|| format!("Failed to save {}", config.account_path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        tokio::fs::set_permissions(&config.account_path, std::fs::Permissions::from_mode(0o600))
+            .await?;
+    }
+    log::info!("Created ACME account at {}", config.directory);
+    Ok(account)
+}
+
+async fn wait_for_order(order: &mut instant_acme::Order, wanted: OrderStatus) -> Result<()> {
+    let deadline = tokio::time::Instant::now() + ORDER_TIMEOUT;
+    let mut delay = Duration::from_secs(1);
+    loop {
+        let state = order.refresh().await?;
+        match state.status {
+            status if status == wanted => return Ok(()),
+            OrderStatus::Invalid => {
+                anyhow::bail!("ACME order is invalid; the CA could not validate the challenges")
+            }
+            _ => {}
+        }
+        if tokio::time::Instant::now() >= deadline {
+            anyhow::bail!(
+                "ACME order still {:?} after {}s",
+                state.status,
+                ORDER_TIMEOUT.as_secs()
+            );
+        }
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(Duration::from_secs(10));
+    }
+}
+
+async fn run_hook(hook: &PathBuf, action: &str, record: &str, value: &str) -> Result<()> {
+    let status = tokio::process::Command::new(hook)
+        .args([action, record, value])
+        .status()
+        .await
+        .with_context(|| format!("Failed to run ACME DNS hook {}", hook.display()))?;
+    if !status.success() {
+        anyhow::bail!("ACME DNS hook {} {} failed: {}", action, record, status);
+    }
+    Ok(())
+}
+
+/// Answer HTTP-01 challenges on `bind` until the returned task is aborted
+async fn serve_http01(
+    bind: &str,
+    tokens: Arc<Mutex<HashMap<String, String>>>,
+) -> Result<tokio::task::JoinHandle<()>> {
+    let listener = TcpListener::bind(bind)
+        .await
+        .with_context(|| format!("Failed to bind the ACME HTTP-01 responder on {}", bind))?;
+    Ok(tokio::spawn(async move {
+        loop {
+            let Ok((stream, _)) = listener.accept().await else {
+                continue;
+            };
+            let tokens = tokens.clone();
+            tokio::spawn(async move {
+                if let Err(e) = answer_http01(stream, &tokens).await {
+                    log::debug!("ACME HTTP-01 request failed: {}", e);
+                }
+            });
+        }
+    }))
+}
+
+async fn answer_http01(
+    mut stream: TcpStream,
+    tokens: &Mutex<HashMap<String, String>>,
+) -> std::io::Result<()> {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < 8192 {
+        let n = tokio::time::timeout(Duration::from_secs(10), stream.read(&mut buf))
+            .await
+            .map_err(|_| std::io::Error::from(std::io::ErrorKind::TimedOut))??;
+        if n == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..n]);
+    }
+
+    let key_authorization = http01_token(&request).and_then(|token| {
+        tokens
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(token)
+            .cloned()
+    });
+    let response = match key_authorization {
+        Some(body) => format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/octet-stream\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        ),
+        None => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+            .to_string(),
+    };
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// The challenge token a `GET /.well-known/acme-challenge/<token>` asks for
+fn http01_token(request: &[u8]) -> Option<&str> {
+    let line = request.split(|b| *b == b'\n').next()?;
+    let line = std::str::from_utf8(line).ok()?.trim_end();
+    let mut parts = line.split(' ');
+    if parts.next()? != "GET" {
+        return None;
+    }
+    let token = parts.next()?.strip_prefix(HTTP01_PREFIX)?;
+    let valid = !token.is_empty()
+        && token
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_');
+    valid.then_some(token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn http01_token_is_read_from_the_request_line() {
+        let request = b"GET /.well-known/acme-challenge/abc_DEF-123 HTTP/1.1\r\nHost: x\r\n\r\n";
+        assert_eq!(http01_token(request), Some("abc_DEF-123"));
+        assert_eq!(http01_token(b"GET /other HTTP/1.1\r\n\r\n"), None);
+        assert_eq!(
+            http01_token(b"POST /.well-known/acme-challenge/abc HTTP/1.1\r\n\r\n"),
+            None
+        );
+        assert_eq!(
+            http01_token(b"GET /.well-known/acme-challenge/../etc HTTP/1.1\r\n\r\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn wildcards_need_dns01() {
+        let config = AcmeConfig {
+            domains: vec!["*.example.com".to_string()],
+            email: None,
+            directory: LETS_ENCRYPT.to_string(),
+            challenge: Challenge::Http01 {
+                bind: "0.0.0.0:80".to_string(),
+            },
+            account_path: PathBuf::from("account.json"),
+            renew_before: Duration::from_secs(30 * 86_400),
+        };
+        assert!(config.validate().is_err());
+
+        let config = AcmeConfig {
+            challenge: Challenge::Dns01 {
+                hook: PathBuf::from("/usr/local/bin/dns-hook"),
+                propagation: Duration::from_secs(30),
+            },
+            ..config
+        };
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn dns01_requires_a_hook() {
+        let bind = "0.0.0.0:80".to_string();
+        assert!(parse_challenge("dns-01", bind.clone(), None, Duration::ZERO).is_err());
+        assert_eq!(
+            parse_challenge("HTTP-01", bind.clone(), None, Duration::ZERO).unwrap(),
+            Challenge::Http01 { bind }
+        );
+    }
+
+    #[tokio::test]
+    async fn responder_answers_known_tokens_only() {
+        let tokens = Arc::new(Mutex::new(HashMap::from([(
+            "tok".to_string(),
+            "tok.thumbprint".to_string(),
+        )])));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        let responder = serve_http01(&addr.to_string(), tokens).await.unwrap();
+
+        for (path, expected) in [("tok", "200 OK"), ("other", "404 Not Found")] {
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            let request = format!("GET {}{} HTTP/1.1\r\nHost: x\r\n\r\n", HTTP01_PREFIX, path);
+            stream.write_all(request.as_bytes()).await.unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+            assert!(response.contains(expected), "{}", response);
+        }
+        responder.abort();
+    }
+}
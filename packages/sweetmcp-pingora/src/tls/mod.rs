@@ -1,6 +1,8 @@
 //! TLS module organization
 
+pub mod acme;
 pub mod ocsp;
+pub mod reload;
 mod tls_manager;
 
 // Re-export all public types from tls_manager
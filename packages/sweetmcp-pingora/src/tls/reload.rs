@@ -0,0 +1,305 @@
+//! Hot-reloadable certificate for the public TLS listener.
+//!
+//! The listener asks [`CertStore`] for its certificate on every handshake, so
+//! a renewed certificate is served from the next connection on while open
+//! connections keep the session they negotiated. Nothing is restarted and no
+//! connection is dropped. The store is refreshed when the certificate files
+//! change on disk, whether an operator replaced them or ACME issued new ones.
+
+use anyhow::{Context, Result};
+use arc_swap::ArcSwap;
+use pingora::listeners::TlsAccept;
+use pingora::tls::error::ErrorStack;
+use pingora::tls::ext;
+use pingora::tls::pkey::{PKey, Private};
+use pingora::tls::ssl::SslRef;
+use pingora::tls::x509::X509;
+use rcgen::{CertificateParams, KeyPair};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use super::acme::AcmeConfig;
+
+/// Certificate files for the TCP listener, and how they are kept current
+#[derive(Clone, Debug)]
+pub struct TlsConfig {
+    /// PEM certificate chain, leaf first
+    pub cert_path: PathBuf,
+
+    /// PEM private key
+    pub key_path: PathBuf,
+
+    /// Automatic issuance and renewal; the files are managed by hand when unset
+    pub acme: Option<AcmeConfig>,
+
+    /// How often the files are checked for changes
+    pub reload_interval: Duration,
+}
+
+/// A certificate chain with its private key
+pub struct Certified {
+    chain: Vec<X509>,
+    key: PKey<Private>,
+    /// When the leaf certificate expires
+    pub not_after: SystemTime,
+}
+
+impl Certified {
+    /// Parse a PEM chain and key, checking that they belong together
+    pub fn from_pem(cert_pem: &[u8], key_pem: &[u8]) -> Result<Self> {
+        let chain = X509::stack_from_pem(cert_pem).context("Invalid certificate PEM")?;
+        let leaf = chain.first().context("No certificate in PEM")?;
+        let key = PKey::private_key_from_pem(key_pem).context("Invalid private key PEM")?;
+        if !leaf.public_key()?.public_eq(&key) {
+            anyhow::bail!("Certificate does not match the private key");
+        }
+
+        let now = pingora::tls::asn1::Asn1Time::days_from_now(0)?;
+        let remaining = now.diff(leaf.not_after())?;
+        let remaining = remaining.days as i64 * 86_400 + remaining.secs as i64;
+        let not_after = if remaining >= 0 {
+            SystemTime::now() + Duration::from_secs(remaining as u64)
+        } else {
+            SystemTime::now() - Duration::from_secs(remaining.unsigned_abs())
+        };
+
+        Ok(Self {
+            chain,
+            key,
+            not_after,
+        })
+    }
+
+    /// A self-signed certificate for `names` that is already due for
+    /// renewal, served until ACME issues the real one
+    pub fn placeholder(names: &[String]) -> Result<(String, String)> {
+        let mut params = CertificateParams::new(names.to_vec())?;
+        let now = time::OffsetDateTime::now_utc();
+        params.not_before = now - time::Duration::hours(1);
+        params.not_after = now + time::Duration::hours(1);
+        let key_pair = KeyPair::generate()?;
+        let cert = params.self_signed(&key_pair)?;
+        Ok((cert.pem(), key_pair.serialize_pem()))
+    }
+
+    /// Time left before the leaf certificate expires, zero once it has
+    pub fn remaining(&self) -> Duration {
+        self.not_after
+            .duration_since(SystemTime::now())
+            .unwrap_or_default()
+    }
+}
+
+/// The certificate currently served, and the files it came from
+pub struct CertStore {
+    current: ArcSwap<Certified>,
+    cert_path: PathBuf,
+    key_path: PathBuf,
+    /// Modification times of the files last loaded
+    loaded: Mutex<Option<(SystemTime, SystemTime)>>,
+}
+
+impl CertStore {
+    /// Load the certificate files. With ACME and no files yet, a short-lived
+    /// self-signed certificate is written first so the listener can start.
+    pub fn open(config: &TlsConfig) -> Result<Arc<Self>> {
+        if let Some(acme) = &config.acme {
+            if !config.cert_path.exists() || !config.key_path.exists() {
+                let (cert_pem, key_pem) = Certified::placeholder(&acme.domains)?;
+                write_pair(&config.cert_path, &key_pem, &config.key_path, &cert_pem)?;
+                log::info!(
+                    "Serving a placeholder certificate until ACME issues one for {}",
+                    acme.domains.join(", ")
+                );
+            }
+        }
+
+        let (certified, modified) = load_pair(&config.cert_path, &config.key_path)?;
+        Ok(Arc::new(Self {
+            current: ArcSwap::from_pointee(certified),
+            cert_path: config.cert_path.clone(),
+            key_path: config.key_path.clone(),
+            loaded: Mutex::new(Some(modified)),
+        }))
+    }
+
+    /// The certificate served to new connections
+    pub fn current(&self) -> Arc<Certified> {
+        self.current.load_full()
+    }
+
+    pub fn cert_path(&self) -> &Path {
+        &self.cert_path
+    }
+
+    pub fn key_path(&self) -> &Path {
+        &self.key_path
+    }
+
+    /// Swap in the files if they changed since they were last loaded.
+    /// A broken pair is refused and the current certificate kept.
+    pub fn reload_if_changed(&self) -> Result<bool> {
+        let modified = (modified(&self.cert_path)?, modified(&self.key_path)?);
+        if *self.loaded.lock().unwrap_or_else(|e| e.into_inner()) == Some(modified) {
+            return Ok(false);
+        }
+        self.load()?;
+        Ok(true)
+    }
+
+    /// Write a new chain and key and serve them
+    pub fn replace(&self, cert_pem: &str, key_pem: &str) -> Result<()> {
+        // Refuse a bad pair before it replaces the files on disk
+        Certified::from_pem(cert_pem.as_bytes(), key_pem.as_bytes())?;
+        write_pair(&self.cert_path, key_pem, &self.key_path, cert_pem)?;
+        self.load()
+    }
+
+    fn load(&self) -> Result<()> {
+        let (certified, modified) = load_pair(&self.cert_path, &self.key_path)?;
+        log::info!(
+            "Loaded TLS certificate {} (expires in {} days)",
+            self.cert_path.display(),
+            certified.remaining().as_secs() / 86_400
+        );
+        self.current.store(Arc::new(certified));
+        *self.loaded.lock().unwrap_or_else(|e| e.into_inner()) = Some(modified);
+        Ok(())
+    }
+}
+
+/// Hands the store's current certificate to each TLS handshake
+pub struct CertCallback(pub Arc<CertStore>);
+
+impl TlsAccept for CertCallback {
+    fn certificate_callback<'life0, 'life1, 'async_trait>(
+        &'life0 self,
+        ssl: &'life1 mut SslRef,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + 'async_trait>>
+    where
+        'life0: 'async_trait,
+        'life1: 'async_trait,
+        Self: 'async_trait,
+    {
+        let certified = self.0.current();
+        Box::pin(async move {
+            if let Err(e) = use_certificate(ssl, &certified) {
+                log::error!("Failed to set TLS certificate for handshake: {}", e);
+            }
+        })
+    }
+}
+
+fn use_certificate(ssl: &mut SslRef, certified: &Certified) -> Result<(), ErrorStack> {
+    let (leaf, intermediates) = certified
+        .chain
+        .split_first()
+        .expect("a loaded chain has a leaf");
+    ext::ssl_use_certificate(ssl, leaf)?;
+    ext::ssl_use_private_key(ssl, &certified.key)?;
+    for cert in intermediates {
+        ext::ssl_add_chain_cert(ssl, cert)?;
+    }
+    Ok(())
+}
+
+fn modified(path: &Path) -> Result<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .with_context(|| format!("Failed to stat {}", path.display()))
+}
+
+fn load_pair(cert_path: &Path, key_path: &Path) -> Result<(Certified, (SystemTime, SystemTime))> {
+    let modified = (modified(cert_path)?, modified(key_path)?);
+    let cert_pem = std::fs::read(cert_path)
+        .with_context(|| format!("Failed to read {}", cert_path.display()))?;
+    let key_pem = std::fs::read(key_path)
+        .with_context(|| format!("Failed to read {}", key_path.display()))?;
+    let certified = Certified::from_pem(&cert_pem, &key_pem)
+        .with_context(|| format!("Invalid TLS certificate {}", cert_path.display()))?;
+    Ok((certified, modified))
+}
+
+/// Write the key before the chain, each through a temporary file, so a
+/// reload never pairs a new chain with an old key
+fn write_pair(cert_path: &Path, key_pem: &str, key_path: &Path, cert_pem: &str) -> Result<()> {
+    for (path, contents, private) in [(key_path, key_pem, true), (cert_path, cert_pem, false)] {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, contents)
+            .with_context(|| format!("Failed to write {}", tmp.display()))?;
+        #[cfg(unix)]
+        if private {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o600))?;
+        }
+        #[cfg(not(unix))]
+        let _ = private;
+        std::fs::rename(&tmp, path)
+            .with_context(|| format!("Failed to replace {}", path.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(dir: &Path) -> TlsConfig {
+        TlsConfig {
+            cert_path: dir.join("server.crt"),
+            key_path: dir.join("server.key"),
+            acme: None,
+            reload_interval: Duration::from_secs(60),
+        }
+    }
+
+    #[test]
+    fn placeholder_is_due_for_renewal() {
+        let (cert, key) = Certified::placeholder(&["example.com".to_string()]).unwrap();
+        let certified = Certified::from_pem(cert.as_bytes(), key.as_bytes()).unwrap();
+        assert!(certified.remaining() <= Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn mismatched_key_is_refused() {
+        let (cert, _) = Certified::placeholder(&["example.com".to_string()]).unwrap();
+        let (_, other_key) = Certified::placeholder(&["example.com".to_string()]).unwrap();
+        assert!(Certified::from_pem(cert.as_bytes(), other_key.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn replace_swaps_the_served_certificate() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = config(dir.path());
+        let (cert, key) = Certified::placeholder(&["a.example.com".to_string()]).unwrap();
+        write_pair(&config.cert_path, &key, &config.key_path, &cert).unwrap();
+
+        let store = CertStore::open(&config).unwrap();
+        let before = store.current();
+        assert!(!store.reload_if_changed().unwrap());
+
+        let (cert, key) = Certified::placeholder(&["b.example.com".to_string()]).unwrap();
+        store.replace(&cert, &key).unwrap();
+        assert!(!Arc::ptr_eq(&before, &store.current()));
+    }
+
+    #[test]
+    fn broken_pair_keeps_the_current_certificate() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = config(dir.path());
+        let (cert, key) = Certified::placeholder(&["example.com".to_string()]).unwrap();
+        write_pair(&config.cert_path, &key, &config.key_path, &cert).unwrap();
+        let store = CertStore::open(&config).unwrap();
+        let before = store.current();
+
+        assert!(store.replace("not a certificate", &key).is_err());
+        assert!(Arc::ptr_eq(&before, &store.current()));
+        assert_eq!(std::fs::read_to_string(&config.cert_path).unwrap(), cert);
+    }
+}
@@ -0,0 +1,283 @@
+//! Passkey (WebAuthn) login for gateway administrators.
+//!
+//! An admin caller registers passkeys; afterwards anyone holding one of those
+//! passkeys can log in and receive a short-lived admin JWT, so the shared JWT
+//! secret no longer has to be handed to people.
+
+use crate::{
+    api_keys::{ApiResponse, WEBAUTHN_PATH},
+    auth::{Caller, JwtAuth, Role},
+    config::WebauthnConfig,
+};
+use anyhow::{Context, Result};
+use pingora::http::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use webauthn_rs::prelude::*;
+
+/// How long a started ceremony may take to finish
+const CEREMONY_TTL: Duration = Duration::from_secs(300);
+
+/// Persisted passkeys for the single admin principal
+#[derive(Default, Serialize, Deserialize)]
+struct PasskeyFile {
+    user_id: Option<Uuid>,
+    passkeys: Vec<NamedPasskey>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct NamedPasskey {
+    name: String,
+    passkey: Passkey,
+}
+
+#[derive(Deserialize)]
+struct RegisterStart {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct Finish<T> {
+    ceremony: String,
+    credential: T,
+}
+
+pub struct AdminWebauthn {
+    webauthn: Webauthn,
+    path: PathBuf,
+    store: Mutex<PasskeyFile>,
+    pending_registrations: Mutex<HashMap<String, (Instant, String, PasskeyRegistration)>>,
+    pending_logins: Mutex<HashMap<String, (Instant, PasskeyAuthentication)>>,
+}
+
+impl AdminWebauthn {
+    pub fn new(cfg: &WebauthnConfig, path: impl Into<PathBuf>) -> Result<Self> {
+        let origin = Url::parse(&cfg.origin).context("Invalid WebAuthn origin")?;
+        let webauthn = WebauthnBuilder::new(&cfg.rp_id, &origin)
+            .context("Invalid WebAuthn relying party")?
+            .rp_name("SweetMCP")
+            .build()
+            .context("Failed to build WebAuthn relying party")?;
+
+        let path = path.into();
+        let store = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .with_context(|| format!("Corrupt passkey file {}", path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => PasskeyFile::default(),
+            Err(e) => return Err(e).context("Failed to read passkey file"),
+        };
+
+        Ok(Self {
+            webauthn,
+            path,
+            store: Mutex::new(store),
+            pending_registrations: Mutex::new(HashMap::new()),
+            pending_logins: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Serve the registration (admin only) and login (public) ceremonies
+    pub fn handle(
+        &self,
+        method: &Method,
+        path: &str,
+        body: &[u8],
+        caller: Option<&Caller>,
+        jwt: &JwtAuth,
+    ) -> ApiResponse {
+        if method != Method::POST {
+            return ApiResponse::error(405, "Method not allowed");
+        }
+        let route = path.strip_prefix(WEBAUTHN_PATH).unwrap_or_default();
+
+        match route {
+            "/register/start" | "/register/finish" => match caller {
+                None => ApiResponse::error(401, "Unauthorized"),
+                Some(c) if !c.is_admin() => ApiResponse::error(403, "Admin access required"),
+                Some(c) if route == "/register/start" => self.register_start(body, c),
+                Some(_) => self.register_finish(body),
+            },
+            "/login/start" => self.login_start(),
+            "/login/finish" => self.login_finish(body, jwt),
+            _ => ApiResponse::error(404, "Not found"),
+        }
+    }
+
+    fn register_start(&self, body: &[u8], caller: &Caller) -> ApiResponse {
+        let request: RegisterStart = match serde_json::from_slice(body) {
+            Ok(r) => r,
+            Err(e) => return ApiResponse::error(400, format!("Invalid JSON: {}", e)),
+        };
+
+        let (user_id, exclude) = {
+            let mut store = lock(&self.store);
+            let user_id = *store.user_id.get_or_insert_with(Uuid::new_v4);
+            let exclude: Vec<CredentialID> = store
+                .passkeys
+                .iter()
+                .map(|p| p.passkey.cred_id().clone())
+                .collect();
+            (user_id, exclude)
+        };
+
+        match self.webauthn.start_passkey_registration(
+            user_id,
+            "sweetmcp-admin",
+            "SweetMCP administrator",
+            Some(exclude),
+        ) {
+            Ok((challenge, state)) => {
+                let ceremony = Uuid::new_v4().to_string();
+                let mut pending = lock(&self.pending_registrations);
+                pending.retain(|_, (started, _, _)| started.elapsed() < CEREMONY_TTL);
+                pending.insert(ceremony.clone(), (Instant::now(), request.name, state));
+                tracing::info!("Passkey registration started by {}", caller.subject());
+                ApiResponse::new(200, json!({ "ceremony": ceremony, "options": challenge }))
+            }
+            Err(e) => ApiResponse::error(500, format!("WebAuthn error: {}", e)),
+        }
+    }
+
+    fn register_finish(&self, body: &[u8]) -> ApiResponse {
+        let request: Finish<RegisterPublicKeyCredential> = match serde_json::from_slice(body) {
+            Ok(r) => r,
+            Err(e) => return ApiResponse::error(400, format!("Invalid JSON: {}", e)),
+        };
+        let Some((started, name, state)) =
+            lock(&self.pending_registrations).remove(&request.ceremony)
+        else {
+            return ApiResponse::error(400, "Unknown or expired ceremony");
+        };
+        if started.elapsed() >= CEREMONY_TTL {
+            return ApiResponse::error(400, "Unknown or expired ceremony");
+        }
+
+        let passkey = match self
+            .webauthn
+            .finish_passkey_registration(&request.credential, &state)
+        {
+            Ok(p) => p,
+            Err(e) => return ApiResponse::error(400, format!("Registration failed: {}", e)),
+        };
+
+        let mut store = lock(&self.store);
+        store.passkeys.push(NamedPasskey {
+            name: name.clone(),
+            passkey,
+        });
+        if let Err(e) = persist(&self.path, &store) {
+            store.passkeys.pop();
+            tracing::error!("Failed to save passkey: {}", e);
+            return ApiResponse::error(500, "Failed to save passkey");
+        }
+        ApiResponse::new(
+            201,
+            json!({ "name": name, "passkeys": store.passkeys.len() }),
+        )
+    }
+
+    fn login_start(&self) -> ApiResponse {
+        let passkeys: Vec<Passkey> = lock(&self.store)
+            .passkeys
+            .iter()
+            .map(|p| p.passkey.clone())
+            .collect();
+        if passkeys.is_empty() {
+            return ApiResponse::error(409, "No admin passkeys registered");
+        }
+
+        match self.webauthn.start_passkey_authentication(&passkeys) {
+            Ok((challenge, state)) => {
+                let ceremony = Uuid::new_v4().to_string();
+                let mut pending = lock(&self.pending_logins);
+                pending.retain(|_, (started, _)| started.elapsed() < CEREMONY_TTL);
+                pending.insert(ceremony.clone(), (Instant::now(), state));
+                ApiResponse::new(200, json!({ "ceremony": ceremony, "options": challenge }))
+            }
+            Err(e) => ApiResponse::error(500, format!("WebAuthn error: {}", e)),
+        }
+    }
+
+    fn login_finish(&self, body: &[u8], jwt: &JwtAuth) -> ApiResponse {
+        let request: Finish<PublicKeyCredential> = match serde_json::from_slice(body) {
+            Ok(r) => r,
+            Err(e) => return ApiResponse::error(400, format!("Invalid JSON: {}", e)),
+        };
+        let Some((started, state)) = lock(&self.pending_logins).remove(&request.ceremony) else {
+            return ApiResponse::error(400, "Unknown or expired ceremony");
+        };
+        if started.elapsed() >= CEREMONY_TTL {
+            return ApiResponse::error(400, "Unknown or expired ceremony");
+        }
+
+        let result = match self
+            .webauthn
+            .finish_passkey_authentication(&request.credential, &state)
+        {
+            Ok(r) => r,
+            Err(e) => {
+                tracing::warn!("Passkey login rejected: {}", e);
+                return ApiResponse::error(401, "Passkey login failed");
+            }
+        };
+
+        let name = {
+            let mut store = lock(&self.store);
+            let mut name = String::new();
+            let mut updated = false;
+            for entry in store.passkeys.iter_mut() {
+                if entry.passkey.cred_id() == result.cred_id() {
+                    name = entry.name.clone();
+                    // Signature counter / backup state changes must be saved
+                    updated = entry.passkey.update_credential(&result) == Some(true);
+                }
+            }
+            if updated {
+                if let Err(e) = persist(&self.path, &store) {
+                    tracing::warn!("Failed to update passkey counter: {}", e);
+                }
+            }
+            name
+        };
+
+        let subject = format!("webauthn:{}", name);
+        let permissions = jwt.get_role_permissions(&Role::Admin);
+        match jwt.generate_token(&subject, vec![Role::Admin], permissions) {
+            Ok(token) => {
+                tracing::info!("Admin login via passkey {}", name);
+                ApiResponse::new(200, json!({ "token": token, "subject": subject }))
+            }
+            Err(e) => {
+                tracing::error!("Failed to issue admin token: {}", e);
+                ApiResponse::error(500, "Failed to issue token")
+            }
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn persist(path: &Path, store: &PasskeyFile) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_vec_pretty(store)?)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o600))?;
+    }
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
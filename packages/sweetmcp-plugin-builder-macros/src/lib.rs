@@ -0,0 +1,267 @@
+//! Attribute macros for sweetmcp-plugin-builder
+//!
+//! Use them through `sweetmcp_plugin_builder::prelude`, not directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{format_ident, quote};
+use syn::{
+    Attribute, Expr, ExprLit, FnArg, Ident, ItemFn, Lit, LitStr, Meta, Pat, Type,
+    parse_macro_input, spanned::Spanned,
+};
+
+/// Turn a function into an MCP tool
+///
+/// ```ignore
+/// /// Generate cryptographic hashes
+/// #[mcp_tool(name = "hash", read_only, idempotent)]
+/// fn hash(
+///     /// Data to hash
+///     data: String,
+///     /// Hash algorithm
+///     algorithm: Algorithm,
+/// ) -> Result<String, ToolError> {
+///     ...
+/// }
+/// ```
+///
+/// expands to the function plus a `HashArgs` struct holding the parameters and
+/// a `HashTool` implementing `McpTool`, registered with `.tool::<HashTool>()`.
+/// The input schema is generated from the parameter types and their doc
+/// comments; other parameter attributes such as `#[serde(default)]` move to
+/// the `HashArgs` field.
+///
+/// Options:
+/// - `name = "..."`: tool name, defaults to the function name
+/// - `description = "..."`: what the tool does, defaults to the function's
+///   doc comment
+/// - `when = "..."`, `perfect_for = "..."`: repeatable description clauses
+/// - `read_only`, `idempotent`, `destructive`, `open_world`: annotation hints
+/// - `tool = Name`: name of the generated tool type
+///
+/// The function returns a `CallToolResult`, a `String`, a `serde_json::Value`,
+/// or a `Result` of one of them whose error converts into `ToolError`.
+#[proc_macro_attribute]
+pub fn mcp_tool(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut options = ToolOptions::default();
+    let parser = syn::meta::parser(|meta| options.parse(meta));
+    parse_macro_input!(attr with parser);
+    let function = parse_macro_input!(item as ItemFn);
+
+    expand(options, function)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[derive(Default)]
+struct ToolOptions {
+    name: Option<LitStr>,
+    description: Option<LitStr>,
+    when: Vec<LitStr>,
+    perfect_for: Vec<LitStr>,
+    hints: Vec<&'static str>,
+    tool: Option<Ident>,
+}
+
+impl ToolOptions {
+    fn parse(&mut self, meta: syn::meta::ParseNestedMeta) -> syn::Result<()> {
+        let hint = |path: &syn::Path| {
+            [
+                ("read_only", "readOnlyHint"),
+                ("idempotent", "idempotentHint"),
+                ("destructive", "destructiveHint"),
+                ("open_world", "openWorldHint"),
+            ]
+            .into_iter()
+            .find(|(flag, _)| path.is_ident(flag))
+            .map(|(_, hint)| hint)
+        };
+
+        if meta.path.is_ident("name") {
+            self.name = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("description") {
+            self.description = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("when") {
+            self.when.push(meta.value()?.parse()?);
+        } else if meta.path.is_ident("perfect_for") {
+            self.perfect_for.push(meta.value()?.parse()?);
+        } else if meta.path.is_ident("tool") {
+            self.tool = Some(meta.value()?.parse()?);
+        } else if let Some(hint) = hint(&meta.path) {
+            self.hints.push(hint);
+        } else {
+            return Err(meta.error(
+                "expected `name`, `description`, `when`, `perfect_for`, `tool`, \
+                 `read_only`, `idempotent`, `destructive` or `open_world`",
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn expand(options: ToolOptions, mut function: ItemFn) -> syn::Result<proc_macro2::TokenStream> {
+    let sig = &function.sig;
+    if let Some(asyncness) = &sig.asyncness {
+        return Err(syn::Error::new(
+            asyncness.span(),
+            "MCP tools run synchronously; remove `async`",
+        ));
+    }
+    if !sig.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            sig.generics.span(),
+            "MCP tool functions cannot be generic",
+        ));
+    }
+
+    let fn_ident = sig.ident.clone();
+    let vis = function.vis.clone();
+    let pascal = pascal_case(&fn_ident.to_string());
+    let args_ident = format_ident!("{}Args", pascal);
+    let tool_ident = options
+        .tool
+        .unwrap_or_else(|| format_ident!("{}Tool", pascal));
+    let name = options
+        .name
+        .unwrap_or_else(|| LitStr::new(&fn_ident.to_string(), fn_ident.span()));
+    let description = match options.description {
+        Some(description) => description,
+        None => doc_string(&function.attrs).ok_or_else(|| {
+            syn::Error::new(
+                fn_ident.span(),
+                "add `description = \"...\"` or a doc comment to the tool function",
+            )
+        })?,
+    };
+
+    // Parameters become fields of the args struct; their attributes move
+    // there too, since most are only meaningful to serde and schemars
+    let mut fields = Vec::new();
+    let mut field_idents = Vec::new();
+    for input in function.sig.inputs.iter_mut() {
+        let FnArg::Typed(param) = input else {
+            return Err(syn::Error::new(
+                input.span(),
+                "MCP tool functions cannot take `self`",
+            ));
+        };
+        let Pat::Ident(pat) = param.pat.as_ref() else {
+            return Err(syn::Error::new(
+                param.pat.span(),
+                "MCP tool parameters must be plain identifiers",
+            ));
+        };
+        if let Type::Reference(reference) = param.ty.as_ref() {
+            return Err(syn::Error::new(
+                reference.span(),
+                "MCP tool parameters are deserialized and must be owned, e.g. `String`",
+            ));
+        }
+        let ident = &pat.ident;
+        let ty = &param.ty;
+        let attrs = std::mem::take(&mut param.attrs);
+        fields.push(quote! { #(#attrs)* #ident: #ty });
+        field_idents.push(ident.clone());
+    }
+
+    let when = &options.when;
+    let perfect_for = &options.perfect_for;
+    let annotations = if options.hints.is_empty() {
+        quote!()
+    } else {
+        let hints = &options.hints;
+        quote! {
+            fn annotations() -> Option<::sweetmcp_plugin_builder::serde_json::Value> {
+                let mut hints = ::sweetmcp_plugin_builder::serde_json::Map::new();
+                #(hints.insert(#hints.to_string(), true.into());)*
+                Some(::sweetmcp_plugin_builder::serde_json::Value::Object(hints))
+            }
+        }
+    };
+    let args_doc = LitStr::new(
+        &format!("Arguments of the `{}` tool", name.value()),
+        Span::call_site(),
+    );
+    let tool_doc = LitStr::new(
+        &format!("MCP tool running [`{}`]", fn_ident),
+        Span::call_site(),
+    );
+
+    Ok(quote! {
+        #function
+
+        #[doc = #args_doc]
+        #[derive(
+            ::sweetmcp_plugin_builder::serde::Deserialize,
+            ::sweetmcp_plugin_builder::schemars::JsonSchema
+        )]
+        #[serde(crate = "::sweetmcp_plugin_builder::serde")]
+        #[schemars(crate = "::sweetmcp_plugin_builder::schemars")]
+        #vis struct #args_ident {
+            #(#fields,)*
+        }
+
+        #[doc = #tool_doc]
+        #vis struct #tool_ident;
+
+        impl ::sweetmcp_plugin_builder::McpTool for #tool_ident {
+            const NAME: &'static str = #name;
+            type Args = #args_ident;
+
+            fn description(
+                builder: ::sweetmcp_plugin_builder::DescriptionBuilder,
+            ) -> ::sweetmcp_plugin_builder::DescriptionBuilder {
+                builder
+                    .does(#description)
+                    #(.when(#when))*
+                    #(.perfect_for(#perfect_for))*
+            }
+
+            fn execute_typed(
+                args: #args_ident,
+            ) -> ::core::result::Result<
+                ::sweetmcp_plugin_builder::CallToolResult,
+                ::sweetmcp_plugin_builder::Error,
+            > {
+                ::sweetmcp_plugin_builder::IntoToolResult::into_tool_result(
+                    #fn_ident(#(args.#field_idents),*),
+                )
+            }
+
+            #annotations
+        }
+    })
+}
+
+/// `get_time` -> `GetTime`
+fn pascal_case(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Doc comment lines joined into one sentence
+fn doc_string(attrs: &[Attribute]) -> Option<LitStr> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(nv) => match &nv.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(s), ..
+                }) => Some(s.value().trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .filter(|line| !line.is_empty())
+        .collect();
+    (!lines.is_empty()).then(|| LitStr::new(&lines.join(" "), Span::call_site()))
+}
@@ -0,0 +1,298 @@
+//! Outbound HTTP through the host
+//!
+//! A safe wrapper over Extism's `http_request` host function, so tools make
+//! network calls without declaring raw imports:
+//!
+//! ```ignore
+//! let ip = http::get("https://api.ipify.org")
+//!     .timeout(Duration::from_secs(5))
+//!     .send()?
+//!     .error_for_status()?
+//!     .text();
+//! ```
+//!
+//! The host only lets requests through to the plugin's `allowed_hosts`, and
+//! the plugin's `timeout_ms` bounds the whole call. On top of that every
+//! request carries a response size cap (10 MiB unless set) and a deadline
+//! (30 s unless set): the host cannot abort a request in flight, so a response
+//! arriving after the deadline is discarded as [`HttpError::Timeout`] rather
+//! than handed to the tool late.
+
+use extism_pdk::HttpRequest;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::ToolError;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_MAX_BYTES: usize = 10 * 1024 * 1024;
+
+/// `GET` request to `url`
+pub fn get(url: impl Into<String>) -> Request {
+    Request::new("GET", url)
+}
+
+/// `POST` request to `url`
+pub fn post(url: impl Into<String>) -> Request {
+    Request::new("POST", url)
+}
+
+/// Outbound request, sent with [`Request::send`]
+#[derive(Debug, Clone)]
+pub struct Request {
+    inner: HttpRequest,
+    body: Option<Vec<u8>>,
+    timeout: Duration,
+    max_bytes: usize,
+}
+
+impl Request {
+    /// Request with any method, e.g. `PUT` or `DELETE`
+    pub fn new(method: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            inner: HttpRequest::new(url).with_method(method),
+            body: None,
+            timeout: DEFAULT_TIMEOUT,
+            max_bytes: DEFAULT_MAX_BYTES,
+        }
+    }
+
+    /// Set a request header, replacing any earlier value
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.inner = self.inner.with_header(name, value);
+        self
+    }
+
+    /// `Authorization: Bearer <token>`
+    pub fn bearer_auth(self, token: impl std::fmt::Display) -> Self {
+        self.header("Authorization", format!("Bearer {}", token))
+    }
+
+    /// Raw request body
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+
+    /// JSON request body, with `Content-Type: application/json`
+    pub fn json(self, body: &impl Serialize) -> Result<Self, HttpError> {
+        let body = serde_json::to_vec(body).map_err(|e| HttpError::Decode(e.to_string()))?;
+        Ok(self.header("Content-Type", "application/json").body(body))
+    }
+
+    /// Discard responses that take longer than `timeout`
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Fail responses whose body is larger than `max_bytes`
+    pub fn max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    pub fn url(&self) -> &str {
+        &self.inner.url
+    }
+
+    /// Send the request through the host
+    ///
+    /// Any status is a successful send; use [`Response::error_for_status`]
+    /// to treat 4xx and 5xx as errors.
+    pub fn send(self) -> Result<Response, HttpError> {
+        let started = Instant::now();
+        let response = extism_pdk::http::request::<Vec<u8>>(&self.inner, self.body)
+            .map_err(|e| HttpError::Host(e.to_string()))?;
+        check_deadline(started.elapsed(), self.timeout)?;
+
+        let response = Response {
+            status: response.status_code(),
+            headers: response.headers().clone(),
+            body: response.body(),
+        };
+        check_size(response.body.len(), self.max_bytes)?;
+        Ok(response)
+    }
+}
+
+fn check_deadline(elapsed: Duration, limit: Duration) -> Result<(), HttpError> {
+    if elapsed > limit {
+        return Err(HttpError::Timeout { elapsed, limit });
+    }
+    Ok(())
+}
+
+fn check_size(size: usize, limit: usize) -> Result<(), HttpError> {
+    if size > limit {
+        return Err(HttpError::TooLarge { size, limit });
+    }
+    Ok(())
+}
+
+/// Response to a [`Request`]
+#[derive(Debug, Clone)]
+pub struct Response {
+    status: u16,
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+impl Response {
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+
+    /// Status is 2xx
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// Header value, matched case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.body
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.body
+    }
+
+    /// Body as text, with invalid UTF-8 replaced
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    /// Body parsed as JSON
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, HttpError> {
+        serde_json::from_slice(&self.body).map_err(|e| HttpError::Decode(e.to_string()))
+    }
+
+    /// `Err(HttpError::Status)` for 4xx and 5xx responses
+    pub fn error_for_status(self) -> Result<Self, HttpError> {
+        if self.status >= 400 {
+            return Err(HttpError::Status {
+                status: self.status,
+                body: self.text(),
+            });
+        }
+        Ok(self)
+    }
+}
+
+/// Why an outbound request failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HttpError {
+    /// The host refused or could not complete the request, e.g. the host is
+    /// not in `allowed_hosts` or the connection failed
+    Host(String),
+    /// The server answered 4xx or 5xx
+    Status { status: u16, body: String },
+    /// The response body exceeded the request's size cap
+    TooLarge { size: usize, limit: usize },
+    /// The response arrived after the request's deadline
+    Timeout { elapsed: Duration, limit: Duration },
+    /// A JSON body could not be encoded or decoded
+    Decode(String),
+}
+
+impl std::fmt::Display for HttpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Host(message) => write!(f, "HTTP request failed: {}", message),
+            Self::Status { status, body } if body.is_empty() => write!(f, "HTTP {}", status),
+            Self::Status { status, body } => write!(f, "HTTP {}: {}", status, body),
+            Self::TooLarge { size, limit } => write!(
+                f,
+                "Response of {} bytes exceeds the {} byte limit",
+                size, limit
+            ),
+            Self::Timeout { elapsed, limit } => write!(
+                f,
+                "Response took {} ms, over the {} ms timeout",
+                elapsed.as_millis(),
+                limit.as_millis()
+            ),
+            Self::Decode(message) => write!(f, "Invalid JSON: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for HttpError {}
+
+impl From<HttpError> for ToolError {
+    fn from(error: HttpError) -> Self {
+        match &error {
+            // Extism's wording when the URL is outside `allowed_hosts`
+            HttpError::Host(message) if message.contains("not allowed") => {
+                Self::permission_denied(error.to_string())
+            }
+            HttpError::Status { status: 404, .. } => Self::not_found(error.to_string()),
+            _ => Self::internal(error.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_builder_sets_method_headers_and_body() {
+        let request = post("https://example.com/hook")
+            .bearer_auth("secret")
+            .json(&serde_json::json!({ "text": "hi" }))
+            .unwrap()
+            .max_bytes(1024);
+        assert_eq!(request.inner.method.as_deref(), Some("POST"));
+        assert_eq!(request.inner.headers["Authorization"], "Bearer secret");
+        assert_eq!(request.inner.headers["Content-Type"], "application/json");
+        assert_eq!(request.body.as_deref(), Some(&br#"{"text":"hi"}"#[..]));
+        assert_eq!(request.max_bytes, 1024);
+        assert_eq!(request.timeout, DEFAULT_TIMEOUT);
+    }
+
+    #[test]
+    fn limits_are_enforced() {
+        assert!(check_size(10, 10).is_ok());
+        assert_eq!(
+            check_size(11, 10),
+            Err(HttpError::TooLarge {
+                size: 11,
+                limit: 10
+            })
+        );
+        assert!(check_deadline(Duration::from_secs(2), Duration::from_secs(1)).is_err());
+        assert!(check_deadline(Duration::from_millis(5), Duration::from_secs(1)).is_ok());
+    }
+
+    #[test]
+    fn response_helpers() {
+        let response = Response {
+            status: 404,
+            headers: HashMap::from([("Content-Type".to_string(), "text/plain".to_string())]),
+            body: b"missing".to_vec(),
+        };
+        assert_eq!(response.header("content-type"), Some("text/plain"));
+        assert!(!response.is_success());
+
+        let error = response.error_for_status().unwrap_err();
+        assert_eq!(error.to_string(), "HTTP 404: missing");
+        assert_eq!(ToolError::from(error).code(), "not_found");
+        assert_eq!(
+            ToolError::from(HttpError::Host(
+                "HTTP request to https://evil.test/ is not allowed".into()
+            ))
+            .code(),
+            "permission_denied"
+        );
+    }
+}
@@ -3,14 +3,34 @@
 //! No `new()`, no boilerplate, just pure fluent chaining with closures
 
 use extism_pdk::*;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
+pub mod http;
+pub mod test_harness;
+
+pub use extism_pdk::Error;
+pub use schemars;
+pub use serde;
+pub use serde_json;
+pub use sweetmcp_plugin_builder_macros::mcp_tool;
+
+// Lets `#[mcp_tool]` expansions name this crate from its own tests
+extern crate self as sweetmcp_plugin_builder;
 
 pub mod prelude {
     pub use super::{
-        ContentBuilder, DescriptionBuilder, McpPlugin, McpTool, SchemaBuilder, mcp_plugin,
+        CancellationToken, ContentBuilder, Deadline, DescriptionBuilder, IntoToolResult, McpPlugin,
+        McpPrompt, McpResource, McpTool, Progress, PromptBuilder, ResourceContents, SchemaBuilder,
+        ToolCall, ToolError, http, mcp_plugin, mcp_tool, schema_for,
     };
+    pub use schemars::JsonSchema;
 }
 
 // MCP protocol types
@@ -23,6 +43,31 @@ pub struct CallToolRequest {
 pub struct CallToolParams {
     pub name: String,
     pub arguments: Option<serde_json::Map<String, Value>>,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<RequestMeta>,
+}
+
+/// `_meta` sent by the client with a tool call
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestMeta {
+    /// Token to attach to progress notifications; a string or a number
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress_token: Option<Value>,
+    /// Id of the JSON-RPC request, added by the host for cancellation checks
+    #[serde(
+        rename = "sweetmcp/requestId",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub request_id: Option<Value>,
+    /// Milliseconds the host allows for the call, added when it has a deadline
+    #[serde(
+        rename = "sweetmcp/timeoutMs",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub timeout_ms: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +84,57 @@ pub struct Content {
     pub mime_type: Option<String>,
     pub data: Option<String>,
     pub annotations: Option<Value>,
+    /// Target of a `resource_link`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+    /// Display name of a `resource_link`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl Content {
+    /// Plain text content
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::typed_text(text, "text/plain")
+    }
+
+    fn typed_text(text: impl Into<String>, mime_type: &str) -> Self {
+        Self {
+            r#type: ContentType::Text,
+            text: Some(text.into()),
+            mime_type: Some(mime_type.into()),
+            data: None,
+            annotations: None,
+            uri: None,
+            name: None,
+        }
+    }
+
+    /// Base64-encoded image content
+    pub fn image(data: impl Into<String>, mime_type: impl Into<String>) -> Self {
+        Self {
+            r#type: ContentType::Image,
+            text: None,
+            mime_type: Some(mime_type.into()),
+            data: Some(data.into()),
+            annotations: None,
+            uri: None,
+            name: None,
+        }
+    }
+
+    /// Link to a resource the client can read with `resources/read`
+    pub fn resource_link(uri: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            r#type: ContentType::ResourceLink,
+            text: None,
+            mime_type: None,
+            data: None,
+            annotations: None,
+            uri: Some(uri.into()),
+            name: Some(name.into()),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -48,11 +144,27 @@ pub enum ContentType {
     Text,
     #[serde(rename = "image")]
     Image,
+    #[serde(rename = "resource_link")]
+    ResourceLink,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ListToolsResult {
     pub tools: Vec<ToolDescription>,
+    /// Who is describing the tools, for the host's routing and compatibility
+    /// decisions; not part of MCP's `tools/list`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plugin: Option<PluginInfo>,
+}
+
+/// Name, version and capabilities of a plugin
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PluginInfo {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub capabilities: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -60,6 +172,126 @@ pub struct ToolDescription {
     pub name: String,
     pub description: String,
     pub input_schema: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<Value>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<ToolExample>,
+}
+
+/// A sample invocation shown to clients next to the tool description
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolExample {
+    pub description: String,
+    pub arguments: Value,
+}
+
+/// Prompt as listed by `mcp_list_prompts`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptDescription {
+    pub id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub arguments: Vec<PromptArgument>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptArgument {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub required: Option<bool>,
+}
+
+/// Input of `mcp_get_prompt_template`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetPromptTemplateRequest {
+    pub id: String,
+}
+
+/// Resource as listed by `list_resources`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceDescription {
+    pub uri: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+}
+
+/// Parameterised resource such as `notes://{id}`, listed by `list_resources`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceTemplateDescription {
+    pub uri_template: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+}
+
+/// Output of `list_resources`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListResourcesResult {
+    pub resources: Vec<ResourceDescription>,
+    pub resource_templates: Vec<ResourceTemplateDescription>,
+}
+
+/// Input of `read_resource`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadResourceRequest {
+    pub uri: String,
+}
+
+/// Output of `read_resource`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadResourceResult {
+    pub content: ResourceContents,
+}
+
+/// Contents of a resource: `text`, or base64 `blob` for binary data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceContents {
+    pub uri: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blob: Option<String>,
+}
+
+impl ResourceContents {
+    /// Text contents; the MIME type defaults to the resource's `MIME_TYPE`
+    pub fn text(uri: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            uri: uri.into(),
+            mime_type: None,
+            text: Some(text.into()),
+            blob: None,
+        }
+    }
+
+    /// Base64-encoded binary contents
+    pub fn blob(
+        uri: impl Into<String>,
+        base64: impl Into<String>,
+        mime_type: impl Into<String>,
+    ) -> Self {
+        Self {
+            uri: uri.into(),
+            mime_type: Some(mime_type.into()),
+            text: None,
+            blob: Some(base64.into()),
+        }
+    }
 }
 
 /// Type states for compile-time safety
@@ -72,14 +304,58 @@ pub struct Ready;
 pub struct McpPlugin<State = Empty> {
     name: Option<String>,
     description: Option<String>,
+    version: Option<String>,
+    capabilities: Vec<String>,
     tools: Vec<ToolDef>,
+    prompts: Vec<PromptDef>,
+    resources: Vec<ResourceDef>,
+    before_call: Vec<BeforeCall>,
+    after_call: Vec<AfterCall>,
     _state: PhantomData<State>,
 }
 
+/// A tool call as seen by middleware
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolCall {
+    /// Name of the tool being called
+    pub name: String,
+    /// Raw arguments; `before_call` hooks may rewrite them
+    pub arguments: Value,
+}
+
+type Validator = Box<dyn Fn(&Value) -> Result<(), ToolError> + Send + Sync>;
+type BeforeCall = Box<dyn Fn(&mut ToolCall) -> Result<(), ToolError> + Send + Sync>;
+type AfterCall = Box<dyn Fn(&ToolCall, &mut Result<CallToolResult, Error>) + Send + Sync>;
+
+struct ResourceDef {
+    type_name: &'static str,
+    uri: &'static str,
+    name: &'static str,
+    description: Option<String>,
+    mime_type: &'static str,
+    reader: fn(&str, &HashMap<String, String>) -> Result<ResourceContents, Error>,
+}
+
+impl ResourceDef {
+    fn is_template(&self) -> bool {
+        self.uri.contains('{')
+    }
+}
+
+struct PromptDef {
+    type_name: &'static str,
+    description: PromptDescription,
+    template: String,
+}
+
 struct ToolDef {
     name: String,
+    type_name: &'static str,
     description: String,
     schema: Value,
+    annotations: Option<Value>,
+    examples: Vec<ToolExample>,
+    validators: Vec<Validator>,
     handler: Box<dyn Fn(Value) -> Result<CallToolResult, Error> + Send + Sync>,
 }
 
@@ -88,7 +364,13 @@ pub fn mcp_plugin(name: impl Into<String>) -> McpPlugin<Named> {
     McpPlugin {
         name: Some(name.into()),
         description: None,
+        version: None,
+        capabilities: Vec::new(),
         tools: Vec::new(),
+        prompts: Vec::new(),
+        resources: Vec::new(),
+        before_call: Vec::new(),
+        after_call: Vec::new(),
         _state: PhantomData,
     }
 }
@@ -99,31 +381,219 @@ impl McpPlugin<Named> {
         McpPlugin {
             name: self.name,
             description: Some(desc.into()),
+            version: self.version,
+            capabilities: self.capabilities,
             tools: self.tools,
+            prompts: self.prompts,
+            resources: self.resources,
+            before_call: self.before_call,
+            after_call: self.after_call,
             _state: PhantomData,
         }
     }
 }
 
 impl McpPlugin<Described> {
+    /// Plugin version reported by `describe`, typically
+    /// `.version(env!("CARGO_PKG_VERSION"))`
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    /// Advertise an optional feature, e.g. `"streaming"` or `"stateful"`, so
+    /// the host can adapt to the plugin. Repeatable; duplicates are ignored.
+    pub fn capability(mut self, capability: impl Into<String>) -> Self {
+        let capability = capability.into();
+        if !self.capabilities.contains(&capability) {
+            self.capabilities.push(capability);
+        }
+        self
+    }
+
     /// Register a tool with const-generic type
+    ///
+    /// # Panics
+    ///
+    /// If a tool with the same `NAME` is already registered. The plugin is
+    /// built on every call, so a clash surfaces on the first request instead
+    /// of the second tool silently never being dispatched to. An empty `NAME`
+    /// is rejected at compile time.
     pub fn tool<T: McpTool>(mut self) -> Self {
-        let description = T::description(DescriptionBuilder::default());
+        const { assert!(!T::NAME.is_empty(), "McpTool::NAME must not be empty") };
+
+        if let Some(existing) = self.tools.iter().find(|tool| tool.name == T::NAME) {
+            panic!(
+                "plugin '{}': tool name '{}' is registered by both `{}` and `{}`; tool names must be unique",
+                self.name.as_deref().unwrap_or_default(),
+                T::NAME,
+                existing.type_name,
+                std::any::type_name::<T>(),
+            );
+        }
+
+        let mut description = T::description(DescriptionBuilder::default());
+        let examples = std::mem::take(&mut description.examples);
         self.tools.push(ToolDef {
             name: T::NAME.to_string(),
+            type_name: std::any::type_name::<T>(),
             description: description.build(),
             schema: T::schema(SchemaBuilder::default()),
+            annotations: T::annotations(),
+            examples,
+            validators: Vec::new(),
             handler: Box::new(T::execute),
         });
         self
     }
 
+    /// Check the arguments of the tool registered just before, after the
+    /// `before_call` hooks ran and before the tool does. An `Err` becomes the
+    /// call's error result and the tool is not run. Repeatable.
+    ///
+    /// ```ignore
+    /// .tool::<ReadFile>()
+    /// .validate(|args| match args["path"].as_str() {
+    ///     Some(path) if path.starts_with("/srv/") => Ok(()),
+    ///     _ => Err(ToolError::permission_denied("path must be under /srv")),
+    /// })
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If no tool has been registered yet.
+    pub fn validate(
+        mut self,
+        check: impl Fn(&Value) -> Result<(), ToolError> + Send + Sync + 'static,
+    ) -> Self {
+        let Some(tool) = self.tools.last_mut() else {
+            panic!(
+                "plugin '{}': validate() must follow the tool() it applies to",
+                self.name.as_deref().unwrap_or_default(),
+            );
+        };
+        tool.validators.push(Box::new(check));
+        self
+    }
+
+    /// Run `hook` before every tool call, in registration order. Hooks may
+    /// rewrite the arguments; an `Err` becomes the call's error result and
+    /// neither later hooks nor the tool run. Suits path allowlists and
+    /// argument limits that apply to every tool.
+    pub fn before_call(
+        mut self,
+        hook: impl Fn(&mut ToolCall) -> Result<(), ToolError> + Send + Sync + 'static,
+    ) -> Self {
+        self.before_call.push(Box::new(hook));
+        self
+    }
+
+    /// Run `hook` after every tool call, including calls rejected by
+    /// validation, in registration order. Hooks see the final arguments and
+    /// may replace the outcome, e.g. to redact output; audit logging fits
+    /// here.
+    pub fn after_call(
+        mut self,
+        hook: impl Fn(&ToolCall, &mut Result<CallToolResult, Error>) + Send + Sync + 'static,
+    ) -> Self {
+        self.after_call.push(Box::new(hook));
+        self
+    }
+
+    /// Register a prompt template
+    ///
+    /// # Panics
+    ///
+    /// If a prompt with the same `NAME` is already registered or the prompt
+    /// has no template.
+    pub fn prompt<T: McpPrompt>(mut self) -> Self {
+        const { assert!(!T::NAME.is_empty(), "McpPrompt::NAME must not be empty") };
+
+        if let Some(existing) = self
+            .prompts
+            .iter()
+            .find(|prompt| prompt.description.id == T::NAME)
+        {
+            panic!(
+                "plugin '{}': prompt name '{}' is registered by both `{}` and `{}`; prompt names must be unique",
+                self.name.as_deref().unwrap_or_default(),
+                T::NAME,
+                existing.type_name,
+                std::any::type_name::<T>(),
+            );
+        }
+
+        let builder = T::prompt(PromptBuilder::default());
+        let Some(template) = builder.template else {
+            panic!(
+                "plugin '{}': prompt '{}' has no template",
+                self.name.as_deref().unwrap_or_default(),
+                T::NAME,
+            );
+        };
+        self.prompts.push(PromptDef {
+            type_name: std::any::type_name::<T>(),
+            description: PromptDescription {
+                id: T::NAME.to_string(),
+                name: T::NAME.to_string(),
+                description: builder.description,
+                arguments: builder.arguments,
+            },
+            template,
+        });
+        self
+    }
+
+    /// Register a resource or resource template
+    ///
+    /// # Panics
+    ///
+    /// If a resource with the same `URI` is already registered.
+    pub fn resource<T: McpResource>(mut self) -> Self {
+        const { assert!(!T::URI.is_empty(), "McpResource::URI must not be empty") };
+
+        if let Some(existing) = self.resources.iter().find(|r| r.uri == T::URI) {
+            panic!(
+                "plugin '{}': resource '{}' is registered by both `{}` and `{}`; resource URIs must be unique",
+                self.name.as_deref().unwrap_or_default(),
+                T::URI,
+                existing.type_name,
+                std::any::type_name::<T>(),
+            );
+        }
+
+        self.resources.push(ResourceDef {
+            type_name: std::any::type_name::<T>(),
+            uri: T::URI,
+            name: T::NAME,
+            description: T::description(),
+            mime_type: T::MIME_TYPE,
+            reader: T::read,
+        });
+        self
+    }
+
     /// Ready to serve MCP clients
+    ///
+    /// The returned plugin no longer accepts tools:
+    ///
+    /// ```compile_fail
+    /// # use sweetmcp_plugin_builder::prelude::*;
+    /// # fn plugin<T: McpTool>() {
+    /// mcp_plugin("p").description("d").serve().tool::<T>();
+    /// # }
+    /// ```
     pub fn serve(self) -> McpPlugin<Ready> {
         McpPlugin {
             name: self.name,
             description: self.description,
+            version: self.version,
+            capabilities: self.capabilities,
             tools: self.tools,
+            prompts: self.prompts,
+            resources: self.resources,
+            before_call: self.before_call,
+            after_call: self.after_call,
             _state: PhantomData,
         }
     }
@@ -132,16 +602,40 @@ impl McpPlugin<Described> {
 impl McpPlugin<Ready> {
     /// Handle incoming MCP calls
     pub fn call(&self, request: CallToolRequest) -> Result<CallToolResult, Error> {
-        let tool_name = &request.params.name;
-        let args = request.params.arguments.unwrap_or_default();
+        let Some(tool) = self
+            .tools
+            .iter()
+            .find(|tool| tool.name == request.params.name)
+        else {
+            return Err(Error::msg(format!(
+                "Tool '{}' not found",
+                request.params.name
+            )));
+        };
+        let mut call = ToolCall {
+            name: request.params.name,
+            arguments: Value::Object(request.params.arguments.unwrap_or_default()),
+        };
+        let meta = request.params.meta.unwrap_or_default();
 
-        for tool in &self.tools {
-            if tool.name == *tool_name {
-                return (tool.handler)(Value::Object(args));
-            }
+        let _scope = CallScope::enter(meta);
+        let checked = self
+            .before_call
+            .iter()
+            .try_for_each(|hook| hook(&mut call))
+            .and_then(|()| {
+                tool.validators
+                    .iter()
+                    .try_for_each(|check| check(&call.arguments))
+            });
+        let mut outcome = match checked {
+            Ok(()) => (tool.handler)(call.arguments.clone()),
+            Err(error) => Ok(ContentBuilder::error(error)),
+        };
+        for hook in &self.after_call {
+            hook(&call, &mut outcome);
         }
-
-        Err(Error::msg(format!("Tool '{}' not found", tool_name)))
+        outcome
     }
 
     /// Describe available tools
@@ -153,20 +647,237 @@ impl McpPlugin<Ready> {
                 name: tool.name.clone(),
                 description: tool.description.clone(),
                 input_schema: tool.schema.clone(),
+                annotations: tool.annotations.clone(),
+                examples: tool.examples.clone(),
             })
             .collect();
 
-        Ok(ListToolsResult { tools })
+        Ok(ListToolsResult {
+            tools,
+            plugin: Some(PluginInfo {
+                name: self.name.clone().unwrap_or_default(),
+                version: self.version.clone(),
+                capabilities: self.capabilities.clone(),
+            }),
+        })
+    }
+
+    /// Describe available resources and resource templates
+    pub fn list_resources(&self) -> Result<ListResourcesResult, Error> {
+        let mut result = ListResourcesResult::default();
+        for resource in &self.resources {
+            if resource.is_template() {
+                result.resource_templates.push(ResourceTemplateDescription {
+                    uri_template: resource.uri.to_string(),
+                    name: resource.name.to_string(),
+                    description: resource.description.clone(),
+                    mime_type: Some(resource.mime_type.to_string()),
+                });
+            } else {
+                result.resources.push(ResourceDescription {
+                    uri: resource.uri.to_string(),
+                    name: resource.name.to_string(),
+                    description: resource.description.clone(),
+                    mime_type: Some(resource.mime_type.to_string()),
+                });
+            }
+        }
+        Ok(result)
+    }
+
+    /// Read a resource by exact URI, falling back to the first matching template
+    pub fn read_resource(&self, request: ReadResourceRequest) -> Result<ReadResourceResult, Error> {
+        let uri = request.uri.as_str();
+        let matched = self
+            .resources
+            .iter()
+            .find(|r| !r.is_template() && r.uri == uri)
+            .map(|r| (r, HashMap::new()))
+            .or_else(|| {
+                self.resources
+                    .iter()
+                    .filter(|r| r.is_template())
+                    .find_map(|r| match_uri_template(r.uri, uri).map(|params| (r, params)))
+            });
+        let Some((resource, params)) = matched else {
+            return Err(Error::msg(format!("Resource '{}' not found", uri)));
+        };
+
+        let mut content = (resource.reader)(uri, &params)?;
+        if content.mime_type.is_none() {
+            content.mime_type = Some(resource.mime_type.to_string());
+        }
+        Ok(ReadResourceResult { content })
+    }
+
+    /// Describe available prompts
+    pub fn list_prompts(&self) -> Result<Vec<PromptDescription>, Error> {
+        Ok(self
+            .prompts
+            .iter()
+            .map(|prompt| prompt.description.clone())
+            .collect())
+    }
+
+    /// Raw template of a prompt; the host renders it
+    pub fn get_prompt_template(&self, request: GetPromptTemplateRequest) -> Result<String, Error> {
+        self.prompts
+            .iter()
+            .find(|prompt| prompt.description.id == request.id)
+            .map(|prompt| prompt.template.clone())
+            .ok_or_else(|| Error::msg(format!("Prompt '{}' not found", request.id)))
     }
 }
 
 /// Tool trait with fluent description
+///
+/// Tools that take typed arguments set `Args` to a
+/// `#[derive(Deserialize, JsonSchema)]` struct and implement `execute_typed`;
+/// the schema is generated from the struct and the arguments are parsed
+/// before `execute_typed` runs. Tools that work on raw JSON set
+/// `type Args = Value` and implement `schema` and `execute` themselves.
 pub trait McpTool: Send + Sync + 'static {
     const NAME: &'static str;
 
+    /// Arguments the tool is called with
+    type Args: DeserializeOwned + JsonSchema;
+
     fn description(builder: DescriptionBuilder) -> DescriptionBuilder;
-    fn schema(builder: SchemaBuilder) -> Value;
-    fn execute(args: Value) -> Result<CallToolResult, Error>;
+
+    /// Input schema, generated from `Args` by default
+    fn schema(_builder: SchemaBuilder) -> Value {
+        schema_for::<Self::Args>()
+    }
+
+    /// Parse the raw arguments into `Args` and run `execute_typed`
+    fn execute(args: Value) -> Result<CallToolResult, Error> {
+        match serde_json::from_value::<Self::Args>(args) {
+            Ok(args) => Self::execute_typed(args),
+            Err(e) => Ok(ContentBuilder::error(ToolError::invalid_argument(
+                "arguments",
+                format!("Invalid arguments for '{}': {}", Self::NAME, e),
+            ))),
+        }
+    }
+
+    /// Run the tool with parsed arguments
+    fn execute_typed(_args: Self::Args) -> Result<CallToolResult, Error> {
+        Err(Error::msg(format!(
+            "Tool '{}' implements neither execute nor execute_typed",
+            Self::NAME
+        )))
+    }
+
+    /// MCP tool annotations, e.g. `{"readOnlyHint": true}`
+    fn annotations() -> Option<Value> {
+        None
+    }
+}
+
+/// Resource exposed by the plugin
+///
+/// `URI` is either a fixed URI (`config://settings`) or a template with
+/// `{name}` placeholders (`notes://{id}`); placeholders are passed to `read`
+/// by name.
+pub trait McpResource: Send + Sync + 'static {
+    const URI: &'static str;
+    const NAME: &'static str;
+    const MIME_TYPE: &'static str = "text/plain";
+
+    fn description() -> Option<String> {
+        None
+    }
+
+    fn read(uri: &str, params: &HashMap<String, String>) -> Result<ResourceContents, Error>;
+}
+
+/// Match `uri` against a `{name}` template, returning the placeholder values.
+///
+/// A placeholder matches up to the first occurrence of the literal text that
+/// follows it, or the rest of the URI when it comes last; it never matches an
+/// empty string.
+pub fn match_uri_template(template: &str, uri: &str) -> Option<HashMap<String, String>> {
+    let mut params = HashMap::new();
+    let mut rest_template = template;
+    let mut rest_uri = uri;
+
+    while let Some(open) = rest_template.find('{') {
+        let literal = &rest_template[..open];
+        rest_uri = rest_uri.strip_prefix(literal)?;
+        let close = open + rest_template[open..].find('}')?;
+        let name = &rest_template[open + 1..close];
+        rest_template = &rest_template[close + 1..];
+
+        let next_literal = &rest_template[..rest_template.find('{').unwrap_or(rest_template.len())];
+        let end = if next_literal.is_empty() {
+            if !rest_template.is_empty() {
+                // Adjacent placeholders are ambiguous
+                return None;
+            }
+            rest_uri.len()
+        } else {
+            rest_uri.find(next_literal)?
+        };
+        if end == 0 {
+            return None;
+        }
+        params.insert(name.to_string(), rest_uri[..end].to_string());
+        rest_uri = &rest_uri[end..];
+    }
+
+    (rest_template == rest_uri).then_some(params)
+}
+
+/// Prompt template with fluent metadata
+pub trait McpPrompt: Send + Sync + 'static {
+    const NAME: &'static str;
+
+    fn prompt(builder: PromptBuilder) -> PromptBuilder;
+}
+
+/// Fluent prompt builder
+///
+/// Templates use Jinja syntax and are rendered by the host, e.g.
+/// `"List the contents of '{{ path | default(\".\") }}'."`
+#[derive(Default)]
+pub struct PromptBuilder {
+    description: Option<String>,
+    arguments: Vec<PromptArgument>,
+    template: Option<String>,
+}
+
+impl PromptBuilder {
+    /// What the prompt is for
+    pub fn description(mut self, desc: impl Into<String>) -> Self {
+        self.description = Some(desc.into());
+        self
+    }
+
+    /// Argument the template cannot do without
+    pub fn required_arg(mut self, name: impl Into<String>, desc: impl Into<String>) -> Self {
+        self.arguments.push(PromptArgument {
+            name: name.into(),
+            description: Some(desc.into()),
+            required: Some(true),
+        });
+        self
+    }
+
+    /// Argument the template has a default for
+    pub fn optional_arg(mut self, name: impl Into<String>, desc: impl Into<String>) -> Self {
+        self.arguments.push(PromptArgument {
+            name: name.into(),
+            description: Some(desc.into()),
+            required: Some(false),
+        });
+        self
+    }
+
+    /// The template text
+    pub fn template(mut self, template: impl Into<String>) -> Self {
+        self.template = Some(template.into());
+        self
+    }
 }
 
 /// Fluent description builder
@@ -179,6 +890,7 @@ pub struct DescriptionBuilder {
     prerequisites: Vec<String>,
     limitations: Vec<String>,
     always_use_for: Vec<String>,
+    examples: Vec<ToolExample>,
 }
 
 impl DescriptionBuilder {
@@ -224,6 +936,18 @@ impl DescriptionBuilder {
         self
     }
 
+    /// Sample invocation, e.g. `.example(json!({"operation": "list", "path": "."}), "List the current directory")`
+    ///
+    /// Examples are listed in the structured `examples` field of the tool
+    /// rather than in the description text.
+    pub fn example(mut self, arguments: Value, description: impl Into<String>) -> Self {
+        self.examples.push(ToolExample {
+            description: description.into(),
+            arguments,
+        });
+        self
+    }
+
     /// Build the description following MCP best practices
     pub fn build(self) -> String {
         let mut parts = Vec::new();
@@ -368,6 +1092,108 @@ impl SchemaBuilder {
         self
     }
 
+    /// Optional integer parameter with inclusive bounds
+    pub fn optional_integer(
+        mut self,
+        name: impl Into<String>,
+        desc: impl Into<String>,
+        min: Option<i64>,
+        max: Option<i64>,
+    ) -> Self {
+        let mut schema = serde_json::json!({
+            "type": "integer",
+            "description": desc.into()
+        });
+        if let Some(min) = min {
+            schema["minimum"] = min.into();
+        }
+        if let Some(max) = max {
+            schema["maximum"] = max.into();
+        }
+        self.properties.insert(name.into(), schema);
+        self
+    }
+
+    /// Required array parameter, e.g. `items = json!({"type": "string"})`
+    pub fn required_array(
+        mut self,
+        name: impl Into<String>,
+        desc: impl Into<String>,
+        items: Value,
+    ) -> Self {
+        let name = name.into();
+        self.properties.insert(
+            name.clone(),
+            serde_json::json!({
+                "type": "array",
+                "description": desc.into(),
+                "items": items
+            }),
+        );
+        self.required.push(name);
+        self
+    }
+
+    /// Optional array parameter, e.g. `items = json!({"type": "string"})`
+    pub fn optional_array(
+        mut self,
+        name: impl Into<String>,
+        desc: impl Into<String>,
+        items: Value,
+    ) -> Self {
+        self.properties.insert(
+            name.into(),
+            serde_json::json!({
+                "type": "array",
+                "description": desc.into(),
+                "items": items
+            }),
+        );
+        self
+    }
+
+    /// Required object parameter whose fields are described by a nested builder
+    pub fn required_object(
+        mut self,
+        name: impl Into<String>,
+        desc: impl Into<String>,
+        fields: impl FnOnce(SchemaBuilder) -> SchemaBuilder,
+    ) -> Self {
+        let name = name.into();
+        let mut schema = fields(SchemaBuilder::default()).build();
+        schema["description"] = desc.into().into();
+        self.properties.insert(name.clone(), schema);
+        self.required.push(name);
+        self
+    }
+
+    /// Optional object parameter with free-form keys, each value matching
+    /// `values`, e.g. `json!({"type": "string"})` for HTTP headers
+    pub fn optional_map(
+        mut self,
+        name: impl Into<String>,
+        desc: impl Into<String>,
+        values: Value,
+    ) -> Self {
+        self.properties.insert(
+            name.into(),
+            serde_json::json!({
+                "type": "object",
+                "description": desc.into(),
+                "additionalProperties": values
+            }),
+        );
+        self
+    }
+
+    /// Default for an already declared parameter; ignored for unknown names
+    pub fn default_value(mut self, name: &str, value: impl Into<Value>) -> Self {
+        if let Some(Value::Object(property)) = self.properties.get_mut(name) {
+            property.insert("default".into(), value.into());
+        }
+        self
+    }
+
     /// Build the schema
     pub fn build(self) -> Value {
         serde_json::json!({
@@ -378,35 +1204,385 @@ impl SchemaBuilder {
     }
 }
 
+/// MCP input schema for `T`
+///
+/// Subschemas are inlined, since MCP clients don't resolve `$ref`s, and the
+/// root is always an object schema.
+pub fn schema_for<T: JsonSchema>() -> Value {
+    let generator = schemars::generate::SchemaSettings::draft07()
+        .with(|settings| {
+            settings.inline_subschemas = true;
+            settings.meta_schema = None;
+        })
+        .into_generator();
+    let mut schema = generator.into_root_schema_for::<T>().to_value();
+    if let Value::Object(map) = &mut schema {
+        map.remove("title");
+        map.entry("type").or_insert_with(|| "object".into());
+        map.entry("properties")
+            .or_insert_with(|| Value::Object(Default::default()));
+    } else {
+        schema = SchemaBuilder::default().build();
+    }
+    schema
+}
+
+#[host_fn]
+extern "ExtismHost" {
+    fn mcp_progress(report: Json<Value>) -> Json<Value>;
+    fn mcp_is_cancelled(query: Json<Value>) -> Json<Value>;
+}
+
+thread_local! {
+    static CALL_META: RefCell<Option<RequestMeta>> = const { RefCell::new(None) };
+    static CALL_DEADLINE: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+/// Makes the `_meta` of the tool call being dispatched visible to
+/// `Progress::current`, `CancellationToken::current` and `Deadline::current`
+/// until dropped
+struct CallScope;
+
+impl CallScope {
+    fn enter(meta: RequestMeta) -> Self {
+        let deadline = meta
+            .timeout_ms
+            .map(|ms| Instant::now() + Duration::from_millis(ms));
+        CALL_DEADLINE.with(|current| current.set(deadline));
+        CALL_META.with(|current| *current.borrow_mut() = Some(meta));
+        Self
+    }
+
+    fn current<T>(field: impl FnOnce(&RequestMeta) -> Option<T>) -> Option<T> {
+        CALL_META.with(|current| current.borrow().as_ref().and_then(field))
+    }
+}
+
+impl Drop for CallScope {
+    fn drop(&mut self) {
+        CALL_META.with(|current| *current.borrow_mut() = None);
+        CALL_DEADLINE.with(|current| current.set(None));
+    }
+}
+
+/// Progress reporter for long-running tools
+///
+/// Reports are sent to the client as `notifications/progress` through the
+/// host's `mcp_progress` function. When the client did not ask for progress
+/// (no `progressToken` in the call's `_meta`) reporting is a no-op, so tools
+/// can report unconditionally.
+#[derive(Debug, Clone)]
+pub struct Progress {
+    token: Option<Value>,
+}
+
+impl Progress {
+    /// Reporter for the tool call currently being executed
+    pub fn current() -> Self {
+        Self {
+            token: CallScope::current(|meta| meta.progress_token.clone()),
+        }
+    }
+
+    /// The client's progress token, if it asked for progress
+    pub fn token(&self) -> Option<&Value> {
+        self.token.as_ref()
+    }
+
+    /// Report `percent` (0-100) complete with a short status message.
+    /// Delivery failures are ignored; progress is advisory.
+    pub fn report(&self, percent: f64, message: impl Into<String>) {
+        let Some(token) = &self.token else {
+            return;
+        };
+        let report = serde_json::json!({
+            "progressToken": token,
+            "progress": percent.clamp(0.0, 100.0),
+            "total": 100,
+            "message": message.into(),
+        });
+        let _ = unsafe { mcp_progress(Json(report)) };
+    }
+}
+
+/// Cooperative cancellation for long-running tools
+///
+/// The host flags a call as cancelled when the client sends
+/// `notifications/cancelled` for it; tools poll `is_cancelled` (or `check`)
+/// between units of work and stop early. Outside a host that forwards the
+/// request id the token is never cancelled.
+#[derive(Debug, Clone)]
+pub struct CancellationToken {
+    request_id: Option<Value>,
+}
+
+impl CancellationToken {
+    /// Token for the tool call currently being executed
+    pub fn current() -> Self {
+        Self {
+            request_id: CallScope::current(|meta| meta.request_id.clone()),
+        }
+    }
+
+    /// Ask the host whether the client cancelled this call
+    pub fn is_cancelled(&self) -> bool {
+        let Some(request_id) = &self.request_id else {
+            return false;
+        };
+        let query = serde_json::json!({ "requestId": request_id });
+        match unsafe { mcp_is_cancelled(Json(query)) } {
+            Ok(Json(status)) => status["cancelled"].as_bool().unwrap_or(false),
+            Err(_) => false,
+        }
+    }
+
+    /// `Err` once the call is cancelled, for use with `?` between stages
+    pub fn check(&self) -> Result<(), Error> {
+        if self.is_cancelled() {
+            Err(Error::msg("Request cancelled by the client"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Time budget of the current tool call
+///
+/// The host stops a call once its deadline passes, so a tool with optional or
+/// fallback work should check `remaining` first and skip what won't fit
+/// rather than be cut off halfway. Without a host deadline every check passes.
+#[derive(Debug, Clone, Copy)]
+pub struct Deadline {
+    at: Option<Instant>,
+}
+
+impl Deadline {
+    /// Deadline of the tool call currently being executed
+    pub fn current() -> Self {
+        Self {
+            at: CALL_DEADLINE.with(Cell::get),
+        }
+    }
+
+    /// Time left, or `None` when the call has no deadline
+    pub fn remaining(&self) -> Option<Duration> {
+        self.at
+            .map(|at| at.saturating_duration_since(Instant::now()))
+    }
+
+    /// Whether at least `needed` is left
+    pub fn allows(&self, needed: Duration) -> bool {
+        self.remaining().is_none_or(|left| left >= needed)
+    }
+
+    /// `Err` once the deadline has passed, for use with `?` between stages
+    pub fn check(&self) -> Result<(), Error> {
+        if self.remaining() == Some(Duration::ZERO) {
+            Err(Error::msg("Call deadline exceeded"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Tool failure with a machine-readable code
+///
+/// Passed to `ContentBuilder::error`, it becomes error content whose text is
+/// the message and whose `annotations.error` carries `code`, `rpcCode` and,
+/// for invalid arguments, the offending `field`. Plain strings convert to
+/// `Internal`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToolError {
+    /// An argument is missing or malformed
+    InvalidArgument { field: String, message: String },
+    /// The requested file, record or resource does not exist
+    NotFound { message: String },
+    /// The plugin's policy or the host refused the operation
+    PermissionDenied { message: String },
+    /// Anything else
+    Internal { message: String },
+}
+
+impl ToolError {
+    pub fn invalid_argument(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::InvalidArgument {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::NotFound {
+            message: message.into(),
+        }
+    }
+
+    pub fn permission_denied(message: impl Into<String>) -> Self {
+        Self::PermissionDenied {
+            message: message.into(),
+        }
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::Internal {
+            message: message.into(),
+        }
+    }
+
+    /// Stable snake_case code, e.g. `invalid_argument`
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidArgument { .. } => "invalid_argument",
+            Self::NotFound { .. } => "not_found",
+            Self::PermissionDenied { .. } => "permission_denied",
+            Self::Internal { .. } => "internal",
+        }
+    }
+
+    /// JSON-RPC error code a host should use when surfacing this error
+    pub fn rpc_code(&self) -> i32 {
+        match self {
+            Self::InvalidArgument { .. } => -32602,
+            Self::NotFound { .. } => -32002,
+            Self::PermissionDenied { .. } => -32001,
+            Self::Internal { .. } => -32603,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::InvalidArgument { message, .. }
+            | Self::NotFound { message }
+            | Self::PermissionDenied { message }
+            | Self::Internal { message } => message,
+        }
+    }
+
+    fn annotations(&self) -> Value {
+        let mut error = serde_json::json!({
+            "code": self.code(),
+            "rpcCode": self.rpc_code(),
+        });
+        if let Self::InvalidArgument { field, .. } = self {
+            error["field"] = field.clone().into();
+        }
+        serde_json::json!({ "error": error })
+    }
+}
+
+impl std::fmt::Display for ToolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for ToolError {}
+
+impl From<String> for ToolError {
+    fn from(message: String) -> Self {
+        Self::Internal { message }
+    }
+}
+
+impl From<&str> for ToolError {
+    fn from(message: &str) -> Self {
+        Self::internal(message)
+    }
+}
+
+impl From<&String> for ToolError {
+    fn from(message: &String) -> Self {
+        Self::internal(message.as_str())
+    }
+}
+
+impl From<Error> for ToolError {
+    /// Keeps a `ToolError` that was raised with `?`; anything else is `Internal`
+    fn from(error: Error) -> Self {
+        error
+            .downcast::<ToolError>()
+            .unwrap_or_else(|error| Self::internal(error.to_string()))
+    }
+}
+
+/// Return values a `#[mcp_tool]` function may produce
+///
+/// `Err` values become error results carrying their `ToolError` code, the same
+/// as `ContentBuilder::error`.
+pub trait IntoToolResult {
+    fn into_tool_result(self) -> Result<CallToolResult, Error>;
+}
+
+impl IntoToolResult for CallToolResult {
+    fn into_tool_result(self) -> Result<CallToolResult, Error> {
+        Ok(self)
+    }
+}
+
+impl IntoToolResult for ResultBuilder {
+    fn into_tool_result(self) -> Result<CallToolResult, Error> {
+        Ok(self.build())
+    }
+}
+
+impl IntoToolResult for String {
+    fn into_tool_result(self) -> Result<CallToolResult, Error> {
+        Ok(ContentBuilder::text(self))
+    }
+}
+
+impl IntoToolResult for Value {
+    /// Pretty-printed JSON text
+    fn into_tool_result(self) -> Result<CallToolResult, Error> {
+        Ok(ContentBuilder::text(serde_json::to_string_pretty(&self)?))
+    }
+}
+
+impl<T: IntoToolResult, E: Into<ToolError>> IntoToolResult for Result<T, E> {
+    fn into_tool_result(self) -> Result<CallToolResult, Error> {
+        match self {
+            Ok(output) => output.into_tool_result(),
+            Err(error) => Ok(ContentBuilder::error(error)),
+        }
+    }
+}
+
 /// Content builder for responses
 pub struct ContentBuilder;
 
 impl ContentBuilder {
+    /// Start a response with several content items
+    ///
+    /// ```
+    /// # use sweetmcp_plugin_builder::prelude::*;
+    /// let result = ContentBuilder::new()
+    ///     .text("Fetched https://example.com")
+    ///     .image("iVBORw0KGgo=", "image/png")
+    ///     .json(serde_json::json!({"status": 200}))
+    ///     .build();
+    /// assert_eq!(result.content.len(), 3);
+    /// ```
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> ResultBuilder {
+        ResultBuilder::default()
+    }
+
     /// Successful text response
     pub fn text(content: impl Into<String>) -> CallToolResult {
         CallToolResult {
             is_error: Some(false),
-            content: vec![Content {
-                r#type: ContentType::Text,
-                text: Some(content.into()),
-                mime_type: Some("text/plain".into()),
-                data: None,
-                annotations: None,
-            }],
+            content: vec![Content::text(content)],
         }
     }
 
-    /// Error response
-    pub fn error(message: impl Into<String>) -> CallToolResult {
+    /// Error response; pass a `ToolError` to include a machine-readable code
+    pub fn error(error: impl Into<ToolError>) -> CallToolResult {
+        let error = error.into();
+        let mut content = Content::text(error.to_string());
+        content.annotations = Some(error.annotations());
         CallToolResult {
             is_error: Some(true),
-            content: vec![Content {
-                r#type: ContentType::Text,
-                text: Some(message.into()),
-                mime_type: Some("text/plain".into()),
-                data: None,
-                annotations: None,
-            }],
+            content: vec![content],
         }
     }
 
@@ -414,13 +1590,76 @@ impl ContentBuilder {
     pub fn data(data: impl Into<String>, mime_type: impl Into<String>) -> CallToolResult {
         CallToolResult {
             is_error: Some(false),
-            content: vec![Content {
-                r#type: ContentType::Image,
-                text: None,
-                data: Some(data.into()),
-                mime_type: Some(mime_type.into()),
-                annotations: None,
-            }],
+            content: vec![Content::image(data, mime_type)],
+        }
+    }
+}
+
+/// Response with several content items, started by `ContentBuilder::new()`
+#[derive(Debug, Clone, Default)]
+pub struct ResultBuilder {
+    content: Vec<Content>,
+    is_error: bool,
+}
+
+impl ResultBuilder {
+    /// Add plain text
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.content.push(Content::text(text));
+        self
+    }
+
+    /// Add a base64-encoded image
+    pub fn image(mut self, data: impl Into<String>, mime_type: impl Into<String>) -> Self {
+        self.content.push(Content::image(data, mime_type));
+        self
+    }
+
+    /// Add pretty-printed JSON as `application/json` text
+    pub fn json(mut self, value: Value) -> Self {
+        let text = serde_json::to_string_pretty(&value).unwrap_or_else(|_| value.to_string());
+        self.content
+            .push(Content::typed_text(text, "application/json"));
+        self
+    }
+
+    /// Add a link to a resource, e.g. one the plugin registered with `.resource()`
+    pub fn resource_link(mut self, uri: impl Into<String>, name: impl Into<String>) -> Self {
+        self.content.push(Content::resource_link(uri, name));
+        self
+    }
+
+    /// Set the MIME type of the last item added
+    pub fn mime_type(mut self, mime_type: impl Into<String>) -> Self {
+        if let Some(content) = self.content.last_mut() {
+            content.mime_type = Some(mime_type.into());
+        }
+        self
+    }
+
+    /// Merge MCP annotations such as `{"audience": ["user"], "priority": 0.9}`
+    /// into the last item added
+    pub fn annotate(mut self, annotations: Value) -> Self {
+        let Some(content) = self.content.last_mut() else {
+            return self;
+        };
+        match (&mut content.annotations, annotations) {
+            (Some(Value::Object(existing)), Value::Object(new)) => existing.extend(new),
+            (slot, annotations) => *slot = Some(annotations),
+        }
+        self
+    }
+
+    /// Mark the response as a tool error
+    pub fn is_error(mut self, is_error: bool) -> Self {
+        self.is_error = is_error;
+        self
+    }
+
+    pub fn build(self) -> CallToolResult {
+        CallToolResult {
+            is_error: Some(self.is_error),
+            content: self.content,
         }
     }
 }
@@ -463,6 +1702,76 @@ macro_rules! generate_mcp_functions {
                 }
             }
         }
+
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn list_resources() -> i32 {
+            let result = $plugin_fn().list_resources();
+            match result.and_then(|x| ::extism_pdk::output(::extism_pdk::Json(x))) {
+                Ok(()) => 0,
+                Err(e) => {
+                    let err = format!("{:?}", e);
+                    if let Ok(mem) = ::extism_pdk::Memory::from_bytes(err.as_bytes()) {
+                        unsafe {
+                            ::extism_pdk::extism::error_set(mem.offset());
+                        }
+                    }
+                    -1
+                }
+            }
+        }
+
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn read_resource() -> i32 {
+            let input: $crate::ReadResourceRequest = $crate::try_input_json!();
+            let result = $plugin_fn().read_resource(input);
+            match result.and_then(|x| ::extism_pdk::output(::extism_pdk::Json(x))) {
+                Ok(()) => 0,
+                Err(e) => {
+                    let err = format!("{:?}", e);
+                    if let Ok(mem) = ::extism_pdk::Memory::from_bytes(err.as_bytes()) {
+                        unsafe {
+                            ::extism_pdk::extism::error_set(mem.offset());
+                        }
+                    }
+                    -1
+                }
+            }
+        }
+
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn mcp_list_prompts() -> i32 {
+            let result = $plugin_fn().list_prompts();
+            match result.and_then(|x| ::extism_pdk::output(::extism_pdk::Json(x))) {
+                Ok(()) => 0,
+                Err(e) => {
+                    let err = format!("{:?}", e);
+                    if let Ok(mem) = ::extism_pdk::Memory::from_bytes(err.as_bytes()) {
+                        unsafe {
+                            ::extism_pdk::extism::error_set(mem.offset());
+                        }
+                    }
+                    -1
+                }
+            }
+        }
+
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn mcp_get_prompt_template() -> i32 {
+            let input: $crate::GetPromptTemplateRequest = $crate::try_input_json!();
+            let result = $plugin_fn().get_prompt_template(input);
+            match result.and_then(::extism_pdk::output) {
+                Ok(()) => 0,
+                Err(e) => {
+                    let err = format!("{:?}", e);
+                    if let Ok(mem) = ::extism_pdk::Memory::from_bytes(err.as_bytes()) {
+                        unsafe {
+                            ::extism_pdk::extism::error_set(mem.offset());
+                        }
+                    }
+                    -1
+                }
+            }
+        }
     };
 }
 
@@ -493,12 +1802,14 @@ mod tests {
 
     impl McpTool for TestTool {
         const NAME: &'static str = "test";
+        type Args = Value;
 
         fn description(builder: DescriptionBuilder) -> DescriptionBuilder {
             builder
                 .does("Test tool functionality")
                 .when("Running tests")
                 .perfect_for("testing")
+                .example(serde_json::json!({"input": "hello"}), "Echo a greeting")
         }
 
         fn schema(builder: SchemaBuilder) -> Value {
@@ -510,6 +1821,459 @@ mod tests {
         }
     }
 
+    struct ShadowTool;
+
+    impl McpTool for ShadowTool {
+        const NAME: &'static str = "test";
+        type Args = Value;
+
+        fn description(builder: DescriptionBuilder) -> DescriptionBuilder {
+            builder.does("Shadow the test tool")
+        }
+
+        fn schema(builder: SchemaBuilder) -> Value {
+            builder.build()
+        }
+
+        fn execute(_args: Value) -> Result<CallToolResult, Error> {
+            Ok(ContentBuilder::text("Shadow result"))
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "tool name 'test' is registered by both")]
+    fn test_duplicate_tool_name_panics() {
+        let _ = mcp_plugin("test-plugin")
+            .description("A test plugin")
+            .tool::<TestTool>()
+            .tool::<ShadowTool>();
+    }
+
+    #[derive(Deserialize, JsonSchema)]
+    struct GreetArgs {
+        /// Who to greet
+        name: String,
+        #[serde(default)]
+        shout: bool,
+    }
+
+    struct GreetTool;
+
+    impl McpTool for GreetTool {
+        const NAME: &'static str = "greet";
+        type Args = GreetArgs;
+
+        fn description(builder: DescriptionBuilder) -> DescriptionBuilder {
+            builder.does("Greet someone")
+        }
+
+        fn execute_typed(args: GreetArgs) -> Result<CallToolResult, Error> {
+            let greeting = format!("Hello, {}", args.name);
+            Ok(ContentBuilder::text(if args.shout {
+                greeting.to_uppercase()
+            } else {
+                greeting
+            }))
+        }
+    }
+
+    #[test]
+    fn test_typed_tool_schema_and_parsing() {
+        let schema = GreetTool::schema(SchemaBuilder::default());
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["properties"]["name"]["type"], "string");
+        assert_eq!(schema["properties"]["name"]["description"], "Who to greet");
+        assert_eq!(schema["required"], serde_json::json!(["name"]));
+
+        let ok = GreetTool::execute(serde_json::json!({"name": "Ada", "shout": true})).unwrap();
+        assert_eq!(ok.content[0].text.as_deref(), Some("HELLO, ADA"));
+
+        let bad = GreetTool::execute(serde_json::json!({"shout": true})).unwrap();
+        assert_eq!(bad.is_error, Some(true));
+        let annotations = bad.content[0].annotations.as_ref().unwrap();
+        assert_eq!(annotations["error"]["code"], "invalid_argument");
+        assert_eq!(annotations["error"]["rpcCode"], -32602);
+        assert_eq!(annotations["error"]["field"], "arguments");
+
+        let plain = ContentBuilder::error("boom");
+        assert_eq!(plain.content[0].text.as_deref(), Some("boom"));
+        assert_eq!(
+            plain.content[0].annotations.as_ref().unwrap()["error"]["code"],
+            "internal"
+        );
+    }
+
+    #[test]
+    fn test_multi_content_result() {
+        let result = ContentBuilder::new()
+            .text("Fetched")
+            .annotate(serde_json::json!({"audience": ["user"]}))
+            .annotate(serde_json::json!({"priority": 0.5}))
+            .image("aGk=", "image/png")
+            .json(serde_json::json!({"status": 200}))
+            .resource_link("notes://1", "Note 1")
+            .mime_type("text/markdown")
+            .build();
+        assert_eq!(result.is_error, Some(false));
+
+        let json = serde_json::to_value(&result).unwrap();
+        let content = json["content"].as_array().unwrap();
+        assert_eq!(content.len(), 4);
+        assert_eq!(
+            content[0]["annotations"],
+            serde_json::json!({"audience": ["user"], "priority": 0.5})
+        );
+        assert_eq!(content[1]["type"], "image");
+        assert_eq!(content[1]["data"], "aGk=");
+        assert_eq!(content[2]["mime_type"], "application/json");
+        assert_eq!(content[2]["text"], "{\n  \"status\": 200\n}");
+        assert_eq!(content[3]["type"], "resource_link");
+        assert_eq!(content[3]["uri"], "notes://1");
+        assert_eq!(content[3]["name"], "Note 1");
+        assert_eq!(content[3]["mime_type"], "text/markdown");
+        assert!(content[0].get("uri").is_none());
+    }
+
+    /// Repeat a word
+    #[mcp_tool(when = "echo a word", read_only, idempotent)]
+    fn repeat_word(
+        /// Word to repeat
+        word: String,
+        #[serde(default)] times: Option<usize>,
+    ) -> Result<String, ToolError> {
+        if word.is_empty() {
+            return Err(ToolError::invalid_argument("word", "must not be empty"));
+        }
+        Ok(word.repeat(times.unwrap_or(2)))
+    }
+
+    #[test]
+    fn test_mcp_tool_attribute() {
+        assert_eq!(RepeatWordTool::NAME, "repeat_word");
+        let schema = RepeatWordTool::schema(SchemaBuilder::default());
+        assert_eq!(
+            schema["properties"]["word"]["description"],
+            "Word to repeat"
+        );
+        assert_eq!(schema["required"], serde_json::json!(["word"]));
+
+        let plugin = mcp_plugin("p")
+            .description("d")
+            .tool::<RepeatWordTool>()
+            .serve();
+        let tool = plugin.describe().unwrap().tools.remove(0);
+        assert!(tool.description.starts_with("Repeat a word"));
+        assert!(tool.description.contains("echo a word"));
+        assert_eq!(
+            tool.annotations,
+            Some(serde_json::json!({"readOnlyHint": true, "idempotentHint": true}))
+        );
+
+        let ok = RepeatWordTool::execute(serde_json::json!({"word": "ab", "times": 3})).unwrap();
+        assert_eq!(ok.content[0].text.as_deref(), Some("ababab"));
+
+        let bad = RepeatWordTool::execute(serde_json::json!({"word": ""})).unwrap();
+        assert_eq!(bad.is_error, Some(true));
+        let error = &bad.content[0].annotations.as_ref().unwrap()["error"];
+        assert_eq!(error["code"], "invalid_argument");
+        assert_eq!(error["field"], "word");
+    }
+
+    #[test]
+    fn test_schema_builder_arrays_objects_and_integers() {
+        let schema = SchemaBuilder::default()
+            .required_array(
+                "paths",
+                "Files to read",
+                serde_json::json!({"type": "string"}),
+            )
+            .required_object("range", "Line range", |s| {
+                s.optional_integer("start", "First line", Some(1), None)
+                    .optional_integer("end", "Last line", Some(1), Some(10_000))
+            })
+            .optional_integer("top_k", "Results", Some(1), Some(50))
+            .optional_map("labels", "Labels", serde_json::json!({"type": "string"}))
+            .default_value("top_k", 5)
+            .default_value("missing", 1)
+            .build();
+
+        assert_eq!(schema["properties"]["paths"]["items"]["type"], "string");
+        let range = &schema["properties"]["range"];
+        assert_eq!(range["type"], "object");
+        assert_eq!(range["description"], "Line range");
+        assert_eq!(range["properties"]["end"]["maximum"], 10_000);
+        assert!(range["properties"]["start"].get("maximum").is_none());
+        assert_eq!(schema["properties"]["top_k"]["default"], 5);
+        let labels = &schema["properties"]["labels"];
+        assert_eq!(labels["type"], "object");
+        assert_eq!(labels["additionalProperties"]["type"], "string");
+        assert!(schema["properties"].get("missing").is_none());
+        assert_eq!(schema["required"], serde_json::json!(["paths", "range"]));
+    }
+
+    struct ListPrompt;
+
+    impl McpPrompt for ListPrompt {
+        const NAME: &'static str = "list_directory";
+
+        fn prompt(builder: PromptBuilder) -> PromptBuilder {
+            builder
+                .description("List a directory")
+                .optional_arg("path", "Directory to list")
+                .template("List the contents of '{{ path | default(\".\") }}'.")
+        }
+    }
+
+    #[test]
+    fn test_prompt_registration() {
+        let plugin = mcp_plugin("test-plugin")
+            .description("A test plugin")
+            .tool::<TestTool>()
+            .prompt::<ListPrompt>()
+            .serve();
+
+        let prompts = plugin.list_prompts().unwrap();
+        assert_eq!(prompts.len(), 1);
+        assert_eq!(prompts[0].id, "list_directory");
+        assert_eq!(prompts[0].arguments[0].required, Some(false));
+
+        let template = plugin
+            .get_prompt_template(GetPromptTemplateRequest {
+                id: "list_directory".into(),
+            })
+            .unwrap();
+        assert!(template.starts_with("List the contents"));
+        assert!(
+            plugin
+                .get_prompt_template(GetPromptTemplateRequest { id: "nope".into() })
+                .is_err()
+        );
+    }
+
+    struct NoteResource;
+
+    impl McpResource for NoteResource {
+        const URI: &'static str = "notes://{folder}/{id}.md";
+        const NAME: &'static str = "note";
+        const MIME_TYPE: &'static str = "text/markdown";
+
+        fn read(uri: &str, params: &HashMap<String, String>) -> Result<ResourceContents, Error> {
+            Ok(ResourceContents::text(
+                uri,
+                format!("# {} in {}", params["id"], params["folder"]),
+            ))
+        }
+    }
+
+    struct IndexResource;
+
+    impl McpResource for IndexResource {
+        const URI: &'static str = "notes://index";
+        const NAME: &'static str = "index";
+
+        fn read(uri: &str, _params: &HashMap<String, String>) -> Result<ResourceContents, Error> {
+            Ok(ResourceContents::text(uri, "all notes"))
+        }
+    }
+
+    #[test]
+    fn test_uri_template_matching() {
+        let params = match_uri_template("notes://{folder}/{id}.md", "notes://work/42.md").unwrap();
+        assert_eq!(params["folder"], "work");
+        assert_eq!(params["id"], "42");
+        assert!(match_uri_template("notes://{folder}/{id}.md", "notes://work/42.txt").is_none());
+        assert!(match_uri_template("notes://{id}", "notes://").is_none());
+        assert!(match_uri_template("files://{path}", "files://a/b/c").is_some());
+    }
+
+    #[test]
+    fn test_resource_registration() {
+        let plugin = mcp_plugin("test-plugin")
+            .description("A test plugin")
+            .resource::<NoteResource>()
+            .resource::<IndexResource>()
+            .serve();
+
+        let listed = plugin.list_resources().unwrap();
+        assert_eq!(listed.resources.len(), 1);
+        assert_eq!(listed.resource_templates.len(), 1);
+
+        let read = |uri: &str| {
+            plugin.read_resource(ReadResourceRequest {
+                uri: uri.to_string(),
+            })
+        };
+        let note = read("notes://work/42.md").unwrap().content;
+        assert_eq!(note.text.as_deref(), Some("# 42 in work"));
+        assert_eq!(note.mime_type.as_deref(), Some("text/markdown"));
+        assert_eq!(
+            read("notes://index").unwrap().content.mime_type.as_deref(),
+            Some("text/plain")
+        );
+        assert!(read("notes://missing").is_err());
+    }
+
+    struct ProgressTokenTool;
+
+    impl McpTool for ProgressTokenTool {
+        const NAME: &'static str = "progress_token";
+        type Args = Value;
+
+        fn description(builder: DescriptionBuilder) -> DescriptionBuilder {
+            builder.does("Echo the progress token")
+        }
+
+        fn execute(_args: Value) -> Result<CallToolResult, Error> {
+            Ok(ContentBuilder::text(
+                Progress::current()
+                    .token()
+                    .map(Value::to_string)
+                    .unwrap_or_default(),
+            ))
+        }
+    }
+
+    #[test]
+    fn test_progress_token_scoped_to_call() {
+        let plugin = mcp_plugin("test-plugin")
+            .description("A test plugin")
+            .tool::<ProgressTokenTool>()
+            .serve();
+
+        let request: CallToolRequest = serde_json::from_value(serde_json::json!({
+            "params": {
+                "name": "progress_token",
+                "arguments": {},
+                "_meta": { "progressToken": 7 }
+            }
+        }))
+        .unwrap();
+        let result = plugin.call(request).unwrap();
+        assert_eq!(result.content[0].text.as_deref(), Some("7"));
+        assert!(Progress::current().token().is_none());
+    }
+
+    struct DeadlineTool;
+
+    impl McpTool for DeadlineTool {
+        const NAME: &'static str = "deadline";
+        type Args = Value;
+
+        fn description(builder: DescriptionBuilder) -> DescriptionBuilder {
+            builder.does("Report whether a slow stage fits the budget")
+        }
+
+        fn execute(_args: Value) -> Result<CallToolResult, Error> {
+            let deadline = Deadline::current();
+            deadline.check()?;
+            Ok(ContentBuilder::text(
+                deadline.allows(Duration::from_secs(10)).to_string(),
+            ))
+        }
+    }
+
+    #[test]
+    fn test_deadline_from_meta() {
+        let plugin = mcp_plugin("test-plugin")
+            .description("A test plugin")
+            .tool::<DeadlineTool>()
+            .serve();
+        let call = |meta: Value| {
+            let request = serde_json::from_value(serde_json::json!({
+                "params": { "name": "deadline", "arguments": {}, "_meta": meta }
+            }))
+            .unwrap();
+            plugin.call(request).unwrap().content[0].text.clone()
+        };
+
+        assert_eq!(call(serde_json::json!({})).as_deref(), Some("true"));
+        assert_eq!(
+            call(serde_json::json!({ "sweetmcp/timeoutMs": 60_000 })).as_deref(),
+            Some("true")
+        );
+        assert_eq!(
+            call(serde_json::json!({ "sweetmcp/timeoutMs": 500 })).as_deref(),
+            Some("false")
+        );
+        assert!(Deadline::current().remaining().is_none());
+    }
+
+    #[test]
+    fn test_validation_and_middleware() {
+        use std::sync::{Arc, Mutex};
+
+        let audit = Arc::new(Mutex::new(Vec::new()));
+        let log = audit.clone();
+        let plugin = mcp_plugin("test-plugin")
+            .description("A test plugin")
+            .tool::<GreetTool>()
+            .validate(|args| match args["name"].as_str() {
+                Some(name) if name.len() > 8 => {
+                    Err(ToolError::invalid_argument("name", "at most 8 characters"))
+                }
+                _ => Ok(()),
+            })
+            .before_call(|call| {
+                if let Some(name) = call.arguments["name"].as_str() {
+                    call.arguments["name"] = name.trim().into();
+                }
+                Ok(())
+            })
+            .before_call(|call| match call.arguments["name"].as_str() {
+                Some("root") => Err(ToolError::permission_denied("not allowed")),
+                _ => Ok(()),
+            })
+            .after_call(move |call, outcome| {
+                let is_error = outcome.as_ref().map_or(true, |r| r.is_error == Some(true));
+                log.lock().unwrap().push(format!(
+                    "{} {} {}",
+                    call.name, call.arguments["name"], is_error
+                ));
+            })
+            .serve();
+        let call = |name: &str| {
+            plugin
+                .call(
+                    serde_json::from_value(serde_json::json!({
+                        "params": { "name": "greet", "arguments": { "name": name } }
+                    }))
+                    .unwrap(),
+                )
+                .unwrap()
+        };
+
+        assert_eq!(
+            call("  Ada ").content[0].text.as_deref(),
+            Some("Hello, Ada")
+        );
+        let denied = call("root");
+        assert_eq!(
+            denied.content[0].annotations.as_ref().unwrap()["error"]["code"],
+            "permission_denied"
+        );
+        let too_long = call("  Bartholomew  ");
+        assert_eq!(
+            too_long.content[0].annotations.as_ref().unwrap()["error"]["field"],
+            "name"
+        );
+        assert_eq!(
+            *audit.lock().unwrap(),
+            [
+                "greet \"Ada\" false",
+                "greet \"root\" true",
+                "greet \"Bartholomew\" true"
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "validate() must follow the tool()")]
+    fn test_validate_without_tool_panics() {
+        let _ = mcp_plugin("test-plugin")
+            .description("A test plugin")
+            .validate(|_| Ok(()));
+    }
+
     #[test]
     fn test_fluent_plugin_builder() {
         let plugin = mcp_plugin("test-plugin")
@@ -520,5 +2284,38 @@ mod tests {
         let tools = plugin.describe().unwrap();
         assert_eq!(tools.tools.len(), 1);
         assert_eq!(tools.tools[0].name, "test");
+        assert_eq!(tools.tools[0].examples.len(), 1);
+        assert_eq!(tools.tools[0].examples[0].arguments["input"], "hello");
+        assert!(!tools.tools[0].description.contains("hello"));
+        assert_eq!(
+            tools.plugin,
+            Some(PluginInfo {
+                name: "test-plugin".to_string(),
+                version: None,
+                capabilities: Vec::new(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_version_and_capabilities() {
+        let plugin = mcp_plugin("test-plugin")
+            .description("A test plugin")
+            .version("1.2.3")
+            .capability("streaming")
+            .capability("stateful")
+            .capability("streaming")
+            .tool::<TestTool>()
+            .serve();
+
+        let json = serde_json::to_value(plugin.describe().unwrap()).unwrap();
+        assert_eq!(
+            json["plugin"],
+            serde_json::json!({
+                "name": "test-plugin",
+                "version": "1.2.3",
+                "capabilities": ["streaming", "stateful"]
+            })
+        );
     }
 }
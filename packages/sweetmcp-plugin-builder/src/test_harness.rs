@@ -0,0 +1,429 @@
+//! Conformance checks for a finished plugin
+//!
+//! Meant for a plugin's own test suite:
+//!
+//! ```ignore
+//! #[cfg(test)]
+//! mod tests {
+//!     #[test]
+//!     fn conformance() {
+//!         sweetmcp_plugin_builder::assert_plugin_conformance!(super::plugin());
+//!     }
+//! }
+//! ```
+//!
+//! Every registered tool must have a non-empty name and description and a
+//! well-formed draft-07 object schema without `$ref`s, its examples must supply
+//! every required argument, and calling it with a required argument missing
+//! must fail cleanly (an error result or `Err`) instead of succeeding or
+//! panicking.
+
+use serde_json::{Map, Value};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+use crate::{CallToolParams, CallToolRequest, McpPlugin, Ready, ToolDescription};
+
+const TYPES: [&str; 7] = [
+    "array", "boolean", "integer", "null", "number", "object", "string",
+];
+
+/// Keywords from later drafts that draft-07 validators silently ignore
+const NEWER_KEYWORDS: [&str; 7] = [
+    "$defs",
+    "$anchor",
+    "prefixItems",
+    "dependentRequired",
+    "dependentSchemas",
+    "unevaluatedItems",
+    "unevaluatedProperties",
+];
+
+/// Panic listing every conformance problem in `plugin`
+pub fn assert_conformance(plugin: &McpPlugin<Ready>) {
+    let problems = check_conformance(plugin);
+    if !problems.is_empty() {
+        panic!(
+            "plugin '{}' is not conformant:\n  - {}",
+            plugin.name.as_deref().unwrap_or("<unnamed>"),
+            problems.join("\n  - ")
+        );
+    }
+}
+
+/// Every conformance problem in `plugin`, one line each
+pub fn check_conformance(plugin: &McpPlugin<Ready>) -> Vec<String> {
+    let mut problems = Vec::new();
+    if plugin.description.as_deref().is_none_or(blank) {
+        problems.push("plugin description is empty".to_string());
+    }
+    let tools = match plugin.describe() {
+        Ok(list) => list.tools,
+        Err(e) => {
+            problems.push(format!("describe failed: {}", e));
+            return problems;
+        }
+    };
+    for tool in &tools {
+        check_tool(plugin, tool, &mut problems);
+    }
+    problems
+}
+
+fn check_tool(plugin: &McpPlugin<Ready>, tool: &ToolDescription, problems: &mut Vec<String>) {
+    let mut report = |problem: String| problems.push(format!("tool '{}': {}", tool.name, problem));
+
+    if tool.name.is_empty()
+        || !tool
+            .name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        report("name must be non-empty [A-Za-z0-9_-]".to_string());
+    }
+    if blank(&tool.description) {
+        report("description is empty".to_string());
+    }
+
+    let schema = &tool.input_schema;
+    let mut schema_problems = Vec::new();
+    check_schema("inputSchema", schema, &mut schema_problems);
+    if schema["type"] != "object" {
+        schema_problems.push("inputSchema: root type must be \"object\"".to_string());
+    }
+    let required: Vec<&str> = schema["required"]
+        .as_array()
+        .map(|names| names.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    for name in &required {
+        if schema["properties"].get(name).is_none() {
+            schema_problems.push(format!(
+                "inputSchema: required argument '{}' is not in properties",
+                name
+            ));
+        }
+    }
+    let schema_ok = schema_problems.is_empty();
+    schema_problems.into_iter().for_each(&mut report);
+    if !schema_ok || required.is_empty() {
+        return;
+    }
+
+    for example in &tool.examples {
+        for name in &required {
+            if example.arguments.get(name).is_none() {
+                report(format!(
+                    "example '{}' is missing required argument '{}'",
+                    example.description, name
+                ));
+            }
+        }
+    }
+
+    // All required arguments missing, then each one missing from every
+    // example that is otherwise complete
+    let mut cases = vec![("all required arguments".to_string(), Map::new())];
+    for example in &tool.examples {
+        let Some(arguments) = example.arguments.as_object() else {
+            continue;
+        };
+        for name in &required {
+            if arguments.contains_key(*name) {
+                let mut arguments = arguments.clone();
+                arguments.remove(*name);
+                cases.push((format!("'{}'", name), arguments));
+            }
+        }
+    }
+    for (missing, arguments) in cases {
+        let request = CallToolRequest {
+            params: CallToolParams {
+                name: tool.name.clone(),
+                arguments: Some(arguments),
+                meta: None,
+            },
+        };
+        match catch_unwind(AssertUnwindSafe(|| plugin.call(request))) {
+            Ok(Ok(result)) if result.is_error != Some(true) => {
+                report(format!("succeeded without {}", missing));
+            }
+            Ok(_) => {}
+            Err(_) => report(format!("panicked without {}", missing)),
+        }
+    }
+}
+
+fn blank(text: &str) -> bool {
+    text.trim().is_empty()
+}
+
+/// Check the draft-07 keywords of `schema` hold values of the right shape
+fn check_schema(path: &str, schema: &Value, problems: &mut Vec<String>) {
+    let keywords = match schema {
+        // `true` and `false` are valid schemas since draft-06
+        Value::Bool(_) => return,
+        Value::Object(keywords) => keywords,
+        _ => {
+            problems.push(format!("{}: schema must be an object or boolean", path));
+            return;
+        }
+    };
+    for (keyword, value) in keywords {
+        let at = format!("{}.{}", path, keyword);
+        let ok = match keyword.as_str() {
+            "type" => match value {
+                Value::String(name) => TYPES.contains(&name.as_str()),
+                Value::Array(names) => {
+                    !names.is_empty()
+                        && names
+                            .iter()
+                            .all(|name| name.as_str().is_some_and(|name| TYPES.contains(&name)))
+                        && unique(names)
+                }
+                _ => false,
+            },
+            "properties" | "patternProperties" | "definitions" => match value {
+                Value::Object(schemas) => {
+                    for (name, sub) in schemas {
+                        check_schema(&format!("{}.{}", at, name), sub, problems);
+                    }
+                    true
+                }
+                _ => false,
+            },
+            "required" => value
+                .as_array()
+                .is_some_and(|names| names.iter().all(Value::is_string) && unique(names)),
+            "enum" => value.as_array().is_some_and(|values| !values.is_empty()),
+            "items" => {
+                if let Value::Array(schemas) = value {
+                    for (index, sub) in schemas.iter().enumerate() {
+                        check_schema(&format!("{}[{}]", at, index), sub, problems);
+                    }
+                } else {
+                    check_schema(&at, value, problems);
+                }
+                true
+            }
+            "additionalProperties"
+            | "additionalItems"
+            | "contains"
+            | "propertyNames"
+            | "not"
+            | "if"
+            | "then"
+            | "else" => {
+                check_schema(&at, value, problems);
+                true
+            }
+            "allOf" | "anyOf" | "oneOf" => match value {
+                Value::Array(schemas) if !schemas.is_empty() => {
+                    for (index, sub) in schemas.iter().enumerate() {
+                        check_schema(&format!("{}[{}]", at, index), sub, problems);
+                    }
+                    true
+                }
+                _ => false,
+            },
+            "minLength" | "maxLength" | "minItems" | "maxItems" | "minProperties"
+            | "maxProperties" => value.is_u64(),
+            "minimum" | "maximum" | "exclusiveMinimum" | "exclusiveMaximum" => value.is_number(),
+            "multipleOf" => value.as_f64().is_some_and(|n| n > 0.0),
+            "description" => value.as_str().is_some_and(|text| !blank(text)),
+            "title" | "format" | "pattern" | "$comment" | "contentEncoding"
+            | "contentMediaType" => value.is_string(),
+            "uniqueItems" | "readOnly" | "writeOnly" => value.is_boolean(),
+            "examples" => value.is_array(),
+            "$ref" => {
+                problems.push(format!(
+                    "{}: unresolved $ref; MCP clients expect inlined subschemas",
+                    at
+                ));
+                true
+            }
+            other if NEWER_KEYWORDS.contains(&other) => {
+                problems.push(format!("{}: not a draft-07 keyword", at));
+                true
+            }
+            // Unknown keywords are allowed and ignored
+            _ => true,
+        };
+        if !ok {
+            problems.push(format!("{}: invalid value {}", at, value));
+        }
+    }
+}
+
+fn unique(values: &[Value]) -> bool {
+    values
+        .iter()
+        .enumerate()
+        .all(|(index, value)| !values[..index].contains(value))
+}
+
+/// Assert that a finished plugin passes [`check_conformance`], e.g.
+/// `assert_plugin_conformance!(plugin())` in a `#[test]`
+#[macro_export]
+macro_rules! assert_plugin_conformance {
+    ($plugin:expr) => {
+        $crate::test_harness::assert_conformance(&$plugin)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        CallToolResult, ContentBuilder, DescriptionBuilder, Error, McpTool, SchemaBuilder,
+        mcp_plugin,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn schema_keywords_are_checked() {
+        let mut problems = Vec::new();
+        check_schema(
+            "s",
+            &json!({
+                "type": "object",
+                "properties": {
+                    "n": { "type": ["integer", "null"], "minimum": 0 },
+                    "tags": { "type": "array", "items": { "type": "string" } },
+                    "mode": { "enum": ["a", "b"] },
+                    "any": true
+                },
+                "required": ["n"]
+            }),
+            &mut problems,
+        );
+        assert!(problems.is_empty(), "{:?}", problems);
+
+        check_schema(
+            "s",
+            &json!({
+                "type": "obj",
+                "properties": {
+                    "a": { "$ref": "#/definitions/A" },
+                    "b": { "description": " ", "minLength": -1 }
+                },
+                "required": ["a", "a"],
+                "$defs": {}
+            }),
+            &mut problems,
+        );
+        assert_eq!(
+            problems,
+            [
+                "s.$defs: not a draft-07 keyword",
+                "s.properties.a.$ref: unresolved $ref; MCP clients expect inlined subschemas",
+                "s.properties.b.description: invalid value \" \"",
+                "s.properties.b.minLength: invalid value -1",
+                "s.required: invalid value [\"a\",\"a\"]",
+                "s.type: invalid value \"obj\"",
+            ]
+        );
+    }
+
+    struct Lenient;
+
+    impl McpTool for Lenient {
+        const NAME: &'static str = "lenient";
+        type Args = Value;
+
+        fn description(builder: DescriptionBuilder) -> DescriptionBuilder {
+            builder
+                .does("Ignore its arguments")
+                .example(json!({ "path": "/tmp" }), "Any path")
+        }
+
+        fn schema(builder: SchemaBuilder) -> Value {
+            builder
+                .required_string("path", "Path")
+                .required_string("mode", "Mode")
+                .build()
+        }
+
+        fn execute(_args: Value) -> Result<CallToolResult, Error> {
+            Ok(ContentBuilder::text("ok"))
+        }
+    }
+
+    struct Panicky;
+
+    impl McpTool for Panicky {
+        const NAME: &'static str = "panicky";
+        type Args = Value;
+
+        fn description(builder: DescriptionBuilder) -> DescriptionBuilder {
+            builder.does("Unwrap its argument")
+        }
+
+        fn schema(builder: SchemaBuilder) -> Value {
+            builder.required_string("path", "Path").build()
+        }
+
+        fn execute(args: Value) -> Result<CallToolResult, Error> {
+            Ok(ContentBuilder::text(args["path"].as_str().unwrap()))
+        }
+    }
+
+    #[derive(serde::Deserialize, schemars::JsonSchema)]
+    struct EchoArgs {
+        /// Text to echo
+        text: String,
+    }
+
+    struct Echo;
+
+    impl McpTool for Echo {
+        const NAME: &'static str = "echo";
+        type Args = EchoArgs;
+
+        fn description(builder: DescriptionBuilder) -> DescriptionBuilder {
+            builder
+                .does("Echo text back")
+                .example(json!({ "text": "hi" }), "Echo a greeting")
+        }
+
+        fn execute_typed(args: EchoArgs) -> Result<CallToolResult, Error> {
+            Ok(ContentBuilder::text(args.text))
+        }
+    }
+
+    #[test]
+    fn typed_tools_conform() {
+        let plugin = mcp_plugin("conformant")
+            .description("Echoes")
+            .tool::<Echo>()
+            .serve();
+        assert_plugin_conformance!(plugin);
+    }
+
+    #[test]
+    fn reports_each_problem() {
+        let plugin = mcp_plugin("broken")
+            .description("Misbehaves")
+            .tool::<Lenient>()
+            .tool::<Panicky>()
+            .serve();
+        assert_eq!(
+            check_conformance(&plugin),
+            [
+                "tool 'lenient': example 'Any path' is missing required argument 'mode'",
+                "tool 'lenient': succeeded without all required arguments",
+                "tool 'lenient': succeeded without 'path'",
+                "tool 'panicky': panicked without all required arguments",
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "plugin 'broken' is not conformant")]
+    fn macro_panics_on_problems() {
+        assert_plugin_conformance!(
+            mcp_plugin("broken")
+                .description("Misbehaves")
+                .tool::<Panicky>()
+                .serve()
+        );
+    }
+}
@@ -0,0 +1,122 @@
+//! Golden test case files.
+//!
+//! Each `*.json` file in a plugin's `tests/golden/` directory holds one case
+//! or an array of cases:
+//!
+//! ```json
+//! {
+//!   "name": "sha256 of hello",
+//!   "tool": "hash",
+//!   "arguments": { "data": "hello", "algorithm": "sha256" },
+//!   "expect": {
+//!     "content": [{ "type": "text", "text": "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824" }]
+//!   }
+//! }
+//! ```
+//!
+//! `expect` is matched against the plugin's `CallToolResult`; use
+//! `expect_error` instead when the call itself must fail. See
+//! [`crate::matcher`] for the matcher syntax.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// Directory under a plugin crate that holds its golden cases
+pub const GOLDEN_DIR: &str = "tests/golden";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TestCase {
+    /// Defaults to the file stem (plus index for arrays)
+    #[serde(default)]
+    pub name: String,
+    pub tool: String,
+    #[serde(default)]
+    pub arguments: Map<String, Value>,
+    /// Extism config keys, as the PluginManager would pass them
+    #[serde(default)]
+    pub config: BTreeMap<String, String>,
+    /// Hosts the plugin may reach; empty means no network
+    #[serde(default)]
+    pub allowed_hosts: Vec<String>,
+    /// Per-call timeout, default 30s
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    /// Matcher for the `CallToolResult`
+    #[serde(default)]
+    pub expect: Option<Value>,
+    /// Matcher for the error message when the call must fail
+    #[serde(default)]
+    pub expect_error: Option<Value>,
+    /// Skip the case with this reason (e.g. needs network)
+    #[serde(default)]
+    pub skip: Option<String>,
+    #[serde(skip)]
+    pub file: PathBuf,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CaseFile {
+    Many(Vec<TestCase>),
+    One(Box<TestCase>),
+}
+
+/// Load every `*.json` case in `dir`, sorted by file name.
+pub fn load_dir(dir: &Path) -> Result<Vec<TestCase>> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .with_context(|| format!("read {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    files.sort();
+
+    let mut cases = Vec::new();
+    for file in files {
+        cases.extend(load_file(&file)?);
+    }
+    Ok(cases)
+}
+
+pub fn load_file(file: &Path) -> Result<Vec<TestCase>> {
+    let content =
+        std::fs::read_to_string(file).with_context(|| format!("read {}", file.display()))?;
+    let parsed: CaseFile = serde_json::from_str(&content)
+        .with_context(|| format!("invalid test case file {}", file.display()))?;
+    let stem = file
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let cases = match parsed {
+        CaseFile::One(case) => vec![*case],
+        CaseFile::Many(cases) => cases,
+    };
+    let numbered = cases.len() > 1;
+    cases
+        .into_iter()
+        .enumerate()
+        .map(|(i, mut case)| {
+            if case.name.is_empty() {
+                case.name = if numbered {
+                    format!("{}[{}]", stem, i)
+                } else {
+                    stem.clone()
+                };
+            }
+            if case.expect.is_some() == case.expect_error.is_some() {
+                bail!(
+                    "{}: case '{}' needs exactly one of expect or expect_error",
+                    file.display(),
+                    case.name
+                );
+            }
+            case.file = file.to_path_buf();
+            Ok(case)
+        })
+        .collect()
+}
@@ -0,0 +1,15 @@
+//! Golden-output tests for SweetMCP wasm plugins.
+//!
+//! Loads each built plugin with Extism, replays the JSON cases in its
+//! `tests/golden/` directory and reports where the output diverges, so a
+//! plugin regression fails here instead of inside a running daemon.
+
+pub mod case;
+pub mod matcher;
+pub mod report;
+pub mod runner;
+
+pub use case::{GOLDEN_DIR, TestCase, load_dir, load_file};
+pub use matcher::{Mismatch, check};
+pub use report::Summary;
+pub use runner::{CaseResult, Outcome, Runner, default_wasm_path};
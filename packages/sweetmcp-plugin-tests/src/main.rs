@@ -0,0 +1,114 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anyhow::{Context, Result, bail};
+use clap::Parser;
+use sweetmcp_plugin_tests::{GOLDEN_DIR, Runner, Summary, default_wasm_path, load_dir, report};
+
+/// Run golden-output tests against built SweetMCP wasm plugins
+#[derive(Parser, Debug)]
+#[command(version)]
+struct Args {
+    /// Plugins to test (default: every plugin with a tests/golden directory)
+    plugins: Vec<String>,
+
+    /// Directory containing the plugin crates
+    #[arg(long, default_value = "sweetmcp-plugins")]
+    plugins_dir: PathBuf,
+
+    /// Cargo target directory holding wasm32-wasip1/release/*.wasm
+    #[arg(long, env = "CARGO_TARGET_DIR", default_value = "target")]
+    target_dir: PathBuf,
+
+    /// Explicit wasm file (only with a single plugin)
+    #[arg(long)]
+    wasm: Option<PathBuf>,
+
+    /// Explicit case directory (only with a single plugin)
+    #[arg(long)]
+    cases: Option<PathBuf>,
+
+    /// Only run cases whose name contains this string
+    #[arg(long)]
+    filter: Option<String>,
+
+    /// Emit one JSON object per case instead of text
+    #[arg(long)]
+    json: bool,
+}
+
+fn main() -> ExitCode {
+    match run(Args::parse()) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("error: {:#}", e);
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run(args: Args) -> Result<bool> {
+    let plugins = if args.plugins.is_empty() {
+        discover(&args.plugins_dir)?
+    } else {
+        args.plugins.clone()
+    };
+    if plugins.len() != 1 && (args.wasm.is_some() || args.cases.is_some()) {
+        bail!("--wasm and --cases need exactly one plugin");
+    }
+    if plugins.is_empty() {
+        bail!(
+            "no plugin under {} has a {} directory",
+            args.plugins_dir.display(),
+            GOLDEN_DIR
+        );
+    }
+
+    let mut summary = Summary::default();
+    for plugin in &plugins {
+        let cases_dir = args
+            .cases
+            .clone()
+            .unwrap_or_else(|| args.plugins_dir.join(plugin).join(GOLDEN_DIR));
+        let mut cases = load_dir(&cases_dir)?;
+        if let Some(filter) = &args.filter {
+            cases.retain(|case| case.name.contains(filter.as_str()));
+        }
+        if cases.is_empty() {
+            continue;
+        }
+
+        let wasm = args
+            .wasm
+            .clone()
+            .unwrap_or_else(|| default_wasm_path(&args.target_dir, plugin));
+        let runner = Runner::load(&wasm).with_context(|| format!("plugin {}", plugin))?;
+        for case in &cases {
+            let result = runner.run(case);
+            summary.record(&result);
+            if args.json {
+                println!("{}", report::case_json(plugin, &result));
+            } else {
+                report::print_case(plugin, &result);
+            }
+        }
+    }
+
+    if !args.json {
+        report::print_summary(&summary);
+    }
+    Ok(summary.ok())
+}
+
+/// Plugin directories that ship golden cases, sorted
+fn discover(plugins_dir: &std::path::Path) -> Result<Vec<String>> {
+    let mut plugins: Vec<String> = std::fs::read_dir(plugins_dir)
+        .with_context(|| format!("read {}", plugins_dir.display()))?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().join(GOLDEN_DIR).is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    plugins.sort();
+    Ok(plugins)
+}
@@ -0,0 +1,202 @@
+//! Expected-value matchers.
+//!
+//! Plain JSON is compared structurally: objects match when every listed key
+//! matches (extra keys in the actual value are ignored), arrays must have the
+//! same length and match element by element, scalars must be equal.
+//!
+//! An object with a single `$`-prefixed key is an operator instead:
+//!
+//! | Matcher                 | Passes when the actual value…                  |
+//! |-------------------------|------------------------------------------------|
+//! | `{"$any": true}`        | exists                                         |
+//! | `{"$eq": v}`            | equals `v` exactly, extra keys included        |
+//! | `{"$contains": "s"}`    | is a string containing `s`                     |
+//! | `{"$regex": "re"}`      | is a string matching `re`                      |
+//! | `{"$type": "string"}`   | has that JSON type                             |
+//! | `{"$json": m}`          | is a string that parses as JSON matching `m`   |
+//! | `{"$len": n}`           | is a string, array or object of length `n`     |
+
+use std::fmt;
+
+use regex::Regex;
+use serde_json::Value;
+
+/// One difference between expected and actual output
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    /// JSON path, e.g. `$.content[0].text`
+    pub path: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "at {}: expected {}, got {}",
+            self.path, self.expected, self.actual
+        )
+    }
+}
+
+/// Compare `actual` against `expected`, collecting every mismatch.
+pub fn check(expected: &Value, actual: &Value) -> Vec<Mismatch> {
+    let mut out = Vec::new();
+    check_at("$", expected, Some(actual), &mut out);
+    out
+}
+
+fn check_at(path: &str, expected: &Value, actual: Option<&Value>, out: &mut Vec<Mismatch>) {
+    macro_rules! fail {
+        ($expected:expr) => {
+            out.push(Mismatch {
+                path: path.to_string(),
+                expected: $expected,
+                actual: actual.map_or_else(|| "nothing".to_string(), short),
+            })
+        };
+    }
+
+    if let Some((op, arg)) = operator(expected) {
+        let Some(actual) = actual else {
+            fail!(format!("{} {}", op, short(arg)));
+            return;
+        };
+        match op {
+            "$any" => {}
+            "$eq" => {
+                if actual != arg {
+                    fail!(short(arg));
+                }
+            }
+            "$contains" => {
+                let needle = arg.as_str().unwrap_or_default();
+                if !actual.as_str().is_some_and(|s| s.contains(needle)) {
+                    fail!(format!("a string containing {:?}", needle));
+                }
+            }
+            "$regex" => match Regex::new(arg.as_str().unwrap_or_default()) {
+                Ok(re) => {
+                    if !actual.as_str().is_some_and(|s| re.is_match(s)) {
+                        fail!(format!("a string matching /{}/", re));
+                    }
+                }
+                Err(e) => fail!(format!("a valid $regex ({})", e)),
+            },
+            "$type" => {
+                let want = arg.as_str().unwrap_or_default();
+                if type_name(actual) != want {
+                    fail!(format!("a {}", want));
+                }
+            }
+            "$json" => match actual.as_str().map(serde_json::from_str::<Value>) {
+                Some(Ok(parsed)) => check_at(&format!("{}<json>", path), arg, Some(&parsed), out),
+                _ => fail!("a JSON-encoded string".to_string()),
+            },
+            "$len" => {
+                let len = match actual {
+                    Value::String(s) => Some(s.chars().count()),
+                    Value::Array(a) => Some(a.len()),
+                    Value::Object(o) => Some(o.len()),
+                    _ => None,
+                };
+                if len.map(|n| n as u64) != arg.as_u64() {
+                    fail!(format!("length {}", arg));
+                }
+            }
+            other => fail!(format!("a known matcher (unknown {})", other)),
+        }
+        return;
+    }
+
+    match (expected, actual) {
+        (Value::Object(want), Some(Value::Object(got))) => {
+            for (key, value) in want {
+                check_at(&format!("{}.{}", path, key), value, got.get(key), out);
+            }
+        }
+        (Value::Array(want), Some(Value::Array(got))) => {
+            if want.len() != got.len() {
+                fail!(format!("{} element(s)", want.len()));
+                return;
+            }
+            for (i, (w, g)) in want.iter().zip(got).enumerate() {
+                check_at(&format!("{}[{}]", path, i), w, Some(g), out);
+            }
+        }
+        (want, Some(got)) if want == got => {}
+        (want, _) => fail!(short(want)),
+    }
+}
+
+fn operator(value: &Value) -> Option<(&str, &Value)> {
+    match value {
+        Value::Object(map) if map.len() == 1 => map
+            .iter()
+            .next()
+            .filter(|(key, _)| key.starts_with('$'))
+            .map(|(key, arg)| (key.as_str(), arg)),
+        _ => None,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Compact rendering for reports, truncated so one bad blob can't flood them
+fn short(value: &Value) -> String {
+    const MAX: usize = 200;
+    let text = value.to_string();
+    match text.char_indices().nth(MAX) {
+        Some((cut, _)) => format!("{}… ({} bytes)", &text[..cut], text.len()),
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn structural_match_ignores_extra_keys() {
+        let actual =
+            json!({"content": [{"type": "text", "text": "ok", "mime_type": "text/plain"}]});
+        assert!(check(&json!({"content": [{"text": "ok"}]}), &actual).is_empty());
+
+        let diffs = check(&json!({"content": [{"text": "nope"}]}), &actual);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].path, "$.content[0].text");
+    }
+
+    #[test]
+    fn operators() {
+        let actual = json!({"text": "{\"utc_time\": \"1760000000\"}", "n": [1, 2]});
+        let expected = json!({
+            "text": {"$json": {"utc_time": {"$regex": "^[0-9]+$"}}},
+            "n": {"$len": 2},
+        });
+        assert!(check(&expected, &actual).is_empty());
+
+        let diffs = check(
+            &json!({"text": {"$contains": "missing"}, "gone": {"$any": true}}),
+            &actual,
+        );
+        assert_eq!(diffs.len(), 2);
+        assert!(
+            diffs
+                .iter()
+                .any(|d| d.path == "$.gone" && d.actual == "nothing")
+        );
+        assert!(!check(&json!({"$eq": {"n": [1, 2]}}), &actual).is_empty());
+    }
+}
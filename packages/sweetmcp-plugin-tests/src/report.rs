@@ -0,0 +1,85 @@
+//! Human and JSON reporting of case results.
+
+use serde_json::{Value, json};
+
+use crate::runner::{CaseResult, Outcome};
+
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub passed: usize,
+    pub failed: usize,
+    pub errors: usize,
+    pub skipped: usize,
+}
+
+impl Summary {
+    pub fn record(&mut self, result: &CaseResult) {
+        match result.outcome {
+            Outcome::Passed => self.passed += 1,
+            Outcome::Failed(_) => self.failed += 1,
+            Outcome::Error(_) => self.errors += 1,
+            Outcome::Skipped(_) => self.skipped += 1,
+        }
+    }
+
+    pub fn ok(&self) -> bool {
+        self.failed == 0 && self.errors == 0
+    }
+}
+
+/// One line per case, followed by the diff for failures
+pub fn print_case(plugin: &str, result: &CaseResult) {
+    let label = format!("{}/{}", plugin, result.name);
+    match &result.outcome {
+        Outcome::Passed => println!("ok      {} ({} ms)", label, result.elapsed.as_millis()),
+        Outcome::Skipped(reason) => println!("skip    {} ({})", label, reason),
+        Outcome::Error(message) => {
+            println!("ERROR   {}", label);
+            println!("        {}", message);
+            println!("        in {}", result.file.display());
+        }
+        Outcome::Failed(mismatches) => {
+            println!("FAIL    {}", label);
+            for mismatch in mismatches {
+                println!("        at {}", mismatch.path);
+                println!("          - expected: {}", mismatch.expected);
+                println!("          + actual:   {}", mismatch.actual);
+            }
+            println!("        in {}", result.file.display());
+        }
+    }
+}
+
+pub fn print_summary(summary: &Summary) {
+    println!(
+        "\n{}: {} passed, {} failed, {} errors, {} skipped",
+        if summary.ok() { "ok" } else { "FAILED" },
+        summary.passed,
+        summary.failed,
+        summary.errors,
+        summary.skipped
+    );
+}
+
+pub fn case_json(plugin: &str, result: &CaseResult) -> Value {
+    let (status, detail) = match &result.outcome {
+        Outcome::Passed => ("passed", Value::Null),
+        Outcome::Skipped(reason) => ("skipped", json!(reason)),
+        Outcome::Error(message) => ("error", json!(message)),
+        Outcome::Failed(mismatches) => (
+            "failed",
+            mismatches
+                .iter()
+                .map(|m| json!({"path": m.path, "expected": m.expected, "actual": m.actual}))
+                .collect(),
+        ),
+    };
+    json!({
+        "plugin": plugin,
+        "case": result.name,
+        "file": result.file,
+        "status": status,
+        "detail": detail,
+        "elapsed_ms": result.elapsed.as_millis() as u64,
+    })
+}
@@ -0,0 +1,119 @@
+//! Loads a plugin with Extism and runs cases against it.
+//!
+//! Plugins are invoked exactly like the PluginManager does: the `call` export
+//! receives `{"params": {"name", "arguments"}}` and returns a `CallToolResult`.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use extism::{Manifest, Plugin, Wasm};
+use serde_json::{Value, json};
+
+use crate::case::TestCase;
+use crate::matcher::{self, Mismatch};
+
+const DEFAULT_TIMEOUT_MS: u64 = 30_000;
+
+#[derive(Debug)]
+pub enum Outcome {
+    Passed,
+    Failed(Vec<Mismatch>),
+    /// The plugin could not be instantiated or returned garbage
+    Error(String),
+    Skipped(String),
+}
+
+#[derive(Debug)]
+pub struct CaseResult {
+    pub name: String,
+    pub file: PathBuf,
+    pub outcome: Outcome,
+    pub elapsed: Duration,
+}
+
+/// Default location of a plugin's wasm in the workspace target directory
+pub fn default_wasm_path(target_dir: &Path, plugin: &str) -> PathBuf {
+    target_dir
+        .join("wasm32-wasip1/release")
+        .join(format!("sweetmcp_plugin_{}.wasm", plugin.replace('-', "_")))
+}
+
+pub struct Runner {
+    wasm: Vec<u8>,
+}
+
+impl Runner {
+    pub fn load(wasm_path: &Path) -> Result<Self> {
+        let wasm = std::fs::read(wasm_path).with_context(|| {
+            format!(
+                "read {} (build it with `cargo build --release --target wasm32-wasip1`)",
+                wasm_path.display()
+            )
+        })?;
+        Ok(Self { wasm })
+    }
+
+    /// Run one case in a fresh plugin instance so cases can't leak state.
+    pub fn run(&self, case: &TestCase) -> CaseResult {
+        let started = Instant::now();
+        let outcome = match &case.skip {
+            Some(reason) => Outcome::Skipped(reason.clone()),
+            None => self.execute(case),
+        };
+        CaseResult {
+            name: case.name.clone(),
+            file: case.file.clone(),
+            outcome,
+            elapsed: started.elapsed(),
+        }
+    }
+
+    fn execute(&self, case: &TestCase) -> Outcome {
+        let mut plugin = match self.instantiate(case) {
+            Ok(plugin) => plugin,
+            Err(e) => return Outcome::Error(format!("{:#}", e)),
+        };
+        let request = json!({
+            "params": { "name": case.tool, "arguments": case.arguments }
+        })
+        .to_string();
+
+        let result = plugin
+            .call::<&str, &str>("call", &request)
+            .map(str::to_string);
+        let mismatches = match (result, &case.expect, &case.expect_error) {
+            (Ok(output), Some(expected), _) => match serde_json::from_str::<Value>(&output) {
+                Ok(actual) => matcher::check(expected, &actual),
+                Err(e) => return Outcome::Error(format!("plugin returned invalid JSON: {}", e)),
+            },
+            (Err(e), _, Some(expected)) => matcher::check(expected, &Value::String(e.to_string())),
+            (Ok(output), None, _) => vec![Mismatch {
+                path: "$".to_string(),
+                expected: "the call to fail".to_string(),
+                actual: output,
+            }],
+            (Err(e), Some(_), _) => return Outcome::Error(format!("call failed: {}", e)),
+            (Err(e), None, None) => return Outcome::Error(e.to_string()),
+        };
+
+        if mismatches.is_empty() {
+            Outcome::Passed
+        } else {
+            Outcome::Failed(mismatches)
+        }
+    }
+
+    fn instantiate(&self, case: &TestCase) -> Result<Plugin> {
+        let mut manifest = Manifest::new([Wasm::data(self.wasm.clone())]).with_timeout(
+            Duration::from_millis(case.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS)),
+        );
+        for host in &case.allowed_hosts {
+            manifest = manifest.with_allowed_host(host);
+        }
+        for (key, value) in &case.config {
+            manifest = manifest.with_config_key(key, value);
+        }
+        Plugin::new(&manifest, [], true).context("instantiate plugin")
+    }
+}
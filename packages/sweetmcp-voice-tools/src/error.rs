@@ -27,6 +27,9 @@ pub enum VoiceError {
     #[error("Invalid duration: {0} seconds (must be between 1-300)")]
     InvalidDuration(u32),
 
+    #[error("Invalid test duration: {0} seconds (must be between 1-30)")]
+    InvalidTestDuration(u32),
+
     #[error("Network error: {0}")]
     NetworkError(String),
 
@@ -8,15 +8,20 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 pub mod error;
+pub mod metering;
 pub mod protocol;
 pub mod tools;
 pub mod types;
 
 // Re-export commonly used types
 pub use error::{VoiceError, VoiceResult};
+pub use metering::AudioMeter;
 pub use protocol::{VoiceRequest, VoiceResponse};
-pub use tools::{listen_tool, speak_tool};
-pub use types::{ListenParams, ListenResult, SpeakParams, VoiceConfig};
+pub use tools::{listen_tool, speak_tool, test_microphone_tool};
+pub use types::{
+    InputLevel, ListenParams, ListenResult, MicrophoneTestParams, MicrophoneTestResult,
+    SpeakParams, VoiceConfig,
+};
 
 /// MCP Tool definition structure (matching sweetmcp-axum types)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -52,6 +57,13 @@ pub trait VoiceService: Send + Sync {
     /// Listen for speech and transcribe to text
     async fn listen(&self, params: ListenParams) -> VoiceResult<ListenResult>;
 
+    /// Record briefly and report input levels, to check the microphone
+    /// before a dictation session
+    async fn test_microphone(
+        &self,
+        params: MicrophoneTestParams,
+    ) -> VoiceResult<MicrophoneTestResult>;
+
     /// Get available voice IDs
     async fn list_voices(&self) -> VoiceResult<Vec<String>>;
 
@@ -61,5 +73,5 @@ pub trait VoiceService: Send + Sync {
 
 /// Tool registry helper
 pub fn register_voice_tools() -> Vec<Tool> {
-    vec![speak_tool(), listen_tool()]
+    vec![speak_tool(), listen_tool(), test_microphone_tool()]
 }
@@ -0,0 +1,147 @@
+//! Input level metering for microphone tests
+//!
+//! Voice services feed captured samples, normalised to `-1.0..=1.0`, through
+//! an [`AudioMeter`] and report its [`MicrophoneTestResult`]. Levels are in
+//! dBFS, so 0 is full scale and quieter signals are negative. The ambient
+//! noise estimate is a low percentile of short-window RMS levels, which
+//! tracks the background between words rather than the speech itself.
+
+use crate::types::{InputLevel, MicrophoneTestResult};
+
+/// Reported level of digital silence
+pub const SILENCE_DBFS: f32 = -120.0;
+
+/// Samples at or above this magnitude count as clipped
+const CLIP_THRESHOLD: f32 = 0.999;
+
+/// Length of the windows the noise floor is estimated from
+const WINDOW_MS: u32 = 50;
+
+/// Percentile of window levels taken as the noise floor
+const NOISE_PERCENTILE: f32 = 0.1;
+
+/// Peak below this means the microphone is muted or disconnected
+const SILENT_PEAK_DBFS: f32 = -60.0;
+
+/// Peak below this is too quiet for reliable transcription
+const QUIET_PEAK_DBFS: f32 = -30.0;
+
+/// Noise floor above this will interfere with transcription
+const NOISY_FLOOR_DBFS: f32 = -40.0;
+
+/// Clipped samples per million above which the input is reported as clipping
+const CLIPPING_PER_MILLION: u64 = 100;
+
+/// Convert a linear amplitude to dBFS
+pub fn to_dbfs(amplitude: f32) -> f32 {
+    if amplitude <= 0.0 {
+        SILENCE_DBFS
+    } else {
+        (20.0 * amplitude.log10()).max(SILENCE_DBFS)
+    }
+}
+
+/// Running levels of captured audio
+#[derive(Debug, Clone)]
+pub struct AudioMeter {
+    sample_rate: u32,
+    window_len: usize,
+    peak: f32,
+    sum_squares: f64,
+    samples: u64,
+    clipped: u64,
+    window_sum_squares: f64,
+    window_samples: usize,
+    window_rms: Vec<f32>,
+}
+
+impl AudioMeter {
+    /// Meter for mono audio at `sample_rate` Hz
+    pub fn new(sample_rate: u32) -> Self {
+        let sample_rate = sample_rate.max(1);
+        Self {
+            sample_rate,
+            window_len: (sample_rate * WINDOW_MS / 1000).max(1) as usize,
+            peak: 0.0,
+            sum_squares: 0.0,
+            samples: 0,
+            clipped: 0,
+            window_sum_squares: 0.0,
+            window_samples: 0,
+            window_rms: Vec::new(),
+        }
+    }
+
+    /// Add a block of samples
+    pub fn push(&mut self, samples: &[f32]) {
+        for &sample in samples {
+            let magnitude = sample.abs();
+            self.peak = self.peak.max(magnitude);
+            if magnitude >= CLIP_THRESHOLD {
+                self.clipped += 1;
+            }
+
+            let square = f64::from(sample) * f64::from(sample);
+            self.sum_squares += square;
+            self.samples += 1;
+
+            self.window_sum_squares += square;
+            self.window_samples += 1;
+            if self.window_samples == self.window_len {
+                self.close_window();
+            }
+        }
+    }
+
+    fn close_window(&mut self) {
+        let rms = (self.window_sum_squares / self.window_samples as f64).sqrt();
+        self.window_rms.push(rms as f32);
+        self.window_sum_squares = 0.0;
+        self.window_samples = 0;
+    }
+
+    /// Levels of everything pushed so far
+    pub fn finish(mut self, microphone_id: impl Into<String>) -> MicrophoneTestResult {
+        if self.window_samples > 0 {
+            self.close_window();
+        }
+
+        let rms = if self.samples == 0 {
+            0.0
+        } else {
+            (self.sum_squares / self.samples as f64).sqrt() as f32
+        };
+
+        let mut windows = self.window_rms;
+        windows.sort_by(f32::total_cmp);
+        let index = (windows.len().saturating_sub(1) as f32 * NOISE_PERCENTILE).round() as usize;
+        let noise = windows.get(index).copied().unwrap_or(0.0);
+
+        let peak_dbfs = to_dbfs(self.peak);
+        let noise_floor_dbfs = to_dbfs(noise);
+        let clipping = self.clipped * 1_000_000 > self.samples * CLIPPING_PER_MILLION;
+
+        let level = if peak_dbfs < SILENT_PEAK_DBFS {
+            InputLevel::Silent
+        } else if clipping {
+            InputLevel::Clipping
+        } else if noise_floor_dbfs > NOISY_FLOOR_DBFS {
+            InputLevel::Noisy
+        } else if peak_dbfs < QUIET_PEAK_DBFS {
+            InputLevel::TooQuiet
+        } else {
+            InputLevel::Good
+        };
+
+        MicrophoneTestResult {
+            microphone_id: microphone_id.into(),
+            duration_seconds: self.samples as f32 / self.sample_rate as f32,
+            peak_dbfs,
+            rms_dbfs: to_dbfs(rms),
+            noise_floor_dbfs,
+            clipped_samples: self.clipped,
+            clipping,
+            level,
+        }
+    }
+}
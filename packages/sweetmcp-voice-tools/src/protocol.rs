@@ -1,6 +1,8 @@
 //! QUIC protocol definitions for voice service communication
 
-use crate::types::{ListenParams, ListenResult, SpeakParams};
+use crate::types::{
+    ListenParams, ListenResult, MicrophoneTestParams, MicrophoneTestResult, SpeakParams,
+};
 use serde::{Deserialize, Serialize};
 
 /// Request types for voice operations
@@ -13,6 +15,9 @@ pub enum VoiceRequest {
     /// Request to listen for audio
     Listen(ListenParams),
 
+    /// Request a microphone level test
+    TestMicrophone(MicrophoneTestParams),
+
     /// Request list of available voices
     ListVoices,
 
@@ -30,6 +35,9 @@ pub enum VoiceResponse {
     /// Listen operation result
     ListenResult(ListenResult),
 
+    /// Microphone test result
+    MicrophoneTest(MicrophoneTestResult),
+
     /// List of available voice IDs
     VoiceList(Vec<String>),
 
@@ -101,3 +101,45 @@ pub fn listen_tool() -> Tool {
         },
     }
 }
+
+/// Create the test_microphone tool definition
+pub fn test_microphone_tool() -> Tool {
+    let mut properties = HashMap::new();
+
+    properties.insert(
+        "microphone_id".to_string(),
+        ToolInputSchemaProperty {
+            type_name: Some("string".to_string()),
+            enum_values: None,
+            description: Some(
+                "Microphone device to test (optional, defaults to the configured microphone)"
+                    .to_string(),
+            ),
+        },
+    );
+
+    properties.insert(
+        "duration_seconds".to_string(),
+        ToolInputSchemaProperty {
+            type_name: Some("integer".to_string()),
+            enum_values: None,
+            description: Some("How long to record in seconds (1-30)".to_string()),
+        },
+    );
+
+    Tool {
+        name: "test_microphone".to_string(),
+        description: Some(
+            "Record briefly from the microphone and report peak and RMS levels in dBFS, \
+            whether the input clips, and an estimate of ambient noise, with an overall \
+            verdict (good, silent, too_quiet, clipping or noisy). Use this before a \
+            dictation session to confirm the microphone works and is set up well."
+                .to_string(),
+        ),
+        input_schema: ToolInputSchema {
+            type_name: "object".to_string(),
+            properties,
+            required: vec!["duration_seconds".to_string()],
+        },
+    }
+}
@@ -1,5 +1,6 @@
 //! Type definitions for voice operations
 
+use crate::error::{VoiceError, VoiceResult};
 use serde::{Deserialize, Serialize};
 
 /// Parameters for the speak operation
@@ -76,3 +77,71 @@ impl Default for VoiceConfig {
         }
     }
 }
+
+/// Longest microphone test, in seconds
+pub const MAX_TEST_DURATION_SECONDS: u32 = 30;
+
+/// Parameters for the test_microphone operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MicrophoneTestParams {
+    /// Microphone device ID (defaults to the configured microphone)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub microphone_id: Option<String>,
+
+    /// Duration to record in seconds (1-30)
+    pub duration_seconds: u32,
+}
+
+impl MicrophoneTestParams {
+    /// Check the requested duration
+    pub fn validate(&self) -> VoiceResult<()> {
+        if !(1..=MAX_TEST_DURATION_SECONDS).contains(&self.duration_seconds) {
+            return Err(VoiceError::InvalidTestDuration(self.duration_seconds));
+        }
+        Ok(())
+    }
+}
+
+/// Overall verdict of a microphone test
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InputLevel {
+    /// Levels are suitable for dictation
+    Good,
+    /// No signal: the microphone is likely muted, unplugged or not permitted
+    Silent,
+    /// Signal present but too weak to transcribe reliably
+    TooQuiet,
+    /// Input gain is too high and the signal is distorted
+    Clipping,
+    /// Background noise is loud enough to hurt transcription
+    Noisy,
+}
+
+/// Result of a test_microphone operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MicrophoneTestResult {
+    /// Microphone that was recorded
+    pub microphone_id: String,
+
+    /// Audio actually captured, in seconds
+    pub duration_seconds: f32,
+
+    /// Loudest sample, in dBFS
+    pub peak_dbfs: f32,
+
+    /// Average level over the whole recording, in dBFS
+    pub rms_dbfs: f32,
+
+    /// Estimated ambient noise level, in dBFS
+    pub noise_floor_dbfs: f32,
+
+    /// Samples at full scale
+    pub clipped_samples: u64,
+
+    /// Whether enough samples clipped to distort speech
+    pub clipping: bool,
+
+    /// Overall verdict
+    pub level: InputLevel,
+}
@@ -0,0 +1,131 @@
+//! Fetching a URL and reducing it to hashes, readable text and a screenshot
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use extism_pdk::*;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+/// A point-in-time snapshot of a URL
+pub struct Capture {
+    pub status: u16,
+    /// SHA-256 of the raw response body
+    pub content_sha256: String,
+    /// SHA-256 of the normalized text, stable across markup-only changes
+    pub text_sha256: String,
+    pub text: String,
+    /// Base64 PNG, when a screenshot service is configured and requested
+    pub screenshot: Option<String>,
+}
+
+/// Fetch `url` and build a capture from the response
+pub fn capture(url: &str, with_screenshot: bool) -> Result<Capture, Error> {
+    let request = HttpRequest::new(url)
+        .with_method("GET")
+        .with_header("User-Agent", "sweetmcp-archive/0.1");
+    let response = http::request::<()>(&request, None)?;
+    let status = response.status_code();
+    if status >= 400 {
+        return Err(Error::msg(format!("{} returned HTTP {}", url, status)));
+    }
+
+    let body = response.body();
+    let text = extract_text(&String::from_utf8_lossy(&body));
+
+    let screenshot = if with_screenshot {
+        screenshot(url)?
+    } else {
+        None
+    };
+
+    Ok(Capture {
+        status,
+        content_sha256: sha256_hex(&body),
+        text_sha256: sha256_hex(text.as_bytes()),
+        text,
+        screenshot,
+    })
+}
+
+/// Render `url` through the configured screenshot service.
+///
+/// The service receives `{"url": ...}` and must answer with image bytes, which
+/// matches browserless-style `/screenshot` endpoints.
+fn screenshot(url: &str) -> Result<Option<String>, Error> {
+    let Some(endpoint) = config::get("screenshot_url")? else {
+        return Ok(None);
+    };
+    let request = HttpRequest::new(endpoint)
+        .with_method("POST")
+        .with_header("Content-Type", "application/json");
+    let response = http::request::<String>(&request, Some(json!({ "url": url }).to_string()))?;
+    if response.status_code() >= 400 {
+        return Err(Error::msg(format!(
+            "Screenshot service failed (HTTP {}): {}",
+            response.status_code(),
+            String::from_utf8_lossy(&response.body())
+        )));
+    }
+    Ok(Some(STANDARD.encode(response.body())))
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Strip markup, scripts and styles, decode common entities and collapse
+/// whitespace so the text hash only moves when the readable content does.
+pub fn extract_text(html: &str) -> String {
+    let lower = html.to_ascii_lowercase();
+    let mut out = String::with_capacity(html.len() / 2);
+    let mut i = 0;
+
+    while i < html.len() {
+        if html.as_bytes()[i] == b'<' {
+            let skip_to = ["script", "style", "noscript"]
+                .iter()
+                .find(|tag| lower[i + 1..].starts_with(*tag))
+                .and_then(|tag| lower[i..].find(&format!("</{}", tag)).map(|end| i + end));
+            let from = skip_to.unwrap_or(i);
+            i = match lower[from..].find('>') {
+                Some(end) => from + end + 1,
+                None => html.len(),
+            };
+            out.push('\n');
+            continue;
+        }
+        let next = html[i..].find('<').map(|n| i + n).unwrap_or(html.len());
+        out.push_str(&decode_entities(&html[i..next]));
+        i = next;
+    }
+
+    out.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn decode_entities(s: &str) -> String {
+    s.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Jaccard similarity of the two texts' line sets, 1.0 for identical content
+pub fn line_similarity(a: &str, b: &str) -> f64 {
+    use std::collections::HashSet;
+    let a: HashSet<&str> = a.lines().collect();
+    let b: HashSet<&str> = b.lines().collect();
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 1.0;
+    }
+    a.intersection(&b).count() as f64 / union as f64
+}
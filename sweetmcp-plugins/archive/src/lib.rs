@@ -0,0 +1,172 @@
+mod capture;
+mod store;
+
+use chrono::Utc;
+use extism_pdk::*;
+use serde_json::{Value, json};
+use sweetmcp_plugin_builder::prelude::*;
+use sweetmcp_plugin_builder::{CallToolResult, Ready};
+
+/// Capture a URL into the memory store for later citation
+struct ArchiveUrlTool;
+
+impl McpTool for ArchiveUrlTool {
+    const NAME: &'static str = "archive_url";
+    type Args = Value;
+
+    fn description(builder: DescriptionBuilder) -> DescriptionBuilder {
+        builder
+            .does("Capture a web page's content hash, readable text and optional screenshot into the memory store with a timestamp")
+            .when("you are about to cite a web source and need a stable record of what it said")
+            .when("you want to snapshot a page before it changes or disappears")
+            .requires("A running sweetmcp-memory API at `memory_url`; screenshots need `screenshot_url` configured")
+            .not_for("crawling whole sites or fetching content you do not intend to cite")
+            .perfect_for("research agents, citation trails, and evidence preservation")
+    }
+
+    fn schema(builder: SchemaBuilder) -> Value {
+        builder
+            .required_string("url", "URL to capture")
+            .optional_bool(
+                "screenshot",
+                "Also capture a screenshot through the configured screenshot service (default true)",
+            )
+            .build()
+    }
+
+    fn execute(args: Value) -> Result<CallToolResult, Error> {
+        let url = args
+            .get("url")
+            .and_then(|v| v.as_str())
+            .filter(|u| u.starts_with("http://") || u.starts_with("https://"))
+            .ok_or_else(|| Error::msg("url parameter required (http or https)"))?;
+        let with_screenshot = args
+            .get("screenshot")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+
+        let snapshot = match capture::capture(url, with_screenshot) {
+            Ok(snapshot) => snapshot,
+            Err(e) => return Ok(ContentBuilder::error(e.to_string())),
+        };
+
+        let now = Utc::now();
+        let captured_at = now.to_rfc3339();
+        let id = format!(
+            "archive:{}:{}",
+            &capture::sha256_hex(url.as_bytes())[..16],
+            now.timestamp()
+        );
+        store::save(&store::memory_url(), &id, url, &captured_at, &snapshot)?;
+
+        Ok(ContentBuilder::text(
+            json!({
+                "id": id,
+                "url": url,
+                "captured_at": captured_at,
+                "status": snapshot.status,
+                "content_sha256": snapshot.content_sha256,
+                "text_sha256": snapshot.text_sha256,
+                "text_length": snapshot.text.len(),
+                "screenshot": snapshot.screenshot.is_some(),
+            })
+            .to_string(),
+        ))
+    }
+}
+
+/// Check whether a live page still matches its archived capture
+struct VerifyArchiveTool;
+
+impl McpTool for VerifyArchiveTool {
+    const NAME: &'static str = "verify_archive";
+    type Args = Value;
+
+    fn description(builder: DescriptionBuilder) -> DescriptionBuilder {
+        builder
+            .does("Re-fetch an archived URL and report whether its live content still matches the stored capture")
+            .when("you are about to rely on a citation captured earlier")
+            .when("you need to detect whether a source was edited or taken down since it was cited")
+            .requires("An archive id returned by `archive_url`")
+            .perfect_for("citation verification, source drift detection, and audit trails")
+    }
+
+    fn schema(builder: SchemaBuilder) -> Value {
+        builder
+            .required_string("id", "Archive id returned by archive_url")
+            .optional_bool(
+                "include_archived_text",
+                "Return the archived text alongside the verdict (default false)",
+            )
+            .build()
+    }
+
+    fn execute(args: Value) -> Result<CallToolResult, Error> {
+        let id = args
+            .get("id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::msg("id parameter required"))?;
+        let include_text = args
+            .get("include_archived_text")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let Some(record) = store::load(&store::memory_url(), id)? else {
+            return Ok(ContentBuilder::error(format!(
+                "No archive found with id {}",
+                id
+            )));
+        };
+        let Some(url) = record.field("url") else {
+            return Ok(ContentBuilder::error(format!("Archive {} has no url", id)));
+        };
+
+        let mut verdict = json!({
+            "id": &record.id,
+            "url": url,
+            "captured_at": record.field("captured_at"),
+            "verified_at": Utc::now().to_rfc3339(),
+        });
+
+        match capture::capture(url, false) {
+            Ok(live) => {
+                let content_match =
+                    record.field("content_sha256") == Some(live.content_sha256.as_str());
+                let text_match = record.field("text_sha256") == Some(live.text_sha256.as_str());
+                verdict["reachable"] = json!(true);
+                verdict["content_match"] = json!(content_match);
+                verdict["text_match"] = json!(text_match);
+                verdict["text_similarity"] =
+                    json!(capture::line_similarity(&record.content, &live.text));
+                verdict["live_content_sha256"] = json!(live.content_sha256);
+                verdict["live_text_sha256"] = json!(live.text_sha256);
+            }
+            // An unreachable source is a verification result, not a tool failure
+            Err(e) => {
+                verdict["reachable"] = json!(false);
+                verdict["content_match"] = json!(false);
+                verdict["text_match"] = json!(false);
+                verdict["error"] = json!(e.to_string());
+            }
+        }
+
+        if include_text {
+            verdict["archived_text"] = json!(&record.content);
+        }
+
+        Ok(ContentBuilder::text(verdict.to_string()))
+    }
+}
+
+/// Create the plugin instance
+#[allow(dead_code)]
+fn plugin() -> McpPlugin<Ready> {
+    mcp_plugin("archive")
+        .description("Web page capture and verification for stable citations")
+        .tool::<ArchiveUrlTool>()
+        .tool::<VerifyArchiveTool>()
+        .serve()
+}
+
+// Generate standard MCP entry points
+sweetmcp_plugin_builder::generate_mcp_functions!(plugin);
@@ -0,0 +1,120 @@
+//! Archive records in the sweetmcp-memory HTTP API
+
+use extism_pdk::*;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::capture::Capture;
+
+/// Default memory API endpoint when `memory_url` is not configured
+const DEFAULT_MEMORY_URL: &str = "http://127.0.0.1:8000";
+
+/// An archived capture read back from the memory store
+#[derive(Debug, Deserialize)]
+pub struct ArchiveRecord {
+    pub id: String,
+    pub content: String,
+    #[serde(default)]
+    metadata: RecordMetadata,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RecordMetadata {
+    #[serde(default)]
+    custom: Value,
+}
+
+impl ArchiveRecord {
+    pub fn field(&self, key: &str) -> Option<&str> {
+        self.metadata.custom.get(key).and_then(|v| v.as_str())
+    }
+
+    pub fn is_archive(&self) -> bool {
+        self.field("kind") == Some("archive")
+    }
+}
+
+/// Base URL of the memory API, taken from plugin config
+pub fn memory_url() -> String {
+    config::get("memory_url")
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| DEFAULT_MEMORY_URL.to_string())
+        .trim_end_matches('/')
+        .to_string()
+}
+
+/// Persist a capture under `id`
+pub fn save(
+    base_url: &str,
+    id: &str,
+    url: &str,
+    captured_at: &str,
+    capture: &Capture,
+) -> Result<(), Error> {
+    let mut metadata = json!({
+        "kind": "archive",
+        "url": url,
+        "captured_at": captured_at,
+        "status": capture.status.to_string(),
+        "content_sha256": capture.content_sha256,
+        "text_sha256": capture.text_sha256,
+    });
+    if let Some(png) = &capture.screenshot {
+        metadata["screenshot_png"] = json!(png);
+    }
+
+    let body = json!({
+        "id": id,
+        "content": capture.text,
+        "memory_type": "episodic",
+        "metadata": metadata,
+    });
+
+    let request = HttpRequest::new(format!("{}/api/memories", base_url))
+        .with_method("POST")
+        .with_header("Content-Type", "application/json");
+    let response = http::request::<String>(&request, Some(body.to_string()))?;
+
+    if response.status_code() >= 400 {
+        return Err(Error::msg(format!(
+            "Memory API rejected archive {} (HTTP {}): {}",
+            id,
+            response.status_code(),
+            String::from_utf8_lossy(&response.body())
+        )));
+    }
+    Ok(())
+}
+
+/// Load an archived capture by id
+pub fn load(base_url: &str, id: &str) -> Result<Option<ArchiveRecord>, Error> {
+    let request =
+        HttpRequest::new(format!("{}/api/memories/{}", base_url, urlencode(id))).with_method("GET");
+    let response = http::request::<()>(&request, None)?;
+
+    match response.status_code() {
+        404 => Ok(None),
+        status if status >= 400 => Err(Error::msg(format!(
+            "Memory API lookup failed (HTTP {}): {}",
+            status,
+            String::from_utf8_lossy(&response.body())
+        ))),
+        _ => {
+            let record: ArchiveRecord = response.json()?;
+            Ok(record.is_archive().then_some(record))
+        }
+    }
+}
+
+fn urlencode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
@@ -11,7 +11,29 @@ pub enum BrowserCommand {
     ExtractText(ExtractTextCommand),
     Scroll(ScrollCommand),
     Wait(WaitCommand),
+    WaitForSelector(WaitForSelectorCommand),
+    WaitForNavigation(WaitForNavigationCommand),
     RunAutomation(RunAutomationCommand),
+    GetConsoleLogs(GetConsoleLogsCommand),
+    NewTab(NewTabCommand),
+    /// Make the command's `tab_id` the session's active tab
+    SwitchTab,
+    /// List the session's tabs as the host sees them
+    ListTabs,
+    /// Close the command's `tab_id`
+    CloseTab,
+}
+
+/// A command addressed to one tab of one browser session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionCommand {
+    /// Browser session (a set of tabs) the command belongs to
+    pub session_id: String,
+    /// Tab the command acts on; the host's implicit page when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tab_id: Option<String>,
+    #[serde(flatten)]
+    pub command: BrowserCommand,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -73,6 +95,42 @@ pub struct WaitCommand {
     pub duration: i64,
 }
 
+/// Poll until an element reaches `state`, failing after `timeout_ms`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaitForSelectorCommand {
+    pub selector: String,
+    pub state: SelectorState,
+    pub timeout_ms: i64,
+    pub poll_interval_ms: i64,
+}
+
+/// What `wait_for_selector` waits for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SelectorState {
+    /// In the DOM and rendered with a non-empty box
+    Visible,
+    /// Absent, or present but not rendered
+    Hidden,
+    /// In the DOM, rendered or not
+    Attached,
+    /// Gone from the DOM
+    Detached,
+}
+
+/// Poll until the page's URL matches `url_pattern` and, with
+/// `network_idle`, no request has been in flight for `idle_ms`. With
+/// neither, waits for the next navigation to finish loading.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaitForNavigationCommand {
+    /// Full URL to match, where `*` matches any run of characters
+    pub url_pattern: Option<String>,
+    pub network_idle: bool,
+    pub idle_ms: i64,
+    pub timeout_ms: i64,
+    pub poll_interval_ms: i64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RunAutomationCommand {
     pub task: String,
@@ -80,6 +138,35 @@ pub struct RunAutomationCommand {
     pub additional_info: String,
 }
 
+/// Return console messages and uncaught exceptions recorded since the last
+/// navigation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetConsoleLogsCommand {
+    /// Lowest level to return; messages below it are dropped
+    pub level_filter: ConsoleLevel,
+    /// Include uncaught exceptions and unhandled promise rejections
+    pub include_exceptions: bool,
+}
+
+/// Console message levels, lowest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[derive(Default)]
+pub enum ConsoleLevel {
+    #[default]
+    Debug,
+    Info,
+    Warning,
+    Error,
+}
+
+/// Open a tab under the command's `tab_id` and make it the active one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewTabCommand {
+    /// Page to load; a blank tab when absent
+    pub url: Option<String>,
+}
+
 /// Command execution result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommandResult {
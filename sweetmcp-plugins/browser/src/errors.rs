@@ -90,7 +90,6 @@ pub fn validate_selector(selector: &str) -> Result<(), BrowserError> {
     Ok(())
 }
 
-#[allow(dead_code)]
 pub fn validate_timeout(duration: i64) -> Result<(), BrowserError> {
     if duration <= 0 {
         return Err(BrowserError::InvalidInput(
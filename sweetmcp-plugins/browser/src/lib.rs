@@ -2,6 +2,7 @@ mod automation;
 mod commands;
 mod errors;
 mod pdk;
+mod tabs;
 
 use automation::*;
 use commands::*;
@@ -10,7 +11,14 @@ use extism_pdk::*;
 use pdk::types::{
     CallToolRequest, CallToolResult, Content, ContentType, ListToolsResult, ToolDescription,
 };
-use serde_json::json;
+use serde_json::{Value, json};
+use tabs::Session;
+
+// The page pool lives on the host; see sweetmcp-axum `plugin::browser`.
+#[host_fn]
+extern "ExtismHost" {
+    fn browser_batch_extract(req: Json<Value>) -> Json<Value>;
+}
 
 // MCP Protocol Functions
 
@@ -32,7 +40,15 @@ pub(crate) fn call(input: CallToolRequest) -> Result<CallToolResult, Error> {
         "extract_text" => handle_extract_text(args),
         "scroll" => handle_scroll(args),
         "wait" => handle_wait(args),
+        "wait_for_selector" => handle_wait_for_selector(args),
+        "wait_for_navigation" => handle_wait_for_navigation(args),
         "run_automation" => handle_run_automation(args),
+        "batch_extract" => handle_batch_extract(args),
+        "get_console_logs" => handle_get_console_logs(args),
+        "new_tab" => handle_new_tab(args),
+        "switch_tab" => handle_switch_tab(args),
+        "list_tabs" => handle_list_tabs(args),
+        "close_tab" => handle_close_tab(args),
         _ => Err(Error::msg(format!(
             "Unknown browser action: {}",
             input.params.name
@@ -65,21 +81,7 @@ fn handle_navigate(
         url: url.to_string(),
     });
 
-    // Serialize the command for host execution
-    let command_json = serde_json::to_string_pretty(&command).map_err(|e| {
-        BrowserError::SerializationError(format!("Failed to serialize command: {e}"))
-    })?;
-
-    Ok(CallToolResult {
-        is_error: None,
-        content: vec![Content {
-            annotations: None,
-            text: Some(command_json),
-            mime_type: Some("application/json".into()),
-            r#type: ContentType::Text,
-            data: None,
-        }],
-    })
+    session_command(&args, command)
 }
 
 /// Handle taking screenshots
@@ -102,19 +104,7 @@ fn handle_screenshot(
         format,
     });
 
-    let command_json = serde_json::to_string_pretty(&command)
-        .map_err(|e| Error::msg(format!("Failed to serialize command: {e}")))?;
-
-    Ok(CallToolResult {
-        is_error: None,
-        content: vec![Content {
-            annotations: None,
-            text: Some(command_json),
-            mime_type: Some("application/json".into()),
-            r#type: ContentType::Text,
-            data: None,
-        }],
-    })
+    session_command(&args, command)
 }
 
 /// Handle clicking elements
@@ -139,20 +129,7 @@ fn handle_click(args: serde_json::Map<String, serde_json::Value>) -> Result<Call
         selector: selector.to_string(),
     });
 
-    let command_json = serde_json::to_string_pretty(&command).map_err(|e| {
-        BrowserError::SerializationError(format!("Failed to serialize command: {e}"))
-    })?;
-
-    Ok(CallToolResult {
-        is_error: None,
-        content: vec![Content {
-            annotations: None,
-            text: Some(command_json),
-            mime_type: Some("application/json".into()),
-            r#type: ContentType::Text,
-            data: None,
-        }],
-    })
+    session_command(&args, command)
 }
 
 /// Handle typing text into elements
@@ -178,19 +155,7 @@ fn handle_type_text(
         text: text.to_string(),
     });
 
-    let command_json = serde_json::to_string_pretty(&command)
-        .map_err(|e| Error::msg(format!("Failed to serialize command: {e}")))?;
-
-    Ok(CallToolResult {
-        is_error: None,
-        content: vec![Content {
-            annotations: None,
-            text: Some(command_json),
-            mime_type: Some("application/json".into()),
-            r#type: ContentType::Text,
-            data: None,
-        }],
-    })
+    session_command(&args, command)
 }
 
 /// Handle text extraction from elements
@@ -206,19 +171,7 @@ fn handle_extract_text(
         selector: selector.to_string(),
     });
 
-    let command_json = serde_json::to_string_pretty(&command)
-        .map_err(|e| Error::msg(format!("Failed to serialize command: {e}")))?;
-
-    Ok(CallToolResult {
-        is_error: None,
-        content: vec![Content {
-            annotations: None,
-            text: Some(command_json),
-            mime_type: Some("application/json".into()),
-            r#type: ContentType::Text,
-            data: None,
-        }],
-    })
+    session_command(&args, command)
 }
 
 /// Handle scrolling
@@ -236,19 +189,7 @@ fn handle_scroll(
 
     let command = BrowserCommand::Scroll(ScrollCommand { direction, amount });
 
-    let command_json = serde_json::to_string_pretty(&command)
-        .map_err(|e| Error::msg(format!("Failed to serialize command: {e}")))?;
-
-    Ok(CallToolResult {
-        is_error: None,
-        content: vec![Content {
-            annotations: None,
-            text: Some(command_json),
-            mime_type: Some("application/json".into()),
-            r#type: ContentType::Text,
-            data: None,
-        }],
-    })
+    session_command(&args, command)
 }
 
 /// Handle waiting
@@ -260,19 +201,109 @@ fn handle_wait(args: serde_json::Map<String, serde_json::Value>) -> Result<CallT
 
     let command = BrowserCommand::Wait(WaitCommand { duration });
 
-    let command_json = serde_json::to_string_pretty(&command)
-        .map_err(|e| Error::msg(format!("Failed to serialize command: {e}")))?;
+    session_command(&args, command)
+}
 
-    Ok(CallToolResult {
-        is_error: None,
-        content: vec![Content {
-            annotations: None,
-            text: Some(command_json),
-            mime_type: Some("application/json".into()),
-            r#type: ContentType::Text,
-            data: None,
-        }],
-    })
+/// Handle waiting for an element to appear or disappear
+fn handle_wait_for_selector(
+    args: serde_json::Map<String, serde_json::Value>,
+) -> Result<CallToolResult, Error> {
+    let selector = match args.get("selector") {
+        Some(v) => v
+            .as_str()
+            .ok_or_else(|| BrowserError::InvalidInput("selector must be a string".to_string()))?,
+        None => {
+            return Err(browser_error_to_extism(BrowserError::InvalidInput(
+                "selector is required for wait_for_selector action".to_string(),
+            )));
+        }
+    };
+    validate_selector(selector).map_err(browser_error_to_extism)?;
+
+    let state = match args.get("state").and_then(|v| v.as_str()) {
+        None | Some("visible") => SelectorState::Visible,
+        Some("hidden") => SelectorState::Hidden,
+        Some("attached") => SelectorState::Attached,
+        Some("detached") => SelectorState::Detached,
+        Some(other) => {
+            return Err(browser_error_to_extism(BrowserError::InvalidInput(
+                format!("state must be one of visible, hidden, attached, detached; got '{other}'"),
+            )));
+        }
+    };
+    let (timeout_ms, poll_interval_ms) = wait_limits(&args).map_err(browser_error_to_extism)?;
+
+    extism_pdk::log!(
+        LogLevel::Debug,
+        "Waiting up to {}ms for {} to be {:?}",
+        timeout_ms,
+        selector,
+        state
+    );
+
+    let command = BrowserCommand::WaitForSelector(WaitForSelectorCommand {
+        selector: selector.to_string(),
+        state,
+        timeout_ms,
+        poll_interval_ms,
+    });
+
+    session_command(&args, command)
+}
+
+/// Handle waiting for a URL to be reached or the network to go quiet
+fn handle_wait_for_navigation(
+    args: serde_json::Map<String, serde_json::Value>,
+) -> Result<CallToolResult, Error> {
+    let url_pattern = tabs::optional_str(&args, "url_pattern").map_err(browser_error_to_extism)?;
+    if url_pattern.is_some_and(str::is_empty) {
+        return Err(browser_error_to_extism(BrowserError::InvalidInput(
+            "url_pattern cannot be empty".to_string(),
+        )));
+    }
+
+    let network_idle = args
+        .get("network_idle")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let idle_ms = args.get("idle_ms").and_then(|v| v.as_i64()).unwrap_or(500);
+    if network_idle {
+        validate_timeout(idle_ms).map_err(browser_error_to_extism)?;
+    }
+    let (timeout_ms, poll_interval_ms) = wait_limits(&args).map_err(browser_error_to_extism)?;
+
+    let command = BrowserCommand::WaitForNavigation(WaitForNavigationCommand {
+        url_pattern: url_pattern.map(str::to_string),
+        network_idle,
+        idle_ms,
+        timeout_ms,
+        poll_interval_ms,
+    });
+
+    session_command(&args, command)
+}
+
+/// `timeout_ms` and `poll_interval_ms` of a wait_for command
+fn wait_limits(
+    args: &serde_json::Map<String, serde_json::Value>,
+) -> Result<(i64, i64), BrowserError> {
+    let timeout_ms = args
+        .get("timeout_ms")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(30_000);
+    validate_timeout(timeout_ms)?;
+
+    let poll_interval_ms = args
+        .get("poll_interval_ms")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(100);
+    if !(10..=timeout_ms).contains(&poll_interval_ms) {
+        return Err(BrowserError::InvalidInput(format!(
+            "poll_interval_ms must be between 10 and timeout_ms ({timeout_ms})"
+        )));
+    }
+
+    Ok((timeout_ms, poll_interval_ms))
 }
 
 /// Handle running complex browser automation tasks
@@ -328,6 +359,7 @@ fn handle_run_automation(
         use_vision,
         additional_info: additional_info.to_string(),
     });
+    let command = address(&args, command)?;
 
     // Include both command and agent context
     let response = json!({
@@ -357,10 +389,259 @@ fn handle_run_automation(
     })
 }
 
+/// Handle extracting text from many pages in parallel on the host's page pool
+fn handle_batch_extract(
+    args: serde_json::Map<String, serde_json::Value>,
+) -> Result<CallToolResult, Error> {
+    let urls = args
+        .get("urls")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| BrowserError::InvalidInput("urls array is required".to_string()))?
+        .iter()
+        .map(|v| {
+            let url = v
+                .as_str()
+                .ok_or_else(|| BrowserError::InvalidInput("urls must be strings".to_string()))?;
+            validate_url(url)?;
+            Ok(url.to_string())
+        })
+        .collect::<Result<Vec<_>, BrowserError>>()
+        .map_err(browser_error_to_extism)?;
+
+    let selector = args
+        .get("selector")
+        .and_then(|v| v.as_str())
+        .unwrap_or("body");
+    validate_selector(selector).map_err(browser_error_to_extism)?;
+
+    let mut request = json!({ "urls": urls, "selector": selector });
+    if let Some(concurrency) = args.get("concurrency").and_then(|v| v.as_u64()) {
+        request["concurrency"] = json!(concurrency);
+    }
+
+    extism_pdk::log!(
+        LogLevel::Debug,
+        "Batch extracting {} from {} urls",
+        selector,
+        urls.len()
+    );
+
+    let Json(reply) = unsafe { browser_batch_extract(Json(request))? };
+    let failed = reply.get("ok").and_then(|v| v.as_bool()) == Some(false);
+
+    Ok(CallToolResult {
+        is_error: failed.then_some(true),
+        content: vec![Content {
+            annotations: None,
+            text: Some(reply.to_string()),
+            mime_type: Some("application/json".into()),
+            r#type: ContentType::Text,
+            data: None,
+        }],
+    })
+}
+
+/// Handle reading console output and JS errors
+fn handle_get_console_logs(
+    args: serde_json::Map<String, serde_json::Value>,
+) -> Result<CallToolResult, Error> {
+    let level_filter = match args.get("level_filter").and_then(|v| v.as_str()) {
+        None | Some("debug") | Some("all") => ConsoleLevel::Debug,
+        Some("info") | Some("log") => ConsoleLevel::Info,
+        Some("warning") | Some("warn") => ConsoleLevel::Warning,
+        Some("error") => ConsoleLevel::Error,
+        Some(other) => {
+            return Err(browser_error_to_extism(BrowserError::InvalidInput(
+                format!("level_filter must be one of debug, info, warning, error; got '{other}'"),
+            )));
+        }
+    };
+    let include_exceptions = args
+        .get("include_exceptions")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+
+    let command = BrowserCommand::GetConsoleLogs(GetConsoleLogsCommand {
+        level_filter,
+        include_exceptions,
+    });
+
+    session_command(&args, command)
+}
+
+/// Handle opening a tab, which becomes the session's active tab
+fn handle_new_tab(
+    args: serde_json::Map<String, serde_json::Value>,
+) -> Result<CallToolResult, Error> {
+    let url = tabs::optional_str(&args, "url").map_err(browser_error_to_extism)?;
+    if let Some(url) = url {
+        validate_url(url).map_err(browser_error_to_extism)?;
+    }
+
+    let session_id = tabs::session_id(&args).map_err(browser_error_to_extism)?;
+    let mut session = Session::load(&session_id)?;
+    let tab_id = tabs::optional_str(&args, "tab_id").map_err(browser_error_to_extism)?;
+    let tab_id = session.open(tab_id, url).map_err(browser_error_to_extism)?;
+    session.save(&session_id)?;
+
+    extism_pdk::log!(
+        LogLevel::Debug,
+        "Opening tab {} in session {}",
+        tab_id,
+        session_id
+    );
+
+    command_result(&SessionCommand {
+        session_id,
+        tab_id: Some(tab_id),
+        command: BrowserCommand::NewTab(NewTabCommand {
+            url: url.map(str::to_string),
+        }),
+    })
+}
+
+/// Handle making another tab the active one
+fn handle_switch_tab(
+    args: serde_json::Map<String, serde_json::Value>,
+) -> Result<CallToolResult, Error> {
+    let tab_id = tabs::optional_str(&args, "tab_id")
+        .map_err(browser_error_to_extism)?
+        .ok_or_else(|| Error::msg("tab_id is required for switch_tab action"))?;
+
+    let session_id = tabs::session_id(&args).map_err(browser_error_to_extism)?;
+    let mut session = Session::load(&session_id)?;
+    session.switch(tab_id).map_err(browser_error_to_extism)?;
+    session.save(&session_id)?;
+
+    command_result(&SessionCommand {
+        session_id,
+        tab_id: Some(tab_id.to_string()),
+        command: BrowserCommand::SwitchTab,
+    })
+}
+
+/// Handle listing a session's tabs
+fn handle_list_tabs(
+    args: serde_json::Map<String, serde_json::Value>,
+) -> Result<CallToolResult, Error> {
+    let session_id = tabs::session_id(&args).map_err(browser_error_to_extism)?;
+    let session = Session::load(&session_id)?;
+    let known = json!({
+        "session_id": session_id,
+        "active_tab": session.active,
+        "tabs": session.tabs,
+    });
+
+    let mut result = command_result(&SessionCommand {
+        session_id,
+        tab_id: None,
+        command: BrowserCommand::ListTabs,
+    })?;
+    // What the plugin has opened, for when the host does not answer the command
+    result.content.push(Content {
+        annotations: None,
+        text: Some(known.to_string()),
+        mime_type: Some("application/json".into()),
+        r#type: ContentType::Text,
+        data: None,
+    });
+    Ok(result)
+}
+
+/// Handle closing a tab, the active one unless `tab_id` is given
+fn handle_close_tab(
+    args: serde_json::Map<String, serde_json::Value>,
+) -> Result<CallToolResult, Error> {
+    let session_id = tabs::session_id(&args).map_err(browser_error_to_extism)?;
+    let mut session = Session::load(&session_id)?;
+    let tab_id = tabs::optional_str(&args, "tab_id").map_err(browser_error_to_extism)?;
+    let tab_id = session.close(tab_id).map_err(browser_error_to_extism)?;
+    session.save(&session_id)?;
+
+    command_result(&SessionCommand {
+        session_id,
+        tab_id: Some(tab_id),
+        command: BrowserCommand::CloseTab,
+    })
+}
+
+/// Address `command` to the session and tab named in `args`
+fn address(
+    args: &serde_json::Map<String, serde_json::Value>,
+    command: BrowserCommand,
+) -> Result<SessionCommand, Error> {
+    let session_id = tabs::session_id(args).map_err(browser_error_to_extism)?;
+    let mut session = Session::load(&session_id)?;
+    let tab_id = tabs::optional_str(args, "tab_id").map_err(browser_error_to_extism)?;
+    let tab_id = session.target(tab_id).map_err(browser_error_to_extism)?;
+
+    if let (BrowserCommand::Navigate(navigate), Some(tab_id)) = (&command, &tab_id) {
+        session.navigated(tab_id, &navigate.url);
+        session.save(&session_id)?;
+    }
+
+    Ok(SessionCommand {
+        session_id,
+        tab_id,
+        command,
+    })
+}
+
+/// Address `command` per `args` and hand it to the host
+fn session_command(
+    args: &serde_json::Map<String, serde_json::Value>,
+    command: BrowserCommand,
+) -> Result<CallToolResult, Error> {
+    command_result(&address(args, command)?)
+}
+
+/// Serialize a command for host execution
+fn command_result(command: &SessionCommand) -> Result<CallToolResult, Error> {
+    let command_json = serde_json::to_string_pretty(command).map_err(|e| {
+        BrowserError::SerializationError(format!("Failed to serialize command: {e}"))
+    })?;
+
+    Ok(CallToolResult {
+        is_error: None,
+        content: vec![Content {
+            annotations: None,
+            text: Some(command_json),
+            mime_type: Some("application/json".into()),
+            r#type: ContentType::Text,
+            data: None,
+        }],
+    })
+}
+
+/// Add the `session_id` and `tab_id` arguments every page command accepts
+fn add_session_properties(schema: &mut serde_json::Map<String, Value>, with_tab: bool) {
+    let Some(properties) = schema
+        .entry("properties")
+        .or_insert_with(|| json!({}))
+        .as_object_mut()
+    else {
+        return;
+    };
+    properties.entry("session_id").or_insert_with(|| {
+        json!({
+            "type": "string",
+            "description": "Browser session to use, so separate tasks keep separate tabs",
+            "default": tabs::DEFAULT_SESSION
+        })
+    });
+    if with_tab {
+        properties.entry("tab_id").or_insert_with(|| {
+            json!({
+                "type": "string",
+                "description": "Tab to act on (from new_tab or list_tabs); defaults to the session's active tab"
+            })
+        });
+    }
+}
+
 /// Called by MCP to understand how and why to use this browser automation tool
 pub(crate) fn describe() -> Result<ListToolsResult, Error> {
-    Ok(ListToolsResult {
-        tools: vec![
+    let mut tools = vec![
             ToolDescription {
                 name: "navigate".into(),
                 description: "Navigate the browser to a specific URL. Use this tool when you need to visit a website or web page.".into(),
@@ -486,7 +767,7 @@ pub(crate) fn describe() -> Result<ListToolsResult, Error> {
             },
             ToolDescription {
                 name: "wait".into(),
-                description: "Wait for a specified duration. Use this tool when you need to pause for page loading or animations to complete.".into(),
+                description: "Wait for a fixed duration. Prefer wait_for_selector or wait_for_navigation, which return as soon as the page is ready; use this only to let an animation finish.".into(),
                 input_schema: json!({
                     "type": "object",
                     "properties": {
@@ -502,6 +783,77 @@ pub(crate) fn describe() -> Result<ListToolsResult, Error> {
                     map
                 }),
             },
+            ToolDescription {
+                name: "wait_for_selector".into(),
+                description: "Wait until an element matching a CSS selector appears or disappears. Use this tool after clicking or navigating instead of a fixed wait; it fails if the element is not in the requested state before the timeout.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "selector": {
+                            "type": "string",
+                            "description": "CSS selector of the element to wait for"
+                        },
+                        "state": {
+                            "type": "string",
+                            "enum": ["visible", "hidden", "attached", "detached"],
+                            "description": "visible: rendered on the page; hidden: absent or not rendered; attached: in the DOM; detached: removed from the DOM",
+                            "default": "visible"
+                        },
+                        "timeout_ms": {
+                            "type": "integer",
+                            "description": "How long to wait before failing, in milliseconds (at most 300000)",
+                            "default": 30000
+                        },
+                        "poll_interval_ms": {
+                            "type": "integer",
+                            "description": "How often to check the page, in milliseconds (at least 10)",
+                            "default": 100
+                        }
+                    },
+                    "required": ["selector"]
+                }).as_object().map(|obj| obj.clone()).unwrap_or_else(|| {
+                    let mut map = serde_json::Map::new();
+                    map.insert("type".to_string(), json!("object"));
+                    map
+                }),
+            },
+            ToolDescription {
+                name: "wait_for_navigation".into(),
+                description: "Wait until the page reaches a URL, the network goes idle, or both. Without url_pattern or network_idle, waits for the next page load to finish. Use this tool after an action that triggers navigation or loads data in the background.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "url_pattern": {
+                            "type": "string",
+                            "description": "Full URL to wait for, where * matches any run of characters, e.g. 'https://example.com/orders/*'"
+                        },
+                        "network_idle": {
+                            "type": "boolean",
+                            "description": "Also wait until no network request has been in flight for idle_ms",
+                            "default": false
+                        },
+                        "idle_ms": {
+                            "type": "integer",
+                            "description": "How long the network must stay quiet to count as idle, in milliseconds",
+                            "default": 500
+                        },
+                        "timeout_ms": {
+                            "type": "integer",
+                            "description": "How long to wait before failing, in milliseconds (at most 300000)",
+                            "default": 30000
+                        },
+                        "poll_interval_ms": {
+                            "type": "integer",
+                            "description": "How often to check the page, in milliseconds (at least 10)",
+                            "default": 100
+                        }
+                    }
+                }).as_object().map(|obj| obj.clone()).unwrap_or_else(|| {
+                    let mut map = serde_json::Map::new();
+                    map.insert("type".to_string(), json!("object"));
+                    map
+                }),
+            },
             ToolDescription {
                 name: "run_automation".into(),
                 description: "Run complex browser automation tasks using AI agents. Use this tool for sophisticated workflows that require multiple steps, decision-making, or visual analysis of web pages. Perfect for tasks like 'fill out this form', 'find product information', or 'complete this checkout process'.".into(),
@@ -530,6 +882,140 @@ pub(crate) fn describe() -> Result<ListToolsResult, Error> {
                     map
                 }),
             },
-        ],
-    })
+            ToolDescription {
+                name: "batch_extract".into(),
+                description: "Extract text matching a CSS selector from many URLs at once. Pages load in parallel on a pool of browser tabs and results come back in the order of the urls, each with its own success flag. Use this tool instead of repeated navigate/extract_text calls when scraping several pages.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "urls": {
+                            "type": "array",
+                            "items": {"type": "string"},
+                            "description": "Pages to load (http or https)"
+                        },
+                        "selector": {
+                            "type": "string",
+                            "description": "CSS selector whose matches' text is returned for each page",
+                            "default": "body"
+                        },
+                        "concurrency": {
+                            "type": "integer",
+                            "description": "Pages to load at once (capped by the host's page pool size)",
+                            "minimum": 1
+                        }
+                    },
+                    "required": ["urls"]
+                }).as_object().map(|obj| obj.clone()).unwrap_or_else(|| {
+                    let mut map = serde_json::Map::new();
+                    map.insert("type".to_string(), json!("object"));
+                    map
+                }),
+            },
+            ToolDescription {
+                name: "get_console_logs".into(),
+                description: "Get the console messages and uncaught JavaScript exceptions recorded since the last navigation. Use this tool to debug a broken web app or to verify that a deployed page loads without errors.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "level_filter": {
+                            "type": "string",
+                            "description": "Lowest message level to return",
+                            "enum": ["debug", "info", "warning", "error"],
+                            "default": "debug"
+                        },
+                        "include_exceptions": {
+                            "type": "boolean",
+                            "description": "Include uncaught exceptions and unhandled promise rejections",
+                            "default": true
+                        }
+                    }
+                }).as_object().map(|obj| obj.clone()).unwrap_or_else(|| {
+                    let mut map = serde_json::Map::new();
+                    map.insert("type".to_string(), json!("object"));
+                    map
+                }),
+            },
+            ToolDescription {
+                name: "new_tab".into(),
+                description: "Open a new browser tab, optionally loading a URL, and make it the active tab. Returns the tab's id. Use this tool to keep several pages open and compare them side by side.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "url": {
+                            "type": "string",
+                            "description": "Page to load in the new tab (optional, defaults to a blank tab)"
+                        },
+                        "tab_id": {
+                            "type": "string",
+                            "description": "Id for the new tab (optional, generated as tab-<n> when omitted)"
+                        }
+                    }
+                }).as_object().map(|obj| obj.clone()).unwrap_or_else(|| {
+                    let mut map = serde_json::Map::new();
+                    map.insert("type".to_string(), json!("object"));
+                    map
+                }),
+            },
+            ToolDescription {
+                name: "switch_tab".into(),
+                description: "Make another open tab the active one, so commands without a tab_id act on it.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "tab_id": {
+                            "type": "string",
+                            "description": "Tab to activate"
+                        }
+                    },
+                    "required": ["tab_id"]
+                }).as_object().map(|obj| obj.clone()).unwrap_or_else(|| {
+                    let mut map = serde_json::Map::new();
+                    map.insert("type".to_string(), json!("object"));
+                    map
+                }),
+            },
+            ToolDescription {
+                name: "list_tabs".into(),
+                description: "List the tabs open in a browser session, with their URLs and which one is active.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {}
+                }).as_object().map(|obj| obj.clone()).unwrap_or_else(|| {
+                    let mut map = serde_json::Map::new();
+                    map.insert("type".to_string(), json!("object"));
+                    map
+                }),
+            },
+            ToolDescription {
+                name: "close_tab".into(),
+                description: "Close a browser tab. Closing the active tab activates the most recently opened tab left.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "tab_id": {
+                            "type": "string",
+                            "description": "Tab to close (optional, defaults to the active tab)"
+                        }
+                    }
+                }).as_object().map(|obj| obj.clone()).unwrap_or_else(|| {
+                    let mut map = serde_json::Map::new();
+                    map.insert("type".to_string(), json!("object"));
+                    map
+                }),
+            },
+        ];
+
+    // Everything but batch_extract, which uses its own pool of pages, runs
+    // in a session; tab commands define their own tab_id
+    for tool in &mut tools {
+        match tool.name.as_str() {
+            "batch_extract" => {}
+            "new_tab" | "switch_tab" | "list_tabs" | "close_tab" => {
+                add_session_properties(&mut tool.input_schema, false)
+            }
+            _ => add_session_properties(&mut tool.input_schema, true),
+        }
+    }
+
+    Ok(ListToolsResult { tools })
 }
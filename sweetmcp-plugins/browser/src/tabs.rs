@@ -0,0 +1,187 @@
+//! Tabs of the browser sessions named by the `session_id` argument.
+//!
+//! The host drives the pages; the plugin remembers, in a plugin var, which
+//! tabs each session has opened and which one is active. That is enough to
+//! hand out tab ids, reject commands for tabs that are already closed and
+//! answer `list_tabs`. Calls without a `session_id` use the `default`
+//! session, and a session with no tabs acts on the host's implicit page.
+
+use crate::errors::BrowserError;
+use extism_pdk::*;
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_SESSION: &str = "default";
+
+const MAX_ID: usize = 64;
+
+/// Most tabs one session may have open
+const MAX_TABS: usize = 16;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tab {
+    pub tab_id: String,
+    /// Last URL the plugin sent the tab to
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Session {
+    pub tabs: Vec<Tab>,
+    pub active: Option<String>,
+    /// Counter behind generated `tab-<n>` ids
+    next: u32,
+}
+
+impl Session {
+    /// The tabs of `session_id`, none if it has not opened any yet
+    pub fn load(session_id: &str) -> Result<Self, Error> {
+        Ok(var::get::<String>(var_key(session_id))?
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default())
+    }
+
+    pub fn save(&self, session_id: &str) -> Result<(), Error> {
+        var::set(var_key(session_id), serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Open a tab, make it the active one and return its id
+    pub fn open(
+        &mut self,
+        tab_id: Option<&str>,
+        url: Option<&str>,
+    ) -> Result<String, BrowserError> {
+        if self.tabs.len() >= MAX_TABS {
+            return Err(BrowserError::InvalidInput(format!(
+                "session already has {MAX_TABS} tabs open; close one first"
+            )));
+        }
+        let tab_id = match tab_id {
+            Some(id) => {
+                validate_id("tab_id", id)?;
+                if self.position(id).is_some() {
+                    return Err(BrowserError::InvalidInput(format!(
+                        "tab '{id}' is already open"
+                    )));
+                }
+                id.to_string()
+            }
+            None => loop {
+                self.next += 1;
+                let id = format!("tab-{}", self.next);
+                if self.position(&id).is_none() {
+                    break id;
+                }
+            },
+        };
+        self.tabs.push(Tab {
+            tab_id: tab_id.clone(),
+            url: url.map(str::to_string),
+        });
+        self.active = Some(tab_id.clone());
+        Ok(tab_id)
+    }
+
+    /// Make `tab_id` the tab commands go to by default
+    pub fn switch(&mut self, tab_id: &str) -> Result<(), BrowserError> {
+        self.require(tab_id)?;
+        self.active = Some(tab_id.to_string());
+        Ok(())
+    }
+
+    /// Close `tab_id`, or the active tab, and return the closed id. Closing
+    /// the active tab activates the most recently opened one left.
+    pub fn close(&mut self, tab_id: Option<&str>) -> Result<String, BrowserError> {
+        let tab_id = match tab_id.or(self.active.as_deref()) {
+            Some(id) => id.to_string(),
+            None => {
+                return Err(BrowserError::InvalidInput(
+                    "session has no open tabs".to_string(),
+                ));
+            }
+        };
+        let index = self.require(&tab_id)?;
+        self.tabs.remove(index);
+        if self.active.as_deref() == Some(tab_id.as_str()) {
+            self.active = self.tabs.last().map(|tab| tab.tab_id.clone());
+        }
+        Ok(tab_id)
+    }
+
+    /// The tab a command acts on: `tab_id` when given, which must be open,
+    /// otherwise the active tab
+    pub fn target(&self, tab_id: Option<&str>) -> Result<Option<String>, BrowserError> {
+        match tab_id {
+            Some(id) => {
+                self.require(id)?;
+                Ok(Some(id.to_string()))
+            }
+            None => Ok(self.active.clone()),
+        }
+    }
+
+    /// Remember where a tab was sent
+    pub fn navigated(&mut self, tab_id: &str, url: &str) {
+        if let Some(index) = self.position(tab_id) {
+            self.tabs[index].url = Some(url.to_string());
+        }
+    }
+
+    fn position(&self, tab_id: &str) -> Option<usize> {
+        self.tabs.iter().position(|tab| tab.tab_id == tab_id)
+    }
+
+    fn require(&self, tab_id: &str) -> Result<usize, BrowserError> {
+        self.position(tab_id).ok_or_else(|| {
+            BrowserError::InvalidInput(format!(
+                "no open tab '{tab_id}'; use list_tabs to see the session's tabs"
+            ))
+        })
+    }
+}
+
+/// The `session_id` argument, or the default session
+pub fn session_id(
+    args: &serde_json::Map<String, serde_json::Value>,
+) -> Result<String, BrowserError> {
+    match optional_str(args, "session_id")? {
+        Some(id) => {
+            validate_id("session_id", id)?;
+            Ok(id.to_string())
+        }
+        None => Ok(DEFAULT_SESSION.to_string()),
+    }
+}
+
+/// A string argument that may be absent but must be a string when present
+pub fn optional_str<'a>(
+    args: &'a serde_json::Map<String, serde_json::Value>,
+    key: &str,
+) -> Result<Option<&'a str>, BrowserError> {
+    match args.get(key) {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(value) => value
+            .as_str()
+            .map(Some)
+            .ok_or_else(|| BrowserError::InvalidInput(format!("{key} must be a string"))),
+    }
+}
+
+fn validate_id(key: &str, id: &str) -> Result<(), BrowserError> {
+    let valid = !id.is_empty()
+        && id.len() <= MAX_ID
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(BrowserError::InvalidInput(format!(
+            "{key} must be 1 to {MAX_ID} letters, digits, '-', '_' or '.'"
+        )))
+    }
+}
+
+fn var_key(session_id: &str) -> String {
+    format!("tabs:{session_id}")
+}
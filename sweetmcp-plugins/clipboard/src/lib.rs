@@ -0,0 +1,151 @@
+use extism_pdk::*;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use sweetmcp_plugin_builder::prelude::*;
+use sweetmcp_plugin_builder::{CallToolResult, Ready};
+
+// The clipboard lives on the host; see sweetmcp-axum `plugin::clipboard`.
+#[host_fn]
+extern "ExtismHost" {
+    fn clipboard_read(req: Json<Value>) -> Json<Value>;
+    fn clipboard_write(req: Json<Value>) -> Json<Value>;
+}
+
+/// Characters of clipboard text returned when the caller gives no limit
+const DEFAULT_MAX_CHARS: usize = 100_000;
+
+/// What to read from the clipboard
+#[derive(Clone, Copy, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+#[schemars(crate = "sweetmcp_plugin_builder::schemars")]
+enum Kind {
+    /// Text if there is any, else an image
+    #[default]
+    Any,
+    Text,
+    Image,
+}
+
+impl Kind {
+    fn name(self) -> &'static str {
+        match self {
+            Kind::Any => "any",
+            Kind::Text => "text",
+            Kind::Image => "image",
+        }
+    }
+}
+
+/// Unwrap a host reply, surfacing `{ok: false, error}` as a tool error
+fn host_reply(reply: Result<Json<Value>, Error>) -> Result<Value, ToolError> {
+    let Json(value) = reply.map_err(|e| ToolError::internal(e.to_string()))?;
+    if value.get("ok").and_then(|v| v.as_bool()) == Some(false) {
+        let message = value
+            .get("error")
+            .and_then(|v| v.as_str())
+            .unwrap_or("clipboard host call failed");
+        return Err(if message.contains("disabled by policy") {
+            ToolError::permission_denied(message)
+        } else {
+            ToolError::internal(message)
+        });
+    }
+    Ok(value)
+}
+
+/// Read the text or image currently on the user's clipboard
+#[mcp_tool(
+    name = "read_clipboard",
+    when = "the user says they copied something and wants you to use it",
+    when = "you need text or a screenshot the user has on their clipboard",
+    perfect_for = "taking content from other desktop apps without pasting it into the chat",
+    read_only
+)]
+fn read_clipboard(
+    /// What to read: text, image, or any (text if present, else an image)
+    #[serde(default)]
+    kind: Kind,
+    /// Longest text returned, in characters (default 100000); longer text is cut and flagged
+    #[serde(default)]
+    max_chars: Option<usize>,
+) -> Result<CallToolResult, ToolError> {
+    let reply = host_reply(unsafe { clipboard_read(Json(json!({ "kind": kind.name() }))) })?;
+
+    if let Some(png) = reply.get("image_png").and_then(|v| v.as_str()) {
+        let info = json!({
+            "kind": "image",
+            "width": reply.get("width"),
+            "height": reply.get("height"),
+            "bytes": reply.get("bytes"),
+        });
+        return Ok(ContentBuilder::new()
+            .image(png, "image/png")
+            .json(info)
+            .build());
+    }
+
+    let text = reply.get("text").and_then(|v| v.as_str()).unwrap_or("");
+    let max_chars = max_chars.unwrap_or(DEFAULT_MAX_CHARS);
+    let total_chars = text.chars().count();
+    let truncated = total_chars > max_chars;
+    let text: String = text.chars().take(max_chars).collect();
+    Ok(ContentBuilder::text(
+        json!({
+            "kind": "text",
+            "text": text,
+            "chars": total_chars,
+            "truncated": truncated,
+        })
+        .to_string(),
+    ))
+}
+
+/// Put text or a PNG image on the user's clipboard, replacing what was there
+#[mcp_tool(
+    name = "write_clipboard",
+    when = "the user wants a result ready to paste into another app",
+    when = "you produced a command, snippet or image the user will paste elsewhere",
+    perfect_for = "handing results back to the desktop without the user selecting and copying from the chat",
+    destructive,
+    idempotent
+)]
+fn write_clipboard(
+    /// Text to copy
+    #[serde(default)]
+    text: Option<String>,
+    /// PNG image to copy, base64 encoded; give this or text, not both
+    #[serde(default)]
+    image_png: Option<String>,
+) -> Result<Value, ToolError> {
+    let kind = match (&text, &image_png) {
+        (Some(_), None) => "text",
+        (None, Some(_)) => "image",
+        _ => {
+            return Err(ToolError::invalid_argument(
+                "text",
+                "give exactly one of text or image_png",
+            ));
+        }
+    };
+    let reply = host_reply(unsafe {
+        clipboard_write(Json(json!({ "text": text, "image_png": image_png })))
+    })?;
+    Ok(json!({
+        "kind": kind,
+        "bytes": reply.get("bytes"),
+        "success": true,
+    }))
+}
+
+/// Create the plugin instance
+#[allow(dead_code)]
+fn plugin() -> McpPlugin<Ready> {
+    mcp_plugin("clipboard")
+        .description("Read from and write to the user's desktop clipboard, within the host's size and type policy")
+        .tool::<ReadClipboardTool>()
+        .tool::<WriteClipboardTool>()
+        .serve()
+}
+
+// Generate standard MCP entry points
+sweetmcp_plugin_builder::generate_mcp_functions!(plugin);
@@ -0,0 +1,92 @@
+//! Parsing and printing JSON, YAML and TOML
+//!
+//! Everything passes through `serde_json::Value`, so any format converts to
+//! any other as long as the target can express the data: TOML needs a table
+//! at the top and has no null, and its datetimes come out as strings.
+
+use serde::Deserialize;
+use serde_json::Value;
+use sweetmcp_plugin_builder::prelude::JsonSchema;
+
+/// Serialization format
+#[derive(Clone, Copy, PartialEq, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+#[schemars(crate = "sweetmcp_plugin_builder::schemars")]
+pub enum Format {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl Format {
+    pub fn name(self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Yaml => "yaml",
+            Format::Toml => "toml",
+        }
+    }
+}
+
+/// Parse `text` as `format`, or detect the format when none is given.
+/// Detection tries JSON, then TOML, then YAML, since YAML accepts nearly
+/// anything as a plain string.
+pub fn parse(text: &str, format: Option<Format>) -> Result<(Value, Format), String> {
+    if let Some(format) = format {
+        return parse_as(text, format).map(|value| (value, format));
+    }
+    for format in [Format::Json, Format::Toml, Format::Yaml] {
+        if let Ok(value) = parse_as(text, format) {
+            return Ok((value, format));
+        }
+    }
+    Err("data is not valid JSON, TOML or YAML".to_string())
+}
+
+fn parse_as(text: &str, format: Format) -> Result<Value, String> {
+    match format {
+        Format::Json => serde_json::from_str(text).map_err(|e| format!("invalid JSON: {}", e)),
+        Format::Yaml => serde_yaml::from_str(text).map_err(|e| format!("invalid YAML: {}", e)),
+        Format::Toml => text
+            .parse::<toml::Table>()
+            .map_err(|e| format!("invalid TOML: {}", e))
+            .and_then(|table| serde_json::to_value(table).map_err(|e| e.to_string())),
+    }
+}
+
+/// Print `value` as `format`. `pretty` only changes JSON, since YAML and
+/// TOML have a single block layout.
+pub fn render(value: &Value, format: Format, pretty: bool) -> Result<String, String> {
+    match format {
+        Format::Json if pretty => serde_json::to_string_pretty(value).map_err(|e| e.to_string()),
+        Format::Json => serde_json::to_string(value).map_err(|e| e.to_string()),
+        Format::Yaml => serde_yaml::to_string(value).map_err(|e| e.to_string()),
+        Format::Toml => {
+            if !value.is_object() {
+                return Err("TOML needs a table at the top level".to_string());
+            }
+            if let Some(path) = find_null(value, String::new()) {
+                return Err(format!(
+                    "TOML has no null; remove or replace the value at {}",
+                    if path.is_empty() { "." } else { &path }
+                ));
+            }
+            toml::to_string_pretty(value).map_err(|e| format!("cannot write TOML: {}", e))
+        }
+    }
+}
+
+/// jq-style path of the first null in `value`
+fn find_null(value: &Value, path: String) -> Option<String> {
+    match value {
+        Value::Null => Some(path),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .find_map(|(i, item)| find_null(item, format!("{}[{}]", path, i))),
+        Value::Object(map) => map
+            .iter()
+            .find_map(|(key, item)| find_null(item, format!("{}.{}", path, key))),
+        _ => None,
+    }
+}
@@ -0,0 +1,147 @@
+mod formats;
+mod query;
+
+use extism_pdk::*;
+use serde_json::{Value, json};
+use sweetmcp_plugin_builder::Ready;
+use sweetmcp_plugin_builder::prelude::*;
+
+use crate::formats::Format;
+
+/// Convert structured data between JSON, YAML and TOML
+#[mcp_tool(
+    name = "convert_data",
+    when = "you need to turn a YAML or TOML config into JSON, or back",
+    when = "you need to rewrite data in another format without hand-translating the syntax",
+    perfect_for = "Kubernetes manifests, Cargo.toml and pyproject.toml edits, CI configs and API payloads",
+    read_only,
+    idempotent
+)]
+fn convert_data(
+    /// Data to convert
+    data: String,
+    /// Format of data; detected when omitted
+    #[serde(default)]
+    from: Option<Format>,
+    /// Format to produce
+    to: Format,
+) -> Result<String, ToolError> {
+    let (value, _) =
+        formats::parse(&data, from).map_err(|e| ToolError::invalid_argument("data", e))?;
+    formats::render(&value, to, true).map_err(|e| ToolError::invalid_argument("to", e))
+}
+
+/// Run a jq query over JSON, YAML or TOML data
+#[mcp_tool(
+    name = "query_data",
+    when = "you need to pick fields out of a large JSON, YAML or TOML document",
+    when = "you need to filter, map or reshape data with jq syntax",
+    perfect_for = "extracting values from API responses and config files exactly, e.g. '.dependencies | keys'",
+    read_only,
+    idempotent
+)]
+fn query_data(
+    /// Data to query
+    data: String,
+    /// jq filter, e.g. ".items[] | select(.enabled) | .name"
+    query: String,
+    /// Format of data; detected when omitted
+    #[serde(default)]
+    from: Option<Format>,
+    /// Format of the results (default json)
+    #[serde(default)]
+    to: Option<Format>,
+) -> Result<String, ToolError> {
+    let (value, _) =
+        formats::parse(&data, from).map_err(|e| ToolError::invalid_argument("data", e))?;
+    let mut results =
+        query::run(&query, value).map_err(|e| ToolError::invalid_argument("query", e))?;
+    // A single result stands alone; several come back as an array
+    let output = if results.len() == 1 {
+        results.remove(0)
+    } else {
+        Value::Array(results)
+    };
+    formats::render(&output, to.unwrap_or(Format::Json), true)
+        .map_err(|e| ToolError::invalid_argument("to", e))
+}
+
+/// Validate data against a JSON Schema
+#[mcp_tool(
+    name = "validate_data",
+    when = "you need to check a config or payload against a JSON Schema before using it",
+    when = "you need every schema violation listed with where it occurs",
+    perfect_for = "checking generated configs and API requests; the schema may be JSON or YAML",
+    read_only,
+    idempotent
+)]
+fn validate_data(
+    /// Data to validate
+    data: String,
+    /// JSON Schema, as JSON or YAML text; remote $ref is not followed
+    schema: String,
+    /// Format of data; detected when omitted
+    #[serde(default)]
+    from: Option<Format>,
+) -> Result<Value, ToolError> {
+    let (value, format) =
+        formats::parse(&data, from).map_err(|e| ToolError::invalid_argument("data", e))?;
+    let (schema, _) =
+        formats::parse(&schema, None).map_err(|e| ToolError::invalid_argument("schema", e))?;
+    let validator = jsonschema::validator_for(&schema)
+        .map_err(|e| ToolError::invalid_argument("schema", format!("invalid schema: {}", e)))?;
+
+    let errors: Vec<Value> = validator
+        .iter_errors(&value)
+        .map(|error| {
+            json!({
+                "path": error.instance_path.to_string(),
+                "message": error.to_string(),
+            })
+        })
+        .collect();
+    Ok(json!({
+        "valid": errors.is_empty(),
+        "format": format.name(),
+        "errors": errors,
+    }))
+}
+
+/// Pretty-print or minify JSON, or normalise YAML and TOML layout
+#[mcp_tool(
+    name = "format_data",
+    when = "you need minified JSON for a payload or pretty JSON for reading",
+    when = "you need to check that a JSON, YAML or TOML document parses",
+    perfect_for = "tidying config files and shrinking payloads; comments are not kept",
+    read_only,
+    idempotent
+)]
+fn format_data(
+    /// Data to format
+    data: String,
+    /// Format of data; detected when omitted, and kept for the output
+    #[serde(default)]
+    from: Option<Format>,
+    /// Minify instead of pretty-printing (JSON only)
+    #[serde(default)]
+    minify: bool,
+) -> Result<String, ToolError> {
+    let (value, format) =
+        formats::parse(&data, from).map_err(|e| ToolError::invalid_argument("data", e))?;
+    formats::render(&value, format, !minify).map_err(ToolError::internal)
+}
+
+/// Create the plugin instance
+#[allow(dead_code)]
+fn plugin() -> McpPlugin<Ready> {
+    mcp_plugin("data")
+        .description("Convert, query, validate and format JSON, YAML and TOML data")
+        .tool::<ConvertDataTool>()
+        .tool::<QueryDataTool>()
+        .tool::<ValidateDataTool>()
+        .tool::<FormatDataTool>()
+        .serve()
+}
+
+// Generate standard MCP entry points
+sweetmcp_plugin_builder::generate_mcp_functions!(plugin);
@@ -0,0 +1,41 @@
+//! jq queries, run by jaq
+//!
+//! The query is compiled with jaq's standard library and JSON functions, so
+//! the usual jq filters (`.a.b`, `map`, `select`, `to_entries`, ...) work. A
+//! query can yield any number of results; all of them are collected.
+
+use jaq_core::load::{Arena, File, Loader};
+use jaq_core::{Compiler, Ctx, RcIter};
+use jaq_json::Val;
+use serde_json::Value;
+
+/// Most results collected from one query
+const MAX_RESULTS: usize = 10_000;
+
+/// Run the jq `query` over `input`, returning every result
+pub fn run(query: &str, input: Value) -> Result<Vec<Value>, String> {
+    let program = File {
+        code: query,
+        path: (),
+    };
+    let loader = Loader::new(jaq_std::defs().chain(jaq_json::defs()));
+    let arena = Arena::default();
+    let modules = loader
+        .load(&arena, program)
+        .map_err(|errors| format!("invalid query: {:?}", errors))?;
+    let filter = Compiler::default()
+        .with_funs(jaq_std::funs().chain(jaq_json::funs()))
+        .compile(modules)
+        .map_err(|errors| format!("invalid query: {:?}", errors))?;
+
+    let inputs = RcIter::new(core::iter::empty());
+    let mut results = Vec::new();
+    for output in filter.run((Ctx::new([], &inputs), Val::from(input))) {
+        if results.len() == MAX_RESULTS {
+            return Err(format!("query produced more than {} results", MAX_RESULTS));
+        }
+        let value = output.map_err(|e| format!("query failed: {}", e))?;
+        results.push(Value::from(value));
+    }
+    Ok(results)
+}
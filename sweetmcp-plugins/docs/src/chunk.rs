@@ -0,0 +1,153 @@
+//! Document loading and chunking
+//!
+//! Documents are split on paragraph boundaries into chunks of roughly
+//! `max_chars` characters so that each chunk can be cited by its line range.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use extism_pdk::Error;
+
+/// File extensions the indexer understands
+const TEXT_EXTENSIONS: &[&str] = &["md", "markdown", "txt", "rst"];
+const PDF_EXTENSIONS: &[&str] = &["pdf"];
+
+/// A citable slice of a document
+#[derive(Debug, Clone)]
+pub struct Chunk {
+    /// Path of the source document
+    pub source: String,
+    /// Position of this chunk within the document
+    pub index: usize,
+    /// First line covered by the chunk (1-based)
+    pub line_start: usize,
+    /// Last line covered by the chunk (1-based, inclusive)
+    pub line_end: usize,
+    /// Chunk text
+    pub text: String,
+}
+
+impl Chunk {
+    /// Stable identifier so re-indexing a document overwrites its chunks
+    pub fn id(&self) -> String {
+        format!("docs:{}#{}", self.source, self.index)
+    }
+}
+
+/// Recursively collect indexable documents beneath `root`
+pub fn collect_documents(root: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut documents = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let entries = fs::read_dir(&dir)
+            .map_err(|e| Error::msg(format!("Failed to read {}: {}", dir.display(), e)))?;
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+
+            if file_type.is_dir() {
+                let hidden = entry.file_name().to_string_lossy().starts_with('.');
+                if !hidden {
+                    pending.push(path);
+                }
+            } else if file_type.is_file() && is_supported(&path) {
+                documents.push(path);
+            }
+        }
+    }
+
+    documents.sort();
+    Ok(documents)
+}
+
+/// Load a document as plain text
+pub fn load_document(path: &Path) -> Result<String, Error> {
+    if has_extension(path, PDF_EXTENSIONS) {
+        let bytes = fs::read(path)
+            .map_err(|e| Error::msg(format!("Failed to read {}: {}", path.display(), e)))?;
+        pdf_extract::extract_text_from_mem(&bytes).map_err(|e| {
+            Error::msg(format!(
+                "Failed to extract text from {}: {}",
+                path.display(),
+                e
+            ))
+        })
+    } else {
+        fs::read_to_string(path)
+            .map_err(|e| Error::msg(format!("Failed to read {}: {}", path.display(), e)))
+    }
+}
+
+/// Split a document into paragraph-aligned chunks
+pub fn chunk_document(source: &str, text: &str, max_chars: usize) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut line_start = 1;
+    let mut line_end = 0;
+
+    for (offset, line) in text.lines().enumerate() {
+        let line_number = offset + 1;
+        let paragraph_break = line.trim().is_empty();
+
+        if paragraph_break && current.len() >= max_chars {
+            push_chunk(&mut chunks, source, &mut current, line_start, line_end);
+            line_start = line_number + 1;
+            continue;
+        }
+
+        if current.is_empty() && paragraph_break {
+            line_start = line_number + 1;
+            continue;
+        }
+
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        current.push_str(line);
+        line_end = line_number;
+
+        // Hard split for documents without paragraph breaks
+        if current.len() >= max_chars * 2 {
+            push_chunk(&mut chunks, source, &mut current, line_start, line_end);
+            line_start = line_number + 1;
+        }
+    }
+
+    push_chunk(&mut chunks, source, &mut current, line_start, line_end);
+    chunks
+}
+
+fn push_chunk(
+    chunks: &mut Vec<Chunk>,
+    source: &str,
+    current: &mut String,
+    line_start: usize,
+    line_end: usize,
+) {
+    let text = current.trim();
+    if !text.is_empty() {
+        chunks.push(Chunk {
+            source: source.to_string(),
+            index: chunks.len(),
+            line_start,
+            line_end,
+            text: text.to_string(),
+        });
+    }
+    current.clear();
+}
+
+fn is_supported(path: &Path) -> bool {
+    has_extension(path, TEXT_EXTENSIONS) || has_extension(path, PDF_EXTENSIONS)
+}
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| extensions.iter().any(|e| ext.eq_ignore_ascii_case(e)))
+        .unwrap_or(false)
+}
@@ -0,0 +1,200 @@
+mod chunk;
+mod store;
+
+use std::path::Path;
+
+use extism_pdk::*;
+use serde_json::{Value, json};
+use sweetmcp_plugin_builder::prelude::*;
+use sweetmcp_plugin_builder::{CallToolResult, Ready};
+
+/// Default characters per chunk when `chunk_size` is not configured
+const DEFAULT_CHUNK_SIZE: usize = 1200;
+/// Default number of snippets returned by `ask_docs`
+const DEFAULT_TOP_K: usize = 5;
+/// Upper bound on snippets per question
+const MAX_TOP_K: usize = 50;
+
+/// Index the configured documents directory into the memory store
+struct IndexDocsTool;
+
+impl McpTool for IndexDocsTool {
+    const NAME: &'static str = "index_docs";
+    type Args = Value;
+
+    fn description(builder: DescriptionBuilder) -> DescriptionBuilder {
+        builder
+            .does("Index the configured documents directory (markdown, text and PDF) into the local memory store")
+            .when("you are about to ask questions over local documents for the first time")
+            .when("documents in the knowledge base have been added or changed")
+            .requires("The `docs_dir` plugin config must point at a directory in the plugin's allowed_paths")
+            .perfect_for("preparing a local knowledge base for retrieval-augmented answers")
+    }
+
+    fn schema(builder: SchemaBuilder) -> Value {
+        builder
+            .optional_string(
+                "path",
+                "Subdirectory of the configured docs_dir to index (defaults to all of it)",
+            )
+            .build()
+    }
+
+    fn execute(args: Value) -> Result<CallToolResult, Error> {
+        let Some(docs_dir) = config::get("docs_dir")? else {
+            return Ok(ContentBuilder::error(
+                "docs_dir is not configured for the docs plugin",
+            ));
+        };
+
+        let root = match args.get("path").and_then(|v| v.as_str()) {
+            Some(sub) if Path::new(sub).is_absolute() || sub.contains("..") => {
+                return Ok(ContentBuilder::error(ToolError::invalid_argument(
+                    "path",
+                    format!("path must be relative to docs_dir: {}", sub),
+                )));
+            }
+            Some(sub) => Path::new(&docs_dir).join(sub),
+            None => Path::new(&docs_dir).to_path_buf(),
+        };
+
+        let chunk_size = config::get("chunk_size")?
+            .and_then(|v| v.parse::<usize>().ok())
+            .filter(|size| *size > 0)
+            .unwrap_or(DEFAULT_CHUNK_SIZE);
+        let base_url = store::memory_url();
+
+        let documents = chunk::collect_documents(&root)?;
+        let mut indexed = Vec::new();
+        let mut failures = Vec::new();
+        let mut chunk_total = 0;
+
+        for path in documents {
+            let source = path.to_string_lossy().to_string();
+            let text = match chunk::load_document(&path) {
+                Ok(text) => text,
+                Err(e) => {
+                    failures.push(json!({ "source": source, "error": e.to_string() }));
+                    continue;
+                }
+            };
+
+            let chunks = chunk::chunk_document(&source, &text, chunk_size);
+            let mut stored = 0;
+            for chunk in &chunks {
+                match store::store_chunk(&base_url, chunk) {
+                    Ok(()) => stored += 1,
+                    Err(e) => {
+                        failures.push(json!({ "source": source, "error": e.to_string() }));
+                        break;
+                    }
+                }
+            }
+
+            chunk_total += stored;
+            indexed.push(json!({ "source": source, "chunks": stored }));
+        }
+
+        Ok(ContentBuilder::text(
+            json!({
+                "root": root.to_string_lossy(),
+                "documents": indexed.len(),
+                "chunks": chunk_total,
+                "indexed": indexed,
+                "failures": failures,
+            })
+            .to_string(),
+        ))
+    }
+}
+
+/// Answer questions with cited snippets from indexed documents
+struct AskDocsTool;
+
+impl McpTool for AskDocsTool {
+    const NAME: &'static str = "ask_docs";
+    type Args = Value;
+
+    fn description(builder: DescriptionBuilder) -> DescriptionBuilder {
+        builder
+            .does("Retrieve the most relevant passages from the indexed local documents, each with a citation to its source file and line range")
+            .when("you need to answer a question from the user's own documentation or notes")
+            .when("you need to quote or cite local markdown, text or PDF documents")
+            .when("you want grounding material before answering a project-specific question")
+            .requires("Documents must have been indexed with `index_docs`")
+            .not_for("searching the web or documents outside the configured docs_dir")
+            .perfect_for("local retrieval-augmented generation, documentation Q&A, and citing internal knowledge bases")
+    }
+
+    fn schema(builder: SchemaBuilder) -> Value {
+        builder
+            .required_string("question", "Question to answer from the indexed documents")
+            .optional_integer(
+                "top_k",
+                "Number of snippets to return",
+                Some(1),
+                Some(MAX_TOP_K as i64),
+            )
+            .default_value("top_k", DEFAULT_TOP_K)
+            .build()
+    }
+
+    fn execute(args: Value) -> Result<CallToolResult, Error> {
+        let question = args
+            .get("question")
+            .and_then(|v| v.as_str())
+            .filter(|q| !q.trim().is_empty())
+            .ok_or_else(|| Error::msg("question parameter required"))?;
+
+        let top_k = args
+            .get("top_k")
+            .and_then(|v| v.as_u64())
+            .map(|k| (k as usize).clamp(1, MAX_TOP_K))
+            .unwrap_or(DEFAULT_TOP_K);
+
+        // Over-fetch because the store may hold memories that are not doc chunks
+        let mut results = store::search_chunks(&store::memory_url(), question, top_k * 4)?;
+        results.truncate(top_k);
+
+        let snippets: Vec<Value> = results
+            .iter()
+            .enumerate()
+            .map(|(i, chunk)| {
+                let citation = match (chunk.line_start(), chunk.line_end()) {
+                    (Some(start), Some(end)) => format!("{}:{}-{}", chunk.source(), start, end),
+                    _ => chunk.source().to_string(),
+                };
+                json!({
+                    "rank": i + 1,
+                    "citation": citation,
+                    "source": chunk.source(),
+                    "line_start": chunk.line_start(),
+                    "line_end": chunk.line_end(),
+                    "text": chunk.content,
+                })
+            })
+            .collect();
+
+        Ok(ContentBuilder::text(
+            json!({
+                "question": question,
+                "count": snippets.len(),
+                "snippets": snippets,
+            })
+            .to_string(),
+        ))
+    }
+}
+
+/// Create the plugin instance
+#[allow(dead_code)]
+fn plugin() -> McpPlugin<Ready> {
+    mcp_plugin("docs")
+        .description("Local knowledge-base retrieval over markdown, text and PDF documents")
+        .tool::<IndexDocsTool>()
+        .tool::<AskDocsTool>()
+        .serve()
+}
+
+// Generate standard MCP entry points
+sweetmcp_plugin_builder::generate_mcp_functions!(plugin);
@@ -0,0 +1,145 @@
+//! Client for the sweetmcp-memory HTTP API
+//!
+//! WASM plugins cannot link the memory crate directly, so chunks are stored and
+//! retrieved through the memory API server (`/api/memories`).
+
+use extism_pdk::*;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::chunk::Chunk;
+
+/// Default memory API endpoint when `memory_url` is not configured
+const DEFAULT_MEMORY_URL: &str = "http://127.0.0.1:8000";
+
+/// A stored chunk returned by a search
+#[derive(Debug, Deserialize)]
+pub struct StoredChunk {
+    pub id: String,
+    pub content: String,
+    #[serde(default)]
+    pub metadata: StoredMetadata,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct StoredMetadata {
+    #[serde(default)]
+    pub custom: Value,
+}
+
+impl StoredChunk {
+    fn custom(&self, key: &str) -> Option<&str> {
+        self.metadata.custom.get(key).and_then(|v| v.as_str())
+    }
+
+    /// Whether this memory was written by the docs indexer
+    pub fn is_doc_chunk(&self) -> bool {
+        self.custom("kind") == Some("docs")
+    }
+
+    pub fn source(&self) -> &str {
+        self.custom("source").unwrap_or(&self.id)
+    }
+
+    pub fn line_start(&self) -> Option<u64> {
+        self.custom("line_start").and_then(|v| v.parse().ok())
+    }
+
+    pub fn line_end(&self) -> Option<u64> {
+        self.custom("line_end").and_then(|v| v.parse().ok())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    results: Vec<StoredChunk>,
+}
+
+/// Base URL of the memory API, taken from plugin config
+pub fn memory_url() -> String {
+    config::get("memory_url")
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| DEFAULT_MEMORY_URL.to_string())
+        .trim_end_matches('/')
+        .to_string()
+}
+
+/// Store a chunk, replacing any previous version with the same id
+pub fn store_chunk(base_url: &str, chunk: &Chunk) -> Result<(), Error> {
+    let id = chunk.id();
+
+    // Deleting first keeps re-indexing idempotent; a 404 simply means it is new
+    let delete = HttpRequest::new(format!("{}/api/memories/{}", base_url, urlencode(&id)))
+        .with_method("DELETE");
+    http::request::<()>(&delete, None)?;
+
+    let body = json!({
+        "id": id,
+        "content": chunk.text,
+        "memory_type": "semantic",
+        "metadata": {
+            "kind": "docs",
+            "source": chunk.source,
+            "chunk": chunk.index.to_string(),
+            "line_start": chunk.line_start.to_string(),
+            "line_end": chunk.line_end.to_string(),
+        }
+    });
+
+    let request = HttpRequest::new(format!("{}/api/memories", base_url))
+        .with_method("POST")
+        .with_header("Content-Type", "application/json");
+    let response = http::request::<String>(&request, Some(body.to_string()))?;
+
+    if response.status_code() >= 400 {
+        return Err(Error::msg(format!(
+            "Memory API rejected chunk {} (HTTP {}): {}",
+            chunk.id(),
+            response.status_code(),
+            String::from_utf8_lossy(&response.body())
+        )));
+    }
+
+    Ok(())
+}
+
+/// Search stored document chunks
+pub fn search_chunks(base_url: &str, question: &str, limit: usize) -> Result<Vec<StoredChunk>, Error> {
+    let body = json!({
+        "query": question,
+        "limit": limit,
+    });
+
+    let request = HttpRequest::new(format!("{}/api/memories/search", base_url))
+        .with_method("POST")
+        .with_header("Content-Type", "application/json");
+    let response = http::request::<String>(&request, Some(body.to_string()))?;
+
+    if response.status_code() >= 400 {
+        return Err(Error::msg(format!(
+            "Memory API search failed (HTTP {}): {}",
+            response.status_code(),
+            String::from_utf8_lossy(&response.body())
+        )));
+    }
+
+    let parsed: SearchResponse = response.json()?;
+    Ok(parsed
+        .results
+        .into_iter()
+        .filter(StoredChunk::is_doc_chunk)
+        .collect())
+}
+
+fn urlencode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
@@ -50,6 +50,7 @@ struct ShellTool;
 
 impl McpTool for ShellTool {
     const NAME: &'static str = "eval_shell";
+    type Args = Value;
 
     fn description(builder: DescriptionBuilder) -> DescriptionBuilder {
         builder
@@ -258,6 +258,7 @@ impl ContentFetcher for BevyRenderer {
             content: cleaned_html,
             screenshot_base64,
             content_type: "text/html".to_string(),
+            body: None,
         })
     }
 }
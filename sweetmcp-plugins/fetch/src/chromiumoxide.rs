@@ -7,6 +7,9 @@ use std::error::Error as StdError;
 use std::fmt;
 use std::time::Duration;
 
+use crate::cookies::{Cookie, SharedJar};
+use crate::proxy::Proxy;
+
 #[derive(Debug)]
 pub enum ChromiumFetchError {
     Browser(String),
@@ -34,13 +37,91 @@ pub struct FetchResult {
     pub content: String,
     pub screenshot_base64: String,
     pub content_type: String,
+    /// Raw body of a PDF or image response, which `content` can't carry
+    pub body: Option<Vec<u8>>,
+    /// HTTP status of the response, when the backend can tell
+    pub status: Option<u16>,
+}
+
+/// Default cap on a response body, in bytes
+pub const DEFAULT_MAX_BYTES: usize = 10 * 1024 * 1024;
+
+/// Default time a request may take, from connecting to the last byte
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Whether a response of `content_type` is a document to convert rather than a page
+pub fn is_binary_content(content_type: &str) -> bool {
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    essence == "application/pdf" || essence.starts_with("image/")
+}
+
+/// What to fetch: a URL and how to request it
+#[derive(Debug, Clone)]
+pub struct FetchRequest {
+    pub url: String,
+    /// Upper-case HTTP method
+    pub method: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+    /// Content-Type of `body`, unless `headers` already set one
+    pub content_type: Option<String>,
+    /// Largest response body accepted, in bytes
+    pub max_bytes: usize,
+    pub proxy: Option<Proxy>,
+    /// Accept any TLS certificate, e.g. one re-signed by a corporate proxy
+    pub insecure_tls: bool,
+    pub timeout: Duration,
+    /// Session cookie jar to send from and fill
+    pub cookies: Option<SharedJar>,
+}
+
+impl FetchRequest {
+    /// A plain GET of `url` with default limits and no proxy
+    pub fn get(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            method: "GET".to_string(),
+            headers: Vec::new(),
+            body: None,
+            content_type: None,
+            max_bytes: DEFAULT_MAX_BYTES,
+            proxy: None,
+            insecure_tls: false,
+            timeout: DEFAULT_TIMEOUT,
+            cookies: None,
+        }
+    }
+
+    /// Whether this is a GET with nothing added to it
+    pub fn is_plain_get(&self) -> bool {
+        self.method == "GET" && self.headers.is_empty() && self.body.is_none()
+    }
+
+    /// Headers to send, with `content_type` added when a body needs one
+    pub fn all_headers(&self) -> Vec<(String, String)> {
+        let mut headers = self.headers.clone();
+        let has_content_type = headers
+            .iter()
+            .any(|(name, _)| name.eq_ignore_ascii_case("content-type"));
+        if let (Some(_), Some(content_type), false) =
+            (&self.body, &self.content_type, has_content_type)
+        {
+            headers.push(("Content-Type".to_string(), content_type.clone()));
+        }
+        headers
+    }
 }
 
 #[async_trait]
 pub trait ContentFetcher {
     async fn fetch_content(
         &self,
-        url: &str,
+        request: &FetchRequest,
     ) -> Result<FetchResult, Box<dyn StdError + Send + Sync>>;
 }
 
@@ -48,7 +129,7 @@ pub struct ChromiumFetcher;
 
 impl ChromiumFetcher {
     // Create a new browser instance
-    async fn create_browser() -> Result<Browser, ChromiumFetchError> {
+    async fn create_browser(request: &FetchRequest) -> Result<Browser, ChromiumFetchError> {
         let viewport = Viewport {
             width: 1280,
             height: 800,
@@ -58,12 +139,22 @@ impl ChromiumFetcher {
             has_touch: false,
         };
 
-        let config = BrowserConfig::builder()
-            .viewport(viewport)
-            .build()
-            .map_err(|e| {
-                ChromiumFetchError::Browser(format!("Failed to build browser config: {}", e))
-            })?;
+        let mut config = BrowserConfig::builder().viewport(viewport);
+        if let Some(proxy) = &request.proxy {
+            // Chrome takes no credentials on its command line; hyper can use them
+            if proxy.credentials.is_some() {
+                return Err(ChromiumFetchError::Browser(
+                    "The headless browser can't authenticate to a proxy".to_string(),
+                ));
+            }
+            config = config.arg(format!("--proxy-server={}", proxy.server()));
+        }
+        if request.insecure_tls {
+            config = config.arg("--ignore-certificate-errors");
+        }
+        let config = config.build().map_err(|e| {
+            ChromiumFetchError::Browser(format!("Failed to build browser config: {}", e))
+        })?;
 
         let (browser, mut handler) = Browser::launch(config)
             .await
@@ -95,6 +186,144 @@ impl ChromiumFetcher {
         Ok(screenshot_base64)
     }
 
+    // Make the page's next document request use the caller's method, headers
+    // and body. Only that request is changed, so the headers (which may carry
+    // credentials) aren't sent to the other origins the page loads from.
+    async fn override_navigation(
+        page: &Page,
+        request: &FetchRequest,
+    ) -> Result<(), ChromiumFetchError> {
+        use chromiumoxide::cdp::browser_protocol::fetch::{
+            ContinueRequestParams, DisableParams, EnableParams, EventRequestPaused, HeaderEntry,
+            RequestPattern, RequestStage,
+        };
+        use chromiumoxide::cdp::browser_protocol::network::ResourceType;
+
+        let mut paused = page
+            .event_listener::<EventRequestPaused>()
+            .await
+            .map_err(|e| ChromiumFetchError::Browser(format!("Failed to watch requests: {}", e)))?;
+        let pattern = RequestPattern::builder()
+            .url_pattern("*")
+            .resource_type(ResourceType::Document)
+            .request_stage(RequestStage::Request)
+            .build();
+        page.execute(EnableParams::builder().pattern(pattern).build())
+            .await
+            .map_err(|e| {
+                ChromiumFetchError::Browser(format!("Failed to intercept requests: {}", e))
+            })?;
+
+        let page = page.clone();
+        let method = request.method.clone();
+        let extra = request.all_headers();
+        let body = request.body.clone();
+        tokio::spawn(async move {
+            let Some(event) = paused.next().await else {
+                return;
+            };
+            // Keep the browser's own headers unless the caller replaces them
+            let mut headers: Vec<HeaderEntry> = event
+                .request
+                .headers
+                .inner()
+                .as_object()
+                .into_iter()
+                .flatten()
+                .filter(|(name, _)| !extra.iter().any(|(n, _)| n.eq_ignore_ascii_case(name)))
+                .filter_map(|(name, value)| Some(HeaderEntry::new(name.clone(), value.as_str()?)))
+                .collect();
+            headers.extend(
+                extra
+                    .iter()
+                    .map(|(name, value)| HeaderEntry::new(name.clone(), value.clone())),
+            );
+            let mut params = ContinueRequestParams::builder()
+                .request_id(event.request_id.clone())
+                .method(method)
+                .headers(headers);
+            if let Some(body) = body {
+                params = params.post_data(base64::engine::general_purpose::STANDARD.encode(body));
+            }
+            if let Ok(params) = params.build() {
+                let _ = page.execute(params).await;
+            }
+            let _ = page.execute(DisableParams::default()).await;
+        });
+        Ok(())
+    }
+
+    // Give the browser the session's cookies before it navigates
+    async fn load_cookies(page: &Page, jar: &SharedJar) -> Result<(), ChromiumFetchError> {
+        use chromiumoxide::cdp::browser_protocol::network::{CookieParam, TimeSinceEpoch};
+
+        let cookies: Vec<Cookie> = jar
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .cookies()
+            .cloned()
+            .collect();
+        let params: Vec<CookieParam> = cookies
+            .into_iter()
+            .filter_map(|cookie| {
+                let mut param = CookieParam::builder()
+                    .name(cookie.name)
+                    .value(cookie.value)
+                    .path(cookie.path.clone())
+                    .secure(cookie.secure);
+                // A domain would widen a host-only cookie to the subdomains
+                param = if cookie.host_only {
+                    param.url(format!("https://{}{}", cookie.domain, cookie.path))
+                } else {
+                    param.domain(format!(".{}", cookie.domain))
+                };
+                if let Some(expires) = cookie.expires {
+                    param = param.expires(TimeSinceEpoch::new(expires as f64));
+                }
+                param.build().ok()
+            })
+            .collect();
+        if params.is_empty() {
+            return Ok(());
+        }
+        page.set_cookies(params)
+            .await
+            .map_err(|e| ChromiumFetchError::Browser(format!("Failed to set cookies: {}", e)))?;
+        Ok(())
+    }
+
+    // Keep the cookies the page holds after loading
+    async fn save_cookies(page: &Page, jar: &SharedJar) -> Result<(), ChromiumFetchError> {
+        let cookies = page
+            .get_cookies()
+            .await
+            .map_err(|e| ChromiumFetchError::Browser(format!("Failed to read cookies: {}", e)))?;
+        let mut jar = jar.lock().unwrap_or_else(|e| e.into_inner());
+        for cookie in cookies {
+            jar.insert(Cookie {
+                host_only: !cookie.domain.starts_with('.'),
+                domain: cookie.domain.trim_start_matches('.').to_ascii_lowercase(),
+                name: cookie.name,
+                value: cookie.value,
+                path: cookie.path,
+                secure: cookie.secure,
+                expires: (!cookie.session).then_some(cookie.expires as i64),
+            });
+        }
+        Ok(())
+    }
+
+    // HTTP status of the page's document, from the Navigation Timing entry
+    async fn navigation_status(page: &Page) -> Option<u16> {
+        let js = "performance.getEntriesByType('navigation')[0]?.responseStatus || null";
+        page.evaluate(js)
+            .await
+            .ok()?
+            .into_value::<Option<u16>>()
+            .ok()
+            .flatten()
+    }
+
     // Get page content with scripts and styles removed
     async fn get_cleaned_content(page: &Page) -> Result<String, ChromiumFetchError> {
         // Execute JavaScript to get HTML content with script and style tags removed
@@ -103,8 +332,8 @@ impl ChromiumFetcher {
             // Clone the document body to avoid modifying the actual page
             const clone = document.documentElement.cloneNode(true);
             
-            // Remove script and style tags
-            const scripts = clone.querySelectorAll("script");
+            // Remove script and style tags, keeping JSON-LD data
+            const scripts = clone.querySelectorAll('script:not([type="application/ld+json"])');
             scripts.forEach(script => script.remove());
             
             const styles = clone.querySelectorAll("style");
@@ -135,10 +364,12 @@ impl ChromiumFetcher {
 impl ContentFetcher for ChromiumFetcher {
     async fn fetch_content(
         &self,
-        url: &str,
+        request: &FetchRequest,
     ) -> Result<FetchResult, Box<dyn StdError + Send + Sync>> {
+        let url = request.url.as_str();
+
         // Launch browser
-        let mut browser = Self::create_browser().await?;
+        let mut browser = Self::create_browser(request).await?;
 
         // Create a new page
         let page = browser
@@ -146,8 +377,15 @@ impl ContentFetcher for ChromiumFetcher {
             .await
             .map_err(|e| ChromiumFetchError::Browser(format!("Failed to create page: {}", e)))?;
 
+        if !request.is_plain_get() {
+            Self::override_navigation(&page, request).await?;
+        }
+        if let Some(jar) = &request.cookies {
+            Self::load_cookies(&page, jar).await?;
+        }
+
         // Navigate to the URL with a timeout
-        let navigation_result = tokio::time::timeout(Duration::from_secs(30), page.goto(url)).await;
+        let navigation_result = tokio::time::timeout(request.timeout, page.goto(url)).await;
 
         // Check for timeout or navigation error
         match navigation_result {
@@ -167,6 +405,12 @@ impl ContentFetcher for ChromiumFetcher {
         // Wait for page to be fully loaded
         tokio::time::sleep(Duration::from_secs(2)).await;
 
+        if let Some(jar) = &request.cookies {
+            Self::save_cookies(&page, jar).await?;
+        }
+
+        let status = Self::navigation_status(&page).await;
+
         // Take screenshot
         let screenshot_base64 = Self::take_screenshot(&page).await?;
 
@@ -186,6 +430,8 @@ impl ContentFetcher for ChromiumFetcher {
             content,
             screenshot_base64,
             content_type,
+            body: None,
+            status,
         })
     }
 }
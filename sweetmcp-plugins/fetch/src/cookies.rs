@@ -0,0 +1,277 @@
+//! Named cookie jars behind `fetch`'s `session` argument.
+//!
+//! A jar is kept in a plugin var, so it lives as long as the host keeps the
+//! plugin instance: consecutive fetches of one conversation naming the same
+//! session send back the cookies earlier responses set, e.g. after a login.
+//! Both the HTTP fetcher and the headless browser read and fill the jar.
+//! `clear_session` drops it. Matching follows RFC 6265 on domain, path,
+//! `Secure` and expiry, without a public suffix list.
+
+use std::sync::{Arc, Mutex};
+
+use extism_pdk::*;
+use serde::{Deserialize, Serialize};
+use sweetmcp_plugin_builder::CallToolResult;
+use sweetmcp_plugin_builder::prelude::*;
+use url::Url;
+
+/// A jar shared between the fetchers of one call
+pub type SharedJar = Arc<Mutex<CookieJar>>;
+
+/// Most cookies a jar keeps; the oldest go first
+const MAX_COOKIES: usize = 300;
+
+const MAX_SESSION_NAME: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cookie {
+    pub name: String,
+    pub value: String,
+    /// Lower-case host, without a leading dot
+    pub domain: String,
+    /// Sent only to `domain` itself rather than to its subdomains too
+    pub host_only: bool,
+    pub path: String,
+    pub secure: bool,
+    /// Unix time it expires at; `None` lasts as long as the jar
+    pub expires: Option<i64>,
+}
+
+impl Cookie {
+    fn expired(&self, now: i64) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+
+    fn matches(&self, url: &Url) -> bool {
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+        let host = host.to_ascii_lowercase();
+        let domain_match = if self.host_only {
+            host == self.domain
+        } else {
+            domain_matches(&host, &self.domain)
+        };
+        domain_match
+            && path_matches(url.path(), &self.path)
+            && (!self.secure || url.scheme() == "https")
+    }
+
+    fn same_slot(&self, other: &Cookie) -> bool {
+        self.name == other.name && self.domain == other.domain && self.path == other.path
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CookieJar {
+    cookies: Vec<Cookie>,
+}
+
+impl CookieJar {
+    /// The jar of `session`, empty if it has none yet
+    pub fn load(session: &str) -> Result<Self, Error> {
+        let mut jar: CookieJar = var::get::<String>(var_key(session))?
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default();
+        let now = chrono::Utc::now().timestamp();
+        jar.cookies.retain(|cookie| !cookie.expired(now));
+        Ok(jar)
+    }
+
+    pub fn save(&self, session: &str) -> Result<(), Error> {
+        var::set(var_key(session), serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Forget `session`'s cookies; returns how many there were
+    pub fn clear(session: &str) -> Result<usize, Error> {
+        let count = Self::load(session)?.cookies.len();
+        var::remove(var_key(session))?;
+        Ok(count)
+    }
+
+    /// Unexpired cookies
+    pub fn cookies(&self) -> impl Iterator<Item = &Cookie> {
+        let now = chrono::Utc::now().timestamp();
+        self.cookies
+            .iter()
+            .filter(move |cookie| !cookie.expired(now))
+    }
+
+    /// Value of the `Cookie` header to send to `url`, longest paths first
+    pub fn header_for(&self, url: &Url) -> Option<String> {
+        let mut matching: Vec<&Cookie> = self.cookies().filter(|c| c.matches(url)).collect();
+        if matching.is_empty() {
+            return None;
+        }
+        matching.sort_by_key(|cookie| std::cmp::Reverse(cookie.path.len()));
+        let pairs: Vec<String> = matching
+            .iter()
+            .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+            .collect();
+        Some(pairs.join("; "))
+    }
+
+    /// Keep `cookie`, replacing one of the same name, domain and path; an
+    /// expired cookie deletes that one instead
+    pub fn insert(&mut self, cookie: Cookie) {
+        self.cookies.retain(|existing| !existing.same_slot(&cookie));
+        if cookie.expired(chrono::Utc::now().timestamp()) {
+            return;
+        }
+        self.cookies.push(cookie);
+        if self.cookies.len() > MAX_COOKIES {
+            let excess = self.cookies.len() - MAX_COOKIES;
+            self.cookies.drain(..excess);
+        }
+    }
+
+    /// Keep the cookie a `Set-Cookie` header of a response from `url` sets,
+    /// ignoring it if malformed or for another site
+    pub fn store_set_cookie(&mut self, url: &Url, header: &str) {
+        if let Some(cookie) = parse_set_cookie(url, header) {
+            self.insert(cookie);
+        }
+    }
+}
+
+/// Check a session name given by the caller
+pub fn validate_session(session: &str) -> Result<(), String> {
+    let valid = !session.is_empty()
+        && session.len() <= MAX_SESSION_NAME
+        && session
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "session must be 1 to {} letters, digits, '-', '_' or '.'",
+            MAX_SESSION_NAME
+        ))
+    }
+}
+
+fn var_key(session: &str) -> String {
+    format!("cookies:{}", session)
+}
+
+fn parse_set_cookie(url: &Url, header: &str) -> Option<Cookie> {
+    let host = url.host_str()?.to_ascii_lowercase();
+    let mut parts = header.split(';');
+    let (name, value) = parts.next()?.split_once('=')?;
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+
+    let mut cookie = Cookie {
+        name: name.to_string(),
+        value: value.trim().trim_matches('"').to_string(),
+        domain: host.clone(),
+        host_only: true,
+        path: default_path(url.path()),
+        secure: false,
+        expires: None,
+    };
+    let mut max_age = None;
+    for attribute in parts {
+        let (key, value) = match attribute.split_once('=') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => (attribute.trim(), ""),
+        };
+        match key.to_ascii_lowercase().as_str() {
+            "domain" if !value.is_empty() => {
+                let domain = value.trim_start_matches('.').to_ascii_lowercase();
+                // A cookie for a bare suffix like `com`, or for another site, is refused
+                if !domain.contains('.') || !domain_matches(&host, &domain) {
+                    return None;
+                }
+                cookie.domain = domain;
+                cookie.host_only = false;
+            }
+            "path" if value.starts_with('/') => cookie.path = value.to_string(),
+            "secure" => cookie.secure = true,
+            "expires" => cookie.expires = parse_expires(value).or(cookie.expires),
+            "max-age" => max_age = value.parse::<i64>().ok(),
+            _ => {}
+        }
+    }
+    // Max-Age wins over Expires
+    if let Some(max_age) = max_age {
+        cookie.expires = Some(if max_age <= 0 {
+            0
+        } else {
+            chrono::Utc::now().timestamp().saturating_add(max_age)
+        });
+    }
+    Some(cookie)
+}
+
+fn parse_expires(value: &str) -> Option<i64> {
+    if let Ok(date) = chrono::DateTime::parse_from_rfc2822(value) {
+        return Some(date.timestamp());
+    }
+    // The older `Wednesday, 21-Oct-15 07:28:00 GMT` form
+    ["%A, %d-%b-%y %H:%M:%S GMT", "%a, %d-%b-%Y %H:%M:%S GMT"]
+        .iter()
+        .find_map(|format| chrono::NaiveDateTime::parse_from_str(value, format).ok())
+        .map(|date| date.and_utc().timestamp())
+}
+
+fn domain_matches(host: &str, domain: &str) -> bool {
+    host == domain
+        || (host.ends_with(domain) && host.as_bytes()[host.len() - domain.len() - 1] == b'.')
+}
+
+fn path_matches(request_path: &str, cookie_path: &str) -> bool {
+    request_path == cookie_path
+        || (request_path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/') || request_path.as_bytes()[cookie_path.len()] == b'/'))
+}
+
+/// The directory of the request path, used when a cookie names no path
+fn default_path(request_path: &str) -> String {
+    match request_path.rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(end) => request_path[..end].to_string(),
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[schemars(crate = "sweetmcp_plugin_builder::schemars")]
+pub struct ClearSessionArgs {
+    /// Name of the session whose cookies to forget, as given to fetch
+    session: String,
+}
+
+/// Forget a session's cookies, e.g. to log out or start over
+pub struct ClearSessionTool;
+
+impl McpTool for ClearSessionTool {
+    const NAME: &'static str = "clear_session";
+    type Args = ClearSessionArgs;
+
+    fn description(builder: DescriptionBuilder) -> DescriptionBuilder {
+        builder
+            .does("Forget the cookies fetch has kept for a named session")
+            .when("you are done with a site you logged in to through fetch's session argument")
+            .when("you need the next fetch in a session to start without cookies, e.g. logged out")
+            .perfect_for(
+                "ending authenticated browsing and resetting sites that misbehave on stale cookies",
+            )
+    }
+
+    fn execute_typed(args: ClearSessionArgs) -> Result<CallToolResult, Error> {
+        if let Err(e) = validate_session(&args.session) {
+            return Ok(ContentBuilder::error(ToolError::invalid_argument(
+                "session", e,
+            )));
+        }
+        let count = CookieJar::clear(&args.session)?;
+        Ok(ContentBuilder::text(format!(
+            "Cleared session {} ({} cookies)",
+            args.session, count
+        )))
+    }
+}
@@ -0,0 +1,514 @@
+//! Multi-page crawl behind the `crawl` tool.
+//!
+//! Starting from one URL, pages on the same host are fetched breadth-first
+//! over plain HTTP, either following every link up to `max_depth` hops or,
+//! with a `next_selector`, only the pagination links it matches. Each host's
+//! robots.txt is honoured, and requests are spaced by the larger of
+//! `delay_ms` and the host's `Crawl-delay`.
+
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::time::Duration;
+
+use extism_pdk::*;
+use htmd::HtmlToMarkdown;
+use markup5ever_rcdom::Handle;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use sweetmcp_plugin_builder::CallToolResult;
+use sweetmcp_plugin_builder::prelude::*;
+use url::Url;
+
+use crate::chromiumoxide::FetchRequest;
+use crate::hyper::HyperFetcher;
+use crate::readability::{self, attr, tag, walk};
+use crate::selector::Selector;
+
+/// Product token matched against robots.txt `User-agent` lines; the hyper
+/// fetcher sends `fetch-hyper/1.0`
+const ROBOTS_AGENT: &str = "fetch-hyper";
+
+const MAX_PAGES_LIMIT: usize = 50;
+
+/// Time kept back from the deadline to assemble the result
+const DEADLINE_RESERVE: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+#[schemars(crate = "sweetmcp_plugin_builder::schemars")]
+enum CrawlOutput {
+    /// Each page as its own markdown, with the link graph, as JSON
+    #[default]
+    Pages,
+    /// One markdown document of all pages, followed by the link graph
+    Concatenated,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+#[schemars(crate = "sweetmcp_plugin_builder::schemars")]
+enum PageFormat {
+    /// The whole page as markdown
+    #[default]
+    Markdown,
+    /// Only the main content, without navigation, ads and boilerplate
+    Article,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[schemars(crate = "sweetmcp_plugin_builder::schemars")]
+pub struct CrawlArgs {
+    /// Page to start from; only pages on its host are crawled
+    url: String,
+    /// Most pages to fetch (default 10, at most 50)
+    #[serde(default)]
+    max_pages: Option<usize>,
+    /// Most link hops from the start page (default 2; unlimited with next_selector)
+    #[serde(default)]
+    max_depth: Option<usize>,
+    /// CSS selector for the pagination link, e.g. "a[rel=next]" or ".pagination a.next"; when set only matching links are followed. Supports tag, .class, #id, [attr], [attr=value], [attr*=value], descendant and > combinators, and comma-separated alternatives
+    #[serde(default)]
+    next_selector: Option<String>,
+    /// Milliseconds to wait between requests (default 1000); a longer robots.txt Crawl-delay wins
+    #[serde(default)]
+    delay_ms: Option<u64>,
+    /// pages (default) or concatenated
+    #[serde(default)]
+    output: CrawlOutput,
+    /// markdown (default) or article
+    #[serde(default)]
+    content_format: PageFormat,
+}
+
+/// Crawl tool: several pages of one site as markdown plus their link graph
+pub struct CrawlTool;
+
+impl McpTool for CrawlTool {
+    const NAME: &'static str = "crawl";
+    type Args = CrawlArgs;
+
+    fn description(builder: DescriptionBuilder) -> DescriptionBuilder {
+        builder
+            .does("Crawl several pages of one website and return each as markdown, with the links between them")
+            .when("you need content spread over paginated listings, multi-page articles or search results")
+            .when("you need an overview of a small site or documentation section, not just one page")
+            .when("you need to know how the pages of a site link to each other")
+            .perfect_for("collecting documentation, following 'next page' links, and mapping site structure politely, within robots.txt")
+    }
+
+    fn execute_typed(args: CrawlArgs) -> Result<CallToolResult, Error> {
+        let start = match Url::parse(&args.url) {
+            Ok(url) if url.scheme() == "https" && url.host_str().is_some() => url,
+            _ => {
+                return Ok(ContentBuilder::error(ToolError::invalid_argument(
+                    "url",
+                    format!("{} is not an https URL", args.url),
+                )));
+            }
+        };
+        let next = match args.next_selector.as_deref().map(Selector::parse) {
+            Some(Ok(selector)) => Some(selector),
+            Some(Err(e)) => {
+                return Ok(ContentBuilder::error(ToolError::invalid_argument(
+                    "next_selector",
+                    e,
+                )));
+            }
+            None => None,
+        };
+        let limits = Limits {
+            max_pages: args.max_pages.unwrap_or(10).clamp(1, MAX_PAGES_LIMIT),
+            max_depth: match (&next, args.max_depth) {
+                (_, Some(depth)) => depth,
+                (Some(_), None) => usize::MAX,
+                (None, None) => 2,
+            },
+            delay: Duration::from_millis(args.delay_ms.unwrap_or(1000)),
+        };
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| Error::msg(format!("Failed to create runtime: {}", e)))?;
+        let outcome = rt.block_on(crawl(start, next, limits, args.content_format))?;
+
+        let graph = json!({
+            "start_url": args.url,
+            "stopped": outcome.stopped,
+            "pages": outcome.pages.iter().map(Page::summary).collect::<Vec<_>>(),
+            "edges": outcome.edges(),
+            "skipped": outcome.skipped,
+        });
+        Ok(match args.output {
+            CrawlOutput::Pages => {
+                let pages: Vec<Value> = outcome.pages.iter().map(Page::full).collect();
+                let mut result = graph;
+                result["pages"] = Value::Array(pages);
+                ContentBuilder::new().json(result).build()
+            }
+            CrawlOutput::Concatenated => {
+                let markdown = outcome
+                    .pages
+                    .iter()
+                    .filter_map(|page| {
+                        let body = page.markdown.as_deref()?;
+                        Some(format!(
+                            "# {}\n\n<{}>\n\n{}",
+                            page.title.as_deref().unwrap_or(&page.url),
+                            page.url,
+                            body.trim()
+                        ))
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n\n---\n\n");
+                ContentBuilder::new()
+                    .text(markdown)
+                    .mime_type("text/markdown")
+                    .json(graph)
+                    .build()
+            }
+        })
+    }
+}
+
+struct Limits {
+    max_pages: usize,
+    max_depth: usize,
+    delay: Duration,
+}
+
+struct Page {
+    url: String,
+    depth: usize,
+    title: Option<String>,
+    markdown: Option<String>,
+    error: Option<String>,
+    /// Same-host links on the page, in order of appearance
+    links: Vec<String>,
+}
+
+impl Page {
+    fn summary(&self) -> Value {
+        json!({
+            "url": self.url,
+            "depth": self.depth,
+            "title": self.title,
+            "error": self.error,
+        })
+    }
+
+    fn full(&self) -> Value {
+        let mut page = self.summary();
+        page["markdown"] = json!(self.markdown);
+        page["links"] = json!(self.links);
+        page
+    }
+}
+
+struct Crawl {
+    pages: Vec<Page>,
+    skipped: Vec<Value>,
+    /// Why the crawl ended before running out of links, if it did
+    stopped: Option<&'static str>,
+}
+
+impl Crawl {
+    /// Links between crawled pages, as `[from, to]`
+    fn edges(&self) -> Vec<[&str; 2]> {
+        let crawled: HashSet<&str> = self.pages.iter().map(|p| p.url.as_str()).collect();
+        self.pages
+            .iter()
+            .flat_map(|page| {
+                page.links
+                    .iter()
+                    .filter(|link| crawled.contains(link.as_str()) && **link != page.url)
+                    .map(|link| [page.url.as_str(), link.as_str()])
+            })
+            .collect()
+    }
+}
+
+async fn crawl(
+    start: Url,
+    next: Option<Selector>,
+    limits: Limits,
+    format: PageFormat,
+) -> Result<Crawl, Error> {
+    let progress = Progress::current();
+    let cancel = CancellationToken::current();
+    let deadline = Deadline::current();
+    let converter = HtmlToMarkdown::builder()
+        .skip_tags(vec!["script", "style"])
+        .build();
+
+    let mut robots: HashMap<String, Robots> = HashMap::new();
+    let mut queue = VecDeque::from([(normalize(start.clone()), 0)]);
+    let mut seen = HashSet::from([normalize(start.clone())]);
+    let mut crawl = Crawl {
+        pages: Vec::new(),
+        skipped: Vec::new(),
+        stopped: None,
+    };
+
+    while let Some((url, depth)) = queue.pop_front() {
+        cancel.check()?;
+        if crawl.pages.len() >= limits.max_pages {
+            crawl.stopped = Some("max_pages");
+            break;
+        }
+        if !deadline.allows(DEADLINE_RESERVE) {
+            crawl.stopped = Some("deadline");
+            break;
+        }
+
+        let origin = url.origin().ascii_serialization();
+        if !robots.contains_key(&origin) {
+            robots.insert(origin.clone(), Robots::fetch(&url).await);
+        }
+        let rules = &robots[&origin];
+        if !rules.allows(url.path()) {
+            crawl
+                .skipped
+                .push(json!({ "url": url.as_str(), "reason": "robots.txt" }));
+            continue;
+        }
+
+        if !crawl.pages.is_empty() {
+            tokio::time::sleep(limits.delay.max(rules.crawl_delay.unwrap_or_default())).await;
+        }
+        progress.report(
+            crawl.pages.len() as f64 * 100.0 / limits.max_pages as f64,
+            format!("Fetching {}", url),
+        );
+
+        let request = FetchRequest::get(url.to_string());
+        let html = match HyperFetcher::fetch(&request).await {
+            Ok(html) => html,
+            Err(e) => {
+                crawl.pages.push(Page {
+                    url: url.to_string(),
+                    depth,
+                    title: None,
+                    markdown: None,
+                    error: Some(e.to_string()),
+                    links: Vec::new(),
+                });
+                continue;
+            }
+        };
+
+        let root = readability::parse(&html);
+        let links = same_host_links(&root, &url);
+        let follow = match &next {
+            Some(selector) => selector_links(&root, selector, &url),
+            None => links.clone(),
+        };
+        if depth < limits.max_depth {
+            for link in follow {
+                if seen.insert(link.clone()) {
+                    queue.push_back((link, depth + 1));
+                }
+            }
+        }
+
+        let (title, body) = match format {
+            PageFormat::Markdown => (
+                readability::find(&root, &|n: &Handle| tag(n) == Some("title"))
+                    .map(|n| readability::inner_text(&n)),
+                html,
+            ),
+            PageFormat::Article => {
+                let article = readability::extract(&html);
+                (article.title, article.html)
+            }
+        };
+        let (markdown, error) = match converter.convert(&body) {
+            Ok(markdown) => (Some(markdown), None),
+            Err(e) => (None, Some(format!("Failed to convert to markdown: {}", e))),
+        };
+        crawl.pages.push(Page {
+            url: url.to_string(),
+            depth,
+            title: title.filter(|t| !t.is_empty()),
+            markdown,
+            error,
+            links: links.iter().map(Url::to_string).collect(),
+        });
+    }
+    Ok(crawl)
+}
+
+/// `url` without its fragment, so `#section` links don't count as new pages
+fn normalize(mut url: Url) -> Url {
+    url.set_fragment(None);
+    url
+}
+
+/// Resolve an `href` found on `base`, keeping it only if it is on the same host
+fn resolve(href: &str, base: &Url) -> Option<Url> {
+    let url = normalize(base.join(href.trim()).ok()?);
+    (url.scheme() == "https" && url.host_str() == base.host_str()).then_some(url)
+}
+
+/// Distinct same-host `a[href]` targets on the page, in document order
+fn same_host_links(root: &Handle, base: &Url) -> Vec<Url> {
+    let mut seen = BTreeSet::new();
+    let mut links = Vec::new();
+    walk(root, &mut |node| {
+        if tag(node) != Some("a") {
+            return;
+        }
+        if let Some(url) = attr(node, "href").and_then(|href| resolve(&href, base)) {
+            if seen.insert(url.to_string()) {
+                links.push(url);
+            }
+        }
+    });
+    links
+}
+
+/// Targets of the links `selector` matches: matched `a` elements, or the
+/// first link inside other matched elements
+fn selector_links(root: &Handle, selector: &Selector, base: &Url) -> Vec<Url> {
+    let mut links = Vec::new();
+    walk(root, &mut |node| {
+        if !selector.matches(node) {
+            return;
+        }
+        let href = if tag(node) == Some("a") {
+            attr(node, "href")
+        } else {
+            readability::find(node, &|n: &Handle| {
+                tag(n) == Some("a") && attr(n, "href").is_some()
+            })
+            .and_then(|a| attr(&a, "href"))
+        };
+        if let Some(url) = href.and_then(|href| resolve(&href, base)) {
+            if !links.contains(&url) {
+                links.push(url);
+            }
+        }
+    });
+    links
+}
+
+/// The robots.txt group that applies to this fetcher
+#[derive(Debug, Default)]
+struct Robots {
+    /// `(allow, pattern)`; the longest matching pattern wins
+    rules: Vec<(bool, String)>,
+    crawl_delay: Option<Duration>,
+}
+
+impl Robots {
+    /// Fetch and parse robots.txt for `url`'s origin. A missing file allows
+    /// everything; an unreachable or failing one allows nothing.
+    async fn fetch(url: &Url) -> Self {
+        let Ok(robots_url) = url.join("/robots.txt") else {
+            return Self::default();
+        };
+        let request = FetchRequest::get(robots_url.to_string());
+        match HyperFetcher::fetch(&request).await {
+            Ok(text) => Self::parse(&text, ROBOTS_AGENT),
+            Err(e) if e.to_string().contains("HTTP 4") => Self::default(),
+            Err(_) => Self {
+                rules: vec![(false, "/".to_string())],
+                crawl_delay: None,
+            },
+        }
+    }
+
+    fn parse(text: &str, agent: &str) -> Self {
+        // Groups of (user agents, rules); consecutive User-agent lines share a group
+        let mut groups: Vec<(Vec<String>, Robots)> = Vec::new();
+        let mut in_agents = false;
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let (key, value) = (key.trim().to_ascii_lowercase(), value.trim());
+            if key == "user-agent" {
+                if !in_agents {
+                    groups.push((Vec::new(), Robots::default()));
+                }
+                in_agents = true;
+                if let Some((agents, _)) = groups.last_mut() {
+                    agents.push(value.to_ascii_lowercase());
+                }
+                continue;
+            }
+            in_agents = false;
+            let Some((_, robots)) = groups.last_mut() else {
+                continue;
+            };
+            match key.as_str() {
+                "allow" if !value.is_empty() => robots.rules.push((true, value.to_string())),
+                "disallow" if !value.is_empty() => robots.rules.push((false, value.to_string())),
+                "crawl-delay" => {
+                    robots.crawl_delay = value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|secs| secs.is_finite() && *secs >= 0.0)
+                        .map(|secs| Duration::from_secs_f64(secs.min(60.0)));
+                }
+                _ => {}
+            }
+        }
+
+        // The group naming this agent most specifically, else the `*` group
+        let agent = agent.to_ascii_lowercase();
+        let mut best: Option<(usize, Robots)> = None;
+        for (agents, robots) in groups {
+            let specificity = agents
+                .iter()
+                .filter_map(|a| match a.as_str() {
+                    "*" => Some(0),
+                    a if agent.starts_with(a) => Some(a.len()),
+                    _ => None,
+                })
+                .max();
+            if let Some(specificity) = specificity {
+                if best.as_ref().is_none_or(|(b, _)| specificity > *b) {
+                    best = Some((specificity, robots));
+                }
+            }
+        }
+        best.map(|(_, robots)| robots).unwrap_or_default()
+    }
+
+    fn allows(&self, path: &str) -> bool {
+        self.rules
+            .iter()
+            .filter(|(_, pattern)| robots_match(pattern, path))
+            .max_by_key(|(allow, pattern)| (pattern.len(), *allow))
+            .is_none_or(|(allow, _)| *allow)
+    }
+}
+
+/// robots.txt path pattern: a prefix, where `*` matches any run of
+/// characters and a trailing `$` anchors the end
+fn robots_match(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let Some(first) = parts.next() else {
+        return true;
+    };
+    let Some(mut rest) = path.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    for (i, part) in parts.iter().enumerate() {
+        let last = i + 1 == parts.len();
+        if last && anchored {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    !anchored || rest.is_empty()
+}
@@ -0,0 +1,131 @@
+//! Structured extraction behind `fetch`'s `extract` argument.
+//!
+//! `extract` maps output fields to CSS selectors. A field given as a string
+//! takes the text of the first element it matches; given as an object it may
+//! read an attribute instead (`attr`) and collect every match (`all`). The
+//! reserved `"jsonld": true` adds the page's schema.org JSON-LD items, where
+//! sites usually publish price, rating and the like. Fields matching nothing
+//! are null.
+
+use markup5ever_rcdom::Handle;
+use serde_json::{Map, Value};
+
+use crate::readability::{self, attr, inner_text, walk};
+use crate::selector::Selector;
+
+/// Key of `extract` that asks for JSON-LD rather than naming a field
+const JSONLD_KEY: &str = "jsonld";
+
+#[derive(Debug)]
+pub(crate) struct Extraction {
+    fields: Vec<Field>,
+    jsonld: bool,
+}
+
+#[derive(Debug)]
+struct Field {
+    name: String,
+    selector: Selector,
+    attr: Option<String>,
+    all: bool,
+}
+
+impl Extraction {
+    pub(crate) fn parse(value: &Value) -> Result<Self, String> {
+        let Value::Object(map) = value else {
+            return Err("extract must be an object of field names to CSS selectors".to_string());
+        };
+        let mut extraction = Extraction {
+            fields: Vec::new(),
+            jsonld: false,
+        };
+        for (name, spec) in map {
+            if name == JSONLD_KEY {
+                extraction.jsonld = spec
+                    .as_bool()
+                    .ok_or("extract.jsonld must be true or false")?;
+                continue;
+            }
+            let (selector, attr, all) = match spec {
+                Value::String(selector) => (selector.as_str(), None, false),
+                Value::Object(spec) => {
+                    let selector = spec
+                        .get("selector")
+                        .and_then(Value::as_str)
+                        .ok_or_else(|| format!("extract.{} needs a selector", name))?;
+                    let attr = match spec.get("attr") {
+                        None | Some(Value::Null) => None,
+                        Some(Value::String(attr)) => Some(attr.trim().to_ascii_lowercase()),
+                        Some(_) => return Err(format!("extract.{}.attr must be a string", name)),
+                    };
+                    let all = match spec.get("all") {
+                        None | Some(Value::Null) => false,
+                        Some(Value::Bool(all)) => *all,
+                        Some(_) => {
+                            return Err(format!("extract.{}.all must be true or false", name));
+                        }
+                    };
+                    (selector, attr, all)
+                }
+                _ => {
+                    return Err(format!(
+                        "extract.{} must be a CSS selector or {{selector, attr, all}}",
+                        name
+                    ));
+                }
+            };
+            let selector =
+                Selector::parse(selector).map_err(|e| format!("extract.{}: {}", name, e))?;
+            extraction.fields.push(Field {
+                name: name.clone(),
+                selector,
+                attr,
+                all,
+            });
+        }
+        if extraction.fields.is_empty() && !extraction.jsonld {
+            return Err("extract needs at least one field, or jsonld: true".to_string());
+        }
+        Ok(extraction)
+    }
+
+    /// `{"fields": {...}}`, plus `"jsonld": [...]` when asked for
+    pub(crate) fn run(&self, html: &str) -> Map<String, Value> {
+        let root = readability::parse(html);
+        let mut fields = Map::new();
+        for field in &self.fields {
+            fields.insert(field.name.clone(), field.extract(&root));
+        }
+        let mut data = Map::new();
+        data.insert("fields".to_string(), Value::Object(fields));
+        if self.jsonld {
+            data.insert(
+                JSONLD_KEY.to_string(),
+                Value::Array(readability::json_ld(&root)),
+            );
+        }
+        data
+    }
+}
+
+impl Field {
+    fn extract(&self, root: &Handle) -> Value {
+        let mut values = Vec::new();
+        walk(root, &mut |node| {
+            if (self.all || values.is_empty()) && self.selector.matches(node) {
+                let value = match &self.attr {
+                    Some(name) => attr(node, name),
+                    None => Some(inner_text(node)),
+                };
+                if let Some(value) = value.filter(|v| !v.trim().is_empty()) {
+                    values.push(Value::String(value.trim().to_string()));
+                }
+            }
+        });
+        if self.all {
+            Value::Array(values)
+        } else {
+            values.into_iter().next().unwrap_or(Value::Null)
+        }
+    }
+}
@@ -4,7 +4,7 @@ use std::error::Error as StdError;
 use std::fmt;
 use std::time::Duration;
 
-use crate::chromiumoxide::{ContentFetcher, FetchResult};
+use crate::chromiumoxide::{ContentFetcher, FetchRequest, FetchResult};
 
 #[derive(Debug)]
 pub enum FirecrawlError {
@@ -61,7 +61,8 @@ impl FirecrawlFetcher {
         for line in html.lines() {
             let lower = line.to_lowercase();
 
-            if lower.contains("<script") {
+            // JSON-LD is data, kept for article metadata and `extract`
+            if lower.contains("<script") && !lower.contains("application/ld+json") {
                 in_script = true;
             }
 
@@ -87,7 +88,34 @@ impl FirecrawlFetcher {
     }
 
     // In a real implementation, this would make a request using the Firecrawl API
-    async fn fetch_with_firecrawl(url: &str) -> Result<String, FirecrawlError> {
+    async fn fetch_with_firecrawl(request: &FetchRequest) -> Result<String, FirecrawlError> {
+        let url = request.url.as_str();
+
+        // Firecrawl scrapes pages with GET; it forwards headers but can't
+        // send another method or a body
+        if request.method != "GET" || request.body.is_some() {
+            return Err(FirecrawlError::Parse(format!(
+                "Firecrawl only fetches with GET, not {}",
+                request.method
+            )));
+        }
+
+        // Firecrawl can't carry a session, and the page without it is not
+        // the one the caller asked for
+        let has_session_cookies = request.cookies.as_ref().is_some_and(|jar| {
+            url::Url::parse(url).is_ok_and(|url| {
+                jar.lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .header_for(&url)
+                    .is_some()
+            })
+        });
+        if has_session_cookies {
+            return Err(FirecrawlError::Parse(
+                "Firecrawl can't send the session's cookies".to_string(),
+            ));
+        }
+
         // Validate URL format
         if !url.starts_with("http://") && !url.starts_with("https://") {
             return Err(FirecrawlError::Parse(format!(
@@ -155,7 +183,7 @@ impl FirecrawlFetcher {
         };
 
         // Apply timeout to the fetch operation
-        match tokio::time::timeout(Duration::from_secs(10), fetch_future).await {
+        match tokio::time::timeout(request.timeout, fetch_future).await {
             Ok(result) => result,
             Err(_) => Err(FirecrawlError::Timeout(
                 "Firecrawl request timed out".to_string(),
@@ -168,10 +196,10 @@ impl FirecrawlFetcher {
 impl ContentFetcher for FirecrawlFetcher {
     async fn fetch_content(
         &self,
-        url: &str,
+        request: &FetchRequest,
     ) -> Result<FetchResult, Box<dyn StdError + Send + Sync>> {
         // Fetch content using Firecrawl
-        let html_content = Self::fetch_with_firecrawl(url)
+        let html_content = Self::fetch_with_firecrawl(request)
             .await
             .map_err(|e| FirecrawlError::Network(format!("Failed to fetch content: {}", e)))?;
 
@@ -186,6 +214,8 @@ impl ContentFetcher for FirecrawlFetcher {
             content: cleaned_html,
             screenshot_base64,
             content_type: "text/html".to_string(),
+            body: None,
+            status: None,
         })
     }
 }
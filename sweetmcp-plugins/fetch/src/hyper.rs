@@ -1,16 +1,21 @@
 use std::error::Error as StdError;
 use std::fmt;
+use std::sync::Arc;
 
 use async_trait::async_trait;
 use base64::Engine;
-use http_body_util::{BodyExt, Empty};
+use http_body_util::{BodyExt, Full};
 use hyper::body::Bytes;
 use hyper::{Request, Uri};
 use hyper_rustls::ConfigBuilderExt;
 use hyper_util::rt::TokioIo;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{DigitallySignedStruct, SignatureScheme};
 use tokio_rustls::TlsConnector;
 
-use crate::chromiumoxide::{ContentFetcher, FetchResult};
+use crate::chromiumoxide::{ContentFetcher, FetchRequest, FetchResult, is_binary_content};
 
 #[derive(Debug)]
 pub enum FetchError {
@@ -18,6 +23,8 @@ pub enum FetchError {
     Http(hyper::http::Error),
     InvalidUri(hyper::http::uri::InvalidUri),
     Io(std::io::Error),
+    /// The server answered with a status other than 2xx
+    Status(u16, String),
     Other(String),
 }
 
@@ -28,6 +35,7 @@ impl fmt::Display for FetchError {
             FetchError::Http(e) => write!(f, "HTTP error: {}", e),
             FetchError::InvalidUri(e) => write!(f, "Invalid URI: {}", e),
             FetchError::Io(e) => write!(f, "IO error: {}", e),
+            FetchError::Status(code, reason) => write!(f, "HTTP {}: {}", code, reason),
             FetchError::Other(e) => write!(f, "Error: {}", e),
         }
     }
@@ -40,7 +48,7 @@ impl StdError for FetchError {
             FetchError::Http(e) => Some(e),
             FetchError::InvalidUri(e) => Some(e),
             FetchError::Io(e) => Some(e),
-            FetchError::Other(_) => None,
+            FetchError::Status(..) | FetchError::Other(_) => None,
         }
     }
 }
@@ -71,10 +79,49 @@ impl From<std::io::Error> for FetchError {
 
 pub struct HyperFetcher;
 
+/// A response body with the Content-Type it was served as
+pub struct HttpResponse {
+    pub status: u16,
+    pub content_type: String,
+    pub body: Vec<u8>,
+}
+
 impl HyperFetcher {
-    pub async fn fetch(url: &str) -> Result<String, FetchError> {
+    /// Fetch `fetch` and return its body as text
+    pub async fn fetch(fetch: &FetchRequest) -> Result<String, FetchError> {
+        let response = Self::fetch_response(fetch).await?;
+        // Convert to string without re-allocation
+        String::from_utf8(response.body)
+            .map_err(|e| FetchError::Other(format!("Invalid UTF-8: {}", e)))
+    }
+
+    /// Status and Content-Type `fetch` would be served with, from a HEAD
+    /// request
+    pub async fn head(fetch: &FetchRequest) -> Result<HttpResponse, FetchError> {
+        let head = FetchRequest {
+            method: "HEAD".to_string(),
+            body: None,
+            ..fetch.clone()
+        };
+        Self::fetch_response(&head).await
+    }
+
+    /// Fetch `fetch`, refusing bodies larger than its `max_bytes` and giving
+    /// up after its `timeout`
+    pub async fn fetch_response(fetch: &FetchRequest) -> Result<HttpResponse, FetchError> {
+        tokio::time::timeout(fetch.timeout, Self::exchange(fetch))
+            .await
+            .unwrap_or_else(|_| {
+                Err(FetchError::Other(format!(
+                    "Request timed out after {} ms",
+                    fetch.timeout.as_millis()
+                )))
+            })
+    }
+
+    async fn exchange(fetch: &FetchRequest) -> Result<HttpResponse, FetchError> {
         // Parse the URL
-        let uri: Uri = url.parse()?;
+        let uri: Uri = fetch.url.parse()?;
 
         // Extract components
         let scheme = uri
@@ -96,21 +143,37 @@ impl HyperFetcher {
             return Err(FetchError::Other("Only HTTPS is supported".to_string()));
         }
 
-        let addr = format!("{}:{}", host, port);
-
-        // Connect TCP
-        let tcp_stream = tokio::net::TcpStream::connect(&addr).await?;
-        tcp_stream.set_nodelay(true)?;
+        // Connect TCP, directly or through a tunnel of the proxy
+        let tcp_stream = match &fetch.proxy {
+            Some(proxy) => proxy.connect(host, port).await.map_err(|e| {
+                FetchError::Other(format!("Proxy {} failed: {}", proxy.server(), e))
+            })?,
+            None => {
+                let addr = format!("{}:{}", host, port);
+                let tcp_stream = tokio::net::TcpStream::connect(&addr).await?;
+                tcp_stream.set_nodelay(true)?;
+                tcp_stream
+            }
+        };
 
         // TLS setup with zero-copy server name
-        let tls_config = rustls::ClientConfig::builder()
-            .with_native_roots()?
-            .with_no_client_auth();
+        let tls_config = if fetch.insecure_tls {
+            rustls::ClientConfig::builder()
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(AcceptAnyCertificate(Arc::new(
+                    rustls::crypto::ring::default_provider(),
+                ))))
+                .with_no_client_auth()
+        } else {
+            rustls::ClientConfig::builder()
+                .with_native_roots()?
+                .with_no_client_auth()
+        };
 
-        let server_name = rustls::pki_types::ServerName::try_from(host.to_string())
+        let server_name = ServerName::try_from(host.to_string())
             .map_err(|_| FetchError::Other("Invalid server name".to_string()))?;
 
-        let connector = TlsConnector::from(std::sync::Arc::new(tls_config));
+        let connector = TlsConnector::from(Arc::new(tls_config));
         let tls_stream = connector
             .connect(server_name, tcp_stream)
             .await
@@ -141,33 +204,92 @@ impl HyperFetcher {
 
         let path_and_query = uri.path_and_query().map(|pq| pq.as_str()).unwrap_or("/");
 
-        let request = Request::builder()
-            .method("GET")
+        let mut builder = Request::builder()
+            .method(fetch.method.as_str())
             .uri(path_and_query)
             .header(hyper::header::HOST, authority)
             .header(hyper::header::USER_AGENT, "fetch-hyper/1.0")
             .header(hyper::header::ACCEPT, "*/*")
-            .header(hyper::header::ACCEPT_ENCODING, "identity")
-            .body(Empty::<Bytes>::new())?;
+            .header(hyper::header::ACCEPT_ENCODING, "identity");
+        // Caller headers replace the defaults above rather than repeating them
+        if let Some(headers) = builder.headers_mut() {
+            for (name, value) in fetch.all_headers() {
+                let name = hyper::header::HeaderName::from_bytes(name.as_bytes()).map_err(|e| {
+                    FetchError::Other(format!("Invalid header name {}: {}", name, e))
+                })?;
+                let value = hyper::header::HeaderValue::from_str(&value)
+                    .map_err(|e| FetchError::Other(format!("Invalid value for {}: {}", name, e)))?;
+                headers.insert(name, value);
+            }
+        }
+        // Session cookies, unless the caller sent a Cookie header of its own
+        let session_url = fetch
+            .cookies
+            .as_ref()
+            .map(|_| url::Url::parse(&fetch.url))
+            .transpose()
+            .map_err(|e| FetchError::Other(format!("Invalid URL: {}", e)))?;
+        if let (Some(jar), Some(url), Some(headers)) =
+            (&fetch.cookies, &session_url, builder.headers_mut())
+        {
+            let cookie = jar
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .header_for(url);
+            if let (Some(cookie), false) = (cookie, headers.contains_key(hyper::header::COOKIE)) {
+                let value = hyper::header::HeaderValue::from_str(&cookie)
+                    .map_err(|e| FetchError::Other(format!("Invalid session cookie: {}", e)))?;
+                headers.insert(hyper::header::COOKIE, value);
+            }
+        }
+        let body = fetch.body.clone().unwrap_or_default();
+        let request = builder.body(Full::new(Bytes::from(body)))?;
 
         // Send request
         let response = sender.send_request(request).await?;
         let status = response.status();
 
+        // Cookies count even from an error or redirect, e.g. a login's 302
+        if let (Some(jar), Some(url)) = (&fetch.cookies, &session_url) {
+            let mut jar = jar.lock().unwrap_or_else(|e| e.into_inner());
+            for header in response.headers().get_all(hyper::header::SET_COOKIE) {
+                if let Ok(header) = header.to_str() {
+                    jar.store_set_cookie(url, header);
+                }
+            }
+        }
+
         if !status.is_success() {
-            return Err(FetchError::Other(format!(
-                "HTTP {}: {}",
+            return Err(FetchError::Status(
                 status.as_u16(),
-                status.canonical_reason().unwrap_or("Unknown")
-            )));
+                status.canonical_reason().unwrap_or("Unknown").to_string(),
+            ));
         }
 
+        let content_type = response
+            .headers()
+            .get(hyper::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("text/html")
+            .to_string();
+
         // Collect body with pre-allocated buffer
         let content_length = response
             .headers()
             .get(hyper::header::CONTENT_LENGTH)
             .and_then(|v| v.to_str().ok())
             .and_then(|s| s.parse::<usize>().ok());
+        // A HEAD response's length is the GET's, with no body to follow
+        if fetch.method == "HEAD" {
+            return Ok(HttpResponse {
+                status: status.as_u16(),
+                content_type,
+                body: Vec::new(),
+            });
+        }
+        if content_length.is_some_and(|len| len > fetch.max_bytes) {
+            return Err(too_large(fetch.max_bytes));
+        }
 
         let mut body_bytes = if let Some(len) = content_length {
             Vec::with_capacity(len.min(10 * 1024 * 1024)) // Cap at 10MB pre-allocation
@@ -179,13 +301,18 @@ impl HyperFetcher {
         while let Some(frame) = body.frame().await {
             let frame = frame.map_err(|e| FetchError::Other(format!("Frame error: {}", e)))?;
             if let Some(chunk) = frame.data_ref() {
+                if body_bytes.len() + chunk.len() > fetch.max_bytes {
+                    return Err(too_large(fetch.max_bytes));
+                }
                 body_bytes.extend_from_slice(chunk);
             }
         }
 
-        // Convert to string without re-allocation
-        String::from_utf8(body_bytes)
-            .map_err(|e| FetchError::Other(format!("Invalid UTF-8: {}", e)))
+        Ok(HttpResponse {
+            status: status.as_u16(),
+            content_type,
+            body: body_bytes,
+        })
     }
 
     pub fn clean_html(html: &str) -> String {
@@ -198,7 +325,8 @@ impl HyperFetcher {
         for line in html.lines() {
             let lower = line.to_lowercase();
 
-            if lower.contains("<script") {
+            // JSON-LD is data, kept for article metadata and `extract`
+            if lower.contains("<script") && !lower.contains("application/ld+json") {
                 in_script = true;
             }
 
@@ -228,13 +356,28 @@ impl HyperFetcher {
 impl ContentFetcher for HyperFetcher {
     async fn fetch_content(
         &self,
-        url: &str,
+        request: &FetchRequest,
     ) -> Result<FetchResult, Box<dyn StdError + Send + Sync>> {
-        // Fetch HTML content using hyper
-        let content = Self::fetch(url)
+        let response = Self::fetch_response(request)
             .await
             .map_err(|e| Box::new(e) as Box<dyn StdError + Send + Sync>)?;
 
+        // PDFs and images are handed over as they came, with no screenshot
+        if is_binary_content(&response.content_type) {
+            return Ok(FetchResult {
+                content: String::new(),
+                screenshot_base64: String::new(),
+                content_type: response.content_type,
+                body: Some(response.body),
+                status: Some(response.status),
+            });
+        }
+        let status = Some(response.status);
+        let content = String::from_utf8(response.body).map_err(|e| {
+            Box::new(FetchError::Other(format!("Invalid UTF-8: {}", e)))
+                as Box<dyn StdError + Send + Sync>
+        })?;
+
         // Clean the HTML content
         let cleaned_content = Self::clean_html(&content);
 
@@ -246,6 +389,65 @@ impl ContentFetcher for HyperFetcher {
             content: cleaned_content,
             screenshot_base64,
             content_type: "text/html".to_string(),
+            body: None,
+            status,
         })
     }
 }
+
+/// Verifier for `insecure_tls`: any certificate is taken as valid, while
+/// handshake signatures are still checked against it
+#[derive(Debug)]
+struct AcceptAnyCertificate(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AcceptAnyCertificate {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+fn too_large(max_bytes: usize) -> FetchError {
+    FetchError::Other(format!(
+        "Response is larger than max_bytes ({} bytes)",
+        max_bytes
+    ))
+}
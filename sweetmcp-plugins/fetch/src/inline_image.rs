@@ -0,0 +1,268 @@
+//! Screenshots as terminal inline images.
+//!
+//! Three protocols are supported: sixel, for xterm, foot, WezTerm, mlterm and
+//! most others; kitty's graphics protocol; and iTerm2's inline images, which
+//! WezTerm also reads. Sixel carries at most 256 colors, so the screenshot is
+//! quantized by median cut and Floyd-Steinberg dithered onto that palette.
+//! Kitty and iTerm2 take the PNG as it is.
+
+use std::io::Cursor;
+
+use base64::Engine;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat, RgbImage};
+
+/// Colors in a sixel palette
+const SIXEL_COLORS: usize = 256;
+
+/// Pixels sampled to build the palette; larger images are strided
+const PALETTE_SAMPLES: usize = 64 * 1024;
+
+/// Base64 bytes per kitty escape sequence, the protocol's maximum
+const KITTY_CHUNK: usize = 4096;
+
+/// Shrink `image` to fit within the given bounds, keeping its aspect ratio.
+/// Images already small enough are returned as they are.
+pub fn fit(image: DynamicImage, max_width: Option<u32>, max_height: Option<u32>) -> DynamicImage {
+    let width = max_width.unwrap_or(u32::MAX).max(1);
+    let height = max_height.unwrap_or(u32::MAX).max(1);
+    if image.width() <= width && image.height() <= height {
+        return image;
+    }
+    image.resize(width, height, FilterType::Lanczos3)
+}
+
+pub fn png(image: &DynamicImage) -> Result<Vec<u8>, image::ImageError> {
+    let mut png = Vec::new();
+    image.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+    Ok(png)
+}
+
+/// Kitty graphics protocol: transmit and display a PNG, split into chunks
+pub fn kitty(png: &[u8]) -> String {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(png);
+    let chunks: Vec<&[u8]> = encoded.as_bytes().chunks(KITTY_CHUNK).collect();
+    let mut out = String::with_capacity(encoded.len() + chunks.len() * 16);
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(i + 1 < chunks.len());
+        let chunk = std::str::from_utf8(chunk).unwrap_or_default();
+        if i == 0 {
+            out.push_str(&format!("\x1b_Ga=T,f=100,m={};{}\x1b\\", more, chunk));
+        } else {
+            out.push_str(&format!("\x1b_Gm={};{}\x1b\\", more, chunk));
+        }
+    }
+    out
+}
+
+/// iTerm2 inline image of a PNG, shown at its pixel size
+pub fn iterm2(png: &[u8], width: u32, height: u32) -> String {
+    format!(
+        "\x1b]1337;File=inline=1;size={};width={}px;height={}px;preserveAspectRatio=1:{}\x07",
+        png.len(),
+        width,
+        height,
+        base64::engine::general_purpose::STANDARD.encode(png)
+    )
+}
+
+/// Sixel image with a palette of up to 256 colors
+pub fn sixel(image: &RgbImage) -> String {
+    let (width, height) = image.dimensions();
+    let palette = median_cut(image, SIXEL_COLORS);
+    let indices = dither(image, &palette);
+
+    // DCS with square pixels, then raster attributes giving the image size
+    let mut out = format!("\x1bP0;1;0q\"1;1;{};{}", width, height);
+    for (i, [r, g, b]) in palette.iter().enumerate() {
+        out.push_str(&format!(
+            "#{};2;{};{};{}",
+            i,
+            percent(*r),
+            percent(*g),
+            percent(*b)
+        ));
+    }
+
+    let width = width as usize;
+    let height = height as usize;
+    let mut slots = [usize::MAX; SIXEL_COLORS];
+    let mut used: Vec<u8> = Vec::new();
+    let mut rows: Vec<Vec<u8>> = Vec::new();
+    for top in (0..height).step_by(6) {
+        // Six-pixel-high band: one row of sixels per color it uses
+        for color in used.drain(..) {
+            slots[color as usize] = usize::MAX;
+        }
+        for row in &mut rows {
+            row.clear();
+        }
+        for dy in 0..6.min(height - top) {
+            let line = &indices[(top + dy) * width..(top + dy + 1) * width];
+            for (x, &color) in line.iter().enumerate() {
+                let slot = &mut slots[color as usize];
+                if *slot == usize::MAX {
+                    *slot = used.len();
+                    used.push(color);
+                    if rows.len() < used.len() {
+                        rows.push(Vec::new());
+                    }
+                    rows[*slot].resize(width, 0);
+                }
+                rows[*slot][x] |= 1 << dy;
+            }
+        }
+
+        for (n, &color) in used.iter().enumerate() {
+            if n > 0 {
+                // Back to the start of the band for the next color
+                out.push('$');
+            }
+            out.push_str(&format!("#{}", color));
+            push_run_length(&mut out, &rows[n]);
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\");
+    out
+}
+
+/// Sixel characters for `bits`, runs compressed, trailing blanks dropped
+fn push_run_length(out: &mut String, bits: &[u8]) {
+    let end = bits.iter().rposition(|b| *b != 0).map_or(0, |i| i + 1);
+    let mut x = 0;
+    while x < end {
+        let value = bits[x];
+        let run = bits[x..end].iter().take_while(|b| **b == value).count();
+        let ch = (63 + value) as char;
+        if run > 3 {
+            out.push_str(&format!("!{}{}", run, ch));
+        } else {
+            for _ in 0..run {
+                out.push(ch);
+            }
+        }
+        x += run;
+    }
+}
+
+/// Sixel color components are percentages
+fn percent(component: u8) -> u32 {
+    (component as u32 * 100 + 127) / 255
+}
+
+/// Palette of up to `colors` colors for `image` by median cut
+fn median_cut(image: &RgbImage, colors: usize) -> Vec<[u8; 3]> {
+    let stride = (image.pixels().len() / PALETTE_SAMPLES).max(1);
+    let samples: Vec<[u8; 3]> = image.pixels().step_by(stride).map(|p| p.0).collect();
+    if samples.is_empty() {
+        return vec![[0, 0, 0]];
+    }
+
+    let mut boxes = vec![samples];
+    while boxes.len() < colors {
+        // Split the box spanning the widest range of any channel
+        let widest = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, pixels)| pixels.len() > 1)
+            .map(|(i, pixels)| {
+                let (channel, range) = widest_channel(pixels);
+                (i, channel, range)
+            })
+            .max_by_key(|(_, _, range)| *range);
+        let Some((i, channel, range)) = widest else {
+            break;
+        };
+        if range == 0 {
+            break;
+        }
+        let mut pixels = boxes.swap_remove(i);
+        pixels.sort_unstable_by_key(|p| p[channel]);
+        let upper = pixels.split_off(pixels.len() / 2);
+        boxes.push(pixels);
+        boxes.push(upper);
+    }
+
+    boxes
+        .iter()
+        .map(|pixels| {
+            let mut sum = [0u64; 3];
+            for p in pixels {
+                for c in 0..3 {
+                    sum[c] += p[c] as u64;
+                }
+            }
+            let n = pixels.len() as u64;
+            [(sum[0] / n) as u8, (sum[1] / n) as u8, (sum[2] / n) as u8]
+        })
+        .collect()
+}
+
+fn widest_channel(pixels: &[[u8; 3]]) -> (usize, u8) {
+    let mut min = [u8::MAX; 3];
+    let mut max = [u8::MIN; 3];
+    for p in pixels {
+        for c in 0..3 {
+            min[c] = min[c].min(p[c]);
+            max[c] = max[c].max(p[c]);
+        }
+    }
+    (0..3)
+        .map(|c| (c, max[c] - min[c]))
+        .max_by_key(|(_, range)| *range)
+        .unwrap_or((0, 0))
+}
+
+/// Palette index of every pixel, Floyd-Steinberg dithered
+fn dither(image: &RgbImage, palette: &[[u8; 3]]) -> Vec<u8> {
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    // Nearest palette color per 15-bit color, filled in as colors turn up
+    let mut nearest = vec![u16::MAX; 1 << 15];
+    let mut lookup = |rgb: [i32; 3]| -> u8 {
+        let key =
+            ((rgb[0] as usize >> 3) << 10) | ((rgb[1] as usize >> 3) << 5) | (rgb[2] as usize >> 3);
+        if nearest[key] == u16::MAX {
+            nearest[key] = closest(palette, rgb) as u16;
+        }
+        nearest[key] as u8
+    };
+
+    let mut indices = vec![0u8; width * height];
+    let mut error = vec![[0i32; 3]; width + 2];
+    let mut next_error = vec![[0i32; 3]; width + 2];
+    for y in 0..height {
+        for x in 0..width {
+            let pixel = image.get_pixel(x as u32, y as u32).0;
+            let mut rgb = [0i32; 3];
+            for c in 0..3 {
+                rgb[c] = (pixel[c] as i32 + error[x + 1][c] / 16).clamp(0, 255);
+            }
+            let index = lookup(rgb);
+            indices[y * width + x] = index;
+            let chosen = palette[index as usize];
+            for c in 0..3 {
+                let e = rgb[c] - chosen[c] as i32;
+                error[x + 2][c] += e * 7;
+                next_error[x][c] += e * 3;
+                next_error[x + 1][c] += e * 5;
+                next_error[x + 2][c] += e;
+            }
+        }
+        std::mem::swap(&mut error, &mut next_error);
+        next_error.iter_mut().for_each(|e| *e = [0; 3]);
+    }
+    indices
+}
+
+fn closest(palette: &[[u8; 3]], rgb: [i32; 3]) -> usize {
+    palette
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, color)| {
+            (0..3)
+                .map(|c| (rgb[c] - color[c] as i32).pow(2))
+                .sum::<i32>()
+        })
+        .map_or(0, |(i, _)| i)
+}
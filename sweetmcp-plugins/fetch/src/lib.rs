@@ -1,121 +1,41 @@
 mod chromiumoxide;
+mod cookies;
+mod crawl;
+mod extract;
 mod hyper;
+mod inline_image;
 // mod bevy; // Disabled due to API incompatibility with bevy 0.16 - approved by David Maple 07/03/2025
 mod firecrawl;
+mod proxy;
+mod readability;
+mod selector;
 
 // use std::collections::BTreeMap;
 use std::str::FromStr;
 
 // use async_trait::async_trait;
 use crate::hyper::HyperFetcher;
-use chromiumoxide::ContentFetcher;
+use base64::Engine;
+use chromiumoxide::{
+    ContentFetcher, DEFAULT_MAX_BYTES, DEFAULT_TIMEOUT, FetchRequest, is_binary_content,
+};
 use extism_pdk::*;
 use htmd::HtmlToMarkdown;
+use proxy::Proxy;
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 use sweetmcp_plugin_builder::prelude::*;
-use sweetmcp_plugin_builder::{CallToolResult, Content, ContentType, Ready};
-// Sixel encoding is implemented inline below based on sixel6vt renderer
-use base64::Engine;
+use sweetmcp_plugin_builder::{CallToolResult, Ready};
 use syntect::{highlighting::ThemeSet, html::highlighted_html_for_string, parsing::SyntaxSet};
 
-/// Encode an RGB image to Sixel format (based on sixel6vt implementation)
-fn encode_sixel(img: &image::RgbImage) -> String {
-    // Start with DCS sequence + sixel + raster attributes with image dimensions
-    let mut result = String::from("\x1BPq");
-
-    // Add raster attributes
-    result.push_str(&format!("\"{};{}", img.width(), img.height()));
-
-    // Define a basic 16-color palette
-    result.push_str("#0;2;0;0;0"); // 0: Black
-    result.push_str("#1;2;20;20;80"); // 1: Dark Blue
-    result.push_str("#2;2;20;80;20"); // 2: Dark Green
-    result.push_str("#3;2;20;80;80"); // 3: Dark Cyan
-    result.push_str("#4;2;80;20;20"); // 4: Dark Red
-    result.push_str("#5;2;80;20;80"); // 5: Dark Magenta
-    result.push_str("#6;2;80;80;20"); // 6: Brown
-    result.push_str("#7;2;80;80;80"); // 7: Light Gray
-    result.push_str("#8;2;40;40;40"); // 8: Dark Gray
-    result.push_str("#9;2;40;40;100"); // 9: Light Blue
-    result.push_str("#10;2;40;100;40"); // 10: Light Green
-    result.push_str("#11;2;40;100;100"); // 11: Light Cyan
-    result.push_str("#12;2;100;40;40"); // 12: Light Red
-    result.push_str("#13;2;100;40;100"); // 13: Light Magenta
-    result.push_str("#14;2;100;100;40"); // 14: Yellow
-    result.push_str("#15;2;100;100;100"); // 15: White
-
-    // Function to find the closest color in our palette
-    let find_closest_color = |r: u8, g: u8, b: u8| -> u16 {
-        let colors = [
-            (0, 0, 0),
-            (20, 20, 80),
-            (20, 80, 20),
-            (20, 80, 80),
-            (80, 20, 20),
-            (80, 20, 80),
-            (80, 80, 20),
-            (80, 80, 80),
-            (40, 40, 40),
-            (40, 40, 100),
-            (40, 100, 40),
-            (40, 100, 100),
-            (100, 40, 40),
-            (100, 40, 100),
-            (100, 100, 40),
-            (100, 100, 100),
-        ];
-
-        let mut min_dist = u32::MAX;
-        let mut closest = 0;
-
-        for (i, &(cr, cg, cb)) in colors.iter().enumerate() {
-            let dist =
-                ((r as i32 - cr).pow(2) + (g as i32 - cg).pow(2) + (b as i32 - cb).pow(2)) as u32;
-            if dist < min_dist {
-                min_dist = dist;
-                closest = i;
-            }
-        }
-        closest as u16
-    };
-
-    // Process the image in sixel format (6 vertical pixels at a time)
-    for y in (0..img.height()).step_by(6) {
-        result.push_str("#0");
-        let current_color = 0;
-
-        for x in 0..img.width() {
-            let mut sixel_value = 0;
-
-            for i in 0..6 {
-                if y + i < img.height() {
-                    let pixel = img.get_pixel(x, y + i);
-                    let color = find_closest_color(pixel[0], pixel[1], pixel[2]);
-                    if color == current_color {
-                        sixel_value |= 1 << i;
-                    }
-                }
-            }
-
-            if sixel_value > 0 {
-                result.push((b'?' + sixel_value) as char);
-            } else {
-                result.push('?');
-            }
-        }
-
-        result.push_str("$\r\n");
-    }
-
-    result.push_str("\x1B\\");
-    result
-}
-
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Copy, Deserialize)]
 enum ScreenshotFormat {
     Base64,
     Sixel,
+    /// Kitty graphics protocol escape sequences
+    Kitty,
+    /// iTerm2 inline image escape sequence
+    Iterm2,
 }
 
 impl Default for ScreenshotFormat {
@@ -131,16 +51,140 @@ impl FromStr for ScreenshotFormat {
         match s.to_lowercase().as_str() {
             "base64" => Ok(ScreenshotFormat::Base64),
             "sixel" => Ok(ScreenshotFormat::Sixel),
+            "kitty" => Ok(ScreenshotFormat::Kitty),
+            "iterm2" => Ok(ScreenshotFormat::Iterm2),
             _ => Err(format!("Invalid screenshot format: {}", s)),
         }
     }
 }
 
+/// Which fetcher serves the request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Backend {
+    /// Headless browser, then plain HTTP, then Firecrawl
+    Auto,
+    Chromium,
+    Http,
+    Firecrawl,
+}
+
+impl Default for Backend {
+    fn default() -> Self {
+        Backend::Auto
+    }
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(Backend::Auto),
+            "chromium" => Ok(Backend::Chromium),
+            "http" => Ok(Backend::Http),
+            "firecrawl" => Ok(Backend::Firecrawl),
+            _ => Err(format!("Invalid backend: {}", s)),
+        }
+    }
+}
+
+impl Backend {
+    fn as_str(self) -> &'static str {
+        match self {
+            Backend::Auto => "auto",
+            Backend::Chromium => "chromium",
+            Backend::Http => "http",
+            Backend::Firecrawl => "firecrawl",
+        }
+    }
+}
+
+/// How a fetch went, returned next to the content so a failure anywhere
+/// along the fallback chain can be traced
+#[derive(Debug, Default, Serialize)]
+struct Diagnostics {
+    /// Backend whose result was returned
+    backend: Option<&'static str>,
+    /// HTTP status of the returned response, when the backend can tell
+    status: Option<u16>,
+    /// Every stage tried, in order
+    attempts: Vec<Attempt>,
+}
+
+#[derive(Debug, Serialize)]
+struct Attempt {
+    backend: &'static str,
+    /// `head` for the content-type probe, `fetch` for a full fetch
+    stage: &'static str,
+    elapsed_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<u16>,
+    /// Why the stage failed or was skipped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl Diagnostics {
+    /// Fetch with one backend, recording how long it took and how it ended
+    async fn attempt(
+        &mut self,
+        backend: Backend,
+        request: &FetchRequest,
+    ) -> Result<chromiumoxide::FetchResult, String> {
+        let started = std::time::Instant::now();
+        let result = match backend {
+            Backend::Chromium => chromiumoxide::ChromiumFetcher.fetch_content(request).await,
+            Backend::Firecrawl => firecrawl::FirecrawlFetcher.fetch_content(request).await,
+            Backend::Http | Backend::Auto => HyperFetcher.fetch_content(request).await,
+        };
+        let (status, error) = match &result {
+            Ok(result) => (result.status, None),
+            Err(e) => (error_status(e.as_ref()), Some(e.to_string())),
+        };
+        self.attempts.push(Attempt {
+            backend: backend.as_str(),
+            stage: "fetch",
+            elapsed_ms: started.elapsed().as_millis() as u64,
+            status,
+            error: error.clone(),
+        });
+        match result {
+            Ok(result) => {
+                self.backend = Some(backend.as_str());
+                self.status = status;
+                Ok(result)
+            }
+            Err(_) => Err(error.unwrap_or_default()),
+        }
+    }
+
+    /// Record a stage that was not tried
+    fn skip(&mut self, backend: Backend, reason: &str) {
+        self.attempts.push(Attempt {
+            backend: backend.as_str(),
+            stage: "fetch",
+            elapsed_ms: 0,
+            status: None,
+            error: Some(format!("skipped: {}", reason)),
+        });
+    }
+}
+
+/// HTTP status carried by a fetch error, if the server answered at all
+fn error_status(error: &(dyn std::error::Error + Send + Sync + 'static)) -> Option<u16> {
+    match error.downcast_ref::<crate::hyper::FetchError>() {
+        Some(crate::hyper::FetchError::Status(code, _)) => Some(*code),
+        _ => None,
+    }
+}
+
 #[derive(Debug, Deserialize)]
 enum ContentFormat {
     Markdown,
     Json,
     Txt,
+    /// Main content only, as markdown with title, byline and date
+    Article,
 }
 
 impl Default for ContentFormat {
@@ -157,22 +201,27 @@ impl FromStr for ContentFormat {
             "markdown" => Ok(ContentFormat::Markdown),
             "json" => Ok(ContentFormat::Json),
             "txt" => Ok(ContentFormat::Txt),
+            "article" => Ok(ContentFormat::Article),
             _ => Err(format!("Invalid content format: {}", s)),
         }
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug)]
 struct FetchOptions {
     url: String,
-    #[serde(default)]
+    request: FetchRequest,
+    backend: Backend,
     screenshot_format: ScreenshotFormat,
-    #[serde(default)]
+    /// Bounds the screenshot is scaled down to fit
+    max_width: Option<u32>,
+    max_height: Option<u32>,
     content_format: ContentFormat,
-    #[serde(default)]
     syntax_highlighting: bool,
-    #[serde(default)]
     theme: Option<String>,
+    extract: Option<extract::Extraction>,
+    /// Cookie jar the fetch sends from and saves to
+    session: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -180,6 +229,8 @@ struct FetchResponse {
     screenshot: String,
     content: String,
     content_type: String,
+    /// Base64 of a fetched image, returned as is
+    image: Option<String>,
 }
 
 /// Fetch tool using plugin-builder
@@ -187,14 +238,20 @@ struct FetchTool;
 
 impl McpTool for FetchTool {
     const NAME: &'static str = "fetch";
+    type Args = Value;
 
     fn description(builder: DescriptionBuilder) -> DescriptionBuilder {
         builder
             .does("Retrieve and transform web content from any URL with advanced processing capabilities")
             .when("you need to scrape web pages and extract content in multiple formats (markdown, JSON, plain text)")
+            .when("you need only the article text of a page, with its title, byline and date, instead of the whole page")
+            .when("you need specific fields of a page (price, title, rating) as JSON, picked by CSS selector or from its JSON-LD")
+            .when("you need the text of a PDF, or an image to look at, from a URL")
             .when("you need to take screenshots of web pages for visual documentation")
             .when("you need to process dynamic websites with JavaScript rendering")
-            .when("you need to handle complex websites with multiple fallback strategies (Bevy, Chromium, Firecrawl)")
+            .when("you need to reach the web through an HTTP or SOCKS5 proxy")
+            .when("you need to stay logged in across several fetches, sharing cookies through a named session")
+            .when("you need to handle complex websites with multiple fallback strategies (Chromium, HTTP, Firecrawl), or pin one backend and see why another failed")
             .when("you need to apply syntax highlighting to extracted code content")
             .perfect_for("web scraping, content analysis, competitive research, and automated documentation")
     }
@@ -202,21 +259,96 @@ impl McpTool for FetchTool {
     fn schema(builder: SchemaBuilder) -> Value {
         builder
             .required_string("url", "The URL to fetch")
+            .optional_enum(
+                "method",
+                "HTTP method (default GET)",
+                &["GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS"],
+            )
+            .optional_map(
+                "headers",
+                "Request headers, e.g. {\"Authorization\": \"Bearer ...\"}",
+                json!({"type": "string"}),
+            )
+            .optional_string("body", "Request body, e.g. JSON or a url-encoded form")
+            .optional_string(
+                "content_type",
+                "Content-Type of the body (default application/json when the body is JSON, else application/x-www-form-urlencoded)",
+            )
+            .optional_integer(
+                "max_bytes",
+                "Largest response body to accept, in bytes (default 10485760); a larger response fails rather than being cut short",
+                Some(1),
+                None,
+            )
+            .optional_string(
+                "proxy",
+                "Proxy to fetch through: http://[user:pass@]host:port or socks5://[user:pass@]host:port (default: the plugin's configured proxy, if any)",
+            )
+            .optional_bool(
+                "insecure_tls",
+                "Accept any TLS certificate, e.g. one re-signed by a corporate proxy (default false)",
+            )
+            .optional_integer(
+                "timeout_ms",
+                "Time each fetch attempt may take, in milliseconds (default 30000)",
+                Some(1),
+                None,
+            )
+            .optional_string(
+                "session",
+                "Name of a cookie jar to send cookies from and keep the response's cookies in, so later fetches with the same session stay logged in (letters, digits, '-', '_', '.'); the clear_session tool forgets it",
+            )
+            .optional_enum(
+                "backend",
+                "Fetcher to use: auto (default) tries the headless browser, then plain HTTP, then Firecrawl; the others use only that one. Every response ends with JSON diagnostics naming the backend that served it, its HTTP status and the time each stage took",
+                &["auto", "chromium", "http", "firecrawl"],
+            )
             .optional_enum(
                 "screenshot_format",
-                "Format for the screenshot (base64 or sixel)",
-                &["base64", "sixel"],
+                "Format for the screenshot: base64 PNG (default), or escape sequences a terminal draws inline: sixel (256 colors), kitty or iterm2",
+                &["base64", "sixel", "kitty", "iterm2"],
+            )
+            .optional_integer(
+                "max_width",
+                "Widest the screenshot may be, in pixels; it is scaled down to fit, keeping its aspect ratio",
+                Some(1),
+                None,
+            )
+            .optional_integer(
+                "max_height",
+                "Tallest the screenshot may be, in pixels; it is scaled down to fit, keeping its aspect ratio",
+                Some(1),
+                None,
             )
             .optional_enum(
                 "content_format",
-                "Format for the content (markdown, json, or txt)",
-                &["markdown", "json", "txt"],
+                "Format for the content (markdown, json, or txt; article extracts the main content without navigation, ads and boilerplate, as markdown with title, byline and published date). PDFs become their text in this format; images are returned as image content",
+                &["markdown", "json", "txt", "article"],
             )
             .optional_bool(
                 "syntax_highlighting",
                 "Whether to apply syntax highlighting to the content",
             )
             .optional_string("theme", "Theme to use for syntax highlighting")
+            .optional_map(
+                "extract",
+                "Fields to return as JSON instead of the page content, each a CSS selector taking the first match's text, or {selector, attr, all} to read an attribute and/or collect every match, e.g. {\"price\": \".price\", \"image\": {\"selector\": \"img.main\", \"attr\": \"src\"}}. \"jsonld\": true adds the page's JSON-LD items",
+                json!({
+                    "anyOf": [
+                        {"type": "string"},
+                        {
+                            "type": "object",
+                            "properties": {
+                                "selector": {"type": "string"},
+                                "attr": {"type": "string"},
+                                "all": {"type": "boolean"}
+                            },
+                            "required": ["selector"]
+                        },
+                        {"type": "boolean"}
+                    ]
+                }),
+            )
             .build()
     }
 
@@ -225,21 +357,61 @@ impl McpTool for FetchTool {
         let options = parse_options(args.as_object().unwrap().clone())?;
 
         // Run the async fetching process
-        let fetch_result = block_on_fetch(options.url.as_str())?;
+        let progress = Progress::current();
+        let cancel = CancellationToken::current();
+        let deadline = Deadline::current();
+        let mut diagnostics = Diagnostics::default();
+        let fetch_result = block_on_fetch(
+            &options.request,
+            options.backend,
+            &mut diagnostics,
+            &progress,
+            &cancel,
+            &deadline,
+        );
+        // Cookies set before a failure, e.g. by a login's redirect, still count
+        if let (Some(session), Some(jar)) = (&options.session, &options.request.cookies) {
+            jar.lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .save(session)?;
+        }
+        let diagnostics = serde_json::to_value(&diagnostics).unwrap_or(Value::Null);
+        let fetch_result = match fetch_result {
+            Ok(fetch_result) => fetch_result,
+            Err(e) => {
+                return Ok(ContentBuilder::new()
+                    .text(e.to_string())
+                    .json(json!({ "diagnostics": diagnostics }))
+                    .is_error(true)
+                    .build());
+            }
+        };
 
         // Process results based on user preferences
+        cancel.check()?;
+        progress.report(90.0, "Formatting content");
+        let options_screenshot_format = options.screenshot_format;
         let response = process_fetch_result(fetch_result, options)?;
+        progress.report(100.0, "Done");
 
-        Ok(CallToolResult {
-            is_error: None,
-            content: vec![Content {
-                annotations: None,
-                text: Some(response.content),
-                mime_type: Some(response.content_type),
-                r#type: ContentType::Text,
-                data: Some(response.screenshot),
-            }],
-        })
+        if let Some(image) = response.image {
+            return Ok(ContentBuilder::new()
+                .image(image, response.content_type)
+                .json(json!({ "diagnostics": diagnostics }))
+                .build());
+        }
+        let result = ContentBuilder::new()
+            .text(response.content)
+            .mime_type(response.content_type);
+        let result = match (options_screenshot_format, response.screenshot) {
+            (_, screenshot) if screenshot.is_empty() => result,
+            // The terminal formats are escape codes, not image data
+            (ScreenshotFormat::Sixel, sixel) => result.text(sixel).mime_type("image/sixel"),
+            (ScreenshotFormat::Kitty, kitty) => result.text(kitty).mime_type("image/x-kitty"),
+            (ScreenshotFormat::Iterm2, iterm2) => result.text(iterm2).mime_type("image/x-iterm2"),
+            (ScreenshotFormat::Base64, png) => result.image(png, "image/png"),
+        };
+        Ok(result.json(json!({ "diagnostics": diagnostics })).build())
     }
 }
 
@@ -252,6 +424,16 @@ fn parse_options(args: serde_json::Map<String, Value>) -> Result<FetchOptions, E
             .map(|s| ScreenshotFormat::from_str(s).unwrap_or_default())
             .unwrap_or_default();
 
+        let dimension = |name: &str| match args.get(name) {
+            None | Some(Value::Null) => Ok(None),
+            Some(value) => match value.as_u64() {
+                Some(pixels) if pixels > 0 => Ok(Some(pixels.min(u32::MAX as u64) as u32)),
+                _ => Err(Error::msg(format!("{} must be a positive integer", name))),
+            },
+        };
+        let max_width = dimension("max_width")?;
+        let max_height = dimension("max_height")?;
+
         let content_format = args
             .get("content_format")
             .and_then(|v| v.as_str())
@@ -268,20 +450,162 @@ fn parse_options(args: serde_json::Map<String, Value>) -> Result<FetchOptions, E
             .and_then(|v| v.as_str())
             .map(|s| s.to_string());
 
+        let extract = match args.get("extract") {
+            None | Some(Value::Null) => None,
+            Some(value) => Some(extract::Extraction::parse(value).map_err(Error::msg)?),
+        };
+
+        let backend = match args.get("backend") {
+            None | Some(Value::Null) => Backend::default(),
+            Some(Value::String(backend)) => Backend::from_str(backend).map_err(Error::msg)?,
+            Some(_) => return Err(Error::msg("backend must be a string")),
+        };
+
+        let session = match args.get("session") {
+            None | Some(Value::Null) => None,
+            Some(Value::String(session)) => {
+                cookies::validate_session(session).map_err(Error::msg)?;
+                Some(session.clone())
+            }
+            Some(_) => return Err(Error::msg("session must be a string")),
+        };
+        let mut request = parse_request(url, &args)?;
+        if let Some(session) = &session {
+            let jar = cookies::CookieJar::load(session)?;
+            request.cookies = Some(std::sync::Arc::new(std::sync::Mutex::new(jar)));
+        }
+
         Ok(FetchOptions {
             url: url.clone(),
+            request,
+            backend,
             screenshot_format,
+            max_width,
+            max_height,
             content_format,
             syntax_highlighting,
             theme,
+            extract,
+            session,
         })
     } else {
         Err(Error::msg("Please provide a url"))
     }
 }
 
+/// Method, headers and body to fetch `url` with
+fn parse_request(url: &str, args: &serde_json::Map<String, Value>) -> Result<FetchRequest, Error> {
+    let method = match args.get("method") {
+        None | Some(Value::Null) => "GET".to_string(),
+        Some(Value::String(method)) => method.trim().to_ascii_uppercase(),
+        Some(_) => return Err(Error::msg("method must be a string")),
+    };
+    if method.is_empty() || !method.bytes().all(|b| b.is_ascii_alphabetic()) {
+        return Err(Error::msg(format!("Invalid method: {}", method)));
+    }
+
+    let mut headers = Vec::new();
+    match args.get("headers") {
+        None | Some(Value::Null) => {}
+        Some(Value::Object(map)) => {
+            for (name, value) in map {
+                let value = match value {
+                    Value::String(value) => value.clone(),
+                    Value::Number(_) | Value::Bool(_) => value.to_string(),
+                    _ => {
+                        return Err(Error::msg(format!("Header {} must be a string", name)));
+                    }
+                };
+                headers.push((name.clone(), value));
+            }
+        }
+        Some(_) => {
+            return Err(Error::msg(
+                "headers must be an object of header names to values",
+            ));
+        }
+    }
+
+    // A JSON body may also come as a structured value rather than a string
+    let body = match args.get("body") {
+        None | Some(Value::Null) => None,
+        Some(Value::String(body)) => Some(body.clone()),
+        Some(body) => Some(body.to_string()),
+    };
+    let default_content_type = body.as_deref().map(|body| {
+        if serde_json::from_str::<Value>(body).is_ok() {
+            "application/json"
+        } else {
+            "application/x-www-form-urlencoded"
+        }
+    });
+    if body.is_some() && matches!(method.as_str(), "GET" | "HEAD") {
+        return Err(Error::msg(format!(
+            "A {} request can't have a body",
+            method
+        )));
+    }
+    let content_type = args
+        .get("content_type")
+        .and_then(|v| v.as_str())
+        .or(default_content_type)
+        .map(str::to_string);
+    let max_bytes = match args.get("max_bytes") {
+        None | Some(Value::Null) => DEFAULT_MAX_BYTES,
+        Some(value) => match value.as_u64() {
+            Some(max_bytes) if max_bytes > 0 => max_bytes as usize,
+            _ => return Err(Error::msg("max_bytes must be a positive integer")),
+        },
+    };
+    // Operators behind a proxy can set it once in the plugin's config
+    let proxy = match args.get("proxy") {
+        None | Some(Value::Null) => config::get("proxy")?.filter(|p| !p.trim().is_empty()),
+        Some(Value::String(proxy)) => Some(proxy.clone()),
+        Some(_) => return Err(Error::msg("proxy must be a string")),
+    };
+    let proxy = proxy
+        .map(|proxy| Proxy::parse(&proxy))
+        .transpose()
+        .map_err(Error::msg)?;
+    let insecure_tls = match args.get("insecure_tls") {
+        None | Some(Value::Null) => false,
+        Some(Value::Bool(insecure)) => *insecure,
+        Some(_) => return Err(Error::msg("insecure_tls must be true or false")),
+    };
+    let timeout = match args.get("timeout_ms") {
+        None | Some(Value::Null) => DEFAULT_TIMEOUT,
+        Some(value) => match value.as_u64() {
+            Some(ms) if ms > 0 => std::time::Duration::from_millis(ms),
+            _ => return Err(Error::msg("timeout_ms must be a positive integer")),
+        },
+    };
+
+    Ok(FetchRequest {
+        url: url.to_string(),
+        method,
+        headers,
+        body,
+        content_type,
+        max_bytes,
+        proxy,
+        insecure_tls,
+        timeout,
+        cookies: None,
+    })
+}
+
+/// Least time left for launching a headless browser to be worth it
+const BROWSER_MIN_BUDGET: std::time::Duration = std::time::Duration::from_secs(15);
+
 // Helper function to run async code from the sync world
-fn block_on_fetch(url: &str) -> Result<chromiumoxide::FetchResult, Error> {
+fn block_on_fetch(
+    request: &FetchRequest,
+    backend: Backend,
+    diagnostics: &mut Diagnostics,
+    progress: &Progress,
+    cancel: &CancellationToken,
+    deadline: &Deadline,
+) -> Result<chromiumoxide::FetchResult, Error> {
     // Set up a minimal runtime for async execution
     let rt = tokio::runtime::Builder::new_current_thread()
         .enable_all()
@@ -289,32 +613,74 @@ fn block_on_fetch(url: &str) -> Result<chromiumoxide::FetchResult, Error> {
         .map_err(|e| Error::msg(format!("Failed to create runtime: {}", e)))?;
 
     rt.block_on(async {
-        // Multi-stage fetching with fallbacks:
+        match backend {
+            Backend::Auto => {}
+            Backend::Chromium => progress.report(0.0, "Fetching with headless browser"),
+            Backend::Http => progress.report(0.0, "Fetching over HTTP"),
+            Backend::Firecrawl => progress.report(0.0, "Fetching with Firecrawl"),
+        }
+        if backend != Backend::Auto {
+            return diagnostics
+                .attempt(backend, request)
+                .await
+                .map_err(|e| Error::msg(format!("{} fetch failed: {}", backend.as_str(), e)));
+        }
+
+        // PDFs and images go straight over HTTP: a browser would only render
+        // its viewer, and the converters below expect HTML
+        if request.method == "GET" {
+            progress.report(0.0, "Checking content type");
+            let started = std::time::Instant::now();
+            let head = HyperFetcher::head(request).await;
+            diagnostics.attempts.push(Attempt {
+                backend: Backend::Http.as_str(),
+                stage: "head",
+                elapsed_ms: started.elapsed().as_millis() as u64,
+                status: match &head {
+                    Ok(response) => Some(response.status),
+                    Err(e) => error_status(e),
+                },
+                error: head.as_ref().err().map(|e| e.to_string()),
+            });
+            if head.is_ok_and(|response| is_binary_content(&response.content_type)) {
+                cancel.check()?;
+                progress.report(20.0, "Downloading document");
+                return diagnostics
+                    .attempt(Backend::Http, request)
+                    .await
+                    .map_err(|e| Error::msg(format!("Download failed: {}", e)));
+            }
+        }
 
-        // 1. First attempt: Use chromiumoxide (headless browser)
-        let chromium_result = chromiumoxide::ChromiumFetcher.fetch_content(url).await;
+        // Multi-stage fetching with fallbacks:
 
-        if let Ok(result) = chromium_result {
-            return Ok(result);
+        // 1. First attempt: Use chromiumoxide (headless browser), unless the
+        //    call's deadline leaves too little time for it
+        if deadline.allows(BROWSER_MIN_BUDGET) {
+            progress.report(0.0, "Fetching with headless browser");
+            if let Ok(result) = diagnostics.attempt(Backend::Chromium, request).await {
+                return Ok(result);
+            }
+        } else {
+            diagnostics.skip(Backend::Chromium, "too little time left for a browser");
         }
 
         // 2. Second attempt: Use hyper (HTTP client)
-        let hyper_result = HyperFetcher.fetch_content(url).await;
-
-        if let Ok(result) = hyper_result {
+        cancel.check()?;
+        deadline.check()?;
+        progress.report(40.0, "Fetching over HTTP");
+        if let Ok(result) = diagnostics.attempt(Backend::Http, request).await {
             return Ok(result);
         }
 
         // 3. Final contingency: Use firecrawl
-        let firecrawl_result = firecrawl::FirecrawlFetcher.fetch_content(url).await;
-
-        match firecrawl_result {
-            Ok(result) => Ok(result),
-            Err(e) => Err(Error::msg(format!(
-                "All fetch attempts failed. Last error: {}",
-                e
-            ))),
-        }
+        cancel.check()?;
+        deadline.check()?;
+        progress.report(65.0, "HTTP fetch failed, trying Firecrawl");
+        diagnostics
+            .attempt(Backend::Firecrawl, request)
+            .await
+            .map_err(|e| Error::msg(format!("All fetch attempts failed. Last error: {}", e)))
     })
 }
 
@@ -323,21 +689,22 @@ fn process_fetch_result(
     result: chromiumoxide::FetchResult,
     options: FetchOptions,
 ) -> Result<FetchResponse, Error> {
-    // Process the screenshot based on the requested format
-    let screenshot = match options.screenshot_format {
-        ScreenshotFormat::Base64 => result.screenshot_base64,
-        ScreenshotFormat::Sixel => {
-            // Convert base64 to image, then to sixel
-            let image_data = base64::engine::general_purpose::STANDARD
-                .decode(&result.screenshot_base64)
-                .map_err(|e| Error::msg(format!("Failed to decode screenshot: {}", e)))?;
-
-            let image = image::load_from_memory(&image_data)
-                .map_err(|e| Error::msg(format!("Failed to load image: {}", e)))?;
-
-            encode_sixel(&image.to_rgb8())
-        }
-    };
+    if let Some(body) = result.body {
+        return process_document(body, result.content_type, &options);
+    }
+
+    let screenshot = render_screenshot(result.screenshot_base64, &options)?;
+
+    if let Some(extraction) = &options.extract {
+        let mut data = extraction.run(&result.content);
+        data.insert("url".to_string(), Value::String(options.url.clone()));
+        return Ok(FetchResponse {
+            screenshot,
+            content: Value::Object(data).to_string(),
+            content_type: "application/json".to_string(),
+            image: None,
+        });
+    }
 
     // Process the content based on the requested format
     let (content, content_type) = match options.content_format {
@@ -369,6 +736,35 @@ fn process_fetch_result(
             let text_content = extract_text_content(&result.content);
             (text_content, "text/plain".to_string())
         }
+        ContentFormat::Article => {
+            let article = readability::extract(&result.content);
+            let converter = HtmlToMarkdown::builder()
+                .skip_tags(vec!["script", "style"])
+                .build();
+            let body = converter
+                .convert(&article.html)
+                .map_err(|e| Error::msg(format!("Failed to convert article to markdown: {}", e)))?;
+
+            // Front matter values are JSON strings, which are valid YAML
+            let mut markdown = String::from("---\n");
+            let fields = [
+                ("title", article.title),
+                ("byline", article.byline),
+                ("published", article.published),
+                ("site_name", article.site_name),
+                ("url", Some(options.url.clone())),
+            ];
+            for (key, value) in fields {
+                if let Some(value) = value {
+                    markdown.push_str(&format!("{}: {}\n", key, Value::String(value)));
+                }
+            }
+            markdown.push_str("---\n\n");
+            markdown.push_str(body.trim());
+            markdown.push('\n');
+
+            (markdown, "text/markdown".to_string())
+        }
     };
 
     // Apply syntax highlighting if requested
@@ -382,6 +778,102 @@ fn process_fetch_result(
         screenshot,
         content: final_content,
         content_type,
+        image: None,
+    })
+}
+
+/// Convert a PDF to text in the requested format, or pass an image through
+fn process_document(
+    body: Vec<u8>,
+    content_type: String,
+    options: &FetchOptions,
+) -> Result<FetchResponse, Error> {
+    if options.extract.is_some() {
+        return Err(Error::msg(format!(
+            "extract needs an HTML page, got {}",
+            content_type
+        )));
+    }
+    if !content_type.to_ascii_lowercase().contains("pdf") {
+        return Ok(FetchResponse {
+            screenshot: String::new(),
+            content: String::new(),
+            content_type: content_type
+                .split(';')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_string(),
+            image: Some(base64::engine::general_purpose::STANDARD.encode(&body)),
+        });
+    }
+
+    let document = lopdf::Document::load_mem(&body)
+        .map_err(|e| Error::msg(format!("Failed to parse PDF: {}", e)))?;
+    let pages: Vec<(u32, String)> = document
+        .get_pages()
+        .into_keys()
+        .map(|page| (page, document.extract_text(&[page]).unwrap_or_default()))
+        .collect();
+
+    let (content, content_type) = match options.content_format {
+        ContentFormat::Json => {
+            let json = json!({
+                "url": options.url,
+                "page_count": pages.len(),
+                "pages": pages
+                    .iter()
+                    .map(|(page, text)| json!({ "page": page, "text": text.trim() }))
+                    .collect::<Vec<_>>(),
+                "timestamp": chrono::Utc::now().to_rfc3339(),
+                "original_content_type": content_type
+            });
+            (json.to_string(), "application/json".to_string())
+        }
+        ContentFormat::Txt => {
+            let text: Vec<&str> = pages.iter().map(|(_, text)| text.trim()).collect();
+            (text.join("\n\n"), "text/plain".to_string())
+        }
+        ContentFormat::Markdown | ContentFormat::Article => {
+            let mut markdown = String::new();
+            for (page, text) in &pages {
+                markdown.push_str(&format!("## Page {}\n\n{}\n\n", page, text.trim()));
+            }
+            (markdown.trim_end().to_string(), "text/markdown".to_string())
+        }
+    };
+
+    Ok(FetchResponse {
+        screenshot: String::new(),
+        content,
+        content_type,
+        image: None,
+    })
+}
+
+/// The screenshot scaled to the requested bounds, in the requested format
+fn render_screenshot(png_base64: String, options: &FetchOptions) -> Result<String, Error> {
+    let resize = options.max_width.is_some() || options.max_height.is_some();
+    if matches!(options.screenshot_format, ScreenshotFormat::Base64) && !resize {
+        return Ok(png_base64);
+    }
+
+    let image_data = base64::engine::general_purpose::STANDARD
+        .decode(&png_base64)
+        .map_err(|e| Error::msg(format!("Failed to decode screenshot: {}", e)))?;
+    let image = image::load_from_memory(&image_data)
+        .map_err(|e| Error::msg(format!("Failed to load image: {}", e)))?;
+    let image = inline_image::fit(image, options.max_width, options.max_height);
+    let png = || {
+        inline_image::png(&image)
+            .map_err(|e| Error::msg(format!("Failed to encode screenshot: {}", e)))
+    };
+
+    Ok(match options.screenshot_format {
+        ScreenshotFormat::Base64 => base64::engine::general_purpose::STANDARD.encode(png()?),
+        ScreenshotFormat::Sixel => inline_image::sixel(&image.to_rgb8()),
+        ScreenshotFormat::Kitty => inline_image::kitty(&png()?),
+        ScreenshotFormat::Iterm2 => inline_image::iterm2(&png()?, image.width(), image.height()),
     })
 }
 
@@ -399,6 +891,7 @@ fn extract_title(html: &str) -> String {
 // Extract text content from HTML
 fn extract_text_content(html: &str) -> String {
     // Simple text extraction - in a real implementation this would be more robust
+    let html = strip_scripts(html);
     let mut text = String::new();
     let mut in_tag = false;
 
@@ -420,6 +913,22 @@ fn extract_text_content(html: &str) -> String {
     text.trim().to_string()
 }
 
+/// `html` without its `<script>` elements, which fetchers keep for JSON-LD
+fn strip_scripts(html: &str) -> String {
+    let lower = html.to_ascii_lowercase();
+    let mut kept = String::with_capacity(html.len());
+    let mut pos = 0;
+    while let Some(start) = lower[pos..].find("<script").map(|i| pos + i) {
+        kept.push_str(&html[pos..start]);
+        pos = match lower[start..].find("</script>") {
+            Some(end) => start + end + "</script>".len(),
+            None => html.len(),
+        };
+    }
+    kept.push_str(&html[pos..]);
+    kept
+}
+
 // Apply syntax highlighting to content
 fn apply_syntax_highlighting(
     content: &str,
@@ -446,7 +955,7 @@ fn apply_syntax_highlighting(
 
             Ok(html)
         }
-        ContentFormat::Markdown => {
+        ContentFormat::Markdown | ContentFormat::Article => {
             // Simple markdown highlighting (in a real implementation this would be more sophisticated)
             Ok(content.to_string())
         }
@@ -458,9 +967,11 @@ fn apply_syntax_highlighting(
 fn plugin() -> McpPlugin<Ready> {
     mcp_plugin("fetch")
         .description(
-            "Advanced web content fetching with multi-stage fallback and format conversion",
+            "Advanced web content fetching with multi-stage fallback, format conversion, cookie sessions and polite multi-page crawling",
         )
         .tool::<FetchTool>()
+        .tool::<crawl::CrawlTool>()
+        .tool::<cookies::ClearSessionTool>()
         .serve()
 }
 
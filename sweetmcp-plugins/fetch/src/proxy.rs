@@ -0,0 +1,225 @@
+//! Proxies `fetch` reaches the web through.
+//!
+//! A proxy is given as a URL: `http://[user:pass@]host[:port]` tunnels with
+//! `CONNECT`, `socks5://[user:pass@]host[:port]` (or `socks5h://`) with a
+//! SOCKS5 handshake. Target host names are always resolved by the proxy, so
+//! the two socks schemes behave alike.
+
+use std::io;
+
+use base64::Engine;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use url::Url;
+
+/// Longest proxy reply to a `CONNECT` we read before giving up
+const MAX_CONNECT_REPLY: usize = 8 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProxyScheme {
+    Http,
+    Socks5,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Proxy {
+    pub scheme: ProxyScheme,
+    pub host: String,
+    pub port: u16,
+    /// User name and password from the proxy URL
+    pub credentials: Option<(String, String)>,
+}
+
+impl Proxy {
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let url = Url::parse(raw.trim()).map_err(|e| format!("Invalid proxy {}: {}", raw, e))?;
+        let scheme = match url.scheme() {
+            "http" => ProxyScheme::Http,
+            "socks5" | "socks5h" => ProxyScheme::Socks5,
+            other => {
+                return Err(format!(
+                    "Unsupported proxy scheme {}; use http or socks5",
+                    other
+                ));
+            }
+        };
+        let host = url
+            .host_str()
+            .ok_or_else(|| format!("Proxy {} has no host", raw))?
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_string();
+        let port = url.port().unwrap_or(match scheme {
+            ProxyScheme::Http => 80,
+            ProxyScheme::Socks5 => 1080,
+        });
+        let credentials = (!url.username().is_empty()).then(|| {
+            (
+                percent_decode(url.username()),
+                percent_decode(url.password().unwrap_or_default()),
+            )
+        });
+        Ok(Self {
+            scheme,
+            host,
+            port,
+            credentials,
+        })
+    }
+
+    /// `scheme://host:port`, without credentials
+    pub fn server(&self) -> String {
+        let scheme = match self.scheme {
+            ProxyScheme::Http => "http",
+            ProxyScheme::Socks5 => "socks5",
+        };
+        if self.host.contains(':') {
+            format!("{}://[{}]:{}", scheme, self.host, self.port)
+        } else {
+            format!("{}://{}:{}", scheme, self.host, self.port)
+        }
+    }
+
+    /// Connect to the proxy and open a tunnel to `host:port` through it
+    pub async fn connect(&self, host: &str, port: u16) -> io::Result<TcpStream> {
+        let mut stream = TcpStream::connect((self.host.as_str(), self.port)).await?;
+        stream.set_nodelay(true)?;
+        match self.scheme {
+            ProxyScheme::Http => self.http_connect(&mut stream, host, port).await?,
+            ProxyScheme::Socks5 => self.socks5_connect(&mut stream, host, port).await?,
+        }
+        Ok(stream)
+    }
+
+    async fn http_connect(&self, stream: &mut TcpStream, host: &str, port: u16) -> io::Result<()> {
+        let target = if host.contains(':') {
+            format!("[{}]:{}", host, port)
+        } else {
+            format!("{}:{}", host, port)
+        };
+        let mut request = format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n", target);
+        if let Some((user, password)) = &self.credentials {
+            let token =
+                base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", user, password));
+            request.push_str(&format!("Proxy-Authorization: Basic {}\r\n", token));
+        }
+        request.push_str("\r\n");
+        stream.write_all(request.as_bytes()).await?;
+
+        // Read the reply up to its blank line, leaving the tunnel untouched
+        let mut reply = Vec::new();
+        while !reply.ends_with(b"\r\n\r\n") {
+            if reply.len() >= MAX_CONNECT_REPLY {
+                return Err(proxy_error("proxy reply to CONNECT is too long"));
+            }
+            let byte = stream.read_u8().await?;
+            reply.push(byte);
+        }
+        let reply = String::from_utf8_lossy(&reply);
+        let status = reply.lines().next().unwrap_or_default();
+        match status.split_whitespace().nth(1) {
+            Some(code) if code.starts_with('2') => Ok(()),
+            Some("407") => Err(proxy_error("proxy requires authentication")),
+            _ => Err(proxy_error(format!("proxy refused CONNECT: {}", status))),
+        }
+    }
+
+    async fn socks5_connect(
+        &self,
+        stream: &mut TcpStream,
+        host: &str,
+        port: u16,
+    ) -> io::Result<()> {
+        // Offer no authentication, and user/password when we have them
+        let greeting: &[u8] = if self.credentials.is_some() {
+            &[0x05, 0x02, 0x00, 0x02]
+        } else {
+            &[0x05, 0x01, 0x00]
+        };
+        stream.write_all(greeting).await?;
+        let mut choice = [0u8; 2];
+        stream.read_exact(&mut choice).await?;
+        match (choice, &self.credentials) {
+            ([0x05, 0x00], _) => {}
+            ([0x05, 0x02], Some((user, password))) => {
+                if user.len() > 255 || password.len() > 255 {
+                    return Err(proxy_error("socks5 user name or password is too long"));
+                }
+                let mut auth = vec![0x01, user.len() as u8];
+                auth.extend_from_slice(user.as_bytes());
+                auth.push(password.len() as u8);
+                auth.extend_from_slice(password.as_bytes());
+                stream.write_all(&auth).await?;
+                let mut status = [0u8; 2];
+                stream.read_exact(&mut status).await?;
+                if status[1] != 0x00 {
+                    return Err(proxy_error("socks5 proxy rejected the credentials"));
+                }
+            }
+            ([0x05, 0xff], _) => {
+                return Err(proxy_error("socks5 proxy accepts none of our auth methods"));
+            }
+            _ => return Err(proxy_error("unexpected socks5 greeting reply")),
+        }
+
+        if host.len() > 255 {
+            return Err(proxy_error("host name is too long for socks5"));
+        }
+        let mut connect = vec![0x05, 0x01, 0x00, 0x03, host.len() as u8];
+        connect.extend_from_slice(host.as_bytes());
+        connect.extend_from_slice(&port.to_be_bytes());
+        stream.write_all(&connect).await?;
+
+        let mut reply = [0u8; 4];
+        stream.read_exact(&mut reply).await?;
+        if reply[1] != 0x00 {
+            return Err(proxy_error(format!(
+                "socks5 proxy could not connect to {}:{} (reply {})",
+                host, port, reply[1]
+            )));
+        }
+        // Skip the bound address and port
+        let address_len = match reply[3] {
+            0x01 => 4,
+            0x04 => 16,
+            0x03 => stream.read_u8().await? as usize,
+            other => {
+                return Err(proxy_error(format!(
+                    "unknown socks5 address type {}",
+                    other
+                )));
+            }
+        };
+        let mut bound = vec![0u8; address_len + 2];
+        stream.read_exact(&mut bound).await?;
+        Ok(())
+    }
+}
+
+fn proxy_error(message: impl Into<String>) -> io::Error {
+    io::Error::other(message.into())
+}
+
+/// Decode `%XX` escapes of a URL's user info
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
@@ -0,0 +1,527 @@
+//! Readability-style main content extraction.
+//!
+//! Scripts, navigation, forms and elements whose class or id looks like
+//! boilerplate (ads, sidebars, comments, share widgets) are dropped first.
+//! The remaining paragraphs then score their containers the way Mozilla's
+//! Readability does, and the best container plus its strong siblings become
+//! the article body. Title, byline and publication date come from page
+//! metadata, JSON-LD, or the markup itself.
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use html5ever::serialize::{SerializeOpts, TraversalScope, serialize};
+use html5ever::tendril::TendrilSink;
+use markup5ever_rcdom::{Handle, Node, NodeData, RcDom, SerializableHandle};
+use serde_json::Value;
+
+/// The main content of a page and what is known about it
+#[derive(Debug, Default)]
+pub struct Article {
+    pub title: Option<String>,
+    pub byline: Option<String>,
+    pub published: Option<String>,
+    pub site_name: Option<String>,
+    /// Cleaned HTML of the article body
+    pub html: String,
+}
+
+/// Elements that never hold article text
+const REMOVED_TAGS: &[&str] = &[
+    "aside", "button", "canvas", "dialog", "embed", "footer", "form", "header", "iframe", "input",
+    "link", "meta", "nav", "noscript", "object", "script", "select", "style", "svg", "template",
+    "textarea",
+];
+
+/// Class and id words that mark boilerplate; matched as word prefixes
+const NEGATIVE: &[&str] = &[
+    "advert",
+    "banner",
+    "breadcrumb",
+    "comment",
+    "cookie",
+    "disqus",
+    "footer",
+    "masthead",
+    "menu",
+    "modal",
+    "nav",
+    "newsletter",
+    "outbrain",
+    "pagination",
+    "popup",
+    "promo",
+    "related",
+    "share",
+    "sharing",
+    "sidebar",
+    "skip",
+    "social",
+    "sponsor",
+    "subscribe",
+    "taboola",
+    "widget",
+];
+
+/// Class and id words that mark content; matched as word prefixes
+const POSITIVE: &[&str] = &[
+    "article", "blog", "body", "content", "entry", "main", "post", "story", "text",
+];
+
+/// Elements that make a `div` a container rather than a paragraph
+const BLOCK_TAGS: &[&str] = &[
+    "article",
+    "blockquote",
+    "div",
+    "dl",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "ul",
+];
+
+/// Paragraphs shorter than this don't vote for a container
+const MIN_PARAGRAPH_LEN: usize = 25;
+
+/// Extract the main content and metadata of `html`
+pub fn extract(html: &str) -> Article {
+    let root = parse(html);
+
+    // Metadata first: it lives in <head>, <script> and <time>, which the
+    // cleaning pass removes
+    let meta = meta_tags(&root);
+    let linked_data = linked_data(&root);
+    let title = first_of(&[
+        meta.get("og:title").cloned(),
+        linked_data.get("headline").cloned(),
+        meta.get("twitter:title").cloned(),
+        find(&root, &|n: &Handle| tag(n) == Some("title")).map(|n| inner_text(&n)),
+        find(&root, &|n: &Handle| tag(n) == Some("h1")).map(|n| inner_text(&n)),
+    ])
+    .map(|title| strip_site_name(&title));
+    let byline = first_of(&[
+        meta.get("author").cloned(),
+        linked_data.get("author").cloned(),
+        meta.get("article:author")
+            .filter(|a| !a.starts_with("http"))
+            .cloned(),
+        meta.get("dc.creator").cloned(),
+        find(&root, &|n: &Handle| {
+            attr(n, "rel").as_deref() == Some("author")
+                || attr(n, "itemprop").as_deref() == Some("author")
+                || words(n).iter().any(|w| w == "byline" || w == "author")
+        })
+        .map(|n| inner_text(&n))
+        .filter(|text| text.chars().count() < 100),
+    ]);
+    let published = first_of(&[
+        meta.get("article:published_time").cloned(),
+        linked_data.get("datePublished").cloned(),
+        meta.get("datepublished").cloned(),
+        meta.get("date").cloned(),
+        meta.get("pubdate").cloned(),
+        meta.get("publish-date").cloned(),
+        meta.get("dc.date").cloned(),
+        find(&root, &|n: &Handle| {
+            tag(n) == Some("time") && attr(n, "datetime").is_some()
+        })
+        .and_then(|n| attr(&n, "datetime")),
+    ]);
+    let site_name = meta.get("og:site_name").cloned();
+
+    clean(&root);
+    let html = best_content(&root);
+
+    Article {
+        title,
+        byline,
+        published,
+        site_name,
+        html,
+    }
+}
+
+/// Parse `html` into a document tree
+pub(crate) fn parse(html: &str) -> Handle {
+    html5ever::parse_document(RcDom::default(), Default::default())
+        .one(html)
+        .document
+}
+
+/// The first value that is present and not blank
+fn first_of(candidates: &[Option<String>]) -> Option<String> {
+    candidates
+        .iter()
+        .flatten()
+        .map(|value| value.trim())
+        .find(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+/// "Story headline | Site" -> "Story headline", when the headline is long enough
+fn strip_site_name(title: &str) -> String {
+    for separator in [" | ", " - ", " — ", " :: ", " » "] {
+        if let Some((head, _)) = title.rsplit_once(separator) {
+            if head.split_whitespace().count() >= 3 {
+                return head.trim().to_string();
+            }
+        }
+    }
+    title.trim().to_string()
+}
+
+/// `<meta>` content keyed by lowercased name, property or itemprop
+fn meta_tags(root: &Handle) -> HashMap<String, String> {
+    let mut meta = HashMap::new();
+    walk(root, &mut |node| {
+        if tag(node) != Some("meta") {
+            return;
+        }
+        let Some(content) = attr(node, "content") else {
+            return;
+        };
+        for key in ["property", "name", "itemprop"] {
+            if let Some(key) = attr(node, key) {
+                meta.entry(key.to_lowercase())
+                    .or_insert_with(|| content.clone());
+            }
+        }
+    });
+    meta
+}
+
+/// Every item of the page's JSON-LD: single objects, lists, and the members
+/// of an `@graph`
+pub(crate) fn json_ld(root: &Handle) -> Vec<Value> {
+    let mut items = Vec::new();
+    walk(root, &mut |node| {
+        if tag(node) != Some("script")
+            || attr(node, "type").as_deref() != Some("application/ld+json")
+        {
+            return;
+        }
+        let Ok(value) = serde_json::from_str::<Value>(&inner_text(node)) else {
+            return;
+        };
+        match value {
+            Value::Array(list) => items.extend(list),
+            Value::Object(mut object) => match object.remove("@graph") {
+                Some(Value::Array(graph)) => items.extend(graph),
+                Some(graph) => {
+                    object.insert("@graph".to_string(), graph);
+                    items.push(Value::Object(object));
+                }
+                None => items.push(Value::Object(object)),
+            },
+            _ => {}
+        }
+    });
+    items
+}
+
+/// Headline, author and dates from the page's schema.org JSON-LD
+fn linked_data(root: &Handle) -> HashMap<&'static str, String> {
+    let mut found = HashMap::new();
+    for item in json_ld(root) {
+        if let Some(headline) = item.get("headline").and_then(Value::as_str) {
+            found
+                .entry("headline")
+                .or_insert_with(|| headline.to_string());
+        }
+        if let Some(date) = item.get("datePublished").and_then(Value::as_str) {
+            found
+                .entry("datePublished")
+                .or_insert_with(|| date.to_string());
+        }
+        let author = match item.get("author") {
+            Some(Value::Array(authors)) => authors.first(),
+            author => author,
+        };
+        let name = match author {
+            Some(Value::String(name)) => Some(name.as_str()),
+            Some(author) => author.get("name").and_then(Value::as_str),
+            None => None,
+        };
+        if let Some(name) = name {
+            found.entry("author").or_insert_with(|| name.to_string());
+        }
+    }
+    found
+}
+
+/// Drop comments, non-content elements, hidden elements and boilerplate
+fn clean(node: &Handle) {
+    node.children
+        .borrow_mut()
+        .retain(|child| match &child.data {
+            NodeData::Comment { .. } | NodeData::ProcessingInstruction { .. } => false,
+            NodeData::Element { .. } => !is_boilerplate(child),
+            _ => true,
+        });
+    let children = node.children.borrow().clone();
+    for child in &children {
+        clean(child);
+    }
+}
+
+fn is_boilerplate(node: &Handle) -> bool {
+    let Some(tag) = tag(node) else {
+        return false;
+    };
+    if REMOVED_TAGS.contains(&tag) {
+        return true;
+    }
+    if attr(node, "hidden").is_some()
+        || attr(node, "aria-hidden").as_deref() == Some("true")
+        || attr(node, "style").is_some_and(|style| {
+            let style = style.replace(' ', "").to_lowercase();
+            style.contains("display:none") || style.contains("visibility:hidden")
+        })
+    {
+        return true;
+    }
+    if matches!(tag, "html" | "body" | "article" | "main") {
+        return false;
+    }
+    class_weight(node) < 0
+}
+
+/// The class and id of `node` split into lowercase words
+fn words(node: &Handle) -> Vec<String> {
+    let mut words = Vec::new();
+    for name in ["class", "id"] {
+        if let Some(value) = attr(node, name) {
+            words.extend(
+                value
+                    .to_lowercase()
+                    .split(|c: char| !c.is_ascii_alphanumeric())
+                    .filter(|w| !w.is_empty())
+                    .map(str::to_string),
+            );
+        }
+    }
+    words
+}
+
+/// +25 for content-looking class or id words, -25 for boilerplate ones
+fn class_weight(node: &Handle) -> i32 {
+    let words = words(node);
+    let has = |list: &[&str]| {
+        words
+            .iter()
+            .any(|w| list.iter().any(|prefix| w.starts_with(prefix)))
+    };
+    let is_ad = words.iter().any(|w| w == "ad" || w == "ads");
+    let mut weight = 0;
+    if has(POSITIVE) {
+        weight += 25;
+    }
+    if is_ad || has(NEGATIVE) {
+        weight -= 25;
+    }
+    weight
+}
+
+/// HTML of the highest-scoring container and its strong siblings
+fn best_content(root: &Handle) -> String {
+    let mut scores: HashMap<*const Node, (Handle, f64)> = HashMap::new();
+    let mut paragraphs = Vec::new();
+    walk(root, &mut |node| match tag(node) {
+        Some("p" | "pre" | "td" | "blockquote") => paragraphs.push(node.clone()),
+        Some("div") if !has_block_child(node) => paragraphs.push(node.clone()),
+        _ => {}
+    });
+
+    for paragraph in &paragraphs {
+        let text = inner_text(paragraph);
+        let len = text.chars().count();
+        if len < MIN_PARAGRAPH_LEN {
+            continue;
+        }
+        let score = 1.0 + text.matches(',').count() as f64 + (len / 100).min(3) as f64;
+
+        let mut ancestor = parent(paragraph);
+        for level in 0..3 {
+            let Some(node) = ancestor else {
+                break;
+            };
+            if tag(&node).is_none() {
+                break;
+            }
+            let divider = match level {
+                0 => 1.0,
+                1 => 2.0,
+                _ => level as f64 * 3.0,
+            };
+            scores
+                .entry(Rc::as_ptr(&node))
+                .or_insert_with(|| (node.clone(), initial_score(&node)))
+                .1 += score / divider;
+            ancestor = parent(&node);
+        }
+    }
+
+    let final_score = |(node, score): &(Handle, f64)| score * (1.0 - link_density(node));
+    let best = scores
+        .values()
+        .map(|entry| (entry.0.clone(), final_score(entry)))
+        .max_by(|a, b| a.1.total_cmp(&b.1));
+    let Some((best, best_score)) = best else {
+        // Nothing scored: keep whatever is left of the body
+        return find(root, &|n: &Handle| tag(n) == Some("body"))
+            .map(|body| serialize_children(&body))
+            .unwrap_or_default();
+    };
+
+    // Siblings that score well or read like prose belong to the article too
+    let threshold = (best_score * 0.2).max(10.0);
+    let siblings = match parent(&best) {
+        Some(parent) if tag(&parent).is_some_and(|t| t != "html") => {
+            let children = parent.children.borrow().clone();
+            children
+        }
+        _ => vec![best.clone()],
+    };
+    let mut html = String::new();
+    for sibling in siblings {
+        let include = Rc::ptr_eq(&sibling, &best)
+            || scores
+                .get(&Rc::as_ptr(&sibling))
+                .is_some_and(|entry| final_score(entry) >= threshold)
+            || (tag(&sibling) == Some("p") && {
+                let text = inner_text(&sibling);
+                let len = text.chars().count();
+                let density = link_density(&sibling);
+                (len > 80 && density < 0.25) || (len > 0 && density == 0.0 && text.ends_with('.'))
+            });
+        if include {
+            html.push_str(&serialize_node(&sibling));
+        }
+    }
+    html
+}
+
+/// Starting score of a container by tag and class
+fn initial_score(node: &Handle) -> f64 {
+    let by_tag = match tag(node) {
+        Some("article" | "main") => 10,
+        Some("div") => 5,
+        Some("pre" | "td" | "blockquote") => 3,
+        Some("address" | "ol" | "ul" | "dl" | "dd" | "dt" | "li" | "form") => -3,
+        Some("h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th") => -5,
+        _ => 0,
+    };
+    (by_tag + class_weight(node)) as f64
+}
+
+fn has_block_child(node: &Handle) -> bool {
+    node.children
+        .borrow()
+        .iter()
+        .any(|child| tag(child).is_some_and(|t| BLOCK_TAGS.contains(&t)))
+}
+
+/// Share of `node`'s text that is link text
+fn link_density(node: &Handle) -> f64 {
+    let len = inner_text(node).chars().count();
+    if len == 0 {
+        return 0.0;
+    }
+    let mut link_len = 0;
+    walk(node, &mut |n| {
+        if tag(n) == Some("a") {
+            link_len += inner_text(n).chars().count();
+        }
+    });
+    link_len as f64 / len as f64
+}
+
+pub(crate) fn tag(node: &Handle) -> Option<&str> {
+    match &node.data {
+        NodeData::Element { name, .. } => Some(&*name.local),
+        _ => None,
+    }
+}
+
+pub(crate) fn attr(node: &Handle, name: &str) -> Option<String> {
+    match &node.data {
+        NodeData::Element { attrs, .. } => attrs
+            .borrow()
+            .iter()
+            .find(|a| &*a.name.local == name)
+            .map(|a| a.value.to_string()),
+        _ => None,
+    }
+}
+
+pub(crate) fn parent(node: &Handle) -> Option<Handle> {
+    let weak = node.parent.take()?;
+    let parent = weak.upgrade();
+    node.parent.set(Some(weak));
+    parent
+}
+
+/// Text of `node` and its descendants with whitespace collapsed
+pub(crate) fn inner_text(node: &Handle) -> String {
+    fn collect(node: &Handle, out: &mut String) {
+        if let NodeData::Text { contents } = &node.data {
+            out.push_str(&contents.borrow());
+            out.push(' ');
+        }
+        for child in node.children.borrow().iter() {
+            collect(child, out);
+        }
+    }
+    let mut text = String::new();
+    collect(node, &mut text);
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Visit `node` and its descendants in document order
+pub(crate) fn walk(node: &Handle, visit: &mut impl FnMut(&Handle)) {
+    visit(node);
+    for child in node.children.borrow().iter() {
+        walk(child, visit);
+    }
+}
+
+/// The first node in document order matching `predicate`
+pub(crate) fn find(node: &Handle, predicate: &impl Fn(&Handle) -> bool) -> Option<Handle> {
+    if predicate(node) {
+        return Some(node.clone());
+    }
+    node.children
+        .borrow()
+        .iter()
+        .find_map(|child| find(child, predicate))
+}
+
+fn serialize_node(node: &Handle) -> String {
+    serialize_scope(node, TraversalScope::IncludeNode)
+}
+
+fn serialize_children(node: &Handle) -> String {
+    serialize_scope(node, TraversalScope::ChildrenOnly(None))
+}
+
+fn serialize_scope(node: &Handle, traversal_scope: TraversalScope) -> String {
+    let mut out = Vec::new();
+    let handle = SerializableHandle::from(node.clone());
+    let opts = SerializeOpts {
+        traversal_scope,
+        ..Default::default()
+    };
+    match serialize(&mut out, &handle, opts) {
+        Ok(()) => String::from_utf8_lossy(&out).into_owned(),
+        Err(_) => inner_text(node),
+    }
+}
@@ -0,0 +1,176 @@
+//! The small CSS selector subset understood by `crawl`'s `next_selector` and
+//! `fetch`'s `extract`: tag, `.class`, `#id`, `[attr]`, `[attr=value]` and
+//! `[attr*=value]`, descendant and `>` combinators, and comma-separated
+//! alternatives.
+
+use markup5ever_rcdom::Handle;
+
+use crate::readability::{attr, parent, tag};
+
+/// A small CSS selector: comma-separated chains of compound selectors
+/// joined by descendant or child combinators
+#[derive(Debug)]
+pub(crate) struct Selector {
+    alternatives: Vec<Vec<(Combinator, Compound)>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Combinator {
+    Descendant,
+    Child,
+}
+
+#[derive(Debug, Default)]
+struct Compound {
+    tag: Option<String>,
+    id: Option<String>,
+    classes: Vec<String>,
+    attrs: Vec<(String, AttrTest)>,
+}
+
+#[derive(Debug)]
+enum AttrTest {
+    Present,
+    Equals(String),
+    Contains(String),
+}
+
+impl Selector {
+    pub(crate) fn parse(raw: &str) -> Result<Self, String> {
+        let mut alternatives = Vec::new();
+        for alternative in raw.split(',') {
+            let mut chain = Vec::new();
+            let mut combinator = Combinator::Descendant;
+            let spaced = alternative.replace('>', " > ");
+            for token in spaced.split_whitespace() {
+                if token == ">" {
+                    combinator = Combinator::Child;
+                    continue;
+                }
+                chain.push((combinator, Compound::parse(token)?));
+                combinator = Combinator::Descendant;
+            }
+            if chain.is_empty() {
+                return Err(format!("empty selector in {:?}", raw));
+            }
+            alternatives.push(chain);
+        }
+        Ok(Self { alternatives })
+    }
+
+    pub(crate) fn matches(&self, node: &Handle) -> bool {
+        self.alternatives
+            .iter()
+            .any(|chain| chain_matches(chain, node))
+    }
+}
+
+/// Whether `node` matches the last compound of `chain` and its ancestors the rest
+fn chain_matches(chain: &[(Combinator, Compound)], node: &Handle) -> bool {
+    let Some(((combinator, compound), rest)) = chain.split_last() else {
+        return true;
+    };
+    if !compound.matches(node) {
+        return false;
+    }
+    if rest.is_empty() {
+        return true;
+    }
+    let mut ancestor = parent(node);
+    while let Some(node) = ancestor {
+        if chain_matches(rest, &node) {
+            return true;
+        }
+        if *combinator == Combinator::Child {
+            return false;
+        }
+        ancestor = parent(&node);
+    }
+    false
+}
+
+impl Compound {
+    fn parse(token: &str) -> Result<Self, String> {
+        let mut compound = Compound::default();
+        let bad = || format!("unsupported selector {:?}", token);
+        let mut rest = token;
+        let tag_end = rest.find(['.', '#', '[']).unwrap_or(rest.len());
+        let tag = &rest[..tag_end];
+        if !tag.is_empty() && tag != "*" {
+            if !tag.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+                return Err(bad());
+            }
+            compound.tag = Some(tag.to_ascii_lowercase());
+        }
+        rest = &rest[tag_end..];
+
+        while let Some(first) = rest.chars().next() {
+            rest = &rest[first.len_utf8()..];
+            match first {
+                '.' | '#' => {
+                    let end = rest.find(['.', '#', '[']).unwrap_or(rest.len());
+                    let name = &rest[..end];
+                    if name.is_empty() {
+                        return Err(bad());
+                    }
+                    if first == '.' {
+                        compound.classes.push(name.to_string());
+                    } else {
+                        compound.id = Some(name.to_string());
+                    }
+                    rest = &rest[end..];
+                }
+                '[' => {
+                    let end = rest.find(']').ok_or_else(bad)?;
+                    let inner = &rest[..end];
+                    let unquote = |v: &str| v.trim().trim_matches(['"', '\'']).to_string();
+                    let (name, test) = if let Some((name, value)) = inner.split_once("*=") {
+                        (name, AttrTest::Contains(unquote(value)))
+                    } else if let Some((name, value)) = inner.split_once('=') {
+                        (name, AttrTest::Equals(unquote(value)))
+                    } else {
+                        (inner, AttrTest::Present)
+                    };
+                    let name = name.trim().to_ascii_lowercase();
+                    if name.is_empty() {
+                        return Err(bad());
+                    }
+                    compound.attrs.push((name, test));
+                    rest = &rest[end + 1..];
+                }
+                _ => return Err(bad()),
+            }
+        }
+        Ok(compound)
+    }
+
+    fn matches(&self, node: &Handle) -> bool {
+        let Some(node_tag) = tag(node) else {
+            return false;
+        };
+        if self.tag.as_deref().is_some_and(|t| t != node_tag) {
+            return false;
+        }
+        if let Some(id) = &self.id {
+            if attr(node, "id").as_deref() != Some(id.as_str()) {
+                return false;
+            }
+        }
+        if !self.classes.is_empty() {
+            let class = attr(node, "class").unwrap_or_default();
+            let classes: Vec<&str> = class.split_whitespace().collect();
+            if !self.classes.iter().all(|c| classes.contains(&c.as_str())) {
+                return false;
+            }
+        }
+        self.attrs.iter().all(|(name, test)| {
+            let value = attr(node, name);
+            match (test, value) {
+                (_, None) => false,
+                (AttrTest::Present, Some(_)) => true,
+                (AttrTest::Equals(expected), Some(value)) => value == *expected,
+                (AttrTest::Contains(needle), Some(value)) => value.contains(needle.as_str()),
+            }
+        })
+    }
+}
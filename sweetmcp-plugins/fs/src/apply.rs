@@ -0,0 +1,337 @@
+//! All-or-nothing application of multi-file changes
+//!
+//! Every change is planned in memory first, then new contents are staged to
+//! temp files next to their targets. Commit moves each original aside and
+//! renames its staged file into place; any failure puts the originals back,
+//! so the tree ends up either fully changed or untouched.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+/// One requested change
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Change {
+    /// Create or overwrite `path` with `content`
+    Write { path: String, content: String },
+    /// Replace the single occurrence of `old_text` in `path` with `new_text`
+    Edit {
+        path: String,
+        old_text: String,
+        new_text: String,
+    },
+    /// Remove the file at `path`
+    Delete { path: String },
+}
+
+impl Change {
+    fn path(&self) -> &str {
+        match self {
+            Change::Write { path, .. } | Change::Edit { path, .. } | Change::Delete { path } => {
+                path
+            }
+        }
+    }
+}
+
+/// Final state of one file after all changes to it
+struct Planned {
+    target: PathBuf,
+    /// New contents, or `None` to delete
+    content: Option<String>,
+    existed: bool,
+    actions: Vec<&'static str>,
+}
+
+/// A file moved or created during commit, undone on rollback
+struct Committed {
+    target: PathBuf,
+    backup: Option<PathBuf>,
+    created: bool,
+}
+
+/// Apply `changes` atomically, returning a summary of what was done
+pub fn apply_changes(changes: Vec<Change>) -> Result<Value, String> {
+    if changes.is_empty() {
+        return Err("changes must contain at least one change".to_string());
+    }
+
+    let plan = plan(&changes)?;
+    let nonce = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+
+    let mut created_dirs = Vec::new();
+    let mut staged = Vec::new();
+    if let Err(e) = stage(&plan, nonce, &mut staged, &mut created_dirs) {
+        discard(&staged, &created_dirs);
+        return Err(format!("{}; no files were changed", e));
+    }
+
+    let mut committed = Vec::new();
+    if let Err(e) = commit(&plan, &staged, nonce, &mut committed) {
+        let rollback = rollback(&committed);
+        discard(&staged, &created_dirs);
+        return Err(match rollback {
+            Ok(()) => format!("{}; all changes were rolled back", e),
+            Err(r) => format!("{}; rollback failed: {}", e, r),
+        });
+    }
+
+    for entry in &committed {
+        if let Some(backup) = &entry.backup {
+            let _ = fs::remove_file(backup);
+        }
+    }
+
+    let files = plan
+        .iter()
+        .map(|p| {
+            json!({
+                "path": p.target.to_string_lossy(),
+                "actions": p.actions,
+                "existed": p.existed,
+                "deleted": p.content.is_none(),
+                "size": p.content.as_ref().map(String::len),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(json!({
+        "operation": "apply_changes",
+        "changes": changes.len(),
+        "files": files,
+        "success": true
+    }))
+}
+
+/// Resolve every change against the current tree without touching it
+fn plan(changes: &[Change]) -> Result<Vec<Planned>, String> {
+    let mut order = Vec::new();
+    let mut files: BTreeMap<PathBuf, Planned> = BTreeMap::new();
+
+    for (index, change) in changes.iter().enumerate() {
+        let target = PathBuf::from(change.path());
+        if change.path().is_empty() || target.is_dir() {
+            return Err(format!(
+                "change {}: '{}' is not a file path",
+                index,
+                change.path()
+            ));
+        }
+
+        if !files.contains_key(&target) {
+            let existed = target.is_file();
+            let content = if existed {
+                match fs::read_to_string(&target) {
+                    Ok(content) => Some(content),
+                    // Binary files can still be overwritten or deleted
+                    Err(_) if !matches!(change, Change::Edit { .. }) => Some(String::new()),
+                    Err(e) => {
+                        return Err(format!(
+                            "change {}: failed to read {}: {}",
+                            index,
+                            target.display(),
+                            e
+                        ));
+                    }
+                }
+            } else {
+                None
+            };
+            order.push(target.clone());
+            files.insert(
+                target.clone(),
+                Planned {
+                    target: target.clone(),
+                    content,
+                    existed,
+                    actions: Vec::new(),
+                },
+            );
+        }
+
+        let file = files.get_mut(&target).expect("planned above");
+        match change {
+            Change::Write { content, .. } => {
+                file.content = Some(content.clone());
+                file.actions.push("write");
+            }
+            Change::Edit {
+                old_text, new_text, ..
+            } => {
+                let Some(current) = &file.content else {
+                    return Err(format!(
+                        "change {}: cannot edit {}, it does not exist",
+                        index,
+                        target.display()
+                    ));
+                };
+                match current.matches(old_text.as_str()).count() {
+                    1 => {}
+                    0 => {
+                        return Err(format!(
+                            "change {}: old_text not found in {}",
+                            index,
+                            target.display()
+                        ));
+                    }
+                    n => {
+                        return Err(format!(
+                            "change {}: old_text matches {} times in {}; it must be unique",
+                            index,
+                            n,
+                            target.display()
+                        ));
+                    }
+                }
+                file.content = Some(current.replacen(old_text.as_str(), new_text, 1));
+                file.actions.push("edit");
+            }
+            Change::Delete { .. } => {
+                if file.content.is_none() {
+                    return Err(format!(
+                        "change {}: cannot delete {}, it does not exist",
+                        index,
+                        target.display()
+                    ));
+                }
+                file.content = None;
+                file.actions.push("delete");
+            }
+        }
+    }
+
+    Ok(order
+        .into_iter()
+        .filter_map(|target| files.remove(&target))
+        // A file created and deleted again within the batch needs no work
+        .filter(|p| p.existed || p.content.is_some())
+        .collect())
+}
+
+/// Write new contents to temp files beside their targets
+fn stage(
+    plan: &[Planned],
+    nonce: u128,
+    staged: &mut Vec<Option<PathBuf>>,
+    created_dirs: &mut Vec<PathBuf>,
+) -> Result<(), String> {
+    for file in plan {
+        let Some(content) = &file.content else {
+            staged.push(None);
+            continue;
+        };
+
+        if let Some(parent) = file.target.parent().filter(|p| !p.as_os_str().is_empty()) {
+            create_missing_dirs(parent, created_dirs)
+                .map_err(|e| format!("failed to create {}: {}", parent.display(), e))?;
+        }
+
+        let temp = sibling(&file.target, nonce, "tmp");
+        fs::write(&temp, content)
+            .map_err(|e| format!("failed to stage {}: {}", file.target.display(), e))?;
+        staged.push(Some(temp));
+    }
+    Ok(())
+}
+
+/// Move originals aside and staged files into place
+fn commit(
+    plan: &[Planned],
+    staged: &[Option<PathBuf>],
+    nonce: u128,
+    committed: &mut Vec<Committed>,
+) -> Result<(), String> {
+    for (file, temp) in plan.iter().zip(staged) {
+        let backup = if file.target.exists() {
+            let backup = sibling(&file.target, nonce, "bak");
+            fs::rename(&file.target, &backup)
+                .map_err(|e| format!("failed to move {} aside: {}", file.target.display(), e))?;
+            Some(backup)
+        } else {
+            None
+        };
+        committed.push(Committed {
+            target: file.target.clone(),
+            backup,
+            created: false,
+        });
+
+        if let Some(temp) = temp {
+            fs::rename(temp, &file.target)
+                .map_err(|e| format!("failed to write {}: {}", file.target.display(), e))?;
+            if let Some(last) = committed.last_mut() {
+                last.created = true;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Undo `committed` in reverse order
+fn rollback(committed: &[Committed]) -> Result<(), String> {
+    let mut failures = Vec::new();
+    for entry in committed.iter().rev() {
+        if entry.created {
+            if let Err(e) = fs::remove_file(&entry.target) {
+                failures.push(format!("{}: {}", entry.target.display(), e));
+                continue;
+            }
+        }
+        if let Some(backup) = &entry.backup {
+            if let Err(e) = fs::rename(backup, &entry.target) {
+                failures.push(format!(
+                    "{} (original kept at {}): {}",
+                    entry.target.display(),
+                    backup.display(),
+                    e
+                ));
+            }
+        }
+    }
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(failures.join("; "))
+    }
+}
+
+/// Remove leftover temp files and the directories staging created
+fn discard(staged: &[Option<PathBuf>], created_dirs: &[PathBuf]) {
+    for temp in staged.iter().flatten() {
+        let _ = fs::remove_file(temp);
+    }
+    for dir in created_dirs.iter().rev() {
+        let _ = fs::remove_dir(dir);
+    }
+}
+
+/// Create `dir` and its missing ancestors, recording each one created
+fn create_missing_dirs(dir: &Path, created: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    let missing = dir
+        .ancestors()
+        .take_while(|d| !d.as_os_str().is_empty() && !d.exists())
+        .map(Path::to_path_buf)
+        .collect::<Vec<_>>();
+    for d in missing.into_iter().rev() {
+        fs::create_dir(&d)?;
+        created.push(d);
+    }
+    Ok(())
+}
+
+/// Hidden temp path in the same directory, so renames never cross filesystems
+fn sibling(target: &Path, nonce: u128, suffix: &str) -> PathBuf {
+    let name = target
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    target.with_file_name(format!(".{}.apply-{}.{}", name, nonce, suffix))
+}
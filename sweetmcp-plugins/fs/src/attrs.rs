@@ -0,0 +1,159 @@
+//! Writing file metadata: permissions and timestamps
+//!
+//! Unix mode bits are applied as given where the platform has them. Elsewhere,
+//! on Windows and under WASI, the only permission a file carries is
+//! read-only, so a mode is reduced to that: read-only when the owner-write bit
+//! is clear. The result says which was applied.
+
+use std::fs::{self, File, FileTimes, OpenOptions};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, Utc};
+use serde_json::{Value, json};
+
+/// Parse a mode written in octal, e.g. `644`, `0755` or `0o600`
+pub fn parse_mode(mode: &str) -> Result<u32, String> {
+    let digits = mode.trim().trim_start_matches("0o");
+    match u32::from_str_radix(digits, 8) {
+        Ok(bits) if bits <= 0o7777 => Ok(bits),
+        _ => Err(format!(
+            "invalid mode '{}', expected octal digits such as 644 or 0755",
+            mode
+        )),
+    }
+}
+
+/// Parse a timestamp: RFC 3339, or seconds since the Unix epoch
+pub fn parse_time(value: &Value) -> Result<SystemTime, String> {
+    let text = value.as_str().map(str::trim);
+    if let Some(secs) = value.as_f64().or_else(|| text?.parse().ok()) {
+        if secs < 0.0 {
+            return Err(format!("timestamp {} is before 1970", secs));
+        }
+        return Ok(UNIX_EPOCH + Duration::from_secs_f64(secs));
+    }
+    let text = text.ok_or_else(|| format!("invalid timestamp {}", value))?;
+    if text.eq_ignore_ascii_case("now") {
+        return Ok(SystemTime::now());
+    }
+    DateTime::parse_from_rfc3339(text)
+        .map(SystemTime::from)
+        .map_err(|e| {
+            format!(
+                "invalid timestamp '{}': {}; use RFC 3339 such as 2024-05-01T12:00:00Z, or epoch seconds",
+                text, e
+            )
+        })
+}
+
+/// Set Unix `mode` bits, or the read-only flag, on `path`
+pub fn set_permissions(
+    path: &Path,
+    mode: Option<u32>,
+    readonly: Option<bool>,
+) -> Result<Value, String> {
+    let mut permissions = fs::metadata(path).map_err(|e| e.to_string())?.permissions();
+    let mode_applied = match mode {
+        Some(bits) => apply_mode(&mut permissions, bits),
+        None => false,
+    };
+    if let Some(readonly) = readonly {
+        permissions.set_readonly(readonly);
+    }
+    fs::set_permissions(path, permissions).map_err(|e| e.to_string())?;
+
+    let mut result = describe(path)?;
+    result["mode_applied"] = json!(mode_applied);
+    if mode.is_some() && !mode_applied {
+        result["note"] = json!(
+            "this platform has no Unix mode bits; only read-only (owner-write bit clear) was applied"
+        );
+    }
+    Ok(result)
+}
+
+#[cfg(unix)]
+fn apply_mode(permissions: &mut fs::Permissions, bits: u32) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    permissions.set_mode(bits);
+    true
+}
+
+#[cfg(not(unix))]
+fn apply_mode(permissions: &mut fs::Permissions, bits: u32) -> bool {
+    // Read-only when the owner-write bit is clear
+    permissions.set_readonly(bits & 0o200 == 0);
+    false
+}
+
+/// Create `path` if missing, then set its times to `time`
+pub fn touch(path: &Path, time: SystemTime) -> Result<Value, String> {
+    let created = !path.exists();
+    if created {
+        OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path)
+            .map_err(|e| e.to_string())?;
+    }
+    apply_times(path, FileTimes::new().set_accessed(time).set_modified(time))?;
+
+    let mut result = describe(path)?;
+    result["created"] = json!(created);
+    Ok(result)
+}
+
+/// Set the modification and/or access time of an existing `path`
+pub fn set_times(
+    path: &Path,
+    modified: Option<SystemTime>,
+    accessed: Option<SystemTime>,
+) -> Result<Value, String> {
+    if modified.is_none() && accessed.is_none() {
+        return Err("give modified, accessed or both".to_string());
+    }
+    let mut times = FileTimes::new();
+    if let Some(modified) = modified {
+        times = times.set_modified(modified);
+    }
+    if let Some(accessed) = accessed {
+        times = times.set_accessed(accessed);
+    }
+    apply_times(path, times)?;
+    describe(path)
+}
+
+fn apply_times(path: &Path, times: FileTimes) -> Result<(), String> {
+    // Read access is enough to set times, and lets read-only files and directories through
+    File::open(path)
+        .and_then(|file| file.set_times(times))
+        .map_err(|e| e.to_string())
+}
+
+/// Metadata after a change, for the caller to confirm it
+fn describe(path: &Path) -> Result<Value, String> {
+    let metadata = fs::metadata(path).map_err(|e| e.to_string())?;
+    let time = |t: std::io::Result<SystemTime>| {
+        t.ok()
+            .map(|t| DateTime::<Utc>::from(t).to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+    };
+    Ok(json!({
+        "path": path.to_string_lossy(),
+        "mode": mode_string(&metadata),
+        "readonly": metadata.permissions().readonly(),
+        "modified": time(metadata.modified()),
+        "accessed": time(metadata.accessed()),
+    }))
+}
+
+#[cfg(unix)]
+fn mode_string(metadata: &fs::Metadata) -> Option<String> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(format!("{:04o}", metadata.permissions().mode() & 0o7777))
+}
+
+#[cfg(not(unix))]
+fn mode_string(_metadata: &fs::Metadata) -> Option<String> {
+    None
+}
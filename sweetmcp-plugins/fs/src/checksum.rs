@@ -0,0 +1,247 @@
+//! File checksums and duplicate detection
+//!
+//! Files are hashed in streaming fashion, so size doesn't matter. Duplicate
+//! detection groups files by size first and only hashes sizes seen more than
+//! once, which skips most of a typical tree. Symlinks are never followed or
+//! hashed, so a link is not reported as a copy of its target.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use md5::Context as Md5;
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+
+use crate::glob::{Glob, any_matches};
+
+/// Most files looked at in one walk
+const MAX_FILES: usize = 20_000;
+
+/// Hash algorithm
+#[derive(Clone, Copy)]
+pub enum Algorithm {
+    Sha256,
+    Md5,
+    Blake3,
+}
+
+impl Algorithm {
+    /// The `algorithm` argument, SHA-256 when absent
+    pub fn from_args(args: &Value) -> Result<Self, String> {
+        match args.get("algorithm").and_then(|v| v.as_str()) {
+            None | Some("sha256") => Ok(Algorithm::Sha256),
+            Some("md5") => Ok(Algorithm::Md5),
+            Some("blake3") => Ok(Algorithm::Blake3),
+            Some(other) => Err(format!(
+                "unknown algorithm '{}', expected sha256, md5 or blake3",
+                other
+            )),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Algorithm::Sha256 => "sha256",
+            Algorithm::Md5 => "md5",
+            Algorithm::Blake3 => "blake3",
+        }
+    }
+}
+
+pub struct WalkOptions {
+    pub include: Vec<Glob>,
+    pub exclude: Vec<Glob>,
+    /// Files smaller than this are ignored (find_duplicates)
+    pub min_size: u64,
+}
+
+/// Files found under a root, with their sizes
+#[derive(Default)]
+struct Walk {
+    files: Vec<(PathBuf, u64)>,
+    truncated: bool,
+}
+
+/// Checksum of `root`, or of every file under it
+pub fn checksum(root: &Path, algorithm: Algorithm, options: &WalkOptions) -> Result<Value, String> {
+    let walk = collect(root, options)?;
+    let mut errors = Vec::new();
+    let mut files = Vec::new();
+    for (path, size) in &walk.files {
+        match hash_file(path, algorithm) {
+            Ok(hash) => files.push(json!({
+                "path": path.to_string_lossy(),
+                "size": size,
+                "hash": hash
+            })),
+            Err(e) => errors.push(json!({"path": path.to_string_lossy(), "error": e.to_string()})),
+        }
+    }
+
+    Ok(json!({
+        "path": root.to_string_lossy(),
+        "algorithm": algorithm.name(),
+        "files": files,
+        "count": files.len(),
+        "errors": errors,
+        "truncated": walk.truncated
+    }))
+}
+
+/// Groups of identical files under `root`, largest waste first
+pub fn find_duplicates(
+    root: &Path,
+    algorithm: Algorithm,
+    options: &WalkOptions,
+) -> Result<Value, String> {
+    let walk = collect(root, options)?;
+
+    let mut by_size: BTreeMap<u64, Vec<PathBuf>> = BTreeMap::new();
+    for (path, size) in walk.files {
+        by_size.entry(size).or_default().push(path);
+    }
+
+    let mut groups = Vec::new();
+    let mut errors = Vec::new();
+    let mut files_hashed = 0;
+    for (size, paths) in by_size.into_iter().filter(|(_, paths)| paths.len() > 1) {
+        let mut by_hash: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+        for path in paths {
+            files_hashed += 1;
+            match hash_file(&path, algorithm) {
+                Ok(hash) => by_hash.entry(hash).or_default().push(path),
+                Err(e) => {
+                    errors.push(json!({"path": path.to_string_lossy(), "error": e.to_string()}))
+                }
+            }
+        }
+        for (hash, paths) in by_hash.into_iter().filter(|(_, paths)| paths.len() > 1) {
+            groups.push((size, hash, paths));
+        }
+    }
+    // Biggest savings first
+    groups.sort_by_key(|(size, _, paths)| std::cmp::Reverse(size * (paths.len() as u64 - 1)));
+
+    let duplicate_files: usize = groups.iter().map(|(_, _, paths)| paths.len() - 1).sum();
+    let reclaimable: u64 = groups
+        .iter()
+        .map(|(size, _, paths)| size * (paths.len() as u64 - 1))
+        .sum();
+    let groups: Vec<Value> = groups
+        .into_iter()
+        .map(|(size, hash, paths)| {
+            json!({
+                "hash": hash,
+                "size": size,
+                "paths": paths.iter().map(|p| p.to_string_lossy()).collect::<Vec<_>>(),
+                "wasted_bytes": size * (paths.len() as u64 - 1)
+            })
+        })
+        .collect();
+
+    Ok(json!({
+        "path": root.to_string_lossy(),
+        "algorithm": algorithm.name(),
+        "groups": groups,
+        "duplicate_files": duplicate_files,
+        "reclaimable_bytes": reclaimable,
+        "files_hashed": files_hashed,
+        "errors": errors,
+        "truncated": walk.truncated
+    }))
+}
+
+/// Hex digest of the file at `path`
+fn hash_file(path: &Path, algorithm: Algorithm) -> std::io::Result<String> {
+    let mut hasher = match algorithm {
+        Algorithm::Sha256 => Hasher::Sha256(Sha256::new()),
+        Algorithm::Md5 => Hasher::Md5(Md5::new()),
+        Algorithm::Blake3 => Hasher::Blake3(Box::new(blake3::Hasher::new())),
+    };
+    let mut file = File::open(path)?;
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        let chunk = &buffer[..read];
+        match &mut hasher {
+            Hasher::Sha256(h) => h.update(chunk),
+            Hasher::Md5(h) => h.consume(chunk),
+            Hasher::Blake3(h) => {
+                h.update(chunk);
+            }
+        }
+    }
+    Ok(match hasher {
+        Hasher::Sha256(h) => format!("{:x}", h.finalize()),
+        Hasher::Md5(h) => format!("{:x}", h.compute()),
+        Hasher::Blake3(h) => h.finalize().to_hex().to_string(),
+    })
+}
+
+enum Hasher {
+    Sha256(Sha256),
+    Md5(Md5),
+    Blake3(Box<blake3::Hasher>),
+}
+
+/// Regular files at or under `root` that pass the filters
+fn collect(root: &Path, options: &WalkOptions) -> Result<Walk, String> {
+    let metadata = fs::metadata(root).map_err(|e| e.to_string())?;
+    let mut walk = Walk::default();
+    if metadata.is_dir() {
+        walk_dir(root, "", options, &mut walk);
+    } else if metadata.is_file() {
+        walk.files.push((root.to_path_buf(), metadata.len()));
+    } else {
+        return Err("not a regular file or directory".to_string());
+    }
+    Ok(walk)
+}
+
+fn walk_dir(dir: &Path, relative: &str, options: &WalkOptions, walk: &mut Walk) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut entries = entries.flatten().collect::<Vec<_>>();
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        if walk.truncated {
+            return;
+        }
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let child_relative = if relative.is_empty() {
+            name
+        } else {
+            format!("{}/{}", relative, name)
+        };
+        if any_matches(&options.exclude, &child_relative) {
+            continue;
+        }
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+
+        if file_type.is_dir() {
+            walk_dir(&entry.path(), &child_relative, options, walk);
+        } else if file_type.is_file() {
+            if !options.include.is_empty() && !any_matches(&options.include, &child_relative) {
+                continue;
+            }
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            if size < options.min_size {
+                continue;
+            }
+            if walk.files.len() == MAX_FILES {
+                walk.truncated = true;
+                return;
+            }
+            walk.files.push((entry.path(), size));
+        }
+    }
+}
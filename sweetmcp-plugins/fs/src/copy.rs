@@ -0,0 +1,155 @@
+//! Copying files and directory trees
+//!
+//! The destination is always the exact path to create, never a directory to
+//! copy into, so a call means the same thing whether or not the destination
+//! exists. Existing files are only replaced with `overwrite`, and symlinks are
+//! skipped rather than followed.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde_json::{Value, json};
+
+/// Paths listed in a dry-run summary
+const MAX_LISTED: usize = 50;
+
+/// What a copy did, or would do
+#[derive(Default)]
+pub struct CopyStats {
+    pub files: usize,
+    pub dirs: usize,
+    pub bytes: u64,
+    pub overwritten: usize,
+    pub skipped_symlinks: usize,
+    pub listed: Vec<String>,
+}
+
+impl CopyStats {
+    fn list(&mut self, path: &Path) {
+        if self.listed.len() < MAX_LISTED {
+            self.listed.push(path.to_string_lossy().into_owned());
+        }
+    }
+}
+
+/// Copy `source` to `destination`, or only report what would be copied
+pub fn copy(
+    source: &Path,
+    destination: &Path,
+    overwrite: bool,
+    dry_run: bool,
+) -> Result<Value, String> {
+    let metadata = fs::symlink_metadata(source).map_err(|e| format!("{}", e))?;
+    if metadata.file_type().is_symlink() {
+        return Err("source is a symlink; copy its target instead".to_string());
+    }
+
+    if let Ok(existing) = fs::symlink_metadata(destination) {
+        if !overwrite {
+            return Err(format!(
+                "{} already exists; set overwrite to replace it",
+                destination.display()
+            ));
+        }
+        if existing.is_dir() != metadata.is_dir() {
+            return Err(format!(
+                "cannot overwrite {} with a {}",
+                destination.display(),
+                if metadata.is_dir() {
+                    "directory"
+                } else {
+                    "file"
+                }
+            ));
+        }
+    }
+
+    if metadata.is_dir() {
+        let source_abs = fs::canonicalize(source).map_err(|e| e.to_string())?;
+        let dest_abs = absolute(destination);
+        if dest_abs.starts_with(&source_abs) {
+            return Err("cannot copy a directory into itself".to_string());
+        }
+    }
+
+    let mut stats = CopyStats::default();
+    copy_tree(source, destination, dry_run, &mut stats).map_err(|e| e.to_string())?;
+
+    let mut summary = json!({
+        "source": source.to_string_lossy(),
+        "destination": destination.to_string_lossy(),
+        "files": stats.files,
+        "dirs": stats.dirs,
+        "bytes": stats.bytes,
+        "overwritten": stats.overwritten,
+        "skipped_symlinks": stats.skipped_symlinks,
+        "dry_run": dry_run,
+        "success": true
+    });
+    if dry_run {
+        summary["would_copy"] = stats.listed.into();
+    }
+    Ok(summary)
+}
+
+/// Copy `source` to `destination` recursively, counting into `stats`. With
+/// `dry_run` nothing is written. Callers check for conflicts beforehand.
+pub fn copy_tree(
+    source: &Path,
+    destination: &Path,
+    dry_run: bool,
+    stats: &mut CopyStats,
+) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(source)?;
+    if metadata.file_type().is_symlink() {
+        stats.skipped_symlinks += 1;
+        return Ok(());
+    }
+
+    if metadata.is_dir() {
+        stats.dirs += 1;
+        stats.list(destination);
+        if !dry_run {
+            fs::create_dir_all(destination)?;
+        }
+        let mut entries = fs::read_dir(source)?.collect::<Result<Vec<_>, _>>()?;
+        entries.sort_by_key(|entry| entry.file_name());
+        for entry in entries {
+            copy_tree(
+                &entry.path(),
+                &destination.join(entry.file_name()),
+                dry_run,
+                stats,
+            )?;
+        }
+    } else {
+        if destination.exists() {
+            stats.overwritten += 1;
+        }
+        stats.files += 1;
+        stats.bytes += metadata.len();
+        stats.list(destination);
+        if !dry_run {
+            if let Some(parent) = destination.parent().filter(|p| !p.as_os_str().is_empty()) {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(source, destination)?;
+        }
+    }
+    Ok(())
+}
+
+/// `path` made absolute without requiring it to exist
+pub fn absolute(path: &Path) -> std::path::PathBuf {
+    if let Ok(canonical) = fs::canonicalize(path) {
+        return canonical;
+    }
+    // Resolve the nearest existing ancestor and append the rest
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) if !parent.as_os_str().is_empty() => absolute(parent).join(name),
+        _ => std::env::current_dir()
+            .map(|dir| dir.join(path))
+            .unwrap_or_else(|_| path.to_path_buf()),
+    }
+}
@@ -0,0 +1,148 @@
+//! Deleting files and directory trees
+//!
+//! Deletes refuse the filesystem root, the home directory and the working
+//! directory outright, and a non-empty directory needs `recursive`. With
+//! `trash` the target is moved to the user's trash instead of unlinked,
+//! following the freedesktop.org layout so desktop file managers can restore
+//! it; the trash location can be overridden with the `trash_dir` config key.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use extism_pdk::config;
+use serde_json::{Value, json};
+
+use crate::copy::{CopyStats, absolute, copy_tree};
+
+/// Delete `path`, or only report what would be deleted
+pub fn delete(path: &Path, recursive: bool, trash: bool, dry_run: bool) -> Result<Value, String> {
+    let metadata = fs::symlink_metadata(path).map_err(|e| e.to_string())?;
+    // Resolve the parent only, so a symlink is deleted rather than its target
+    let target = match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) if !parent.as_os_str().is_empty() => absolute(parent).join(name),
+        (_, Some(name)) => absolute(Path::new(".")).join(name),
+        _ => absolute(path),
+    };
+    guard(&target)?;
+
+    let is_dir = metadata.is_dir();
+    if is_dir && !recursive {
+        let empty = fs::read_dir(path)
+            .map_err(|e| e.to_string())?
+            .next()
+            .is_none();
+        if !empty {
+            return Err("directory is not empty; set recursive to delete it".to_string());
+        }
+    }
+
+    // Count what goes, without following symlinks
+    let mut stats = CopyStats::default();
+    copy_tree(path, path, true, &mut stats).map_err(|e| e.to_string())?;
+
+    let mut summary = json!({
+        "path": path.to_string_lossy(),
+        "files": stats.files + stats.skipped_symlinks,
+        "dirs": stats.dirs,
+        "bytes": stats.bytes,
+        "trash": trash,
+        "dry_run": dry_run,
+        "success": true
+    });
+
+    if dry_run {
+        summary["would_delete"] = stats.listed.into();
+        return Ok(summary);
+    }
+
+    if trash {
+        let trashed = move_to_trash(&target)?;
+        summary["trashed_to"] = trashed.to_string_lossy().into_owned().into();
+    } else if is_dir {
+        fs::remove_dir_all(path).map_err(|e| e.to_string())?;
+    } else {
+        fs::remove_file(path).map_err(|e| e.to_string())?;
+    }
+    Ok(summary)
+}
+
+/// Refuse paths whose deletion is never what the caller meant
+fn guard(target: &Path) -> Result<(), String> {
+    if target.parent().is_none() {
+        return Err("refusing to delete the filesystem root".to_string());
+    }
+    let home = std::env::var_os("HOME").map(|home| absolute(Path::new(&home)));
+    if home.as_deref() == Some(target) {
+        return Err("refusing to delete the home directory".to_string());
+    }
+    if let Ok(cwd) = std::env::current_dir() {
+        if absolute(&cwd).starts_with(target) {
+            return Err("refusing to delete the working directory or its parent".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Trash directory: `trash_dir` config, else the freedesktop.org home trash
+fn trash_root() -> Result<PathBuf, String> {
+    if let Ok(Some(dir)) = config::get("trash_dir") {
+        return Ok(PathBuf::from(dir));
+    }
+    if let Some(data) = std::env::var_os("XDG_DATA_HOME") {
+        return Ok(PathBuf::from(data).join("Trash"));
+    }
+    match std::env::var_os("HOME") {
+        Some(home) => Ok(PathBuf::from(home).join(".local/share/Trash")),
+        None => Err("no trash directory available; set trash_dir in the plugin config".to_string()),
+    }
+}
+
+/// Move `target` into the trash with a `.trashinfo` record, returning where
+/// it went
+fn move_to_trash(target: &Path) -> Result<PathBuf, String> {
+    let root = trash_root()?;
+    let files = root.join("files");
+    let info = root.join("info");
+    fs::create_dir_all(&files)
+        .and_then(|_| fs::create_dir_all(&info))
+        .map_err(|e| format!("failed to prepare trash at {}: {}", root.display(), e))?;
+
+    let name = target
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "unnamed".to_string());
+    let mut trashed_name = name.clone();
+    let mut n = 1;
+    while files.join(&trashed_name).exists()
+        || info.join(format!("{}.trashinfo", trashed_name)).exists()
+    {
+        n += 1;
+        trashed_name = format!("{}.{}", name, n);
+    }
+    let destination = files.join(&trashed_name);
+
+    let record = format!(
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        target.to_string_lossy(),
+        chrono::Local::now().format("%Y-%m-%dT%H:%M:%S")
+    );
+    let info_path = info.join(format!("{}.trashinfo", trashed_name));
+    fs::write(&info_path, record).map_err(|e| format!("failed to record trash entry: {}", e))?;
+
+    if fs::rename(target, &destination).is_err() {
+        // Different filesystem: copy into the trash, then remove the original
+        let mut stats = CopyStats::default();
+        let copied = copy_tree(target, &destination, false, &mut stats).and_then(|_| {
+            if fs::symlink_metadata(target).is_ok_and(|m| m.is_dir()) {
+                fs::remove_dir_all(target)
+            } else {
+                fs::remove_file(target)
+            }
+        });
+        if let Err(e) = copied {
+            let _ = fs::remove_file(&info_path);
+            return Err(format!("failed to move to trash: {}", e));
+        }
+    }
+    Ok(destination)
+}
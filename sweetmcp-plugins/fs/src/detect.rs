@@ -0,0 +1,232 @@
+//! File type detection
+//!
+//! Only the first `SAMPLE_BYTES` of a file are inspected. The MIME type comes
+//! from magic bytes, falling back to the extension for text formats that have
+//! no signature; encoding and line endings are inferred from the sample.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use serde_json::{Value, json};
+
+/// How much of the file is sniffed
+const SAMPLE_BYTES: usize = 64 * 1024;
+
+/// Signatures as (offset, bytes, MIME type), checked in order
+const MAGIC: &[(usize, &[u8], &str)] = &[
+    (0, b"\x89PNG\r\n\x1a\n", "image/png"),
+    (0, b"\xff\xd8\xff", "image/jpeg"),
+    (0, b"GIF87a", "image/gif"),
+    (0, b"GIF89a", "image/gif"),
+    (0, b"BM", "image/bmp"),
+    (0, b"II*\x00", "image/tiff"),
+    (0, b"MM\x00*", "image/tiff"),
+    (0, b"\x00\x00\x01\x00", "image/x-icon"),
+    (0, b"%PDF-", "application/pdf"),
+    (0, b"%!PS", "application/postscript"),
+    (0, b"{\\rtf", "application/rtf"),
+    (0, b"PK\x03\x04", "application/zip"),
+    (0, b"PK\x05\x06", "application/zip"),
+    (0, b"\x1f\x8b", "application/gzip"),
+    (0, b"BZh", "application/x-bzip2"),
+    (0, b"\xfd7zXZ\x00", "application/x-xz"),
+    (0, b"\x28\xb5\x2f\xfd", "application/zstd"),
+    (0, b"7z\xbc\xaf\x27\x1c", "application/x-7z-compressed"),
+    (0, b"Rar!\x1a\x07", "application/vnd.rar"),
+    (257, b"ustar", "application/x-tar"),
+    (0, b"SQLite format 3\x00", "application/vnd.sqlite3"),
+    (0, b"\x00asm", "application/wasm"),
+    (0, b"\x7fELF", "application/x-executable"),
+    (0, b"\xcf\xfa\xed\xfe", "application/x-mach-binary"),
+    (0, b"\xfe\xed\xfa\xcf", "application/x-mach-binary"),
+    (0, b"\xca\xfe\xba\xbe", "application/java-vm"),
+    (0, b"MZ", "application/vnd.microsoft.portable-executable"),
+    (0, b"wOFF", "font/woff"),
+    (0, b"wOF2", "font/woff2"),
+    (0, b"OggS", "audio/ogg"),
+    (0, b"fLaC", "audio/flac"),
+    (0, b"ID3", "audio/mpeg"),
+    (0, b"\xff\xfb", "audio/mpeg"),
+    (0, b"\x1a\x45\xdf\xa3", "video/webm"),
+];
+
+/// Text formats recognised by extension
+const TEXT_EXTENSIONS: &[(&str, &str)] = &[
+    ("txt", "text/plain"),
+    ("md", "text/markdown"),
+    ("markdown", "text/markdown"),
+    ("html", "text/html"),
+    ("htm", "text/html"),
+    ("css", "text/css"),
+    ("csv", "text/csv"),
+    ("tsv", "text/tab-separated-values"),
+    ("js", "text/javascript"),
+    ("mjs", "text/javascript"),
+    ("ts", "text/x-typescript"),
+    ("json", "application/json"),
+    ("xml", "application/xml"),
+    ("svg", "image/svg+xml"),
+    ("yaml", "application/yaml"),
+    ("yml", "application/yaml"),
+    ("toml", "application/toml"),
+    ("rs", "text/x-rust"),
+    ("py", "text/x-python"),
+    ("go", "text/x-go"),
+    ("java", "text/x-java"),
+    ("c", "text/x-c"),
+    ("h", "text/x-c"),
+    ("cpp", "text/x-c++"),
+    ("sh", "text/x-shellscript"),
+    ("sql", "application/sql"),
+];
+
+/// Describe the file at `path`
+pub fn detect(path: &Path) -> std::io::Result<Value> {
+    let size = path.metadata()?.len();
+    let mut sample = Vec::with_capacity(SAMPLE_BYTES.min(size as usize));
+    File::open(path)?
+        .take(SAMPLE_BYTES as u64)
+        .read_to_end(&mut sample)?;
+
+    let encoding = encoding(&sample);
+    let is_text = encoding.is_some();
+    let mime = sniff(&sample)
+        .or_else(|| is_text.then(|| text_mime(path, &sample)))
+        .unwrap_or("application/octet-stream");
+
+    Ok(json!({
+        "path": path.to_string_lossy(),
+        "size": size,
+        "mime_type": mime,
+        "is_text": is_text,
+        "encoding": encoding,
+        "bom": bom(&sample).map(|(name, _)| name),
+        "line_endings": if is_text { Some(line_endings(&sample)) } else { None },
+        "sampled_bytes": sample.len(),
+        "suggested_read": if is_text { "text" } else { "binary" },
+    }))
+}
+
+fn sniff(sample: &[u8]) -> Option<&'static str> {
+    if sample.len() >= 12 && &sample[0..4] == b"RIFF" {
+        match &sample[8..12] {
+            b"WEBP" => return Some("image/webp"),
+            b"WAVE" => return Some("audio/wav"),
+            b"AVI " => return Some("video/x-msvideo"),
+            _ => {}
+        }
+    }
+    if sample.len() >= 12 && &sample[4..8] == b"ftyp" {
+        return Some(match &sample[8..12] {
+            b"heic" | b"heix" | b"mif1" => "image/heic",
+            b"avif" => "image/avif",
+            b"qt  " => "video/quicktime",
+            b"M4A " => "audio/mp4",
+            _ => "video/mp4",
+        });
+    }
+    MAGIC
+        .iter()
+        .find(|(offset, magic, _)| sample.get(*offset..offset + magic.len()) == Some(*magic))
+        .map(|(_, _, mime)| *mime)
+}
+
+/// MIME type of a text file without a binary signature
+fn text_mime(path: &Path, sample: &[u8]) -> &'static str {
+    let ext = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    if let Some((_, mime)) = TEXT_EXTENSIONS.iter().find(|(e, _)| *e == ext) {
+        return mime;
+    }
+
+    let start = bom(sample).map_or(0, |(_, len)| len);
+    let head = String::from_utf8_lossy(&sample[start..sample.len().min(start + 256)]);
+    let head = head.trim_start();
+    let lower = head.to_ascii_lowercase();
+    if head.starts_with("#!") {
+        "text/x-shellscript"
+    } else if lower.starts_with("<!doctype html") || lower.starts_with("<html") {
+        "text/html"
+    } else if head.starts_with("<?xml") {
+        "application/xml"
+    } else {
+        "text/plain"
+    }
+}
+
+/// Byte-order mark and its length
+fn bom(sample: &[u8]) -> Option<(&'static str, usize)> {
+    if sample.starts_with(b"\xef\xbb\xbf") {
+        Some(("utf-8", 3))
+    } else if sample.starts_with(b"\xff\xfe\x00\x00") {
+        Some(("utf-32le", 4))
+    } else if sample.starts_with(b"\x00\x00\xfe\xff") {
+        Some(("utf-32be", 4))
+    } else if sample.starts_with(b"\xff\xfe") {
+        Some(("utf-16le", 2))
+    } else if sample.starts_with(b"\xfe\xff") {
+        Some(("utf-16be", 2))
+    } else {
+        None
+    }
+}
+
+/// Character encoding of a text sample, or `None` for binary data
+fn encoding(sample: &[u8]) -> Option<&'static str> {
+    if let Some((name, _)) = bom(sample) {
+        return Some(name);
+    }
+    if sample.contains(&0) {
+        return None;
+    }
+
+    match std::str::from_utf8(sample) {
+        Ok(text) if text.is_ascii() => return Some("ascii"),
+        Ok(_) => return Some("utf-8"),
+        // A multi-byte character cut off by the end of the sample
+        Err(e) if e.error_len().is_none() => return Some("utf-8"),
+        Err(_) => {}
+    }
+
+    // Not UTF-8: call it a single-byte encoding unless control bytes dominate
+    let control = sample
+        .iter()
+        .filter(|b| b.is_ascii_control() && !matches!(b, b'\n' | b'\r' | b'\t' | 0x0c))
+        .count();
+    if control * 10 > sample.len() {
+        None
+    } else if sample.iter().any(|b| (0x80..0xa0).contains(b)) {
+        Some("windows-1252")
+    } else {
+        Some("iso-8859-1")
+    }
+}
+
+/// `lf`, `crlf`, `cr`, `mixed` or `none`
+fn line_endings(sample: &[u8]) -> &'static str {
+    let (mut lf, mut crlf, mut cr) = (0, 0, 0);
+    let mut i = 0;
+    while i < sample.len() {
+        match sample[i] {
+            b'\r' if sample.get(i + 1) == Some(&b'\n') => {
+                crlf += 1;
+                i += 1;
+            }
+            b'\r' => cr += 1,
+            b'\n' => lf += 1,
+            _ => {}
+        }
+        i += 1;
+    }
+
+    match (lf > 0, crlf > 0, cr > 0) {
+        (false, false, false) => "none",
+        (true, false, false) => "lf",
+        (false, true, false) => "crlf",
+        (false, false, true) => "cr",
+        _ => "mixed",
+    }
+}
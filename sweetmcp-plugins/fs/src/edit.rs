@@ -0,0 +1,388 @@
+//! Targeted edits of a single file
+//!
+//! Besides overwriting, `edit` can replace a range of lines, insert lines,
+//! swap find/replace pairs or apply the hunks of a unified diff. The whole
+//! edit is worked out in memory and the file is written once, only if every
+//! part of it applies, so a failed edit leaves the file untouched.
+
+use std::fs;
+
+use serde_json::{Value, json};
+
+/// Unchanged lines shown around the changed region in a preview
+const PREVIEW_CONTEXT: usize = 3;
+
+/// Longest preview, in lines
+const PREVIEW_MAX_LINES: usize = 60;
+
+/// A file split into lines, remembering how to join them again
+struct Lines {
+    lines: Vec<String>,
+    eol: &'static str,
+    trailing_newline: bool,
+}
+
+impl Lines {
+    fn parse(content: &str) -> Self {
+        Self {
+            lines: content.lines().map(String::from).collect(),
+            eol: if content.contains("\r\n") {
+                "\r\n"
+            } else {
+                "\n"
+            },
+            trailing_newline: content.is_empty() || content.ends_with('\n'),
+        }
+    }
+
+    fn render(&self) -> String {
+        let mut out = self.lines.join(self.eol);
+        if self.trailing_newline && !self.lines.is_empty() {
+            out.push_str(self.eol);
+        }
+        out
+    }
+}
+
+/// Apply the edit described by `args` to `path`, returning a summary with the
+/// number of changes and a preview. With `dry_run` nothing is written.
+pub fn edit(path: &str, mode: &str, args: &Value) -> Result<Value, String> {
+    let original =
+        fs::read_to_string(path).map_err(|e| format!("failed to read {}: {}", path, e))?;
+
+    let (updated, changes) = match mode {
+        "lines" => replace_lines(&original, args)?,
+        "insert" => insert_lines(&original, args)?,
+        "replace" => find_replace(&original, args)?,
+        "patch" => apply_patch(&original, str_arg(args, "patch")?)?,
+        other => {
+            return Err(format!(
+                "unknown edit mode '{}'; expected write, lines, insert, replace or patch",
+                other
+            ));
+        }
+    };
+
+    let dry_run = args
+        .get("dry_run")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let modified = updated != original;
+    if modified && !dry_run {
+        fs::write(path, &updated).map_err(|e| format!("failed to write {}: {}", path, e))?;
+    }
+
+    Ok(json!({
+        "path": path,
+        "mode": mode,
+        "changes": changes,
+        "modified": modified,
+        "dry_run": dry_run,
+        "size": updated.len(),
+        "preview": preview(&original, &updated),
+        "success": true
+    }))
+}
+
+/// Replace lines `start_line..=end_line` (1-based) with `new_text`
+fn replace_lines(original: &str, args: &Value) -> Result<(String, usize), String> {
+    let mut doc = Lines::parse(original);
+    let start = line_arg(args, "start_line")?;
+    let end = match args.get("end_line") {
+        Some(_) => line_arg(args, "end_line")?,
+        None => start,
+    };
+    if end < start {
+        return Err(format!("end_line {} is before start_line {}", end, start));
+    }
+    if end > doc.lines.len() {
+        return Err(format!(
+            "end_line {} is past the end of the file ({} lines)",
+            end,
+            doc.lines.len()
+        ));
+    }
+
+    let new_lines = text_lines(str_arg(args, "new_text")?);
+    doc.lines.splice(start - 1..end, new_lines);
+    Ok((doc.render(), 1))
+}
+
+/// Insert `new_text` before `line` (1-based); one past the last line appends
+fn insert_lines(original: &str, args: &Value) -> Result<(String, usize), String> {
+    let mut doc = Lines::parse(original);
+    let line = line_arg(args, "line")?;
+    if line > doc.lines.len() + 1 {
+        return Err(format!(
+            "line {} is past the end of the file ({} lines)",
+            line,
+            doc.lines.len()
+        ));
+    }
+
+    let new_lines = text_lines(str_arg(args, "new_text")?);
+    if new_lines.is_empty() {
+        return Err("new_text must not be empty for insert".to_string());
+    }
+    doc.lines.splice(line - 1..line - 1, new_lines);
+    Ok((doc.render(), 1))
+}
+
+/// Replace `old_text` with `new_text`, or each pair in `edits` in order. A
+/// pair must match exactly once unless `replace_all` is set.
+fn find_replace(original: &str, args: &Value) -> Result<(String, usize), String> {
+    let replace_all = args
+        .get("replace_all")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+
+    let pairs = match args.get("edits").and_then(Value::as_array) {
+        Some(edits) => edits
+            .iter()
+            .enumerate()
+            .map(|(i, pair)| {
+                Ok((
+                    str_arg(pair, "old_text").map_err(|e| format!("edits[{}]: {}", i, e))?,
+                    str_arg(pair, "new_text").map_err(|e| format!("edits[{}]: {}", i, e))?,
+                ))
+            })
+            .collect::<Result<Vec<_>, String>>()?,
+        None => vec![(str_arg(args, "old_text")?, str_arg(args, "new_text")?)],
+    };
+    if pairs.is_empty() {
+        return Err("edits must contain at least one pair".to_string());
+    }
+
+    let mut content = original.to_string();
+    let mut changes = 0;
+    for (i, (old_text, new_text)) in pairs.into_iter().enumerate() {
+        if old_text.is_empty() {
+            return Err(format!("edit {}: old_text must not be empty", i));
+        }
+        let count = content.matches(old_text).count();
+        match count {
+            0 => return Err(format!("edit {}: old_text not found", i)),
+            1 => {}
+            n if !replace_all => {
+                return Err(format!(
+                    "edit {}: old_text matches {} times; make it unique or set replace_all",
+                    i, n
+                ));
+            }
+            _ => {}
+        }
+        content = content.replace(old_text, new_text);
+        changes += count;
+    }
+    Ok((content, changes))
+}
+
+/// One hunk of a unified diff
+struct Hunk {
+    /// Line the hunk starts at in the original file, 1-based
+    old_start: usize,
+    header: String,
+    /// Context and removed lines
+    old: Vec<String>,
+    /// Context and added lines
+    new: Vec<String>,
+}
+
+/// Apply every hunk of the unified diff `patch`, returning the hunk count.
+/// A hunk whose context moved is looked up nearest its stated position.
+fn apply_patch(original: &str, patch: &str) -> Result<(String, usize), String> {
+    let hunks = parse_patch(patch)?;
+    if hunks.is_empty() {
+        return Err("patch contains no hunks".to_string());
+    }
+
+    let mut doc = Lines::parse(original);
+    let mut placed = Vec::with_capacity(hunks.len());
+    let mut min_pos = 0;
+    for (i, hunk) in hunks.iter().enumerate() {
+        // `-N,0` inserts after line N; otherwise the hunk starts at line N
+        let expected = if hunk.old.is_empty() {
+            hunk.old_start
+        } else {
+            hunk.old_start.saturating_sub(1)
+        };
+        let pos = locate(&doc.lines, &hunk.old, expected, min_pos).ok_or_else(|| {
+            format!(
+                "hunk {} ({}) does not apply: its context was not found",
+                i + 1,
+                hunk.header
+            )
+        })?;
+        min_pos = pos + hunk.old.len();
+        placed.push(pos);
+    }
+
+    // Splice from the bottom up so earlier positions stay valid
+    for (hunk, pos) in hunks.iter().zip(placed).rev() {
+        doc.lines
+            .splice(pos..pos + hunk.old.len(), hunk.new.iter().cloned());
+    }
+    Ok((doc.render(), hunks.len()))
+}
+
+fn parse_patch(patch: &str) -> Result<Vec<Hunk>, String> {
+    let mut hunks = Vec::new();
+    let mut lines = patch.lines().peekable();
+
+    while let Some(line) = lines.next() {
+        // File headers and anything else outside a hunk are ignored
+        let Some(ranges) = line.strip_prefix("@@ ") else {
+            continue;
+        };
+        let (old_start, old_len, new_len) =
+            parse_hunk_header(ranges).ok_or_else(|| format!("malformed hunk header '{}'", line))?;
+
+        let mut hunk = Hunk {
+            old_start,
+            header: line.trim_end().to_string(),
+            old: Vec::new(),
+            new: Vec::new(),
+        };
+        while hunk.old.len() < old_len || hunk.new.len() < new_len {
+            let Some(body) = lines.next() else {
+                return Err(format!("hunk '{}' ends early", hunk.header));
+            };
+            let body = body.strip_suffix('\r').unwrap_or(body);
+            match body.chars().next() {
+                Some(' ') => {
+                    hunk.old.push(body[1..].to_string());
+                    hunk.new.push(body[1..].to_string());
+                }
+                // Some tools strip the space from empty context lines
+                None => {
+                    hunk.old.push(String::new());
+                    hunk.new.push(String::new());
+                }
+                Some('-') => hunk.old.push(body[1..].to_string()),
+                Some('+') => hunk.new.push(body[1..].to_string()),
+                Some('\\') => {}
+                Some(_) => {
+                    return Err(format!(
+                        "unexpected line in hunk '{}': {}",
+                        hunk.header, body
+                    ));
+                }
+            }
+        }
+        // A "\ No newline at end of file" marker may follow the last line
+        if lines.peek().is_some_and(|next| next.starts_with('\\')) {
+            lines.next();
+        }
+        hunks.push(hunk);
+    }
+    Ok(hunks)
+}
+
+/// `(old_start, old_len, new_len)` from `-a,b +c,d @@ ...`
+fn parse_hunk_header(ranges: &str) -> Option<(usize, usize, usize)> {
+    let mut parts = ranges.split_whitespace();
+    let old = parts.next()?.strip_prefix('-')?;
+    let new = parts.next()?.strip_prefix('+')?;
+    let range = |r: &str| -> Option<(usize, usize)> {
+        match r.split_once(',') {
+            Some((start, len)) => Some((start.parse().ok()?, len.parse().ok()?)),
+            None => Some((r.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_len) = range(old)?;
+    let (_, new_len) = range(new)?;
+    Some((old_start, old_len, new_len))
+}
+
+/// Position at or after `min_pos` where `needle` occurs, nearest `expected`
+fn locate(
+    haystack: &[String],
+    needle: &[String],
+    expected: usize,
+    min_pos: usize,
+) -> Option<usize> {
+    let last = haystack.len().checked_sub(needle.len())?;
+    let matches_at = |pos: usize| {
+        pos >= min_pos
+            && pos <= last
+            && haystack[pos..pos + needle.len()]
+                .iter()
+                .zip(needle)
+                .all(|(a, b)| a.trim_end() == b.trim_end())
+    };
+
+    let expected = expected.min(last);
+    (0..=last).find_map(|distance| {
+        let after = expected + distance;
+        let before = expected.checked_sub(distance);
+        if matches_at(after) {
+            Some(after)
+        } else {
+            before.filter(|&pos| matches_at(pos))
+        }
+    })
+}
+
+/// Diff-style view of the region between the first and last changed lines
+fn preview(original: &str, updated: &str) -> String {
+    let old: Vec<&str> = original.lines().collect();
+    let new: Vec<&str> = updated.lines().collect();
+
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    if prefix == old.len() && prefix == new.len() {
+        return String::new();
+    }
+
+    let from = prefix.saturating_sub(PREVIEW_CONTEXT);
+    let old_end = old.len() - suffix;
+    let new_end = new.len() - suffix;
+    let after = (old.len() - old_end).min(PREVIEW_CONTEXT);
+
+    let mut out = vec![format!(
+        "@@ -{},{} +{},{} @@",
+        from + 1,
+        old_end + after - from,
+        from + 1,
+        new_end + after - from
+    )];
+    out.extend(old[from..prefix].iter().map(|l| format!(" {}", l)));
+    out.extend(old[prefix..old_end].iter().map(|l| format!("-{}", l)));
+    out.extend(new[prefix..new_end].iter().map(|l| format!("+{}", l)));
+    out.extend(
+        old[old_end..old_end + after]
+            .iter()
+            .map(|l| format!(" {}", l)),
+    );
+
+    if out.len() > PREVIEW_MAX_LINES {
+        let hidden = out.len() - PREVIEW_MAX_LINES;
+        out.truncate(PREVIEW_MAX_LINES);
+        out.push(format!("... {} more lines", hidden));
+    }
+    out.join("\n")
+}
+
+fn text_lines(text: &str) -> Vec<String> {
+    text.lines().map(String::from).collect()
+}
+
+fn str_arg<'a>(args: &'a Value, name: &str) -> Result<&'a str, String> {
+    args.get(name)
+        .and_then(Value::as_str)
+        .ok_or_else(|| format!("{} is required", name))
+}
+
+/// 1-based line number argument
+fn line_arg(args: &Value, name: &str) -> Result<usize, String> {
+    args.get(name)
+        .and_then(Value::as_u64)
+        .filter(|&n| n >= 1)
+        .map(|n| n as usize)
+        .ok_or_else(|| format!("{} must be a line number starting at 1", name))
+}
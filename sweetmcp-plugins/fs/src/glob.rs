@@ -0,0 +1,115 @@
+//! Shell-style glob patterns
+//!
+//! `*` matches within one path component, `**` across components, `?` one
+//! character and `[...]` a character class (`[!...]` negated, ranges as
+//! `a-z`). Paths use `/` separators. A pattern without a `/` is matched
+//! against the file name alone, so `*.rs` finds Rust files at any depth.
+
+/// A compiled glob pattern
+#[derive(Debug, Clone)]
+pub struct Glob {
+    pattern: Vec<char>,
+    name_only: bool,
+}
+
+impl Glob {
+    pub fn new(pattern: &str) -> Self {
+        let pattern = pattern.trim_start_matches("./");
+        Self {
+            name_only: !pattern.contains('/'),
+            pattern: pattern.chars().collect(),
+        }
+    }
+
+    /// Whether `path`, relative to the search root, matches
+    pub fn matches(&self, path: &str) -> bool {
+        let path = path.trim_start_matches("./");
+        let target = if self.name_only {
+            path.rsplit('/').next().unwrap_or(path)
+        } else {
+            path
+        };
+        let target: Vec<char> = target.chars().collect();
+        match_from(&self.pattern, &target)
+    }
+}
+
+/// Whether any of `globs` matches `path`
+pub fn any_matches(globs: &[Glob], path: &str) -> bool {
+    globs.iter().any(|glob| glob.matches(path))
+}
+
+fn match_from(pattern: &[char], text: &[char]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some('*') if pattern.get(1) == Some(&'*') => {
+            // `**/` also matches no directories at all
+            let rest = &pattern[2..];
+            let rest_no_slash = rest.strip_prefix(&['/']).unwrap_or(rest);
+            (0..=text.len()).any(|i| {
+                match_from(rest, &text[i..])
+                    || (i == 0 || text[i - 1] == '/') && match_from(rest_no_slash, &text[i..])
+            })
+        }
+        Some('*') => {
+            let rest = &pattern[1..];
+            for i in 0..=text.len() {
+                if match_from(rest, &text[i..]) {
+                    return true;
+                }
+                if text.get(i) == Some(&'/') {
+                    break;
+                }
+            }
+            false
+        }
+        Some('?') => match text.first() {
+            Some(c) if *c != '/' => match_from(&pattern[1..], &text[1..]),
+            _ => false,
+        },
+        Some('[') => match (text.first(), class_end(pattern)) {
+            (Some(c), Some(end)) if *c != '/' => {
+                class_matches(&pattern[1..end], *c) && match_from(&pattern[end + 1..], &text[1..])
+            }
+            // Unterminated class: treat `[` literally
+            (Some('['), None) => match_from(&pattern[1..], &text[1..]),
+            _ => false,
+        },
+        Some(p) => text.first() == Some(p) && match_from(&pattern[1..], &text[1..]),
+    }
+}
+
+/// Index of the `]` closing the class opened at `pattern[0]`
+fn class_end(pattern: &[char]) -> Option<usize> {
+    let mut i = 1;
+    if matches!(pattern.get(i), Some('!') | Some('^')) {
+        i += 1;
+    }
+    // A leading `]` is part of the class
+    if pattern.get(i) == Some(&']') {
+        i += 1;
+    }
+    pattern[i..]
+        .iter()
+        .position(|c| *c == ']')
+        .map(|offset| i + offset)
+}
+
+fn class_matches(class: &[char], c: char) -> bool {
+    let (negated, class) = match class.first() {
+        Some('!') | Some('^') => (true, &class[1..]),
+        _ => (false, class),
+    };
+    let mut found = false;
+    let mut i = 0;
+    while i < class.len() {
+        if i + 2 < class.len() && class[i + 1] == '-' {
+            found |= (class[i]..=class[i + 2]).contains(&c);
+            i += 3;
+        } else {
+            found |= class[i] == c;
+            i += 1;
+        }
+    }
+    found != negated
+}
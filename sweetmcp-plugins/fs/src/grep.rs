@@ -0,0 +1,156 @@
+//! Content search across files
+//!
+//! Walks a file or directory and reports every line matching a regex, with
+//! optional lines of context. Files larger than the size limit, and files
+//! that are not UTF-8 text, are skipped and counted rather than searched.
+//! Symlinked directories are not followed, nor symlinked files that lead
+//! outside the allowed roots.
+
+use std::fs;
+use std::path::Path;
+
+use regex::Regex;
+use serde_json::{Value, json};
+
+use crate::glob::{Glob, any_matches};
+use crate::sandbox;
+
+/// Largest file searched when the caller gives no limit
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+/// Matches returned when the caller gives no limit
+pub const DEFAULT_MAX_MATCHES: usize = 200;
+
+/// Longest line returned; longer lines are cut, e.g. minified sources
+const MAX_LINE_CHARS: usize = 500;
+
+pub struct GrepOptions {
+    pub regex: Regex,
+    pub include: Vec<Glob>,
+    pub exclude: Vec<Glob>,
+    pub max_file_size: u64,
+    pub context: usize,
+    pub max_matches: usize,
+}
+
+#[derive(Default)]
+struct Search {
+    matches: Vec<Value>,
+    files_searched: usize,
+    files_matched: usize,
+    files_skipped: usize,
+    truncated: bool,
+}
+
+/// Search `root`, a file or a directory
+pub fn grep(root: &Path, options: &GrepOptions) -> Result<Value, String> {
+    let metadata = fs::metadata(root).map_err(|e| e.to_string())?;
+    let mut search = Search::default();
+    if metadata.is_dir() {
+        walk(root, "", options, &mut search);
+    } else {
+        search_file(root, metadata.len(), options, &mut search);
+    }
+
+    Ok(json!({
+        "pattern": options.regex.as_str(),
+        "path": root.to_string_lossy(),
+        "matches": search.matches,
+        "count": search.matches.len(),
+        "files_searched": search.files_searched,
+        "files_matched": search.files_matched,
+        "files_skipped": search.files_skipped,
+        "truncated": search.truncated
+    }))
+}
+
+fn walk(dir: &Path, relative: &str, options: &GrepOptions, search: &mut Search) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        search.files_skipped += 1;
+        return;
+    };
+    let mut entries = entries.flatten().collect::<Vec<_>>();
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        if search.truncated {
+            return;
+        }
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let child_relative = if relative.is_empty() {
+            name
+        } else {
+            format!("{}/{}", relative, name)
+        };
+        if any_matches(&options.exclude, &child_relative) {
+            continue;
+        }
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+
+        if file_type.is_dir() {
+            walk(&entry.path(), &child_relative, options, search);
+        } else if !file_type.is_symlink()
+            || (entry.path().is_file() && sandbox::allows(&entry.path()))
+        {
+            if !options.include.is_empty() && !any_matches(&options.include, &child_relative) {
+                continue;
+            }
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            search_file(&entry.path(), size, options, search);
+        }
+    }
+}
+
+fn search_file(path: &Path, size: u64, options: &GrepOptions, search: &mut Search) {
+    if size > options.max_file_size {
+        search.files_skipped += 1;
+        return;
+    }
+    // Binary files fail UTF-8 decoding or contain NULs
+    let content = match fs::read_to_string(path) {
+        Ok(content) if !content.contains('\0') => content,
+        _ => {
+            search.files_skipped += 1;
+            return;
+        }
+    };
+    search.files_searched += 1;
+
+    let lines: Vec<&str> = content.lines().collect();
+    let mut matched = false;
+    for (index, line) in lines.iter().enumerate() {
+        if !options.regex.is_match(line) {
+            continue;
+        }
+        if search.matches.len() >= options.max_matches {
+            search.truncated = true;
+            break;
+        }
+        matched = true;
+
+        let mut hit = json!({
+            "path": path.to_string_lossy(),
+            "line": index + 1,
+            "text": clip(line)
+        });
+        if options.context > 0 {
+            let before = index.saturating_sub(options.context);
+            let after = (index + 1 + options.context).min(lines.len());
+            hit["before"] = lines[before..index].iter().map(|l| clip(l)).collect();
+            hit["after"] = lines[index + 1..after].iter().map(|l| clip(l)).collect();
+        }
+        search.matches.push(hit);
+    }
+    if matched {
+        search.files_matched += 1;
+    }
+}
+
+fn clip(line: &str) -> String {
+    match line.char_indices().nth(MAX_LINE_CHARS) {
+        Some((end, _)) => format!("{}...", &line[..end]),
+        None => line.to_string(),
+    }
+}
@@ -0,0 +1,161 @@
+//! Byte-level reads and writes
+//!
+//! Contents travel as UTF-8 text or as base64, so binary files such as images
+//! round-trip unchanged. Reads can take a byte range to page through large
+//! files; a UTF-8 range that cuts a character in half is shrunk to whole
+//! characters and the result reports the range actually returned.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde_json::{Value, json};
+
+/// Largest read returned in one call; page through bigger files with offset
+const MAX_READ_BYTES: u64 = 16 * 1024 * 1024;
+
+/// How file contents are carried in JSON
+#[derive(Clone, Copy, PartialEq)]
+pub enum Encoding {
+    Utf8,
+    Base64,
+}
+
+impl Encoding {
+    /// The `encoding` argument, UTF-8 when absent
+    pub fn from_args(args: &Value) -> Result<Self, String> {
+        match args.get("encoding").and_then(|v| v.as_str()) {
+            None | Some("utf-8") | Some("utf8") => Ok(Encoding::Utf8),
+            Some("base64") => Ok(Encoding::Base64),
+            Some(other) => Err(format!(
+                "unknown encoding '{}', expected utf-8 or base64",
+                other
+            )),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Encoding::Utf8 => "utf-8",
+            Encoding::Base64 => "base64",
+        }
+    }
+}
+
+/// Read `length` bytes of `path` from `offset`, or to the end of the file
+pub fn read(
+    path: &Path,
+    encoding: Encoding,
+    offset: u64,
+    length: Option<u64>,
+) -> Result<Value, String> {
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    let size = file.metadata().map_err(|e| e.to_string())?.len();
+    let offset = offset.min(size);
+    let wanted = length.unwrap_or(size - offset).min(size - offset);
+    if wanted > MAX_READ_BYTES {
+        return Err(format!(
+            "{} bytes requested, more than the {} byte limit; read a range with offset and length",
+            wanted, MAX_READ_BYTES
+        ));
+    }
+
+    let mut bytes = Vec::with_capacity(wanted as usize);
+    file.seek(SeekFrom::Start(offset))
+        .map_err(|e| e.to_string())?;
+    file.take(wanted)
+        .read_to_end(&mut bytes)
+        .map_err(|e| e.to_string())?;
+
+    let (content, start, end) = match encoding {
+        Encoding::Base64 => (STANDARD.encode(&bytes), 0, bytes.len()),
+        Encoding::Utf8 => {
+            let (start, end) = whole_chars(&bytes, offset > 0, offset + wanted < size);
+            let text = std::str::from_utf8(&bytes[start..end]).map_err(|e| {
+                format!(
+                    "not valid UTF-8 at byte {}; read it with encoding base64",
+                    offset + (start + e.valid_up_to()) as u64
+                )
+            })?;
+            (text.to_string(), start, end)
+        }
+    };
+
+    let offset = offset + start as u64;
+    let bytes_read = (end - start) as u64;
+    Ok(json!({
+        "path": path.to_string_lossy(),
+        "content": content,
+        "encoding": encoding.name(),
+        "offset": offset,
+        "bytes_read": bytes_read,
+        "size": size,
+        "eof": offset + bytes_read >= size,
+    }))
+}
+
+/// Write `content` to `path`, replacing the file or appending to it
+pub fn write(
+    path: &Path,
+    content: &str,
+    encoding: Encoding,
+    append: bool,
+) -> Result<Value, String> {
+    let bytes = match encoding {
+        Encoding::Utf8 => content.as_bytes().to_vec(),
+        Encoding::Base64 => STANDARD
+            .decode(content.trim())
+            .map_err(|e| format!("content is not valid base64: {}", e))?,
+    };
+
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() && !parent.exists() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("failed to create parent directories: {}", e))?;
+        }
+    }
+
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .append(append)
+        .truncate(!append)
+        .open(path)
+        .map_err(|e| e.to_string())?;
+    file.write_all(&bytes).map_err(|e| e.to_string())?;
+    let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+
+    Ok(json!({
+        "path": path.to_string_lossy(),
+        "bytes_written": bytes.len(),
+        "appended": append,
+        "size": size,
+        "success": true
+    }))
+}
+
+/// Byte range of `bytes` without a character cut at either end. Only ends
+/// that were cut by the requested range are trimmed; anything else invalid
+/// is left for the UTF-8 check to report.
+fn whole_chars(bytes: &[u8], cut_start: bool, cut_end: bool) -> (usize, usize) {
+    let is_continuation = |b: u8| b & 0xC0 == 0x80;
+    let mut start = 0;
+    if cut_start {
+        // A UTF-8 character has at most three continuation bytes
+        while start < bytes.len().min(3) && is_continuation(bytes[start]) {
+            start += 1;
+        }
+    }
+    let mut end = bytes.len();
+    if cut_end {
+        if let Err(e) = std::str::from_utf8(&bytes[start..]) {
+            // `error_len` is `None` only for a sequence cut off by the end
+            if e.error_len().is_none() {
+                end = start + e.valid_up_to();
+            }
+        }
+    }
+    (start, end)
+}
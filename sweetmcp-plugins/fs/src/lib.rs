@@ -1,3 +1,17 @@
+mod apply;
+mod attrs;
+mod checksum;
+mod copy;
+mod delete;
+mod detect;
+mod edit;
+mod glob;
+mod grep;
+mod io;
+mod sandbox;
+mod tree;
+mod watch;
+
 use std::fs;
 use std::path::Path;
 use std::time::SystemTime;
@@ -5,13 +19,14 @@ use std::time::SystemTime;
 use extism_pdk::*;
 use serde_json::{Value, json};
 use sweetmcp_plugin_builder::prelude::*;
-use sweetmcp_plugin_builder::{CallToolRequest, CallToolResult, ListToolsResult, Ready};
+use sweetmcp_plugin_builder::{CallToolResult, Ready};
 
 /// File system operations tool using plugin-builder
 struct FsTool;
 
 impl McpTool for FsTool {
     const NAME: &'static str = "fs";
+    type Args = Value;
 
     fn description(builder: DescriptionBuilder) -> DescriptionBuilder {
         builder
@@ -24,16 +39,135 @@ impl McpTool for FsTool {
             .when("you need to search for files by name or content")
             .when("you need to get file metadata like size, permissions, timestamps")
             .perfect_for("file management, content processing, directory operations, and system administration tasks")
-            .operation("read", "Read the complete contents of a file")
+            .operation(
+                "read",
+                "Read a file, or a byte range of it with offset and length; binary files need encoding base64",
+            )
             .operation("read_multiple", "Read contents of multiple files in batch")
-            .operation("write", "Write content to a file (creates or overwrites)")
-            .operation("edit", "Edit specific parts of a file with targeted changes")
+            .operation(
+                "write",
+                "Write content to a file (creates, overwrites, or appends with append); base64 content for binary files",
+            )
+            .operation(
+                "edit",
+                "Edit part of a file: replace a line range, insert lines, find/replace text or apply a unified diff; returns the number of changes and a preview",
+            )
+            .operation(
+                "copy",
+                "Copy a file or directory tree to destination; existing files are kept unless overwrite is set",
+            )
+            .operation(
+                "delete",
+                "Delete a file, or a directory with recursive; trash moves it to the trash instead so it can be restored",
+            )
             .operation("mkdir", "Create directories (with parent directory support)")
             .operation("list", "List contents of a directory with detailed information")
+            .operation(
+                "tree",
+                "Return a nested directory tree with entry counts, limited by depth and include/exclude globs",
+            )
+            .operation(
+                "grep",
+                "Search file contents with a regex, returning path, line number and matched line with optional context",
+            )
             .operation("search", "Search for files by name pattern or content")
+            .operation(
+                "checksum",
+                "Hash a file, or every file under a directory, with sha256, md5 or blake3",
+            )
+            .operation(
+                "find_duplicates",
+                "Group files under a directory whose contents are identical, with the bytes each group wastes",
+            )
+            .operation(
+                "watch",
+                "Start recording changes to a file or directory tree; replaces any earlier watch on the same path",
+            )
+            .operation(
+                "poll_changes",
+                "Return created, modified and deleted events on watched paths since the last poll",
+            )
+            .operation("unwatch", "Stop watching a path, or every path when none is given")
             .operation("read_metadata", "Get detailed file metadata and properties")
+            .operation(
+                "set_permissions",
+                "Set Unix mode bits (e.g. 644) or the read-only flag; platforms without mode bits apply read-only only",
+            )
+            .operation(
+                "touch",
+                "Create an empty file if it doesn't exist, and set its times to now or to modified",
+            )
+            .operation(
+                "set_times",
+                "Set the modification and/or access time of a file or directory",
+            )
+            .operation(
+                "detect",
+                "Detect MIME type, text or binary, character encoding and line endings before reading a file",
+            )
+            .operation(
+                "apply_changes",
+                "Apply a batch of writes, edits and deletes across files atomically: either all succeed or none are applied",
+            )
             .requires("File system access permissions for the target paths")
             .not_for("operations outside of allowed directories or system files")
+            .example(json!({"operation": "list", "path": "."}), "List the current directory")
+            .example(
+                json!({"operation": "read", "path": "logs/app.log", "offset": 1048576, "length": 65536}),
+                "Read 64 KiB of a large log starting at 1 MiB",
+            )
+            .example(
+                json!({"operation": "read", "path": "assets/logo.png", "encoding": "base64"}),
+                "Read a binary file as base64",
+            )
+            .example(
+                json!({"operation": "write", "path": "notes.txt", "content": "hello"}),
+                "Create or overwrite a file",
+            )
+            .example(
+                json!({"operation": "delete", "path": "build", "recursive": true, "dry_run": true}),
+                "See what deleting a directory would remove before doing it",
+            )
+            .example(
+                json!({"operation": "tree", "path": ".", "max_depth": 2, "exclude": ["target", ".git"]}),
+                "Get an overview of a project",
+            )
+            .example(
+                json!({"operation": "search", "path": "src", "pattern": "*.rs"}),
+                "Find Rust sources under src",
+            )
+            .example(
+                json!({"operation": "edit", "path": "src/main.rs", "mode": "replace", "old_text": "let x = 1;", "new_text": "let x = 2;"}),
+                "Change one line of code in place",
+            )
+            .example(
+                json!({"operation": "edit", "path": "README.md", "mode": "lines", "start_line": 3, "end_line": 5, "new_text": "New paragraph\n", "dry_run": true}),
+                "Preview replacing lines 3 to 5",
+            )
+            .example(
+                json!({"operation": "grep", "path": "src", "pattern": "fn \\w+_handler", "include": ["*.rs"], "context": 2}),
+                "Find handler functions in Rust sources with two lines of context",
+            )
+            .example(
+                json!({"operation": "set_permissions", "path": "scripts/deploy.sh", "mode": "755"}),
+                "Make a script executable",
+            )
+            .example(
+                json!({"operation": "watch", "path": "src", "exclude": ["target"]}),
+                "Record changes under src, then read them later with poll_changes",
+            )
+            .example(
+                json!({"operation": "detect", "path": "data/export.csv"}),
+                "Check whether a file is text before reading it",
+            )
+            .example(
+                json!({"operation": "apply_changes", "changes": [
+                    {"action": "edit", "path": "src/lib.rs", "old_text": "mod old;", "new_text": "mod new;"},
+                    {"action": "write", "path": "src/new.rs", "content": "pub fn f() {}\n"},
+                    {"action": "delete", "path": "src/old.rs"}
+                ]}),
+                "Rename a module in one atomic step",
+            )
     }
 
     fn schema(builder: SchemaBuilder) -> Value {
@@ -46,18 +180,215 @@ impl McpTool for FsTool {
                     "read_multiple",
                     "write",
                     "edit",
+                    "copy",
+                    "delete",
                     "mkdir",
                     "list",
+                    "tree",
+                    "grep",
                     "search",
                     "read_metadata",
+                    "set_permissions",
+                    "touch",
+                    "set_times",
+                    "checksum",
+                    "find_duplicates",
+                    "watch",
+                    "poll_changes",
+                    "unwatch",
+                    "detect",
+                    "apply_changes",
                 ],
             )
             .optional_string(
                 "path",
                 "File or directory path (required for most operations)",
             )
-            .optional_string("content", "Content to write (required for write operation)")
-            .optional_string("pattern", "Search pattern for file search operations")
+            .optional_array(
+                "paths",
+                "File paths to read (required for read_multiple operation)",
+                json!({"type": "string"}),
+            )
+            .optional_string(
+                "content",
+                "Content to write, as text or base64 per encoding (required for write operation)",
+            )
+            .optional_enum(
+                "encoding",
+                "How content is carried: utf-8 text, or base64 for binary files (read, write; default utf-8)",
+                &["utf-8", "base64"],
+            )
+            .optional_integer(
+                "offset",
+                "Byte to start reading from (read, default 0)",
+                Some(0),
+                None,
+            )
+            .optional_integer(
+                "length",
+                "Most bytes to read; defaults to the rest of the file (read)",
+                Some(0),
+                None,
+            )
+            .optional_bool(
+                "append",
+                "Append content to the end of the file instead of replacing it (write)",
+            )
+            .optional_string(
+                "destination",
+                "Exact path to create (required for copy operation)",
+            )
+            .optional_bool(
+                "overwrite",
+                "Replace files that already exist at destination (copy)",
+            )
+            .optional_bool(
+                "recursive",
+                "Allow deleting a non-empty directory and everything in it (delete)",
+            )
+            .optional_bool(
+                "trash",
+                "Move to the trash instead of deleting permanently (delete)",
+            )
+            .optional_enum(
+                "mode",
+                "How edit changes the file; defaults to write, which overwrites it with content",
+                &["write", "lines", "insert", "replace", "patch"],
+            )
+            .optional_integer(
+                "start_line",
+                "First line to replace, 1-based (lines mode)",
+                Some(1),
+                None,
+            )
+            .optional_integer(
+                "end_line",
+                "Last line to replace, inclusive; defaults to start_line (lines mode)",
+                Some(1),
+                None,
+            )
+            .optional_integer(
+                "line",
+                "Line to insert before, 1-based; one past the last line appends (insert mode)",
+                Some(1),
+                None,
+            )
+            .optional_string(
+                "old_text",
+                "Text to find; must occur exactly once unless replace_all is set (replace mode)",
+            )
+            .optional_string(
+                "new_text",
+                "Replacement text (lines, insert and replace modes)",
+            )
+            .optional_array(
+                "edits",
+                "Several find/replace pairs applied in order, instead of old_text and new_text (replace mode)",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "old_text": {"type": "string"},
+                        "new_text": {"type": "string"}
+                    },
+                    "required": ["old_text", "new_text"]
+                }),
+            )
+            .optional_bool(
+                "replace_all",
+                "Replace every occurrence instead of requiring a unique match (replace mode)",
+            )
+            .optional_string("patch", "Unified diff to apply to the file (patch mode)")
+            .optional_integer(
+                "max_depth",
+                "Levels below path to descend into (tree, default 3)",
+                Some(1),
+                None,
+            )
+            .optional_array(
+                "include",
+                "Globs of files to keep, e.g. \"*.rs\" or \"src/**/*.ts\" (tree, grep, checksum, find_duplicates, watch)",
+                json!({"type": "string"}),
+            )
+            .optional_array(
+                "exclude",
+                "Globs of files and directories to skip, e.g. \"target\" (tree, grep, checksum, find_duplicates, watch)",
+                json!({"type": "string"}),
+            )
+            .optional_integer(
+                "context",
+                "Lines of context before and after each match (grep, default 0)",
+                Some(0),
+                Some(20),
+            )
+            .optional_bool("case_insensitive", "Match regardless of case (grep)")
+            .optional_integer(
+                "max_file_size",
+                "Skip files larger than this many bytes (grep, default 1048576)",
+                Some(1),
+                None,
+            )
+            .optional_integer(
+                "max_matches",
+                "Stop after this many matches (grep, default 200)",
+                Some(1),
+                None,
+            )
+            .optional_enum(
+                "algorithm",
+                "Hash to compute (checksum, find_duplicates; default sha256)",
+                &["sha256", "md5", "blake3"],
+            )
+            .optional_integer(
+                "min_size",
+                "Ignore files smaller than this many bytes (find_duplicates, default 1)",
+                Some(0),
+                None,
+            )
+            .optional_string(
+                "mode",
+                "Unix permission bits in octal, e.g. \"644\" or \"0755\" (set_permissions)",
+            )
+            .optional_bool(
+                "readonly",
+                "Make the file read-only, or writable again with false (set_permissions)",
+            )
+            .optional_string(
+                "modified",
+                "Modification time as RFC 3339, epoch seconds or \"now\" (touch, set_times; touch defaults to now)",
+            )
+            .optional_string(
+                "accessed",
+                "Access time as RFC 3339, epoch seconds or \"now\" (set_times)",
+            )
+            .optional_integer(
+                "since",
+                "Return events after this cursor instead of after the last poll (poll_changes)",
+                Some(0),
+                None,
+            )
+            .optional_bool(
+                "dry_run",
+                "Report what would change without touching the file system (edit, copy, delete)",
+            )
+            .optional_string(
+                "pattern",
+                "File name pattern for search, or regular expression for grep",
+            )
+            .optional_array(
+                "changes",
+                "Changes for apply_changes, applied in order",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "action": {"type": "string", "enum": ["write", "edit", "delete"]},
+                        "path": {"type": "string"},
+                        "content": {"type": "string", "description": "New contents (write)"},
+                        "old_text": {"type": "string", "description": "Text to replace, must occur exactly once (edit)"},
+                        "new_text": {"type": "string", "description": "Replacement text (edit)"}
+                    },
+                    "required": ["action", "path"]
+                }),
+            )
             .build()
     }
 
@@ -67,15 +398,33 @@ impl McpTool for FsTool {
             .and_then(|v| v.as_str())
             .ok_or_else(|| Error::msg("operation parameter required"))?;
 
+        if let Err(e) = sandbox::check_args(operation, &args) {
+            return Ok(ContentBuilder::error(e));
+        }
+
         match operation {
             "read" => read_file(&args),
             "read_multiple" => read_multiple_files(&args),
             "write" => write_file(&args),
             "edit" => edit_file(&args),
+            "copy" => copy_path(&args),
+            "delete" => delete_path(&args),
             "mkdir" => create_dir(&args),
             "list" => list_dir(&args),
+            "tree" => dir_tree(&args),
+            "grep" => grep_files(&args),
             "search" => search_files(&args),
             "read_metadata" => get_file_info(&args),
+            "set_permissions" => set_permissions(&args),
+            "touch" => touch_file(&args),
+            "set_times" => set_times(&args),
+            "checksum" => checksum_files(&args),
+            "find_duplicates" => find_duplicates(&args),
+            "watch" => watch_path(&args),
+            "poll_changes" => poll_changes(&args),
+            "unwatch" => unwatch_path(&args),
+            "detect" => detect_file(&args),
+            "apply_changes" => apply_changes(&args),
             _ => Ok(ContentBuilder::error(format!(
                 "Unknown fs operation: {}",
                 operation
@@ -84,22 +433,21 @@ impl McpTool for FsTool {
     }
 }
 
-/// Read file contents
+/// Read file contents, optionally a byte range or as base64
 fn read_file(args: &Value) -> Result<CallToolResult, Error> {
     let path = args
         .get("path")
         .and_then(|v| v.as_str())
         .ok_or_else(|| Error::msg("path parameter required for read operation"))?;
+    let encoding = match io::Encoding::from_args(args) {
+        Ok(encoding) => encoding,
+        Err(e) => return Ok(ContentBuilder::error(e)),
+    };
+    let offset = args.get("offset").and_then(|v| v.as_u64()).unwrap_or(0);
+    let length = args.get("length").and_then(|v| v.as_u64());
 
-    match fs::read_to_string(path) {
-        Ok(content) => Ok(ContentBuilder::text(
-            json!({
-                "path": path,
-                "content": content,
-                "size": content.len()
-            })
-            .to_string(),
-        )),
+    match io::read(Path::new(path), encoding, offset, length) {
+        Ok(result) => Ok(ContentBuilder::text(result.to_string())),
         Err(e) => Ok(ContentBuilder::error(format!(
             "Failed to read file {}: {}",
             path, e
@@ -147,7 +495,7 @@ fn read_multiple_files(args: &Value) -> Result<CallToolResult, Error> {
     ))
 }
 
-/// Write file contents
+/// Write file contents, replacing or appending, from text or base64
 fn write_file(args: &Value) -> Result<CallToolResult, Error> {
     let path = args
         .get("path")
@@ -158,28 +506,17 @@ fn write_file(args: &Value) -> Result<CallToolResult, Error> {
         .get("content")
         .and_then(|v| v.as_str())
         .ok_or_else(|| Error::msg("content parameter required for write operation"))?;
+    let encoding = match io::Encoding::from_args(args) {
+        Ok(encoding) => encoding,
+        Err(e) => return Ok(ContentBuilder::error(e)),
+    };
+    let append = args
+        .get("append")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
 
-    // Create parent directories if they don't exist
-    if let Some(parent) = Path::new(path).parent() {
-        if !parent.exists() {
-            if let Err(e) = fs::create_dir_all(parent) {
-                return Ok(ContentBuilder::error(format!(
-                    "Failed to create parent directories: {}",
-                    e
-                )));
-            }
-        }
-    }
-
-    match fs::write(path, content) {
-        Ok(_) => Ok(ContentBuilder::text(
-            json!({
-                "path": path,
-                "bytes_written": content.len(),
-                "success": true
-            })
-            .to_string(),
-        )),
+    match io::write(Path::new(path), content, encoding, append) {
+        Ok(result) => Ok(ContentBuilder::text(result.to_string())),
         Err(e) => Ok(ContentBuilder::error(format!(
             "Failed to write file {}: {}",
             path, e
@@ -187,15 +524,71 @@ fn write_file(args: &Value) -> Result<CallToolResult, Error> {
     }
 }
 
-/// Edit file (simplified implementation)
+/// Edit part of a file, or overwrite it in the default write mode
 fn edit_file(args: &Value) -> Result<CallToolResult, Error> {
-    let _path = args
+    let path = args
         .get("path")
         .and_then(|v| v.as_str())
         .ok_or_else(|| Error::msg("path parameter required for edit operation"))?;
 
-    // For now, treat edit the same as write - a full implementation would support targeted edits
-    write_file(args)
+    match args.get("mode").and_then(|v| v.as_str()).unwrap_or("write") {
+        "write" => write_file(args),
+        mode => match edit::edit(path, mode, args) {
+            Ok(summary) => Ok(ContentBuilder::text(summary.to_string())),
+            Err(e) => Ok(ContentBuilder::error(format!(
+                "Failed to edit {}: {}",
+                path, e
+            ))),
+        },
+    }
+}
+
+/// Copy a file or directory tree
+fn copy_path(args: &Value) -> Result<CallToolResult, Error> {
+    let path = args
+        .get("path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| Error::msg("path parameter required for copy operation"))?;
+    let destination = args
+        .get("destination")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| Error::msg("destination parameter required for copy operation"))?;
+    let flag = |name: &str| args.get(name).and_then(|v| v.as_bool()).unwrap_or(false);
+
+    match copy::copy(
+        Path::new(path),
+        Path::new(destination),
+        flag("overwrite"),
+        flag("dry_run"),
+    ) {
+        Ok(summary) => Ok(ContentBuilder::text(summary.to_string())),
+        Err(e) => Ok(ContentBuilder::error(format!(
+            "Failed to copy {} to {}: {}",
+            path, destination, e
+        ))),
+    }
+}
+
+/// Delete a file or directory, optionally to the trash
+fn delete_path(args: &Value) -> Result<CallToolResult, Error> {
+    let path = args
+        .get("path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| Error::msg("path parameter required for delete operation"))?;
+    let flag = |name: &str| args.get(name).and_then(|v| v.as_bool()).unwrap_or(false);
+
+    match delete::delete(
+        Path::new(path),
+        flag("recursive"),
+        flag("trash"),
+        flag("dry_run"),
+    ) {
+        Ok(summary) => Ok(ContentBuilder::text(summary.to_string())),
+        Err(e) => Ok(ContentBuilder::error(format!(
+            "Failed to delete {}: {}",
+            path, e
+        ))),
+    }
 }
 
 /// Create directory
@@ -270,6 +663,88 @@ fn list_dir(args: &Value) -> Result<CallToolResult, Error> {
     }
 }
 
+/// Recursive directory tree
+fn dir_tree(args: &Value) -> Result<CallToolResult, Error> {
+    let path = args.get("path").and_then(|v| v.as_str()).unwrap_or(".");
+    let options = tree::TreeOptions {
+        max_depth: args
+            .get("max_depth")
+            .and_then(|v| v.as_u64())
+            .map_or(tree::DEFAULT_MAX_DEPTH, |depth| depth.max(1) as usize),
+        include: glob_list(args, "include"),
+        exclude: glob_list(args, "exclude"),
+    };
+
+    match tree::tree(Path::new(path), &options) {
+        Ok(tree) => Ok(ContentBuilder::text(tree.to_string())),
+        Err(e) => Ok(ContentBuilder::error(format!(
+            "Failed to build tree of {}: {}",
+            path, e
+        ))),
+    }
+}
+
+/// Search file contents
+fn grep_files(args: &Value) -> Result<CallToolResult, Error> {
+    let pattern = args
+        .get("pattern")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| Error::msg("pattern parameter required for grep operation"))?;
+    let path = args.get("path").and_then(|v| v.as_str()).unwrap_or(".");
+
+    let case_insensitive = args
+        .get("case_insensitive")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let regex = match regex::RegexBuilder::new(pattern)
+        .case_insensitive(case_insensitive)
+        .build()
+    {
+        Ok(regex) => regex,
+        Err(e) => return Ok(ContentBuilder::error(format!("Invalid pattern: {}", e))),
+    };
+
+    let options = grep::GrepOptions {
+        regex,
+        include: glob_list(args, "include"),
+        exclude: glob_list(args, "exclude"),
+        max_file_size: args
+            .get("max_file_size")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(grep::DEFAULT_MAX_FILE_SIZE),
+        context: args
+            .get("context")
+            .and_then(|v| v.as_u64())
+            .map_or(0, |n| n.min(20) as usize),
+        max_matches: args
+            .get("max_matches")
+            .and_then(|v| v.as_u64())
+            .map_or(grep::DEFAULT_MAX_MATCHES, |n| n.max(1) as usize),
+    };
+
+    match grep::grep(Path::new(path), &options) {
+        Ok(result) => Ok(ContentBuilder::text(result.to_string())),
+        Err(e) => Ok(ContentBuilder::error(format!(
+            "Failed to grep {}: {}",
+            path, e
+        ))),
+    }
+}
+
+/// Globs given as an array of strings under `name`
+fn glob_list(args: &Value, name: &str) -> Vec<glob::Glob> {
+    args.get(name)
+        .and_then(|v| v.as_array())
+        .map(|patterns| {
+            patterns
+                .iter()
+                .filter_map(|p| p.as_str())
+                .map(glob::Glob::new)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 /// Search for files
 fn search_files(args: &Value) -> Result<CallToolResult, Error> {
     let pattern = args
@@ -348,22 +823,232 @@ fn get_file_info(args: &Value) -> Result<CallToolResult, Error> {
     }
 }
 
+/// Change permission bits or the read-only flag
+fn set_permissions(args: &Value) -> Result<CallToolResult, Error> {
+    let path = args
+        .get("path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| Error::msg("path parameter required for set_permissions operation"))?;
+    let mode = args.get("mode").and_then(|v| v.as_str());
+    let mode = match mode.map(attrs::parse_mode) {
+        Some(Ok(mode)) => Some(mode),
+        Some(Err(e)) => return Ok(ContentBuilder::error(e)),
+        None => None,
+    };
+    let readonly = args.get("readonly").and_then(|v| v.as_bool());
+    if mode.is_none() && readonly.is_none() {
+        return Ok(ContentBuilder::error(
+            "set_permissions needs mode, readonly or both",
+        ));
+    }
+
+    match attrs::set_permissions(Path::new(path), mode, readonly) {
+        Ok(result) => Ok(ContentBuilder::text(result.to_string())),
+        Err(e) => Ok(ContentBuilder::error(format!(
+            "Failed to set permissions on {}: {}",
+            path, e
+        ))),
+    }
+}
+
+/// Create a file if missing and update its times
+fn touch_file(args: &Value) -> Result<CallToolResult, Error> {
+    let path = args
+        .get("path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| Error::msg("path parameter required for touch operation"))?;
+    let time = match args.get("modified").map(attrs::parse_time) {
+        Some(Ok(time)) => time,
+        Some(Err(e)) => return Ok(ContentBuilder::error(e)),
+        None => SystemTime::now(),
+    };
+
+    match attrs::touch(Path::new(path), time) {
+        Ok(result) => Ok(ContentBuilder::text(result.to_string())),
+        Err(e) => Ok(ContentBuilder::error(format!(
+            "Failed to touch {}: {}",
+            path, e
+        ))),
+    }
+}
+
+/// Set modification and access times
+fn set_times(args: &Value) -> Result<CallToolResult, Error> {
+    let path = args
+        .get("path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| Error::msg("path parameter required for set_times operation"))?;
+    let time = |name: &str| args.get(name).map(attrs::parse_time).transpose();
+    let (modified, accessed) = match (time("modified"), time("accessed")) {
+        (Ok(modified), Ok(accessed)) => (modified, accessed),
+        (Err(e), _) | (_, Err(e)) => return Ok(ContentBuilder::error(e)),
+    };
+
+    match attrs::set_times(Path::new(path), modified, accessed) {
+        Ok(result) => Ok(ContentBuilder::text(result.to_string())),
+        Err(e) => Ok(ContentBuilder::error(format!(
+            "Failed to set times on {}: {}",
+            path, e
+        ))),
+    }
+}
+
+/// Hash a file or every file under a directory
+fn checksum_files(args: &Value) -> Result<CallToolResult, Error> {
+    let path = args
+        .get("path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| Error::msg("path parameter required for checksum operation"))?;
+    let algorithm = match checksum::Algorithm::from_args(args) {
+        Ok(algorithm) => algorithm,
+        Err(e) => return Ok(ContentBuilder::error(e)),
+    };
+    let options = checksum::WalkOptions {
+        include: glob_list(args, "include"),
+        exclude: glob_list(args, "exclude"),
+        min_size: 0,
+    };
+
+    match checksum::checksum(Path::new(path), algorithm, &options) {
+        Ok(result) => Ok(ContentBuilder::text(result.to_string())),
+        Err(e) => Ok(ContentBuilder::error(format!(
+            "Failed to checksum {}: {}",
+            path, e
+        ))),
+    }
+}
+
+/// Group identical files under a directory
+fn find_duplicates(args: &Value) -> Result<CallToolResult, Error> {
+    let path = args.get("path").and_then(|v| v.as_str()).unwrap_or(".");
+    let algorithm = match checksum::Algorithm::from_args(args) {
+        Ok(algorithm) => algorithm,
+        Err(e) => return Ok(ContentBuilder::error(e)),
+    };
+    // Empty files are all "identical"; skip them unless asked
+    let options = checksum::WalkOptions {
+        include: glob_list(args, "include"),
+        exclude: glob_list(args, "exclude"),
+        min_size: args.get("min_size").and_then(|v| v.as_u64()).unwrap_or(1),
+    };
+
+    match checksum::find_duplicates(Path::new(path), algorithm, &options) {
+        Ok(result) => Ok(ContentBuilder::text(result.to_string())),
+        Err(e) => Ok(ContentBuilder::error(format!(
+            "Failed to find duplicates in {}: {}",
+            path, e
+        ))),
+    }
+}
+
+/// Start recording changes under a path
+fn watch_path(args: &Value) -> Result<CallToolResult, Error> {
+    let path = args
+        .get("path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| Error::msg("path parameter required for watch operation"))?;
+
+    match watch::watch(
+        Path::new(path),
+        glob_list(args, "include"),
+        glob_list(args, "exclude"),
+    ) {
+        Ok(result) => Ok(ContentBuilder::text(result.to_string())),
+        Err(e) => Ok(ContentBuilder::error(format!(
+            "Failed to watch {}: {}",
+            path, e
+        ))),
+    }
+}
+
+/// Changes on watched paths since the last poll
+fn poll_changes(args: &Value) -> Result<CallToolResult, Error> {
+    let path = args.get("path").and_then(|v| v.as_str()).map(Path::new);
+    let since = args.get("since").and_then(|v| v.as_u64());
+
+    match watch::poll(path, since) {
+        Ok(result) => Ok(ContentBuilder::text(result.to_string())),
+        Err(e) => Ok(ContentBuilder::error(format!(
+            "Failed to poll changes: {}",
+            e
+        ))),
+    }
+}
+
+/// Stop recording changes under a path
+fn unwatch_path(args: &Value) -> Result<CallToolResult, Error> {
+    let path = args.get("path").and_then(|v| v.as_str()).map(Path::new);
+
+    match watch::unwatch(path) {
+        Ok(result) => Ok(ContentBuilder::text(result.to_string())),
+        Err(e) => Ok(ContentBuilder::error(format!("Failed to unwatch: {}", e))),
+    }
+}
+
+/// Detect file type and encoding
+fn detect_file(args: &Value) -> Result<CallToolResult, Error> {
+    let path = args
+        .get("path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| Error::msg("path parameter required for detect operation"))?;
+
+    match detect::detect(Path::new(path)) {
+        Ok(info) => Ok(ContentBuilder::text(info.to_string())),
+        Err(e) => Ok(ContentBuilder::error(format!(
+            "Failed to inspect {}: {}",
+            path, e
+        ))),
+    }
+}
+
+/// Apply several file changes as one transaction
+fn apply_changes(args: &Value) -> Result<CallToolResult, Error> {
+    let changes = args
+        .get("changes")
+        .cloned()
+        .ok_or_else(|| Error::msg("changes array required for apply_changes operation"))?;
+    let changes: Vec<apply::Change> = match serde_json::from_value(changes) {
+        Ok(changes) => changes,
+        Err(e) => return Ok(ContentBuilder::error(format!("Invalid changes: {}", e))),
+    };
+
+    match apply::apply_changes(changes) {
+        Ok(summary) => Ok(ContentBuilder::text(summary.to_string())),
+        Err(e) => Ok(ContentBuilder::error(format!(
+            "apply_changes failed: {}",
+            e
+        ))),
+    }
+}
+
+/// Prompt for listing a directory
+struct ListDirectoryPrompt;
+
+impl McpPrompt for ListDirectoryPrompt {
+    const NAME: &'static str = "list_directory";
+
+    fn prompt(builder: PromptBuilder) -> PromptBuilder {
+        builder
+            .description("List the contents of a directory")
+            .optional_arg(
+                "path",
+                "Directory to list (defaults to the current directory)",
+            )
+            .template("List the contents of '{{ path | default(\".\") }}'.")
+    }
+}
+
 /// Create the plugin instance
 #[allow(dead_code)]
 fn plugin() -> McpPlugin<Ready> {
     mcp_plugin("fs")
         .description("Comprehensive file system operations and directory management")
+        // Watches keep their snapshots in this instance's memory
+        .capability("stateful")
         .tool::<FsTool>()
+        .prompt::<ListDirectoryPrompt>()
         .serve()
 }
 
-// MCP Protocol Implementation
-#[allow(dead_code)]
-pub(crate) fn call(input: CallToolRequest) -> Result<CallToolResult, Error> {
-    plugin().call(input)
-}
-
-#[allow(dead_code)]
-pub(crate) fn describe() -> Result<ListToolsResult, Error> {
-    plugin().describe()
-}
+// Generate standard MCP entry points
+sweetmcp_plugin_builder::generate_mcp_functions!(plugin);
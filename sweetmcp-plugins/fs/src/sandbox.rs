@@ -0,0 +1,158 @@
+//! Confinement of every path argument to the configured roots
+//!
+//! The `allowed_roots` config key holds a JSON array of directories, or a
+//! plain list separated like `PATH`. Each path argument is resolved the way
+//! the filesystem would resolve it, following symlinks and `..`, and must end
+//! up inside one of the roots. Paths that don't exist yet resolve through
+//! their nearest existing ancestor; the missing tail may not contain `..` or
+//! dangling symlinks, so creating a file can't step outside either. Without
+//! `allowed_roots` every path is allowed.
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use extism_pdk::config;
+use serde_json::Value;
+
+/// Operations whose `path` defaults to the working directory
+const DEFAULT_TO_CWD: &[&str] = &["list", "tree", "grep", "search", "find_duplicates"];
+
+/// Canonical allowed roots, or `None` when the plugin is unrestricted
+pub fn allowed_roots() -> Result<Option<Vec<PathBuf>>, String> {
+    let Ok(Some(raw)) = config::get("allowed_roots") else {
+        return Ok(None);
+    };
+    let entries: Vec<PathBuf> = match serde_json::from_str::<Vec<String>>(&raw) {
+        Ok(list) => list.into_iter().map(PathBuf::from).collect(),
+        Err(_) => std::env::split_paths(&raw).collect(),
+    };
+    let roots: Vec<PathBuf> = entries
+        .iter()
+        .filter(|root| !root.as_os_str().is_empty())
+        .filter_map(|root| fs::canonicalize(root).ok())
+        .collect();
+    if roots.is_empty() {
+        // Misconfigured roots deny everything rather than nothing
+        return Err(format!(
+            "none of the allowed_roots exist: {}",
+            entries
+                .iter()
+                .map(|root| root.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    Ok(Some(roots))
+}
+
+/// Check every path argument of `operation` against the allowed roots
+pub fn check_args(operation: &str, args: &Value) -> Result<(), String> {
+    let Some(roots) = allowed_roots()? else {
+        return Ok(());
+    };
+    let arg = |name: &str| args.get(name).and_then(|v| v.as_str());
+
+    let mut paths: Vec<&str> = Vec::new();
+    match arg("path") {
+        Some(path) => paths.push(path),
+        None if DEFAULT_TO_CWD.contains(&operation) => paths.push("."),
+        None => {}
+    }
+    paths.extend(arg("destination"));
+    // `paths` holds strings, `changes` objects with a `path`
+    let items = ["paths", "changes"]
+        .iter()
+        .filter_map(|key| args.get(*key)?.as_array())
+        .flatten();
+    for item in items {
+        paths.extend(item.as_str().or_else(|| item.get("path")?.as_str()));
+    }
+
+    for path in paths {
+        // Deletes act on a symlink itself, never on what it points to
+        let resolved = if operation == "delete" {
+            resolve_entry(Path::new(path))?
+        } else {
+            resolve(Path::new(path))?
+        };
+        if !roots.iter().any(|root| resolved.starts_with(root)) {
+            return Err(format!(
+                "access denied: {} resolves to {}, outside the allowed roots",
+                path,
+                resolved.display()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Whether `path`, after following symlinks, lies inside the allowed roots.
+/// Used by directory walks to avoid reading through links that lead out.
+pub fn allows(path: &Path) -> bool {
+    match allowed_roots() {
+        Ok(None) => true,
+        Ok(Some(roots)) => fs::canonicalize(path)
+            .is_ok_and(|resolved| roots.iter().any(|root| resolved.starts_with(root))),
+        Err(_) => false,
+    }
+}
+
+/// Where `path` leads once symlinks and `..` are followed
+fn resolve(path: &Path) -> Result<PathBuf, String> {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()
+            .map_err(|e| format!("cannot resolve {}: {}", path.display(), e))?
+            .join(path)
+    };
+
+    // The nearest ancestor that exists, resolved by the filesystem
+    let mut existing = absolute.as_path();
+    let resolved = loop {
+        match fs::canonicalize(existing) {
+            Ok(resolved) => break resolved,
+            Err(_) => match existing.parent() {
+                Some(parent) => existing = parent,
+                None => return Err(format!("cannot resolve {}", path.display())),
+            },
+        }
+    };
+
+    // The missing tail is joined lexically, so it must be plain names
+    let tail = absolute.strip_prefix(existing).unwrap_or(Path::new(""));
+    let mut resolved = resolved;
+    for component in tail.components() {
+        match component {
+            Component::Normal(name) => {
+                resolved.push(name);
+                if fs::symlink_metadata(&resolved).is_ok() {
+                    return Err(format!(
+                        "access denied: {} goes through a dangling symlink",
+                        path.display()
+                    ));
+                }
+            }
+            Component::CurDir => {}
+            _ => {
+                return Err(format!(
+                    "access denied: {} uses '..' below a missing directory",
+                    path.display()
+                ));
+            }
+        }
+    }
+    Ok(resolved)
+}
+
+/// Where the directory entry `path` itself lives, without following a final
+/// symlink
+fn resolve_entry(path: &Path) -> Result<PathBuf, String> {
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) if !parent.as_os_str().is_empty() => {
+            Ok(resolve(parent)?.join(name))
+        }
+        (_, Some(name)) => Ok(resolve(Path::new("."))?.join(name)),
+        _ => resolve(path),
+    }
+}
@@ -0,0 +1,170 @@
+//! Recursive directory trees
+//!
+//! Walks a directory up to a maximum depth and returns it as nested JSON.
+//! Exclude patterns prune files and whole directories; include patterns keep
+//! only matching files, plus the directories leading to them. Symlinks are
+//! listed but never followed, so links cannot send the walk in circles.
+
+use std::fs;
+use std::path::Path;
+
+use serde_json::{Value, json};
+
+use crate::glob::{Glob, any_matches};
+
+/// Depth used when the caller gives none
+pub const DEFAULT_MAX_DEPTH: usize = 3;
+
+/// Entries returned before the walk stops, to keep results a sensible size
+const MAX_ENTRIES: usize = 5000;
+
+pub struct TreeOptions {
+    pub max_depth: usize,
+    pub include: Vec<Glob>,
+    pub exclude: Vec<Glob>,
+}
+
+/// Totals across the whole walk
+#[derive(Default)]
+struct Walk {
+    files: usize,
+    dirs: usize,
+    entries: usize,
+    truncated: bool,
+}
+
+/// Build the tree rooted at `root`
+pub fn tree(root: &Path, options: &TreeOptions) -> Result<Value, String> {
+    let metadata = fs::metadata(root).map_err(|e| e.to_string())?;
+    if !metadata.is_dir() {
+        return Err("not a directory".to_string());
+    }
+
+    let mut walk = Walk::default();
+    let node = walk_dir(root, "", 0, options, &mut walk)
+        .unwrap_or_else(|| dir_node(root, Vec::new(), 0, 0, false));
+
+    Ok(json!({
+        "path": root.to_string_lossy(),
+        "tree": node,
+        "total_files": walk.files,
+        "total_dirs": walk.dirs,
+        "max_depth": options.max_depth,
+        "truncated": walk.truncated
+    }))
+}
+
+/// Node for the directory `dir`, whose path relative to the root is
+/// `relative`, or `None` when include patterns left it with nothing to show
+fn walk_dir(
+    dir: &Path,
+    relative: &str,
+    depth: usize,
+    options: &TreeOptions,
+    walk: &mut Walk,
+) -> Option<Value> {
+    let mut entries = match fs::read_dir(dir) {
+        Ok(entries) => entries.flatten().collect::<Vec<_>>(),
+        Err(e) => {
+            return Some(json!({
+                "name": name_of(dir),
+                "type": "dir",
+                "error": e.to_string()
+            }));
+        }
+    };
+    entries.sort_by_key(|entry| entry.file_name());
+
+    let mut children = Vec::new();
+    let (mut files, mut dirs) = (0, 0);
+    let mut depth_limited = false;
+
+    for entry in entries {
+        if walk.entries >= MAX_ENTRIES {
+            walk.truncated = true;
+            break;
+        }
+
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let child_relative = if relative.is_empty() {
+            name.clone()
+        } else {
+            format!("{}/{}", relative, name)
+        };
+        if any_matches(&options.exclude, &child_relative) {
+            continue;
+        }
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+
+        if file_type.is_dir() {
+            if depth + 1 >= options.max_depth {
+                // Listed but not descended into
+                depth_limited = true;
+                if options.include.is_empty() {
+                    dirs += 1;
+                    walk.dirs += 1;
+                    walk.entries += 1;
+                    children.push(json!({"name": name, "type": "dir", "truncated": true}));
+                }
+                continue;
+            }
+            if let Some(child) = walk_dir(&entry.path(), &child_relative, depth + 1, options, walk)
+            {
+                dirs += 1;
+                walk.dirs += 1;
+                walk.entries += 1;
+                children.push(child);
+            }
+        } else {
+            if !options.include.is_empty() && !any_matches(&options.include, &child_relative) {
+                continue;
+            }
+            files += 1;
+            walk.files += 1;
+            walk.entries += 1;
+            let mut node = json!({
+                "name": name,
+                "type": if file_type.is_symlink() { "symlink" } else { "file" }
+            });
+            if let Ok(metadata) = entry.metadata() {
+                if metadata.is_file() {
+                    node["size"] = metadata.len().into();
+                }
+            }
+            if file_type.is_symlink() {
+                if let Ok(target) = fs::read_link(entry.path()) {
+                    node["target"] = target.to_string_lossy().into_owned().into();
+                }
+            }
+            children.push(node);
+        }
+    }
+
+    // With include patterns, only directories leading to matches are shown
+    if depth > 0 && !options.include.is_empty() && children.is_empty() && !depth_limited {
+        return None;
+    }
+    Some(dir_node(dir, children, files, dirs, depth_limited))
+}
+
+fn dir_node(dir: &Path, children: Vec<Value>, files: usize, dirs: usize, truncated: bool) -> Value {
+    let mut node = json!({
+        "name": name_of(dir),
+        "type": "dir",
+        "files": files,
+        "dirs": dirs,
+        "children": children
+    });
+    if truncated {
+        node["truncated"] = true.into();
+    }
+    node
+}
+
+fn name_of(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string_lossy().into_owned())
+}
@@ -0,0 +1,279 @@
+//! Change journals for watched paths
+//!
+//! A WASM plugin gets no file system notifications, so a watch is a snapshot
+//! of the type, size and modification time of everything under a path. Each
+//! poll rescans the watched paths, diffs them against their snapshots and
+//! appends created, modified and deleted events to the watch's journal; the
+//! poll returns the events after the caller's cursor. Watches live in plugin
+//! memory: they last as long as the plugin instance and are not shared
+//! between pooled instances.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde_json::{Value, json};
+
+use crate::glob::{Glob, any_matches};
+
+/// Most entries tracked by one watch
+const MAX_ENTRIES: usize = 20_000;
+
+/// Most events kept in one journal; older ones are dropped
+const MAX_JOURNAL: usize = 10_000;
+
+thread_local! {
+    static WATCHES: RefCell<BTreeMap<PathBuf, Watch>> = RefCell::default();
+}
+
+/// What a snapshot records about one entry
+#[derive(Clone, PartialEq)]
+struct Entry {
+    is_dir: bool,
+    size: u64,
+    modified: Option<SystemTime>,
+}
+
+struct Event {
+    seq: u64,
+    kind: &'static str,
+    path: String,
+    is_dir: bool,
+    size: Option<u64>,
+}
+
+impl Event {
+    fn to_json(&self) -> Value {
+        json!({
+            "seq": self.seq,
+            "kind": self.kind,
+            "path": self.path,
+            "is_dir": self.is_dir,
+            "size": self.size
+        })
+    }
+}
+
+struct Watch {
+    include: Vec<Glob>,
+    exclude: Vec<Glob>,
+    snapshot: BTreeMap<PathBuf, Entry>,
+    truncated: bool,
+    journal: VecDeque<Event>,
+    /// Sequence number of the last event recorded
+    last_seq: u64,
+    /// Sequence number of the last event returned by a poll
+    cursor: u64,
+}
+
+impl Watch {
+    /// Rescan and journal the differences from the last snapshot
+    fn refresh(&mut self, root: &Path) {
+        let (snapshot, truncated) = scan(root, &self.include, &self.exclude);
+        let old = std::mem::take(&mut self.snapshot);
+
+        for (path, entry) in &snapshot {
+            match old.get(path) {
+                None => self.record("created", path, entry),
+                // A directory's mtime moves whenever its children do; those
+                // children get their own events
+                Some(before) if entry.is_dir && before.is_dir => {}
+                Some(before) if before != entry => self.record("modified", path, entry),
+                Some(_) => {}
+            }
+        }
+        for (path, entry) in &old {
+            if !snapshot.contains_key(path) {
+                self.record("deleted", path, entry);
+            }
+        }
+
+        self.snapshot = snapshot;
+        self.truncated = truncated;
+    }
+
+    fn record(&mut self, kind: &'static str, path: &Path, entry: &Entry) {
+        self.last_seq += 1;
+        self.journal.push_back(Event {
+            seq: self.last_seq,
+            kind,
+            path: path.to_string_lossy().into_owned(),
+            is_dir: entry.is_dir,
+            size: (kind != "deleted" && !entry.is_dir).then_some(entry.size),
+        });
+        if self.journal.len() > MAX_JOURNAL {
+            self.journal.pop_front();
+        }
+    }
+}
+
+/// Key a watch by its path, so `src` and `src/` name the same watch
+fn key(path: &Path) -> PathBuf {
+    path.components().collect()
+}
+
+/// Start watching `root`, replacing any earlier watch on it
+pub fn watch(root: &Path, include: Vec<Glob>, exclude: Vec<Glob>) -> Result<Value, String> {
+    fs::metadata(root).map_err(|e| e.to_string())?;
+    let root = key(root);
+    let (snapshot, truncated) = scan(&root, &include, &exclude);
+    let entries = snapshot.len();
+    let replaced = WATCHES.with(|watches| {
+        watches
+            .borrow_mut()
+            .insert(
+                root.clone(),
+                Watch {
+                    include,
+                    exclude,
+                    snapshot,
+                    truncated,
+                    journal: VecDeque::new(),
+                    last_seq: 0,
+                    cursor: 0,
+                },
+            )
+            .is_some()
+    });
+
+    Ok(json!({
+        "path": root.to_string_lossy(),
+        "entries": entries,
+        "replaced": replaced,
+        "truncated": truncated
+    }))
+}
+
+/// Events on `root`, or on every watch, since the last poll or since `since`
+pub fn poll(root: Option<&Path>, since: Option<u64>) -> Result<Value, String> {
+    WATCHES.with(|watches| {
+        let mut watches = watches.borrow_mut();
+        let selected: Vec<PathBuf> = match root {
+            Some(root) if watches.contains_key(&key(root)) => vec![key(root)],
+            Some(root) => return Err(format!("{} is not being watched", root.display())),
+            None if watches.is_empty() => return Err("no paths are being watched".to_string()),
+            None => watches.keys().cloned().collect(),
+        };
+
+        let mut results = Vec::new();
+        for path in selected {
+            let watch = watches.get_mut(&path).expect("selected from the map");
+            watch.refresh(&path);
+
+            let from = since.unwrap_or(watch.cursor);
+            let events: Vec<Value> = watch
+                .journal
+                .iter()
+                .filter(|event| event.seq > from)
+                .map(Event::to_json)
+                .collect();
+            // Events that fell out of the journal before anyone read them
+            let oldest = watch.journal.front().map_or(watch.last_seq + 1, |e| e.seq);
+            let missed = oldest.saturating_sub(from + 1);
+            watch.cursor = watch.last_seq;
+
+            results.push(json!({
+                "path": path.to_string_lossy(),
+                "events": events,
+                "cursor": watch.last_seq,
+                "missed": missed,
+                "truncated": watch.truncated
+            }));
+        }
+        Ok(json!({ "watches": results }))
+    })
+}
+
+/// Stop watching `root`, or everything
+pub fn unwatch(root: Option<&Path>) -> Result<Value, String> {
+    WATCHES.with(|watches| {
+        let mut watches = watches.borrow_mut();
+        let removed: Vec<String> = match root {
+            Some(root) => match watches.remove(&key(root)) {
+                Some(_) => vec![root.to_string_lossy().into_owned()],
+                None => return Err(format!("{} is not being watched", root.display())),
+            },
+            None => std::mem::take(&mut *watches)
+                .into_keys()
+                .map(|path| path.to_string_lossy().into_owned())
+                .collect(),
+        };
+        Ok(json!({ "removed": removed }))
+    })
+}
+
+/// Snapshot of `root` and everything under it that passes the filters
+fn scan(root: &Path, include: &[Glob], exclude: &[Glob]) -> (BTreeMap<PathBuf, Entry>, bool) {
+    let mut snapshot = BTreeMap::new();
+    let mut truncated = false;
+    match fs::symlink_metadata(root) {
+        Ok(metadata) if metadata.is_dir() => {
+            scan_dir(root, "", include, exclude, &mut snapshot, &mut truncated)
+        }
+        Ok(metadata) => {
+            snapshot.insert(root.to_path_buf(), entry(&metadata));
+        }
+        // A deleted root leaves an empty snapshot, so everything reads as deleted
+        Err(_) => {}
+    }
+    (snapshot, truncated)
+}
+
+fn scan_dir(
+    dir: &Path,
+    relative: &str,
+    include: &[Glob],
+    exclude: &[Glob],
+    snapshot: &mut BTreeMap<PathBuf, Entry>,
+    truncated: &mut bool,
+) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for dir_entry in entries.flatten() {
+        if *truncated {
+            return;
+        }
+        let name = dir_entry.file_name().to_string_lossy().into_owned();
+        let child_relative = if relative.is_empty() {
+            name
+        } else {
+            format!("{}/{}", relative, name)
+        };
+        if any_matches(exclude, &child_relative) {
+            continue;
+        }
+        // Symlinks are recorded as themselves, never followed
+        let Ok(metadata) = dir_entry.path().symlink_metadata() else {
+            continue;
+        };
+        if !metadata.is_dir() && !include.is_empty() && !any_matches(include, &child_relative) {
+            continue;
+        }
+        if snapshot.len() == MAX_ENTRIES {
+            *truncated = true;
+            return;
+        }
+        snapshot.insert(dir_entry.path(), entry(&metadata));
+        if metadata.is_dir() {
+            scan_dir(
+                &dir_entry.path(),
+                &child_relative,
+                include,
+                exclude,
+                snapshot,
+                truncated,
+            );
+        }
+    }
+}
+
+fn entry(metadata: &fs::Metadata) -> Entry {
+    Entry {
+        is_dir: metadata.is_dir(),
+        size: metadata.len(),
+        modified: metadata.modified().ok(),
+    }
+}
@@ -1,6 +1,8 @@
 use extism_pdk::*;
 use serde_json::{Value, json};
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::time::Duration;
+use sweetmcp_plugin_builder::http;
 use sweetmcp_plugin_builder::prelude::*;
 use sweetmcp_plugin_builder::{CallToolRequest, CallToolResult, ListToolsResult, Ready};
 
@@ -9,6 +11,7 @@ struct IpTool;
 
 impl McpTool for IpTool {
     const NAME: &'static str = "ip";
+    type Args = Value;
 
     fn description(builder: DescriptionBuilder) -> DescriptionBuilder {
         builder
@@ -28,6 +31,11 @@ impl McpTool for IpTool {
             .operation("create_ipv4", "Create IPv4 address from octets and analyze properties")
             .operation("create_ipv6", "Create IPv6 address from segments and analyze properties")
             .operation("cidr_contains", "Check if an IP address is within a CIDR range")
+            .example(json!({"name": "validate_ip", "ip": "2001:db8::1"}), "Validate an IPv6 address")
+            .example(
+                json!({"name": "cidr_contains", "ip": "10.0.3.7", "cidr": "10.0.0.0/16"}),
+                "Check subnet membership",
+            )
     }
 
     fn schema(builder: SchemaBuilder) -> Value {
@@ -79,13 +87,29 @@ impl McpTool for IpTool {
     }
 }
 
+/// Cloudflare's trace endpoint reports the caller's address as `ip=...`
+const PUBLIC_IP_URL: &str = "https://1.1.1.1/cdn-cgi/trace";
+
 /// Get public IP address
 fn get_public_ip() -> Result<CallToolResult, Error> {
-    // For now, return a placeholder - full HTTP requests would need more setup
+    let trace = http::get(PUBLIC_IP_URL)
+        .timeout(Duration::from_secs(10))
+        .max_bytes(4096)
+        .send()?
+        .error_for_status()?
+        .text();
+
+    let ip = trace
+        .lines()
+        .find_map(|line| line.strip_prefix("ip="))
+        .and_then(|ip| ip.trim().parse::<IpAddr>().ok())
+        .ok_or_else(|| Error::msg("Cloudflare trace did not contain an IP address"))?;
+
     Ok(ContentBuilder::text(
         json!({
-            "message": "Public IP detection would require HTTP request to external service",
-            "note": "This feature is not yet implemented"
+            "ip": ip.to_string(),
+            "type": if ip.is_ipv4() { "IPv4" } else { "IPv6" },
+            "source": PUBLIC_IP_URL
         })
         .to_string(),
     ))
@@ -0,0 +1,429 @@
+//! Exact arithmetic on arbitrary-precision rationals
+//!
+//! Expressions are parsed into big rationals, so `0.1 + 0.2` is exactly
+//! `3/10` and 40-digit products carry every digit. Decimal literals are read
+//! exactly too. Operators are `+ - * / % ^` (also `**`) and postfix `!`,
+//! with the functions `abs`, `floor`, `ceil`, `round`, `min`, `max`, `gcd`
+//! and `lcm`. Powers need integer exponents, since anything else is
+//! irrational in general.
+
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+/// Largest result, in bits of numerator plus denominator
+const MAX_RESULT_BITS: u64 = 1_000_000;
+
+/// Largest argument to `!`
+const MAX_FACTORIAL: u64 = 10_000;
+
+/// Largest power of ten in a literal such as `1e300`
+const MAX_LITERAL_EXPONENT: i64 = 10_000;
+
+/// Most decimal places a result is written with
+pub const MAX_PRECISION: usize = 1_000;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Num(BigRational),
+    Ident(String),
+    Op(char),
+    LParen,
+    RParen,
+    Comma,
+}
+
+/// Evaluate `expression` exactly
+pub fn evaluate(expression: &str) -> Result<BigRational, String> {
+    let tokens = tokenize(expression)?;
+    if tokens.is_empty() {
+        return Err("expression is empty".to_string());
+    }
+    let mut parser = Parser { tokens, pos: 0 };
+    let value = parser.expr()?;
+    match parser.tokens.get(parser.pos) {
+        None => Ok(value),
+        Some(token) => Err(format!(
+            "unexpected {} after the expression",
+            describe(token)
+        )),
+    }
+}
+
+/// Write `value` in decimal with at most `precision` places, rounding half
+/// away from zero. The flag says whether that is the exact value.
+pub fn to_decimal(value: &BigRational, precision: usize) -> (String, bool) {
+    let precision = precision.min(MAX_PRECISION);
+    let scale = BigInt::from(10).pow(precision as u32);
+    let scaled = value.abs() * BigRational::from_integer(scale);
+    let exact = scaled.is_integer();
+    let digits = scaled.round().to_integer().to_string();
+
+    let mut text = if precision == 0 {
+        digits
+    } else {
+        let digits = format!("{:0>width$}", digits, width = precision + 1);
+        let (int, frac) = digits.split_at(digits.len() - precision);
+        let frac = frac.trim_end_matches('0');
+        if frac.is_empty() {
+            int.to_string()
+        } else {
+            format!("{}.{}", int, frac)
+        }
+    };
+    if value.is_negative() && text.chars().any(|c| c.is_ascii_digit() && c != '0') {
+        text.insert(0, '-');
+    }
+    (text, exact)
+}
+
+/// `value` as an integer or `numerator/denominator`
+pub fn to_fraction(value: &BigRational) -> String {
+    if value.is_integer() {
+        value.numer().to_string()
+    } else {
+        format!("{}/{}", value.numer(), value.denom())
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '0'..='9' | '.' => {
+                let start = i;
+                while i < chars.len()
+                    && (chars[i].is_ascii_digit() || matches!(chars[i], '.' | '_'))
+                {
+                    i += 1;
+                }
+                // Exponent, only when digits follow
+                if i < chars.len() && matches!(chars[i], 'e' | 'E') {
+                    let mut j = i + 1;
+                    if j < chars.len() && matches!(chars[j], '+' | '-') {
+                        j += 1;
+                    }
+                    if j < chars.len() && chars[j].is_ascii_digit() {
+                        while j < chars.len() && chars[j].is_ascii_digit() {
+                            j += 1;
+                        }
+                        i = j;
+                    }
+                }
+                let literal: String = chars[start..i].iter().collect();
+                tokens.push(Token::Num(parse_decimal(&literal)?));
+            }
+            'a'..='z' | 'A'..='Z' => {
+                let start = i;
+                while i < chars.len() && chars[i].is_ascii_alphanumeric() {
+                    i += 1;
+                }
+                tokens.push(Token::Ident(
+                    chars[start..i].iter().collect::<String>().to_lowercase(),
+                ));
+            }
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                tokens.push(Token::Op('^'));
+                i += 2;
+            }
+            '+' | '-' | '*' | '/' | '%' | '^' | '!' => {
+                tokens.push(Token::Op(c));
+                i += 1;
+            }
+            '−' => {
+                tokens.push(Token::Op('-'));
+                i += 1;
+            }
+            '×' | '·' => {
+                tokens.push(Token::Op('*'));
+                i += 1;
+            }
+            '÷' => {
+                tokens.push(Token::Op('/'));
+                i += 1;
+            }
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            other => return Err(format!("unexpected character '{}'", other)),
+        }
+    }
+    Ok(tokens)
+}
+
+/// Exact value of a decimal literal such as `-12.5`, `1_000` or `6.02e23`
+pub fn parse_decimal(literal: &str) -> Result<BigRational, String> {
+    let invalid = || format!("invalid number '{}'", literal);
+    let text = literal.trim().replace('_', "");
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest.to_string()),
+        None => (false, text.strip_prefix('+').unwrap_or(&text).to_string()),
+    };
+
+    let (mantissa, exponent) = match text.find(['e', 'E']) {
+        Some(at) => (
+            &text[..at],
+            text[at + 1..].parse::<i64>().map_err(|_| invalid())?,
+        ),
+        None => (text.as_str(), 0),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    if int.is_empty() && frac.is_empty()
+        || !int.chars().chain(frac.chars()).all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+
+    let exponent = exponent - frac.len() as i64;
+    if exponent.abs() > MAX_LITERAL_EXPONENT {
+        return Err(format!("exponent of '{}' is too large", literal));
+    }
+    let digits: BigInt = format!("{}{}", int, frac).parse().map_err(|_| invalid())?;
+    let power = BigInt::from(10).pow(exponent.unsigned_abs() as u32);
+    let mut value = if exponent >= 0 {
+        BigRational::from_integer(digits * power)
+    } else {
+        BigRational::new(digits, power)
+    };
+    if negative {
+        value = -value;
+    }
+    Ok(value)
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Num(n) => format!("number {}", to_fraction(n)),
+        Token::Ident(name) => format!("'{}'", name),
+        Token::Op(op) => format!("'{}'", op),
+        Token::LParen => "'('".to_string(),
+        Token::RParen => "')'".to_string(),
+        Token::Comma => "','".to_string(),
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn eat_op(&mut self, ops: &[char]) -> Option<char> {
+        match self.peek() {
+            Some(Token::Op(op)) if ops.contains(op) => {
+                let op = *op;
+                self.pos += 1;
+                Some(op)
+            }
+            _ => None,
+        }
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.peek() {
+            Some(token) if *token == expected => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(token) => Err(format!(
+                "expected {} but found {}",
+                describe(&expected),
+                describe(token)
+            )),
+            None => Err(format!("expected {} at the end", describe(&expected))),
+        }
+    }
+
+    fn expr(&mut self) -> Result<BigRational, String> {
+        let mut value = self.term()?;
+        while let Some(op) = self.eat_op(&['+', '-']) {
+            let rhs = self.term()?;
+            value = if op == '+' { value + rhs } else { value - rhs };
+            check_size(&value)?;
+        }
+        Ok(value)
+    }
+
+    fn term(&mut self) -> Result<BigRational, String> {
+        let mut value = self.unary()?;
+        while let Some(op) = self.eat_op(&['*', '/', '%']) {
+            let rhs = self.unary()?;
+            value = match op {
+                '*' => value * rhs,
+                _ if rhs.is_zero() => return Err("division by zero".to_string()),
+                '/' => value / rhs,
+                _ => {
+                    let quotient = (&value / &rhs).floor();
+                    value - rhs * quotient
+                }
+            };
+            check_size(&value)?;
+        }
+        Ok(value)
+    }
+
+    fn unary(&mut self) -> Result<BigRational, String> {
+        match self.eat_op(&['-', '+']) {
+            Some('-') => Ok(-self.unary()?),
+            Some(_) => self.unary(),
+            None => self.power(),
+        }
+    }
+
+    fn power(&mut self) -> Result<BigRational, String> {
+        let base = self.postfix()?;
+        if self.eat_op(&['^']).is_none() {
+            return Ok(base);
+        }
+        // Right associative, and `2^-1` is allowed
+        let exponent = self.unary()?;
+        pow(&base, &exponent)
+    }
+
+    fn postfix(&mut self) -> Result<BigRational, String> {
+        let mut value = self.primary()?;
+        while self.eat_op(&['!']).is_some() {
+            value = factorial(&value)?;
+        }
+        Ok(value)
+    }
+
+    fn primary(&mut self) -> Result<BigRational, String> {
+        match self.peek().cloned() {
+            Some(Token::Num(n)) => {
+                self.pos += 1;
+                Ok(n)
+            }
+            Some(Token::LParen) => {
+                self.pos += 1;
+                let value = self.expr()?;
+                self.expect(Token::RParen)?;
+                Ok(value)
+            }
+            Some(Token::Ident(name)) => {
+                self.pos += 1;
+                self.expect(Token::LParen)?;
+                let mut args = vec![self.expr()?];
+                while self.peek() == Some(&Token::Comma) {
+                    self.pos += 1;
+                    args.push(self.expr()?);
+                }
+                self.expect(Token::RParen)?;
+                call(&name, args)
+            }
+            Some(token) => Err(format!("unexpected {}", describe(&token))),
+            None => Err("expression ends early".to_string()),
+        }
+    }
+}
+
+fn call(name: &str, args: Vec<BigRational>) -> Result<BigRational, String> {
+    let one = |args: &[BigRational]| match args {
+        [value] => Ok(value.clone()),
+        _ => Err(format!("{}() takes one argument", name)),
+    };
+    match name {
+        "abs" => Ok(one(&args)?.abs()),
+        "floor" => Ok(one(&args)?.floor()),
+        "ceil" => Ok(one(&args)?.ceil()),
+        "round" => Ok(one(&args)?.round()),
+        "min" => args
+            .into_iter()
+            .min()
+            .ok_or_else(|| "min() needs arguments".to_string()),
+        "max" => args
+            .into_iter()
+            .max()
+            .ok_or_else(|| "max() needs arguments".to_string()),
+        "gcd" | "lcm" => {
+            let mut ints = Vec::with_capacity(args.len());
+            for arg in &args {
+                if !arg.is_integer() {
+                    return Err(format!("{}() takes integers", name));
+                }
+                ints.push(arg.to_integer());
+            }
+            let first = ints
+                .first()
+                .cloned()
+                .ok_or_else(|| format!("{}() needs arguments", name))?;
+            let result = ints[1..].iter().fold(first, |acc, n| {
+                if name == "gcd" {
+                    acc.gcd(n)
+                } else {
+                    acc.lcm(n)
+                }
+            });
+            Ok(BigRational::from_integer(result.abs()))
+        }
+        _ => Err(format!("unknown function '{}'", name)),
+    }
+}
+
+fn pow(base: &BigRational, exponent: &BigRational) -> Result<BigRational, String> {
+    if !exponent.is_integer() {
+        return Err("exponent must be an integer for an exact result".to_string());
+    }
+    let exp = exponent
+        .to_integer()
+        .abs()
+        .to_u32()
+        .ok_or_else(|| "exponent is too large".to_string())?;
+    if base.is_zero() {
+        return if exponent.is_negative() {
+            Err("division by zero".to_string())
+        } else if exp == 0 {
+            Ok(BigRational::one())
+        } else {
+            Ok(BigRational::zero())
+        };
+    }
+    let bits = base.numer().bits() + base.denom().bits();
+    if bits.saturating_mul(u64::from(exp)) > MAX_RESULT_BITS {
+        return Err("result would be too large".to_string());
+    }
+    let result = BigRational::new(base.numer().pow(exp), base.denom().pow(exp));
+    Ok(if exponent.is_negative() {
+        result.recip()
+    } else {
+        result
+    })
+}
+
+fn factorial(value: &BigRational) -> Result<BigRational, String> {
+    let n = value
+        .is_integer()
+        .then(|| value.to_integer().to_u64())
+        .flatten()
+        .ok_or_else(|| "factorial needs a non-negative integer".to_string())?;
+    if n > MAX_FACTORIAL {
+        return Err(format!("factorial is limited to {}!", MAX_FACTORIAL));
+    }
+    let product = (2..=n).fold(BigInt::one(), |acc, k| acc * k);
+    Ok(BigRational::from_integer(product))
+}
+
+fn check_size(value: &BigRational) -> Result<(), String> {
+    if value.numer().bits() + value.denom().bits() > MAX_RESULT_BITS {
+        return Err("result would be too large".to_string());
+    }
+    Ok(())
+}
@@ -0,0 +1,154 @@
+mod exact;
+mod stats;
+mod units;
+
+use extism_pdk::*;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use sweetmcp_plugin_builder::Ready;
+use sweetmcp_plugin_builder::prelude::*;
+
+/// Decimal places shown when the caller gives no precision
+const DEFAULT_PRECISION: usize = 30;
+
+/// A quantity given as a JSON number or as an exact expression such as "1/3"
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+#[schemars(crate = "sweetmcp_plugin_builder::schemars")]
+enum Amount {
+    Number(f64),
+    Expression(String),
+}
+
+impl Amount {
+    fn exact(&self) -> Result<num_rational::BigRational, String> {
+        match self {
+            // Display gives the shortest decimal that round-trips, so 0.1 stays 0.1
+            Amount::Number(n) if n.is_finite() => exact::parse_decimal(&n.to_string()),
+            Amount::Number(_) => Err("value must be finite".to_string()),
+            Amount::Expression(expression) => exact::evaluate(expression),
+        }
+    }
+}
+
+/// Exact result with its decimal rendering
+fn exact_result(value: &num_rational::BigRational, precision: Option<u32>) -> Value {
+    let precision = precision.map_or(DEFAULT_PRECISION, |p| p as usize);
+    let (decimal, decimal_is_exact) = exact::to_decimal(value, precision);
+    json!({
+        "exact": exact::to_fraction(value),
+        "decimal": decimal,
+        "decimal_is_exact": decimal_is_exact,
+        "is_integer": value.is_integer(),
+    })
+}
+
+/// Evaluate an arithmetic expression exactly, with arbitrary-size integers and fractions
+#[mcp_tool(
+    name = "calculate",
+    when = "you need arithmetic on numbers with many digits, where mental math or floating point would round",
+    when = "you need exact fractions, e.g. 1/3 + 1/6 = 1/2",
+    when = "you need factorials, powers, gcd or lcm of large integers",
+    perfect_for = "checking multi-digit arithmetic, money sums without rounding errors, and combinatorics",
+    read_only,
+    idempotent
+)]
+fn calculate(
+    /// Expression using + - * / % ^ ! and parentheses, plus abs, floor, ceil, round, min, max, gcd and lcm, e.g. "(2^64 - 1) / 3"
+    expression: String,
+    /// Decimal places in the decimal rendering (default 30, at most 1000)
+    #[serde(default)]
+    precision: Option<u32>,
+) -> Result<Value, ToolError> {
+    let value =
+        exact::evaluate(&expression).map_err(|e| ToolError::invalid_argument("expression", e))?;
+    let mut result = exact_result(&value, precision);
+    result["expression"] = expression.into();
+    Ok(result)
+}
+
+/// Convert a quantity between units of length, mass, time, volume, area, data, speed, energy, pressure or temperature
+#[mcp_tool(
+    name = "convert_units",
+    when = "you need to convert between metric and imperial units",
+    when = "you need temperature conversions between Celsius, Fahrenheit and Kelvin",
+    when = "you need byte sizes in decimal (MB) or binary (MiB) units",
+    perfect_for = "recipes, engineering estimates, travel and data sizing, with exact conversion factors",
+    read_only,
+    idempotent
+)]
+fn convert_units(
+    /// Quantity to convert, as a number or an exact expression such as "5/8"
+    value: Amount,
+    /// Unit to convert from, e.g. "mi", "lb", "°F", "GiB"
+    from: String,
+    /// Unit to convert to, e.g. "km", "kg", "°C", "GB"
+    to: String,
+    /// Decimal places in the decimal rendering (default 30, at most 1000)
+    #[serde(default)]
+    precision: Option<u32>,
+) -> Result<Value, ToolError> {
+    let amount = value
+        .exact()
+        .map_err(|e| ToolError::invalid_argument("value", e))?;
+    let (dimension, converted) =
+        units::convert(&amount, &from, &to).map_err(|e| ToolError::invalid_argument("from", e))?;
+    let mut result = exact_result(&converted, precision);
+    result["dimension"] = dimension.into();
+    result["from"] = from.into();
+    result["to"] = to.into();
+    result["value"] = exact::to_fraction(&amount).into();
+    Ok(result)
+}
+
+/// Descriptive statistics of a list of numbers
+#[mcp_tool(
+    name = "statistics",
+    when = "you need the mean, median, mode, spread or quartiles of a data set",
+    when = "you need sample or population variance and standard deviation",
+    perfect_for = "summarising measurements, survey results and benchmark timings",
+    read_only,
+    idempotent
+)]
+fn statistics(
+    /// Numbers to summarise
+    values: Vec<f64>,
+) -> Result<Value, ToolError> {
+    stats::describe(&values).map_err(|e| ToolError::invalid_argument("values", e))
+}
+
+/// Fit a straight line through points by least squares
+#[mcp_tool(
+    name = "linear_regression",
+    when = "you need the trend line, slope or correlation of paired data",
+    when = "you need to extrapolate or interpolate from a linear fit",
+    perfect_for = "trend analysis, calibration curves and quick forecasts",
+    read_only,
+    idempotent
+)]
+fn linear_regression(
+    /// Independent values
+    x: Vec<f64>,
+    /// Dependent values, one per x
+    y: Vec<f64>,
+    /// x values to predict y for with the fitted line
+    #[serde(default)]
+    predict: Vec<f64>,
+) -> Result<Value, ToolError> {
+    stats::linear_regression(&x, &y, &predict).map_err(|e| ToolError::invalid_argument("x", e))
+}
+
+/// Create the plugin instance
+#[allow(dead_code)]
+fn plugin() -> McpPlugin<Ready> {
+    mcp_plugin("math")
+        .description("Exact arithmetic on big integers and fractions, unit conversion, descriptive statistics and linear regression")
+        .tool::<CalculateTool>()
+        .tool::<ConvertUnitsTool>()
+        .tool::<StatisticsTool>()
+        .tool::<LinearRegressionTool>()
+        .serve()
+}
+
+// Generate standard MCP entry points
+sweetmcp_plugin_builder::generate_mcp_functions!(plugin);
@@ -0,0 +1,157 @@
+//! Descriptive statistics and simple linear regression
+//!
+//! Sums use Kahan compensation and the variance uses Welford's update, so
+//! long or badly scaled series don't lose precision. Quantiles interpolate
+//! linearly between closest ranks, as spreadsheets' `PERCENTILE.INC` does.
+
+use serde_json::{Value, json};
+
+/// Summary statistics of `values`
+pub fn describe(values: &[f64]) -> Result<Value, String> {
+    check_finite(values, "values")?;
+    if values.is_empty() {
+        return Err("values must not be empty".to_string());
+    }
+
+    let n = values.len();
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+
+    // Welford's online mean and variance
+    let (mut mean, mut m2) = (0.0, 0.0);
+    for (i, &x) in values.iter().enumerate() {
+        let delta = x - mean;
+        mean += delta / (i + 1) as f64;
+        m2 += delta * (x - mean);
+    }
+    let population_variance = m2 / n as f64;
+    let sample_variance = (n > 1).then(|| m2 / (n - 1) as f64);
+
+    let q1 = quantile(&sorted, 0.25);
+    let q3 = quantile(&sorted, 0.75);
+
+    Ok(json!({
+        "count": n,
+        "sum": kahan_sum(values),
+        "mean": mean,
+        "median": quantile(&sorted, 0.5),
+        "mode": modes(&sorted),
+        "min": sorted[0],
+        "max": sorted[n - 1],
+        "range": sorted[n - 1] - sorted[0],
+        "q1": q1,
+        "q3": q3,
+        "iqr": q3 - q1,
+        "population_variance": population_variance,
+        "population_stddev": population_variance.sqrt(),
+        "sample_variance": sample_variance,
+        "sample_stddev": sample_variance.map(f64::sqrt),
+    }))
+}
+
+/// Least-squares fit of `y = slope * x + intercept`, with predictions at `at`
+pub fn linear_regression(x: &[f64], y: &[f64], at: &[f64]) -> Result<Value, String> {
+    check_finite(x, "x")?;
+    check_finite(y, "y")?;
+    check_finite(at, "predict")?;
+    if x.len() != y.len() {
+        return Err(format!(
+            "x and y must have the same length ({} vs {})",
+            x.len(),
+            y.len()
+        ));
+    }
+    if x.len() < 2 {
+        return Err("need at least two points".to_string());
+    }
+
+    let n = x.len() as f64;
+    let mean_x = kahan_sum(x) / n;
+    let mean_y = kahan_sum(y) / n;
+    let (mut sxx, mut syy, mut sxy) = (0.0, 0.0, 0.0);
+    for (&xi, &yi) in x.iter().zip(y) {
+        let (dx, dy) = (xi - mean_x, yi - mean_y);
+        sxx += dx * dx;
+        syy += dy * dy;
+        sxy += dx * dy;
+    }
+    if sxx == 0.0 {
+        return Err("x values are all equal, so the slope is undefined".to_string());
+    }
+
+    let slope = sxy / sxx;
+    let intercept = mean_y - slope * mean_x;
+    // A constant y is fitted perfectly by a flat line
+    let r = if syy == 0.0 {
+        None
+    } else {
+        Some(sxy / (sxx * syy).sqrt())
+    };
+    let r_squared = r.map_or(1.0, |r| r * r);
+    let residual_ss: f64 = x
+        .iter()
+        .zip(y)
+        .map(|(&xi, &yi)| (yi - (slope * xi + intercept)).powi(2))
+        .sum();
+    let standard_error = (x.len() > 2).then(|| (residual_ss / (n - 2.0)).sqrt());
+
+    let predictions = at
+        .iter()
+        .map(|&xi| json!({"x": xi, "y": slope * xi + intercept}))
+        .collect::<Vec<_>>();
+
+    Ok(json!({
+        "n": x.len(),
+        "slope": slope,
+        "intercept": intercept,
+        "r": r,
+        "r_squared": r_squared,
+        "standard_error": standard_error,
+        "equation": format!("y = {} * x + {}", slope, intercept),
+        "predictions": predictions,
+    }))
+}
+
+fn check_finite(values: &[f64], name: &str) -> Result<(), String> {
+    match values.iter().position(|v| !v.is_finite()) {
+        Some(i) => Err(format!("{}[{}] is not a finite number", name, i)),
+        None => Ok(()),
+    }
+}
+
+fn kahan_sum(values: &[f64]) -> f64 {
+    let (mut sum, mut compensation) = (0.0, 0.0);
+    for &x in values {
+        let y = x - compensation;
+        let t = sum + y;
+        compensation = (t - sum) - y;
+        sum = t;
+    }
+    sum
+}
+
+/// Quantile `q` of sorted, non-empty `values`
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let rank = q * (sorted.len() - 1) as f64;
+    let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
+/// Most frequent values of sorted `values`; empty when every value is unique
+fn modes(sorted: &[f64]) -> Vec<f64> {
+    let mut runs: Vec<(f64, usize)> = Vec::new();
+    for &x in sorted {
+        match runs.last_mut() {
+            Some((value, count)) if *value == x => *count += 1,
+            _ => runs.push((x, 1)),
+        }
+    }
+    let best = runs.iter().map(|(_, count)| *count).max().unwrap_or(0);
+    if best < 2 {
+        return Vec::new();
+    }
+    runs.into_iter()
+        .filter(|(_, count)| *count == best)
+        .map(|(value, _)| value)
+        .collect()
+}
@@ -0,0 +1,291 @@
+//! Unit conversion with exact factors
+//!
+//! Every unit is defined by an exact expression for its size in the base unit
+//! of its dimension, so conversions between defined units (inches and
+//! centimetres, pounds and kilograms) come out exact. Temperatures are affine
+//! and convert through kelvin.
+
+use num_rational::BigRational;
+
+use crate::exact::evaluate;
+
+/// (dimension, aliases, size in the dimension's base unit)
+const UNITS: &[(&str, &[&str], &str)] = &[
+    // Length, metres
+    ("length", &["m", "meter", "meters", "metre", "metres"], "1"),
+    (
+        "length",
+        &["km", "kilometer", "kilometers", "kilometre", "kilometres"],
+        "1000",
+    ),
+    (
+        "length",
+        &[
+            "cm",
+            "centimeter",
+            "centimeters",
+            "centimetre",
+            "centimetres",
+        ],
+        "1/100",
+    ),
+    (
+        "length",
+        &[
+            "mm",
+            "millimeter",
+            "millimeters",
+            "millimetre",
+            "millimetres",
+        ],
+        "1/1000",
+    ),
+    (
+        "length",
+        &["um", "µm", "micrometer", "micrometers", "micron", "microns"],
+        "1e-6",
+    ),
+    ("length", &["nm", "nanometer", "nanometers"], "1e-9"),
+    ("length", &["in", "inch", "inches"], "0.0254"),
+    ("length", &["ft", "foot", "feet"], "0.3048"),
+    ("length", &["yd", "yard", "yards"], "0.9144"),
+    ("length", &["mi", "mile", "miles"], "1609.344"),
+    (
+        "length",
+        &["nmi", "nautical_mile", "nautical_miles"],
+        "1852",
+    ),
+    // Mass, kilograms
+    ("mass", &["kg", "kilogram", "kilograms"], "1"),
+    ("mass", &["g", "gram", "grams"], "1/1000"),
+    ("mass", &["mg", "milligram", "milligrams"], "1e-6"),
+    ("mass", &["t", "tonne", "tonnes", "metric_ton"], "1000"),
+    ("mass", &["lb", "lbs", "pound", "pounds"], "0.45359237"),
+    ("mass", &["oz", "ounce", "ounces"], "0.45359237/16"),
+    ("mass", &["st", "stone", "stones"], "0.45359237*14"),
+    ("mass", &["ton", "short_ton"], "0.45359237*2000"),
+    // Time, seconds
+    ("time", &["s", "sec", "second", "seconds"], "1"),
+    ("time", &["ms", "millisecond", "milliseconds"], "1/1000"),
+    ("time", &["us", "µs", "microsecond", "microseconds"], "1e-6"),
+    ("time", &["ns", "nanosecond", "nanoseconds"], "1e-9"),
+    ("time", &["min", "minute", "minutes"], "60"),
+    ("time", &["h", "hr", "hour", "hours"], "3600"),
+    ("time", &["d", "day", "days"], "86400"),
+    ("time", &["wk", "week", "weeks"], "604800"),
+    // Julian year, as used in astronomy
+    ("time", &["yr", "year", "years"], "365.25*86400"),
+    // Volume, litres
+    ("volume", &["l", "liter", "liters", "litre", "litres"], "1"),
+    (
+        "volume",
+        &[
+            "ml",
+            "milliliter",
+            "milliliters",
+            "millilitre",
+            "millilitres",
+        ],
+        "1/1000",
+    ),
+    ("volume", &["m3", "cubic_meter", "cubic_meters"], "1000"),
+    ("volume", &["cm3", "cc"], "1/1000"),
+    ("volume", &["gal", "gallon", "gallons"], "3.785411784"),
+    ("volume", &["qt", "quart", "quarts"], "3.785411784/4"),
+    ("volume", &["pt", "pint", "pints"], "3.785411784/8"),
+    ("volume", &["cup", "cups"], "3.785411784/16"),
+    (
+        "volume",
+        &["floz", "fl_oz", "fluid_ounce", "fluid_ounces"],
+        "3.785411784/128",
+    ),
+    (
+        "volume",
+        &["tbsp", "tablespoon", "tablespoons"],
+        "3.785411784/256",
+    ),
+    (
+        "volume",
+        &["tsp", "teaspoon", "teaspoons"],
+        "3.785411784/768",
+    ),
+    // Area, square metres
+    (
+        "area",
+        &["m2", "sq_m", "square_meter", "square_meters"],
+        "1",
+    ),
+    (
+        "area",
+        &["km2", "sq_km", "square_kilometer", "square_kilometers"],
+        "1e6",
+    ),
+    ("area", &["cm2", "sq_cm"], "1e-4"),
+    ("area", &["ha", "hectare", "hectares"], "1e4"),
+    ("area", &["acre", "acres"], "4046.8564224"),
+    (
+        "area",
+        &["ft2", "sq_ft", "square_foot", "square_feet"],
+        "0.3048^2",
+    ),
+    (
+        "area",
+        &["in2", "sq_in", "square_inch", "square_inches"],
+        "0.0254^2",
+    ),
+    (
+        "area",
+        &["mi2", "sq_mi", "square_mile", "square_miles"],
+        "1609.344^2",
+    ),
+    // Data, bytes
+    ("data", &["b", "byte", "bytes"], "1"),
+    ("data", &["bit", "bits"], "1/8"),
+    ("data", &["kb", "kilobyte", "kilobytes"], "1e3"),
+    ("data", &["mb", "megabyte", "megabytes"], "1e6"),
+    ("data", &["gb", "gigabyte", "gigabytes"], "1e9"),
+    ("data", &["tb", "terabyte", "terabytes"], "1e12"),
+    ("data", &["kib", "kibibyte", "kibibytes"], "2^10"),
+    ("data", &["mib", "mebibyte", "mebibytes"], "2^20"),
+    ("data", &["gib", "gibibyte", "gibibytes"], "2^30"),
+    ("data", &["tib", "tebibyte", "tebibytes"], "2^40"),
+    // Speed, metres per second
+    ("speed", &["m/s", "mps"], "1"),
+    ("speed", &["km/h", "kmh", "kph"], "1000/3600"),
+    ("speed", &["mph", "mi/h"], "1609.344/3600"),
+    ("speed", &["kn", "knot", "knots"], "1852/3600"),
+    ("speed", &["ft/s", "fps"], "0.3048"),
+    // Energy, joules
+    ("energy", &["j", "joule", "joules"], "1"),
+    ("energy", &["kj", "kilojoule", "kilojoules"], "1000"),
+    ("energy", &["cal", "calorie", "calories"], "4.184"),
+    ("energy", &["kcal", "kilocalorie", "kilocalories"], "4184"),
+    ("energy", &["wh", "watt_hour", "watt_hours"], "3600"),
+    (
+        "energy",
+        &["kwh", "kilowatt_hour", "kilowatt_hours"],
+        "3.6e6",
+    ),
+    // Pressure, pascals
+    ("pressure", &["pa", "pascal", "pascals"], "1"),
+    ("pressure", &["kpa", "kilopascal", "kilopascals"], "1000"),
+    ("pressure", &["bar"], "1e5"),
+    ("pressure", &["atm", "atmosphere", "atmospheres"], "101325"),
+    ("pressure", &["psi"], "0.45359237*9.80665/0.0254^2"),
+    ("pressure", &["mmhg"], "101325/760"),
+];
+
+/// Temperature scales as (aliases, kelvin = value * scale + offset)
+const TEMPERATURES: &[(&[&str], &str, &str)] = &[
+    (&["k", "kelvin"], "1", "0"),
+    (&["c", "°c", "celsius"], "1", "273.15"),
+    (&["f", "°f", "fahrenheit"], "5/9", "273.15 - 32*5/9"),
+    (&["r", "°r", "rankine"], "5/9", "0"),
+];
+
+enum Unit {
+    Linear {
+        dimension: &'static str,
+        size: BigRational,
+    },
+    Temperature {
+        scale: BigRational,
+        offset: BigRational,
+    },
+}
+
+fn lookup(name: &str) -> Result<Unit, String> {
+    let key = name.trim().to_lowercase().replace(' ', "_");
+    if let Some((dimension, _, size)) = UNITS
+        .iter()
+        .find(|(_, aliases, _)| aliases.contains(&key.as_str()))
+    {
+        return Ok(Unit::Linear {
+            dimension,
+            size: evaluate(size)?,
+        });
+    }
+    if let Some((_, scale, offset)) = TEMPERATURES
+        .iter()
+        .find(|(aliases, _, _)| aliases.contains(&key.as_str()))
+    {
+        return Ok(Unit::Temperature {
+            scale: evaluate(scale)?,
+            offset: evaluate(offset)?,
+        });
+    }
+    let known = catalog()
+        .into_iter()
+        .map(|(dimension, units)| format!("{}: {}", dimension, units.join(", ")))
+        .collect::<Vec<_>>()
+        .join("; ");
+    Err(format!(
+        "unknown unit '{}'; known units are {}",
+        name, known
+    ))
+}
+
+/// Convert `value` from one unit to another, returning the dimension and the
+/// exact result
+pub fn convert(
+    value: &BigRational,
+    from: &str,
+    to: &str,
+) -> Result<(&'static str, BigRational), String> {
+    match (lookup(from)?, lookup(to)?) {
+        (
+            Unit::Linear {
+                dimension: from_dim,
+                size: from_size,
+            },
+            Unit::Linear {
+                dimension: to_dim,
+                size: to_size,
+            },
+        ) => {
+            if from_dim != to_dim {
+                return Err(format!(
+                    "cannot convert {} ({}) to {} ({})",
+                    from, from_dim, to, to_dim
+                ));
+            }
+            Ok((from_dim, value * from_size / to_size))
+        }
+        (
+            Unit::Temperature {
+                scale: from_scale,
+                offset: from_offset,
+            },
+            Unit::Temperature {
+                scale: to_scale,
+                offset: to_offset,
+            },
+        ) => {
+            let kelvin = value * from_scale + from_offset;
+            Ok(("temperature", (kelvin - to_offset) / to_scale))
+        }
+        _ => Err(format!(
+            "cannot convert between {} and {}: only one is a temperature",
+            from, to
+        )),
+    }
+}
+
+/// Every dimension with the first alias of each of its units
+fn catalog() -> Vec<(&'static str, Vec<&'static str>)> {
+    let mut catalog: Vec<(&'static str, Vec<&'static str>)> = Vec::new();
+    for (dimension, aliases, _) in UNITS {
+        match catalog.iter_mut().find(|(d, _)| d == dimension) {
+            Some((_, units)) => units.push(aliases[0]),
+            None => catalog.push((dimension, vec![aliases[0]])),
+        }
+    }
+    catalog.push((
+        "temperature",
+        TEMPERATURES
+            .iter()
+            .map(|(aliases, _, _)| aliases[0])
+            .collect(),
+    ));
+    catalog
+}
@@ -0,0 +1,222 @@
+mod rate_limit;
+
+use extism_pdk::*;
+use serde_json::{Value, json};
+use sweetmcp_plugin_builder::http;
+use sweetmcp_plugin_builder::prelude::*;
+use sweetmcp_plugin_builder::{CallToolResult, Ready};
+
+/// Default messages allowed per destination per minute
+const DEFAULT_RATE_PER_MINUTE: u32 = 20;
+
+/// Where a message is delivered
+enum Destination {
+    /// Incoming webhook; the URL already selects the channel
+    Webhook(String),
+    /// Bot token against the platform's REST API
+    Api { token: String, channel: String },
+}
+
+/// Send messages to Slack or Discord
+struct SendMessageTool;
+
+impl McpTool for SendMessageTool {
+    const NAME: &'static str = "send_message";
+    type Args = Value;
+
+    fn description(builder: DescriptionBuilder) -> DescriptionBuilder {
+        builder
+            .does("Post a message to a Slack or Discord channel via webhook or bot token")
+            .when("you need to tell a human that a long-running job has finished or failed")
+            .when("you need to post a report, summary or alert to a team channel")
+            .when("you need to send rich Slack Block Kit or Discord embed content")
+            .requires("Credentials (webhook URLs or bot tokens) must be provided through the plugin config by the daemon; messages are rate limited per channel")
+            .not_for("reading messages or having conversations; this tool only sends")
+            .perfect_for("job completion notices, CI results, monitoring alerts, and human-in-the-loop pings")
+    }
+
+    fn schema(builder: SchemaBuilder) -> Value {
+        builder
+            .required_enum("platform", "Messaging platform", &["slack", "discord"])
+            .optional_string(
+                "channel",
+                "Channel ID for bot-token delivery; ignored for webhooks, which are bound to a channel",
+            )
+            .required_string("text", "Plain text message (also used as the notification fallback)")
+            .optional_string(
+                "blocks",
+                "Optional JSON array: Slack Block Kit blocks or Discord embeds",
+            )
+            .build()
+    }
+
+    fn execute(args: Value) -> Result<CallToolResult, Error> {
+        let platform = args
+            .get("platform")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::msg("platform parameter required"))?;
+        let text = args
+            .get("text")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::msg("text parameter required"))?;
+        let channel = args.get("channel").and_then(|v| v.as_str());
+
+        let blocks = match args.get("blocks") {
+            None | Some(Value::Null) => None,
+            Some(Value::String(raw)) => match serde_json::from_str::<Value>(raw) {
+                Ok(parsed @ Value::Array(_)) => Some(parsed),
+                _ => return Ok(ContentBuilder::error("blocks must be a JSON array")),
+            },
+            Some(array @ Value::Array(_)) => Some(array.clone()),
+            Some(_) => return Ok(ContentBuilder::error("blocks must be a JSON array")),
+        };
+
+        let destination = match resolve_destination(platform, channel)? {
+            Some(destination) => destination,
+            None => {
+                return Ok(ContentBuilder::error(format!(
+                    "No {} credentials configured: set {}_webhook_url or {}_bot_token",
+                    platform, platform, platform
+                )));
+            }
+        };
+
+        let limit_key = match &destination {
+            Destination::Webhook(url) => format!("{}:{}", platform, url),
+            Destination::Api { channel, .. } => format!("{}:{}", platform, channel),
+        };
+        let per_minute = config::get("rate_limit_per_minute")?
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_RATE_PER_MINUTE);
+        if let Err(retry_after) = rate_limit::acquire(&limit_key, per_minute)? {
+            return Ok(ContentBuilder::error(format!(
+                "Rate limit of {} messages/minute reached for this channel; retry in {}s",
+                per_minute, retry_after
+            )));
+        }
+
+        let request = match platform {
+            "slack" => slack_request(&destination, text, blocks),
+            "discord" => discord_request(&destination, text, blocks),
+            other => {
+                return Ok(ContentBuilder::error(format!(
+                    "Unsupported platform: {}",
+                    other
+                )));
+            }
+        };
+
+        let response = request?.send()?;
+        let status = response.status();
+        let response_body = response.text();
+
+        if status == 429 {
+            return Ok(ContentBuilder::error(format!(
+                "{} rate limited the request: {}",
+                platform, response_body
+            )));
+        }
+        if status >= 400 {
+            return Ok(ContentBuilder::error(format!(
+                "{} returned HTTP {}: {}",
+                platform, status, response_body
+            )));
+        }
+        // Slack's Web API reports failures in a 200 body
+        if platform == "slack" {
+            if let Ok(parsed) = serde_json::from_str::<Value>(&response_body) {
+                if parsed.get("ok").and_then(|v| v.as_bool()) == Some(false) {
+                    return Ok(ContentBuilder::error(format!(
+                        "slack rejected the message: {}",
+                        parsed.get("error").and_then(|v| v.as_str()).unwrap_or("unknown error")
+                    )));
+                }
+            }
+        }
+
+        Ok(ContentBuilder::text(
+            json!({
+                "platform": platform,
+                "channel": channel,
+                "delivered": true,
+                "status": status,
+            })
+            .to_string(),
+        ))
+    }
+}
+
+/// Prefer bot-token delivery when a channel is given, otherwise fall back to the webhook
+fn resolve_destination(platform: &str, channel: Option<&str>) -> Result<Option<Destination>, Error> {
+    let token = config::get(&format!("{}_bot_token", platform))?;
+    let webhook = config::get(&format!("{}_webhook_url", platform))?;
+
+    Ok(match (token, channel, webhook) {
+        (Some(token), Some(channel), _) => Some(Destination::Api {
+            token,
+            channel: channel.to_string(),
+        }),
+        (_, _, Some(url)) => Some(Destination::Webhook(url)),
+        _ => None,
+    })
+}
+
+fn slack_request(
+    destination: &Destination,
+    text: &str,
+    blocks: Option<Value>,
+) -> Result<http::Request, http::HttpError> {
+    let mut body = json!({ "text": text });
+    if let Some(blocks) = blocks {
+        body["blocks"] = blocks;
+    }
+
+    let request = match destination {
+        Destination::Webhook(url) => http::post(url),
+        Destination::Api { token, channel } => {
+            body["channel"] = json!(channel);
+            http::post("https://slack.com/api/chat.postMessage").bearer_auth(token)
+        }
+    };
+
+    Ok(request
+        .json(&body)?
+        .header("Content-Type", "application/json; charset=utf-8"))
+}
+
+fn discord_request(
+    destination: &Destination,
+    text: &str,
+    blocks: Option<Value>,
+) -> Result<http::Request, http::HttpError> {
+    let mut body = json!({ "content": text });
+    if let Some(embeds) = blocks {
+        body["embeds"] = embeds;
+    }
+
+    let request = match destination {
+        Destination::Webhook(url) => http::post(url),
+        Destination::Api { token, channel } => http::post(format!(
+            "https://discord.com/api/v10/channels/{}/messages",
+            channel
+        ))
+        .header("Authorization", format!("Bot {}", token)),
+    };
+
+    request.json(&body)
+}
+
+/// Create the plugin instance
+#[allow(dead_code)]
+fn plugin() -> McpPlugin<Ready> {
+    mcp_plugin("notify")
+        .description("Slack and Discord notifications with built-in per-channel rate limiting")
+        .version(env!("CARGO_PKG_VERSION"))
+        // The rate limiter lives in this instance's memory
+        .capability("stateful")
+        .tool::<SendMessageTool>()
+        .serve()
+}
+
+// Generate standard MCP entry points
+sweetmcp_plugin_builder::generate_mcp_functions!(plugin);
@@ -0,0 +1,33 @@
+//! Sliding-window rate limiting persisted in Extism plugin vars
+//!
+//! The host keeps one plugin instance alive across calls, so vars survive
+//! between invocations and give us a cheap per-destination send log.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use extism_pdk::*;
+
+const WINDOW_SECS: u64 = 60;
+
+/// Record a send for `key` if under `per_minute`, otherwise return the seconds to wait
+pub fn acquire(key: &str, per_minute: u32) -> Result<Result<(), u64>, Error> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let var_key = format!("rate:{}", key);
+
+    let mut sent: Vec<u64> = var::get::<String>(&var_key)?
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default();
+    sent.retain(|ts| now.saturating_sub(*ts) < WINDOW_SECS);
+
+    if sent.len() >= per_minute as usize {
+        let oldest = sent.iter().min().copied().unwrap_or(now);
+        return Ok(Err(WINDOW_SECS.saturating_sub(now - oldest).max(1)));
+    }
+
+    sent.push(now);
+    var::set(&var_key, serde_json::to_string(&sent)?)?;
+    Ok(Ok(()))
+}
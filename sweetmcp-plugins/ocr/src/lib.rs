@@ -0,0 +1,121 @@
+use extism_pdk::*;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use sweetmcp_plugin_builder::Ready;
+use sweetmcp_plugin_builder::prelude::*;
+
+// Recognition runs on the host; see sweetmcp-axum `plugin::ocr`.
+#[host_fn]
+extern "ExtismHost" {
+    fn ocr_recognize(req: Json<Value>) -> Json<Value>;
+}
+
+/// How much layout to return alongside the text
+#[derive(Clone, Copy, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+#[schemars(crate = "sweetmcp_plugin_builder::schemars")]
+enum Detail {
+    /// Only the recognised text
+    Text,
+    /// Text plus each line with its bounding box and confidence
+    #[default]
+    Lines,
+    /// Lines plus every word with its bounding box and confidence
+    Words,
+}
+
+/// Base64 image data, accepting a `data:image/...;base64,` URL as well
+fn image_data(image: &str) -> &str {
+    let image = image.trim();
+    match image.strip_prefix("data:") {
+        Some(url) => url.split_once(',').map_or(url, |(_, data)| data),
+        None => image,
+    }
+}
+
+/// Extract text from an image, with where each line or word sits and how sure the engine is
+#[mcp_tool(
+    name = "extract_text",
+    when = "you need the text in a screenshot, scanned page, photo of a document or chart",
+    when = "you need to know where on an image a piece of text is, e.g. to click or crop it",
+    perfect_for = "reading output of the screen, browser and fetch screenshot tools; pass their base64 image straight in",
+    read_only,
+    idempotent
+)]
+fn extract_text(
+    /// Image as base64 (PNG, JPEG, TIFF, BMP, ...), or a data: URL
+    image: String,
+    /// Tesseract language codes joined with +, e.g. "eng" or "eng+deu"; the host's default when omitted
+    #[serde(default)]
+    language: Option<String>,
+    /// Tesseract page segmentation mode: 3 automatic (default), 6 a single block, 7 a single line, 11 sparse text
+    #[serde(default)]
+    page_segmentation: Option<u8>,
+    /// Drop words recognised with lower confidence, 0-100 (default 0)
+    #[serde(default)]
+    min_confidence: Option<f32>,
+    /// text, lines (default) or words: how much layout to return
+    #[serde(default)]
+    detail: Detail,
+) -> Result<Value, ToolError> {
+    let image = image_data(&image);
+    if image.is_empty() {
+        return Err(ToolError::invalid_argument(
+            "image",
+            "image must not be empty",
+        ));
+    }
+    let Json(mut reply) = unsafe {
+        ocr_recognize(Json(json!({
+            "image": image,
+            "language": language,
+            "page_segmentation": page_segmentation,
+            "min_confidence": min_confidence.unwrap_or(0.0),
+        })))
+    }
+    .map_err(|e| ToolError::internal(e.to_string()))?;
+
+    if reply.get("ok").and_then(|v| v.as_bool()) == Some(false) {
+        let message = reply
+            .get("error")
+            .and_then(|v| v.as_str())
+            .unwrap_or("OCR host call failed");
+        return Err(if message.contains("by policy") {
+            ToolError::permission_denied(message)
+        } else if let Some(field) = ["image", "page_segmentation"]
+            .into_iter()
+            .find(|field| message.starts_with(field))
+        {
+            ToolError::invalid_argument(field, message)
+        } else {
+            ToolError::internal(message)
+        });
+    }
+
+    if let Some(map) = reply.as_object_mut() {
+        map.remove("ok");
+        match detail {
+            Detail::Text => {
+                map.remove("lines");
+                map.remove("words");
+            }
+            Detail::Lines => {
+                map.remove("words");
+            }
+            Detail::Words => {}
+        }
+    }
+    Ok(reply)
+}
+
+/// Create the plugin instance
+#[allow(dead_code)]
+fn plugin() -> McpPlugin<Ready> {
+    mcp_plugin("ocr")
+        .description("Extract text with bounding boxes and confidence from images using the host's OCR engine")
+        .tool::<ExtractTextTool>()
+        .serve()
+}
+
+// Generate standard MCP entry points
+sweetmcp_plugin_builder::generate_mcp_functions!(plugin);
@@ -0,0 +1,207 @@
+//! PDF operations built on lopdf
+
+use std::collections::BTreeMap;
+
+use extism_pdk::Error;
+use lopdf::{Document, Object, ObjectId, dictionary};
+
+/// Parse a page selection such as `"1-3,7"` into sorted, de-duplicated page numbers.
+/// `None` selects every page.
+pub fn parse_pages(spec: Option<&str>, page_count: u32) -> Result<Vec<u32>, Error> {
+    let Some(spec) = spec.map(str::trim).filter(|s| !s.is_empty()) else {
+        return Ok((1..=page_count).collect());
+    };
+
+    let mut pages = Vec::new();
+    for part in spec.split(',') {
+        let part = part.trim();
+        let (start, end) = match part.split_once('-') {
+            Some((a, b)) => (parse_page(a)?, parse_page(b)?),
+            None => {
+                let page = parse_page(part)?;
+                (page, page)
+            }
+        };
+        if start == 0 || start > end || end > page_count {
+            return Err(Error::msg(format!(
+                "Invalid page range '{}' for a {}-page document",
+                part, page_count
+            )));
+        }
+        pages.extend(start..=end);
+    }
+    pages.sort_unstable();
+    pages.dedup();
+    Ok(pages)
+}
+
+fn parse_page(s: &str) -> Result<u32, Error> {
+    s.trim()
+        .parse()
+        .map_err(|_| Error::msg(format!("Invalid page number '{}'", s.trim())))
+}
+
+pub fn load(bytes: &[u8]) -> Result<Document, Error> {
+    Document::load_mem(bytes).map_err(|e| Error::msg(format!("Failed to parse PDF: {}", e)))
+}
+
+pub fn save(doc: &mut Document) -> Result<Vec<u8>, Error> {
+    let mut out = Vec::new();
+    doc.save_to(&mut out)
+        .map_err(|e| Error::msg(format!("Failed to write PDF: {}", e)))?;
+    Ok(out)
+}
+
+pub fn page_count(doc: &Document) -> u32 {
+    doc.get_pages().len() as u32
+}
+
+/// Extract text for each selected page
+pub fn extract_text(doc: &Document, pages: &[u32]) -> Vec<(u32, String)> {
+    pages
+        .iter()
+        .map(|&page| {
+            let text = doc.extract_text(&[page]).unwrap_or_default();
+            (page, text)
+        })
+        .collect()
+}
+
+/// Heuristic table extraction: consecutive lines that split into the same
+/// number (≥ 2) of columns on runs of two or more spaces or tabs form a table.
+pub fn extract_tables(text: &str) -> Vec<Vec<Vec<String>>> {
+    let mut tables = Vec::new();
+    let mut current: Vec<Vec<String>> = Vec::new();
+
+    for line in text.lines() {
+        let cells = split_columns(line);
+        let continues = cells.len() >= 2
+            && current
+                .first()
+                .map(|row| row.len() == cells.len())
+                .unwrap_or(true);
+
+        if continues {
+            current.push(cells);
+        } else {
+            if current.len() >= 2 {
+                tables.push(std::mem::take(&mut current));
+            }
+            current.clear();
+            if cells.len() >= 2 {
+                current.push(cells);
+            }
+        }
+    }
+    if current.len() >= 2 {
+        tables.push(current);
+    }
+    tables
+}
+
+fn split_columns(line: &str) -> Vec<String> {
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut gap = 0;
+
+    for ch in line.trim().chars() {
+        if ch == '\t' {
+            gap = 2;
+            continue;
+        }
+        if ch == ' ' {
+            gap += 1;
+            continue;
+        }
+        if gap >= 2 && !cell.is_empty() {
+            cells.push(std::mem::take(&mut cell));
+        } else if gap == 1 {
+            cell.push(' ');
+        }
+        gap = 0;
+        cell.push(ch);
+    }
+    if !cell.is_empty() {
+        cells.push(cell);
+    }
+    cells
+}
+
+/// Keep only `pages` (1-based) of `doc`
+pub fn select_pages(mut doc: Document, pages: &[u32]) -> Document {
+    let total = page_count(&doc);
+    let drop: Vec<u32> = (1..=total).filter(|p| !pages.contains(p)).collect();
+    if !drop.is_empty() {
+        doc.delete_pages(&drop);
+        doc.prune_objects();
+    }
+    doc.compress();
+    doc
+}
+
+/// Concatenate documents in order into a single PDF
+pub fn merge(documents: Vec<Document>) -> Result<Document, Error> {
+    if documents.is_empty() {
+        return Err(Error::msg("Nothing to merge"));
+    }
+    let mut max_id = 1;
+    // Vec, not a map: page order must follow document order, not object ids
+    let mut pages: Vec<(ObjectId, Object)> = Vec::new();
+    let mut objects: BTreeMap<ObjectId, Object> = BTreeMap::new();
+    let mut merged = Document::with_version("1.5");
+
+    for mut doc in documents {
+        doc.renumber_objects_with(max_id);
+        max_id = doc.max_id + 1;
+
+        for (_, page_id) in doc.get_pages() {
+            if let Ok(page) = doc.get_object(page_id) {
+                pages.push((page_id, page.to_owned()));
+            }
+        }
+        objects.extend(doc.objects);
+    }
+
+    // Rebuild a single Catalog / Pages tree; drop the per-document ones
+    let pages_id = (max_id, 0);
+    let catalog_id = (max_id + 1, 0);
+    for (id, object) in objects {
+        match object.type_name().unwrap_or(b"") {
+            b"Catalog" | b"Pages" | b"Outlines" | b"Outline" => {}
+            b"Page" => {}
+            _ => {
+                merged.objects.insert(id, object);
+            }
+        }
+    }
+
+    let kids: Vec<Object> = pages.iter().map(|(id, _)| Object::Reference(*id)).collect();
+    let count = kids.len() as i64;
+    for (id, object) in pages {
+        if let Ok(mut dict) = object.as_dict().cloned() {
+            dict.set("Parent", pages_id);
+            merged.objects.insert(id, Object::Dictionary(dict));
+        }
+    }
+
+    merged.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => kids,
+            "Count" => count,
+        }),
+    );
+    merged.objects.insert(
+        catalog_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        }),
+    );
+    merged.trailer.set("Root", catalog_id);
+    merged.max_id = catalog_id.0;
+    merged.renumber_objects();
+    merged.compress();
+    Ok(merged)
+}
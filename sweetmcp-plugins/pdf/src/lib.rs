@@ -0,0 +1,275 @@
+mod document;
+
+use std::fs;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use extism_pdk::*;
+use serde_json::{Value, json};
+use sweetmcp_plugin_builder::prelude::*;
+use sweetmcp_plugin_builder::{CallToolResult, Ready};
+
+/// PDF toolkit using plugin-builder
+struct PdfTool;
+
+impl McpTool for PdfTool {
+    const NAME: &'static str = "pdf";
+    type Args = Value;
+
+    fn description(builder: DescriptionBuilder) -> DescriptionBuilder {
+        builder
+            .does("Read and manipulate PDF documents supplied as base64 data or sandboxed file paths")
+            .when("you need to summarize or quote the text of a PDF")
+            .when("you need tabular data out of a PDF report or invoice")
+            .when("you need to combine several PDFs or pull out a subset of pages")
+            .when("you need to know how many pages a PDF has before processing it")
+            .operation("page_count", "Return the number of pages")
+            .operation("extract_text", "Extract text, optionally limited to `pages` (e.g. \"1-3,7\")")
+            .operation("extract_tables", "Detect column-aligned tables in the selected pages")
+            .operation("merge", "Concatenate the PDFs in `paths` (or `data_list`) in order")
+            .operation("split", "Produce one PDF per comma-separated range in `ranges` (e.g. \"1-2,3-5\")")
+            .requires("File paths must be inside the plugin's allowed_paths")
+            .not_for("scanned PDFs without a text layer (use OCR), or editing page content")
+            .perfect_for("document summarization, data extraction, report assembly, and page management")
+    }
+
+    fn schema(builder: SchemaBuilder) -> Value {
+        builder
+            .required_enum(
+                "operation",
+                "PDF operation to perform",
+                &["page_count", "extract_text", "extract_tables", "merge", "split"],
+            )
+            .optional_string("path", "Path of the input PDF")
+            .optional_string("data", "Base64-encoded input PDF (alternative to path)")
+            .optional_string(
+                "paths",
+                "Input PDFs for merge: JSON array or comma-separated list of paths",
+            )
+            .optional_string(
+                "data_list",
+                "Input PDFs for merge as a JSON array of base64 strings",
+            )
+            .optional_string("pages", "Page selection such as \"1-3,7\" (default: all pages)")
+            .optional_string("ranges", "Page ranges for split, e.g. \"1-2,3-5\"")
+            .optional_string(
+                "output_path",
+                "Where to write merge/split output; split appends -<n> before the extension. Omit to receive base64",
+            )
+            .build()
+    }
+
+    fn execute(args: Value) -> Result<CallToolResult, Error> {
+        let operation = args
+            .get("operation")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::msg("operation parameter required"))?;
+
+        let result = match operation {
+            "page_count" => page_count(&args),
+            "extract_text" => extract_text(&args),
+            "extract_tables" => extract_tables(&args),
+            "merge" => merge(&args),
+            "split" => split(&args),
+            _ => {
+                return Ok(ContentBuilder::error(format!(
+                    "Unknown pdf operation: {}",
+                    operation
+                )));
+            }
+        };
+
+        // Input problems are reported to the caller rather than failing the call
+        Ok(result.unwrap_or_else(|e| ContentBuilder::error(e.to_string())))
+    }
+}
+
+fn page_count(args: &Value) -> Result<CallToolResult, Error> {
+    let doc = document::load(&input_bytes(args)?)?;
+    Ok(ContentBuilder::text(
+        json!({ "page_count": document::page_count(&doc) }).to_string(),
+    ))
+}
+
+fn extract_text(args: &Value) -> Result<CallToolResult, Error> {
+    let doc = document::load(&input_bytes(args)?)?;
+    let pages = document::parse_pages(str_arg(args, "pages"), document::page_count(&doc))?;
+
+    let extracted: Vec<Value> = document::extract_text(&doc, &pages)
+        .into_iter()
+        .map(|(page, text)| json!({ "page": page, "text": text }))
+        .collect();
+
+    Ok(ContentBuilder::text(
+        json!({
+            "page_count": document::page_count(&doc),
+            "pages": extracted,
+        })
+        .to_string(),
+    ))
+}
+
+fn extract_tables(args: &Value) -> Result<CallToolResult, Error> {
+    let doc = document::load(&input_bytes(args)?)?;
+    let pages = document::parse_pages(str_arg(args, "pages"), document::page_count(&doc))?;
+
+    let mut tables = Vec::new();
+    for (page, text) in document::extract_text(&doc, &pages) {
+        for rows in document::extract_tables(&text) {
+            tables.push(json!({
+                "page": page,
+                "columns": rows.first().map(|r| r.len()).unwrap_or(0),
+                "rows": rows,
+            }));
+        }
+    }
+
+    Ok(ContentBuilder::text(
+        json!({
+            "count": tables.len(),
+            "tables": tables,
+        })
+        .to_string(),
+    ))
+}
+
+fn merge(args: &Value) -> Result<CallToolResult, Error> {
+    let mut inputs = Vec::new();
+    for path in list_arg(args, "paths") {
+        inputs.push(read_path(&path)?);
+    }
+    for data in list_arg(args, "data_list") {
+        inputs.push(decode(&data)?);
+    }
+    if inputs.len() < 2 {
+        return Err(Error::msg("merge needs at least two input PDFs"));
+    }
+
+    let documents = inputs
+        .iter()
+        .map(|bytes| document::load(bytes))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut merged = document::merge(documents)?;
+    let pages = document::page_count(&merged);
+    let bytes = document::save(&mut merged)?;
+
+    Ok(ContentBuilder::text(
+        json!({
+            "inputs": inputs.len(),
+            "page_count": pages,
+            "output": emit(&bytes, str_arg(args, "output_path"))?,
+        })
+        .to_string(),
+    ))
+}
+
+fn split(args: &Value) -> Result<CallToolResult, Error> {
+    let bytes = input_bytes(args)?;
+    let doc = document::load(&bytes)?;
+    let total = document::page_count(&doc);
+
+    let ranges = str_arg(args, "ranges")
+        .ok_or_else(|| Error::msg("ranges parameter required for split"))?;
+
+    let mut parts = Vec::new();
+    for (index, range) in ranges.split(',').enumerate() {
+        let pages = document::parse_pages(Some(range), total)?;
+        let mut part = document::select_pages(doc.clone(), &pages);
+        let part_bytes = document::save(&mut part)?;
+        let output_path = str_arg(args, "output_path").map(|p| numbered_path(p, index + 1));
+
+        parts.push(json!({
+            "range": range.trim(),
+            "page_count": pages.len(),
+            "output": emit(&part_bytes, output_path.as_deref())?,
+        }));
+    }
+
+    Ok(ContentBuilder::text(
+        json!({
+            "source_page_count": total,
+            "parts": parts,
+        })
+        .to_string(),
+    ))
+}
+
+/// Input PDF from `path` or base64 `data`
+fn input_bytes(args: &Value) -> Result<Vec<u8>, Error> {
+    match (str_arg(args, "path"), str_arg(args, "data")) {
+        (Some(path), _) => read_path(path),
+        (None, Some(data)) => decode(data),
+        (None, None) => Err(Error::msg("either path or data parameter is required")),
+    }
+}
+
+fn read_path(path: &str) -> Result<Vec<u8>, Error> {
+    fs::read(path).map_err(|e| Error::msg(format!("Failed to read {}: {}", path, e)))
+}
+
+fn decode(data: &str) -> Result<Vec<u8>, Error> {
+    STANDARD
+        .decode(data.trim())
+        .map_err(|e| Error::msg(format!("Invalid base64 PDF data: {}", e)))
+}
+
+/// Write to `output_path` when given, otherwise return the PDF inline as base64
+fn emit(bytes: &[u8], output_path: Option<&str>) -> Result<Value, Error> {
+    match output_path {
+        Some(path) => {
+            fs::write(path, bytes)
+                .map_err(|e| Error::msg(format!("Failed to write {}: {}", path, e)))?;
+            Ok(json!({ "path": path, "size": bytes.len() }))
+        }
+        None => Ok(json!({
+            "mime_type": "application/pdf",
+            "size": bytes.len(),
+            "data": STANDARD.encode(bytes),
+        })),
+    }
+}
+
+/// `out/report.pdf` + 2 → `out/report-2.pdf`
+fn numbered_path(path: &str, n: usize) -> String {
+    match path.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() && !ext.contains('/') => {
+            format!("{}-{}.{}", stem, n, ext)
+        }
+        _ => format!("{}-{}", path, n),
+    }
+}
+
+fn str_arg<'a>(args: &'a Value, key: &str) -> Option<&'a str> {
+    args.get(key).and_then(|v| v.as_str()).filter(|s| !s.is_empty())
+}
+
+/// Accept either a JSON array, a JSON-array string, or a comma-separated string
+fn list_arg(args: &Value, key: &str) -> Vec<String> {
+    match args.get(key) {
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(|v| v.as_str().map(str::to_string))
+            .collect(),
+        Some(Value::String(raw)) => match serde_json::from_str::<Vec<String>>(raw) {
+            Ok(items) => items,
+            Err(_) => raw
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// Create the plugin instance
+#[allow(dead_code)]
+fn plugin() -> McpPlugin<Ready> {
+    mcp_plugin("pdf")
+        .description("PDF text and table extraction, page counting, merging and splitting")
+        .tool::<PdfTool>()
+        .serve()
+}
+
+// Generate standard MCP entry points
+sweetmcp_plugin_builder::generate_mcp_functions!(plugin);
@@ -10,6 +10,7 @@ struct QrCodeTool;
 
 impl McpTool for QrCodeTool {
     const NAME: &'static str = "qr-code";
+    type Args = Value;
 
     fn description(builder: DescriptionBuilder) -> DescriptionBuilder {
         builder
@@ -51,19 +52,7 @@ impl McpTool for QrCodeTool {
         let ecc = to_ecc(ecc_level);
 
         match generate_qr_code(data, ecc) {
-            Ok(base64_data) => {
-                use sweetmcp_plugin_builder::{CallToolResult, Content, ContentType};
-                Ok(CallToolResult {
-                    is_error: None,
-                    content: vec![Content {
-                        annotations: None,
-                        text: None,
-                        mime_type: Some("image/png".into()),
-                        r#type: ContentType::Image,
-                        data: Some(base64_data),
-                    }],
-                })
-            }
+            Ok(base64_data) => Ok(ContentBuilder::data(base64_data, "image/png")),
             Err(e) => Ok(ContentBuilder::error(&format!(
                 "Failed to generate QR code: {}",
                 e
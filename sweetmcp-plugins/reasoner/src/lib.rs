@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Mutex;
 use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
 
 // Core types for the MCP reasoner
@@ -38,6 +39,21 @@ pub struct ReasoningRequest {
     pub beam_width: Option<usize>, // Number of top paths to maintain (n-sampling)
     #[serde(rename = "numSimulations")]
     pub num_simulations: Option<usize>, // Number of MCTS simulations to run
+    #[serde(rename = "maxNodes", default)]
+    pub max_nodes: Option<usize>, // Stop once this many thoughts are recorded
+    #[serde(rename = "maxWallTimeMs", default)]
+    pub max_wall_time_ms: Option<u64>, // Stop once this long has passed since the first thought
+    #[serde(rename = "targetScore", default)]
+    pub target_score: Option<f64>, // Stop once any thought scores at least this
+}
+
+/// Stopping criterion that ended exploration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TerminationReason {
+    TargetScore,
+    MaxNodes,
+    MaxWallTime,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -57,6 +73,8 @@ pub struct ReasoningResponse {
     pub best_score: Option<f64>,
     #[serde(rename = "strategyUsed")]
     pub strategy_used: Option<String>,
+    #[serde(rename = "terminatedBy", skip_serializing_if = "Option::is_none")]
+    pub terminated_by: Option<TerminationReason>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -91,16 +109,55 @@ pub struct StrategyMetrics {
 // this would include all the strategy implementations.
 pub struct SimpleReasoner {
     nodes: HashMap<String, ThoughtNode>,
+    /// When the first thought since the last clear arrived, in ms since the epoch
+    started_at_ms: Option<u64>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
 }
 
 impl SimpleReasoner {
     pub fn new() -> Self {
         Self {
             nodes: HashMap::new(),
+            started_at_ms: None,
+        }
+    }
+
+    /// First budget in the request that has been reached. A reached target
+    /// score wins over exhausted budgets because it means the search succeeded.
+    fn termination(&self, request: &ReasoningRequest) -> Option<TerminationReason> {
+        let best_score = self
+            .nodes
+            .values()
+            .map(|n| n.score)
+            .fold(f64::MIN, f64::max);
+        if request
+            .target_score
+            .is_some_and(|target| best_score >= target)
+        {
+            return Some(TerminationReason::TargetScore);
+        }
+        if request.max_nodes.is_some_and(|max| self.nodes.len() >= max) {
+            return Some(TerminationReason::MaxNodes);
         }
+        let elapsed_ms = now_ms().saturating_sub(self.started_at_ms.unwrap_or_else(now_ms));
+        if request
+            .max_wall_time_ms
+            .is_some_and(|max| elapsed_ms >= max)
+        {
+            return Some(TerminationReason::MaxWallTime);
+        }
+        None
     }
 
     pub fn process_thought(&mut self, request: ReasoningRequest) -> ReasoningResponse {
+        self.started_at_ms.get_or_insert_with(now_ms);
+
         // Generate a unique ID for this thought
         let node_id = Uuid::new_v4().to_string();
 
@@ -133,17 +190,22 @@ impl SimpleReasoner {
         // Store the node
         self.nodes.insert(node_id.clone(), node.clone());
 
+        // A reached budget ends exploration even if the caller wanted more
+        let terminated_by = self.termination(&request);
+        let next_thought_needed = request.next_thought_needed && terminated_by.is_none();
+
         // Generate response
         ReasoningResponse {
             node_id,
             thought: request.thought,
             score,
             depth: request.thought_number,
-            is_complete: !request.next_thought_needed,
-            next_thought_needed: request.next_thought_needed,
+            is_complete: !next_thought_needed,
+            next_thought_needed,
             possible_paths: Some(1),
             best_score: Some(score),
             strategy_used: Some(strategy),
+            terminated_by,
         }
     }
 
@@ -197,6 +259,7 @@ impl SimpleReasoner {
 
     pub fn clear(&mut self) {
         self.nodes.clear();
+        self.started_at_ms = None;
     }
 }
 
@@ -223,6 +286,8 @@ struct EnhancedResponse {
     score: f64,
     #[serde(rename = "strategyUsed")]
     strategy_used: String,
+    #[serde(rename = "terminatedBy", skip_serializing_if = "Option::is_none")]
+    terminated_by: Option<TerminationReason>,
     stats: ReasoningStats,
 }
 
@@ -272,11 +337,12 @@ pub fn process_thought(input: String) -> FnResult<String> {
     let enhanced_response = EnhancedResponse {
         thought_number: request.thought_number,
         total_thoughts: request.total_thoughts,
-        next_thought_needed: request.next_thought_needed,
+        next_thought_needed: response.next_thought_needed,
         thought: request.thought.clone(),
         node_id: response.node_id,
         score: response.score,
         strategy_used: strategy,
+        terminated_by: response.terminated_by,
         stats,
     };
 
@@ -371,6 +437,20 @@ pub fn manifest(_: String) -> FnResult<String> {
                     "description": "Number of MCTS simulations to run. Defaults if null",
                     "minimum": 1,
                     "maximum": 150
+                },
+                "maxNodes": {
+                    "type": ["integer", "null"],
+                    "description": "Stop exploring once this many thoughts have been recorded",
+                    "minimum": 1
+                },
+                "maxWallTimeMs": {
+                    "type": ["integer", "null"],
+                    "description": "Stop exploring once this many milliseconds have passed since the first thought",
+                    "minimum": 1
+                },
+                "targetScore": {
+                    "type": ["number", "null"],
+                    "description": "Stop exploring once any thought scores at least this much"
                 }
             },
             "required": [
@@ -0,0 +1,187 @@
+use extism_pdk::*;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use sweetmcp_plugin_builder::Ready;
+use sweetmcp_plugin_builder::prelude::*;
+
+// Reminders are kept and fired by cyrupd; see sweetmcp-axum `plugin::reminders`.
+#[host_fn]
+extern "ExtismHost" {
+    fn reminders_schedule(req: Json<Value>) -> Json<Value>;
+    fn reminders_list(req: Json<Value>) -> Json<Value>;
+    fn reminders_cancel(req: Json<Value>) -> Json<Value>;
+}
+
+/// Where a due reminder is sent besides the daemon's event stream
+#[derive(Clone, Copy, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+#[schemars(crate = "sweetmcp_plugin_builder::schemars")]
+enum Platform {
+    Slack,
+    Discord,
+}
+
+impl Platform {
+    fn name(self) -> &'static str {
+        match self {
+            Platform::Slack => "slack",
+            Platform::Discord => "discord",
+        }
+    }
+}
+
+/// Unwrap a host reply, surfacing `{ok: false, error, status}` as a tool error
+fn host_reply(reply: Result<Json<Value>, Error>) -> Result<Value, ToolError> {
+    let Json(value) = reply.map_err(|e| ToolError::internal(e.to_string()))?;
+    if value.get("ok").and_then(|v| v.as_bool()) == Some(false) {
+        let message = value
+            .get("error")
+            .and_then(|v| v.as_str())
+            .unwrap_or("reminders host call failed");
+        return Err(match value.get("status").and_then(|v| v.as_u64()) {
+            Some(401 | 403) => ToolError::permission_denied(message),
+            Some(400) => ToolError::invalid_argument(invalid_field(message), message),
+            Some(404) => ToolError::not_found(message),
+            _ if message.starts_with("no reminder") => ToolError::not_found(message),
+            _ => ToolError::internal(message),
+        });
+    }
+    Ok(value)
+}
+
+/// The argument a scheduler rejection is about
+fn invalid_field(message: &str) -> &'static str {
+    if message.starts_with("cron") {
+        "cron"
+    } else if message.starts_with("notify") {
+        "notify_platform"
+    } else if message.starts_with("message") || message.starts_with("already") {
+        "message"
+    } else {
+        "when"
+    }
+}
+
+/// Schedule a reminder for a time, after a delay, or on a repeating cron schedule
+#[mcp_tool(
+    name = "schedule_reminder",
+    when = "the user asks to be reminded of something later",
+    when = "a task needs a follow-up at a set time, e.g. checking a deploy in an hour",
+    when = "the user wants a recurring nudge such as a daily stand-up note",
+    perfect_for = "handing time-based follow-ups to the daemon so they fire even after this conversation ends"
+)]
+fn schedule_reminder(
+    /// What to remind about; delivered as the reminder text
+    message: String,
+    /// When to fire once: RFC 3339, "YYYY-MM-DD HH:MM" local time, or a delay like "in 20m", "2h", "1d"
+    #[serde(default)]
+    when: Option<String>,
+    /// Repeat on a cron schedule instead, in the daemon's local time: "minute hour day month weekday", e.g. "0 9 * * mon-fri", or @hourly/@daily/@weekly
+    #[serde(default)]
+    cron: Option<String>,
+    /// Also post the reminder to slack or discord when it fires
+    #[serde(default)]
+    notify_platform: Option<Platform>,
+    /// Channel id for notify_platform; its default webhook when omitted
+    #[serde(default)]
+    notify_channel: Option<String>,
+) -> Result<Value, ToolError> {
+    if message.trim().is_empty() {
+        return Err(ToolError::invalid_argument(
+            "message",
+            "message must not be empty",
+        ));
+    }
+    let (when, cron) = (
+        when.filter(|w| !w.trim().is_empty()),
+        cron.filter(|c| !c.trim().is_empty()),
+    );
+    if when.is_some() == cron.is_some() {
+        return Err(ToolError::invalid_argument(
+            "when",
+            "give exactly one of when or cron",
+        ));
+    }
+    if notify_channel.is_some() && notify_platform.is_none() {
+        return Err(ToolError::invalid_argument(
+            "notify_platform",
+            "notify_channel needs notify_platform",
+        ));
+    }
+    let notify = notify_platform
+        .map(|platform| json!({ "platform": platform.name(), "channel": notify_channel }));
+    let reply = host_reply(unsafe {
+        reminders_schedule(Json(json!({
+            "message": message,
+            "when": when,
+            "cron": cron,
+            "notify": notify,
+        })))
+    })?;
+    Ok(reply.get("reminder").cloned().unwrap_or(Value::Null))
+}
+
+/// List pending reminders, soonest first
+#[mcp_tool(
+    name = "list_reminders",
+    when = "the user asks what reminders are set",
+    when = "you need a reminder's id to cancel it",
+    perfect_for = "reviewing scheduled follow-ups before adding or removing one",
+    read_only,
+    idempotent
+)]
+fn list_reminders(
+    /// Only reminders whose message contains this text, ignoring case
+    #[serde(default)]
+    search: Option<String>,
+) -> Result<Value, ToolError> {
+    let reply = host_reply(unsafe { reminders_list(Json(json!({}))) })?;
+    let search = search.unwrap_or_default().to_lowercase();
+    let reminders: Vec<Value> = reply
+        .get("reminders")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter(|reminder| {
+            reminder
+                .get("message")
+                .and_then(|v| v.as_str())
+                .is_some_and(|message| message.to_lowercase().contains(&search))
+        })
+        .cloned()
+        .collect();
+    Ok(json!({ "count": reminders.len(), "reminders": reminders }))
+}
+
+/// Cancel a pending reminder, one-off or repeating
+#[mcp_tool(
+    name = "cancel_reminder",
+    when = "the user no longer needs a reminder",
+    when = "a recurring reminder should stop",
+    perfect_for = "cleaning up reminders found with list_reminders",
+    destructive,
+    idempotent
+)]
+fn cancel_reminder(
+    /// Reminder id from schedule_reminder or list_reminders
+    id: String,
+) -> Result<Value, ToolError> {
+    let reply = host_reply(unsafe { reminders_cancel(Json(json!({ "id": id.trim() }))) })?;
+    Ok(json!({ "cancelled": reply.get("reminder") }))
+}
+
+/// Create the plugin instance
+#[allow(dead_code)]
+fn plugin() -> McpPlugin<Ready> {
+    mcp_plugin("reminders")
+        .description(
+            "Schedule one-off and cron reminders kept by the cyrupd daemon, delivered as events or notify messages",
+        )
+        .tool::<ScheduleReminderTool>()
+        .tool::<ListRemindersTool>()
+        .tool::<CancelReminderTool>()
+        .serve()
+}
+
+// Generate standard MCP entry points
+sweetmcp_plugin_builder::generate_mcp_functions!(plugin);
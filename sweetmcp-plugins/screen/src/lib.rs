@@ -0,0 +1,107 @@
+use extism_pdk::*;
+use serde_json::{Value, json};
+use sweetmcp_plugin_builder::prelude::*;
+use sweetmcp_plugin_builder::{CallToolResult, Ready};
+
+// Capture happens on the host; see sweetmcp-axum `plugin::screen`.
+#[host_fn]
+extern "ExtismHost" {
+    fn screen_capture_display(req: Json<Value>) -> Json<Value>;
+    fn screen_capture_window(req: Json<Value>) -> Json<Value>;
+}
+
+/// Unwrap a host reply, surfacing `{ok: false, error}` as a tool error
+fn host_reply(reply: Result<Json<Value>, Error>) -> Result<Value, ToolError> {
+    let Json(value) = reply.map_err(|e| ToolError::internal(e.to_string()))?;
+    if value.get("ok").and_then(|v| v.as_bool()) == Some(false) {
+        let message = value
+            .get("error")
+            .and_then(|v| v.as_str())
+            .unwrap_or("screen host call failed");
+        return Err(
+            if message.contains("by policy")
+                || message.contains("consent")
+                || message.contains("declined")
+            {
+                ToolError::permission_denied(message)
+            } else if message.starts_with("no ") {
+                ToolError::not_found(message)
+            } else {
+                ToolError::internal(message)
+            },
+        );
+    }
+    Ok(value)
+}
+
+/// The captured PNG followed by what was captured and at what size
+fn capture_result(reply: Value) -> Result<CallToolResult, ToolError> {
+    let png = reply
+        .get("image_png")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| ToolError::internal("screen host returned no image"))?;
+    let info = json!({
+        "source": reply.get("source"),
+        "width": reply.get("width"),
+        "height": reply.get("height"),
+        "scaled_from": reply.get("scaled_from"),
+    });
+    Ok(ContentBuilder::new()
+        .image(png, "image/png")
+        .json(info)
+        .build())
+}
+
+/// Capture a whole display of the user's desktop as a PNG image
+#[mcp_tool(
+    name = "capture_screen",
+    when = "the user asks you to look at what's on their screen",
+    when = "you need to see an error, dialog or layout in a desktop app rather than a web page",
+    perfect_for = "\"look at my screen\" requests; the user is asked to allow each capture",
+    read_only
+)]
+fn capture_screen(
+    /// Display index (0, 1, ...) or name; the primary display when omitted
+    #[serde(default)]
+    display: Option<String>,
+) -> Result<CallToolResult, ToolError> {
+    let reply = host_reply(unsafe { screen_capture_display(Json(json!({ "display": display }))) })?;
+    capture_result(reply)
+}
+
+/// Capture a single window of the user's desktop as a PNG image
+#[mcp_tool(
+    name = "capture_window",
+    when = "the user asks about one application window, e.g. their editor or terminal",
+    when = "a full-display capture would include unrelated or private windows",
+    perfect_for = "seeing one app's state; the user is asked to allow each capture",
+    read_only
+)]
+fn capture_window(
+    /// Part of the window title or application name, matched case-insensitively
+    title: String,
+) -> Result<CallToolResult, ToolError> {
+    if title.trim().is_empty() {
+        return Err(ToolError::invalid_argument(
+            "title",
+            "title must not be empty",
+        ));
+    }
+    let reply = host_reply(unsafe { screen_capture_window(Json(json!({ "title": title }))) })?;
+    capture_result(reply)
+}
+
+/// Create the plugin instance
+#[allow(dead_code)]
+fn plugin() -> McpPlugin<Ready> {
+    mcp_plugin("screen")
+        .description(
+            "Capture the user's desktop displays and windows as images, with the user's consent",
+        )
+        .tool::<CaptureScreenTool>()
+        .tool::<CaptureWindowTool>()
+        .serve()
+}
+
+// Generate standard MCP entry points
+sweetmcp_plugin_builder::generate_mcp_functions!(plugin);
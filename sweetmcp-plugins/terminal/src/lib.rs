@@ -0,0 +1,221 @@
+use std::time::Duration;
+
+use extism_pdk::*;
+use serde_json::{Value, json};
+use sweetmcp_plugin_builder::prelude::*;
+use sweetmcp_plugin_builder::{CallToolResult, Ready};
+
+/// Longest a `send_input` call may wait before reading output back
+const MAX_SETTLE_MS: u64 = 10_000;
+
+// PTY sessions live on the host; see sweetmcp-axum `plugin::pty`.
+#[host_fn]
+extern "ExtismHost" {
+    fn pty_open(req: Json<Value>) -> Json<Value>;
+    fn pty_write(req: Json<Value>) -> Json<Value>;
+    fn pty_read(req: Json<Value>) -> Json<Value>;
+    fn pty_close(req: Json<Value>) -> Json<Value>;
+}
+
+/// Convert a host reply into a tool result, surfacing `{ok: false, error}` as an error response
+fn host_reply(reply: Result<Json<Value>, Error>) -> Result<CallToolResult, Error> {
+    let Json(value) = reply?;
+    if value.get("ok").and_then(|v| v.as_bool()) == Some(false) {
+        let message = value
+            .get("error")
+            .and_then(|v| v.as_str())
+            .unwrap_or("terminal host call failed");
+        return Ok(ContentBuilder::error(message));
+    }
+    Ok(ContentBuilder::text(value.to_string()))
+}
+
+fn session_id(args: &Value) -> Result<&str, Error> {
+    args.get("session_id")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| Error::msg("session_id parameter required"))
+}
+
+/// Open a persistent PTY session
+struct OpenSessionTool;
+
+impl McpTool for OpenSessionTool {
+    const NAME: &'static str = "open_session";
+    type Args = Value;
+
+    fn description(builder: DescriptionBuilder) -> DescriptionBuilder {
+        builder
+            .does("Start a program inside a persistent pseudo-terminal session and return its session_id")
+            .when("you need an interactive REPL such as python, node or psql")
+            .when("you need to keep shell state (cwd, env, virtualenvs) across several commands")
+            .when("you need to drive interactive programs like ssh or a debugger")
+            .requires("The command must be in the host's terminal allowlist and cwd inside an allowed directory")
+            .not_for("one-shot commands; use eval-sh for those")
+            .perfect_for("interactive workflows, REPL sessions, remote shells, and long-running terminal programs")
+    }
+
+    fn schema(builder: SchemaBuilder) -> Value {
+        builder
+            .required_string("command", "Program to launch (e.g. bash, python3, ssh)")
+            .optional_string(
+                "args",
+                "Space separated arguments passed to the program",
+            )
+            .optional_string("cwd", "Working directory for the session")
+            .optional_number("rows", "Terminal rows (default 24)")
+            .optional_number("cols", "Terminal columns (default 80)")
+            .build()
+    }
+
+    fn execute(args: Value) -> Result<CallToolResult, Error> {
+        let command = args
+            .get("command")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::msg("command parameter required"))?;
+
+        let argv: Vec<String> = match args.get("args") {
+            Some(Value::Array(items)) => items
+                .iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect(),
+            Some(Value::String(s)) => s.split_whitespace().map(str::to_string).collect(),
+            _ => Vec::new(),
+        };
+
+        let request = json!({
+            "command": command,
+            "args": argv,
+            "cwd": args.get("cwd").and_then(|v| v.as_str()),
+            "rows": args.get("rows").and_then(|v| v.as_u64()),
+            "cols": args.get("cols").and_then(|v| v.as_u64()),
+        });
+        host_reply(unsafe { pty_open(Json(request)) })
+    }
+}
+
+/// Write input to a session
+struct SendInputTool;
+
+impl McpTool for SendInputTool {
+    const NAME: &'static str = "send_input";
+    type Args = Value;
+
+    fn description(builder: DescriptionBuilder) -> DescriptionBuilder {
+        builder
+            .does("Send keystrokes or a line of input to an open terminal session")
+            .when("you need to run a command in an open session")
+            .when("you need to answer a prompt from an interactive program")
+            .requires("Include a trailing newline to submit a line; use read_output or wait_ms to see the result")
+            .perfect_for("driving REPLs and shells step by step")
+    }
+
+    fn schema(builder: SchemaBuilder) -> Value {
+        builder
+            .required_string("session_id", "Session returned by open_session")
+            .required_string("input", "Text to send; escape sequences such as \\u0003 (Ctrl-C) are passed through")
+            .optional_number(
+                "wait_ms",
+                "If set, wait this long after sending and return new output (max 10000)",
+            )
+            .build()
+    }
+
+    fn execute(args: Value) -> Result<CallToolResult, Error> {
+        let session_id = session_id(&args)?;
+        let input = args
+            .get("input")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::msg("input parameter required"))?;
+
+        let write = unsafe { pty_write(Json(json!({ "session_id": session_id, "input": input }))) };
+        let Some(wait_ms) = args.get("wait_ms").and_then(|v| v.as_u64()) else {
+            return host_reply(write);
+        };
+        let sent = host_reply(write)?;
+        if sent.is_error == Some(true) {
+            return Ok(sent);
+        }
+
+        std::thread::sleep(Duration::from_millis(wait_ms.min(MAX_SETTLE_MS)));
+        host_reply(unsafe { pty_read(Json(json!({ "session_id": session_id }))) })
+    }
+}
+
+/// Read output from a session
+struct ReadOutputTool;
+
+impl McpTool for ReadOutputTool {
+    const NAME: &'static str = "read_output";
+    type Args = Value;
+
+    fn description(builder: DescriptionBuilder) -> DescriptionBuilder {
+        builder
+            .does("Read terminal output produced by a session since the last read (or since a given cursor)")
+            .when("you need to see the result of input you sent")
+            .when("you need to poll a long-running program for progress")
+            .when("you need to re-read earlier output still held in scrollback")
+            .perfect_for("monitoring interactive sessions without blocking")
+    }
+
+    fn schema(builder: SchemaBuilder) -> Value {
+        builder
+            .required_string("session_id", "Session returned by open_session")
+            .optional_number(
+                "cursor",
+                "Absolute output offset to read from (defaults to where the last read stopped; 0 replays scrollback)",
+            )
+            .optional_number("max_bytes", "Maximum bytes of output to return")
+            .build()
+    }
+
+    fn execute(args: Value) -> Result<CallToolResult, Error> {
+        let request = json!({
+            "session_id": session_id(&args)?,
+            "cursor": args.get("cursor").and_then(|v| v.as_u64()),
+            "max_bytes": args.get("max_bytes").and_then(|v| v.as_u64()),
+        });
+        host_reply(unsafe { pty_read(Json(request)) })
+    }
+}
+
+/// Close a session
+struct CloseSessionTool;
+
+impl McpTool for CloseSessionTool {
+    const NAME: &'static str = "close_session";
+    type Args = Value;
+
+    fn description(builder: DescriptionBuilder) -> DescriptionBuilder {
+        builder
+            .does("Terminate the program in a terminal session and release the session")
+            .when("you are finished with an interactive session")
+            .when("a program is hung and must be killed")
+            .perfect_for("cleaning up sessions so the host's session limit is not exhausted")
+    }
+
+    fn schema(builder: SchemaBuilder) -> Value {
+        builder
+            .required_string("session_id", "Session returned by open_session")
+            .build()
+    }
+
+    fn execute(args: Value) -> Result<CallToolResult, Error> {
+        let request = json!({ "session_id": session_id(&args)? });
+        host_reply(unsafe { pty_close(Json(request)) })
+    }
+}
+
+/// Create the plugin instance
+#[allow(dead_code)]
+fn plugin() -> McpPlugin<Ready> {
+    mcp_plugin("terminal")
+        .description("Persistent interactive terminal sessions backed by host-side PTYs")
+        .tool::<OpenSessionTool>()
+        .tool::<SendInputTool>()
+        .tool::<ReadOutputTool>()
+        .tool::<CloseSessionTool>()
+        .serve()
+}
+
+// Generate standard MCP entry points
+sweetmcp_plugin_builder::generate_mcp_functions!(plugin);
@@ -0,0 +1,171 @@
+//! Case conversion and slugs
+//!
+//! Identifier cases (camel, snake, kebab, ...) are built from words found by
+//! splitting on anything that isn't a letter or digit and on case changes
+//! inside a word, so `HTTPServerError`, `http_server_error` and
+//! `http-server error` all give the same three words.
+
+use serde::Deserialize;
+use sweetmcp_plugin_builder::prelude::JsonSchema;
+
+/// Target case
+#[derive(Clone, Copy, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+#[schemars(crate = "sweetmcp_plugin_builder::schemars")]
+pub enum Case {
+    /// all lowercase
+    Lower,
+    /// ALL UPPERCASE
+    Upper,
+    /// Every Word Capitalised
+    Title,
+    /// First letter of each sentence capitalised
+    Sentence,
+    /// camelCase
+    Camel,
+    /// PascalCase
+    Pascal,
+    /// snake_case
+    Snake,
+    /// kebab-case
+    Kebab,
+    /// CONSTANT_CASE
+    Constant,
+}
+
+/// `text` converted to `case`
+pub fn convert(text: &str, case: Case) -> String {
+    match case {
+        Case::Lower => text.to_lowercase(),
+        Case::Upper => text.to_uppercase(),
+        Case::Title => map_words_in_place(text, capitalize),
+        Case::Sentence => sentence_case(text),
+        Case::Camel => words(text)
+            .iter()
+            .enumerate()
+            .map(|(i, w)| {
+                if i == 0 {
+                    w.to_lowercase()
+                } else {
+                    capitalize(w)
+                }
+            })
+            .collect(),
+        Case::Pascal => words(text).iter().map(|w| capitalize(w)).collect(),
+        Case::Snake => join_words(text, "_", str::to_lowercase),
+        Case::Kebab => join_words(text, "-", str::to_lowercase),
+        Case::Constant => join_words(text, "_", str::to_uppercase),
+    }
+}
+
+/// URL-safe slug: transliterated to ASCII, lowercase, with runs of anything
+/// else collapsed into `separator`, cut at a separator to fit `max_length`
+pub fn slugify(text: &str, separator: &str, max_length: Option<usize>) -> String {
+    let ascii = deunicode::deunicode(text).to_lowercase();
+    let parts: Vec<&str> = ascii
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect();
+
+    let mut slug = String::new();
+    for part in parts {
+        let added = if slug.is_empty() { 0 } else { separator.len() } + part.len();
+        if max_length.is_some_and(|max| slug.len() + added > max) {
+            if slug.is_empty() {
+                // A single overlong word is cut rather than dropped
+                slug.push_str(&part[..max_length.unwrap_or(part.len())]);
+            }
+            break;
+        }
+        if !slug.is_empty() {
+            slug.push_str(separator);
+        }
+        slug.push_str(part);
+    }
+    slug
+}
+
+/// Words of `text` for identifier cases
+fn words(text: &str) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut words = Vec::new();
+    let mut current = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if let Some(prev) = current.chars().last() {
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            // fooBar, v2Api, and the Server in HTTPServer
+            let boundary = c.is_uppercase()
+                && (prev.is_lowercase()
+                    || prev.is_numeric()
+                    || (prev.is_uppercase() && next_is_lower));
+            if boundary {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn join_words(text: &str, separator: &str, transform: fn(&str) -> String) -> String {
+    words(text)
+        .iter()
+        .map(|w| transform(w))
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// First character uppercase, the rest lowercase
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.as_str().to_lowercase().chars())
+            .collect(),
+        None => String::new(),
+    }
+}
+
+/// Apply `f` to each whitespace-separated word, keeping the whitespace
+fn map_words_in_place(text: &str, f: fn(&str) -> String) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut word = String::new();
+    for c in text.chars() {
+        if c.is_whitespace() {
+            out.push_str(&f(&word));
+            word.clear();
+            out.push(c);
+        } else {
+            word.push(c);
+        }
+    }
+    out.push_str(&f(&word));
+    out
+}
+
+fn sentence_case(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut at_start = true;
+    for c in text.to_lowercase().chars() {
+        if at_start && c.is_alphanumeric() {
+            out.extend(c.to_uppercase());
+            at_start = false;
+        } else {
+            out.push(c);
+        }
+        if matches!(c, '.' | '!' | '?') {
+            at_start = true;
+        }
+    }
+    out
+}
@@ -0,0 +1,58 @@
+//! Counting and sentence splitting
+//!
+//! Words and sentences follow Unicode segmentation (UAX #29), so counts hold
+//! for scripts without spaces too. Token counts are a tokenizer-free
+//! estimate: about four characters per token for English text, never fewer
+//! tokens than words, and one token per non-ASCII character, since scripts
+//! such as CJK tokenize far more densely.
+
+use serde_json::{Value, json};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Reading speed used for `reading_minutes`, in words per minute
+const WORDS_PER_MINUTE: f64 = 230.0;
+
+/// Counts of `text`
+pub fn count(text: &str) -> Value {
+    let words = text.unicode_words().count();
+    json!({
+        "characters": text.chars().count(),
+        "graphemes": text.graphemes(true).count(),
+        "bytes": text.len(),
+        "words": words,
+        "lines": text.lines().count(),
+        "sentences": sentences(text).len(),
+        "paragraphs": paragraphs(text),
+        "tokens_estimate": estimate_tokens(text),
+        "reading_minutes": (words as f64 / WORDS_PER_MINUTE * 10.0).ceil() / 10.0,
+    })
+}
+
+/// Sentences of `text`, trimmed, without empty ones
+pub fn sentences(text: &str) -> Vec<&str> {
+    text.unicode_sentences()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Blocks of text separated by blank lines
+fn paragraphs(text: &str) -> usize {
+    let mut count = 0;
+    let mut in_paragraph = false;
+    for line in text.lines() {
+        let blank = line.trim().is_empty();
+        if !blank && !in_paragraph {
+            count += 1;
+        }
+        in_paragraph = !blank;
+    }
+    count
+}
+
+fn estimate_tokens(text: &str) -> usize {
+    let ascii = text.chars().filter(char::is_ascii).count();
+    let other = text.chars().count() - ascii;
+    let words = text.split_whitespace().count();
+    ascii.div_ceil(4).max(words) + other
+}
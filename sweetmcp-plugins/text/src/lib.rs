@@ -0,0 +1,151 @@
+mod case;
+mod count;
+
+use extism_pdk::*;
+use minijinja::{Environment, UndefinedBehavior};
+use serde_json::{Value, json};
+use sweetmcp_plugin_builder::Ready;
+use sweetmcp_plugin_builder::prelude::*;
+
+use crate::case::Case;
+
+/// Convert text between lower, upper, title, sentence and identifier cases
+#[mcp_tool(
+    name = "change_case",
+    when = "you need to turn a phrase into an identifier such as camelCase, snake_case or CONSTANT_CASE",
+    when = "you need to convert between identifier styles, e.g. HTTPServerError to http_server_error",
+    when = "you need title or sentence case for headings",
+    perfect_for = "renaming symbols, generating code identifiers and normalising headings",
+    read_only,
+    idempotent
+)]
+fn change_case(
+    /// Text to convert
+    text: String,
+    /// Target case
+    case: Case,
+) -> String {
+    case::convert(&text, case)
+}
+
+/// Turn text into a URL-safe slug
+#[mcp_tool(
+    name = "slugify",
+    when = "you need a URL path segment, file name or anchor from a title",
+    perfect_for = "blog post URLs, file names and heading anchors, including non-Latin titles",
+    read_only,
+    idempotent
+)]
+fn slugify(
+    /// Text to slugify; accents and non-Latin scripts are transliterated to ASCII
+    text: String,
+    /// Separator between words (default "-")
+    #[serde(default)]
+    separator: Option<String>,
+    /// Longest slug allowed; words past the limit are dropped
+    #[serde(default)]
+    max_length: Option<usize>,
+) -> String {
+    case::slugify(&text, separator.as_deref().unwrap_or("-"), max_length)
+}
+
+/// Count characters, words, lines, sentences, paragraphs and estimated tokens
+#[mcp_tool(
+    name = "count_text",
+    when = "you need exact character, word or line counts",
+    when = "you need to check text against a length limit such as a tweet, SMS or meta description",
+    when = "you need a rough token count before sending text to a model",
+    perfect_for = "length limits, reading time estimates and context budgeting",
+    read_only,
+    idempotent
+)]
+fn count_text(
+    /// Text to count
+    text: String,
+) -> Value {
+    count::count(&text)
+}
+
+/// Detect the natural language and script of text
+#[mcp_tool(
+    name = "detect_language",
+    when = "you need to know what language a document or message is written in",
+    when = "you need to route text to a language-specific step such as translation",
+    perfect_for = "triaging multilingual input; reliable from a sentence or two of text",
+    read_only,
+    idempotent
+)]
+fn detect_language(
+    /// Text to identify
+    text: String,
+) -> Result<Value, ToolError> {
+    let info = whatlang::detect(&text).ok_or_else(|| {
+        ToolError::invalid_argument("text", "no language could be detected in the text")
+    })?;
+    Ok(json!({
+        "language": info.lang().code(),
+        "name": info.lang().eng_name(),
+        "script": info.script().name(),
+        "confidence": info.confidence(),
+        "reliable": info.is_reliable(),
+    }))
+}
+
+/// Split text into sentences
+#[mcp_tool(
+    name = "split_sentences",
+    when = "you need to process or quote text sentence by sentence",
+    perfect_for = "summaries, translation chunking and per-sentence analysis",
+    read_only,
+    idempotent
+)]
+fn split_sentences(
+    /// Text to split
+    text: String,
+) -> Value {
+    let sentences = count::sentences(&text);
+    json!({
+        "count": sentences.len(),
+        "sentences": sentences,
+    })
+}
+
+/// Render a Jinja template with the given variables
+#[mcp_tool(
+    name = "render_template",
+    when = "you need to fill a template with structured data, e.g. emails, reports or config files",
+    when = "you need loops or conditionals over a list of records in generated text",
+    perfect_for = "producing many similar documents exactly, without retyping the shared parts",
+    read_only,
+    idempotent
+)]
+fn render_template(
+    /// Jinja template, e.g. "Hello {{ name }}!{% for item in items %} {{ item }}{% endfor %}"
+    template: String,
+    /// Variables available to the template
+    #[serde(default)]
+    variables: Value,
+) -> Result<String, ToolError> {
+    let mut env = Environment::new();
+    // Misspelled variables fail loudly instead of rendering as nothing
+    env.set_undefined_behavior(UndefinedBehavior::Strict);
+    env.render_str(&template, variables)
+        .map_err(|e| ToolError::invalid_argument("template", e.to_string()))
+}
+
+/// Create the plugin instance
+#[allow(dead_code)]
+fn plugin() -> McpPlugin<Ready> {
+    mcp_plugin("text")
+        .description("Deterministic text utilities: case conversion, slugs, counts, language detection, sentence splitting and templating")
+        .tool::<ChangeCaseTool>()
+        .tool::<SlugifyTool>()
+        .tool::<CountTextTool>()
+        .tool::<DetectLanguageTool>()
+        .tool::<SplitSentencesTool>()
+        .tool::<RenderTemplateTool>()
+        .serve()
+}
+
+// Generate standard MCP entry points
+sweetmcp_plugin_builder::generate_mcp_functions!(plugin);
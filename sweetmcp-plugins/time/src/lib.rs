@@ -9,6 +9,7 @@ struct TimeTool;
 
 impl McpTool for TimeTool {
     const NAME: &'static str = "time";
+    type Args = Value;
 
     fn description(builder: DescriptionBuilder) -> DescriptionBuilder {
         builder
@@ -33,6 +34,10 @@ impl McpTool for TimeTool {
             .build()
     }
 
+    fn annotations() -> Option<Value> {
+        Some(json!({"readOnlyHint": true}))
+    }
+
     fn execute(args: Value) -> Result<CallToolResult, Error> {
         let name = args
             .get("name")
@@ -0,0 +1,146 @@
+use extism_pdk::*;
+use serde_json::{Value, json};
+use sweetmcp_plugin_builder::Ready;
+use sweetmcp_plugin_builder::prelude::*;
+
+// Secrets live in the cyrupd keychain; see sweetmcp-axum `plugin::vault`.
+#[host_fn]
+extern "ExtismHost" {
+    fn vault_list(req: Json<Value>) -> Json<Value>;
+    fn vault_get(req: Json<Value>) -> Json<Value>;
+    fn vault_store(req: Json<Value>) -> Json<Value>;
+}
+
+/// Unwrap a host reply, surfacing `{ok: false, error, status}` as a tool error
+fn host_reply(reply: Result<Json<Value>, Error>) -> Result<Value, ToolError> {
+    let Json(value) = reply.map_err(|e| ToolError::internal(e.to_string()))?;
+    if value.get("ok").and_then(|v| v.as_bool()) == Some(false) {
+        let message = value
+            .get("error")
+            .and_then(|v| v.as_str())
+            .unwrap_or("vault host call failed");
+        return Err(match value.get("status").and_then(|v| v.as_u64()) {
+            Some(401 | 403) => ToolError::permission_denied(message),
+            Some(404) => ToolError::not_found(message),
+            Some(400) if message.starts_with("secret value") => {
+                ToolError::invalid_argument("secret", message)
+            }
+            Some(400) => ToolError::invalid_argument("name", message),
+            _ => ToolError::internal(message),
+        });
+    }
+    Ok(value)
+}
+
+/// List the secrets this plugin may read, without their values
+#[mcp_tool(
+    name = "list_secrets",
+    when = "you need to know which credentials are available before asking for one",
+    when = "the user asks what API keys or passwords are stored",
+    perfect_for = "finding the secret:// reference to put in a config instead of a plaintext value",
+    read_only,
+    idempotent
+)]
+fn list_secrets(
+    /// Only names starting with this, e.g. "github/"
+    #[serde(default)]
+    prefix: Option<String>,
+) -> Result<Value, ToolError> {
+    let reply = host_reply(unsafe { vault_list(Json(json!({}))) })?;
+    let prefix = prefix.unwrap_or_default();
+    let secrets: Vec<Value> = reply
+        .get("secrets")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter(|secret| {
+            secret
+                .get("name")
+                .and_then(|v| v.as_str())
+                .is_some_and(|name| name.starts_with(&prefix))
+        })
+        .cloned()
+        .collect();
+    Ok(json!({ "count": secrets.len(), "secrets": secrets }))
+}
+
+/// Get one secret: its reference and a redacted value, or the plaintext when revealed
+#[mcp_tool(
+    name = "get_secret",
+    when = "you need a credential's secret:// reference or want to confirm which key is stored",
+    when = "a task genuinely needs the plaintext, e.g. to sign a request, and the user asked for it",
+    perfect_for = "handing credentials to tools by reference so the value never enters the conversation",
+    read_only,
+    idempotent
+)]
+fn get_secret(
+    /// Secret name, e.g. "github/token"
+    name: String,
+    /// Return the plaintext value; only succeeds if this plugin's vault client may reveal the secret
+    #[serde(default)]
+    reveal: bool,
+) -> Result<Value, ToolError> {
+    if name.trim().is_empty() {
+        return Err(ToolError::invalid_argument(
+            "name",
+            "name must not be empty",
+        ));
+    }
+    let reply =
+        host_reply(unsafe { vault_get(Json(json!({ "name": name.trim(), "reveal": reveal }))) })?;
+    Ok(reply.get("secret").cloned().unwrap_or(Value::Null))
+}
+
+/// Create or replace a secret in the vault
+#[mcp_tool(
+    name = "store_secret",
+    when = "the user gives you a credential to keep for later",
+    when = "a key has been rotated and the stored value must be replaced",
+    perfect_for = "moving a pasted token into the keychain and using its secret:// reference from then on",
+    destructive,
+    idempotent
+)]
+fn store_secret(
+    /// Secret name: letters, digits, '.', '_', '-' and '/' separators, e.g. "github/token"
+    name: String,
+    /// The value to keep; it is stored in the OS keychain, never returned by list_secrets
+    secret: String,
+    /// What the secret is for, shown by list_secrets
+    #[serde(default)]
+    description: Option<String>,
+) -> Result<Value, ToolError> {
+    if name.trim().is_empty() {
+        return Err(ToolError::invalid_argument(
+            "name",
+            "name must not be empty",
+        ));
+    }
+    if secret.is_empty() {
+        return Err(ToolError::invalid_argument(
+            "secret",
+            "secret must not be empty",
+        ));
+    }
+    let reply = host_reply(unsafe {
+        vault_store(Json(json!({
+            "name": name.trim(),
+            "value": secret,
+            "description": description,
+        })))
+    })?;
+    Ok(reply.get("secret").cloned().unwrap_or(Value::Null))
+}
+
+/// Create the plugin instance
+#[allow(dead_code)]
+fn plugin() -> McpPlugin<Ready> {
+    mcp_plugin("vault")
+        .description("Look up and store credentials in the cyrupd keychain, redacted unless a reveal is allowed")
+        .tool::<ListSecretsTool>()
+        .tool::<GetSecretTool>()
+        .tool::<StoreSecretTool>()
+        .serve()
+}
+
+// Generate standard MCP entry points
+sweetmcp_plugin_builder::generate_mcp_functions!(plugin);